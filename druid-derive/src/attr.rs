@@ -17,11 +17,12 @@
 use proc_macro2::{Ident, Literal, Span, TokenStream, TokenTree};
 use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
-use syn::{Error, ExprPath, Meta, NestedMeta};
+use syn::{Error, ExprPath, Lit, Meta, NestedMeta};
 
 const BASE_ATTR_PATH: &str = "druid";
 const IGNORE_ATTR_PATH: &str = "ignore";
 const SAME_FN_ATTR_PATH: &str = "same_fn";
+const EPSILON_ATTR_PATH: &str = "epsilon";
 
 /// The fields for a struct or an enum variant.
 #[derive(Debug)]
@@ -50,6 +51,8 @@ pub struct Field {
     /// `true` if this field should be ignored.
     pub ignore: bool,
     pub same_fn: Option<ExprPath>,
+    /// The tolerance for comparing floats, if any.
+    pub epsilon: Option<Lit>,
     //TODO: more attrs here
 }
 
@@ -87,6 +90,7 @@ impl Field {
 
         let mut ignore = false;
         let mut same_fn = None;
+        let mut epsilon = None;
 
         for attr in field
             .attrs
@@ -115,6 +119,22 @@ impl Field {
                                 let path = parse_lit_into_expr_path(&meta.lit)?;
                                 same_fn = Some(path);
                             }
+                            NestedMeta::Meta(Meta::NameValue(meta))
+                                if meta.path.is_ident(EPSILON_ATTR_PATH) =>
+                            {
+                                if epsilon.is_some() {
+                                    return Err(Error::new(meta.span(), "Duplicate attribute"));
+                                }
+                                match meta.lit {
+                                    Lit::Float(_) | Lit::Int(_) => epsilon = Some(meta.lit.clone()),
+                                    ref other => {
+                                        return Err(Error::new(
+                                            other.span(),
+                                            "expected a number, found... something else",
+                                        ))
+                                    }
+                                }
+                            }
                             other => return Err(Error::new(other.span(), "Unknown attribute")),
                         }
                     }
@@ -127,10 +147,17 @@ impl Field {
                 }
             }
         }
+        if let (Some(_), Some(epsilon)) = (&same_fn, &epsilon) {
+            return Err(Error::new(
+                epsilon.span(),
+                "epsilon and same_fn cannot be used together",
+            ));
+        }
         Ok(Field {
             ident,
             ignore,
             same_fn,
+            epsilon,
         })
     }
 
@@ -148,13 +175,17 @@ impl Field {
         }
     }
 
-    /// The tokens to be used as the function for 'same'.
-    pub fn same_fn_path_tokens(&self) -> TokenStream {
-        match self.same_fn {
-            Some(ref f) => quote!(#f),
-            None => {
+    /// The tokens that compare `left` and `right`, which are references to
+    /// this field in two values.
+    pub fn same_tokens(&self, left: TokenStream, right: TokenStream) -> TokenStream {
+        match (&self.same_fn, &self.epsilon) {
+            (Some(f), _) => quote!(#f(#left, #right)),
+            (None, Some(epsilon)) => {
+                quote!(druid::ApproxEq::approx_eq(#left, #right, #epsilon as f64))
+            }
+            (None, None) => {
                 let span = Span::call_site();
-                quote_spanned!(span=> druid::Data::same)
+                quote_spanned!(span=> druid::Data::same(#left, #right))
             }
         }
     }
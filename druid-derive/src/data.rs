@@ -14,7 +14,7 @@
 
 //! The implementation for #[derive(Data)]
 
-use crate::attr::{Field, FieldKind, Fields};
+use crate::attr::{FieldKind, Fields};
 
 use quote::{quote, quote_spanned};
 use syn::{spanned::Spanned, Data, DataEnum, DataStruct};
@@ -43,12 +43,11 @@ fn derive_struct(
     let fields = Fields::parse_ast(&s.fields)?;
 
     let diff = if fields.len() > 0 {
-        let same_fns = fields
-            .iter()
-            .filter(|f| !f.ignore)
-            .map(Field::same_fn_path_tokens);
-        let fields = fields.iter().filter(|f| !f.ignore).map(Field::ident_tokens);
-        quote!( #( #same_fns(&self.#fields, &other.#fields) )&&* )
+        let tests = fields.iter().filter(|f| !f.ignore).map(|f| {
+            let ident = f.ident_tokens();
+            f.same_tokens(quote!(&self.#ident), quote!(&other.#ident))
+        });
+        quote!( #( #tests )&&* )
     } else {
         quote!(true)
     };
@@ -106,10 +105,9 @@ fn derive_enum(
                 .iter()
                 .filter(|field| !field.ignore)
                 .map(|field| {
-                    let same_fn = field.same_fn_path_tokens();
                     let var_left = ident_from_str(&format!("__self_{}", field.ident_string()));
                     let var_right = ident_from_str(&format!("__other_{}", field.ident_string()));
-                    quote!( #same_fn(#var_left, #var_right) )
+                    field.same_tokens(quote!(#var_left), quote!(#var_right))
                 })
                 .collect();
 
@@ -34,6 +34,33 @@ fn same_fn() {
     assert!(one.same(&two));
 }
 
+#[test]
+fn epsilon() {
+    #[derive(Clone, Data)]
+    struct Progress {
+        #[druid(epsilon = 0.01)]
+        value: f64,
+        #[druid(epsilon = 1)]
+        coarse: f32,
+    }
+
+    let one = Progress {
+        value: 0.5,
+        coarse: 10.0,
+    };
+    let two = Progress {
+        value: 0.505,
+        coarse: 10.5,
+    };
+    assert!(one.same(&two));
+
+    let three = Progress {
+        value: 0.52,
+        coarse: 10.0,
+    };
+    assert!(!one.same(&three));
+}
+
 #[test]
 fn enums() {
     #[derive(Debug, Clone, Data)]
@@ -0,0 +1,97 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! System appearance settings.
+
+use crate::piet::Color;
+use crate::platform::appearance as platform;
+
+/// Whether the system prefers light or dark colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorScheme {
+    /// Dark text on light backgrounds.
+    Light,
+    /// Light text on dark backgrounds.
+    Dark,
+}
+
+impl ColorScheme {
+    /// The color scheme the system prefers. This is `Light` where the
+    /// platform has no preference, or it can't be found.
+    pub fn current() -> ColorScheme {
+        platform::color_scheme()
+    }
+}
+
+/// The colors of the system's high contrast mode.
+///
+/// When high contrast is on, applications are expected to draw with only
+/// these colors. Where the platform only reports that contrast should be
+/// increased, they are black on white.
+#[derive(Debug, Clone)]
+pub struct HighContrast {
+    /// The background of windows.
+    pub window: Color,
+    /// Text drawn on the window background.
+    pub window_text: Color,
+    /// The face of buttons and other controls.
+    pub button_face: Color,
+    /// Text drawn on button faces.
+    pub button_text: Color,
+    /// The background of selected or highlighted items.
+    pub highlight: Color,
+    /// Text drawn on the highlight color.
+    pub highlight_text: Color,
+    /// Text of disabled controls.
+    pub disabled_text: Color,
+}
+
+impl HighContrast {
+    /// The system's high contrast colors, or `None` if high contrast mode
+    /// is off.
+    pub fn current() -> Option<HighContrast> {
+        platform::high_contrast()
+    }
+
+    /// Black text on a white background, with a blue highlight.
+    #[allow(dead_code)]
+    pub(crate) fn black_on_white() -> HighContrast {
+        HighContrast {
+            window: Color::WHITE,
+            window_text: Color::BLACK,
+            button_face: Color::WHITE,
+            button_text: Color::BLACK,
+            highlight: Color::rgb8(0x00, 0x00, 0xc0),
+            highlight_text: Color::WHITE,
+            disabled_text: Color::rgb8(0x60, 0x60, 0x60),
+        }
+    }
+}
+
+impl PartialEq for HighContrast {
+    fn eq(&self, other: &HighContrast) -> bool {
+        let colors = |hc: &HighContrast| {
+            [
+                hc.window.as_rgba_u32(),
+                hc.window_text.as_rgba_u32(),
+                hc.button_face.as_rgba_u32(),
+                hc.button_text.as_rgba_u32(),
+                hc.highlight.as_rgba_u32(),
+                hc.highlight_text.as_rgba_u32(),
+                hc.disabled_text.as_rgba_u32(),
+            ]
+        };
+        colors(self) == colors(other)
+    }
+}
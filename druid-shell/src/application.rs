@@ -14,8 +14,21 @@
 
 //! The top-level application type.
 
+use std::cell::RefCell;
+use std::path::PathBuf;
+
 use crate::clipboard::Clipboard;
 use crate::platform::application as platform;
+use crate::system_event::{self, SystemEvent};
+
+/// A function called with files the platform asks the application to open.
+type OpenFilesHandler = Box<dyn FnMut(Vec<PathBuf>)>;
+
+thread_local! {
+    /// The handler for opened files, and any files opened before it was set.
+    static OPEN_FILES: RefCell<(Option<OpenFilesHandler>, Vec<PathBuf>)> =
+        RefCell::new((None, Vec::new()));
+}
 
 //TODO: we may want to make the user create an instance of this (Application::global()?)
 //but for now I'd like to keep changes minimal.
@@ -51,6 +64,60 @@ impl Application {
         platform::Application::clipboard().into()
     }
 
+    /// Whether the user expects the windows open when the application quits
+    /// to be reopened when it next launches.
+    ///
+    /// On macOS this follows the "Close windows when quitting an app"
+    /// setting; elsewhere it is always true.
+    pub fn restores_windows() -> bool {
+        platform::Application::restores_windows()
+    }
+
+    /// Set the function called when the platform asks the application to
+    /// open files.
+    ///
+    /// On macOS this happens when the user opens a document associated with
+    /// the application, or drops one on its icon; these requests can arrive
+    /// before the application has finished launching, and are kept until a
+    /// handler is set. Other platforms pass such files as command line
+    /// arguments instead.
+    pub fn set_open_files_handler(handler: impl FnMut(Vec<PathBuf>) + 'static) {
+        let pending = OPEN_FILES.with(|open_files| {
+            let mut open_files = open_files.borrow_mut();
+            open_files.0 = Some(Box::new(handler));
+            std::mem::replace(&mut open_files.1, Vec::new())
+        });
+        if !pending.is_empty() {
+            open_files(pending);
+        }
+    }
+
+    /// Set the function called with changes to the state of the system,
+    /// such as the computer going to sleep or the session being locked.
+    ///
+    /// Changes that happen before a handler is set aren't kept.
+    pub fn set_system_event_handler(handler: impl FnMut(SystemEvent) + 'static) {
+        system_event::set_handler(handler)
+    }
+
+    /// Start watching for the internet becoming reachable or unreachable,
+    /// which is reported to the handler set with
+    /// [`set_system_event_handler`] as [`SystemEvent::NetworkChanged`].
+    ///
+    /// The internet is taken to be reachable when there is a route to it,
+    /// which doesn't guarantee that any particular server will answer.
+    ///
+    /// [`set_system_event_handler`]: #method.set_system_event_handler
+    /// [`SystemEvent::NetworkChanged`]: enum.SystemEvent.html#variant.NetworkChanged
+    pub fn monitor_network() {
+        system_event::monitor_network()
+    }
+
+    /// Whether there is a route to the internet.
+    pub fn network_reachable() -> bool {
+        system_event::network_reachable()
+    }
+
     /// Returns the current locale string.
     ///
     /// This should a [Unicode language identifier].
@@ -60,3 +127,22 @@ impl Application {
         platform::Application::get_locale()
     }
 }
+
+/// Pass files the platform asked the application to open to the handler,
+/// or keep them until there is one.
+pub(crate) fn open_files(paths: Vec<PathBuf>) {
+    // The handler is taken out while it runs, so that it can set another.
+    let handler = OPEN_FILES.with(|open_files| open_files.borrow_mut().0.take());
+    match handler {
+        Some(mut handler) => {
+            handler(paths);
+            OPEN_FILES.with(|open_files| {
+                let mut open_files = open_files.borrow_mut();
+                if open_files.0.is_none() {
+                    open_files.0 = Some(handler);
+                }
+            });
+        }
+        None => OPEN_FILES.with(|open_files| open_files.borrow_mut().1.extend(paths)),
+    }
+}
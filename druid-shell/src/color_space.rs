@@ -0,0 +1,120 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Color spaces, and conversion between them.
+
+use crate::piet::Color;
+
+/// The color space that color values are in.
+///
+/// Colors are in sRGB unless something says otherwise. Wide-gamut displays
+/// show more saturated colors than sRGB can describe; a window can be
+/// tagged as Display P3 with [`WindowBuilder::set_color_space`], where the
+/// platform supports it, and colors painted in it should then be converted
+/// with [`convert`].
+///
+/// [`WindowBuilder::set_color_space`]: struct.WindowBuilder.html#method.set_color_space
+/// [`convert`]: #method.convert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// The standard color space of the web and most displays.
+    Srgb,
+    /// The wide-gamut color space of recent Apple displays, with the sRGB
+    /// transfer function and a D65 white point.
+    DisplayP3,
+}
+
+/// Linear sRGB to linear Display P3.
+const SRGB_TO_P3: [[f64; 3]; 3] = [
+    [0.822_462_1, 0.177_538_0, 0.0],
+    [0.033_194_1, 0.966_805_8, 0.0],
+    [0.017_082_7, 0.072_397_4, 0.910_519_9],
+];
+
+/// Linear Display P3 to linear sRGB.
+const P3_TO_SRGB: [[f64; 3]; 3] = [
+    [1.224_940_1, -0.224_940_4, 0.0],
+    [-0.042_056_9, 1.042_057_1, 0.0],
+    [-0.019_637_6, -0.078_636_1, 1.098_273_5],
+];
+
+impl ColorSpace {
+    /// Convert a color in this color space to `to`.
+    ///
+    /// Colors that `to` can't describe are clamped to its gamut.
+    pub fn convert(self, color: &Color, to: ColorSpace) -> Color {
+        let rgba = color.as_rgba_u32();
+        let channel = |shift: u32| f64::from((rgba >> shift) & 0xff) / 255.0;
+        let [r, g, b] = self.convert_rgb([channel(24), channel(16), channel(8)], to);
+        Color::rgba(r, g, b, channel(0))
+    }
+
+    /// Convert the red, green and blue channels of a color, from 0 to 1, in
+    /// this color space to `to`.
+    pub fn convert_rgb(self, rgb: [f64; 3], to: ColorSpace) -> [f64; 3] {
+        let matrix = match (self, to) {
+            (ColorSpace::Srgb, ColorSpace::DisplayP3) => &SRGB_TO_P3,
+            (ColorSpace::DisplayP3, ColorSpace::Srgb) => &P3_TO_SRGB,
+            _ => return rgb,
+        };
+        let linear = [to_linear(rgb[0]), to_linear(rgb[1]), to_linear(rgb[2])];
+        let mut out = [0.0; 3];
+        for (out, row) in out.iter_mut().zip(matrix) {
+            let value = row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2];
+            *out = from_linear(value.max(0.0).min(1.0));
+        }
+        out
+    }
+}
+
+impl Default for ColorSpace {
+    fn default() -> Self {
+        ColorSpace::Srgb
+    }
+}
+
+/// The sRGB transfer function, which Display P3 shares, undone.
+fn to_linear(value: f64) -> f64 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// The sRGB transfer function.
+fn from_linear(value: f64) -> f64 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_red_in_p3() {
+        let [r, g, b] = ColorSpace::Srgb.convert_rgb([1.0, 0.0, 0.0], ColorSpace::DisplayP3);
+        assert!((r - 0.9175).abs() < 1e-3);
+        assert!((g - 0.2003).abs() < 1e-3);
+        assert!((b - 0.1386).abs() < 1e-3);
+        let back = ColorSpace::DisplayP3.convert_rgb([r, g, b], ColorSpace::Srgb);
+        for (channel, expected) in back.iter().zip(&[1.0, 0.0, 0.0]) {
+            assert!((channel - expected).abs() < 1e-3);
+        }
+    }
+}
@@ -16,6 +16,11 @@
 
 use std::any::Any;
 
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use crate::text_metrics::FontMetrics;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use crate::text_rendering::{TextAntialiasing, TextHinting, TextRendering};
+
 /// Strip the access keys from the menu string.
 ///
 /// Changes "E&xit" to "Exit". Actual ampersands are escaped as "&&".
@@ -38,6 +43,54 @@ pub fn strip_access_key(raw_menu_text: &str) -> String {
     result
 }
 
+/// Cairo's font options for the given text rendering.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub fn cairo_font_options(text_rendering: TextRendering) -> cairo::FontOptions {
+    let options = cairo::FontOptions::new();
+    if let Some(antialiasing) = text_rendering.antialiasing {
+        options.set_antialias(match antialiasing {
+            TextAntialiasing::Subpixel => cairo::Antialias::Subpixel,
+            TextAntialiasing::Grayscale => cairo::Antialias::Gray,
+            TextAntialiasing::Aliased => cairo::Antialias::None,
+        });
+    }
+    if let Some(hinting) = text_rendering.hinting {
+        options.set_hint_style(match hinting {
+            TextHinting::None => cairo::HintStyle::None,
+            TextHinting::Slight => cairo::HintStyle::Slight,
+            TextHinting::Full => cairo::HintStyle::Full,
+        });
+    }
+    options
+}
+
+/// The metrics of a font, as cairo measures it.
+///
+/// The font is chosen the way piet's cairo backend chooses it, so the
+/// metrics match the text it draws.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub fn cairo_font_metrics(font_name: &str, size: f64) -> Option<FontMetrics> {
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 1, 1).ok()?;
+    let cr = cairo::Context::new(&surface);
+    cr.select_font_face(
+        font_name,
+        cairo::FontSlant::Normal,
+        cairo::FontWeight::Normal,
+    );
+    cr.set_font_size(size);
+    let font = cr.font_extents();
+    // Bearings are measured downwards, from the baseline to the glyph's top.
+    let cap_height = -cr.text_extents("H").y_bearing;
+    let x_height = -cr.text_extents("x").y_bearing;
+    Some(FontMetrics {
+        ascent: font.ascent,
+        descent: font.descent,
+        line_height: font.height,
+        cap_height,
+        x_height,
+    })
+}
+
 /// A trait for implementing the boxed callback hack.
 pub(crate) trait IdleCallback: Send {
     fn call(self: Box<Self>, a: &dyn Any);
@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! File open/save dialogs.
+//! File open/save dialogs, and message dialogs.
 
 use std::path::{Path, PathBuf};
 
 /// Information about a file to be opened or saved.
+///
+/// If the dialog allowed [multiple selection], this holds every file
+/// chosen.
+///
+/// [multiple selection]: struct.FileDialogOptions.html#method.multi_selection
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub(crate) path: PathBuf,
+    pub(crate) paths: Vec<PathBuf>,
 }
 
 /// Type of file dialog.
@@ -36,12 +42,39 @@ pub struct FileDialogOptions {
     pub show_hidden: bool,
     pub allowed_types: Option<Vec<FileSpec>>,
     pub default_type: Option<FileSpec>,
+    pub multi_selection: bool,
     // we don't want a library user to be able to construct this type directly
     __non_exhaustive: (),
-    // multi selection
     // select directories
 }
 
+/// The buttons of a message dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageButtons {
+    /// A single OK button.
+    Ok,
+    /// OK and Cancel.
+    OkCancel,
+    /// Yes and No.
+    YesNo,
+}
+
+impl Default for MessageButtons {
+    fn default() -> Self {
+        MessageButtons::OkCancel
+    }
+}
+
+/// Options for a modal message dialog, which asks the user to confirm
+/// something.
+#[derive(Debug, Clone, Default)]
+pub struct MessageDialogOptions {
+    pub title: String,
+    pub message: String,
+    pub buttons: MessageButtons,
+    pub warning: bool,
+}
+
 /// A description of a filetype, for specifiying allowed types in a file dialog.
 ///
 /// # Windows
@@ -68,10 +101,21 @@ pub struct FileSpec {
 }
 
 impl FileInfo {
-    /// The file's path.
+    /// Make a `FileInfo` for the chosen paths, if there are any.
+    pub(crate) fn from_paths(paths: Vec<PathBuf>) -> Option<FileInfo> {
+        let path = paths.first()?.clone();
+        Some(FileInfo { path, paths })
+    }
+
+    /// The file's path, or the first file's, if several were chosen.
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// The paths of all the files chosen.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
 }
 
 impl FileDialogOptions {
@@ -99,6 +143,37 @@ impl FileDialogOptions {
         self.default_type = Some(default_type);
         self
     }
+
+    /// Let the user choose more than one file. This only applies to open
+    /// dialogs.
+    pub fn multi_selection(mut self) -> Self {
+        self.multi_selection = true;
+        self
+    }
+}
+
+impl MessageDialogOptions {
+    /// Create options for a dialog with this title and message, and OK and
+    /// Cancel buttons.
+    pub fn new(title: impl Into<String>, message: impl Into<String>) -> MessageDialogOptions {
+        MessageDialogOptions {
+            title: title.into(),
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the buttons.
+    pub fn buttons(mut self, buttons: MessageButtons) -> Self {
+        self.buttons = buttons;
+        self
+    }
+
+    /// Show the dialog as a warning, for actions that can't be undone.
+    pub fn warning(mut self) -> Self {
+        self.warning = true;
+        self
+    }
 }
 
 impl FileSpec {
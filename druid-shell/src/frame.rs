@@ -0,0 +1,76 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The timing of frames, as predicted by the platform's compositor.
+
+use std::time::{Duration, Instant};
+
+/// When a frame is expected to reach the screen.
+///
+/// Each platform paces painting with the display's refresh, and predicts
+/// when the frame being painted will be shown. Animations that advance by
+/// the time between presentations, rather than between paints, move
+/// evenly even when painting itself is uneven.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameTiming {
+    /// When the frame being painted is expected to be shown.
+    pub presentation_time: Instant,
+    /// The time between refreshes of the display.
+    pub refresh_interval: Duration,
+}
+
+impl FrameTiming {
+    /// The timing of a frame given in ticks of a platform clock, which
+    /// currently reads `now`.
+    pub(crate) fn from_clock(
+        now: i64,
+        presentation: i64,
+        refresh_interval: i64,
+        ticks_per_second: f64,
+    ) -> FrameTiming {
+        let nanos_per_tick = 1e9 / ticks_per_second;
+        let to_duration =
+            |ticks: i64| Duration::from_nanos((ticks as f64 * nanos_per_tick).round() as u64);
+        let instant_now = Instant::now();
+        let presentation_time = if presentation >= now {
+            instant_now + to_duration(presentation - now)
+        } else {
+            instant_now
+                .checked_sub(to_duration(now - presentation))
+                .unwrap_or(instant_now)
+        };
+        FrameTiming {
+            presentation_time,
+            refresh_interval: to_duration(refresh_interval.max(0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_ticks() {
+        let before = Instant::now();
+        let timing = FrameTiming::from_clock(1_000_000, 1_016_000, 16_000, 1e6);
+        let ahead = timing.presentation_time - before;
+        assert!(ahead >= Duration::from_millis(16) && ahead < Duration::from_secs(1));
+        assert_eq!(timing.refresh_interval, Duration::from_millis(16));
+
+        let past = FrameTiming::from_clock(1_000_000, 990_000, -1, 1e6);
+        assert!(past.presentation_time <= Instant::now());
+        assert_eq!(past.refresh_interval, Duration::from_secs(0));
+    }
+}
@@ -36,11 +36,14 @@ extern crate objc;
 #[macro_use]
 extern crate lazy_static;
 
+mod appearance;
 mod application;
 mod clipboard;
+mod color_space;
 mod common_util;
 mod dialog;
 mod error;
+mod frame;
 mod hotkey;
 mod keyboard;
 mod keycodes;
@@ -50,16 +53,34 @@ mod mouse;
 //a bunch of compiler warnings, so let's revisit that later.
 pub mod platform;
 mod runloop;
+mod screen;
+#[cfg(all(target_os = "macos", not(feature = "use_gtk")))]
+mod spellcheck;
+mod system_event;
+mod text_metrics;
+mod text_rendering;
 mod window;
 
+pub use appearance::{ColorScheme, HighContrast};
 pub use application::Application;
 pub use clipboard::{Clipboard, ClipboardFormat, FormatId};
-pub use dialog::{FileDialogOptions, FileInfo, FileSpec};
+pub use color_space::ColorSpace;
+pub use dialog::{FileDialogOptions, FileInfo, FileSpec, MessageButtons, MessageDialogOptions};
 pub use error::Error;
+pub use frame::FrameTiming;
 pub use hotkey::{HotKey, KeyCompare, RawMods, SysMods};
 pub use keyboard::{KeyEvent, KeyModifiers};
 pub use keycodes::KeyCode;
 pub use menu::Menu;
 pub use mouse::{Cursor, MouseButton, MouseEvent};
 pub use runloop::RunLoop;
-pub use window::{Text, TimerToken, WinCtx, WinHandler, WindowBuilder, WindowHandle};
+pub use screen::{Monitor, Screen};
+#[cfg(all(target_os = "macos", not(feature = "use_gtk")))]
+pub use spellcheck::SystemSpellChecker;
+pub use system_event::{PowerSource, SystemEvent};
+pub use text_metrics::{FontMetrics, TextMetrics};
+pub use text_rendering::{TextAntialiasing, TextHinting, TextRendering};
+pub use window::{
+    DialogStyle, Politeness, RenderBackend, Text, TimerToken, WinCtx, WinHandler, WindowBuilder,
+    WindowHandle,
+};
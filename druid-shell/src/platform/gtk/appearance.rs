@@ -0,0 +1,60 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK high contrast and dark themes.
+
+use gtk::SettingsExt;
+
+use crate::appearance::{ColorScheme, HighContrast};
+use crate::piet::Color;
+
+pub(crate) fn high_contrast() -> Option<HighContrast> {
+    let theme = gtk::Settings::get_default()?.get_property_gtk_theme_name()?;
+    // GNOME's high contrast themes are "HighContrast" and
+    // "HighContrastInverse"; other desktops follow the same naming.
+    if !theme.as_str().contains("HighContrast") {
+        return None;
+    }
+    if theme.as_str().contains("Inverse") {
+        Some(HighContrast {
+            window: Color::BLACK,
+            window_text: Color::WHITE,
+            button_face: Color::BLACK,
+            button_text: Color::WHITE,
+            highlight: Color::rgb8(0xff, 0xff, 0x00),
+            highlight_text: Color::BLACK,
+            disabled_text: Color::rgb8(0xa0, 0xa0, 0xa0),
+        })
+    } else {
+        Some(HighContrast::black_on_white())
+    }
+}
+
+pub(crate) fn color_scheme() -> ColorScheme {
+    let settings = match gtk::Settings::get_default() {
+        Some(settings) => settings,
+        None => return ColorScheme::Light,
+    };
+    // Dark themes are either asked for explicitly, or are variants named
+    // like "Adwaita-dark".
+    let dark_theme = settings
+        .get_property_gtk_theme_name()
+        .map(|theme| theme.as_str().to_lowercase().ends_with("dark"))
+        .unwrap_or(false);
+    if settings.get_property_gtk_application_prefer_dark_theme() || dark_theme {
+        ColorScheme::Dark
+    } else {
+        ColorScheme::Light
+    }
+}
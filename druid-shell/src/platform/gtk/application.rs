@@ -18,6 +18,7 @@ use gtk::GtkApplicationExt;
 
 use super::clipboard::Clipboard;
 use super::runloop;
+use super::system_event;
 use super::util;
 
 pub struct Application;
@@ -25,6 +26,7 @@ pub struct Application;
 impl Application {
     pub fn init() {
         gtk::init().expect("GTK initialization failed");
+        system_event::observe();
     }
 
     pub fn quit() {
@@ -46,6 +48,10 @@ impl Application {
         Clipboard
     }
 
+    pub fn restores_windows() -> bool {
+        true
+    }
+
     pub fn get_locale() -> String {
         //TODO ahem
         "en-US".into()
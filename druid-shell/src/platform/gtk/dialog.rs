@@ -12,20 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! File open/save dialogs, GTK implementation.
+//! File open/save dialogs and message dialogs, GTK implementation.
 
 use std::ffi::OsString;
 
-use crate::dialog::{FileDialogOptions, FileDialogType};
-use gtk::{FileChooserAction, FileChooserExt, NativeDialogExt, Window};
+use crate::dialog::{FileDialogOptions, FileDialogType, MessageButtons, MessageDialogOptions};
+use gtk::{
+    ButtonsType, DialogExt, DialogFlags, FileChooserAction, FileChooserExt, GtkWindowExt,
+    MessageType, NativeDialogExt, ResponseType, WidgetExt, Window,
+};
 
 use crate::Error;
 
-pub(crate) fn get_file_dialog_path(
+pub(crate) fn get_file_dialog_paths(
     window: &Window,
     ty: FileDialogType,
     options: FileDialogOptions,
-) -> Result<OsString, Error> {
+) -> Result<Vec<OsString>, Error> {
     // TODO: support message localization
     let (title, action) = match ty {
         FileDialogType::Open => ("Open File", FileChooserAction::Open),
@@ -41,13 +44,37 @@ pub(crate) fn get_file_dialog_path(
 
     dialog.set_show_hidden(options.show_hidden);
 
+    if let FileDialogType::Open = ty {
+        dialog.set_select_multiple(options.multi_selection);
+    }
+
+    for spec in options.allowed_types.iter().flatten() {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(spec.name));
+        for ext in spec.extensions {
+            filter.add_pattern(&format!("*.{}", ext));
+        }
+        let is_default = options.default_type.as_ref() == Some(spec);
+        dialog.add_filter(&filter);
+        if is_default {
+            dialog.set_filter(&filter);
+        }
+    }
+
     let result = dialog.run();
 
     let result = match result {
-        gtk_sys::GTK_RESPONSE_ACCEPT => match dialog.get_filename() {
-            Some(path) => Ok(path.into_os_string()),
-            None => Err(Error::Other("No path received for filename")),
-        },
+        gtk_sys::GTK_RESPONSE_ACCEPT => {
+            let paths = dialog.get_filenames();
+            if paths.is_empty() {
+                Err(Error::Other("No path received for filename"))
+            } else {
+                Ok(paths
+                    .into_iter()
+                    .map(|path| path.into_os_string())
+                    .collect())
+            }
+        }
         gtk_sys::GTK_RESPONSE_CANCEL => Err(Error::Other("Dialog was deleted")),
         _ => {
             eprintln!("Unhandled dialog result: {:?}", result);
@@ -61,3 +88,27 @@ pub(crate) fn get_file_dialog_path(
 
     result
 }
+
+pub(crate) fn show_message_dialog(window: &Window, options: MessageDialogOptions) -> bool {
+    let message_type = if options.warning {
+        MessageType::Warning
+    } else {
+        MessageType::Question
+    };
+    let buttons = match options.buttons {
+        MessageButtons::Ok => ButtonsType::Ok,
+        MessageButtons::OkCancel => ButtonsType::OkCancel,
+        MessageButtons::YesNo => ButtonsType::YesNo,
+    };
+    let dialog = gtk::MessageDialog::new(
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        message_type,
+        buttons,
+        &options.message,
+    );
+    dialog.set_title(&options.title);
+    let response: ResponseType = dialog.run().into();
+    dialog.destroy();
+    response == ResponseType::Ok || response == ResponseType::Yes
+}
@@ -14,6 +14,7 @@
 
 //! GTK-based platform support
 
+pub mod appearance;
 pub mod application;
 pub mod clipboard;
 pub mod dialog;
@@ -21,5 +22,8 @@ pub mod error;
 pub mod keycodes;
 pub mod menu;
 pub mod runloop;
+pub mod screen;
+pub mod system_event;
+pub mod text_metrics;
 pub mod util;
 pub mod window;
@@ -0,0 +1,47 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK monitor information.
+
+use gdk::Rectangle;
+
+use crate::kurbo::Rect;
+use crate::screen::Monitor;
+
+// The per-screen monitor functions are deprecated in favor of `gdk::Monitor`,
+// which requires gtk 3.22.
+#[allow(deprecated)]
+pub(crate) fn get_monitors() -> Vec<Monitor> {
+    let screen = match gdk::Screen::get_default() {
+        Some(screen) => screen,
+        None => return Vec::new(),
+    };
+    let primary = screen.get_primary_monitor();
+    (0..screen.get_n_monitors())
+        .map(|idx| {
+            Monitor::new(
+                idx == primary,
+                to_rect(screen.get_monitor_geometry(idx)),
+                to_rect(screen.get_monitor_workarea(idx)),
+            )
+        })
+        .collect()
+}
+
+fn to_rect(rect: Rectangle) -> Rect {
+    Rect::from_origin_size(
+        (f64::from(rect.x), f64::from(rect.y)),
+        (f64::from(rect.width), f64::from(rect.height)),
+    )
+}
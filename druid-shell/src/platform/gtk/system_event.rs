@@ -0,0 +1,237 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK power and network information.
+//!
+//! Sleep, wake, lock and unlock are announced by logind on the system bus,
+//! and changes to the network are watched by GIO. Changes of power source
+//! are announced by UPower, which isn't watched; the power source is read
+//! from the kernel when it is asked for.
+
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::ptr;
+
+use gio::{NetworkMonitor, NetworkMonitorExt};
+use gio_sys::GDBusConnection;
+use glib_sys::{gpointer, GError, GVariant};
+
+use crate::system_event::{self, PowerSource, SystemEvent};
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+const LOGIN_NAME: &str = "org.freedesktop.login1";
+const LOGIN_PATH: &str = "/org/freedesktop/login1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+const SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+
+/// A handler for a D-Bus signal.
+type SignalCallback = unsafe extern "C" fn(
+    *mut GDBusConnection,
+    *const c_char,
+    *const c_char,
+    *const c_char,
+    *const c_char,
+    *mut GVariant,
+    gpointer,
+);
+
+/// The power source, from the power supplies the kernel knows of.
+pub(crate) fn power_source() -> Option<PowerSource> {
+    let read = |path: &Path, name: &str| {
+        fs::read_to_string(path.join(name))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut has_battery = false;
+    for entry in fs::read_dir(POWER_SUPPLY_DIR).ok()?.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_str() {
+            "Mains" | "USB" if read(&path, "online") == "1" => return Some(PowerSource::External),
+            "Battery" => has_battery = true,
+            _ => (),
+        }
+    }
+    if has_battery {
+        Some(PowerSource::Battery)
+    } else {
+        None
+    }
+}
+
+/// Whether GIO's network monitor finds a route to the internet.
+pub(crate) fn network_reachable() -> bool {
+    NetworkMonitor::get_default().map_or(true, |monitor| monitor.get_network_available())
+}
+
+/// Report changes to the network.
+pub(crate) fn monitor_network() {
+    if let Some(monitor) = NetworkMonitor::get_default() {
+        monitor.connect_network_changed(|_, available| {
+            system_event::system_event(SystemEvent::NetworkChanged(available));
+        });
+    }
+}
+
+/// Report sleep, wake, lock and unlock, as logind announces them.
+///
+/// Systems without logind, or without a system bus, report none of them.
+pub(crate) fn observe() {
+    unsafe {
+        let mut error = ptr::null_mut();
+        let bus = gio_sys::g_bus_get_sync(gio_sys::G_BUS_TYPE_SYSTEM, ptr::null_mut(), &mut error);
+        if bus.is_null() {
+            log::warn!("not watching for sleep and lock: {}", take_error(error));
+            return;
+        }
+        // The connection is kept, and its signals handled, for as long as
+        // the application runs.
+        subscribe(
+            bus,
+            MANAGER_INTERFACE,
+            "PrepareForSleep",
+            LOGIN_PATH,
+            prepare_for_sleep,
+        );
+        match session_path(bus) {
+            Ok(path) => {
+                subscribe(bus, SESSION_INTERFACE, "Lock", &path, lock);
+                subscribe(bus, SESSION_INTERFACE, "Unlock", &path, unlock);
+            }
+            Err(e) => log::warn!("not watching for lock, no login session: {}", e),
+        }
+    }
+}
+
+/// Call `callback` with the logind signal `member` of `interface`, sent by
+/// the object at `path`.
+unsafe fn subscribe(
+    bus: *mut GDBusConnection,
+    interface: &str,
+    member: &str,
+    path: &str,
+    callback: SignalCallback,
+) {
+    let sender = CString::new(LOGIN_NAME).unwrap();
+    let interface = CString::new(interface).unwrap();
+    let member = CString::new(member).unwrap();
+    let path = CString::new(path).unwrap();
+    gio_sys::g_dbus_connection_signal_subscribe(
+        bus,
+        sender.as_ptr(),
+        interface.as_ptr(),
+        member.as_ptr(),
+        path.as_ptr(),
+        ptr::null(),
+        gio_sys::G_DBUS_SIGNAL_FLAGS_NONE,
+        Some(callback),
+        ptr::null_mut(),
+        None,
+    );
+}
+
+/// The object path of the login session this process belongs to.
+unsafe fn session_path(bus: *mut GDBusConnection) -> Result<String, String> {
+    let name = CString::new(LOGIN_NAME).unwrap();
+    let path = CString::new(LOGIN_PATH).unwrap();
+    let interface = CString::new(MANAGER_INTERFACE).unwrap();
+    let method = CString::new("GetSessionByPID").unwrap();
+    let mut pid = glib_sys::g_variant_new_uint32(std::process::id());
+    let parameters = glib_sys::g_variant_new_tuple(&mut pid, 1);
+    let mut error = ptr::null_mut();
+    let reply = gio_sys::g_dbus_connection_call_sync(
+        bus,
+        name.as_ptr(),
+        path.as_ptr(),
+        interface.as_ptr(),
+        method.as_ptr(),
+        parameters,
+        ptr::null(),
+        gio_sys::G_DBUS_CALL_FLAGS_NONE,
+        -1,
+        ptr::null_mut(),
+        &mut error,
+    );
+    if reply.is_null() {
+        return Err(take_error(error));
+    }
+    let session = glib_sys::g_variant_get_child_value(reply, 0);
+    let session_path = CStr::from_ptr(glib_sys::g_variant_get_string(session, ptr::null_mut()))
+        .to_string_lossy()
+        .into_owned();
+    glib_sys::g_variant_unref(session);
+    glib_sys::g_variant_unref(reply);
+    Ok(session_path)
+}
+
+/// The message of an error, which is freed.
+unsafe fn take_error(error: *mut GError) -> String {
+    if error.is_null() {
+        return "unknown error".into();
+    }
+    let message = CStr::from_ptr((*error).message)
+        .to_string_lossy()
+        .into_owned();
+    glib_sys::g_error_free(error);
+    message
+}
+
+/// `PrepareForSleep` is sent with `true` before sleeping, and with `false`
+/// after waking.
+unsafe extern "C" fn prepare_for_sleep(
+    _bus: *mut GDBusConnection,
+    _sender: *const c_char,
+    _path: *const c_char,
+    _interface: *const c_char,
+    _member: *const c_char,
+    parameters: *mut GVariant,
+    _data: gpointer,
+) {
+    let start = glib_sys::g_variant_get_child_value(parameters, 0);
+    let sleeping = glib_sys::g_variant_get_boolean(start) != glib_sys::GFALSE;
+    glib_sys::g_variant_unref(start);
+    let event = if sleeping {
+        SystemEvent::WillSleep
+    } else {
+        SystemEvent::DidWake
+    };
+    system_event::system_event(event);
+}
+
+/// `Lock` asks the session's screen locker to lock the screen.
+unsafe extern "C" fn lock(
+    _bus: *mut GDBusConnection,
+    _sender: *const c_char,
+    _path: *const c_char,
+    _interface: *const c_char,
+    _member: *const c_char,
+    _parameters: *mut GVariant,
+    _data: gpointer,
+) {
+    system_event::system_event(SystemEvent::SessionLocked);
+}
+
+unsafe extern "C" fn unlock(
+    _bus: *mut GDBusConnection,
+    _sender: *const c_char,
+    _path: *const c_char,
+    _interface: *const c_char,
+    _member: *const c_char,
+    _parameters: *mut GVariant,
+    _data: gpointer,
+) {
+    system_event::system_event(SystemEvent::SessionUnlocked);
+}
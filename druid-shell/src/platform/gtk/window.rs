@@ -21,12 +21,12 @@ use std::ffi::OsString;
 use std::os::raw::{c_int, c_uint};
 use std::ptr;
 use std::slice;
-use std::sync::{Arc, Mutex, Weak};
+use std::sync::{Arc, Mutex, Once, Weak};
 
-use gdk::{EventKey, EventMask, ModifierType, ScrollDirection, WindowExt};
+use gdk::{EventKey, EventMask, ModifierType, ScrollDirection, WindowExt, WindowTypeHint};
 use gio::ApplicationExt;
 use gtk::prelude::*;
-use gtk::{AccelGroup, ApplicationWindow};
+use gtk::{AccelGroup, ApplicationWindow, WindowGroup};
 
 use crate::kurbo::{Point, Size, Vec2};
 use crate::piet::{Piet, RenderContext};
@@ -36,11 +36,14 @@ use super::menu::Menu;
 use super::runloop::with_application;
 use super::util::assert_main_thread;
 
-use crate::common_util::IdleCallback;
-use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo};
+use crate::color_space::ColorSpace;
+use crate::common_util::{cairo_font_options, IdleCallback};
+use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo, MessageDialogOptions};
+use crate::frame::FrameTiming;
 use crate::keyboard;
 use crate::mouse::{Cursor, MouseButton, MouseEvent};
-use crate::window::{Text, TimerToken, WinCtx, WinHandler};
+use crate::text_rendering::TextRendering;
+use crate::window::{DialogStyle, Politeness, RenderBackend, Text, TimerToken, WinCtx, WinHandler};
 use crate::Error;
 
 /// Taken from https://gtk-rs.org/docs-src/tutorial/closures
@@ -84,6 +87,11 @@ pub struct WindowBuilder {
     title: String,
     menu: Option<Menu>,
     size: Size,
+    position: Option<Point>,
+    text_rendering: TextRendering,
+    restoration_id: Option<String>,
+    /// The window this one is a dialog of.
+    parent: Option<(ApplicationWindow, DialogStyle)>,
 }
 
 #[derive(Clone)]
@@ -97,6 +105,7 @@ pub(crate) struct WindowState {
     pub(crate) handler: RefCell<Box<dyn WinHandler>>,
     idle_queue: Arc<Mutex<Vec<Box<dyn IdleCallback>>>>,
     current_keyval: RefCell<Option<u32>>,
+    text_rendering: Cell<TextRendering>,
 }
 
 pub(crate) struct WinCtxImpl<'a> {
@@ -111,6 +120,10 @@ impl WindowBuilder {
             title: String::new(),
             menu: None,
             size: Size::new(500.0, 400.0),
+            position: None,
+            text_rendering: Default::default(),
+            restoration_id: None,
+            parent: None,
         }
     }
 
@@ -122,6 +135,33 @@ impl WindowBuilder {
         self.size = size;
     }
 
+    pub fn set_position(&mut self, position: Point) {
+        self.position = Some(position);
+    }
+
+    pub fn set_render_backend(&mut self, backend: RenderBackend) {
+        // Painting is always done on the CPU here; resolving reports a
+        // request for the GPU.
+        backend.resolve();
+    }
+
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        if color_space != ColorSpace::Srgb {
+            log::info!(
+                "{:?} windows are not available on this platform",
+                color_space
+            );
+        }
+    }
+
+    pub fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        self.text_rendering = text_rendering;
+    }
+
+    pub fn set_restoration_id(&mut self, id: String) {
+        self.restoration_id = Some(id);
+    }
+
     pub fn set_title(&mut self, title: impl Into<String>) {
         self.title = title.into();
     }
@@ -130,6 +170,13 @@ impl WindowBuilder {
         self.menu = Some(menu);
     }
 
+    pub fn set_parent(&mut self, parent: &WindowHandle, style: DialogStyle) {
+        match parent.state.upgrade() {
+            Some(state) => self.parent = Some((state.window.clone(), style)),
+            None => log::warn!("the parent of a dialog has closed"),
+        }
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
         assert_main_thread();
 
@@ -150,6 +197,28 @@ impl WindowBuilder {
             (self.size.height * dpi_scale) as i32,
         );
 
+        if let Some(pos) = self.position {
+            window.move_(pos.x as i32, pos.y as i32);
+        }
+
+        if let Some(id) = &self.restoration_id {
+            window.set_role(id);
+        }
+
+        if let Some((parent, style)) = &self.parent {
+            window.set_transient_for(Some(parent));
+            window.set_destroy_with_parent(true);
+            window.set_type_hint(WindowTypeHint::Dialog);
+            if *style == DialogStyle::Modal {
+                // Modal windows block every window in their group, so the
+                // dialog and its parent get a group of their own.
+                let group = WindowGroup::new();
+                group.add_window(parent);
+                group.add_window(&window);
+                window.set_modal(true);
+            }
+        }
+
         let accel_group = AccelGroup::new();
         window.add_accel_group(&accel_group);
 
@@ -161,6 +230,7 @@ impl WindowBuilder {
             handler: RefCell::new(handler),
             idle_queue: Arc::new(Mutex::new(vec![])),
             current_keyval: RefCell::new(None),
+            text_rendering: Cell::new(self.text_rendering),
         });
 
         with_application(|app| {
@@ -176,6 +246,16 @@ impl WindowBuilder {
             state: Arc::downgrade(&win_state),
         };
 
+        win_state
+            .window
+            .connect_delete_event(clone!(handle => move |_widget, _event| {
+                if let Some(state) = handle.state.upgrade() {
+                    let mut ctx = WinCtxImpl::from(&handle);
+                    state.handler.borrow_mut().will_close(&mut ctx);
+                }
+                Inhibit(false)
+            }));
+
         if let Some(menu) = self.menu {
             let menu = menu.into_gtk_menubar(&handle, &accel_group);
             vbox.pack_start(&menu, false, false, 0);
@@ -225,15 +305,26 @@ impl WindowBuilder {
 
                 // For some reason piet needs a mutable context, so give it one I guess.
                 let mut context = context.clone();
+                let mut text_rendering = state.text_rendering.get();
+                if context.get_target().get_content() != cairo::Content::Color {
+                    // The window is composited, and may be transparent.
+                    text_rendering = text_rendering.without_subpixel();
+                }
+                context.set_font_options(&cairo_font_options(text_rendering));
                 let mut piet_context = Piet::new(&mut context);
 
                 if let Ok(mut handler_borrow) = state.handler.try_borrow_mut() {
+                    if let Some(timing) = widget.get_frame_clock().map(|c| frame_timing(&c)) {
+                        handler_borrow.prepare_frame(timing);
+                    }
                     let anim = handler_borrow
                         .paint(&mut piet_context, &mut ctx);
                     if let Err(e) = piet_context.finish() {
                         eprintln!("piet error on render: {:?}", e);
                     }
 
+                    // Drawing is driven by the frame clock, so this paints
+                    // again at the next refresh of the display.
                     if anim {
                         widget.queue_draw();
                     }
@@ -377,6 +468,23 @@ impl WindowBuilder {
             }
         }));
 
+        if let Some(settings) = gtk::Settings::get_default() {
+            settings.connect_property_gtk_theme_name_notify(clone!(handle => move |_settings| {
+                if let Some(state) = handle.state.upgrade() {
+                    let mut ctx = WinCtxImpl::from(&handle);
+                    state.handler.borrow_mut().appearance_changed(&mut ctx);
+                }
+            }));
+            settings.connect_property_gtk_application_prefer_dark_theme_notify(
+                clone!(handle => move |_settings| {
+                    if let Some(state) = handle.state.upgrade() {
+                        let mut ctx = WinCtxImpl::from(&handle);
+                        state.handler.borrow_mut().appearance_changed(&mut ctx);
+                    }
+                }),
+            );
+        }
+
         vbox.pack_end(&drawing_area, true, true, 0);
 
         win_state
@@ -413,12 +521,51 @@ impl WindowHandle {
         log::warn!("bring_to_front_and_focus not yet implemented for gtk");
     }
 
+    pub fn announce(&self, text: &str, _politeness: Politeness) {
+        // ATK has no way to make announcements in the version GTK 3 uses.
+        static WARNED: Once = Once::new();
+        WARNED.call_once(|| log::warn!("screen reader announcements are not supported on GTK"));
+        log::debug!("announcement not made: {:?}", text);
+    }
+
     // Request invalidation of the entire window contents.
     pub fn invalidate(&self) {
         if let Some(state) = self.state.upgrade() {
             state.window.queue_draw();
         }
     }
+
+    pub fn set_text_rendering(&self, text_rendering: TextRendering) {
+        if let Some(state) = self.state.upgrade() {
+            state.text_rendering.set(text_rendering);
+            state.window.queue_draw();
+        }
+    }
+
+    pub fn set_size(&self, size: Size) {
+        if let Some(state) = self.state.upgrade() {
+            let dpi_scale = f64::from(self.get_dpi()) / 96.0;
+            // The window holds the menu bar as well as the drawing area,
+            // whose own preferred height is zero.
+            let menu_height = state
+                .window
+                .get_child()
+                .map(|vbox| vbox.get_preferred_height().1)
+                .unwrap_or(0);
+            state.window.resize(
+                (size.width * dpi_scale) as i32,
+                (size.height * dpi_scale) as i32 + menu_height,
+            );
+        }
+    }
+
+    pub fn get_position(&self) -> Option<Point> {
+        self.state.upgrade().map(|s| {
+            let (x, y) = s.window.get_position();
+            Point::new(f64::from(x), f64::from(y))
+        })
+    }
+
     /// Get a handle that can be used to schedule an idle task.
     pub fn get_idle_handle(&self) -> Option<IdleHandle> {
         self.state.upgrade().map(|s| IdleHandle {
@@ -510,9 +657,9 @@ impl WindowHandle {
         &self,
         ty: FileDialogType,
         options: FileDialogOptions,
-    ) -> Result<OsString, Error> {
+    ) -> Result<Vec<OsString>, Error> {
         if let Some(state) = self.state.upgrade() {
-            dialog::get_file_dialog_path(state.window.upcast_ref(), ty, options)
+            dialog::get_file_dialog_paths(state.window.upcast_ref(), ty, options)
         } else {
             Err(Error::Other(
                 "Cannot upgrade state from weak pointer to arc",
@@ -587,14 +734,21 @@ impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
         self.handle
             .file_dialog(FileDialogType::Open, options)
             .ok()
-            .map(|s| FileInfo { path: s.into() })
+            .and_then(|paths| FileInfo::from_paths(paths.into_iter().map(Into::into).collect()))
     }
 
     fn save_as_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo> {
         self.handle
             .file_dialog(FileDialogType::Save, options)
             .ok()
-            .map(|s| FileInfo { path: s.into() })
+            .and_then(|paths| FileInfo::from_paths(paths.into_iter().map(Into::into).collect()))
+    }
+
+    fn message_dialog_sync(&mut self, options: MessageDialogOptions) -> bool {
+        match self.handle.state.upgrade() {
+            Some(state) => dialog::show_message_dialog(state.window.upcast_ref(), options),
+            None => false,
+        }
     }
 
     fn request_timer(&mut self, deadline: std::time::Instant) -> TimerToken {
@@ -636,6 +790,25 @@ fn time_interval_from_deadline(deadline: std::time::Instant) -> u32 {
     }
 }
 
+/// The timing of the frame being drawn, from the window's frame clock.
+fn frame_timing(clock: &gdk::FrameClock) -> FrameTiming {
+    let frame_time = clock.get_frame_time();
+    let (refresh_interval, presentation_time) = clock.get_refresh_info(frame_time);
+    // Without a history of presented frames, the clock can't predict.
+    let presentation_time = if presentation_time != 0 {
+        presentation_time
+    } else {
+        frame_time + refresh_interval
+    };
+    // The frame clock uses the monotonic clock, in microseconds.
+    FrameTiming::from_clock(
+        glib::get_monotonic_time(),
+        presentation_time,
+        refresh_interval,
+        1e6,
+    )
+}
+
 fn next_timer_id() -> usize {
     use std::sync::atomic::{AtomicUsize, Ordering};
     static TIMER_ID: AtomicUsize = AtomicUsize::new(1);
@@ -0,0 +1,57 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! macOS appearance and accessibility display settings.
+
+use cocoa::base::{id, nil, BOOL, NO};
+
+use super::util::{from_nsstring, make_nsstring};
+use crate::appearance::{ColorScheme, HighContrast};
+
+pub(crate) fn high_contrast() -> Option<HighContrast> {
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        if workspace == nil {
+            return None;
+        }
+        // Available since 10.10.
+        let responds: BOOL = msg_send![
+            workspace,
+            respondsToSelector: sel!(accessibilityDisplayShouldIncreaseContrast)
+        ];
+        if responds == NO {
+            return None;
+        }
+        let increase: BOOL = msg_send![workspace, accessibilityDisplayShouldIncreaseContrast];
+        if increase == NO {
+            None
+        } else {
+            Some(HighContrast::black_on_white())
+        }
+    }
+}
+
+pub(crate) fn color_scheme() -> ColorScheme {
+    unsafe {
+        // The key is only set, to "Dark", in dark mode.
+        let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+        let key = make_nsstring("AppleInterfaceStyle");
+        let style: id = msg_send![defaults, stringForKey: key];
+        if style != nil && from_nsstring(style).eq_ignore_ascii_case("dark") {
+            ColorScheme::Dark
+        } else {
+            ColorScheme::Light
+        }
+    }
+}
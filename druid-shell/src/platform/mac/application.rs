@@ -16,14 +16,20 @@
 
 #![allow(non_upper_case_globals)]
 
+use std::path::PathBuf;
+
 use super::clipboard::Clipboard;
+use super::system_event;
 use super::util;
 
 use cocoa::appkit::NSApp;
-use cocoa::base::{id, nil, YES};
+use cocoa::base::{id, nil, BOOL, YES};
+use cocoa::foundation::{NSArray, NSUInteger};
 use objc::declare::ClassDecl;
 use objc::runtime::{Class, Object, Sel};
 
+const NSApplicationDelegateReplySuccess: NSUInteger = 0;
+
 pub struct Application;
 
 impl Application {
@@ -32,6 +38,7 @@ impl Application {
             let delegate: id = msg_send![APP_DELEGATE.0, alloc];
             let () = msg_send![delegate, init];
             let () = msg_send![NSApp(), setDelegate: delegate];
+            system_event::observe(delegate);
         }
     }
 
@@ -61,6 +68,17 @@ impl Application {
         Clipboard
     }
 
+    /// Whether "Close windows when quitting an app" is turned off in the
+    /// system preferences.
+    pub fn restores_windows() -> bool {
+        unsafe {
+            let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+            let key = util::make_nsstring("NSQuitAlwaysKeepsWindows");
+            let keeps: BOOL = msg_send![defaults, boolForKey: key];
+            keeps == YES
+        }
+    }
+
     pub fn get_locale() -> String {
         unsafe {
             let nslocale_class = class!(NSLocale);
@@ -87,6 +105,26 @@ lazy_static! {
             sel!(applicationDidFinishLaunching:),
             application_did_finish_launching as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(application:openFiles:),
+            application_open_files as extern "C" fn(&mut Object, Sel, id, id),
+        );
+        decl.add_method(
+            sel!(workspaceWillSleep:),
+            system_event::workspace_will_sleep as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(workspaceDidWake:),
+            system_event::workspace_did_wake as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(screenIsLocked:),
+            system_event::screen_is_locked as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(screenIsUnlocked:),
+            system_event::screen_is_unlocked as extern "C" fn(&mut Object, Sel, id),
+        );
         AppDelegate(decl.register())
     };
 }
@@ -96,3 +134,17 @@ extern "C" fn application_did_finish_launching(_this: &mut Object, _: Sel, _noti
         let () = msg_send![NSApp(), activateIgnoringOtherApps: YES];
     }
 }
+
+/// Called when the user opens documents with the application, which may be
+/// before it has finished launching.
+extern "C" fn application_open_files(_this: &mut Object, _: Sel, _app: id, filenames: id) {
+    let paths = unsafe {
+        (0..filenames.count())
+            .map(|i| PathBuf::from(util::from_nsstring(filenames.objectAtIndex(i))))
+            .collect()
+    };
+    crate::application::open_files(paths);
+    unsafe {
+        let () = msg_send![NSApp(), replyToOpenOrPrint: NSApplicationDelegateReplySuccess];
+    }
+}
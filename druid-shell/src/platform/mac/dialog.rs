@@ -12,25 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! File open/save dialogs, macOS implementation.
+//! File open/save dialogs and message dialogs, macOS implementation.
 
 #![allow(non_upper_case_globals)]
 
 use std::ffi::OsString;
 
 use cocoa::base::{id, nil, YES};
-use cocoa::foundation::{NSArray, NSInteger};
+use cocoa::foundation::{NSArray, NSInteger, NSUInteger};
 
 use super::util::{from_nsstring, make_nsstring};
-use crate::dialog::{FileDialogOptions, FileDialogType};
+use crate::dialog::{FileDialogOptions, FileDialogType, MessageButtons, MessageDialogOptions};
 
 const NSModalResponseOK: NSInteger = 1;
 const NSModalResponseCancel: NSInteger = 0;
+const NSAlertFirstButtonReturn: NSInteger = 1000;
+const NSAlertStyleWarning: NSUInteger = 0;
+const NSAlertStyleInformational: NSUInteger = 1;
 
-pub(crate) fn get_file_dialog_path(
+pub(crate) fn get_file_dialog_paths(
     ty: FileDialogType,
     options: FileDialogOptions,
-) -> Option<OsString> {
+) -> Option<Vec<OsString>> {
     unsafe {
         let panel: id = match ty {
             FileDialogType::Open => msg_send![class!(NSOpenPanel), openPanel],
@@ -42,6 +45,14 @@ pub(crate) fn get_file_dialog_path(
             let () = msg_send![panel, setShowsHiddenFiles: YES];
         }
 
+        let multi_selection = match ty {
+            FileDialogType::Open => options.multi_selection,
+            FileDialogType::Save => false,
+        };
+        if multi_selection {
+            let () = msg_send![panel, setAllowsMultipleSelection: YES];
+        }
+
         // A vector of NSStrings. this must outlive `nsarray_allowed_types`.
         let allowed_types = options.allowed_types.as_ref().map(|specs| {
             specs
@@ -59,14 +70,49 @@ pub(crate) fn get_file_dialog_path(
 
         let result: NSInteger = msg_send![panel, runModal];
         match result {
+            NSModalResponseOK if multi_selection => {
+                let urls: id = msg_send![panel, URLs];
+                let paths = (0..urls.count())
+                    .map(|i| url_path(urls.objectAtIndex(i)))
+                    .collect();
+                Some(paths)
+            }
             NSModalResponseOK => {
                 let url: id = msg_send![panel, URL];
-                let path: id = msg_send![url, path];
-                let path: OsString = from_nsstring(path).into();
-                Some(path)
+                Some(vec![url_path(url)])
             }
             NSModalResponseCancel => None,
             _ => unreachable!(),
         }
     }
 }
+
+unsafe fn url_path(url: id) -> OsString {
+    let path: id = msg_send![url, path];
+    from_nsstring(path).into()
+}
+
+pub(crate) fn show_message_dialog(options: MessageDialogOptions) -> bool {
+    let buttons: &[&str] = match options.buttons {
+        MessageButtons::Ok => &["OK"],
+        MessageButtons::OkCancel => &["OK", "Cancel"],
+        MessageButtons::YesNo => &["Yes", "No"],
+    };
+    let style = if options.warning {
+        NSAlertStyleWarning
+    } else {
+        NSAlertStyleInformational
+    };
+    unsafe {
+        let alert: id = msg_send![class!(NSAlert), new];
+        let () = msg_send![alert, setMessageText: make_nsstring(&options.title)];
+        let () = msg_send![alert, setInformativeText: make_nsstring(&options.message)];
+        let () = msg_send![alert, setAlertStyle: style];
+        for button in buttons {
+            let _: id = msg_send![alert, addButtonWithTitle: make_nsstring(button)];
+        }
+        let result: NSInteger = msg_send![alert, runModal];
+        let () = msg_send![alert, release];
+        result == NSAlertFirstButtonReturn
+    }
+}
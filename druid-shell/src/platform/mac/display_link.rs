@@ -0,0 +1,209 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pacing animation with the display's refresh, using `CVDisplayLink`.
+
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use cocoa::base::{id, nil, NO};
+use log::error;
+
+use crate::frame::FrameTiming;
+
+type CVDisplayLinkRef = *mut c_void;
+type CVReturn = i32;
+
+const K_CV_RETURN_SUCCESS: CVReturn = 0;
+
+#[repr(C)]
+#[allow(non_snake_case, dead_code)]
+struct CVSMPTETime {
+    subframes: i16,
+    subframeDivisor: i16,
+    counter: u32,
+    type_: u32,
+    flags: u32,
+    hours: i16,
+    minutes: i16,
+    seconds: i16,
+    frames: i16,
+}
+
+#[repr(C)]
+#[allow(non_snake_case, dead_code)]
+struct CVTimeStamp {
+    version: u32,
+    videoTimeScale: i32,
+    videoTime: i64,
+    hostTime: u64,
+    rateScalar: f64,
+    videoRefreshPeriod: i64,
+    smpteTime: CVSMPTETime,
+    flags: u64,
+    reserved: u64,
+}
+
+type CVDisplayLinkOutputCallback = extern "C" fn(
+    CVDisplayLinkRef,
+    *const CVTimeStamp,
+    *const CVTimeStamp,
+    u64,
+    *mut u64,
+    *mut c_void,
+) -> CVReturn;
+
+#[link(name = "CoreVideo", kind = "framework")]
+extern "C" {
+    fn CVDisplayLinkCreateWithActiveCGDisplays(link: *mut CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkSetOutputCallback(
+        link: CVDisplayLinkRef,
+        callback: CVDisplayLinkOutputCallback,
+        user_info: *mut c_void,
+    ) -> CVReturn;
+    fn CVDisplayLinkSetCurrentCGDisplay(link: CVDisplayLinkRef, display: u32) -> CVReturn;
+    fn CVDisplayLinkStart(link: CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkStop(link: CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkIsRunning(link: CVDisplayLinkRef) -> u8;
+    fn CVDisplayLinkRelease(link: CVDisplayLinkRef);
+    fn CVGetCurrentHostTime() -> u64;
+    fn CVGetHostClockFrequency() -> f64;
+}
+
+/// The state shared with the display link's thread.
+struct Shared {
+    /// The view to redraw. The display link is stopped before the view is
+    /// deallocated.
+    view: id,
+    /// The timing of the next frame, until it is drawn.
+    timing: Mutex<Option<FrameTiming>>,
+    /// Whether a redraw has been requested and not yet drawn, so that a
+    /// slow frame doesn't queue up more.
+    pending: AtomicBool,
+}
+
+/// A display link that redraws a view at every refresh of the display,
+/// while it is running.
+pub(crate) struct DisplayLink {
+    link: CVDisplayLinkRef,
+    shared: Box<Shared>,
+}
+
+impl DisplayLink {
+    /// Create a display link for `view`, stopped.
+    pub(crate) fn new(view: id) -> Option<DisplayLink> {
+        let shared = Box::new(Shared {
+            view,
+            timing: Mutex::new(None),
+            pending: AtomicBool::new(false),
+        });
+        unsafe {
+            let mut link = ptr::null_mut();
+            if CVDisplayLinkCreateWithActiveCGDisplays(&mut link) != K_CV_RETURN_SUCCESS {
+                error!("failed to create display link");
+                return None;
+            }
+            let user_info = &*shared as *const Shared as *mut c_void;
+            CVDisplayLinkSetOutputCallback(link, output, user_info);
+            Some(DisplayLink { link, shared })
+        }
+    }
+
+    /// Follow the refresh of `display`, a `CGDirectDisplayID`.
+    ///
+    /// The link starts out following whichever display is active; this
+    /// should be set to the one the view is on, and again when it moves to
+    /// another, or frames are paced by the wrong display's refresh.
+    pub(crate) fn set_display(&self, display: u32) {
+        unsafe {
+            if CVDisplayLinkSetCurrentCGDisplay(self.link, display) != K_CV_RETURN_SUCCESS {
+                error!("failed to set the display link's display to {}", display);
+            }
+        }
+    }
+
+    /// Redraw the view at every refresh, if it isn't already.
+    pub(crate) fn start(&self) {
+        unsafe {
+            if CVDisplayLinkIsRunning(self.link) == 0 {
+                CVDisplayLinkStart(self.link);
+            }
+        }
+    }
+
+    /// Stop redrawing the view.
+    pub(crate) fn stop(&self) {
+        unsafe {
+            if CVDisplayLinkIsRunning(self.link) != 0 {
+                CVDisplayLinkStop(self.link);
+            }
+        }
+    }
+
+    /// The timing of the frame being drawn, if it was requested by the
+    /// display link. This allows the next redraw to be requested.
+    pub(crate) fn take_timing(&self) -> Option<FrameTiming> {
+        self.shared.pending.store(false, Ordering::Release);
+        self.shared.timing.lock().ok().and_then(|mut t| t.take())
+    }
+}
+
+impl Drop for DisplayLink {
+    fn drop(&mut self) {
+        unsafe {
+            CVDisplayLinkStop(self.link);
+            CVDisplayLinkRelease(self.link);
+        }
+    }
+}
+
+/// Called on the display link's thread, ahead of each refresh.
+extern "C" fn output(
+    _link: CVDisplayLinkRef,
+    _now: *const CVTimeStamp,
+    output_time: *const CVTimeStamp,
+    _flags_in: u64,
+    _flags_out: *mut u64,
+    user_info: *mut c_void,
+) -> CVReturn {
+    unsafe {
+        let shared = &*(user_info as *const Shared);
+        if shared.pending.swap(true, Ordering::AcqRel) {
+            return K_CV_RETURN_SUCCESS;
+        }
+        let output_time = &*output_time;
+        // The refresh period is in units of the video time scale, and host
+        // times in those of the host clock.
+        let frequency = CVGetHostClockFrequency();
+        let period = if output_time.videoTimeScale > 0 {
+            output_time.videoRefreshPeriod as f64 / f64::from(output_time.videoTimeScale)
+        } else {
+            0.0
+        };
+        let timing = FrameTiming::from_clock(
+            CVGetCurrentHostTime() as i64,
+            output_time.hostTime as i64,
+            (period * frequency) as i64,
+            frequency,
+        );
+        if let Ok(mut t) = shared.timing.lock() {
+            *t = Some(timing);
+        }
+        let () = msg_send![shared.view, performSelectorOnMainThread: sel!(redraw)
+            withObject: nil waitUntilDone: NO];
+    }
+    K_CV_RETURN_SUCCESS
+}
@@ -16,12 +16,18 @@
 
 #![allow(clippy::let_unit_value)]
 
+pub mod appearance;
 pub mod application;
 pub mod clipboard;
 pub mod dialog;
+pub mod display_link;
 pub mod error;
 pub mod keycodes;
 pub mod menu;
 pub mod runloop;
+pub mod screen;
+pub mod spellcheck;
+pub mod system_event;
+pub mod text_metrics;
 pub mod util;
 pub mod window;
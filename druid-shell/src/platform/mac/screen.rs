@@ -0,0 +1,63 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! macOS monitor information.
+
+use cocoa::appkit::NSScreen;
+use cocoa::base::{id, nil};
+use cocoa::foundation::{NSArray, NSRect};
+
+use crate::kurbo::Rect;
+use crate::screen::Monitor;
+
+pub(crate) fn get_monitors() -> Vec<Monitor> {
+    unsafe {
+        let screens = NSScreen::screens(nil);
+        let height = primary_height();
+        (0..screens.count())
+            .map(|idx| {
+                let screen: id = screens.objectAtIndex(idx);
+                let frame = flip_rect(NSScreen::frame(screen), height);
+                let work = flip_rect(NSScreen::visibleFrame(screen), height);
+                // The first screen is the one with the menu bar.
+                Monitor::new(idx == 0, frame, work)
+            })
+            .collect()
+    }
+}
+
+/// The height of the primary screen.
+///
+/// Cocoa screen coordinates have their origin at the bottom left of the
+/// primary screen, with y increasing upwards; this is needed to convert them.
+pub(crate) fn primary_height() -> f64 {
+    unsafe {
+        let screens = NSScreen::screens(nil);
+        if screens.count() == 0 {
+            return 0.0;
+        }
+        NSScreen::frame(screens.objectAtIndex(0)).size.height
+    }
+}
+
+/// Convert a rect in Cocoa screen coordinates to our coordinates.
+pub(crate) fn flip_rect(rect: NSRect, primary_height: f64) -> Rect {
+    let y0 = primary_height - (rect.origin.y + rect.size.height);
+    Rect::new(
+        rect.origin.x,
+        y0,
+        rect.origin.x + rect.size.width,
+        y0 + rect.size.height,
+    )
+}
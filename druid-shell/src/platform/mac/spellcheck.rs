@@ -0,0 +1,76 @@
+// Copyright 2018 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spellchecking with `NSSpellChecker`.
+
+use cocoa::base::{id, nil};
+use cocoa::foundation::{NSInteger, NSRange, NSUInteger};
+
+use super::util::{assert_main_thread, from_nsstring, make_nsstring};
+
+/// The `NSNotFound` location of an `NSRange`.
+const NS_NOT_FOUND: NSUInteger = NSInteger::max_value() as NSUInteger;
+
+#[derive(Debug)]
+pub struct SystemSpellChecker {
+    checker: id,
+}
+
+impl SystemSpellChecker {
+    pub fn new() -> Option<SystemSpellChecker> {
+        assert_main_thread();
+        unsafe {
+            let checker: id = msg_send![class!(NSSpellChecker), sharedSpellChecker];
+            if checker == nil {
+                None
+            } else {
+                Some(SystemSpellChecker { checker })
+            }
+        }
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        unsafe {
+            let word = make_nsstring(word);
+            let start: NSInteger = 0;
+            let misspelled: NSRange =
+                msg_send![self.checker, checkSpellingOfString: word startingAt: start];
+            misspelled.location == NS_NOT_FOUND
+        }
+    }
+
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        unsafe {
+            let word = make_nsstring(word);
+            // Ranges are in UTF-16 code units, which is what `length` counts.
+            let length: NSUInteger = msg_send![word, length];
+            let range = NSRange::new(0, length);
+            let tag: NSInteger = 0;
+            let guesses: id = msg_send![self.checker, guessesForWordRange: range
+                inString: word
+                language: nil
+                inSpellDocumentWithTag: tag];
+            if guesses == nil {
+                return Vec::new();
+            }
+            let count: NSUInteger = msg_send![guesses, count];
+            (0..count)
+                .map(|i| {
+                    let guess: id = msg_send![guesses, objectAtIndex: i];
+                    from_nsstring(guess)
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! macOS power, session and network notifications.
+
+use std::ffi::c_void;
+use std::mem;
+use std::ptr;
+
+use cocoa::base::{id, nil};
+use log::error;
+use objc::runtime::{Object, Sel};
+
+use super::util;
+use crate::system_event::{self, PowerSource, SystemEvent};
+
+type CFTypeRef = *const c_void;
+type CFStringRef = *const c_void;
+type CFRunLoopRef = *mut c_void;
+type CFRunLoopSourceRef = *mut c_void;
+type IOPowerSourceCallbackType = extern "C" fn(*mut c_void);
+type SCNetworkReachabilityRef = *mut c_void;
+type SCNetworkReachabilityFlags = u32;
+type SCNetworkReachabilityCallBack =
+    extern "C" fn(SCNetworkReachabilityRef, SCNetworkReachabilityFlags, *mut c_void);
+
+const K_SC_NETWORK_REACHABILITY_FLAGS_REACHABLE: SCNetworkReachabilityFlags = 1 << 1;
+const K_SC_NETWORK_REACHABILITY_FLAGS_CONNECTION_REQUIRED: SCNetworkReachabilityFlags = 1 << 2;
+const AF_INET: u8 = 2;
+
+/// A `sockaddr_in`, as laid out on macOS.
+#[repr(C)]
+struct SockaddrIn {
+    sin_len: u8,
+    sin_family: u8,
+    sin_port: u16,
+    sin_addr: u32,
+    sin_zero: [u8; 8],
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+    fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFStringRef;
+    fn IOPSNotificationCreateRunLoopSource(
+        callback: IOPowerSourceCallbackType,
+        context: *mut c_void,
+    ) -> CFRunLoopSourceRef;
+}
+
+#[link(name = "SystemConfiguration", kind = "framework")]
+extern "C" {
+    fn SCNetworkReachabilityCreateWithAddress(
+        allocator: *const c_void,
+        address: *const SockaddrIn,
+    ) -> SCNetworkReachabilityRef;
+    fn SCNetworkReachabilityGetFlags(
+        target: SCNetworkReachabilityRef,
+        flags: *mut SCNetworkReachabilityFlags,
+    ) -> u8;
+    fn SCNetworkReachabilitySetCallback(
+        target: SCNetworkReachabilityRef,
+        callout: SCNetworkReachabilityCallBack,
+        context: *mut c_void,
+    ) -> u8;
+    fn SCNetworkReachabilityScheduleWithRunLoop(
+        target: SCNetworkReachabilityRef,
+        run_loop: CFRunLoopRef,
+        mode: CFStringRef,
+    ) -> u8;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFRunLoopDefaultMode: CFStringRef;
+    fn CFRunLoopGetMain() -> CFRunLoopRef;
+    fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRelease(cf: CFTypeRef);
+}
+
+/// The power source, as reported by IOKit.
+pub(crate) fn power_source() -> Option<PowerSource> {
+    unsafe {
+        let snapshot = IOPSCopyPowerSourcesInfo();
+        if snapshot.is_null() {
+            return None;
+        }
+        // The type is owned by the snapshot, and toll-free bridged to NSString.
+        let kind = IOPSGetProvidingPowerSourceType(snapshot);
+        let source = if kind.is_null() {
+            None
+        } else {
+            match util::from_nsstring(kind as id).as_str() {
+                "AC Power" => Some(PowerSource::External),
+                "Battery Power" => Some(PowerSource::Battery),
+                _ => None,
+            }
+        };
+        CFRelease(snapshot);
+        source
+    }
+}
+
+/// Register the application delegate for the notifications of changes to
+/// the state of the system, and watch for changes of power source.
+pub(crate) fn observe(delegate: id) {
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let workspace_center: id = msg_send![workspace, notificationCenter];
+        // Locking the screen is only announced in distributed notifications.
+        let distributed_center: id =
+            msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+        let observers: [(id, Sel, &str); 4] = [
+            (
+                workspace_center,
+                sel!(workspaceWillSleep:),
+                "NSWorkspaceWillSleepNotification",
+            ),
+            (
+                workspace_center,
+                sel!(workspaceDidWake:),
+                "NSWorkspaceDidWakeNotification",
+            ),
+            (
+                distributed_center,
+                sel!(screenIsLocked:),
+                "com.apple.screenIsLocked",
+            ),
+            (
+                distributed_center,
+                sel!(screenIsUnlocked:),
+                "com.apple.screenIsUnlocked",
+            ),
+        ];
+        for &(center, selector, name) in &observers {
+            let name = util::make_nsstring(name);
+            let () = msg_send![center, addObserver: delegate selector: selector
+                name: name object: nil];
+        }
+
+        let source = IOPSNotificationCreateRunLoopSource(power_sources_changed, ptr::null_mut());
+        if !source.is_null() {
+            CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopDefaultMode);
+            CFRelease(source);
+        }
+    }
+}
+
+/// Create a reachability target for the default route, through which the
+/// internet is reached.
+fn default_route() -> SCNetworkReachabilityRef {
+    let address = SockaddrIn {
+        sin_len: mem::size_of::<SockaddrIn>() as u8,
+        sin_family: AF_INET,
+        sin_port: 0,
+        sin_addr: 0,
+        sin_zero: [0; 8],
+    };
+    unsafe { SCNetworkReachabilityCreateWithAddress(ptr::null(), &address) }
+}
+
+fn is_reachable(flags: SCNetworkReachabilityFlags) -> bool {
+    flags & K_SC_NETWORK_REACHABILITY_FLAGS_REACHABLE != 0
+        && flags & K_SC_NETWORK_REACHABILITY_FLAGS_CONNECTION_REQUIRED == 0
+}
+
+/// Whether there is a route to the internet that doesn't need a connection
+/// to be made first.
+pub(crate) fn network_reachable() -> bool {
+    let target = default_route();
+    if target.is_null() {
+        return true;
+    }
+    unsafe {
+        let mut flags = 0;
+        let got_flags = SCNetworkReachabilityGetFlags(target, &mut flags) != 0;
+        CFRelease(target);
+        !got_flags || is_reachable(flags)
+    }
+}
+
+/// Report changes to the network. The reachability target is kept for the
+/// life of the application.
+pub(crate) fn monitor_network() {
+    let target = default_route();
+    if target.is_null() {
+        error!("failed to create network reachability target");
+        return;
+    }
+    unsafe {
+        if SCNetworkReachabilitySetCallback(target, reachability_changed, ptr::null_mut()) == 0
+            || SCNetworkReachabilityScheduleWithRunLoop(
+                target,
+                CFRunLoopGetMain(),
+                kCFRunLoopDefaultMode,
+            ) == 0
+        {
+            error!("failed to watch network reachability");
+            CFRelease(target);
+        }
+    }
+}
+
+/// Called on the main thread when the reachability of the internet changes.
+extern "C" fn reachability_changed(
+    _target: SCNetworkReachabilityRef,
+    flags: SCNetworkReachabilityFlags,
+    _info: *mut c_void,
+) {
+    system_event::system_event(SystemEvent::NetworkChanged(is_reachable(flags)));
+}
+
+pub(crate) extern "C" fn workspace_will_sleep(_this: &mut Object, _: Sel, _notification: id) {
+    system_event::system_event(SystemEvent::WillSleep);
+}
+
+pub(crate) extern "C" fn workspace_did_wake(_this: &mut Object, _: Sel, _notification: id) {
+    system_event::system_event(SystemEvent::DidWake);
+}
+
+pub(crate) extern "C" fn screen_is_locked(_this: &mut Object, _: Sel, _notification: id) {
+    system_event::system_event(SystemEvent::SessionLocked);
+}
+
+pub(crate) extern "C" fn screen_is_unlocked(_this: &mut Object, _: Sel, _notification: id) {
+    system_event::system_event(SystemEvent::SessionUnlocked);
+}
+
+/// Called on the main thread when anything about the power sources changes,
+/// including the battery's charge.
+extern "C" fn power_sources_changed(_context: *mut c_void) {
+    if let Some(source) = power_source() {
+        system_event::system_event(SystemEvent::PowerSourceChanged(source));
+    }
+}
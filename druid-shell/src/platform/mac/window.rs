@@ -28,10 +28,12 @@ use cocoa::appkit::{
     NSWindowStyleMask,
 };
 use cocoa::base::{id, nil, BOOL, NO, YES};
-use cocoa::foundation::{NSAutoreleasePool, NSPoint, NSRect, NSSize, NSString};
+use cocoa::foundation::{
+    NSAutoreleasePool, NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger,
+};
 use objc::declare::ClassDecl;
 use objc::rc::WeakPtr;
-use objc::runtime::{Class, Object, Sel};
+use objc::runtime::{Class, Object, Protocol, Sel};
 
 use cairo::{Context, QuartzSurface};
 use log::{error, info};
@@ -40,18 +42,32 @@ use crate::kurbo::{Point, Size, Vec2};
 use crate::piet::{Piet, RenderContext};
 
 use super::dialog;
+use super::display_link::DisplayLink;
 use super::menu::Menu;
+use super::screen;
 use super::util::{assert_main_thread, make_nsstring};
-use crate::common_util::IdleCallback;
-use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo};
+use crate::color_space::ColorSpace;
+use crate::common_util::{cairo_font_options, IdleCallback};
+use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo, MessageDialogOptions};
 use crate::keyboard::{KeyEvent, KeyModifiers};
 use crate::keycodes::KeyCode;
 use crate::mouse::{Cursor, MouseButton, MouseEvent};
-use crate::window::{Text, TimerToken, WinCtx, WinHandler};
+use crate::text_rendering::TextRendering;
+use crate::window::{DialogStyle, Politeness, RenderBackend, Text, TimerToken, WinCtx, WinHandler};
 use crate::Error;
 
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    static NSAccessibilityAnnouncementRequestedNotification: id;
+    static NSAccessibilityAnnouncementKey: id;
+    static NSAccessibilityPriorityKey: id;
+    fn NSAccessibilityPostNotificationWithUserInfo(element: id, notification: id, user_info: id);
+}
+
 #[allow(non_upper_case_globals)]
 const NSWindowDidBecomeKeyNotification: &str = "NSWindowDidBecomeKeyNotification";
+#[allow(non_upper_case_globals)]
+const NSWindowDidChangeScreenNotification: &str = "NSWindowDidChangeScreenNotification";
 
 #[derive(Clone)]
 pub(crate) struct WindowHandle {
@@ -59,6 +75,8 @@ pub(crate) struct WindowHandle {
     /// a view. Also, this is better for hosted applications such as VST.
     nsview: WeakPtr,
     idle_queue: Weak<Mutex<Vec<Box<dyn IdleCallback>>>>,
+    /// The view of the window this one is shown as a sheet on.
+    sheet_parent: Option<WeakPtr>,
 }
 
 impl Default for WindowHandle {
@@ -66,6 +84,7 @@ impl Default for WindowHandle {
         WindowHandle {
             nsview: unsafe { WeakPtr::new(nil) },
             idle_queue: Default::default(),
+            sheet_parent: None,
         }
     }
 }
@@ -76,6 +95,11 @@ pub(crate) struct WindowBuilder {
     title: String,
     menu: Option<Menu>,
     size: Size,
+    position: Option<Point>,
+    color_space: ColorSpace,
+    text_rendering: TextRendering,
+    restoration_id: Option<String>,
+    sheet_parent: Option<WeakPtr>,
 }
 
 #[derive(Clone)]
@@ -90,6 +114,9 @@ struct ViewState {
     handler: Box<dyn WinHandler>,
     idle_queue: Arc<Mutex<Vec<Box<dyn IdleCallback>>>>,
     last_mods: KeyModifiers,
+    text_rendering: TextRendering,
+    /// Redraws the view at each refresh of the display, while animating.
+    display_link: Option<DisplayLink>,
 }
 
 struct WinCtxImpl<'a> {
@@ -104,6 +131,11 @@ impl WindowBuilder {
             title: String::new(),
             menu: None,
             size: Size::new(500.0, 400.0),
+            position: None,
+            color_space: ColorSpace::Srgb,
+            text_rendering: Default::default(),
+            restoration_id: None,
+            sheet_parent: None,
         }
     }
 
@@ -115,14 +147,43 @@ impl WindowBuilder {
         self.size = size;
     }
 
+    pub fn set_position(&mut self, position: Point) {
+        self.position = Some(position);
+    }
+
+    pub fn set_render_backend(&mut self, backend: RenderBackend) {
+        // Painting is always done on the CPU here; resolving reports a
+        // request for the GPU.
+        backend.resolve();
+    }
+
     pub fn set_title(&mut self, title: impl Into<String>) {
         self.title = title.into();
     }
 
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    pub fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        self.text_rendering = text_rendering;
+    }
+
+    pub fn set_restoration_id(&mut self, id: String) {
+        self.restoration_id = Some(id);
+    }
+
     pub fn set_menu(&mut self, menu: Menu) {
         self.menu = Some(menu);
     }
 
+    pub fn set_parent(&mut self, parent: &WindowHandle, style: DialogStyle) {
+        // Dialogs that aren't modal are ordinary windows on macOS.
+        if style == DialogStyle::Modal {
+            self.sheet_parent = Some(parent.nsview.clone());
+        }
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
         assert_main_thread();
         unsafe {
@@ -142,8 +203,34 @@ impl WindowBuilder {
                 NO,
             );
 
-            window.cascadeTopLeftFromPoint_(NSPoint::new(20.0, 20.0));
+            match self.position {
+                Some(pos) => {
+                    let top_left = NSPoint::new(pos.x, screen::primary_height() - pos.y);
+                    window.setFrameTopLeftPoint_(top_left);
+                }
+                None => {
+                    window.cascadeTopLeftFromPoint_(NSPoint::new(20.0, 20.0));
+                }
+            }
             window.setTitle_(make_nsstring(&self.title));
+            if let Some(id) = &self.restoration_id {
+                let () = msg_send![window, setIdentifier: make_nsstring(id)];
+                let () = msg_send![window, setRestorable: YES];
+                let () = msg_send![window, setRestorationClass: RESTORATION_CLASS.0];
+            }
+            if self.color_space == ColorSpace::DisplayP3 {
+                // Available since 10.12.
+                let responds: BOOL = msg_send![
+                    class!(NSColorSpace),
+                    respondsToSelector: sel!(displayP3ColorSpace)
+                ];
+                if responds == YES {
+                    let space: id = msg_send![class!(NSColorSpace), displayP3ColorSpace];
+                    let () = msg_send![window, setColorSpace: space];
+                } else {
+                    info!("Display P3 windows need macOS 10.12");
+                }
+            }
             // TODO: this should probably be a tracking area instead
             window.setAcceptsMouseMovedEvents_(YES);
 
@@ -161,9 +248,11 @@ impl WindowBuilder {
             content_view.addSubview_(view);
             let view_state: *mut c_void = *(*view).get_ivar("viewState");
             let view_state = &mut *(view_state as *mut ViewState);
+            view_state.text_rendering = self.text_rendering;
             let handle = WindowHandle {
                 nsview: view_state.nsview.clone(),
                 idle_queue,
+                sheet_parent: self.sheet_parent,
             };
             (*view_state).handler.connect(&handle.clone().into());
             let mut ctx = WinCtxImpl {
@@ -171,6 +260,8 @@ impl WindowBuilder {
                 text: Text::new(),
             };
             (*view_state).handler.connected(&mut ctx);
+            // The handler may have resized the window when it connected.
+            let frame = NSView::frame(view);
             (*view_state)
                 .handler
                 .size(frame.size.width as u32, frame.size.height as u32, &mut ctx);
@@ -184,6 +275,59 @@ impl WindowBuilder {
 struct ViewClass(*const Class);
 unsafe impl Sync for ViewClass {}
 
+/// The layout of an Objective-C block taking a window and an error.
+#[repr(C)]
+struct RestorationHandler {
+    isa: *const c_void,
+    flags: i32,
+    reserved: i32,
+    invoke: unsafe extern "C" fn(*mut RestorationHandler, id, id),
+}
+
+lazy_static! {
+    /// The class AppKit asks for the windows it restores the state of.
+    ///
+    /// The application has already reopened its windows by the time AppKit
+    /// restores their state, so this finds the open window with the
+    /// identifier, instead of making one.
+    static ref RESTORATION_CLASS: ViewClass = unsafe {
+        let mut decl = ClassDecl::new("DruidWindowRestoration", class!(NSObject))
+            .expect("Window restoration class defined");
+        if let Some(protocol) = Protocol::get("NSWindowRestoration") {
+            decl.add_protocol(protocol);
+        }
+        decl.add_class_method(
+            sel!(restoreWindowWithIdentifier:state:completionHandler:),
+            restore_window as extern "C" fn(&Class, Sel, id, id, *mut c_void),
+        );
+        extern "C" fn restore_window(
+            _this: &Class,
+            _sel: Sel,
+            identifier: id,
+            _state: id,
+            handler: *mut c_void,
+        ) {
+            unsafe {
+                let handler = handler as *mut RestorationHandler;
+                let windows: id = msg_send![NSApp(), windows];
+                let count: NSUInteger = msg_send![windows, count];
+                let mut found = nil;
+                for i in 0..count {
+                    let window: id = msg_send![windows, objectAtIndex: i];
+                    let window_id: id = msg_send![window, identifier];
+                    let equal: BOOL = msg_send![window_id, isEqualToString: identifier];
+                    if window_id != nil && equal == YES {
+                        found = window;
+                        break;
+                    }
+                }
+                ((*handler).invoke)(handler, found, nil);
+            }
+        }
+        ViewClass(decl.register())
+    };
+}
+
 lazy_static! {
     static ref VIEW_CLASS: ViewClass = unsafe {
         let mut decl = ClassDecl::new("DruidView", class!(NSView)).expect("View class defined");
@@ -216,6 +360,10 @@ lazy_static! {
             sel!(windowDidBecomeKey:),
             window_did_become_key as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(windowDidChangeScreen:),
+            window_did_change_screen as extern "C" fn(&mut Object, Sel, id),
+        );
         decl.add_method(
             sel!(setFrameSize:),
             set_frame_size as extern "C" fn(&mut Object, Sel, NSSize),
@@ -300,6 +448,8 @@ fn make_view(handler: Box<dyn WinHandler>) -> (id, Weak<Mutex<Vec<BoxedCallback>
             handler,
             idle_queue,
             last_mods: KeyModifiers::default(),
+            text_rendering: Default::default(),
+            display_link: DisplayLink::new(view),
         };
         let state_ptr = Box::into_raw(Box::new(state));
         (*view).set_ivar("viewState", state_ptr as *mut c_void);
@@ -521,22 +671,34 @@ extern "C" fn draw_rect(this: &mut Object, _: Sel, dirtyRect: NSRect) {
         let mut cairo_ctx = Context::new(&cairo_surface);
         cairo_ctx.set_source_rgb(0.0, 0.5, 0.0);
         cairo_ctx.paint();
-        let mut piet_ctx = Piet::new(&mut cairo_ctx);
         let view_state: *mut c_void = *this.get_ivar("viewState");
         let view_state = &mut *(view_state as *mut ViewState);
+        cairo_ctx.set_font_options(&cairo_font_options((*view_state).text_rendering));
+        let mut piet_ctx = Piet::new(&mut cairo_ctx);
         let mut ctx = WinCtxImpl {
             nsview: &(*view_state).nsview,
             text: Text::new(),
         };
+        let timing = (*view_state)
+            .display_link
+            .as_ref()
+            .and_then(|link| link.take_timing());
+        if let Some(timing) = timing {
+            (*view_state).handler.prepare_frame(timing);
+        }
         let anim = (*view_state).handler.paint(&mut piet_ctx, &mut ctx);
         if let Err(e) = piet_ctx.finish() {
             error!("{}", e)
         }
 
-        if anim {
-            // TODO: synchronize with screen refresh rate using CVDisplayLink instead.
-            let () = msg_send!(this as *const _, performSelectorOnMainThread: sel!(redraw)
-                withObject: nil waitUntilDone: NO);
+        match &(*view_state).display_link {
+            Some(link) if anim => link.start(),
+            Some(link) => link.stop(),
+            None if anim => {
+                let () = msg_send!(this as *const _, performSelectorOnMainThread: sel!(redraw)
+                    withObject: nil waitUntilDone: NO);
+            }
+            None => (),
         }
 
         let superclass = msg_send![this, superclass];
@@ -619,6 +781,34 @@ extern "C" fn window_did_become_key(this: &mut Object, _: Sel, _notification: id
     }
 }
 
+extern "C" fn window_did_change_screen(this: &mut Object, _: Sel, notification: id) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let window: id = msg_send![notification, object];
+        if let (Some(link), Some(display)) = (&view_state.display_link, display_id(window)) {
+            link.set_display(display);
+        }
+    }
+}
+
+/// The `CGDirectDisplayID` of the screen `window` is on, if it is on one.
+unsafe fn display_id(window: id) -> Option<u32> {
+    let screen: id = msg_send![window, screen];
+    if screen.is_null() {
+        return None;
+    }
+    let description: id = msg_send![screen, deviceDescription];
+    let key = NSString::alloc(nil)
+        .init_str("NSScreenNumber")
+        .autorelease();
+    let number: id = msg_send![description, objectForKey: key];
+    if number.is_null() {
+        return None;
+    }
+    Some(msg_send![number, unsignedIntValue])
+}
+
 extern "C" fn window_will_close(this: &mut Object, _: Sel, _window: id) {
     unsafe {
         let view_state: *mut c_void = *this.get_ivar("viewState");
@@ -627,6 +817,8 @@ extern "C" fn window_will_close(this: &mut Object, _: Sel, _window: id) {
             nsview: &(*view_state).nsview,
             text: Text::new(),
         };
+        // The window is still on screen, so its frame can be read.
+        (*view_state).handler.will_close(&mut ctx);
         (*view_state).handler.destroy(&mut ctx);
     }
 }
@@ -642,7 +834,24 @@ impl WindowHandle {
                 .autorelease();
             let notif_center: id = msg_send![notif_center_class, defaultCenter];
             let () = msg_send![notif_center, addObserver:*self.nsview.load() selector: sel!(windowDidBecomeKey:) name: notif_string object: window];
-            window.makeKeyAndOrderFront_(nil)
+            // The display link follows the window from screen to screen.
+            let notif_string = NSString::alloc(nil)
+                .init_str(NSWindowDidChangeScreenNotification)
+                .autorelease();
+            let () = msg_send![notif_center, addObserver:*self.nsview.load() selector: sel!(windowDidChangeScreen:) name: notif_string object: window];
+            let parent = self.sheet_parent.as_ref().map(|view| view.load());
+            match parent.filter(|view| !view.is_null()) {
+                Some(view) => {
+                    let parent_window: id = msg_send![*view, window];
+                    let () = msg_send![parent_window, beginSheet: window completionHandler: nil];
+                }
+                None => window.makeKeyAndOrderFront_(nil),
+            }
+            let view_state: *mut c_void = *(**self.nsview.load()).get_ivar("viewState");
+            let view_state = &*(view_state as *const ViewState);
+            if let (Some(link), Some(display)) = (&view_state.display_link, display_id(window)) {
+                link.set_display(display);
+            }
         }
     }
 
@@ -650,6 +859,11 @@ impl WindowHandle {
     pub fn close(&self) {
         unsafe {
             let window: id = msg_send![*self.nsview.load(), window];
+            // A sheet is detached from its parent before it closes.
+            let sheet_parent: id = msg_send![window, sheetParent];
+            if sheet_parent != nil {
+                let () = msg_send![sheet_parent, endSheet: window];
+            }
             let () = msg_send![window, performSelectorOnMainThread: sel!(close) withObject: nil waitUntilDone: NO];
         }
     }
@@ -670,6 +884,30 @@ impl WindowHandle {
         }
     }
 
+    pub fn set_text_rendering(&self, text_rendering: TextRendering) {
+        unsafe {
+            let view = *self.nsview.load();
+            if view == nil {
+                return;
+            }
+            let view_state: *mut c_void = *(*view).get_ivar("viewState");
+            (*(view_state as *mut ViewState)).text_rendering = text_rendering;
+            let () = msg_send![view, setNeedsDisplay: YES];
+        }
+    }
+
+    pub fn set_size(&self, size: Size) {
+        unsafe {
+            let view = *self.nsview.load();
+            if view == nil {
+                return;
+            }
+            let window: id = msg_send![view, window];
+            // The view is resized along with the content view.
+            window.setContentSize_(NSSize::new(size.width, size.height));
+        }
+    }
+
     /// Set the title for this menu.
     pub fn set_title(&self, title: &str) {
         unsafe {
@@ -685,6 +923,29 @@ impl WindowHandle {
         }
     }
 
+    pub fn announce(&self, text: &str, politeness: Politeness) {
+        // NSAccessibilityPriorityMedium and NSAccessibilityPriorityHigh.
+        let priority: NSInteger = match politeness {
+            Politeness::Polite => 50,
+            Politeness::Assertive => 90,
+        };
+        unsafe {
+            let text = make_nsstring(text);
+            let priority: id = msg_send![class!(NSNumber), numberWithInteger: priority];
+            let keys = [NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey];
+            let values = [text, priority];
+            let user_info: id = msg_send![class!(NSDictionary),
+                dictionaryWithObjects: values.as_ptr()
+                forKeys: keys.as_ptr()
+                count: keys.len()];
+            NSAccessibilityPostNotificationWithUserInfo(
+                NSApp(),
+                NSAccessibilityAnnouncementRequestedNotification,
+                user_info,
+            );
+        }
+    }
+
     //FIXME: we should be using the x, y values passed by the caller, but then
     //we have to figure out some way to pass them along with this performSelector:
     //call. This isn't super hard, I'm just not up for it right now.
@@ -694,6 +955,18 @@ impl WindowHandle {
         }
     }
 
+    pub fn get_position(&self) -> Option<Point> {
+        unsafe {
+            let view = self.nsview.load();
+            if view.is_null() {
+                return None;
+            }
+            let window: id = msg_send![*view, window];
+            let frame = screen::flip_rect(NSWindow::frame(window), screen::primary_height());
+            Some(frame.origin())
+        }
+    }
+
     /// Get a handle that can be used to schedule an idle task.
     pub fn get_idle_handle(&self) -> Option<IdleHandle> {
         if self.nsview.load().is_null() {
@@ -786,13 +1059,17 @@ impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
     }
 
     fn open_file_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo> {
-        dialog::get_file_dialog_path(FileDialogType::Open, options)
-            .map(|s| FileInfo { path: s.into() })
+        dialog::get_file_dialog_paths(FileDialogType::Open, options)
+            .and_then(|paths| FileInfo::from_paths(paths.into_iter().map(Into::into).collect()))
     }
 
     fn save_as_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo> {
-        dialog::get_file_dialog_path(FileDialogType::Save, options)
-            .map(|s| FileInfo { path: s.into() })
+        dialog::get_file_dialog_paths(FileDialogType::Save, options)
+            .and_then(|paths| FileInfo::from_paths(paths.into_iter().map(Into::into).collect()))
+    }
+
+    fn message_dialog_sync(&mut self, options: MessageDialogOptions) -> bool {
+        dialog::show_message_dialog(options)
     }
 }
 
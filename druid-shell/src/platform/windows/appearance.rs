@@ -0,0 +1,114 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Windows high contrast and color settings.
+
+use std::mem;
+use std::ptr;
+
+use winapi::shared::minwindef::*;
+use winapi::shared::windef::*;
+use winapi::shared::winerror::ERROR_SUCCESS;
+use winapi::um::winnt::LPCWSTR;
+use winapi::um::winreg::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+use winapi::um::winuser::*;
+
+use log::warn;
+
+use super::util::ToWide;
+use crate::appearance::{ColorScheme, HighContrast};
+use crate::piet::Color;
+
+/// The system colors, if high contrast mode is on.
+pub(crate) fn high_contrast() -> Option<HighContrast> {
+    unsafe {
+        let mut info: HIGHCONTRASTW = mem::zeroed();
+        info.cbSize = mem::size_of::<HIGHCONTRASTW>() as UINT;
+        let ptr = &mut info as *mut HIGHCONTRASTW;
+        if SystemParametersInfoW(SPI_GETHIGHCONTRAST, info.cbSize, ptr as *mut _, 0) == 0 {
+            warn!("failed to get high contrast settings");
+            return None;
+        }
+        if info.dwFlags & HCF_HIGHCONTRASTON == 0 {
+            return None;
+        }
+        Some(HighContrast {
+            window: sys_color(COLOR_WINDOW),
+            window_text: sys_color(COLOR_WINDOWTEXT),
+            button_face: sys_color(COLOR_BTNFACE),
+            button_text: sys_color(COLOR_BTNTEXT),
+            highlight: sys_color(COLOR_HIGHLIGHT),
+            highlight_text: sys_color(COLOR_HIGHLIGHTTEXT),
+            disabled_text: sys_color(COLOR_GRAYTEXT),
+        })
+    }
+}
+
+/// The color scheme the user chose for apps.
+pub(crate) fn color_scheme() -> ColorScheme {
+    let key = "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize".to_wide();
+    let value = "AppsUseLightTheme".to_wide();
+    let mut light: DWORD = 1;
+    let mut size = mem::size_of::<DWORD>() as DWORD;
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_DWORD,
+            ptr::null_mut(),
+            &mut light as *mut DWORD as *mut _,
+            &mut size,
+        )
+    };
+    // The value is missing before Windows 10, which has no dark mode.
+    if result as DWORD == ERROR_SUCCESS && light == 0 {
+        ColorScheme::Dark
+    } else {
+        ColorScheme::Light
+    }
+}
+
+/// Whether a `WM_SETTINGCHANGE` message is about high contrast mode or the
+/// color scheme.
+pub(crate) fn is_appearance_change(wparam: WPARAM, lparam: LPARAM) -> bool {
+    if wparam as UINT == SPI_SETHIGHCONTRAST {
+        return true;
+    }
+    // Color scheme changes name the "ImmersiveColorSet" setting.
+    let name = lparam as LPCWSTR;
+    if name.is_null() {
+        return false;
+    }
+    let expected = "ImmersiveColorSet".to_wide();
+    unsafe {
+        // Stop at the terminating null, or at the first difference.
+        for (i, &c) in expected.iter().enumerate() {
+            if *name.add(i) != c {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn sys_color(index: i32) -> Color {
+    // A COLORREF is 0x00bbggrr.
+    let color: COLORREF = unsafe { GetSysColor(index) };
+    Color::rgb8(
+        (color & 0xff) as u8,
+        ((color >> 8) & 0xff) as u8,
+        ((color >> 16) & 0xff) as u8,
+    )
+}
@@ -71,6 +71,10 @@ impl Application {
         Clipboard
     }
 
+    pub fn restores_windows() -> bool {
+        true
+    }
+
     pub fn get_locale() -> String {
         //TODO ahem
         "en-US".into()
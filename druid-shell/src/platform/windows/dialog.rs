@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! File open/save dialogs, Windows implementation.
+//! File open/save dialogs and message dialogs, Windows implementation.
 //!
 //! For more information about how windows handles file dialogs, see
 //! documentation for [_FILEOPENDIALOGOPTIONS] and [SetFileTypes].
@@ -30,12 +30,15 @@ use winapi::um::combaseapi::*;
 use winapi::um::shobjidl::*;
 use winapi::um::shobjidl_core::*;
 use winapi::um::shtypes::COMDLG_FILTERSPEC;
+use winapi::um::winuser::*;
 use winapi::Interface;
 use wio::com::ComPtr;
 
 use super::error::Error;
 use super::util::{as_result, FromWide, ToWide};
-use crate::dialog::{FileDialogOptions, FileDialogType, FileSpec};
+use crate::dialog::{
+    FileDialogOptions, FileDialogType, FileSpec, MessageButtons, MessageDialogOptions,
+};
 use std::ffi::OsString;
 use std::ptr::null_mut;
 
@@ -71,11 +74,11 @@ fn normalize_extension(ext: &&str) -> String {
     format!("*.{}", ext.trim_start_matches('*').trim_start_matches('.'))
 }
 
-pub(crate) unsafe fn get_file_dialog_path(
+pub(crate) unsafe fn get_file_dialog_paths(
     hwnd_owner: HWND,
     ty: FileDialogType,
     options: FileDialogOptions,
-) -> Result<OsString, Error> {
+) -> Result<Vec<OsString>, Error> {
     let mut pfd: *mut IFileDialog = null_mut();
     let (class, id) = match ty {
         FileDialogType::Open => (&CLSID_FileOpenDialog, IFileOpenDialog::uuidof()),
@@ -95,6 +98,13 @@ pub(crate) unsafe fn get_file_dialog_path(
     if options.show_hidden {
         flags |= FOS_FORCESHOWHIDDEN;
     }
+    let multi_selection = match ty {
+        FileDialogType::Open => options.multi_selection,
+        FileDialogType::Save => false,
+    };
+    if multi_selection {
+        flags |= FOS_ALLOWMULTISELECT;
+    }
 
     // - allowed filetypes
 
@@ -143,13 +153,57 @@ pub(crate) unsafe fn get_file_dialog_path(
 
     // show the dialog
     as_result(file_dialog.Show(hwnd_owner))?;
-    let mut result_ptr: *mut IShellItem = null_mut();
-    as_result(file_dialog.GetResult(&mut result_ptr))?;
-    let shell_item = ComPtr::from_raw(result_ptr);
+
+    if multi_selection {
+        let open_dialog = file_dialog.cast::<IFileOpenDialog>().map_err(Error::Hr)?;
+        let mut results_ptr: *mut IShellItemArray = null_mut();
+        as_result(open_dialog.GetResults(&mut results_ptr))?;
+        let results = ComPtr::from_raw(results_ptr);
+        let mut count: DWORD = 0;
+        as_result(results.GetCount(&mut count))?;
+        let mut paths = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let mut item_ptr: *mut IShellItem = null_mut();
+            as_result(results.GetItemAt(i, &mut item_ptr))?;
+            paths.push(shell_item_path(&ComPtr::from_raw(item_ptr))?);
+        }
+        Ok(paths)
+    } else {
+        let mut result_ptr: *mut IShellItem = null_mut();
+        as_result(file_dialog.GetResult(&mut result_ptr))?;
+        Ok(vec![shell_item_path(&ComPtr::from_raw(result_ptr))?])
+    }
+}
+
+unsafe fn shell_item_path(shell_item: &ComPtr<IShellItem>) -> Result<OsString, Error> {
     let mut display_name: LPWSTR = null_mut();
     as_result(shell_item.GetDisplayName(SIGDN_FILESYSPATH, &mut display_name))?;
     let filename = display_name.to_os_string();
     CoTaskMemFree(display_name as LPVOID);
-
     Ok(filename)
 }
+
+/// Show a message box, modal to `hwnd_owner`, returning `true` if the user
+/// chose OK or Yes.
+pub(crate) unsafe fn show_message_dialog(hwnd_owner: HWND, options: MessageDialogOptions) -> bool {
+    let mut flags = match options.buttons {
+        MessageButtons::Ok => MB_OK,
+        MessageButtons::OkCancel => MB_OKCANCEL,
+        MessageButtons::YesNo => MB_YESNO,
+    };
+    flags |= if options.warning {
+        MB_ICONWARNING
+    } else {
+        MB_ICONQUESTION
+    };
+    // Without an owner, block the rest of the application's windows too.
+    flags |= if hwnd_owner.is_null() {
+        MB_TASKMODAL
+    } else {
+        MB_APPLMODAL
+    };
+    let title = options.title.to_wide();
+    let message = options.message.to_wide();
+    let result = MessageBoxW(hwnd_owner, message.as_ptr(), title.as_ptr(), flags);
+    result == IDOK || result == IDYES
+}
@@ -14,6 +14,7 @@
 
 //! Windows implementation of druid-shell.
 
+pub mod appearance;
 pub mod application;
 pub mod clipboard;
 pub mod dcomp;
@@ -23,6 +24,10 @@ pub mod keycodes;
 pub mod menu;
 pub mod paint;
 pub mod runloop;
+pub mod screen;
+pub mod system_event;
+pub mod text_metrics;
 mod timers;
 pub mod util;
+mod vsync;
 pub mod window;
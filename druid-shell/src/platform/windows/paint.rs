@@ -41,6 +41,7 @@ use direct2d::render_target::{DxgiSurfaceRenderTarget, GenericRenderTarget, Hwnd
 
 use super::error::Error;
 use super::util::as_result;
+use crate::window::RenderBackend;
 
 /// Context for painting by app into window.
 pub struct PaintCtx<'a> {
@@ -48,9 +49,14 @@ pub struct PaintCtx<'a> {
     pub(crate) render_target: &'a mut GenericRenderTarget,
 }
 
+/// Create a render target for a window.
+///
+/// If the GPU was requested but a hardware render target can't be created,
+/// this falls back to a software one.
 pub(crate) unsafe fn create_render_target(
     d2d_factory: &direct2d::Factory,
     hwnd: HWND,
+    backend: RenderBackend,
 ) -> Result<HwndRenderTarget, Error> {
     let mut rect: RECT = mem::zeroed();
     if GetClientRect(hwnd, &mut rect) == 0 {
@@ -59,12 +65,27 @@ pub(crate) unsafe fn create_render_target(
     } else {
         let width = (rect.right - rect.left) as u32;
         let height = (rect.bottom - rect.top) as u32;
-        let res = HwndRenderTarget::create(d2d_factory)
-            .with_hwnd(hwnd)
-            .with_target_type(RenderTargetType::Default)
-            .with_alpha_mode(AlphaMode::Unknown)
-            .with_pixel_size(width, height)
-            .build();
+        let target_type = match backend {
+            RenderBackend::Default => RenderTargetType::Default,
+            RenderBackend::Gpu => RenderTargetType::Hardware,
+            RenderBackend::Cpu => RenderTargetType::Software,
+        };
+        let build = |target_type| {
+            HwndRenderTarget::create(d2d_factory)
+                .with_hwnd(hwnd)
+                .with_target_type(target_type)
+                .with_alpha_mode(AlphaMode::Unknown)
+                .with_pixel_size(width, height)
+                .build()
+        };
+        let mut res = build(target_type);
+        if let (Err(ref e), RenderBackend::Gpu) = (&res, backend) {
+            warn!(
+                "Creating hardware render target failed, falling back to software: {:?}",
+                e
+            );
+            res = build(RenderTargetType::Software);
+        }
         if let Err(ref e) = res {
             error!("Creating hwnd render target failed: {:?}", e);
         }
@@ -23,6 +23,8 @@ use winapi::um::winbase::*;
 use winapi::um::winnt::*;
 use winapi::um::winuser::*;
 
+use super::system_event;
+
 // TODO: remove this, it's been obsoleted by IdleHandle
 #[derive(Clone, Default)]
 pub struct RunLoopHandle(Arc<Mutex<RunLoopState>>);
@@ -98,6 +100,10 @@ impl RunLoop {
                         return;
                     }
                     let mut msg: MSG = msg.assume_init();
+                    if msg.hwnd.is_null() && msg.message == system_event::XI_NETWORK_CHANGED {
+                        system_event::network_changed();
+                        continue;
+                    }
                     if self.accel.is_null()
                         || TranslateAcceleratorW(msg.hwnd, self.accel, &mut msg) == 0
                     {
@@ -0,0 +1,81 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Windows monitor information.
+
+use std::mem;
+use std::ptr::{null, null_mut};
+
+use winapi::shared::minwindef::*;
+use winapi::shared::windef::*;
+use winapi::um::winuser::*;
+
+use log::warn;
+
+use super::util::OPTIONAL_FUNCTIONS;
+use crate::kurbo::Rect;
+use crate::screen::Monitor;
+
+pub(crate) fn get_monitors() -> Vec<Monitor> {
+    let mut monitors = Vec::new();
+    unsafe {
+        let ptr = &mut monitors as *mut Vec<Monitor>;
+        if EnumDisplayMonitors(null_mut(), null(), Some(monitor_enum_proc), ptr as LPARAM) == 0 {
+            warn!("failed to enumerate monitors");
+        }
+    }
+    monitors
+}
+
+unsafe extern "system" fn monitor_enum_proc(
+    hmonitor: HMONITOR,
+    _hdc: HDC,
+    _rect: LPRECT,
+    lparam: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(lparam as *mut Vec<Monitor>);
+    let mut info: MONITORINFO = mem::zeroed();
+    info.cbSize = mem::size_of::<MONITORINFO>() as DWORD;
+    if GetMonitorInfoW(hmonitor, &mut info) == 0 {
+        warn!("failed to get monitor info");
+        return TRUE;
+    }
+    let primary = info.dwFlags & MONITORINFOF_PRIMARY != 0;
+    monitors.push(Monitor::new(
+        primary,
+        rect_to_px(info.rcMonitor),
+        rect_to_px(info.rcWork),
+    ));
+    TRUE
+}
+
+/// The scale from physical pixels to px, using the system dpi.
+pub(crate) fn system_scale() -> f64 {
+    let dpi = match OPTIONAL_FUNCTIONS.GetDpiForSystem {
+        // Only supported on windows 10
+        Some(func) => unsafe { func() as f64 },
+        None => 96.0,
+    };
+    96.0 / dpi
+}
+
+fn rect_to_px(rect: RECT) -> Rect {
+    let scale = system_scale();
+    Rect::new(
+        f64::from(rect.left) * scale,
+        f64::from(rect.top) * scale,
+        f64::from(rect.right) * scale,
+        f64::from(rect.bottom) * scale,
+    )
+}
@@ -0,0 +1,141 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Windows power, session and network notifications.
+//!
+//! Power and session changes are sent to every top-level window, which
+//! pass them on here. Changes to the network are waited for on a thread of
+//! their own, which tells the run loop.
+
+use std::mem;
+use std::ptr::null_mut;
+use std::thread;
+
+use log::warn;
+use winapi::shared::minwindef::*;
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::NO_ERROR;
+use winapi::um::minwinbase::OVERLAPPED;
+use winapi::um::processthreadsapi::GetCurrentThreadId;
+use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+use winapi::um::winnt::HANDLE;
+use winapi::um::winuser::{PostThreadMessageW, WM_POWERBROADCAST, WM_USER};
+
+use crate::system_event::{self, PowerSource, SystemEvent};
+
+pub(crate) const WM_WTSSESSION_CHANGE: UINT = 0x02B1;
+/// Posted to the main thread when the network's addresses have changed.
+pub(crate) const XI_NETWORK_CHANGED: UINT = WM_USER + 1;
+
+const PBT_APMSUSPEND: WPARAM = 0x0004;
+const PBT_APMRESUMESUSPEND: WPARAM = 0x0007;
+const PBT_APMPOWERSTATUSCHANGE: WPARAM = 0x000A;
+const PBT_APMRESUMEAUTOMATIC: WPARAM = 0x0012;
+const WTS_SESSION_LOCK: WPARAM = 0x7;
+const WTS_SESSION_UNLOCK: WPARAM = 0x8;
+const NOTIFY_FOR_THIS_SESSION: DWORD = 0;
+
+#[link(name = "wtsapi32")]
+extern "system" {
+    fn WTSRegisterSessionNotification(hwnd: HWND, flags: DWORD) -> BOOL;
+    fn WTSUnRegisterSessionNotification(hwnd: HWND) -> BOOL;
+}
+
+#[link(name = "iphlpapi")]
+extern "system" {
+    fn GetBestInterface(dest_addr: DWORD, best_if_index: *mut DWORD) -> DWORD;
+    fn NotifyAddrChange(handle: *mut HANDLE, overlapped: *mut OVERLAPPED) -> DWORD;
+}
+
+/// The power source, from the state of the AC line.
+pub(crate) fn power_source() -> Option<PowerSource> {
+    unsafe {
+        let mut status: SYSTEM_POWER_STATUS = mem::zeroed();
+        if GetSystemPowerStatus(&mut status) == 0 {
+            warn!("failed to get power status");
+            return None;
+        }
+        match status.ACLineStatus {
+            0 => Some(PowerSource::Battery),
+            1 => Some(PowerSource::External),
+            _ => None,
+        }
+    }
+}
+
+/// Ask for `hwnd` to be told when the session is locked and unlocked.
+pub(crate) fn register(hwnd: HWND) {
+    unsafe {
+        if WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) == FALSE {
+            warn!("failed to register for session notifications");
+        }
+    }
+}
+
+/// Stop telling `hwnd` of session changes, before it is destroyed.
+pub(crate) fn unregister(hwnd: HWND) {
+    unsafe {
+        WTSUnRegisterSessionNotification(hwnd);
+    }
+}
+
+/// Report the change announced by a `WM_POWERBROADCAST` or
+/// `WM_WTSSESSION_CHANGE` message.
+pub(crate) fn handle_message(msg: UINT, wparam: WPARAM) {
+    let event = match (msg, wparam) {
+        (WM_POWERBROADCAST, PBT_APMSUSPEND) => SystemEvent::WillSleep,
+        // Both are sent when the user wakes the computer, and only the first
+        // when it wakes by itself, such as for a scheduled task.
+        (WM_POWERBROADCAST, PBT_APMRESUMEAUTOMATIC) | (WM_POWERBROADCAST, PBT_APMRESUMESUSPEND) => {
+            SystemEvent::DidWake
+        }
+        (WM_POWERBROADCAST, PBT_APMPOWERSTATUSCHANGE) => match power_source() {
+            Some(source) => SystemEvent::PowerSourceChanged(source),
+            None => return,
+        },
+        (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => SystemEvent::SessionLocked,
+        (WM_WTSSESSION_CHANGE, WTS_SESSION_UNLOCK) => SystemEvent::SessionUnlocked,
+        _ => return,
+    };
+    system_event::system_event(event);
+}
+
+/// Whether there is a route to an address on the internet.
+pub(crate) fn network_reachable() -> bool {
+    // 8.8.8.8, which is the same in either byte order.
+    const INTERNET_ADDR: DWORD = 0x0808_0808;
+    let mut index = 0;
+    unsafe { GetBestInterface(INTERNET_ADDR, &mut index) == NO_ERROR }
+}
+
+/// Report changes to the network, from a thread that waits for addresses
+/// to be added or removed.
+pub(crate) fn monitor_network() {
+    let main_thread = unsafe { GetCurrentThreadId() };
+    thread::spawn(move || loop {
+        // Without a handle, this blocks until the next change.
+        if unsafe { NotifyAddrChange(null_mut(), null_mut()) } != NO_ERROR {
+            warn!("failed to wait for network changes");
+            break;
+        }
+        unsafe {
+            PostThreadMessageW(main_thread, XI_NETWORK_CHANGED, 0, 0);
+        }
+    });
+}
+
+/// Report the state of the network, after `XI_NETWORK_CHANGED`.
+pub(crate) fn network_changed() {
+    system_event::system_event(SystemEvent::NetworkChanged(network_reachable()));
+}
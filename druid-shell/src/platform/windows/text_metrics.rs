@@ -0,0 +1,90 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Windows font metrics, from DirectWrite.
+
+use std::mem;
+use std::ptr::null_mut;
+
+use winapi::shared::minwindef::FALSE;
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::um::dwrite::*;
+use winapi::um::unknwnbase::IUnknown;
+use winapi::Interface;
+use wio::com::ComPtr;
+
+use super::util::ToWide;
+use crate::text_metrics::FontMetrics;
+
+/// The metrics of the regular face of the system font family named
+/// `font_name`, which is the face piet's Direct2D backend draws with.
+pub(crate) fn font_metrics(font_name: &str, size: f64) -> Option<FontMetrics> {
+    unsafe {
+        let mut factory: *mut IDWriteFactory = null_mut();
+        let hr = DWriteCreateFactory(
+            DWRITE_FACTORY_TYPE_SHARED,
+            &IDWriteFactory::uuidof(),
+            &mut factory as *mut *mut IDWriteFactory as *mut *mut IUnknown,
+        );
+        if !SUCCEEDED(hr) {
+            return None;
+        }
+        let factory = ComPtr::from_raw(factory);
+
+        let mut collection = null_mut();
+        if !SUCCEEDED(factory.GetSystemFontCollection(&mut collection, FALSE)) {
+            return None;
+        }
+        let collection = ComPtr::from_raw(collection);
+
+        let name = font_name.to_wide();
+        let mut index = 0;
+        let mut exists = FALSE;
+        let hr = collection.FindFamilyName(name.as_ptr(), &mut index, &mut exists);
+        if !SUCCEEDED(hr) || exists == FALSE {
+            return None;
+        }
+        let mut family = null_mut();
+        if !SUCCEEDED(collection.GetFontFamily(index, &mut family)) {
+            return None;
+        }
+        let family = ComPtr::from_raw(family);
+
+        let mut font = null_mut();
+        let hr = family.GetFirstMatchingFont(
+            DWRITE_FONT_WEIGHT_NORMAL,
+            DWRITE_FONT_STRETCH_NORMAL,
+            DWRITE_FONT_STYLE_NORMAL,
+            &mut font,
+        );
+        if !SUCCEEDED(hr) {
+            return None;
+        }
+        let font = ComPtr::from_raw(font);
+
+        let mut metrics: DWRITE_FONT_METRICS = mem::zeroed();
+        font.GetMetrics(&mut metrics);
+        // Metrics are in design units, a fraction of the font size.
+        let scale = size / f64::from(metrics.designUnitsPerEm);
+        let ascent = f64::from(metrics.ascent) * scale;
+        let descent = f64::from(metrics.descent) * scale;
+        Some(FontMetrics {
+            ascent,
+            descent,
+            line_height: ascent + descent + f64::from(metrics.lineGap) * scale,
+            cap_height: f64::from(metrics.capHeight) * scale,
+            x_height: f64::from(metrics.xHeight) * scale,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2018 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pacing animation with the display's refresh.
+
+use std::ptr::null;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::error;
+use winapi::shared::minwindef::FALSE;
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::InvalidateRect;
+
+use super::window::DwmFlush;
+
+/// How long to wait between frames when the desktop window manager doesn't
+/// wait for the refresh, as when composition is off.
+const FALLBACK_PERIOD: Duration = Duration::from_millis(16);
+
+/// What the window asks of the thread.
+#[derive(Default)]
+struct State {
+    /// Whether the window is animating.
+    animating: bool,
+    /// Whether the window has closed, so that the thread should end.
+    closed: bool,
+}
+
+/// A thread that repaints a window after every refresh of the display,
+/// while it is animating.
+///
+/// The thread waits for each composition of the desktop, which follows
+/// the display's vertical blank, so frames are neither painted faster than
+/// the display shows them nor delayed by the message loop.
+#[derive(Default)]
+pub(crate) struct Vsync {
+    shared: Arc<(Mutex<State>, Condvar)>,
+    /// Whether the thread has been started.
+    started: bool,
+}
+
+impl Vsync {
+    /// Repaint `hwnd` at every refresh, if it isn't already.
+    pub(crate) fn start(&mut self, hwnd: HWND) {
+        let (state, wake) = &*self.shared;
+        state.lock().unwrap().animating = true;
+        wake.notify_one();
+        if !self.started {
+            self.started = true;
+            let shared = self.shared.clone();
+            // Window handles may be used from any thread.
+            let hwnd = hwnd as usize;
+            let spawned = thread::Builder::new()
+                .name("vsync".into())
+                .spawn(move || run(&shared, hwnd as HWND));
+            if let Err(e) = spawned {
+                error!("failed to start the vsync thread: {}", e);
+            }
+        }
+    }
+
+    /// Stop repainting the window.
+    pub(crate) fn stop(&self) {
+        self.shared.0.lock().unwrap().animating = false;
+    }
+
+    /// End the thread, once the window has closed.
+    pub(crate) fn close(&self) {
+        let (state, wake) = &*self.shared;
+        state.lock().unwrap().closed = true;
+        wake.notify_one();
+    }
+}
+
+impl Drop for Vsync {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+fn run(shared: &(Mutex<State>, Condvar), hwnd: HWND) {
+    let (state, wake) = shared;
+    loop {
+        {
+            let mut state = state.lock().unwrap();
+            while !state.animating && !state.closed {
+                state = wake.wait(state).unwrap();
+            }
+            if state.closed {
+                return;
+            }
+        }
+        let start = Instant::now();
+        unsafe {
+            DwmFlush();
+        }
+        if start.elapsed() < Duration::from_millis(1) {
+            thread::sleep(FALLBACK_PERIOD);
+        }
+        unsafe {
+            InvalidateRect(hwnd, null(), FALSE);
+        }
+    }
+}
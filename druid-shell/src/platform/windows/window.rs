@@ -22,9 +22,9 @@ use std::mem;
 use std::ops::Deref;
 use std::ptr::{null, null_mut};
 use std::rc::{Rc, Weak};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Once};
 
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
 use winapi::ctypes::{c_int, c_void};
 use winapi::shared::basetsd::*;
 use winapi::shared::dxgi::*;
@@ -35,6 +35,8 @@ use winapi::shared::minwindef::*;
 use winapi::shared::windef::*;
 use winapi::shared::winerror::*;
 use winapi::um::d2d1::*;
+use winapi::um::dwmapi::{DwmGetCompositionTimingInfo, DWM_TIMING_INFO};
+use winapi::um::profileapi::{QueryPerformanceCounter, QueryPerformanceFrequency};
 use winapi::um::unknwnbase::*;
 use winapi::um::winnt::*;
 use winapi::um::winuser::*;
@@ -47,20 +49,26 @@ use direct2d::render_target::{GenericRenderTarget, HwndRenderTarget, RenderTarge
 use crate::kurbo::{Point, Size, Vec2};
 use crate::piet::{Piet, RenderContext};
 
+use super::appearance;
 use super::dcomp::{D3D11Device, DCompositionDevice, DCompositionTarget, DCompositionVisual};
-use super::dialog::get_file_dialog_path;
+use super::dialog::{get_file_dialog_paths, show_message_dialog};
 use super::error::Error;
 use super::menu::Menu;
 use super::paint;
+use super::system_event;
 use super::timers::TimerSlots;
 use super::util::{as_result, FromWide, ToWide, OPTIONAL_FUNCTIONS};
+use super::vsync::Vsync;
 
+use crate::color_space::ColorSpace;
 use crate::common_util::IdleCallback;
-use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo};
+use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo, MessageDialogOptions};
+use crate::frame::FrameTiming;
 use crate::keyboard::{KeyEvent, KeyModifiers};
 use crate::keycodes::KeyCode;
 use crate::mouse::{Cursor, MouseButton, MouseEvent};
-use crate::window::{Text, TimerToken, WinCtx, WinHandler};
+use crate::text_rendering::{TextAntialiasing, TextRendering};
+use crate::window::{DialogStyle, Politeness, RenderBackend, Text, TimerToken, WinCtx, WinHandler};
 
 extern "system" {
     pub fn DwmFlush();
@@ -73,7 +81,12 @@ pub struct WindowBuilder {
     title: String,
     menu: Option<Menu>,
     present_strategy: PresentStrategy,
+    render_backend: RenderBackend,
+    text_rendering: TextRendering,
     size: Size,
+    position: Option<Point>,
+    /// The window that owns this one, if it is a dialog.
+    owner: Option<(HWND, DialogStyle)>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -132,6 +145,14 @@ struct WindowState {
     // as a mutable reference down through WinCtx, but that would require
     // some refactoring.
     timers: Arc<Mutex<TimerSlots>>,
+    text_rendering: Cell<TextRendering>,
+    /// Whether the next `WM_PAINT` must paint, whatever the handler says,
+    /// because the last frame is gone or out of date.
+    repaint: Cell<bool>,
+    /// The owner of a modal dialog, disabled while the dialog is shown.
+    modal_owner: Option<HWND>,
+    /// Repaints the window at every refresh while it animates.
+    vsync: RefCell<Vsync>,
 }
 
 /// Generic handler trait for the winapi window procedure entry point.
@@ -156,6 +177,7 @@ struct WndState {
     handler: Box<dyn WinHandler>,
     render_target: Option<GenericRenderTarget>,
     dcomp_state: Option<DCompState>,
+    render_backend: RenderBackend,
     dpi: f32,
     /// The `KeyCode` of the last `WM_KEYDOWN` event. We stash this so we can
     /// include it when handling `WM_CHAR` events.
@@ -192,6 +214,10 @@ struct DCompState {
 /// Message indicating there are idle tasks to run.
 const XI_RUN_IDLE: UINT = WM_USER;
 
+/// Message asking the window to resize, to the outer width and height in
+/// the `WPARAM` and `LPARAM`.
+const XI_SET_SIZE: UINT = WM_USER + 1;
+
 impl Default for PresentStrategy {
     fn default() -> PresentStrategy {
         // We probably want to change this, but we need GDI to work. Too bad about
@@ -237,8 +263,22 @@ impl WndState {
         handle: &RefCell<WindowHandle>,
         c: &mut WinCtxOwner,
     ) {
+        let text_rendering = handle.borrow().text_rendering();
         let rt = self.render_target.as_mut().unwrap();
         rt.begin_draw();
+        // The render targets are opaque, so subpixel antialiasing is safe.
+        let mode = match text_rendering.antialiasing {
+            Some(TextAntialiasing::Subpixel) => D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE,
+            Some(TextAntialiasing::Grayscale) => D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE,
+            Some(TextAntialiasing::Aliased) => D2D1_TEXT_ANTIALIAS_MODE_ALIASED,
+            None => D2D1_TEXT_ANTIALIAS_MODE_DEFAULT,
+        };
+        unsafe {
+            rt.rt().SetTextAntialiasMode(mode);
+        }
+        if let Some(timing) = frame_timing() {
+            self.handler.prepare_frame(timing);
+        }
         let anim;
         {
             let mut piet_ctx = Piet::new(d2d, dw, rt);
@@ -252,15 +292,55 @@ impl WndState {
         if let Err(e) = res {
             error!("EndDraw error: {:?}", e);
         }
-        if anim {
-            let handle = handle.borrow().get_idle_handle().unwrap();
-            // Note: maybe add WindowHandle as arg to idle handler so we don't need this.
-            let handle2 = handle.clone();
-            handle.add_idle(move |_| handle2.invalidate());
+        // The frames of an animation are paced by the display's refresh.
+        if let Some(w) = handle.borrow().state.upgrade() {
+            let mut vsync = w.vsync.borrow_mut();
+            if anim {
+                vsync.start(w.hwnd.get());
+            } else {
+                vsync.stop();
+            }
         }
     }
 }
 
+/// The timing of the next frame, from the desktop window manager.
+fn frame_timing() -> Option<FrameTiming> {
+    unsafe {
+        let mut info: DWM_TIMING_INFO = mem::zeroed();
+        info.cbSize = mem::size_of::<DWM_TIMING_INFO>() as u32;
+        // Since Windows 8.1, timing is only reported for the whole desktop.
+        if FAILED(DwmGetCompositionTimingInfo(null_mut(), &mut info)) {
+            return None;
+        }
+        let mut now: LARGE_INTEGER = mem::zeroed();
+        let mut frequency: LARGE_INTEGER = mem::zeroed();
+        if QueryPerformanceCounter(&mut now) == FALSE
+            || QueryPerformanceFrequency(&mut frequency) == FALSE
+        {
+            return None;
+        }
+        let now = *now.QuadPart();
+        let period = info.qpcRefreshPeriod as i64;
+        if period <= 0 {
+            return None;
+        }
+        // The first vertical blank after now.
+        let last = info.qpcVBlank as i64;
+        let next = if last > now {
+            last
+        } else {
+            last + ((now - last) / period + 1) * period
+        };
+        Some(FrameTiming::from_clock(
+            now,
+            next,
+            period,
+            *frequency.QuadPart() as f64,
+        ))
+    }
+}
+
 impl MyWndProc {
     /// Create debugging output for dropped messages due to wndproc reentrancy.
     ///
@@ -326,26 +406,47 @@ impl WndProc for MyWndProc {
                 }
                 Some(0)
             }
+            WM_SETTINGCHANGE | WM_SYSCOLORCHANGE => {
+                if msg == WM_SETTINGCHANGE && !appearance::is_appearance_change(wparam, lparam) {
+                    return None;
+                }
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                    s.handler.appearance_changed(&mut c.ctx());
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                None
+            }
             WM_PAINT => unsafe {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
+                    let mut repaint = self.handle.borrow().take_repaint();
                     if s.render_target.is_none() {
-                        let rt = paint::create_render_target(&self.d2d_factory, hwnd)
-                            .map(|rt| rt.as_generic());
+                        let rt =
+                            paint::create_render_target(&self.d2d_factory, hwnd, s.render_backend)
+                                .map(|rt| rt.as_generic());
                         s.render_target = rt.ok();
+                        repaint = true;
                     }
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
-                    s.handler.rebuild_resources(&mut c.ctx());
-                    s.render(
-                        &self.d2d_factory,
-                        &self.dwrite_factory,
-                        &self.handle,
-                        &mut c,
-                    );
-                    if let Some(ref mut ds) = s.dcomp_state {
-                        if !ds.sizing {
-                            (*ds.swap_chain).Present(1, 0);
-                            let _ = ds.dcomp_device.commit();
+                    // The window keeps showing the last frame, either in the
+                    // swap chain or in DWM's copy of the window, so a frame
+                    // with nothing new in it can be skipped.
+                    if repaint || s.handler.needs_paint(&mut c.ctx()) {
+                        s.handler.rebuild_resources(&mut c.ctx());
+                        s.render(
+                            &self.d2d_factory,
+                            &self.dwrite_factory,
+                            &self.handle,
+                            &mut c,
+                        );
+                        if let Some(ref mut ds) = s.dcomp_state {
+                            if !ds.sizing {
+                                (*ds.swap_chain).Present(1, 0);
+                                let _ = ds.dcomp_device.commit();
+                            }
                         }
                     }
                     ValidateRect(hwnd, null_mut());
@@ -358,8 +459,9 @@ impl WndProc for MyWndProc {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
                     if s.dcomp_state.is_some() {
-                        let rt = paint::create_render_target(&self.d2d_factory, hwnd)
-                            .map(|rt| rt.as_generic());
+                        let rt =
+                            paint::create_render_target(&self.d2d_factory, hwnd, s.render_backend)
+                                .map(|rt| rt.as_generic());
                         s.render_target = rt.ok();
                         {
                             let mut c =
@@ -442,6 +544,7 @@ impl WndProc for MyWndProc {
                     let height = HIWORD(lparam as u32) as u32;
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
                     s.handler.size(width, height, &mut c.ctx());
+                    self.handle.borrow().force_repaint();
                     let use_hwnd = if let Some(ref dcomp_state) = s.dcomp_state {
                         dcomp_state.sizing
                     } else {
@@ -689,7 +792,28 @@ impl WndProc for MyWndProc {
                 }
                 Some(0)
             }
+            WM_CLOSE => {
+                // The owner must be enabled before the dialog is destroyed,
+                // or another application is activated in its place.
+                self.handle.borrow().enable_modal_owner();
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
+                    s.handler.will_close(&mut c.ctx());
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                None
+            }
+            WM_POWERBROADCAST | system_event::WM_WTSSESSION_CHANGE => {
+                system_event::handle_message(msg, wparam);
+                None
+            }
             WM_DESTROY => {
+                system_event::unregister(hwnd);
+                if let Some(w) = self.handle.borrow().state.upgrade() {
+                    w.vsync.borrow().close();
+                }
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
                     let mut c = WinCtxOwner::new(self.handle.borrow(), &self.dwrite_factory);
@@ -713,6 +837,18 @@ impl WndProc for MyWndProc {
                 }
                 Some(1)
             }
+            XI_SET_SIZE => unsafe {
+                // `SetWindowPos` sends `WM_SIZE` before returning, so it is
+                // called here, where the handler isn't borrowed; otherwise
+                // the new size would be dropped.
+                let flags = SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE;
+                if SetWindowPos(hwnd, null_mut(), 0, 0, wparam as i32, lparam as i32, flags)
+                    == FALSE
+                {
+                    warn!("failed to resize window");
+                }
+                Some(0)
+            },
             XI_RUN_IDLE => {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
@@ -731,6 +867,14 @@ impl WndProc for MyWndProc {
     }
 }
 
+/// Direct2D's hinting follows the system settings and can't be changed
+/// per window.
+fn warn_unsupported_hinting(text_rendering: TextRendering) {
+    if text_rendering.hinting.is_some() {
+        info!("text hinting can't be changed on this platform");
+    }
+}
+
 // Note: there's a clone method in 0.3.0-alpha4. We work around
 // the lack in 0.1.2 by calling the low-level unsafe operations.
 fn clone_dwrite(dwrite: &directwrite::Factory) -> directwrite::Factory {
@@ -748,7 +892,11 @@ impl WindowBuilder {
             title: String::new(),
             menu: None,
             present_strategy: Default::default(),
+            render_backend: Default::default(),
+            text_rendering: Default::default(),
             size: Size::new(500.0, 400.0),
+            position: None,
+            owner: None,
         }
     }
 
@@ -771,6 +919,17 @@ impl WindowBuilder {
         self.size = size;
     }
 
+    pub fn set_position(&mut self, position: Point) {
+        self.position = Some(position);
+    }
+
+    pub fn set_parent(&mut self, parent: &WindowHandle, style: DialogStyle) {
+        match parent.get_hwnd() {
+            Some(hwnd) => self.owner = Some((hwnd, style)),
+            None => warn!("the parent of a dialog has closed"),
+        }
+    }
+
     pub fn set_title<S: Into<String>>(&mut self, title: S) {
         self.title = title.into();
     }
@@ -783,6 +942,28 @@ impl WindowBuilder {
         self.present_strategy = present_strategy;
     }
 
+    pub fn set_render_backend(&mut self, backend: RenderBackend) {
+        self.render_backend = backend;
+    }
+
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        if color_space != ColorSpace::Srgb {
+            info!(
+                "{:?} windows are not available on this platform",
+                color_space
+            );
+        }
+    }
+
+    pub fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        warn_unsupported_hinting(text_rendering);
+        self.text_rendering = text_rendering;
+    }
+
+    pub fn set_restoration_id(&mut self, _id: String) {
+        // Windows has no window restoration.
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
         unsafe {
             // Maybe separate registration in build api? Probably only need to
@@ -804,6 +985,13 @@ impl WindowBuilder {
                 wndproc: Box::new(wndproc),
                 idle_queue: Default::default(),
                 timers: Arc::new(Mutex::new(TimerSlots::new(1))),
+                text_rendering: Cell::new(self.text_rendering),
+                repaint: Cell::new(true),
+                modal_owner: match self.owner {
+                    Some((owner, DialogStyle::Modal)) => Some(owner),
+                    _ => None,
+                },
+                vsync: Default::default(),
             };
             let win = Rc::new(window);
             let handle = WindowHandle {
@@ -823,25 +1011,45 @@ impl WindowBuilder {
             win.dpi.set(dpi);
             let width = (self.size.width * (f64::from(dpi) / 96.0)) as i32;
             let height = (self.size.height * (f64::from(dpi) / 96.0)) as i32;
+            let (x, y) = match self.position {
+                Some(pos) => (
+                    (pos.x * (f64::from(dpi) / 96.0)) as i32,
+                    (pos.y * (f64::from(dpi) / 96.0)) as i32,
+                ),
+                None => (CW_USEDEFAULT, CW_USEDEFAULT),
+            };
 
             let hmenu = match self.menu {
                 Some(menu) => menu.into_hmenu(),
                 None => 0 as HMENU,
             };
+            let render_backend = self.render_backend.resolve();
+            // The swapchain is always on the GPU, so painting on the CPU
+            // means presenting through the hwnd.
+            let present_strategy = match render_backend {
+                RenderBackend::Cpu => PresentStrategy::Hwnd,
+                _ => self.present_strategy,
+            };
             let mut dwExStyle = 0;
-            if self.present_strategy == PresentStrategy::Flip {
+            if present_strategy == PresentStrategy::Flip {
                 dwExStyle |= WS_EX_NOREDIRECTIONBITMAP;
             }
+            // An owned window stays above its owner, is minimized with it,
+            // and has no taskbar button of its own.
+            let (owner, dwStyle) = match self.owner {
+                Some((owner, _)) => (owner, self.dwStyle & !(WS_MINIMIZEBOX | WS_MAXIMIZEBOX)),
+                None => (0 as HWND, self.dwStyle),
+            };
             let hwnd = create_window(
                 dwExStyle,
                 class_name.as_ptr(),
                 self.title.to_wide().as_ptr(),
-                self.dwStyle,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
+                dwStyle,
+                x,
+                y,
                 width,
                 height,
-                0 as HWND,
+                owner,
                 hmenu,
                 0 as HINSTANCE,
                 win.clone(),
@@ -850,16 +1058,18 @@ impl WindowBuilder {
                 return Err(Error::NullHwnd);
             }
 
-            let dcomp_state = create_dcomp_state(self.present_strategy, hwnd).unwrap_or_else(|e| {
+            let dcomp_state = create_dcomp_state(present_strategy, hwnd).unwrap_or_else(|e| {
                 warn!("Creating swapchain failed, falling back to hwnd: {:?}", e);
                 None
             });
 
             win.hwnd.set(hwnd);
+            system_event::register(hwnd);
             let state = WndState {
                 handler: self.handler.unwrap(),
                 render_target: None,
                 dcomp_state,
+                render_backend,
                 dpi,
                 stashed_key_code: KeyCode::Unknown(0),
                 stashed_char: None,
@@ -1070,6 +1280,9 @@ impl WindowHandle {
         if let Some(w) = self.state.upgrade() {
             let hwnd = w.hwnd.get();
             unsafe {
+                if let Some(owner) = w.modal_owner {
+                    EnableWindow(owner, FALSE);
+                }
                 ShowWindow(hwnd, SW_SHOWNORMAL);
                 UpdateWindow(hwnd);
             }
@@ -1077,6 +1290,7 @@ impl WindowHandle {
     }
 
     pub fn close(&self) {
+        self.enable_modal_owner();
         if let Some(w) = self.state.upgrade() {
             let hwnd = w.hwnd.get();
             unsafe {
@@ -1085,12 +1299,29 @@ impl WindowHandle {
         }
     }
 
+    /// Give input back to the owner of a modal dialog, which is closing.
+    fn enable_modal_owner(&self) {
+        if let Some(owner) = self.state.upgrade().and_then(|w| w.modal_owner) {
+            unsafe {
+                EnableWindow(owner, TRUE);
+            }
+        }
+    }
+
     /// Bring this window to the front of the window stack and give it focus.
     pub fn bring_to_front_and_focus(&self) {
         //FIXME: implementation goes here
         log::warn!("bring_to_front_and_focus not yet implemented on windows");
     }
 
+    pub fn announce(&self, text: &str, _politeness: Politeness) {
+        // UI Automation notifications need an accessibility provider for
+        // the window, which we don't have yet.
+        static WARNED: Once = Once::new();
+        WARNED.call_once(|| warn!("screen reader announcements are not supported on Windows"));
+        debug!("announcement not made: {:?}", text);
+    }
+
     pub fn invalidate(&self) {
         if let Some(w) = self.state.upgrade() {
             let hwnd = w.hwnd.get();
@@ -1100,6 +1331,36 @@ impl WindowHandle {
         }
     }
 
+    pub fn set_text_rendering(&self, text_rendering: TextRendering) {
+        warn_unsupported_hinting(text_rendering);
+        if let Some(w) = self.state.upgrade() {
+            w.text_rendering.set(text_rendering);
+        }
+        self.force_repaint();
+        self.invalidate();
+    }
+
+    /// Make the next `WM_PAINT` paint, even if the handler has nothing new.
+    fn force_repaint(&self) {
+        if let Some(w) = self.state.upgrade() {
+            w.repaint.set(true);
+        }
+    }
+
+    fn take_repaint(&self) -> bool {
+        self.state
+            .upgrade()
+            .map(|w| w.repaint.replace(false))
+            .unwrap_or(true)
+    }
+
+    fn text_rendering(&self) -> TextRendering {
+        self.state
+            .upgrade()
+            .map(|w| w.text_rendering.get())
+            .unwrap_or_default()
+    }
+
     /// Set the title for this menu.
     pub fn set_title(&self, title: &str) {
         if let Some(w) = self.state.upgrade() {
@@ -1143,6 +1404,50 @@ impl WindowHandle {
         }
     }
 
+    pub fn set_size(&self, size: Size) {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            let scale = f64::from(w.dpi.get()) / 96.0;
+            unsafe {
+                let mut window_rect: RECT = mem::zeroed();
+                let mut client_rect: RECT = mem::zeroed();
+                if GetWindowRect(hwnd, &mut window_rect) == FALSE
+                    || GetClientRect(hwnd, &mut client_rect) == FALSE
+                {
+                    warn!("failed to get window rect");
+                    return;
+                }
+                // The frame, title bar and menu bar are outside the client area.
+                let frame_width = (window_rect.right - window_rect.left) - client_rect.right;
+                let frame_height = (window_rect.bottom - window_rect.top) - client_rect.bottom;
+                let width = (size.width * scale) as i32 + frame_width;
+                let height = (size.height * scale) as i32 + frame_height;
+                // This may be called from inside the handler, so the resize
+                // is done from the message loop.
+                if PostMessageW(hwnd, XI_SET_SIZE, width as WPARAM, height as LPARAM) == FALSE {
+                    warn!("failed to resize window");
+                }
+            }
+        }
+    }
+
+    pub fn get_position(&self) -> Option<Point> {
+        let w = self.state.upgrade()?;
+        let hwnd = w.hwnd.get();
+        unsafe {
+            let mut rect: RECT = mem::zeroed();
+            if GetWindowRect(hwnd, &mut rect) == FALSE {
+                warn!("failed to get window rect");
+                return None;
+            }
+            let scale = 96.0 / f64::from(w.dpi.get());
+            Some(Point::new(
+                f64::from(rect.left) * scale,
+                f64::from(rect.top) * scale,
+            ))
+        }
+    }
+
     /// Get the raw HWND handle, for uses that are not wrapped in
     /// druid_win_shell.
     pub fn get_hwnd(&self) -> Option<HWND> {
@@ -1290,11 +1595,9 @@ impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
     fn open_file_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo> {
         let hwnd = self.handle.get_hwnd()?;
         unsafe {
-            get_file_dialog_path(hwnd, FileDialogType::Open, options)
+            get_file_dialog_paths(hwnd, FileDialogType::Open, options)
                 .ok()
-                .map(|os_str| FileInfo {
-                    path: os_str.into(),
-                })
+                .and_then(|paths| FileInfo::from_paths(paths.into_iter().map(Into::into).collect()))
         }
     }
 
@@ -1304,13 +1607,19 @@ impl<'a> WinCtx<'a> for WinCtxImpl<'a> {
     fn save_as_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo> {
         let hwnd = self.handle.get_hwnd()?;
         unsafe {
-            get_file_dialog_path(hwnd, FileDialogType::Save, options)
+            get_file_dialog_paths(hwnd, FileDialogType::Save, options)
                 .ok()
-                .map(|os_str| FileInfo {
-                    path: os_str.into(),
-                })
+                .and_then(|paths| FileInfo::from_paths(paths.into_iter().map(Into::into).collect()))
         }
     }
+
+    /// Show a message dialog, modal to this window.
+    ///
+    /// Blocks until the user dismisses it.
+    fn message_dialog_sync(&mut self, options: MessageDialogOptions) -> bool {
+        let hwnd = self.handle.get_hwnd().unwrap_or(null_mut());
+        unsafe { show_message_dialog(hwnd, options) }
+    }
 }
 
 /// Casts render target to hwnd variant.
@@ -0,0 +1,70 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Information about the monitors attached to the system.
+
+use crate::kurbo::Rect;
+use crate::platform::screen as platform;
+
+/// A monitor attached to the system.
+///
+/// All rects are in display points, in a coordinate space whose origin is
+/// the top left corner of the primary monitor, with y increasing downwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    primary: bool,
+    rect: Rect,
+    work_rect: Rect,
+}
+
+impl Monitor {
+    pub(crate) fn new(primary: bool, rect: Rect, work_rect: Rect) -> Monitor {
+        Monitor {
+            primary,
+            rect,
+            work_rect,
+        }
+    }
+
+    /// Returns `true` if this is the primary monitor.
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    /// The area covered by the monitor.
+    pub fn virtual_rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// The area of the monitor that is available to windows, excluding
+    /// things like the taskbar or the menu bar.
+    pub fn virtual_work_rect(&self) -> Rect {
+        self.work_rect
+    }
+}
+
+/// The monitors attached to the system.
+pub struct Screen;
+
+impl Screen {
+    /// Return the monitors attached to the system.
+    pub fn get_monitors() -> Vec<Monitor> {
+        platform::get_monitors()
+    }
+
+    /// Return the primary monitor, if there is one.
+    pub fn get_primary() -> Option<Monitor> {
+        Screen::get_monitors().into_iter().find(Monitor::is_primary)
+    }
+}
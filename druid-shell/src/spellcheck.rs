@@ -0,0 +1,43 @@
+// Copyright 2018 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The platform's spellchecker, on macOS.
+
+use crate::platform::spellcheck as platform;
+
+/// The spellchecker of the platform, for the languages the user has
+/// chosen.
+///
+/// This is `NSSpellChecker`, and is only available on macOS.
+#[derive(Debug)]
+pub struct SystemSpellChecker(platform::SystemSpellChecker);
+
+impl SystemSpellChecker {
+    /// Get the platform's spellchecker, if it has one.
+    ///
+    /// This must be called, and the spellchecker used, on the main thread.
+    pub fn new() -> Option<SystemSpellChecker> {
+        platform::SystemSpellChecker::new().map(SystemSpellChecker)
+    }
+
+    /// Return `true` if `word` is spelled correctly.
+    pub fn is_correct(&self, word: &str) -> bool {
+        self.0.is_correct(word)
+    }
+
+    /// Return possible replacements for a misspelled `word`, best first.
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        self.0.suggestions(word)
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Changes to the state of the system, such as sleep and power.
+
+use std::cell::RefCell;
+
+use crate::platform::system_event as platform;
+
+/// Where the computer draws its power from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerSource {
+    /// A power adapter, or another external supply.
+    External,
+    /// The computer's battery.
+    Battery,
+}
+
+impl PowerSource {
+    /// The current power source, or `None` if it can't be found.
+    pub fn current() -> Option<PowerSource> {
+        platform::power_source()
+    }
+}
+
+/// A change to the state of the system, outside of the application.
+///
+/// Changes to the network are only reported once
+/// [`Application::monitor_network`] has been called, and are the only
+/// changes reported on Linux.
+///
+/// [`Application::monitor_network`]: struct.Application.html#method.monitor_network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SystemEvent {
+    /// The computer is about to sleep.
+    WillSleep,
+    /// The computer woke from sleep.
+    DidWake,
+    /// The computer switched to another power source.
+    PowerSourceChanged(PowerSource),
+    /// The user's session was locked, such as by the screen saver.
+    SessionLocked,
+    /// The user's session was unlocked.
+    SessionUnlocked,
+    /// The internet became reachable, if this is `true`, or unreachable.
+    NetworkChanged(bool),
+}
+
+/// A function called with changes to the state of the system.
+type SystemEventHandler = Box<dyn FnMut(SystemEvent)>;
+
+#[derive(Default)]
+struct State {
+    handler: Option<SystemEventHandler>,
+    /// The last event reported, other than a change of power source or
+    /// network.
+    last: Option<SystemEvent>,
+    /// The last power source reported.
+    power: Option<PowerSource>,
+    /// Whether the internet was reachable when last reported, once the
+    /// network is monitored.
+    network: Option<bool>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+/// Set the function called with changes to the state of the system.
+pub(crate) fn set_handler(handler: impl FnMut(SystemEvent) + 'static) {
+    let power = PowerSource::current();
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.handler = Some(Box::new(handler));
+        state.power = power;
+    });
+}
+
+/// Whether there is a route to the internet.
+pub(crate) fn network_reachable() -> bool {
+    platform::network_reachable()
+}
+
+/// Start reporting changes to the network, if they aren't already.
+pub(crate) fn monitor_network() {
+    let reachable = platform::network_reachable();
+    let started = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let started = state.network.is_none();
+        if started {
+            state.network = Some(reachable);
+        }
+        started
+    });
+    if started {
+        platform::monitor_network();
+    }
+}
+
+/// Pass a change to the state of the system to the handler.
+///
+/// Platforms that tell every window of a change report it once for each,
+/// and some report changes to the battery's charge as changes of power
+/// source; events that don't change anything since the last are dropped.
+pub(crate) fn system_event(event: SystemEvent) {
+    // The handler is taken out while it runs, so that it can set another.
+    let handler = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        match event {
+            SystemEvent::PowerSourceChanged(source) if state.power == Some(source) => return None,
+            SystemEvent::PowerSourceChanged(source) => state.power = Some(source),
+            SystemEvent::NetworkChanged(reachable) if state.network == Some(reachable) => {
+                return None
+            }
+            SystemEvent::NetworkChanged(reachable) => state.network = Some(reachable),
+            _ if state.last == Some(event) => return None,
+            _ => state.last = Some(event),
+        }
+        state.handler.take()
+    });
+    if let Some(mut handler) = handler {
+        handler(event);
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            if state.handler.is_none() {
+                state.handler = Some(handler);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn repeated_events_are_dropped() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let handler_seen = seen.clone();
+        set_handler(move |event| handler_seen.borrow_mut().push(event));
+        STATE.with(|state| state.borrow_mut().power = Some(PowerSource::External));
+
+        system_event(SystemEvent::WillSleep);
+        system_event(SystemEvent::WillSleep);
+        system_event(SystemEvent::PowerSourceChanged(PowerSource::External));
+        system_event(SystemEvent::PowerSourceChanged(PowerSource::Battery));
+        system_event(SystemEvent::DidWake);
+        system_event(SystemEvent::PowerSourceChanged(PowerSource::Battery));
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                SystemEvent::WillSleep,
+                SystemEvent::PowerSourceChanged(PowerSource::Battery),
+                SystemEvent::DidWake,
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Vertical metrics of fonts.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use log::warn;
+
+use crate::piet;
+use crate::platform::text_metrics as platform;
+
+/// The vertical metrics of a font at a given size, in display points.
+///
+/// Distances are measured from the baseline, and are positive both above
+/// it (ascent, cap height and x-height) and below it (descent).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    /// The distance from the baseline to the top of the tallest glyphs.
+    pub ascent: f64,
+    /// The distance from the baseline to the bottom of the lowest glyphs.
+    pub descent: f64,
+    /// The distance between the baselines of consecutive lines.
+    pub line_height: f64,
+    /// The height of capital letters, such as 'H'.
+    pub cap_height: f64,
+    /// The height of lowercase letters without ascenders, such as 'x'.
+    pub x_height: f64,
+}
+
+impl FontMetrics {
+    /// Typical metrics for a font of `size`, used when the font can't be
+    /// measured.
+    pub fn estimate(size: f64) -> FontMetrics {
+        FontMetrics {
+            ascent: size * 0.9,
+            descent: size * 0.25,
+            line_height: size * 1.2,
+            cap_height: size * 0.7,
+            x_height: size * 0.5,
+        }
+    }
+
+    /// The offset of the baseline from the top of a line, with the space
+    /// between lines shared equally above and below the glyphs.
+    pub fn line_baseline(&self) -> f64 {
+        (self.line_height - self.ascent - self.descent) / 2.0 + self.ascent
+    }
+
+    /// The offset of the baseline from the top of a box of `height` that
+    /// centers capital letters in it.
+    ///
+    /// This looks centered for most text, as descenders are usually
+    /// perceived to hang below the line.
+    pub fn centered_baseline(&self, height: f64) -> f64 {
+        (height + self.cap_height) / 2.0
+    }
+}
+
+/// Measuring fonts, implemented by the text factories given to widgets.
+///
+/// Each backend measures fonts in the same way it draws them, so text
+/// positioned with these metrics lines up alike on every platform.
+pub trait TextMetrics {
+    /// The metrics of the font named `font_name` at `size`.
+    ///
+    /// Fonts that can't be found are estimated from their size, and a
+    /// warning is logged.
+    fn font_metrics(&mut self, font_name: &str, size: f64) -> FontMetrics;
+}
+
+impl<T: piet::Text> TextMetrics for T {
+    fn font_metrics(&mut self, font_name: &str, size: f64) -> FontMetrics {
+        thread_local! {
+            static CACHE: RefCell<HashMap<(String, u64), FontMetrics>> =
+                RefCell::new(HashMap::new());
+        }
+        CACHE.with(|cache| {
+            *cache
+                .borrow_mut()
+                .entry((font_name.to_string(), size.to_bits()))
+                .or_insert_with(|| {
+                    platform::font_metrics(font_name, size).unwrap_or_else(|| {
+                        warn!("failed to measure font {:?}, estimating", font_name);
+                        FontMetrics::estimate(size)
+                    })
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baselines() {
+        let metrics = FontMetrics {
+            ascent: 10.0,
+            descent: 4.0,
+            line_height: 16.0,
+            cap_height: 8.0,
+            x_height: 6.0,
+        };
+        assert_eq!(metrics.line_baseline(), 11.0);
+        assert_eq!(metrics.centered_baseline(20.0), 14.0);
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Options for how text is rasterized.
+
+/// How the edges of glyphs are smoothed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextAntialiasing {
+    /// Smooth edges using the separate red, green and blue parts of each
+    /// pixel. This is sharpest on LCD screens, but only works when text is
+    /// drawn onto an opaque background.
+    Subpixel,
+    /// Smooth edges with shades of the text color.
+    Grayscale,
+    /// Don't smooth edges.
+    Aliased,
+}
+
+/// How much glyph outlines are moved to line up with the pixel grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextHinting {
+    /// Keep the outlines as designed.
+    None,
+    /// Line up outlines vertically only, keeping their shapes.
+    Slight,
+    /// Line up outlines in both directions, for the crispest text.
+    Full,
+}
+
+/// How the text in a window is rasterized.
+///
+/// Settings that are `None` are left to the platform, which usually
+/// follows the user's preferences. Settings a platform can't honor are
+/// ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TextRendering {
+    /// How the edges of glyphs are smoothed.
+    pub antialiasing: Option<TextAntialiasing>,
+    /// How glyphs are lined up with the pixel grid.
+    pub hinting: Option<TextHinting>,
+}
+
+impl TextRendering {
+    /// Builder-style method to set the antialiasing.
+    pub fn antialiasing(mut self, antialiasing: TextAntialiasing) -> Self {
+        self.antialiasing = Some(antialiasing);
+        self
+    }
+
+    /// Builder-style method to set the hinting.
+    pub fn hinting(mut self, hinting: TextHinting) -> Self {
+        self.hinting = Some(hinting);
+        self
+    }
+
+    /// These settings, changed to suit drawing onto a surface that may be
+    /// transparent, such as a composited window or a cached layer.
+    ///
+    /// Subpixel antialiasing blends each color channel with what is behind
+    /// the text, which isn't known yet on such a surface, and leaves colored
+    /// fringes when it is composited later. It is replaced with grayscale,
+    /// as is the platform's choice, which may be subpixel.
+    pub fn without_subpixel(mut self) -> Self {
+        match self.antialiasing {
+            Some(TextAntialiasing::Subpixel) | None => {
+                self.antialiasing = Some(TextAntialiasing::Grayscale)
+            }
+            Some(_) => (),
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transparent_surfaces_lose_subpixel() {
+        let subpixel = TextRendering::default().antialiasing(TextAntialiasing::Subpixel);
+        assert_eq!(
+            subpixel.without_subpixel().antialiasing,
+            Some(TextAntialiasing::Grayscale)
+        );
+        assert_eq!(
+            TextRendering::default().without_subpixel().antialiasing,
+            Some(TextAntialiasing::Grayscale)
+        );
+        let aliased = TextRendering::default()
+            .antialiasing(TextAntialiasing::Aliased)
+            .hinting(TextHinting::Full);
+        assert_eq!(aliased.without_subpixel(), aliased);
+    }
+}
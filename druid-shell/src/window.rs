@@ -18,13 +18,16 @@
 
 use std::any::Any;
 
-use crate::dialog::{FileDialogOptions, FileInfo};
+use crate::color_space::ColorSpace;
+use crate::dialog::{FileDialogOptions, FileInfo, MessageDialogOptions};
 use crate::error::Error;
+use crate::frame::FrameTiming;
 use crate::keyboard::{KeyEvent, KeyModifiers};
 use crate::kurbo::{Point, Size, Vec2};
 use crate::menu::Menu;
 use crate::mouse::{Cursor, MouseEvent};
 use crate::platform::window as platform;
+use crate::text_rendering::TextRendering;
 
 // It's possible we'll want to make this type alias at a lower level,
 // see https://github.com/linebender/piet/pull/37 for more discussion.
@@ -49,6 +52,18 @@ impl TimerToken {
     }
 }
 
+/// How urgently a screen reader should speak an announcement; see
+/// [`WindowHandle::announce`], which is only supported on macOS.
+///
+/// [`WindowHandle::announce`]: struct.WindowHandle.html#method.announce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Politeness {
+    /// Spoken when the screen reader has finished what it is saying.
+    Polite,
+    /// Spoken immediately, interrupting the screen reader.
+    Assertive,
+}
+
 //NOTE: this has a From<platform::Handle> impl for construction
 /// A handle that can enqueue tasks on the window loop.
 #[derive(Clone)]
@@ -97,11 +112,39 @@ impl WindowHandle {
         self.0.invalidate()
     }
 
+    /// Set the size of the window's content area, in display points.
+    ///
+    /// The window's frame and menu bar are added around this size.
+    pub fn set_size(&self, size: Size) {
+        self.0.set_size(size)
+    }
+
+    /// Change how the text in the window is rasterized, and repaint it.
+    pub fn set_text_rendering(&self, text_rendering: TextRendering) {
+        self.0.set_text_rendering(text_rendering)
+    }
+
     /// Set the title for this menu.
     pub fn set_title(&self, title: &str) {
         self.0.set_title(title)
     }
 
+    /// Ask screen readers to speak `text`, without moving focus.
+    ///
+    /// This is for changes a user would otherwise not notice, such as
+    /// "3 results found" or "file saved".
+    ///
+    /// # Platform support
+    ///
+    /// Only macOS makes announcements. On Windows, UI Automation
+    /// notifications need an accessibility provider for the window, and on
+    /// Linux the ATK of GTK 3 has no announcements; there this does nothing,
+    /// and logs a warning the first time. Applications that must reach
+    /// screen readers everywhere should also show the text in a widget.
+    pub fn announce(&self, text: &str, politeness: Politeness) {
+        self.0.announce(text, politeness)
+    }
+
     /// Set the top-level menu for this window.
     pub fn set_menu(&self, menu: Menu) {
         self.0.set_menu(menu.into_inner())
@@ -114,6 +157,16 @@ impl WindowHandle {
         self.0.show_context_menu(menu.into_inner(), pos)
     }
 
+    /// Get the position of the top left corner of the window frame, in the
+    /// coordinate space of [`Screen`].
+    ///
+    /// Returns `None` if the window has been closed.
+    ///
+    /// [`Screen`]: struct.Screen.html
+    pub fn get_position(&self) -> Option<Point> {
+        self.0.get_position()
+    }
+
     /// Get a handle that can be used to schedule an idle task.
     pub fn get_idle_handle(&self) -> Option<IdleHandle> {
         self.0.get_idle_handle().map(IdleHandle)
@@ -128,6 +181,80 @@ impl WindowHandle {
     }
 }
 
+/// The renderer used to paint a window.
+///
+/// This is a hint that is only used on Windows. Windows are always painted
+/// through piet, so it chooses where piet's output goes, not a different
+/// drawing library: `Gpu` paints with a Direct2D hardware render target
+/// into a DXGI swap chain, and `Cpu` with a software render target
+/// presented through the window. If the GPU renderer can't be created, the
+/// window falls back to the CPU renderer.
+///
+/// On macOS and with GTK, windows are always painted with Cairo on the CPU,
+/// whatever is chosen here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderBackend {
+    /// Let the platform choose.
+    Default,
+    /// Paint with the GPU.
+    Gpu,
+    /// Paint with the CPU.
+    Cpu,
+}
+
+impl RenderBackend {
+    /// The renderer to use on this platform.
+    ///
+    /// If this is `Default`, the choice in the `DRUID_RENDER_BACKEND`
+    /// environment variable is used, if there is one. This is always `Cpu`
+    /// on platforms that can only paint on the CPU.
+    pub(crate) fn resolve(self) -> RenderBackend {
+        let requested = self.requested();
+        if cfg!(target_os = "windows") {
+            return requested;
+        }
+        if requested == RenderBackend::Gpu {
+            log::info!("GPU rendering is not available on this platform, using the CPU");
+        }
+        RenderBackend::Cpu
+    }
+
+    /// If this is `Default`, use the choice in the `DRUID_RENDER_BACKEND`
+    /// environment variable, if there is one.
+    fn requested(self) -> RenderBackend {
+        if self != RenderBackend::Default {
+            return self;
+        }
+        match std::env::var("DRUID_RENDER_BACKEND") {
+            Ok(ref s) if s.eq_ignore_ascii_case("gpu") => RenderBackend::Gpu,
+            Ok(ref s) if s.eq_ignore_ascii_case("cpu") => RenderBackend::Cpu,
+            Ok(s) => {
+                log::warn!("unknown DRUID_RENDER_BACKEND '{}', ignoring", s);
+                RenderBackend::Default
+            }
+            Err(_) => RenderBackend::Default,
+        }
+    }
+}
+
+impl Default for RenderBackend {
+    fn default() -> Self {
+        RenderBackend::Default
+    }
+}
+
+/// How a dialog is attached to the window that owns it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DialogStyle {
+    /// A window that stays above its owner, and is minimized and closed
+    /// with it; on Linux, the owner is a hint to the window manager. On
+    /// macOS, where such dialogs are ordinary windows, it is independent.
+    Owned,
+    /// A window-modal dialog, which blocks input to its owner until it
+    /// closes. On macOS it is a sheet, attached to the owner's title bar.
+    Modal,
+}
+
 /// A builder type for creating new windows.
 pub struct WindowBuilder(platform::WindowBuilder);
 
@@ -150,6 +277,25 @@ impl WindowBuilder {
         self.0.set_size(size)
     }
 
+    /// Set the window's initial position.
+    ///
+    /// This is the position of the top left corner of the window frame, in
+    /// the coordinate space of [`Screen`]. If it is not set, the platform
+    /// chooses a position.
+    ///
+    /// [`Screen`]: struct.Screen.html
+    pub fn set_position(&mut self, position: Point) {
+        self.0.set_position(position)
+    }
+
+    /// Make the window a dialog of `parent`.
+    ///
+    /// Sheets are placed by the platform, so the position is ignored for
+    /// modal dialogs on macOS.
+    pub fn set_parent(&mut self, parent: &WindowHandle, style: DialogStyle) {
+        self.0.set_parent(&parent.0, style)
+    }
+
     /// Set the window's initial title.
     pub fn set_title(&mut self, title: impl Into<String>) {
         self.0.set_title(title)
@@ -160,6 +306,44 @@ impl WindowBuilder {
         self.0.set_menu(menu.into_inner())
     }
 
+    /// Set the renderer used to paint the window, on Windows.
+    ///
+    /// The default is [`RenderBackend::Default`]. This can also be
+    /// set with the `DRUID_RENDER_BACKEND` environment variable, to `gpu`
+    /// or `cpu`; an explicit setting takes precedence.
+    ///
+    /// [`RenderBackend::Default`]: enum.RenderBackend.html#variant.Default
+    pub fn set_render_backend(&mut self, backend: RenderBackend) {
+        self.0.set_render_backend(backend)
+    }
+
+    /// Set the color space the window's contents are painted in.
+    ///
+    /// The default is sRGB. Only macOS can tag a window as Display P3;
+    /// elsewhere this setting is ignored, and colors are shown as sRGB.
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.0.set_color_space(color_space)
+    }
+
+    /// Set how the text in the window is rasterized.
+    ///
+    /// The default leaves this to the platform.
+    pub fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        self.0.set_text_rendering(text_rendering)
+    }
+
+    /// Let the platform restore this window's state when the application is
+    /// relaunched, such as whether it was minimized or full screen.
+    ///
+    /// `id` must be unique among the application's windows, and the same
+    /// across launches. The application reopens the window itself, with the
+    /// same id; the platform only restores its state. On macOS this takes
+    /// part in window restoration; with GTK the id is the window's role, for
+    /// session managers. Elsewhere it is ignored.
+    pub fn set_restoration_id(&mut self, id: impl Into<String>) {
+        self.0.set_restoration_id(id.into())
+    }
+
     /// Attempt to construct the platform window.
     ///
     /// If this fails, your application should exit.
@@ -204,6 +388,12 @@ pub trait WinCtx<'a> {
     ///
     /// Blocks while the user picks a file.
     fn save_as_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo>;
+
+    /// Show a modal message dialog.
+    ///
+    /// Blocks until the user answers; returns `true` if they chose OK or
+    /// Yes.
+    fn message_dialog_sync(&mut self, options: MessageDialogOptions) -> bool;
 }
 
 /// App behavior, supplied by the app.
@@ -231,11 +421,30 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn size(&mut self, width: u32, height: u32, ctx: &mut dyn WinCtx) {}
 
+    /// Called before `paint`, with the platform's prediction of when the
+    /// frame about to be painted will be shown.
+    ///
+    /// It isn't called when the compositor doesn't report its timing.
+    #[allow(unused_variables)]
+    fn prepare_frame(&mut self, timing: FrameTiming) {}
+
     /// Request the handler to paint the window contents. Return value
     /// indicates whether window is animating, i.e. whether another paint
-    /// should be scheduled for the next animation frame.
+    /// should be scheduled for the next refresh of the display.
     fn paint(&mut self, piet: &mut piet_common::Piet, ctx: &mut dyn WinCtx) -> bool;
 
+    /// Called before painting, when the window still shows its last frame,
+    /// to ask whether anything has changed since. If this returns `false`
+    /// the frame is skipped and `paint` isn't called, which saves drawing
+    /// the same thing again when the window is asked to redraw for other
+    /// reasons.
+    ///
+    /// Only some platforms keep the last frame; the others always paint.
+    #[allow(unused_variables)]
+    fn needs_paint(&mut self, ctx: &mut dyn WinCtx) -> bool {
+        true
+    }
+
     /// Called when the resources need to be rebuilt.
     ///
     /// Discussion: this function is mostly motivated by using
@@ -299,10 +508,32 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn timer(&mut self, token: TimerToken, ctx: &mut dyn WinCtx) {}
 
+    /// Called when a system appearance setting, such as high contrast
+    /// mode or the color scheme, may have changed; see [`HighContrast`] and
+    /// [`ColorScheme`].
+    ///
+    /// This is not called on macOS, where the settings should be checked
+    /// again when the window gets focus.
+    ///
+    /// [`HighContrast`]: struct.HighContrast.html
+    /// [`ColorScheme`]: enum.ColorScheme.html
+    #[allow(unused_variables)]
+    fn appearance_changed(&mut self, ctx: &mut dyn WinCtx) {}
+
     /// Called when this window becomes the focused window.
     #[allow(unused_variables)]
     fn got_focus(&mut self, ctx: &mut dyn WinCtx) {}
 
+    /// Called when the user closes the window, before it is destroyed, while
+    /// its position can still be read.
+    ///
+    /// This may not be called when the window is closed with
+    /// [`WindowHandle::close`].
+    ///
+    /// [`WindowHandle::close`]: struct.WindowHandle.html#method.close
+    #[allow(unused_variables)]
+    fn will_close(&mut self, ctx: &mut dyn WinCtx) {}
+
     /// Called when the window is being destroyed. Note that this happens
     /// earlier in the sequence than drop (at WM_DESTROY, while the latter is
     /// WM_NCDESTROY).
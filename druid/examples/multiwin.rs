@@ -30,6 +30,8 @@ const MENU_DECREMENT_ACTION: Selector = Selector::new("menu-decrement-action");
 struct State {
     menu_count: usize,
     selected: usize,
+    /// The number of open windows, kept by the delegate.
+    windows: usize,
 }
 
 fn main() {
@@ -66,11 +68,20 @@ fn ui_builder() -> impl Widget<State> {
     let label = Label::new(text);
     let inc_button = Button::<State>::new("Add menu item", |ctx, data, _env| {
         data.menu_count += 1;
-        ctx.set_menu(make_menu::<State>(data));
+        ctx.set_menu(make_menu(data));
     });
     let dec_button = Button::<State>::new("Remove menu item", |ctx, data, _env| {
         data.menu_count = data.menu_count.saturating_sub(1);
-        ctx.set_menu(make_menu::<State>(data));
+        ctx.set_menu(make_menu(data));
+    });
+    let windows = Label::new(|data: &State, _env: &Env| format!("{} windows open", data.windows));
+    let new_button = Button::<State>::new("New window", |ctx, data, _env| {
+        // Every window builds its own root widget, and shares `State`.
+        let new_win = WindowDesc::new(ui_builder).menu(make_menu(data));
+        ctx.submit_command(Command::new(druid::commands::NEW_WINDOW, new_win), None);
+    });
+    let close_button = Button::<State>::new("Close window", |ctx, _data, _env| {
+        ctx.submit_command(druid::commands::CLOSE_WINDOW, None);
     });
 
     let mut col = Flex::column();
@@ -79,6 +90,11 @@ fn ui_builder() -> impl Widget<State> {
     row.add_child(Padding::new(5.0, inc_button), 1.0);
     row.add_child(Padding::new(5.0, dec_button), 1.0);
     col.add_child(row, 1.0);
+    let mut row = Flex::row();
+    row.add_child(Padding::new(5.0, windows), 1.0);
+    row.add_child(Padding::new(5.0, new_button), 1.0);
+    row.add_child(Padding::new(5.0, close_button), 1.0);
+    col.add_child(row, 1.0);
     col
 }
 
@@ -106,24 +122,29 @@ impl AppDelegate<State> for Delegate {
                 None
             }
             Event::Command(ref cmd) if cmd.selector == MENU_COUNT_ACTION => {
+                // The checkmark follows `selected`, without a new menu.
                 data.selected = *cmd.get_object().unwrap();
-                ctx.set_menu(make_menu::<State>(data));
                 None
             }
             // wouldn't it be nice if a menu (like a button) could just mutate state
             // directly if desired?
             Event::Command(ref cmd) if cmd.selector == MENU_INCREMENT_ACTION => {
                 data.menu_count += 1;
-                ctx.set_menu(make_menu::<State>(data));
+                ctx.set_menu(make_menu(data));
                 None
             }
             Event::Command(ref cmd) if cmd.selector == MENU_DECREMENT_ACTION => {
                 data.menu_count = data.menu_count.saturating_sub(1);
-                ctx.set_menu(make_menu::<State>(data));
+                ctx.set_menu(make_menu(data));
                 None
             }
+            Event::Command(ref cmd) if cmd.selector == druid::commands::WINDOW_CLOSED => {
+                let id: &WindowId = cmd.get_object().unwrap();
+                info!("Window closed, id: {:?}", id);
+                Some(event)
+            }
             Event::MouseDown(ref mouse) if mouse.button.is_right() => {
-                let menu = ContextMenu::new(make_context_menu::<State>(), mouse.pos);
+                let menu = ContextMenu::new(make_context_menu(), mouse.window_pos);
                 let cmd = Command::new(druid::commands::SHOW_CONTEXT_MENU, menu);
                 ctx.submit_command(cmd, None);
                 None
@@ -131,28 +152,24 @@ impl AppDelegate<State> for Delegate {
             other => Some(other),
         }
     }
-    fn window_added(
-        &mut self,
-        id: WindowId,
-        _data: &mut State,
-        _env: &Env,
-        _ctx: &mut DelegateCtx,
-    ) {
+    fn window_added(&mut self, id: WindowId, data: &mut State, _env: &Env, _ctx: &mut DelegateCtx) {
+        data.windows += 1;
         info!("Window added, id: {:?}", id);
     }
     fn window_removed(
         &mut self,
         id: WindowId,
-        _data: &mut State,
+        data: &mut State,
         _env: &Env,
         _ctx: &mut DelegateCtx,
     ) {
+        data.windows -= 1;
         info!("Window removed, id: {:?}", id);
     }
 }
 
 #[allow(unused_assignments)]
-fn make_menu<T: Data>(state: &State) -> MenuDesc<T> {
+fn make_menu(state: &State) -> MenuDesc<State> {
     let mut base = MenuDesc::empty();
     #[cfg(target_os = "macos")]
     {
@@ -172,7 +189,7 @@ fn make_menu<T: Data>(state: &State) -> MenuDesc<T> {
                         Command::new(MENU_COUNT_ACTION, i),
                     )
                     .disabled_if(|| i % 3 == 0)
-                    .selected_if(|| i == state.selected)
+                    .selected_when(move |data: &State, _env| data.selected == i)
                 })
             }),
         );
@@ -180,14 +197,14 @@ fn make_menu<T: Data>(state: &State) -> MenuDesc<T> {
     base
 }
 
-fn make_context_menu<T: Data>() -> MenuDesc<T> {
+fn make_context_menu() -> MenuDesc<State> {
     MenuDesc::empty()
         .append(MenuItem::new(
             LocalizedString::new("Increment"),
             MENU_INCREMENT_ACTION,
         ))
-        .append(MenuItem::new(
-            LocalizedString::new("Decrement"),
-            MENU_DECREMENT_ACTION,
-        ))
+        .append(
+            MenuItem::new(LocalizedString::new("Decrement"), MENU_DECREMENT_ACTION)
+                .enabled_when(|data: &State, _env| data.menu_count > 0),
+        )
 }
@@ -56,7 +56,8 @@ fn make_main_menu<T: Data>() -> MenuDesc<T> {
     let edit_menu = MenuDesc::new(LocalizedString::new("common-menu-edit-menu"))
         .append(druid::platform_menus::common::cut())
         .append(druid::platform_menus::common::copy())
-        .append(druid::platform_menus::common::paste());
+        .append(druid::platform_menus::common::paste())
+        .append(druid::platform_menus::common::select_all());
 
     MenuDesc::platform_default()
         .unwrap_or(MenuDesc::empty())
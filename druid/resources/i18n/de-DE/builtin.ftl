@@ -39,3 +39,8 @@ common-menu-copy = Kopieren
 common-menu-paste = Einfügen
 common-menu-undo = Rückgängig
 common-menu-redo = Wiederherstellen
+common-menu-select-all = Alles auswählen
+
+# spelling suggestions
+druid-spelling-no-guesses = Keine Vorschläge gefunden
+druid-spelling-suggestion = { $word }
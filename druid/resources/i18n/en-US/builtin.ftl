@@ -39,3 +39,8 @@ common-menu-copy = Copy
 common-menu-paste = Paste
 common-menu-undo = Undo
 common-menu-redo = Redo
+common-menu-select-all = Select All
+
+# spelling suggestions
+druid-spelling-no-guesses = No Guesses Found
+druid-spelling-suggestion = { $word }
@@ -39,3 +39,8 @@ common-menu-copy = Copier
 common-menu-paste = Coller
 common-menu-undo = Annuler
 common-menu-redo = Rétablir
+common-menu-select-all = Tout sélectionner
+
+# spelling suggestions
+druid-spelling-no-guesses = Aucune suggestion
+druid-spelling-suggestion = { $word }
@@ -0,0 +1,381 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Animating values over time.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::kurbo::{Point, Size, Vec2};
+use crate::piet::Color;
+use crate::{Easing, Event, EventCtx};
+
+/// A value that can be animated, by blending between two of them.
+pub trait Interpolate: Clone {
+    /// The value `t` of the way from `self` to `other`. `t` is usually
+    /// between 0.0 and 1.0, but goes beyond with some easing curves.
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Point {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Point::new(
+            self.x.interpolate(&other.x, t),
+            self.y.interpolate(&other.y, t),
+        )
+    }
+}
+
+impl Interpolate for Vec2 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Vec2::new(
+            self.x.interpolate(&other.x, t),
+            self.y.interpolate(&other.y, t),
+        )
+    }
+}
+
+impl Interpolate for Size {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Size::new(
+            self.width.interpolate(&other.width, t),
+            self.height.interpolate(&other.height, t),
+        )
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let from = self.as_rgba_u32();
+        let to = other.as_rgba_u32();
+        let channel = |shift: u32| {
+            let from = f64::from((from >> shift) & 0xff);
+            let to = f64::from((to >> shift) & 0xff);
+            from.interpolate(&to, t).round().max(0.0).min(255.0) as u8
+        };
+        Color::rgba8(channel(24), channel(16), channel(8), channel(0))
+    }
+}
+
+/// A value moving from one place to another, over a duration.
+///
+/// An animation is advanced by the interval of each [`Event::AnimFrame`],
+/// usually by an [`Animator`] that holds it.
+///
+/// [`Event::AnimFrame`]: enum.Event.html#variant.AnimFrame
+/// [`Animator`]: struct.Animator.html
+#[derive(Debug, Clone)]
+pub struct Animation<V> {
+    from: V,
+    to: V,
+    duration: Duration,
+    easing: Easing,
+    /// The fraction of the duration that has passed.
+    progress: f64,
+}
+
+impl<V: Interpolate> Animation<V> {
+    /// Create an animation from `from` to `to`, that takes `duration`.
+    pub fn new(from: V, to: V, duration: Duration) -> Animation<V> {
+        Animation {
+            from,
+            to,
+            duration,
+            easing: Easing::default(),
+            progress: 0.0,
+        }
+    }
+
+    /// Builder-style method to set the pace of the animation. The default
+    /// is [`Easing::EaseInOut`].
+    ///
+    /// [`Easing::EaseInOut`]: enum.Easing.html#variant.EaseInOut
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// The value at this point of the animation.
+    pub fn value(&self) -> V {
+        self.from
+            .interpolate(&self.to, self.easing.apply(self.progress))
+    }
+
+    /// The value the animation ends at.
+    pub fn target(&self) -> &V {
+        &self.to
+    }
+
+    /// The fraction of the duration that has passed, from 0.0 to 1.0.
+    pub fn progress(&self) -> f64 {
+        self.progress
+    }
+
+    /// Whether the whole duration has passed.
+    pub fn is_finished(&self) -> bool {
+        self.progress >= 1.0
+    }
+
+    /// Advance the animation by `interval` nanoseconds, as given by
+    /// [`Event::AnimFrame`]. Returns `true` if this finished it.
+    ///
+    /// [`Event::AnimFrame`]: enum.Event.html#variant.AnimFrame
+    pub fn advance(&mut self, interval: u64) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        let duration = self.duration.as_secs_f64() * 1e9;
+        self.progress = if duration > 0.0 {
+            (self.progress + interval as f64 / duration).min(1.0)
+        } else {
+            1.0
+        };
+        self.is_finished()
+    }
+
+    /// Start again from the current value, towards `to`, taking the whole
+    /// duration.
+    pub fn retarget(&mut self, to: V) {
+        self.from = self.value();
+        self.to = to;
+        self.progress = 0.0;
+    }
+}
+
+/// Identifies an [`Animation`] held by an [`Animator`].
+///
+/// [`Animation`]: struct.Animation.html
+/// [`Animator`]: struct.Animator.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnimationId(u64);
+
+/// An [`Animation`] of any type of value.
+///
+/// [`Animation`]: struct.Animation.html
+trait AnyAnimation {
+    fn advance(&mut self, interval: u64) -> bool;
+    fn is_finished(&self) -> bool;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<V: Interpolate + 'static> AnyAnimation for Animation<V> {
+    fn advance(&mut self, interval: u64) -> bool {
+        Animation::advance(self, interval)
+    }
+
+    fn is_finished(&self) -> bool {
+        Animation::is_finished(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Runs a widget's animations.
+///
+/// A widget keeps an `Animator`, adds animations to it, and requests an
+/// animation frame. Passing each event to [`event`] then advances the
+/// animations, asks for more frames while any is running, and reports
+/// the animations that finished. Finished animations keep their last
+/// value until they are removed or retargeted.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use druid::kurbo::{Point, Rect, Size};
+/// use druid::piet::{Color, RenderContext};
+/// use druid::{
+///     Animation, AnimationId, Animator, BaseState, BoxConstraints, Env, Event, EventCtx,
+///     LayoutCtx, PaintCtx, UpdateCtx, Widget,
+/// };
+///
+/// struct Glow {
+///     animator: Animator,
+///     color: AnimationId,
+/// }
+///
+/// impl Glow {
+///     fn new() -> Glow {
+///         let mut animator = Animator::new();
+///         let black = Color::rgb8(0, 0, 0);
+///         let color = animator.add(Animation::new(black.clone(), black, Duration::from_millis(150)));
+///         Glow { animator, color }
+///     }
+/// }
+///
+/// impl Widget<()> for Glow {
+///     fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut (), _env: &Env) {
+///         if let Event::HotChanged(hot) = event {
+///             let color = if *hot { Color::rgb8(0xff, 0xc0, 0) } else { Color::rgb8(0, 0, 0) };
+///             self.animator.retarget(self.color, color);
+///             ctx.request_anim_frame();
+///         }
+///         self.animator.event(ctx, event);
+///     }
+///
+///     fn update(&mut self, _ctx: &mut UpdateCtx, _old: Option<&()>, _data: &(), _env: &Env) {}
+///
+///     fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &(), _env: &Env) -> Size {
+///         bc.constrain(Size::new(40.0, 40.0))
+///     }
+///
+///     fn paint(&mut self, ctx: &mut PaintCtx, base_state: &BaseState, _data: &(), _env: &Env) {
+///         let color: Color = self.animator.value(self.color).unwrap();
+///         let rect = Rect::from_origin_size(Point::ORIGIN, base_state.size());
+///         ctx.fill(rect, &color);
+///     }
+/// }
+/// ```
+///
+/// [`event`]: #method.event
+#[derive(Default)]
+pub struct Animator {
+    animations: HashMap<AnimationId, Box<dyn AnyAnimation>>,
+    next_id: u64,
+}
+
+impl Animator {
+    /// Create an animator with no animations.
+    pub fn new() -> Animator {
+        Animator::default()
+    }
+
+    /// Add an animation, which runs from the next animation frame.
+    pub fn add<V: Interpolate + 'static>(&mut self, animation: Animation<V>) -> AnimationId {
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        self.animations.insert(id, Box::new(animation));
+        id
+    }
+
+    /// Remove an animation.
+    pub fn remove(&mut self, id: AnimationId) {
+        self.animations.remove(&id);
+    }
+
+    /// The animation with the given id, if it animates values of type `V`.
+    pub fn get<V: Interpolate + 'static>(&self, id: AnimationId) -> Option<&Animation<V>> {
+        self.animations.get(&id)?.as_any().downcast_ref()
+    }
+
+    /// The animation with the given id, if it animates values of type `V`.
+    pub fn get_mut<V: Interpolate + 'static>(
+        &mut self,
+        id: AnimationId,
+    ) -> Option<&mut Animation<V>> {
+        self.animations.get_mut(&id)?.as_any_mut().downcast_mut()
+    }
+
+    /// The current value of an animation of values of type `V`.
+    pub fn value<V: Interpolate + 'static>(&self, id: AnimationId) -> Option<V> {
+        self.get(id).map(Animation::value)
+    }
+
+    /// Send an animation from its current value towards `to`. See
+    /// [`Animation::retarget`].
+    ///
+    /// [`Animation::retarget`]: struct.Animation.html#method.retarget
+    pub fn retarget<V: Interpolate + 'static>(&mut self, id: AnimationId, to: V) {
+        if let Some(animation) = self.get_mut(id) {
+            animation.retarget(to);
+        }
+    }
+
+    /// Whether any animation is still running.
+    pub fn is_running(&self) -> bool {
+        self.animations.values().any(|anim| !anim.is_finished())
+    }
+
+    /// Advance the animations by `interval` nanoseconds, returning the ids
+    /// of those that finished.
+    pub fn advance(&mut self, interval: u64) -> Vec<AnimationId> {
+        self.animations
+            .iter_mut()
+            .filter_map(|(id, anim)| {
+                if anim.advance(interval) {
+                    Some(*id)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Handle an event: on an [`Event::AnimFrame`], advance the
+    /// animations, invalidate, and request another frame if any is still
+    /// running. Returns the ids of the animations that finished.
+    ///
+    /// The returned ids are the only notice that an animation finished;
+    /// nothing else in the widget tree is told. A widget that does
+    /// something when an animation ends, like removing a child it was
+    /// fading out, must act on them here.
+    ///
+    /// [`Event::AnimFrame`]: enum.Event.html#variant.AnimFrame
+    pub fn event(&mut self, ctx: &mut EventCtx, event: &Event) -> Vec<AnimationId> {
+        let interval = match event {
+            Event::AnimFrame(interval) => *interval,
+            _ => return Vec::new(),
+        };
+        if self.animations.is_empty() {
+            return Vec::new();
+        }
+        let finished = self.advance(interval);
+        if self.is_running() {
+            ctx.request_anim_frame();
+        }
+        ctx.invalidate();
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animator_runs_to_completion() {
+        let second = Duration::from_secs(1);
+        let mut animator = Animator::new();
+        let x = animator.add(Animation::new(0.0, 10.0, second).easing(Easing::Linear));
+        let origin = Point::new(0.0, 0.0);
+        let point = animator.add(Animation::new(origin, Point::new(4.0, 8.0), second * 2));
+
+        assert!(animator.advance(500_000_000).is_empty());
+        assert_eq!(animator.value(x), Some(5.0));
+        assert_eq!(animator.value::<Point>(x), None);
+        assert_eq!(animator.advance(500_000_000), vec![x]);
+        assert!(animator.is_running());
+        assert_eq!(animator.advance(1_000_000_000), vec![point]);
+        assert!(!animator.is_running());
+        assert_eq!(animator.value(point), Some(Point::new(4.0, 8.0)));
+
+        animator.retarget(x, 20.0);
+        assert!(animator.is_running());
+        assert_eq!(animator.value(x), Some(10.0));
+    }
+}
@@ -15,41 +15,131 @@
 //! Window building and app lifecycle.
 
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 
-use crate::kurbo::Size;
-use crate::shell::{Application, Error as PlatformError, RunLoop, WindowBuilder, WindowHandle};
+use crate::crash::{self, CrashCallback};
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::session::{Session, SessionStateFn};
+use crate::shell::{
+    Application, ColorSpace, DialogStyle, Error as PlatformError, RenderBackend, RunLoop, Screen,
+    SystemEvent, TextRendering, WindowBuilder, WindowHandle,
+};
+use crate::spawn::Spawner;
+use crate::theme::Density;
 use crate::win_handler::AppState;
-use crate::window::{Window, WindowId};
-use crate::{theme, AppDelegate, Data, DruidHandler, Env, LocalizedString, MenuDesc, Widget};
+use crate::window::{PendingWindow, RootBuilderFn, WindowId};
+use crate::{
+    commands, AppDelegate, ColorScheme, Command, CommandLine, CrashReport, Data, DruidHandler, Env,
+    Executor, ExtEventSink, HotKey, Lens, LensWrap, LocalizedString, MenuDesc, SessionWindow,
+    Store, Target, Widget,
+};
 
-/// A function that modifies the initial environment.
-type EnvSetupFn = dyn FnOnce(&mut Env);
+/// A function that modifies the theme.
+pub(crate) type EnvSetupFn = dyn Fn(&mut Env);
+
+/// A function that makes the description of a window saved in a session.
+type RestoreFn<T> = dyn Fn(&SessionWindow, &mut T) -> Option<WindowDesc<T>>;
 
 /// Handles initial setup of an application, and starts the runloop.
 pub struct AppLauncher<T> {
     windows: Vec<WindowDesc<T>>,
     env_setup: Option<Box<EnvSetupFn>>,
     delegate: Option<Box<dyn AppDelegate<T>>>,
+    ext_event_sink: ExtEventSink,
+    executor: Option<Arc<dyn Executor>>,
+    store_file: Option<PathBuf>,
+    /// Commands sent once the first window is open.
+    launch_commands: Vec<(Target, Command)>,
+    /// Shortcuts that work in every window.
+    hotkeys: Vec<(HotKey, Command)>,
+    color_scheme: Option<ColorScheme>,
+    density: Density,
+    crash_report_dir: Option<PathBuf>,
+    crash_callback: Option<Box<CrashCallback>>,
+    /// How the windows saved in the session are reopened, if the session
+    /// is kept.
+    session: Option<Box<RestoreFn<T>>>,
+    monitor_network: bool,
+}
+
+/// Where a new window is placed on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowPlacement {
+    /// Let the platform choose.
+    Default,
+    /// Centered in the work area of the primary monitor.
+    CenteredOnPrimary,
+    /// Centered on another window, typically the one that opened this one.
+    CenteredOnParent(WindowId),
+    /// At a position relative to the top left of the work area of a
+    /// monitor. The monitor is an index into [`Screen::get_monitors`].
+    ///
+    /// [`Screen::get_monitors`]: struct.Screen.html#method.get_monitors
+    OnMonitor { monitor: usize, position: Point },
+    /// Where the last window with this name was when it was closed. The
+    /// position is kept in the application's [`Store`], so with a
+    /// [`store_file`] it is also remembered across launches. The first such
+    /// window is centered on the primary monitor.
+    ///
+    /// [`Store`]: struct.Store.html
+    /// [`store_file`]: struct.AppLauncher.html#method.store_file
+    RememberLast(String),
 }
 
-/// A function that can create a widget.
-type WidgetBuilderFn<T> = dyn Fn() -> Box<dyn Widget<T>> + 'static;
+/// How the size of a new window is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowSizePolicy {
+    /// The size set with [`WindowDesc::window_size`], or the platform's
+    /// default.
+    ///
+    /// [`WindowDesc::window_size`]: struct.WindowDesc.html#method.window_size
+    Default,
+    /// The size the root widget asks for, measured with a layout pass
+    /// when the window is created, and applied once the platform has
+    /// finished setting the window up. The window is kept within the work
+    /// area of its monitor; content that asks for unbounded space, such as
+    /// flex children, is given all of it.
+    SizeToContent,
+}
 
 /// A description of a window to be instantiated.
 ///
 /// This includes a function that can build the root widget, as well as other
 /// window properties such as the title.
+///
+/// The root widget is built when the platform window is created, so a
+/// `WindowDesc` can be made before the application launches, and the same
+/// description can be used to open any number of windows, for instance as
+/// the argument of the [`NEW_WINDOW`] command.
+///
+/// [`NEW_WINDOW`]: commands/constant.NEW_WINDOW.html
 pub struct WindowDesc<T> {
-    pub(crate) root_builder: Arc<WidgetBuilderFn<T>>,
+    pub(crate) root_builder: Arc<RootBuilderFn<T>>,
+    /// Builders for the layers above the root widget, from the bottom up.
+    pub(crate) layer_builders: Vec<Arc<RootBuilderFn<T>>>,
     pub(crate) title: Option<LocalizedString<T>>,
     pub(crate) size: Option<Size>,
+    pub(crate) size_policy: WindowSizePolicy,
     pub(crate) menu: Option<MenuDesc<T>>,
+    pub(crate) coalesce_input: bool,
+    pub(crate) placement: WindowPlacement,
+    /// The window this one is a dialog of, and how it is attached.
+    pub(crate) parent: Option<(WindowId, DialogStyle)>,
+    pub(crate) render_backend: RenderBackend,
+    pub(crate) color_space: ColorSpace,
+    pub(crate) text_rendering: TextRendering,
+    /// The kind of window and its state, if it is saved in the session.
+    pub(crate) session: Option<(String, Rc<SessionStateFn<T>>)>,
+    /// The id the platform restores this window's state by, if it was
+    /// reopened from the session.
+    pub(crate) restoration_id: Option<String>,
     /// The `WindowId` that will be assigned to this window.
     ///
     /// This can be used to track a window from when it is launched and when
-    /// it actually connects.
+    /// it actually connects. If a `WindowDesc` is used to open more than one
+    /// window, only the first is given this id; the others get new ids.
     pub id: WindowId,
 }
 
@@ -60,18 +150,69 @@ impl<T: Data + 'static> AppLauncher<T> {
             windows: vec![window],
             env_setup: None,
             delegate: None,
+            ext_event_sink: ExtEventSink::new(),
+            executor: None,
+            store_file: None,
+            launch_commands: Vec::new(),
+            hotkeys: Vec::new(),
+            color_scheme: Some(ColorScheme::Dark),
+            density: Density::Normal,
+            crash_report_dir: None,
+            crash_callback: None,
+            session: None,
+            monitor_network: false,
         }
     }
 
     /// Provide an optional closure that will be given mutable access to
     /// the environment before launch.
     ///
-    /// This can be used to set or override theme values.
+    /// This can be used to set or override theme values. The closure is
+    /// called again whenever the theme is rebuilt, when its color scheme
+    /// changes; it can check [`theme::DARK_MODE`] to adapt its colors.
+    ///
+    /// [`theme::DARK_MODE`]: theme/constant.DARK_MODE.html
     pub fn configure_env(mut self, f: impl Fn(&mut Env) + 'static) -> Self {
         self.env_setup = Some(Box::new(f));
         self
     }
 
+    /// Choose the color scheme of the theme, or `None` to follow the
+    /// system's, switching when it does.
+    ///
+    /// The theme is dark by default. The scheme can be changed while the
+    /// application runs by submitting a [`SET_COLOR_SCHEME`] command.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use druid::widget::Label;
+    /// use druid::{AppLauncher, WindowDesc};
+    ///
+    /// let window = WindowDesc::new(|| Label::new("Follows the system"));
+    /// AppLauncher::with_window(window)
+    ///     .color_scheme(None)
+    ///     .launch(0u32)
+    ///     .expect("launch failed");
+    /// ```
+    ///
+    /// [`SET_COLOR_SCHEME`]: commands/constant.SET_COLOR_SCHEME.html
+    pub fn color_scheme(mut self, scheme: Option<ColorScheme>) -> Self {
+        self.color_scheme = scheme;
+        self
+    }
+
+    /// Choose how tightly widgets are packed.
+    ///
+    /// The density is normal by default. It can be changed while the
+    /// application runs by submitting a [`SET_DENSITY`] command.
+    ///
+    /// [`SET_DENSITY`]: commands/constant.SET_DENSITY.html
+    pub fn density(mut self, density: Density) -> Self {
+        self.density = density;
+        self
+    }
+
     /// Set the [`AppDelegate`].
     ///
     /// [`AppDelegate`]: struct.AppDelegate.html
@@ -80,6 +221,234 @@ impl<T: Data + 'static> AppLauncher<T> {
         self
     }
 
+    /// Returns an [`ExtEventSink`] that can be moved between threads,
+    /// and can be used to submit commands back to the application.
+    ///
+    /// [`ExtEventSink`]: struct.ExtEventSink.html
+    pub fn get_external_handle(&self) -> ExtEventSink {
+        self.ext_event_sink.clone()
+    }
+
+    /// Set the [`Executor`] that runs futures spawned with
+    /// [`EventCtx::spawn_future`].
+    ///
+    /// By default each future is run on a thread of its own.
+    ///
+    /// [`Executor`]: trait.Executor.html
+    /// [`EventCtx::spawn_future`]: struct.EventCtx.html#method.spawn_future
+    pub fn executor(mut self, executor: impl Executor + 'static) -> Self {
+        self.executor = Some(Arc::new(executor));
+        self
+    }
+
+    /// Keep the application's [`Store`] in a file, so that the values saved
+    /// in it, such as the settings of a [`Settings`] pane, survive a restart.
+    ///
+    /// The file is read at launch, if it exists. It is written shortly after
+    /// the store changes, once pending input has been handled, and when a
+    /// window closes or the application quits.
+    ///
+    /// [`Store`]: struct.Store.html
+    /// [`Settings`]: widget/struct.Settings.html
+    pub fn store_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.store_file = Some(path.into());
+        self
+    }
+
+    /// Keep the application's session, so that the windows open when it
+    /// quits are reopened at the next launch.
+    ///
+    /// The session is saved in the application's [`Store`], as windows open,
+    /// close and change, so this needs a [`store_file`]. Only windows made
+    /// [`restorable`] are saved, with their frames and the state the
+    /// application gives them. At launch, `restore` is called for
+    /// each saved window, to make its description from its kind and state;
+    /// it may also update the data, and returns `None` to leave the window
+    /// out. If no window is restored, the launcher's own windows are opened
+    /// instead, as they are when the user has asked macOS to close windows
+    /// when quitting an app.
+    ///
+    /// Closing the last window keeps it in the session. On platforms that
+    /// restore windows themselves, such as macOS, the platform then restores
+    /// the reopened windows' own state, such as whether they were full
+    /// screen.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use druid::widget::Label;
+    /// use druid::{AppLauncher, WindowDesc};
+    ///
+    /// fn editor(path: String) -> WindowDesc<u32> {
+    ///     let title = path.clone();
+    ///     WindowDesc::new(move || Label::new(title.clone()))
+    ///         .restorable("editor", move |_data: &u32| path.clone())
+    /// }
+    ///
+    /// AppLauncher::with_window(editor("untitled.txt".into()))
+    ///     .store_file("store.json")
+    ///     .restore_session(|window, _data| match window.kind.as_str() {
+    ///         "editor" => Some(editor(window.state.clone())),
+    ///         _ => None,
+    ///     })
+    ///     .launch(0)
+    ///     .expect("launch failed");
+    /// ```
+    ///
+    /// [`Store`]: struct.Store.html
+    /// [`store_file`]: #method.store_file
+    /// [`restorable`]: struct.WindowDesc.html#method.restorable
+    pub fn restore_session(
+        mut self,
+        restore: impl Fn(&SessionWindow, &mut T) -> Option<WindowDesc<T>> + 'static,
+    ) -> Self {
+        self.session = Some(Box::new(restore));
+        self
+    }
+
+    /// Send a command once the first window is open, such as one for the
+    /// command line arguments.
+    ///
+    /// Launch commands go to the [`AppDelegate`] first, like any other, and
+    /// are sent in the order they were added, after the first window has
+    /// been connected. If no `target` is given, the command is sent to
+    /// every window.
+    ///
+    /// # Examples
+    ///
+    /// Opening the files named on the command line, as file associations do
+    /// on Windows and Linux:
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    /// use druid::widget::Label;
+    /// use druid::{commands, AppLauncher, Command, WindowDesc};
+    ///
+    /// let paths: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+    /// let window = WindowDesc::new(|| Label::new("Editor"));
+    /// let mut launcher = AppLauncher::with_window(window);
+    /// if !paths.is_empty() {
+    ///     launcher = launcher.launch_command(Command::new(commands::OPEN_FILES, paths), None);
+    /// }
+    /// launcher.launch(0u32).expect("launch failed");
+    /// ```
+    ///
+    /// [`AppDelegate`]: trait.AppDelegate.html
+    pub fn launch_command(
+        mut self,
+        command: impl Into<Command>,
+        target: impl Into<Option<Target>>,
+    ) -> Self {
+        let target = target.into().unwrap_or(Target::Global);
+        self.launch_commands.push((target, command.into()));
+        self
+    }
+
+    /// Builder-style method to send the arguments the application was
+    /// started with, once the first window is open.
+    ///
+    /// A [`COMMAND_LINE`] command with the arguments is sent first, and then,
+    /// if any files were named, an [`OPEN_FILES`] command with their paths,
+    /// which is how file associations open documents on Windows and Linux.
+    /// Both go to every window, after the [`AppDelegate`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use druid::widget::Label;
+    /// use druid::{AppLauncher, CommandLine, WindowDesc};
+    ///
+    /// let window = WindowDesc::new(|| Label::new("Editor"));
+    /// AppLauncher::with_window(window)
+    ///     .command_line(CommandLine::from_env())
+    ///     .launch(0u32)
+    ///     .expect("launch failed");
+    /// ```
+    ///
+    /// [`COMMAND_LINE`]: commands/constant.COMMAND_LINE.html
+    /// [`OPEN_FILES`]: commands/constant.OPEN_FILES.html
+    /// [`AppDelegate`]: trait.AppDelegate.html
+    pub fn command_line(self, args: CommandLine) -> Self {
+        let files = args.files.clone();
+        let launcher = self.launch_command(Command::new(commands::COMMAND_LINE, args), None);
+        if files.is_empty() {
+            launcher
+        } else {
+            launcher.launch_command(Command::new(commands::OPEN_FILES, files), None)
+        }
+    }
+
+    /// Add a keyboard shortcut that sends `command` in every window, even
+    /// when no widget has focus.
+    ///
+    /// The command is sent to the window the key was pressed in. Shortcuts
+    /// with Ctrl, Alt or Command are matched before the key reaches the
+    /// widgets; the others only if no widget handled the key, so typing in
+    /// a text box doesn't trigger them. As with menu items, use [`SysMods`]
+    /// for shortcuts that use Command on macOS and Ctrl elsewhere. If
+    /// several shortcuts match, the first one added wins.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use druid::widget::Label;
+    /// use druid::{AppLauncher, HotKey, Selector, SysMods, WindowDesc};
+    ///
+    /// const NEW_DOCUMENT: Selector = Selector::new("my-app.new-document");
+    ///
+    /// let window = WindowDesc::new(|| Label::new("Editor"));
+    /// AppLauncher::with_window(window)
+    ///     .hotkey(HotKey::new(SysMods::Cmd, "n"), NEW_DOCUMENT)
+    ///     .launch(0u32)
+    ///     .expect("launch failed");
+    /// ```
+    ///
+    /// [`SysMods`]: enum.SysMods.html
+    pub fn hotkey(mut self, hotkey: HotKey, command: impl Into<Command>) -> Self {
+        self.hotkeys.push((hotkey, command.into()));
+        self
+    }
+
+    /// Write a [`CrashReport`] to a file in `dir` if the application panics.
+    ///
+    /// Reports have the panic message and backtrace, the widgets being
+    /// processed, the root widget of each window, and the most recent events
+    /// and commands. The directory is created if needed.
+    ///
+    /// [`CrashReport`]: struct.CrashReport.html
+    pub fn crash_report_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.crash_report_dir = Some(dir.into());
+        self
+    }
+
+    /// Call a function with a [`CrashReport`] if the application panics, for
+    /// instance to offer to send it.
+    ///
+    /// The function is called after the report is written, if a
+    /// [`crash_report_dir`] is set, and before the panic unwinds or aborts;
+    /// it should not panic itself.
+    ///
+    /// [`CrashReport`]: struct.CrashReport.html
+    /// [`crash_report_dir`]: #method.crash_report_dir
+    pub fn on_crash(mut self, f: impl Fn(&CrashReport) + Send + Sync + 'static) -> Self {
+        self.crash_callback = Some(Box::new(f));
+        self
+    }
+
+    /// Send a [`NETWORK_CHANGED`] command whenever the internet becomes
+    /// reachable or unreachable, so that the application can switch to
+    /// working offline and back.
+    ///
+    /// Whether the internet is reachable at launch can be found with
+    /// [`Application::network_reachable`].
+    ///
+    /// [`NETWORK_CHANGED`]: commands/constant.NETWORK_CHANGED.html
+    /// [`Application::network_reachable`]: struct.Application.html#method.network_reachable
+    pub fn monitor_network(mut self) -> Self {
+        self.monitor_network = true;
+        self
+    }
+
     /// Initialize a minimal logger for printing logs out to stderr.
     ///
     /// Meant for use during development only.
@@ -93,16 +462,67 @@ impl<T: Data + 'static> AppLauncher<T> {
     /// Returns an error if a window cannot be instantiated. This is usually
     /// a fatal error.
     pub fn launch(mut self, data: T) -> Result<(), PlatformError> {
+        if self.crash_report_dir.is_some() || self.crash_callback.is_some() {
+            crash::install(self.crash_report_dir.take(), self.crash_callback.take());
+        }
         Application::init();
         let mut main_loop = RunLoop::new();
-        let mut env = theme::init();
-        if let Some(f) = self.env_setup.take() {
-            f(&mut env);
+
+        // Files opened before the first window are delivered once it connects.
+        let sink = self.ext_event_sink.clone();
+        Application::set_open_files_handler(move |paths| {
+            sink.submit_command(commands::OPEN_FILES, paths, Target::Global)
+        });
+        let sink = self.ext_event_sink.clone();
+        Application::set_system_event_handler(move |event| match event {
+            SystemEvent::WillSleep => {
+                sink.submit_selector(commands::SYSTEM_WILL_SLEEP, Target::Global)
+            }
+            SystemEvent::DidWake => sink.submit_selector(commands::SYSTEM_DID_WAKE, Target::Global),
+            SystemEvent::PowerSourceChanged(source) => {
+                sink.submit_command(commands::POWER_SOURCE_CHANGED, source, Target::Global)
+            }
+            SystemEvent::SessionLocked => {
+                sink.submit_selector(commands::SESSION_LOCKED, Target::Global)
+            }
+            SystemEvent::SessionUnlocked => {
+                sink.submit_selector(commands::SESSION_UNLOCKED, Target::Global)
+            }
+            SystemEvent::NetworkChanged(reachable) => {
+                sink.submit_command(commands::NETWORK_CHANGED, reachable, Target::Global)
+            }
+        });
+        if self.monitor_network {
+            Application::monitor_network();
         }
 
-        let state = AppState::new(data, env, self.delegate.take());
+        let spawner = Spawner::new(self.executor.take(), self.ext_event_sink.clone());
+        let state = AppState::new(
+            data,
+            self.env_setup.take(),
+            self.color_scheme,
+            self.density,
+            self.delegate.take(),
+            self.ext_event_sink,
+            spawner,
+        );
+        if let Some(path) = self.store_file {
+            state.borrow_mut().load_store(path);
+        }
+        state.borrow_mut().launch_commands = self.launch_commands;
+        state.borrow_mut().hotkeys = self.hotkeys;
 
-        for desc in self.windows {
+        let mut windows = Vec::new();
+        if let Some(restore) = self.session {
+            let mut state = state.borrow_mut();
+            let state = &mut *state;
+            windows = restore_session(&state.store, &mut state.data, restore);
+            state.keeps_session = true;
+        }
+        if windows.is_empty() {
+            windows = self.windows;
+        }
+        for desc in windows {
             let window = desc.build_native(&state)?;
             window.show();
         }
@@ -123,19 +543,87 @@ impl<T: Data + 'static> WindowDesc<T> {
     where
         W: Widget<T> + 'static,
         F: Fn() -> W + 'static,
+    {
+        WindowDesc::new_with_handle(move |_| root())
+    }
+
+    /// Create a new `WindowDesc`, taking a function that will generate the
+    /// root [`Widget`] given the handle of the new window.
+    ///
+    /// The function is called on the UI thread once the platform window
+    /// exists, so it can use the handle to adapt to the window, for instance
+    /// to its DPI.
+    ///
+    /// [`Widget`]: trait.Widget.html
+    pub fn new_with_handle<W, F>(root: F) -> WindowDesc<T>
+    where
+        W: Widget<T> + 'static,
+        F: Fn(&WindowHandle) -> W + 'static,
     {
         // wrap this closure in another closure that dyns the result
         // this just makes our API slightly cleaner; callers don't need to explicitly box.
-        let root_builder: Arc<WidgetBuilderFn<T>> = Arc::new(move || Box::new(root()));
+        let root_builder: Arc<RootBuilderFn<T>> = Arc::new(move |handle| Box::new(root(handle)));
         WindowDesc {
             root_builder,
+            layer_builders: Vec::new(),
             title: None,
             size: None,
+            size_policy: WindowSizePolicy::Default,
             menu: MenuDesc::platform_default(),
+            coalesce_input: false,
+            placement: WindowPlacement::Default,
+            parent: None,
+            session: None,
+            restoration_id: None,
+            render_backend: RenderBackend::Default,
+            color_space: ColorSpace::Srgb,
+            text_rendering: TextRendering::default(),
             id: WindowId::next(),
         }
     }
 
+    /// Add a layer above the window's content, with a function that builds
+    /// its root widget.
+    ///
+    /// Layers are stacked in the order they are added, each covering the
+    /// whole window, and see the data through their own `lens`. They are
+    /// for things that sit above an application's content, such as
+    /// overlays and debugging displays, without every window having to
+    /// stack them itself. Mouse events reach the top layer first, and only
+    /// reach the layers below if it doesn't handle them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::{Label, WidgetExt};
+    /// use druid::{Data, Env, Lens, WindowDesc};
+    ///
+    /// #[derive(Clone, Data, Lens)]
+    /// struct AppState {
+    ///     document: String,
+    ///     frame_count: u64,
+    /// }
+    ///
+    /// let window = WindowDesc::new(|| {
+    ///     Label::new(|doc: &String, _env: &Env| doc.clone()).lens(AppState::document)
+    /// })
+    /// .layer(AppState::frame_count, || {
+    ///     Label::new(|count: &u64, _env: &Env| format!("{} frames", count)).align_start()
+    /// });
+    /// ```
+    pub fn layer<U, L, W, F>(mut self, lens: L, layer: F) -> Self
+    where
+        U: Data,
+        L: Lens<T, U> + Clone + 'static,
+        W: Widget<U> + 'static,
+        F: Fn() -> W + 'static,
+    {
+        let builder: Arc<RootBuilderFn<T>> =
+            Arc::new(move |_| Box::new(LensWrap::new(layer(), lens.clone())));
+        self.layer_builders.push(builder);
+        self
+    }
+
     /// Set the title for this window. This is a [`LocalizedString`] that will
     /// be kept up to date as the application's state changes.
     ///
@@ -156,11 +644,166 @@ impl<T: Data + 'static> WindowDesc<T> {
         self
     }
 
+    /// Set how the window's size is chosen when it opens.
+    ///
+    /// With [`WindowSizePolicy::SizeToContent`], the window fits its
+    /// content, so dialogs don't need sizes in pixels; it is given the size
+    /// set with [`window_size`] until the content is measured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::{Flex, Label};
+    /// use druid::{WindowDesc, WindowSizePolicy};
+    ///
+    /// let dialog = WindowDesc::new(|| {
+    ///     Flex::column()
+    ///         .with_child(Label::<u32>::new("Delete this file?"), 0.0)
+    ///         .with_child(Label::new("This can't be undone."), 0.0)
+    /// })
+    /// .window_size_policy(WindowSizePolicy::SizeToContent);
+    /// ```
+    ///
+    /// [`WindowSizePolicy::SizeToContent`]: enum.WindowSizePolicy.html#variant.SizeToContent
+    /// [`window_size`]: #method.window_size
+    pub fn window_size_policy(mut self, policy: WindowSizePolicy) -> Self {
+        self.size_policy = policy;
+        self
+    }
+
+    /// Set where the window is placed when it opens.
+    ///
+    /// If the placement can't be resolved (for instance, if the parent window
+    /// has closed), the platform chooses the position.
+    pub fn placement(mut self, placement: WindowPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Open this window as a dialog of the window `parent`.
+    ///
+    /// The dialog is attached the way the platform's own dialogs are: a
+    /// modal dialog is a sheet on macOS, an owned window that disables its
+    /// owner on Windows, and a modal transient window on Linux. Unless
+    /// another placement is set, it is centered on its parent. If the
+    /// parent has closed, the window opens on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::Label;
+    /// use druid::{DialogStyle, WindowDesc, WindowId};
+    ///
+    /// let main = WindowDesc::new(|| Label::<u32>::new("Document"));
+    /// let confirm = WindowDesc::new(|| Label::<u32>::new("Save changes?"))
+    ///     .parent_window(main.id, DialogStyle::Modal);
+    /// ```
+    pub fn parent_window(mut self, parent: WindowId, style: DialogStyle) -> Self {
+        self.parent = Some((parent, style));
+        self
+    }
+
+    /// Save this window in the session, as a window of `kind` with the state
+    /// returned by `state`, so that it is reopened at the next launch.
+    ///
+    /// The state is whatever the application needs to make the window
+    /// again, such as the path of the document it shows. See
+    /// [`AppLauncher::restore_session`].
+    ///
+    /// [`AppLauncher::restore_session`]: struct.AppLauncher.html#method.restore_session
+    pub fn restorable(
+        mut self,
+        kind: impl Into<String>,
+        state: impl Fn(&T) -> String + 'static,
+    ) -> Self {
+        self.session = Some((kind.into(), Rc::new(state)));
+        self
+    }
+
+    /// Set the renderer used to paint this window.
+    ///
+    /// This is a hint that is only used on Windows; on other platforms,
+    /// windows are always painted on the CPU. By default the platform
+    /// chooses. If the GPU is requested but can't be used, the window is
+    /// painted on the CPU instead. See [`RenderBackend`].
+    ///
+    /// [`RenderBackend`]: enum.RenderBackend.html
+    pub fn render_backend(mut self, backend: RenderBackend) -> Self {
+        self.render_backend = backend;
+        self
+    }
+
+    /// Set the color space the window is painted in, for wide-gamut
+    /// displays.
+    ///
+    /// Colors painted in a Display P3 window are taken to be in Display P3.
+    /// The theme's colors are converted to it, as are images shown with
+    /// [`Image`]; other sRGB colors a widget paints should be converted with
+    /// [`ColorSpace::convert`], using [`PaintCtx::color_space`]. Only macOS
+    /// supports Display P3 windows; elsewhere this setting is ignored, and
+    /// windows are sRGB.
+    ///
+    /// [`Image`]: widget/struct.Image.html
+    /// [`ColorSpace::convert`]: enum.ColorSpace.html#method.convert
+    /// [`PaintCtx::color_space`]: struct.PaintCtx.html#method.color_space
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Set how text in this window is rasterized.
+    ///
+    /// By default the platform chooses, usually following the user's
+    /// preferences. Subpixel antialiasing is only used where the window is
+    /// opaque. It can be changed later with
+    /// [`WindowHandle::set_text_rendering`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::Label;
+    /// use druid::{TextAntialiasing, TextRendering, WindowDesc};
+    ///
+    /// let rendering = TextRendering::default().antialiasing(TextAntialiasing::Grayscale);
+    /// let window = WindowDesc::new(|| Label::<u32>::new("Hello")).text_rendering(rendering);
+    /// ```
+    ///
+    /// [`WindowHandle::set_text_rendering`]: struct.WindowHandle.html#method.set_text_rendering
+    pub fn text_rendering(mut self, text_rendering: TextRendering) -> Self {
+        self.text_rendering = text_rendering;
+        self
+    }
+
+    /// The position of the top left of the window, for its placement.
+    fn initial_position(&self, state: &AppState<T>) -> Option<Point> {
+        // druid-shell's default size.
+        let size = self.size.unwrap_or_else(|| Size::new(500., 400.));
+        let centered = |area: Rect| area.center() - Vec2::new(size.width, size.height) / 2.0;
+        let on_primary = || Screen::get_primary().map(|m| centered(m.virtual_work_rect()));
+        match &self.placement {
+            WindowPlacement::Default => self
+                .parent
+                .and_then(|(id, _)| state.window_frame(id))
+                .map(centered),
+            WindowPlacement::CenteredOnPrimary => on_primary(),
+            WindowPlacement::CenteredOnParent(id) => state.window_frame(*id).map(centered),
+            WindowPlacement::OnMonitor { monitor, position } => Screen::get_monitors()
+                .get(*monitor)
+                .map(|m| m.virtual_work_rect().origin() + position.to_vec2()),
+            WindowPlacement::RememberLast(name) => state.last_position(name).or_else(on_primary),
+        }
+    }
+
     /// Attempt to create a platform window from this `WindowDesc`.
     pub(crate) fn build_native(
         &self,
         state: &Rc<RefCell<AppState<T>>>,
     ) -> Result<WindowHandle, PlatformError> {
+        let id = if state.borrow().has_window(self.id) {
+            WindowId::next()
+        } else {
+            self.id
+        };
         let mut title = self
             .title
             .clone()
@@ -171,22 +814,58 @@ impl<T: Data + 'static> WindowDesc<T> {
             .as_mut()
             .map(|m| m.build_window_menu(&state.borrow().data, &state.borrow().env));
 
-        let handler = DruidHandler::new_shared(state.clone(), self.id);
+        let handler = DruidHandler::new_shared(state.clone(), id, self.coalesce_input);
 
         let mut builder = WindowBuilder::new();
         builder.set_handler(Box::new(handler));
         if let Some(size) = self.size {
             builder.set_size(size);
         }
+        if let Some(position) = self.initial_position(&state.borrow()) {
+            builder.set_position(position);
+        }
+        if let Some((parent, style)) = self.parent {
+            match state.borrow().window_handle(parent) {
+                Some(handle) => builder.set_parent(&handle, style),
+                None => log::warn!("the parent of window {:?} has closed", id),
+            }
+        }
         builder.set_title(title.localized_str());
+        builder.set_render_backend(self.render_backend);
+        // Other platforms can't tag a window's color space, and show its
+        // colors as sRGB, so they aren't converted there.
+        let color_space = if cfg!(target_os = "macos") {
+            self.color_space
+        } else {
+            ColorSpace::Srgb
+        };
+        builder.set_color_space(color_space);
+        builder.set_text_rendering(self.text_rendering);
         if let Some(menu) = platform_menu {
             builder.set_menu(menu);
         }
 
-        let root = (self.root_builder)();
-        state
-            .borrow_mut()
-            .add_window(self.id, Window::new(root, title, menu));
+        let window = PendingWindow::new(
+            self.root_builder.clone(),
+            title,
+            menu,
+            color_space,
+            self.size_policy,
+            self.layer_builders.clone(),
+        );
+        state.borrow_mut().add_window(id, window);
+        if let WindowPlacement::RememberLast(name) = &self.placement {
+            state.borrow_mut().remember_position(id, name.clone());
+        }
+        if let Some((kind, session_state)) = &self.session {
+            let restoration_id = state.borrow_mut().add_session_window(
+                id,
+                kind.clone(),
+                session_state.clone(),
+                self.restoration_id.clone(),
+            );
+            builder.set_restoration_id(restoration_id);
+        }
 
         builder.build()
     }
@@ -196,4 +875,52 @@ impl<T: Data + 'static> WindowDesc<T> {
         self.menu = Some(menu);
         self
     }
+
+    /// Coalesce mouse move and wheel events, delivering them once per
+    /// frame.
+    ///
+    /// Mice with high polling rates can produce many more events than there
+    /// are frames. With coalescing, a run of mouse moves is delivered as its
+    /// latest position, and a run of wheel events as the sum of their
+    /// deltas, as long as the modifiers stay the same. Events are still
+    /// delivered in the order they happened. This is off by default.
+    pub fn coalesce_input(mut self, coalesce: bool) -> Self {
+        self.coalesce_input = coalesce;
+        self
+    }
+}
+
+/// The descriptions of the windows saved in the session, placed where they
+/// were if their monitor is still connected.
+fn restore_session<T: Data + 'static>(
+    store: &Store,
+    data: &mut T,
+    restore: Box<RestoreFn<T>>,
+) -> Vec<WindowDesc<T>> {
+    if !Application::restores_windows() {
+        return Vec::new();
+    }
+    let session = Session::load(store);
+    let monitors = Screen::get_monitors();
+    session
+        .windows
+        .iter()
+        .filter_map(|window| {
+            let mut desc = restore(window, data)?;
+            let origin = window.frame.origin();
+            desc.size = Some(window.frame.size());
+            desc.restoration_id = Some(window.restoration_id.clone());
+            let monitor = monitors
+                .iter()
+                .position(|m| m.virtual_rect().contains(origin));
+            if let Some(monitor) = monitor {
+                let work_origin = monitors[monitor].virtual_work_rect().origin();
+                desc.placement = WindowPlacement::OnMonitor {
+                    monitor,
+                    position: origin - work_origin.to_vec2(),
+                };
+            }
+            Some(desc)
+        })
+        .collect()
 }
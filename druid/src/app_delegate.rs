@@ -16,12 +16,12 @@
 
 use std::collections::VecDeque;
 
-use crate::{Command, Data, Env, Event, WindowId};
+use crate::{Command, Data, Env, Event, Target, WindowId};
 
 /// A context passed in to [`AppDelegate`] functions.
 pub struct DelegateCtx<'a> {
     pub(crate) source_id: WindowId,
-    pub(crate) command_queue: &'a mut VecDeque<(WindowId, Command)>,
+    pub(crate) command_queue: &'a mut VecDeque<(Target, Command)>,
 }
 
 impl<'a> DelegateCtx<'a> {
@@ -31,11 +31,18 @@ impl<'a> DelegateCtx<'a> {
     /// submitted during the handling of an event are executed before
     /// the [`update()`] method is called.
     ///
+    /// The target can be a [`Target`], a [`WindowId`] or a [`WidgetId`];
+    /// if it is `None`, the command is sent to the window the current event
+    /// came from.
+    ///
     /// [`Command`]: struct.Command.html
     /// [`update()`]: trait.Widget.html#tymethod.update
-    pub fn submit_command(&mut self, command: Command, window_id: impl Into<Option<WindowId>>) {
-        let window_id = window_id.into().unwrap_or(self.source_id);
-        self.command_queue.push_back((window_id, command))
+    /// [`Target`]: enum.Target.html
+    /// [`WindowId`]: struct.WindowId.html
+    /// [`WidgetId`]: struct.WidgetId.html
+    pub fn submit_command(&mut self, command: Command, target: impl Into<Option<Target>>) {
+        let target = target.into().unwrap_or(Target::Window(self.source_id));
+        self.command_queue.push_back((target, command))
     }
 }
 
@@ -44,7 +51,9 @@ impl<'a> DelegateCtx<'a> {
 /// The `AppDelegate` is a trait that is allowed to handle and modify
 /// events before they are passed down the widget tree.
 ///
-/// It is a natural place for things like window and menu management.
+/// It is a natural place for things like window and menu management, and
+/// for application-level commands such as opening a file, which would
+/// otherwise have to be handled by a root widget.
 ///
 /// You customize the `AppDelegate` by implementing its methods on your own type.
 #[allow(unused)]
@@ -65,6 +74,27 @@ pub trait AppDelegate<T: Data> {
         Some(event)
     }
 
+    /// The handler for [`Command`]s. This function receives every command,
+    /// including druid's own such as [`QUIT_APP`] and [`NEW_WINDOW`],
+    /// before it is handled or sent to the target.
+    ///
+    /// Returning `false` stops the command there; returning `true` lets it
+    /// continue as usual. Commands sent to several windows are seen once.
+    ///
+    /// [`Command`]: struct.Command.html
+    /// [`QUIT_APP`]: commands/constant.QUIT_APP.html
+    /// [`NEW_WINDOW`]: commands/constant.NEW_WINDOW.html
+    fn command(
+        &mut self,
+        target: Target,
+        cmd: &Command,
+        data: &mut T,
+        env: &Env,
+        ctx: &mut DelegateCtx,
+    ) -> bool {
+        true
+    }
+
     /// The handler for window creation events.
     /// This function is called after a window has been added,
     /// allowing you to customize the window creation behavior of your app.
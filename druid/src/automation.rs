@@ -0,0 +1,101 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Finding widgets by automation id, for UI testing tools.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::kurbo::Rect;
+use crate::{WidgetId, WindowId};
+
+/// A widget tagged with an automation id, as last painted.
+///
+/// Widgets are tagged with [`WidgetExt::automation_id`], and found with
+/// an [`AutomationQuery`].
+///
+/// [`WidgetExt::automation_id`]: widget/trait.WidgetExt.html#method.automation_id
+/// [`AutomationQuery`]: struct.AutomationQuery.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutomationNode {
+    /// The automation id.
+    pub id: String,
+    /// The window the widget is in.
+    pub window_id: WindowId,
+    /// The id of the tagged widget, which can be the target of a command.
+    pub widget_id: WidgetId,
+    /// The widget's bounds, in the window's coordinates.
+    pub rect: Rect,
+}
+
+/// A request for the widgets tagged with automation ids.
+///
+/// The query is the argument of a [`QUERY_AUTOMATION`] command; the nodes
+/// of the windows it targets, or of every window for [`Target::Global`],
+/// are sent back on the receiver. Only widgets that were painted are
+/// listed, so widgets that are scrolled out of view may be missing.
+///
+/// Automation tools running on another thread can submit the command with
+/// an [`ExtEventSink`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::{commands, AutomationQuery, ExtEventSink, Target};
+///
+/// fn find_button(sink: &ExtEventSink) -> Option<druid::kurbo::Rect> {
+///     let (query, nodes) = AutomationQuery::new();
+///     sink.submit_command(commands::QUERY_AUTOMATION, query, Target::Global);
+///     let nodes = nodes.recv().ok()?;
+///     nodes.into_iter().find(|node| node.id == "save").map(|node| node.rect)
+/// }
+/// ```
+///
+/// [`QUERY_AUTOMATION`]: commands/constant.QUERY_AUTOMATION.html
+/// [`Target::Global`]: enum.Target.html#variant.Global
+/// [`ExtEventSink`]: struct.ExtEventSink.html
+pub struct AutomationQuery {
+    sender: Sender<Vec<AutomationNode>>,
+}
+
+impl AutomationQuery {
+    /// Create a query, and the receiver its answer is sent to.
+    pub fn new() -> (AutomationQuery, Receiver<Vec<AutomationNode>>) {
+        let (sender, receiver) = channel();
+        (AutomationQuery { sender }, receiver)
+    }
+
+    /// Answer the query.
+    pub(crate) fn reply(&self, nodes: Vec<AutomationNode>) {
+        // The asker may have given up; that's fine.
+        let _ = self.sender.send(nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_is_received() {
+        let (query, receiver) = AutomationQuery::new();
+        let node = AutomationNode {
+            id: "save".into(),
+            window_id: WindowId::next(),
+            widget_id: WidgetId::next(),
+            rect: Rect::new(0.0, 0.0, 10.0, 20.0),
+        };
+        query.reply(vec![node.clone()]);
+        assert_eq!(receiver.recv().unwrap(), vec![node]);
+    }
+}
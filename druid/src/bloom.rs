@@ -0,0 +1,172 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A counting Bloom filter, used to route events to descendants.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// The number of counters. With two counters per item, about one in six
+/// checks for items that weren't added passes once a filter holds 64 items.
+const NUM_SLOTS: u64 = 256;
+
+/// A counting Bloom filter: a multiset that can report false positives, but
+/// never false negatives.
+///
+/// Each widget keeps a filter of the ids of its descendants, so that events
+/// can skip the subtrees that certainly don't contain their target.
+///
+/// Each item increments two counters, so items can be removed again. The
+/// counters are only allocated once an item is added, so the filters of
+/// the many widgets without descendants cost little. If a counter would
+/// overflow, which takes tens of thousands of items, the filter saturates:
+/// it then contains every item until it is [`clear`]ed, as removals can no
+/// longer be counted.
+///
+/// [`clear`]: #method.clear
+pub(crate) struct Bloom<T: ?Sized> {
+    /// The counters, empty until an item is added.
+    counts: Vec<u16>,
+    saturated: bool,
+    phantom: PhantomData<fn(&T)>,
+}
+
+impl<T: ?Sized + Hash> Bloom<T> {
+    /// Create a new, empty filter.
+    pub fn new() -> Bloom<T> {
+        Bloom {
+            counts: Vec::new(),
+            saturated: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Add an item.
+    pub fn add(&mut self, item: &T) {
+        if self.counts.is_empty() {
+            self.counts = vec![0; NUM_SLOTS as usize];
+        }
+        for &slot in &Self::slots(item) {
+            match self.counts[slot].checked_add(1) {
+                Some(count) => self.counts[slot] = count,
+                None => self.saturated = true,
+            }
+        }
+    }
+
+    /// Remove an item that was added.
+    pub fn remove(&mut self, item: &T) {
+        if self.saturated || self.counts.is_empty() {
+            debug_assert!(self.saturated, "removed an item that was not added");
+            return;
+        }
+        for &slot in &Self::slots(item) {
+            debug_assert!(self.counts[slot] > 0, "removed an item that was not added");
+            self.counts[slot] = self.counts[slot].saturating_sub(1);
+        }
+    }
+
+    /// Returns `true` if the item may have been added. If this returns
+    /// `false`, it definitely has not been.
+    pub fn may_contain(&self, item: &T) -> bool {
+        if self.saturated {
+            return true;
+        }
+        !self.counts.is_empty() && Self::slots(item).iter().all(|&slot| self.counts[slot] > 0)
+    }
+
+    /// Remove every item, and reset the counters.
+    pub fn clear(&mut self) {
+        *self = Bloom::new();
+    }
+
+    /// The counters for an item: two, from two halves of its hash.
+    fn slots(item: &T) -> [usize; 2] {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let hash = hasher.finish();
+        let first = (hash & 0xffff_ffff) % NUM_SLOTS;
+        let second = (hash >> 32) % NUM_SLOTS;
+        [first as usize, second as usize]
+    }
+}
+
+impl<T: ?Sized> Clone for Bloom<T> {
+    fn clone(&self) -> Self {
+        Bloom {
+            counts: self.counts.clone(),
+            saturated: self.saturated,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized + Hash> Default for Bloom<T> {
+    fn default() -> Self {
+        Bloom::new()
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Bloom<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let len = self.counts.iter().filter(|&&count| count > 0).count();
+        write!(f, "Bloom({}/{} slots", len, NUM_SLOTS)?;
+        if self.saturated {
+            write!(f, ", saturated")?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_false_negatives() {
+        let mut bloom = Bloom::new();
+        for i in 0..20u32 {
+            bloom.add(&i);
+        }
+        for i in 0..20u32 {
+            assert!(bloom.may_contain(&i));
+        }
+    }
+
+    #[test]
+    fn remove() {
+        let mut bloom = Bloom::new();
+        bloom.add(&1u32);
+        bloom.add(&2u32);
+        bloom.remove(&1u32);
+        assert!(!bloom.may_contain(&1u32));
+        assert!(bloom.may_contain(&2u32));
+        bloom.remove(&2u32);
+        assert!(!bloom.may_contain(&2u32));
+    }
+
+    #[test]
+    fn saturate_and_clear() {
+        let mut bloom = Bloom::new();
+        for _ in 0..=u16::max_value() {
+            bloom.add(&1u32);
+        }
+        bloom.remove(&1u32);
+        assert!(bloom.may_contain(&2u32));
+        bloom.clear();
+        assert!(!bloom.may_contain(&1u32));
+    }
+}
@@ -14,7 +14,7 @@
 
 //! The fundamental druid types.
 
-use crate::kurbo::Size;
+use crate::kurbo::{Insets, Size};
 use log;
 
 /// Constraints for layout.
@@ -118,4 +118,83 @@ impl BoxConstraints {
 
         BoxConstraints::new(min, max)
     }
+
+    /// Shrink min and max constraints by the given insets.
+    ///
+    /// This is the constraint a container passes to a child that is inset
+    /// from its own edges, as with padding or a border.
+    pub fn deflate(&self, insets: impl Into<Insets>) -> BoxConstraints {
+        let insets = insets.into();
+        self.shrink((insets.x0 + insets.x1, insets.y0 + insets.y1))
+    }
+
+    /// Make a version of the constraints with no upper bound on the width.
+    pub fn unbound_max_width(&self) -> BoxConstraints {
+        let max = Size::new(std::f64::INFINITY, self.max.height);
+        BoxConstraints::new(self.min, max)
+    }
+
+    /// Make a version of the constraints with no upper bound on the height.
+    pub fn unbound_max_height(&self) -> BoxConstraints {
+        let max = Size::new(self.max.width, std::f64::INFINITY);
+        BoxConstraints::new(self.min, max)
+    }
+
+    /// Whether the given size satisfies the constraints.
+    pub fn contains(&self, size: impl Into<Size>) -> bool {
+        let size = size.into();
+        self.min.width <= size.width
+            && size.width <= self.max.width
+            && self.min.height <= size.height
+            && size.height <= self.max.height
+    }
+
+    /// Clamp a size to the constraints, rounding it to whole device pixels.
+    ///
+    /// `scale` is the number of device pixels per display point, as for the
+    /// window the widget is in. The size is rounded to the nearest pixel and
+    /// then clamped; if a bound is not itself on the pixel grid the result
+    /// is rounded inward so that it still satisfies the constraints.
+    pub fn constrain_to_pixels(&self, size: impl Into<Size>, scale: f64) -> Size {
+        let size = size.into();
+        let round = |value: f64, min: f64, max: f64| {
+            let value = (value * scale).round() / scale;
+            if value > max {
+                (max * scale).floor() / scale
+            } else if value < min {
+                (min * scale).ceil() / scale
+            } else {
+                value
+            }
+        };
+        Size::new(
+            round(size.width, self.min.width, self.max.width),
+            round(size.height, self.min.height, self.max.height),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deflate_and_unbound() {
+        let bc = BoxConstraints::new(Size::new(10., 10.), Size::new(100., 50.));
+        let inner = bc.deflate(Insets::new(5., 2., 15., 8.));
+        assert_eq!(inner.min(), Size::ZERO);
+        assert_eq!(inner.max(), Size::new(80., 40.));
+        assert!(!bc.unbound_max_height().is_height_bounded());
+        assert!(bc.unbound_max_height().is_width_bounded());
+        assert!(!bc.unbound_max_width().is_width_bounded());
+    }
+
+    #[test]
+    fn pixel_rounding() {
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(10.3, 100.));
+        assert_eq!(bc.constrain_to_pixels((3.3, 4.6), 2.0), Size::new(3.5, 4.5));
+        let size = bc.constrain_to_pixels((20., 20.), 2.0);
+        assert_eq!(size, Size::new(10., 20.));
+        assert!(bc.contains(size));
+    }
 }
@@ -0,0 +1,271 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Capturing the contents of windows and widgets as images.
+
+#[cfg(feature = "png-export")]
+use std::fs;
+#[cfg(feature = "png-export")]
+use std::io;
+#[cfg(feature = "png-export")]
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::kurbo::Rect;
+use crate::shell::ColorSpace;
+use crate::{Command, Selector, Target, WidgetId};
+
+/// An image in memory, as 8-bit RGBA pixels with separate alpha, row by
+/// row from the top.
+///
+/// The pixels are in sRGB unless the image says otherwise; images can be
+/// converted to the color space of the window they are shown in with
+/// [`to_color_space`].
+///
+/// [`to_color_space`]: #method.to_color_space
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageBuf {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    color_space: ColorSpace,
+}
+
+impl ImageBuf {
+    /// Create an image from its pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there aren't `width * height * 4` bytes of pixels.
+    pub fn from_raw(pixels: Vec<u8>, width: usize, height: usize) -> ImageBuf {
+        assert_eq!(pixels.len(), width * height * 4, "wrong number of pixels");
+        ImageBuf {
+            width,
+            height,
+            pixels,
+            color_space: ColorSpace::Srgb,
+        }
+    }
+
+    /// Say which color space the pixels are in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> ImageBuf {
+        self.color_space = color_space;
+        self
+    }
+
+    /// The color space of the pixels.
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// The image with its pixels converted to another color space.
+    pub fn to_color_space(&self, color_space: ColorSpace) -> ImageBuf {
+        let mut pixels = self.pixels.clone();
+        if color_space != self.color_space {
+            for pixel in pixels.chunks_mut(4) {
+                let channel = |i: usize| f64::from(pixel[i]) / 255.0;
+                let rgb = [channel(0), channel(1), channel(2)];
+                let rgb = self.color_space.convert_rgb(rgb, color_space);
+                for (channel, value) in pixel.iter_mut().zip(&rgb) {
+                    *channel = (value * 255.0).round() as u8;
+                }
+            }
+        }
+        ImageBuf::from_raw(pixels, self.width, self.height).with_color_space(color_space)
+    }
+
+    /// The width of the image, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the image, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The pixels of the image.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The part of the image within `rect`, in pixels.
+    pub(crate) fn crop(&self, rect: Rect) -> ImageBuf {
+        let rect = Rect::new(
+            rect.x0.round(),
+            rect.y0.round(),
+            rect.x1.round(),
+            rect.y1.round(),
+        )
+        .intersect(Rect::new(0.0, 0.0, self.width as f64, self.height as f64));
+        let (x0, y0) = (rect.x0 as usize, rect.y0 as usize);
+        let (width, height) = (rect.width() as usize, rect.height() as usize);
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in self.pixels.chunks(self.width * 4).skip(y0).take(height) {
+            pixels.extend_from_slice(&row[x0 * 4..(x0 + width) * 4]);
+        }
+        ImageBuf::from_raw(pixels, width, height).with_color_space(self.color_space)
+    }
+
+    /// The image encoded as a PNG, tagged with its color space.
+    ///
+    /// This needs the `png-export` feature.
+    #[cfg(feature = "png-export")]
+    pub fn to_png(&self) -> io::Result<Vec<u8>> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        let (kind, data): (&[u8; 4], &[u8]) = match self.color_space {
+            // Perceptual rendering intent.
+            ColorSpace::Srgb => (b"sRGB", &[0]),
+            // Coding-independent code points: P3 D65 primaries, the sRGB
+            // transfer function, RGB, full range.
+            ColorSpace::DisplayP3 => (b"cICP", &[12, 13, 0, 1]),
+        };
+        writer.write_chunk(*kind, data).map_err(png_error)?;
+        writer.write_image_data(&self.pixels).map_err(png_error)?;
+        drop(writer);
+        Ok(png)
+    }
+
+    /// Save the image to a PNG file.
+    ///
+    /// This needs the `png-export` feature.
+    #[cfg(feature = "png-export")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_png()?)
+    }
+}
+
+#[cfg(feature = "png-export")]
+fn png_error(e: png::EncodingError) -> io::Error {
+    match e {
+        png::EncodingError::IoError(e) => e,
+        e => io::Error::new(io::ErrorKind::Other, e),
+    }
+}
+
+/// A request for an image of a window, or of one widget in it.
+///
+/// The request is the argument of a [`CAPTURE_IMAGE`] command, targeting
+/// the window to capture. The window is painted into an image at its
+/// current size and resolution, and the image, or `None` if it couldn't
+/// be made or the widget wasn't painted, is sent back.
+///
+/// Requests made with [`new`] are answered on a channel, which suits tools
+/// running on another thread, like those that make screenshots for
+/// documentation. Widgets can instead have the image sent to them as a
+/// command, with [`with_reply`], for features like "copy as image".
+///
+/// # Examples
+///
+/// Saving a screenshot, with the `png-export` feature:
+///
+/// ```no_run
+/// use druid::{commands, CaptureRequest, ExtEventSink, Target, WindowId};
+///
+/// # #[cfg(feature = "png-export")]
+/// fn screenshot(sink: &ExtEventSink, window: WindowId) -> std::io::Result<()> {
+///     let (request, image) = CaptureRequest::new(None);
+///     sink.submit_command(commands::CAPTURE_IMAGE, request, Target::Window(window));
+///     match image.recv() {
+///         Ok(Some(image)) => image.save_png("screenshot.png"),
+///         _ => Ok(()),
+///     }
+/// }
+/// ```
+///
+/// [`CAPTURE_IMAGE`]: commands/constant.CAPTURE_IMAGE.html
+/// [`new`]: #method.new
+/// [`with_reply`]: #method.with_reply
+pub struct CaptureRequest {
+    pub(crate) widget: Option<WidgetId>,
+    reply: Reply,
+}
+
+enum Reply {
+    Channel(Sender<Option<ImageBuf>>),
+    Command(Selector, Target),
+}
+
+impl CaptureRequest {
+    /// Create a request for an image of a widget, or of the whole window if
+    /// `widget` is `None`, and the receiver the image is sent to.
+    pub fn new(widget: Option<WidgetId>) -> (CaptureRequest, Receiver<Option<ImageBuf>>) {
+        let (sender, receiver) = channel();
+        let request = CaptureRequest {
+            widget,
+            reply: Reply::Channel(sender),
+        };
+        (request, receiver)
+    }
+
+    /// Create a request for an image of a widget, or of the whole window if
+    /// `widget` is `None`, that is answered with a command.
+    ///
+    /// The command has the given selector and target, and its argument is
+    /// an `Option<ImageBuf>`.
+    pub fn with_reply(
+        widget: Option<WidgetId>,
+        selector: Selector,
+        target: impl Into<Target>,
+    ) -> CaptureRequest {
+        CaptureRequest {
+            widget,
+            reply: Reply::Command(selector, target.into()),
+        }
+    }
+
+    /// Answer the request, returning the command to send, if it wants one.
+    pub(crate) fn reply(&self, image: Option<ImageBuf>) -> Option<(Target, Command)> {
+        match &self.reply {
+            Reply::Channel(sender) => {
+                // The asker may have given up; that's fine.
+                let _ = sender.send(image);
+                None
+            }
+            Reply::Command(selector, target) => Some((*target, Command::new(*selector, image))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "png-export")]
+    fn png_layout() {
+        let image = ImageBuf::from_raw(vec![0xff; 2 * 3 * 4], 2, 3);
+        let png = image.to_png().unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 3]);
+        assert_eq!(&png[37..41], b"sRGB");
+        assert_eq!(&png[50..54], b"IDAT");
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn crop() {
+        let pixels = (0..4 * 4 * 4).map(|i| (i / 4) as u8).collect();
+        let image = ImageBuf::from_raw(pixels, 4, 4);
+        let cropped = image.crop(Rect::new(1.0, 2.0, 3.0, 10.0));
+        assert_eq!((cropped.width(), cropped.height()), (2, 2));
+        let firsts: Vec<u8> = cropped.pixels().chunks(4).map(|p| p[0]).collect();
+        assert_eq!(firsts, vec![9, 10, 13, 14]);
+    }
+}
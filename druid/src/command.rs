@@ -17,8 +17,10 @@
 use std::any::Any;
 use std::sync::Arc;
 
+use crate::{WidgetId, WindowId};
+
 /// An identifier for a particular command.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Selector(&'static str);
 
 /// An arbitrary command.
@@ -42,6 +44,22 @@ pub struct Command {
     object: Option<Arc<dyn Any>>,
 }
 
+/// The recipient of a [`Command`].
+///
+/// [`Command`]: struct.Command.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// The command is sent to every window.
+    Global,
+    /// The command is sent to the widgets of one window.
+    Window(WindowId),
+    /// The command is sent only to one widget and its descendants.
+    ///
+    /// Only widgets that have been laid out can be found; a command for a
+    /// widget that doesn't exist is dropped.
+    Widget(WidgetId),
+}
+
 /// [`Command`]s with special meaning, defined by druid.
 ///
 /// [`Command`]: struct.Command.html
@@ -58,19 +76,65 @@ pub mod sys {
     pub const HIDE_OTHERS: Selector = Selector::new("druid-builtin.menu-hide-others");
 
     /// The selector for a command to create a new window.
+    ///
+    /// The command's argument should be a [`WindowDesc`]. The new window has
+    /// its own root widget, built from the description, and shares the
+    /// application's data with every other window.
+    ///
+    /// [`WindowDesc`]: ../struct.WindowDesc.html
     pub const NEW_WINDOW: Selector = Selector::new("druid-builtin.new-window");
 
     /// The selector for a command to close a window. The command's argument
     /// should be the id of the window to close.
     pub const CLOSE_WINDOW: Selector = Selector::new("druid-builtin.close-window");
 
+    /// Sent to every window, and to the [`AppDelegate`], when a window has
+    /// closed. The command's argument is the id of the window.
+    ///
+    /// This is sent by druid; submitting it has no effect on any window.
+    ///
+    /// [`AppDelegate`]: ../trait.AppDelegate.html
+    pub const WINDOW_CLOSED: Selector = Selector::new("druid-builtin.window-closed");
+
+    /// Find the widgets tagged with automation ids. The argument must be an
+    /// [`AutomationQuery`], which is answered with the widgets of the target
+    /// window, or of every window.
+    ///
+    /// [`AutomationQuery`]: ../struct.AutomationQuery.html
+    pub const QUERY_AUTOMATION: Selector = Selector::new("druid-builtin.query-automation");
+
+    /// Capture the contents of the target window, or of one widget in it, as
+    /// an image. The argument must be a [`CaptureRequest`], which is
+    /// answered with the image.
+    ///
+    /// [`CaptureRequest`]: ../struct.CaptureRequest.html
+    pub const CAPTURE_IMAGE: Selector = Selector::new("druid-builtin.capture-image");
+
+    /// Switch the theme's palette. The argument must be an
+    /// `Option<ColorScheme>`; `None` follows the system's color scheme.
+    ///
+    /// Every window is repainted, and its widgets are sent
+    /// [`LifeCycle::ColorSchemeChanged`] if the palette changed.
+    ///
+    /// [`LifeCycle::ColorSchemeChanged`]: ../enum.LifeCycle.html#variant.ColorSchemeChanged
+    pub const SET_COLOR_SCHEME: Selector = Selector::new("druid-builtin.set-color-scheme");
+
+    /// Switch the theme's density. The argument must be a [`Density`].
+    ///
+    /// Every window is laid out and repainted with the new spacing.
+    ///
+    /// [`Density`]: ../theme/enum.Density.html
+    pub const SET_DENSITY: Selector = Selector::new("druid-builtin.set-density");
+
     /// The selector for a command to bring a window to the front, and give it focus.
     ///
     /// The command's argument should be the id of the target window.
     pub const SHOW_WINDOW: Selector = Selector::new("druid-builtin.show-window");
 
-    /// Display a context (right-click) menu. The argument should be a...
-    //TODO
+    /// Display a context (right-click) menu. The argument should be a
+    /// [`ContextMenu`] for the application's data type.
+    ///
+    /// [`ContextMenu`]: ../struct.ContextMenu.html
     pub const SHOW_CONTEXT_MENU: Selector = Selector::new("druid-builtin.show-context-menu");
 
     /// The selector for a command to set the window's menu. The argument should
@@ -106,6 +170,101 @@ pub mod sys {
     /// [`FileInfo`]: struct.FileInfo.html
     pub const OPEN_FILE: Selector = Selector::new("druid-builtin.open-file-path");
 
+    /// Open some files, at the request of the platform or from the command
+    /// line. The argument is a `Vec<PathBuf>`.
+    ///
+    /// On macOS, druid sends this when the user opens documents with the
+    /// application, such as from the Finder, including the documents that
+    /// launched it. Other platforms pass such files as command line
+    /// arguments; an application can send them with
+    /// [`AppLauncher::launch_command`] so that they are handled the same way.
+    ///
+    /// Either way, the command is only sent once the first window is open.
+    ///
+    /// [`AppLauncher::launch_command`]: ../struct.AppLauncher.html#method.launch_command
+    pub const OPEN_FILES: Selector = Selector::new("druid-builtin.open-files");
+
+    /// The arguments the application was started with. The argument is a
+    /// [`CommandLine`].
+    ///
+    /// This is sent once the first window is open, if the arguments were
+    /// given to [`AppLauncher::command_line`].
+    ///
+    /// [`CommandLine`]: ../struct.CommandLine.html
+    /// [`AppLauncher::command_line`]: ../struct.AppLauncher.html#method.command_line
+    pub const COMMAND_LINE: Selector = Selector::new("druid-builtin.command-line");
+
+    /// Sent when the computer is about to sleep, so that the application
+    /// can save its work or pause what it is doing.
+    ///
+    /// This, and the other system commands below, are sent to every window,
+    /// and seen by the [`AppDelegate`] first. They are delivered after the
+    /// change, through the event loop, so there may be little time left
+    /// before the computer sleeps.
+    ///
+    /// On Linux, this and [`SYSTEM_DID_WAKE`] are only sent where logind
+    /// runs, as it does with systemd.
+    ///
+    /// [`AppDelegate`]: ../trait.AppDelegate.html
+    /// [`SYSTEM_DID_WAKE`]: constant.SYSTEM_DID_WAKE.html
+    pub const SYSTEM_WILL_SLEEP: Selector = Selector::new("druid-builtin.system-will-sleep");
+
+    /// Sent when the computer wakes from sleep; connections made before
+    /// it slept may need to be made again.
+    pub const SYSTEM_DID_WAKE: Selector = Selector::new("druid-builtin.system-did-wake");
+
+    /// Sent when the computer switches to another power source. The
+    /// argument is the new [`PowerSource`].
+    ///
+    /// This is not sent on Linux, where the power source can only be asked
+    /// for, with [`PowerSource::current`].
+    ///
+    /// [`PowerSource::current`]: ../enum.PowerSource.html#method.current
+    /// [`PowerSource`]: ../enum.PowerSource.html
+    pub const POWER_SOURCE_CHANGED: Selector = Selector::new("druid-builtin.power-source-changed");
+
+    /// Sent when the user's session is locked.
+    ///
+    /// On Linux, this and [`SESSION_UNLOCKED`] are only sent where logind
+    /// runs, and are sent when it asks the screen locker to lock or unlock;
+    /// a screen locker started another way is not noticed.
+    ///
+    /// [`SESSION_UNLOCKED`]: constant.SESSION_UNLOCKED.html
+    pub const SESSION_LOCKED: Selector = Selector::new("druid-builtin.session-locked");
+
+    /// Sent when the user's session is unlocked, such as after the user
+    /// has signed in again.
+    pub const SESSION_UNLOCKED: Selector = Selector::new("druid-builtin.session-unlocked");
+
+    /// Sent when the internet becomes reachable or unreachable, if the
+    /// application asked for it with [`AppLauncher::monitor_network`]. The
+    /// argument is a `bool`, which is `true` if it is now reachable.
+    ///
+    /// [`AppLauncher::monitor_network`]: ../struct.AppLauncher.html#method.monitor_network
+    pub const NETWORK_CHANGED: Selector = Selector::new("druid-builtin.network-changed");
+
+    /// Sent by a [`Process`] for each line it writes. The argument is a
+    /// [`ProcessOutput`].
+    ///
+    /// [`Process`]: ../process/struct.Process.html
+    /// [`ProcessOutput`]: ../process/struct.ProcessOutput.html
+    pub const PROCESS_OUTPUT: Selector = Selector::new("druid-builtin.process-output");
+
+    /// Sent by a [`Process`] when it exits, after its last line of output.
+    /// The argument is a [`ProcessExit`].
+    ///
+    /// [`Process`]: ../process/struct.Process.html
+    /// [`ProcessExit`]: ../process/struct.ProcessExit.html
+    pub const PROCESS_EXITED: Selector = Selector::new("druid-builtin.process-exited");
+
+    /// Sent by a [`FileWatcher`] when a watched file changes. The argument
+    /// is a [`FileChange`].
+    ///
+    /// [`FileWatcher`]: ../file_watcher/struct.FileWatcher.html
+    /// [`FileChange`]: ../file_watcher/enum.FileChange.html
+    #[cfg(feature = "file-watcher")]
+    pub const FILE_CHANGED: Selector = Selector::new("druid-builtin.file-changed");
+
     /// Special command. When issued, the system will show the 'save as' panel,
     /// and if a path is selected the system will issue a `SAVE_FILE` command
     /// with the selected path as the argument.
@@ -117,9 +276,20 @@ pub mod sys {
 
     /// Save the current file.
     ///
-    /// The argument, if present, should be the path where the file should be saved.
+    /// The argument, if present, should be the [`FileInfo`] for the path
+    /// where the file should be saved.
+    ///
+    /// [`FileInfo`]: struct.FileInfo.html
     pub const SAVE_FILE: Selector = Selector::new("druid-builtin.menu-file-save");
 
+    /// Show a modal message dialog, and send a command depending on the
+    /// user's answer.
+    ///
+    /// The argument must be a [`MessageDialog`].
+    ///
+    /// [`MessageDialog`]: ../struct.MessageDialog.html
+    pub const SHOW_MESSAGE_DIALOG: Selector = Selector::new("druid-builtin.show-message-dialog");
+
     /// Show the print-setup window.
     pub const PRINT_SETUP: Selector = Selector::new("druid-builtin.menu-file-print-setup");
 
@@ -143,6 +313,103 @@ pub mod sys {
 
     /// Redo.
     pub const REDO: Selector = Selector::new("druid-builtin.menu-redo");
+
+    /// Select all.
+    pub const SELECT_ALL: Selector = Selector::new("druid-builtin.menu-select-all");
+
+    /// Delete the current selection.
+    pub const DELETE: Selector = Selector::new("druid-builtin.menu-delete");
+
+    /// The standard editing commands.
+    ///
+    /// These commands are delivered only to the focused widget and its
+    /// ancestors, rather than to the whole widget tree. Before a menu is
+    /// shown, its items for these commands are disabled unless the focused
+    /// widget reports, in response to [`Event::ValidateCommand`], that it
+    /// can currently handle them.
+    ///
+    /// [`Event::ValidateCommand`]: ../enum.Event.html#variant.ValidateCommand
+    pub const FOCUS_COMMANDS: &[Selector] = &[CUT, COPY, PASTE, SELECT_ALL, DELETE];
+
+    /// The commands that go to the focused editor, if there is one.
+    ///
+    /// If the focused widget reports, in response to
+    /// [`Event::ValidateCommand`], that it can currently handle one of these
+    /// commands, the command is sent to it alone. Otherwise the command is
+    /// delivered like any other, so that an application can keep its own
+    /// undo history; their menu items are never disabled.
+    ///
+    /// [`Event::ValidateCommand`]: ../enum.Event.html#variant.ValidateCommand
+    pub const EDITOR_COMMANDS: &[Selector] = &[UNDO, REDO];
+
+    /// Perform an editing action in the focused text widget.
+    ///
+    /// The argument must be an [`EditAction`].
+    ///
+    /// [`EditAction`]: ../enum.EditAction.html
+    pub const EDIT_ACTION: Selector = Selector::new("druid-builtin.edit-action");
+
+    /// Set the query of a [`searchable`] `TextArea`.
+    ///
+    /// The argument should be the query `String`; an empty query clears the
+    /// current matches.
+    ///
+    /// [`searchable`]: ../widget/struct.TextArea.html#method.searchable
+    pub const FIND: Selector = Selector::new("druid-builtin.find");
+
+    /// Select the next match of the current find query.
+    pub const FIND_NEXT: Selector = Selector::new("druid-builtin.find-next");
+
+    /// Select the previous match of the current find query.
+    pub const FIND_PREVIOUS: Selector = Selector::new("druid-builtin.find-previous");
+
+    /// Sent by a searchable `TextArea` when its matches change.
+    ///
+    /// The argument is a [`FindMatches`] object. This is not handled by druid;
+    /// an [`AppDelegate`] can use it to store the match count in the data.
+    ///
+    /// [`FindMatches`]: ../widget/struct.FindMatches.html
+    /// [`AppDelegate`]: ../trait.AppDelegate.html
+    pub const FIND_MATCHES: Selector = Selector::new("druid-builtin.find-matches");
+
+    /// Zoom and center views so their content fits within them.
+    ///
+    /// This is handled by [`ZoomView`].
+    ///
+    /// [`ZoomView`]: ../widget/struct.ZoomView.html
+    pub const ZOOM_TO_FIT: Selector = Selector::new("druid-builtin.zoom-to-fit");
+
+    /// Reset views to show their content at its actual size.
+    ///
+    /// This is handled by [`ZoomView`].
+    ///
+    /// [`ZoomView`]: ../widget/struct.ZoomView.html
+    pub const ZOOM_ACTUAL_SIZE: Selector = Selector::new("druid-builtin.zoom-actual-size");
+
+    /// Move the playhead of timelines.
+    ///
+    /// The argument must be the time, as an `f64`. This is handled by
+    /// [`Timeline`].
+    ///
+    /// [`Timeline`]: ../widget/struct.Timeline.html
+    pub const SET_PLAYHEAD: Selector = Selector::new("druid-builtin.set-playhead");
+
+    /// Show a context menu of spelling suggestions for a misspelled word.
+    ///
+    /// The argument must be a [`SpellingSuggestions`] object. Choosing an
+    /// entry sends a [`REPLACE_MISSPELLING`] command.
+    ///
+    /// [`SpellingSuggestions`]: ../spellcheck/struct.SpellingSuggestions.html
+    /// [`REPLACE_MISSPELLING`]: constant.REPLACE_MISSPELLING.html
+    #[cfg(feature = "spellcheck")]
+    pub const SHOW_SPELLING_SUGGESTIONS: Selector =
+        Selector::new("druid-builtin.show-spelling-suggestions");
+
+    /// Replace the focused text widget's selection with a spelling suggestion.
+    ///
+    /// The argument must be the replacement `String`.
+    #[cfg(feature = "spellcheck")]
+    pub const REPLACE_MISSPELLING: Selector = Selector::new("druid-builtin.replace-misspelling");
 }
 
 impl Selector {
@@ -165,6 +432,15 @@ impl Command {
         }
     }
 
+    /// Create a `Command` whose argument was sent from another thread.
+    pub(crate) fn from_boxed(selector: Selector, arg: Box<dyn Any + Send>) -> Self {
+        let object: Arc<dyn Any + Send> = arg.into();
+        Command {
+            selector,
+            object: Some(object as Arc<dyn Any>),
+        }
+    }
+
     /// Return a reference to this command's object, if it has one.
     pub fn get_object<T: Any>(&self) -> Option<&T> {
         match self.object.as_ref() {
@@ -189,6 +465,30 @@ impl std::fmt::Display for Selector {
     }
 }
 
+impl From<WindowId> for Target {
+    fn from(id: WindowId) -> Target {
+        Target::Window(id)
+    }
+}
+
+impl From<WidgetId> for Target {
+    fn from(id: WidgetId) -> Target {
+        Target::Widget(id)
+    }
+}
+
+impl From<WindowId> for Option<Target> {
+    fn from(id: WindowId) -> Option<Target> {
+        Some(Target::Window(id))
+    }
+}
+
+impl From<WidgetId> for Option<Target> {
+    fn from(id: WidgetId) -> Option<Target> {
+        Some(Target::Widget(id))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,4 +499,17 @@ mod tests {
         let command = Command::new(sel, objs);
         assert_eq!(command.get_object(), Some(&vec![0, 1, 2]));
     }
+
+    #[test]
+    fn into_target() {
+        fn target(target: impl Into<Option<Target>>) -> Option<Target> {
+            target.into()
+        }
+        let window_id = WindowId::next();
+        let widget_id = WidgetId::next();
+        assert_eq!(target(None), None);
+        assert_eq!(target(window_id), Some(Target::Window(window_id)));
+        assert_eq!(target(widget_id), Some(Target::Widget(widget_id)));
+        assert_eq!(target(Target::Global), Some(Target::Global));
+    }
 }
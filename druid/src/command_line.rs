@@ -0,0 +1,124 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing command line arguments.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// The arguments an application was started with.
+///
+/// Arguments are parsed without knowing which options the application has:
+///
+/// - `--name` and `-n` are flags, and `-abc` is the three flags `a`, `b`
+///   and `c`;
+/// - `--name=value` is an option with a value;
+/// - everything else, including everything after `--`, is the path of a
+///   file to open. A lone `-` is a path too, as it often stands for
+///   standard input.
+///
+/// This covers opening documents from a file manager, which passes their
+/// paths as arguments on Windows and Linux, and simple switches. Give the
+/// arguments to [`AppLauncher::command_line`] to have them sent to the
+/// [`AppDelegate`] once the first window is open.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use druid::CommandLine;
+///
+/// let args = CommandLine::parse(vec!["-v", "--theme=dark", "notes.txt", "--", "--odd"]);
+/// assert!(args.flag("v"));
+/// assert_eq!(args.value("theme"), Some("dark"));
+/// assert_eq!(args.files, vec![PathBuf::from("notes.txt"), PathBuf::from("--odd")]);
+/// ```
+///
+/// [`AppLauncher::command_line`]: struct.AppLauncher.html#method.command_line
+/// [`AppDelegate`]: trait.AppDelegate.html
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandLine {
+    /// The flags and options, in order, by name without the dashes, with
+    /// the values of options.
+    pub options: Vec<(String, Option<String>)>,
+    /// The paths of the files to open, in order.
+    pub files: Vec<PathBuf>,
+}
+
+impl CommandLine {
+    /// The arguments of the current process, without the name of the
+    /// program.
+    pub fn from_env() -> CommandLine {
+        CommandLine::parse(std::env::args_os().skip(1))
+    }
+
+    /// Parse arguments, not including the name of the program.
+    ///
+    /// Paths are kept exactly; options that aren't valid unicode are
+    /// converted lossily.
+    pub fn parse<I>(args: I) -> CommandLine
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString>,
+    {
+        let mut command_line = CommandLine::default();
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            let text = arg.to_string_lossy();
+            match text.as_bytes() {
+                b"--" => command_line.files.extend(args.by_ref().map(PathBuf::from)),
+                [b'-', b'-', ..] => {
+                    let mut parts = text[2..].splitn(2, '=');
+                    let name = parts.next().unwrap_or_default().to_owned();
+                    let value = parts.next().map(str::to_owned);
+                    command_line.options.push((name, value));
+                }
+                [b'-', _, ..] => {
+                    let flags = text[1..].chars().map(|flag| (flag.to_string(), None));
+                    command_line.options.extend(flags);
+                }
+                _ => command_line.files.push(PathBuf::from(arg)),
+            }
+        }
+        command_line
+    }
+
+    /// Returns `true` if the flag or option `name` was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    /// The value of the last option `name` that has one.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .filter(|(option, _)| option == name)
+            .find_map(|(_, value)| value.as_ref().map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let args = CommandLine::parse(vec!["-", "-xy", "--size=10", "--size", "a=b", "--size=12"]);
+        assert_eq!(args.files, vec![PathBuf::from("-"), PathBuf::from("a=b")]);
+        assert!(args.flag("x") && args.flag("y") && !args.flag("z"));
+        assert_eq!(args.value("size"), Some("12"));
+        assert_eq!(args.options.len(), 5);
+    }
+}
@@ -14,22 +14,111 @@
 
 //! The fundamental druid types.
 
-use std::collections::VecDeque;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::mem;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
 use log;
 
-use crate::kurbo::{Affine, Rect, Shape, Size};
-use crate::piet::{Piet, RenderContext};
+use crate::bloom::Bloom;
+use crate::command::sys as sys_cmd;
+use crate::crash;
+use crate::kurbo::{Affine, Point, Rect, Shape, Size, Vec2};
+use crate::piet::{Device, ImageFormat, Piet, RenderContext};
+use crate::spawn::Spawner;
 use crate::{
-    BoxConstraints, Command, Cursor, Data, Env, Event, Text, TimerToken, Widget, WinCtx,
-    WindowHandle, WindowId,
+    AutomationNode, BoxConstraints, ColorSpace, Command, ContextMenu, Cursor, Data, Env, Event,
+    FileDialogOptions, FrameTiming, IdleToken, LifeCycle, MenuDesc, MessageDialog, PaintError,
+    Politeness, Raster, Resources, Selector, Store, Target, Text, TimerToken, WheelEvent, Widget,
+    WinCtx, WindowHandle, WindowId, WindowResources,
 };
 
 /// Convenience type for dynamic boxed widget.
 pub type BoxedWidget<T> = WidgetPod<T, Box<dyn Widget<T>>>;
 
+thread_local! {
+    /// The type names of the widgets whose methods are currently running,
+    /// outermost first. This is only maintained in debug builds, and while
+    /// crash reports are enabled.
+    static WIDGET_PATH: RefCell<Vec<&'static str>> = RefCell::new(Vec::new());
+}
+
+/// Pushes a widget onto the debug widget path, popping it when dropped.
+struct WidgetPathGuard {
+    /// Whether the widget was pushed.
+    entered: bool,
+}
+
+impl WidgetPathGuard {
+    fn enter(name: &'static str) -> WidgetPathGuard {
+        let entered = cfg!(debug_assertions) || crash::is_recording();
+        if entered {
+            WIDGET_PATH.with(|path| path.borrow_mut().push(name));
+        }
+        WidgetPathGuard { entered }
+    }
+}
+
+impl Drop for WidgetPathGuard {
+    fn drop(&mut self) {
+        if self.entered {
+            WIDGET_PATH.with(|path| path.borrow_mut().pop());
+        }
+    }
+}
+
+/// The path to the widget currently being processed, for use in
+/// diagnostic messages.
+///
+/// This is empty in release builds, unless crash reports are enabled.
+pub(crate) fn widget_path() -> String {
+    WIDGET_PATH.with(|path| path.borrow().join(" > "))
+}
+
+/// The type names of the widgets currently being processed, outermost
+/// first, or `None` if the path is borrowed.
+pub(crate) fn widget_stack() -> Option<Vec<&'static str>> {
+    WIDGET_PATH
+        .try_with(|path| path.try_borrow().ok().map(|path| path.clone()))
+        .ok()
+        .flatten()
+}
+
+/// Report a failure of the render context, inside the span of the widget
+/// being painted.
+fn report_paint_error(error: PaintError) {
+    tracing::error!(error = %error, widget = %widget_path(), "painting failed");
+}
+
+/// A unique identifier for a widget.
+///
+/// Each [`WidgetPod`] has an id, which can be used to send a [`Command`] to
+/// that widget with [`Target::Widget`].
+///
+/// [`WidgetPod`]: struct.WidgetPod.html
+/// [`Command`]: struct.Command.html
+/// [`Target::Widget`]: enum.Target.html#variant.Widget
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WidgetId(u64);
+
+static WIDGET_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// A change of focus requested by a widget, resolved by the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum FocusChange {
+    /// Focus the widget with this id.
+    Focus(WidgetId),
+    /// Focus the next widget in the focus chain.
+    Next,
+    /// Focus the previous widget in the focus chain.
+    Previous,
+}
+
 /// A container for one widget in the hierarchy.
 ///
 /// Generally, container widgets don't contain other widgets directly,
@@ -47,6 +136,18 @@ pub struct WidgetPod<T: Data, W: Widget<T>> {
     old_data: Option<T>,
     env: Option<Env>,
     inner: W,
+    /// What the widget painted, if it is retained.
+    retained: Option<RetainedPaint>,
+}
+
+/// The painting kept for a retained widget.
+#[derive(Default)]
+struct RetainedPaint {
+    raster: Option<Raster>,
+    /// The automation nodes painted with the raster.
+    automation: Vec<AutomationNode>,
+    /// The transform the raster was painted with.
+    transform: Affine,
 }
 
 /// Generic state for all widgets in the hierarchy.
@@ -64,8 +165,9 @@ pub struct WidgetPod<T: Data, W: Widget<T>> {
 ///
 /// [`paint`]: trait.Widget.html#tymethod.paint
 /// [`WidgetPod`]: struct.WidgetPod.html
-#[derive(Default)]
 pub struct BaseState {
+    id: WidgetId,
+
     layout_rect: Rect,
 
     // TODO: consider using bitflags for the booleans.
@@ -73,6 +175,15 @@ pub struct BaseState {
     // This should become an invalidation rect.
     pub(crate) needs_inval: bool,
 
+    /// The widget or a descendant has been invalidated, or updated, since
+    /// it was last painted. Retained widgets are painted again when this is
+    /// set.
+    needs_repaint: bool,
+
+    /// Counts the changes that set `needs_repaint`, for widgets that keep
+    /// their own copy of what a descendant paints.
+    changes: u64,
+
     is_hot: bool,
 
     is_active: bool,
@@ -83,17 +194,79 @@ pub struct BaseState {
     /// Any descendant has requested an animation frame.
     pub(crate) request_anim: bool,
 
-    /// Any descendant has requested a timer.
+    /// The pending timers requested by this widget or a descendant.
     ///
-    /// Note: we don't have any way of clearing this request, as it's
-    /// likely not worth the complexity.
-    request_timer: bool,
+    /// Together, these sets route each timer event down the path to the
+    /// widget that requested it. A token is removed as the event passes.
+    timers: HashSet<TimerToken>,
+
+    /// Timers requested during the current event, not yet added to the
+    /// parent's `timers`.
+    new_timers: Vec<TimerToken>,
+
+    /// Timers cancelled, or dropped with a removed widget, during the
+    /// current event, not yet removed from the parent's `timers`.
+    cancelled_timers: Vec<TimerToken>,
+
+    /// The pending idle work scheduled by this widget or a descendant.
+    ///
+    /// Like `timers`, a token is removed as its event passes.
+    idle: HashSet<IdleToken>,
 
     /// This widget or a descendant has focus.
-    has_focus: bool,
+    pub(crate) has_focus: bool,
+
+    /// A change of focus requested by this widget or a descendant.
+    pub(crate) request_focus: Option<FocusChange>,
+
+    /// The widget has received `LifeCycle::WidgetAdded`, and not been
+    /// removed since.
+    is_added: bool,
+
+    /// The ids of this widget and its descendants that registered for
+    /// focus, in the order focus moves through them.
+    focus_chain: Vec<WidgetId>,
 
-    /// This widget or a descendant has requested focus.
-    pub(crate) request_focus: bool,
+    /// Ids removed from `focus_chain` that the parent has not yet removed
+    /// from its own.
+    removed_focus: Vec<WidgetId>,
+
+    /// `focus_chain` changed during the current event.
+    focus_chain_changed: bool,
+
+    /// The paint order of this widget relative to its siblings.
+    z_index: i32,
+
+    /// Commands this widget or a descendant registered to handle, that the
+    /// parent has not yet passed on to the window.
+    added_handlers: Vec<(Selector, WidgetId)>,
+
+    /// The ids of this widget's descendants.
+    ///
+    /// This is kept up to date by `WidgetAdded` and `WidgetRemoved`, which
+    /// report the ids to the ancestors through `added_ids` and `removed_ids`.
+    children: Bloom<WidgetId>,
+
+    /// Ids added to `children` that the parent has not yet added to its own.
+    added_ids: Vec<WidgetId>,
+
+    /// Ids removed from `children` that the parent has not yet removed from
+    /// its own.
+    removed_ids: Vec<WidgetId>,
+}
+
+impl WidgetId {
+    /// Allocate a new, unique widget id.
+    pub fn next() -> WidgetId {
+        let id = WIDGET_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+        WidgetId(id)
+    }
+
+    /// The id of the base state at the root of a window's event handling,
+    /// which doesn't belong to a widget.
+    pub(crate) fn root() -> WidgetId {
+        WidgetId(0)
+    }
 }
 
 impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
@@ -103,14 +276,43 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
     /// so it can participate in layout and event flow. The process of
     /// adding a child widget to a container should call this method.
     pub fn new(inner: W) -> WidgetPod<T, W> {
+        WidgetPod::with_id(inner, WidgetId::next())
+    }
+
+    /// Create a new widget pod with a given id.
+    ///
+    /// This is useful when the id must be known before the widget is built,
+    /// for instance to send it commands.
+    pub fn with_id(inner: W, id: WidgetId) -> WidgetPod<T, W> {
         WidgetPod {
-            state: Default::default(),
+            state: BaseState::new(id),
             old_data: None,
             env: None,
             inner,
+            retained: None,
         }
     }
 
+    /// Builder-style method to retain what the widget paints.
+    ///
+    /// A retained widget is painted into a [`Raster`], which is drawn on
+    /// later paints instead of painting the widget and its descendants
+    /// again, until one of them is invalidated or updated, or the widget's
+    /// size changes. This suits subtrees that are expensive to paint and
+    /// rarely change. Whatever the widget paints outside of its bounds is
+    /// clipped, and a subtree that paints overlays is painted each time.
+    ///
+    /// [`Raster`]: struct.Raster.html
+    pub fn retained(mut self) -> Self {
+        self.retained = Some(RetainedPaint::default());
+        self
+    }
+
+    /// The id of the widget.
+    pub fn id(&self) -> WidgetId {
+        self.state.id
+    }
+
     /// Query the "active" state of the widget.
     pub fn is_active(&self) -> bool {
         self.state.is_active
@@ -126,6 +328,18 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         self.state.is_hot
     }
 
+    /// A count that changes whenever what the widget paints may have
+    /// changed, because it or a descendant was invalidated or updated.
+    pub(crate) fn changes(&self) -> u64 {
+        self.state.changes
+    }
+
+    /// The ids of this widget and its descendants that registered for
+    /// focus, in order.
+    pub(crate) fn focus_chain(&self) -> &[WidgetId] {
+        &self.state.focus_chain
+    }
+
     /// Return a reference to the inner widget.
     pub fn widget(&self) -> &W {
         &self.inner
@@ -133,6 +347,8 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
 
     /// Return a mutable reference to the inner widget.
     pub fn widget_mut(&mut self) -> &mut W {
+        // Changes made through this reference may change what it paints.
+        self.state.note_change();
         &mut self.inner
     }
 
@@ -151,6 +367,26 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         self.state.layout_rect
     }
 
+    /// Set the z-index of the widget.
+    ///
+    /// [`Flex`], [`List`] and [`Split`] paint children with a higher
+    /// z-index after (and so above) their siblings, and give them mouse
+    /// events first. Children with the same z-index are painted in order.
+    ///
+    /// [`Flex`]: widget/struct.Flex.html
+    /// [`List`]: widget/struct.List.html
+    /// [`Split`]: widget/struct.Split.html
+    pub fn set_z_index(&mut self, z_index: i32) {
+        self.state.z_index = z_index;
+    }
+
+    /// The z-index of the widget.
+    ///
+    /// See [`set_z_index`](#method.set_z_index).
+    pub fn z_index(&self) -> i32 {
+        self.state.z_index
+    }
+
     /// Paint a child widget.
     ///
     /// Generally called by container widgets as part of their [`paint`]
@@ -163,7 +399,9 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
     /// [`paint`]: trait.Widget.html#method.paint
     /// [`paint_with_offset`]: #method.paint_with_offset
     pub fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
-        self.inner.paint(paint_ctx, &self.state, data, &env);
+        let _path = WidgetPathGuard::enter(self.inner.type_name());
+        paint_ctx.note_painted(&self.state);
+        self.paint_inner(paint_ctx, data, env);
     }
 
     /// Paint the widget, translating it by the origin of its layout rectangle.
@@ -193,22 +431,73 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             return;
         }
 
-        if let Err(e) = paint_ctx.save() {
-            log::error!("saving render context failed: {:?}", e);
-            return;
-        }
-
+        let _path = WidgetPathGuard::enter(self.inner.type_name());
+        let span = tracing::trace_span!("paint", widget = self.inner.type_name());
+        let _span = span.enter();
         let layout_origin = self.state.layout_rect.origin().to_vec2();
-        paint_ctx.transform(Affine::translate(layout_origin));
-
         let visible = paint_ctx.region().to_rect() - layout_origin;
 
-        paint_ctx.with_child_ctx(visible, |ctx| {
-            self.inner.paint(ctx, &self.state, data, &env)
+        paint_ctx.transform_save(Affine::translate(layout_origin), |ctx| {
+            ctx.with_child_ctx(visible, |ctx| {
+                ctx.note_painted(&self.state);
+                self.paint_inner(ctx, data, env)
+            });
         });
+    }
 
-        if let Err(e) = paint_ctx.restore() {
-            log::error!("restoring render context failed: {:?}", e);
+    /// Paint the inner widget, or draw what it painted before if it is
+    /// retained and nothing has changed.
+    fn paint_inner(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let retained = match &mut self.retained {
+            // Capturing a widget inside the subtree needs it painted.
+            Some(retained) if paint_ctx.find_rect.is_none() => retained,
+            _ => return self.inner.paint(paint_ctx, &self.state, data, env),
+        };
+        let size = self.state.size();
+        let fresh = !self.state.needs_repaint
+            && retained
+                .raster
+                .as_ref()
+                .map_or(false, |raster| raster.fits(size, paint_ctx.scale()));
+        if fresh {
+            if let Some(raster) = &retained.raster {
+                raster.draw(paint_ctx);
+            }
+            let moved = paint_ctx.transform * retained.transform.inverse();
+            for node in &retained.automation {
+                let p0 = moved * node.rect.origin();
+                let p1 = moved * Point::new(node.rect.x1, node.rect.y1);
+                paint_ctx.automation.push(AutomationNode {
+                    rect: Rect::from_points(p0, p1),
+                    ..node.clone()
+                });
+            }
+            return;
+        }
+
+        let overlays = paint_ctx.overlays.len();
+        let nodes = paint_ctx.automation.len();
+        let (inner, state) = (&mut self.inner, &self.state);
+        match paint_ctx.rasterize(size, |ctx| inner.paint(ctx, state, data, env)) {
+            Some(raster) => {
+                raster.draw(paint_ctx);
+                // Overlays can't be drawn from the raster, so a subtree that
+                // paints them is painted again next time.
+                if paint_ctx.overlays.len() == overlays {
+                    retained.raster = Some(raster);
+                    retained.automation = paint_ctx.automation[nodes..].to_vec();
+                    retained.transform = paint_ctx.transform;
+                    self.state.needs_repaint = false;
+                } else {
+                    retained.raster = None;
+                }
+            }
+            None => {
+                paint_ctx.overlays.truncate(overlays);
+                paint_ctx.automation.truncate(nodes);
+                retained.raster = None;
+                self.inner.paint(paint_ctx, &self.state, data, env);
+            }
         }
     }
 
@@ -225,7 +514,22 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         data: &T,
         env: &Env,
     ) -> Size {
-        self.inner.layout(layout_ctx, bc, data, &env)
+        let _path = WidgetPathGuard::enter(self.inner.type_name());
+        // As in `update`, a request made while updating a child during
+        // layout is recorded on each pod it passes through.
+        let sibling_request_anim = std::mem::replace(&mut layout_ctx.request_anim, false);
+        let size = self.inner.layout(layout_ctx, bc, data, &env);
+        self.state.request_anim |= layout_ctx.request_anim;
+        layout_ctx.request_anim |= sibling_request_anim;
+        if cfg!(debug_assertions) && !bc.contains(size) {
+            log::warn!(
+                "{} returned size {:?}, which violates its constraints {:?}",
+                widget_path(),
+                size,
+                bc
+            );
+        }
+        size
     }
 
     /// Propagate an event.
@@ -244,23 +548,99 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             // from other points in the library.
             return;
         }
+        // Check this before building the child event, so that commands are
+        // only cloned for the widgets they are routed to.
+        if let Event::Command(cmd) = event {
+            if !self.routes_command(&cmd.selector) {
+                return;
+            }
+        }
+        if !self.state.is_added {
+            match event {
+                Event::LifeCycle(LifeCycle::WidgetAdded) => (),
+                // A widget that was never added has nothing to clean up.
+                Event::LifeCycle(LifeCycle::WidgetRemoved) => return,
+                _ => {
+                    let added = Event::LifeCycle(LifeCycle::WidgetAdded);
+                    self.event(ctx, &added, data, env);
+                }
+            }
+        }
+        let _path = WidgetPathGuard::enter(self.inner.type_name());
         let had_active = self.state.has_active;
         let mut child_ctx = EventCtx {
             win_ctx: ctx.win_ctx,
             cursor: ctx.cursor,
             command_queue: ctx.command_queue,
+            idle_queue: ctx.idle_queue,
             window: &ctx.window,
             window_id: ctx.window_id,
             base_state: &mut self.state,
             had_active,
             is_handled: false,
             is_root: false,
+            wheel_consumed: Vec2::new(0.0, 0.0),
+            spawner: ctx.spawner,
+            frame_timing: ctx.frame_timing,
+            store: ctx.store,
         };
         let rect = child_ctx.base_state.layout_rect;
         // Note: could also represent this as `Option<Event>`.
         let mut recurse = true;
         let mut hot_changed = None;
+        let mut focus_changed = None;
         let child_event = match event {
+            Event::LifeCycle(LifeCycle::WidgetAdded) => {
+                let state = &mut child_ctx.base_state;
+                recurse = !state.is_added;
+                if recurse {
+                    state.is_added = true;
+                    state.focus_chain.clear();
+                    state.focus_chain_changed = true;
+                    // The descendants add themselves again; this also resets
+                    // a saturated filter.
+                    state.children.clear();
+                    state.added_ids.push(state.id);
+                }
+                Event::LifeCycle(LifeCycle::WidgetAdded)
+            }
+            Event::LifeCycle(LifeCycle::WidgetRemoved) => {
+                let state = &mut child_ctx.base_state;
+                state.is_added = false;
+                state.has_focus = false;
+                let removed = mem::replace(&mut state.focus_chain, Vec::new());
+                state.removed_focus.extend(removed);
+                state.focus_chain_changed = true;
+                state.removed_ids.push(state.id);
+                // The events of the removed widgets' timers have nowhere to go.
+                state.new_timers.clear();
+                let timers = state.timers.drain();
+                state.cancelled_timers.extend(timers);
+                Event::LifeCycle(LifeCycle::WidgetRemoved)
+            }
+            Event::LifeCycle(LifeCycle::RouteFocusChanged { old, new }) => {
+                let state = &mut child_ctx.base_state;
+                // Only widgets in the focus chain can take focus, so the
+                // chain tells exactly which children lead to them.
+                let on_path = |target: &Option<WidgetId>| match target {
+                    Some(target) => {
+                        *target == state.id
+                            || (state.children.may_contain(target)
+                                && state.focus_chain.contains(target))
+                    }
+                    None => false,
+                };
+                let (had_old, has_new) = (on_path(old), on_path(new));
+                recurse = had_old || has_new;
+                if state.has_focus != has_new {
+                    state.has_focus = has_new;
+                    focus_changed = Some(has_new);
+                }
+                Event::LifeCycle(LifeCycle::RouteFocusChanged {
+                    old: *old,
+                    new: *new,
+                })
+            }
             Event::LifeCycle(event) => Event::LifeCycle(*event),
             Event::Size(size) => {
                 recurse = ctx.is_root;
@@ -296,19 +676,27 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 Event::MouseMoved(mouse_event)
             }
             Event::KeyDown(e) => {
-                recurse = child_ctx.base_state.has_focus;
+                // When no widget has focus, keys still reach the root widget,
+                // so that window-wide keys such as a menu bar's work. The
+                // window's state has focus if any widget in it does.
+                let unfocused_root = ctx.is_root && !ctx.base_state.has_focus;
+                recurse = child_ctx.base_state.has_focus || unfocused_root;
                 Event::KeyDown(*e)
             }
             Event::KeyUp(e) => {
-                recurse = child_ctx.base_state.has_focus;
+                let unfocused_root = ctx.is_root && !ctx.base_state.has_focus;
+                recurse = child_ctx.base_state.has_focus || unfocused_root;
                 Event::KeyUp(*e)
             }
+            Event::Mnemonic(c) => Event::Mnemonic(*c),
             Event::Paste(e) => {
                 recurse = child_ctx.base_state.has_focus;
                 Event::Paste(e.clone())
             }
             Event::Wheel(wheel_event) => {
-                recurse = had_active || child_ctx.base_state.is_hot;
+                // Wheel events follow the pointer, even while another widget
+                // is active, so they reach the scroll area under it.
+                recurse = child_ctx.base_state.is_hot;
                 Event::Wheel(wheel_event.clone())
             }
             Event::Zoom(zoom) => {
@@ -316,24 +704,34 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 Event::Zoom(*zoom)
             }
             Event::HotChanged(is_hot) => Event::HotChanged(*is_hot),
-            Event::FocusChanged(_is_focused) => {
-                let had_focus = child_ctx.base_state.has_focus;
-                let focus = child_ctx.base_state.request_focus;
-                child_ctx.base_state.request_focus = false;
-                child_ctx.base_state.has_focus = focus;
-                recurse = focus || had_focus;
-                Event::FocusChanged(focus)
-            }
+            Event::FocusChanged(is_focused) => Event::FocusChanged(*is_focused),
             Event::AnimFrame(interval) => {
                 recurse = child_ctx.base_state.request_anim;
                 child_ctx.base_state.request_anim = false;
                 Event::AnimFrame(*interval)
             }
             Event::Timer(id) => {
-                recurse = child_ctx.base_state.request_timer;
+                recurse = child_ctx.base_state.timers.remove(id);
                 Event::Timer(*id)
             }
+            Event::Idle(token) => {
+                recurse = child_ctx.base_state.idle.remove(token);
+                Event::Idle(*token)
+            }
             Event::Command(cmd) => Event::Command(cmd.clone()),
+            Event::TargetedCommand(Target::Widget(id), cmd) if *id == child_ctx.base_state.id => {
+                Event::Command(cmd.clone())
+            }
+            Event::TargetedCommand(target, cmd) => {
+                if let Target::Widget(id) = target {
+                    recurse = child_ctx.base_state.children.may_contain(id);
+                }
+                Event::TargetedCommand(*target, cmd.clone())
+            }
+            Event::ValidateCommand(selector) => {
+                recurse = child_ctx.base_state.has_focus;
+                Event::ValidateCommand(selector.clone())
+            }
         };
         child_ctx.base_state.needs_inval = false;
         if let Some(is_hot) = hot_changed {
@@ -341,18 +739,75 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             self.inner
                 .event(&mut child_ctx, &hot_changed_event, data, &env);
         }
+        if let Some(is_focused) = focus_changed {
+            let focus_changed_event = Event::FocusChanged(is_focused);
+            self.inner
+                .event(&mut child_ctx, &focus_changed_event, data, &env);
+        }
         if recurse {
             child_ctx.base_state.has_active = false;
             self.inner.event(&mut child_ctx, &child_event, data, &env);
             child_ctx.base_state.has_active |= child_ctx.base_state.is_active;
         };
+        if child_ctx.base_state.needs_inval {
+            child_ctx.base_state.note_change();
+        }
         ctx.base_state.needs_inval |= child_ctx.base_state.needs_inval;
         ctx.base_state.request_anim |= child_ctx.base_state.request_anim;
-        ctx.base_state.request_timer |= child_ctx.base_state.request_timer;
+        for token in child_ctx.base_state.new_timers.drain(..) {
+            ctx.base_state.timers.insert(token);
+            ctx.base_state.new_timers.push(token);
+        }
+        for token in child_ctx.base_state.cancelled_timers.drain(..) {
+            ctx.base_state.timers.remove(&token);
+            ctx.base_state.new_timers.retain(|t| *t != token);
+            ctx.base_state.cancelled_timers.push(token);
+        }
+        ctx.base_state.idle.extend(&child_ctx.base_state.idle);
         ctx.base_state.is_hot |= child_ctx.base_state.is_hot;
         ctx.base_state.has_active |= child_ctx.base_state.has_active;
-        ctx.base_state.request_focus |= child_ctx.base_state.request_focus;
+        if let Some(change) = child_ctx.base_state.request_focus.take() {
+            ctx.base_state.request_focus = Some(change);
+        }
+        if child_ctx.base_state.focus_chain_changed {
+            child_ctx.base_state.focus_chain_changed = false;
+            let removed = mem::replace(&mut child_ctx.base_state.removed_focus, Vec::new());
+            let chain = &mut ctx.base_state.focus_chain;
+            chain.retain(|id| !removed.contains(id));
+            for id in &child_ctx.base_state.focus_chain {
+                if !chain.contains(id) {
+                    chain.push(*id);
+                }
+            }
+            ctx.base_state.removed_focus.extend(removed);
+            ctx.base_state.focus_chain_changed = true;
+        }
+        for id in child_ctx.base_state.added_ids.drain(..) {
+            ctx.base_state.children.add(&id);
+            ctx.base_state.added_ids.push(id);
+        }
+        for id in child_ctx.base_state.removed_ids.drain(..) {
+            ctx.base_state.children.remove(&id);
+            ctx.base_state.removed_ids.push(id);
+        }
+        ctx.base_state
+            .added_handlers
+            .extend(child_ctx.base_state.added_handlers.drain(..));
         ctx.is_handled |= child_ctx.is_handled;
+        ctx.wheel_consumed += child_ctx.wheel_consumed;
+    }
+
+    /// Whether a command with this selector should be delivered to this
+    /// widget.
+    ///
+    /// The standard editing commands go to the focused widget. Commands that
+    /// widgets registered for are sent by the window to each of them as a
+    /// [`Event::TargetedCommand`], so the commands that get here go to every
+    /// widget.
+    ///
+    /// [`Event::TargetedCommand`]: enum.Event.html#variant.TargetedCommand
+    fn routes_command(&self, selector: &Selector) -> bool {
+        !sys_cmd::FOCUS_COMMANDS.contains(selector) || self.state.has_focus
     }
 
     /// Propagate a data update.
@@ -367,6 +822,23 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         } else {
             false
         };
+        self.update_with_data_same(ctx, data_same, data, env);
+    }
+
+    /// The data from the last update, if there has been one.
+    pub(crate) fn old_data(&self) -> Option<&T> {
+        self.old_data.as_ref()
+    }
+
+    /// Propagate a data update, when it is already known whether the data
+    /// is the same as in the last update.
+    pub(crate) fn update_with_data_same(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        data_same: bool,
+        data: &T,
+        env: &Env,
+    ) {
         let env_same = if let Some(ref old_env) = self.env {
             old_env.same(env)
         } else {
@@ -376,7 +848,16 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         if data_same && env_same {
             return;
         }
+        let _path = WidgetPathGuard::enter(self.inner.type_name());
+        // Animation frames are only sent down the widgets that asked for
+        // them, so a request is recorded on each pod it passes through.
+        let sibling_request_anim = std::mem::replace(&mut ctx.request_anim, false);
         self.inner.update(ctx, self.old_data.as_ref(), data, env);
+        // Widgets don't always invalidate when their data changes, since a
+        // paint follows anyway.
+        self.state.note_change();
+        self.state.request_anim |= ctx.request_anim;
+        ctx.request_anim |= sibling_request_anim;
         self.old_data = Some(data.clone());
         self.env = Some(env.clone());
     }
@@ -393,11 +874,60 @@ impl<T: Data, W: Widget<T> + 'static> WidgetPod<T, W> {
             old_data: self.old_data,
             env: self.env,
             inner: Box::new(self.inner),
+            retained: self.retained,
         }
     }
 }
 
 impl BaseState {
+    pub(crate) fn id(&self) -> WidgetId {
+        self.id
+    }
+
+    pub(crate) fn new(id: WidgetId) -> BaseState {
+        BaseState {
+            id,
+            layout_rect: Rect::ZERO,
+            needs_inval: false,
+            needs_repaint: true,
+            changes: 0,
+            is_hot: false,
+            is_active: false,
+            has_active: false,
+            request_anim: false,
+            timers: HashSet::new(),
+            new_timers: Vec::new(),
+            cancelled_timers: Vec::new(),
+            idle: HashSet::new(),
+            has_focus: false,
+            request_focus: None,
+            is_added: false,
+            focus_chain: Vec::new(),
+            removed_focus: Vec::new(),
+            focus_chain_changed: false,
+            z_index: 0,
+            added_handlers: Vec::new(),
+            children: Bloom::new(),
+            added_ids: Vec::new(),
+            removed_ids: Vec::new(),
+        }
+    }
+
+    /// Note that what the widget paints may have changed.
+    fn note_change(&mut self) {
+        self.needs_repaint = true;
+        self.changes += 1;
+    }
+
+    /// Take the command registrations and the removed widgets that the
+    /// window should know of.
+    pub(crate) fn take_handler_changes(&mut self) -> (Vec<(Selector, WidgetId)>, Vec<WidgetId>) {
+        (
+            mem::replace(&mut self.added_handlers, Vec::new()),
+            mem::replace(&mut self.removed_ids, Vec::new()),
+        )
+    }
+
     /// The "hot" (aka hover) status of a widget.
     ///
     /// A widget is "hot" when the mouse is hovered over it. Widgets will
@@ -434,22 +964,31 @@ impl BaseState {
     ///
     /// Focus means that the widget receives keyboard events.
     ///
-    /// A widget can request focus using the [`request_focus`] method.
-    /// This will generally result in a separate event propagation of
-    /// a `FocusChanged` method, including sending `false` to the previous
-    /// widget that held focus.
+    /// A widget can request focus using the [`request_focus`] method, or
+    /// move focus along the focus chain with [`focus_next`]. The window
+    /// then routes a `LifeCycle::RouteFocusChanged` event to the widgets
+    /// involved, and each widget whose focus status changed is sent a
+    /// `FocusChanged` event, including `false` to the previous widget that
+    /// held focus.
     ///
     /// Only one leaf widget at a time has focus. However, in a container
-    /// hierarchy, all ancestors of that leaf widget are also invoked with
-    /// `FocusChanged(true)`.
+    /// hierarchy, all ancestors of that leaf widget also have focus.
     ///
     /// Discussion question: is "is_focused" a better name?
     ///
     /// [`request_focus`]: struct.EventCtx.html#method.request_focus
+    /// [`focus_next`]: struct.EventCtx.html#method.focus_next
     pub fn has_focus(&self) -> bool {
         self.has_focus
     }
 
+    /// The z-index of the widget.
+    ///
+    /// See [`WidgetPod::set_z_index`](struct.WidgetPod.html#method.set_z_index).
+    pub fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
     /// The layout size.
     ///
     /// This is the layout size as ultimately determined by the parent
@@ -474,6 +1013,28 @@ pub struct PaintCtx<'a, 'b: 'a> {
     pub window_id: WindowId,
     /// The currently visible region.
     pub(crate) region: Region,
+    /// The transform from this context to window coordinates, as applied
+    /// with [`transform_save`](#method.transform_save).
+    pub(crate) transform: Affine,
+    /// Overlays to paint after the widget tree.
+    pub(crate) overlays: Vec<Overlay>,
+    pub(crate) resources: &'a Resources,
+    /// The widgets with automation ids painted so far.
+    pub(crate) automation: &'a mut Vec<AutomationNode>,
+    /// A widget whose bounds in window coordinates are wanted, and where to
+    /// put them once it is painted.
+    pub(crate) find_rect: Option<(WidgetId, &'a mut Option<Rect>)>,
+    /// The number of pixels per display point.
+    pub(crate) scale: f64,
+    /// The color space of the window being painted.
+    pub(crate) color_space: ColorSpace,
+}
+
+/// A paint operation deferred to the overlay pass.
+pub(crate) struct Overlay {
+    z_index: i32,
+    transform: Affine,
+    paint: Box<dyn FnOnce(&mut PaintCtx)>,
 }
 
 /// A region of a widget, generally used to describe what needs to be drawn.
@@ -522,6 +1083,12 @@ impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
         &self.region
     }
 
+    /// The resources shared by all of the application's windows, as used
+    /// by this one.
+    pub fn resources(&self) -> WindowResources<'a> {
+        self.resources.for_window(self.window_id)
+    }
+
     /// Creates a temporary `PaintCtx` with a new visible region, and calls
     /// the provided function with that `PaintCtx`.
     ///
@@ -531,14 +1098,215 @@ impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
         let PaintCtx {
             render_ctx,
             window_id,
+            transform,
+            overlays,
+            resources,
+            automation,
+            find_rect,
+            scale,
+            color_space,
             ..
         } = self;
         let mut child_ctx = PaintCtx {
             render_ctx,
             window_id: *window_id,
             region: region.into(),
+            transform: *transform,
+            overlays: Vec::new(),
+            resources,
+            automation,
+            find_rect: find_rect.as_mut().map(|(id, rect)| (*id, &mut **rect)),
+            scale: *scale,
+            color_space: *color_space,
+        };
+        f(&mut child_ctx);
+        overlays.append(&mut child_ctx.overlays);
+    }
+
+    /// The number of pixels per display point of the window being painted.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The color space of the window being painted.
+    ///
+    /// Colors and images painted are taken to be in this color space. The
+    /// theme's colors are converted to it already; other colors and images
+    /// in sRGB should be converted with [`ColorSpace::convert`] and
+    /// [`ImageBuf::to_color_space`].
+    ///
+    /// [`ColorSpace::convert`]: enum.ColorSpace.html#method.convert
+    /// [`ImageBuf::to_color_space`]: struct.ImageBuf.html#method.to_color_space
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Paint into a [`Raster`] instead of the window, and return it.
+    ///
+    /// The provided function paints content `size` display points large,
+    /// with its top left at the origin, at the window's scale. Overlays it
+    /// adds are painted with the window as usual. Returns `None`, after
+    /// logging the error, if the raster can't be made.
+    ///
+    /// [`Raster`]: struct.Raster.html
+    pub fn rasterize(&mut self, size: Size, f: impl FnOnce(&mut PaintCtx)) -> Option<Raster> {
+        let width = (size.width * self.scale).ceil() as usize;
+        let height = (size.height * self.scale).ceil() as usize;
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let mut device = match Device::new() {
+            Ok(device) => device,
+            Err(e) => {
+                log::error!("failed to create a device for a raster: {:?}", e);
+                return None;
+            }
+        };
+        let mut target = match device.bitmap_target(width, height, self.scale) {
+            Ok(target) => target,
+            Err(e) => {
+                log::error!("failed to create a bitmap for a raster: {:?}", e);
+                return None;
+            }
         };
-        f(&mut child_ctx)
+        {
+            let mut piet = target.render_context();
+            let mut ctx = PaintCtx {
+                render_ctx: &mut piet,
+                window_id: self.window_id,
+                region: Rect::from_origin_size(Point::ORIGIN, size).into(),
+                transform: self.transform,
+                overlays: Vec::new(),
+                resources: self.resources,
+                automation: &mut *self.automation,
+                find_rect: None,
+                scale: self.scale,
+                color_space: self.color_space,
+            };
+            f(&mut ctx);
+            self.overlays.append(&mut ctx.overlays);
+            if let Err(e) = piet.finish() {
+                log::error!("failed to paint a raster: {:?}", e);
+                return None;
+            }
+        }
+        let pixels = match target.into_raw_pixels(ImageFormat::RgbaPremul) {
+            Ok(pixels) => pixels,
+            Err(e) => {
+                log::error!("failed to read back the pixels of a raster: {:?}", e);
+                return None;
+            }
+        };
+        match self
+            .render_ctx
+            .make_image(width, height, &pixels, ImageFormat::RgbaPremul)
+        {
+            Ok(image) => Some(Raster::new(image, size, self.scale)),
+            Err(e) => {
+                log::error!("failed to make an image from a raster: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the state of the render context, calls the provided function,
+    /// and then restores the state.
+    ///
+    /// Any transform or clip applied by the function is undone afterwards.
+    /// If the state can't be saved, the function is not called. Failures
+    /// are reported as a [`PaintError`].
+    ///
+    /// [`PaintError`]: enum.PaintError.html
+    pub fn with_save(&mut self, f: impl FnOnce(&mut PaintCtx)) {
+        if let Err(e) = self.render_ctx.save() {
+            report_paint_error(PaintError::Save(e));
+            return;
+        }
+
+        f(self);
+
+        if let Err(e) = self.render_ctx.restore() {
+            report_paint_error(PaintError::Restore(e));
+        }
+    }
+
+    /// Like [`with_save`], but applies `transform` before calling the
+    /// provided function.
+    ///
+    /// Unlike transforms applied directly to the render context, this
+    /// transform is taken into account by [`paint_overlay`].
+    ///
+    /// [`with_save`]: #method.with_save
+    /// [`paint_overlay`]: #method.paint_overlay
+    pub fn transform_save(&mut self, transform: Affine, f: impl FnOnce(&mut PaintCtx)) {
+        self.with_save(|ctx| {
+            let prev = ctx.transform;
+            ctx.transform = prev * transform;
+            ctx.render_ctx.transform(transform);
+            f(ctx);
+            ctx.transform = prev;
+        })
+    }
+
+    /// Paint something above the rest of the window, such as a tooltip, a
+    /// drag image, or a dropdown.
+    ///
+    /// The provided function is called after the whole widget tree has been
+    /// painted, in the current coordinate space but without any clip, so it
+    /// can draw outside the widget's bounds. Overlays with a higher
+    /// `z_index` are painted later; overlays with the same `z_index` are
+    /// painted in the order they were added.
+    pub fn paint_overlay(&mut self, z_index: i32, f: impl FnOnce(&mut PaintCtx) + 'static) {
+        self.overlays.push(Overlay {
+            z_index,
+            transform: self.transform,
+            paint: Box::new(f),
+        });
+    }
+
+    /// Record that a widget tagged with an automation id was painted.
+    pub(crate) fn add_automation_node(&mut self, id: &str, base_state: &BaseState) {
+        let rect = self.window_rect(base_state);
+        self.automation.push(AutomationNode {
+            id: id.to_owned(),
+            window_id: self.window_id,
+            widget_id: base_state.id(),
+            rect,
+        });
+    }
+
+    /// Record the bounds of a widget about to be painted, if they are wanted.
+    fn note_painted(&mut self, base_state: &BaseState) {
+        let rect = self.window_rect(base_state);
+        if let Some((id, found)) = &mut self.find_rect {
+            if *id == base_state.id() {
+                **found = Some(rect);
+            }
+        }
+    }
+
+    /// The bounds of a widget painted in this context, in window coordinates.
+    fn window_rect(&self, base_state: &BaseState) -> Rect {
+        let size = base_state.size();
+        let p0 = self.transform * Point::ORIGIN;
+        let p1 = self.transform * Point::new(size.width, size.height);
+        Rect::from_points(p0, p1)
+    }
+
+    /// Paint the overlays added while painting the widget tree.
+    pub(crate) fn paint_overlays(&mut self, region: Rect) {
+        // Overlays may add overlays of their own.
+        while !self.overlays.is_empty() {
+            let mut overlays = std::mem::replace(&mut self.overlays, Vec::new());
+            // The sort is stable, so equal z-indices keep their order.
+            overlays.sort_by_key(|overlay| overlay.z_index);
+            for overlay in overlays {
+                let Overlay {
+                    transform, paint, ..
+                } = overlay;
+                self.with_child_ctx(region, |ctx| ctx.transform_save(transform, paint));
+            }
+        }
     }
 }
 
@@ -546,10 +1314,20 @@ impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
 ///
 /// As of now, the main service provided is access to a factory for
 /// creating text layout objects, which are likely to be useful
-/// during widget layout.
+/// during widget layout. The factory also implements [`TextMetrics`],
+/// for measuring fonts, to size and align text precisely.
+///
+/// [`TextMetrics`]: trait.TextMetrics.html
 pub struct LayoutCtx<'a, 'b: 'a> {
     pub(crate) text_factory: &'a mut Text<'b>,
+    pub(crate) window: &'a WindowHandle,
     pub(crate) window_id: WindowId,
+    /// The number of device pixels per display point.
+    pub(crate) scale: f64,
+    pub(crate) resources: &'a Resources,
+    pub(crate) store: &'a Store,
+    /// Whether a child updated during layout requested an animation frame.
+    pub(crate) request_anim: bool,
 }
 
 /// A mutable context provided to event handling methods of widgets.
@@ -564,7 +1342,9 @@ pub struct EventCtx<'a, 'b> {
     pub(crate) win_ctx: &'a mut dyn WinCtx<'b>,
     pub(crate) cursor: &'a mut Option<Cursor>,
     /// Commands submitted to be run after this event.
-    pub(crate) command_queue: &'a mut VecDeque<(WindowId, Command)>,
+    pub(crate) command_queue: &'a mut VecDeque<(Target, Command)>,
+    /// Idle work scheduled for this window.
+    pub(crate) idle_queue: &'a mut Vec<IdleToken>,
     pub(crate) window_id: WindowId,
     // TODO: migrate most usage of `WindowHandle` to `WinCtx` instead.
     pub(crate) window: &'a WindowHandle,
@@ -572,6 +1352,12 @@ pub struct EventCtx<'a, 'b> {
     pub(crate) had_active: bool,
     pub(crate) is_handled: bool,
     pub(crate) is_root: bool,
+    /// The part of the delta of a wheel event used by descendants.
+    pub(crate) wheel_consumed: Vec2,
+    pub(crate) spawner: &'a Spawner,
+    /// The timing of the frame being prepared, during an animation frame.
+    pub(crate) frame_timing: Option<FrameTiming>,
+    pub(crate) store: &'a Store,
 }
 
 /// A mutable context provided to data update methods of widgets.
@@ -588,7 +1374,10 @@ pub struct UpdateCtx<'a, 'b: 'a> {
     // `EventCtx` (and possibly using the same structure). But for
     // now keep it super-simple.
     pub(crate) needs_inval: bool,
+    /// Whether the widget being updated asked for an animation frame.
+    pub(crate) request_anim: bool,
     pub(crate) window_id: WindowId,
+    pub(crate) store: &'a Store,
 }
 
 impl<'a, 'b> EventCtx<'a, 'b> {
@@ -610,6 +1399,18 @@ impl<'a, 'b> EventCtx<'a, 'b> {
         self.win_ctx.text_factory()
     }
 
+    /// When the frame being prepared is expected to be shown, and how often
+    /// the display refreshes.
+    ///
+    /// This is only known while handling [`Event::AnimFrame`], and only on
+    /// platforms that report it. Animations can advance to the presentation
+    /// time, so that they are where they should be when they are seen.
+    ///
+    /// [`Event::AnimFrame`]: enum.Event.html#variant.AnimFrame
+    pub fn frame_timing(&self) -> Option<FrameTiming> {
+        self.frame_timing
+    }
+
     /// Set the cursor icon.
     ///
     /// Call this when handling a mouse move event, to set the cursor for the
@@ -671,6 +1472,26 @@ impl<'a, 'b> EventCtx<'a, 'b> {
         self.is_handled
     }
 
+    /// Record that part of the delta of a wheel event has been used, for
+    /// instance to scroll.
+    ///
+    /// Scrolling containers use this to chain scrolling: a container only
+    /// scrolls by the part of the delta that its descendants did not use,
+    /// and only sets the event as handled once all of it is used. This
+    /// way, once a nested scroll area reaches its end, the enclosing one
+    /// continues scrolling.
+    pub fn consume_wheel(&mut self, delta: Vec2) {
+        self.wheel_consumed += delta;
+    }
+
+    /// The part of the delta of this wheel event that has not been used by
+    /// this widget or its descendants.
+    ///
+    /// See [`consume_wheel`](#method.consume_wheel).
+    pub fn wheel_remaining(&self, event: &WheelEvent) -> Vec2 {
+        event.delta - self.wheel_consumed
+    }
+
     /// Query the focus state of the widget.
     ///
     /// See [`BaseState::has_focus`](struct.BaseState.html#method.has_focus).
@@ -680,9 +1501,56 @@ impl<'a, 'b> EventCtx<'a, 'b> {
 
     /// Request keyboard focus.
     ///
+    /// This also registers the widget for focus, as only widgets in the
+    /// focus chain can take it; see [`register_for_focus`].
+    ///
     /// Discussion question: is method needed in contexts other than event?
+    ///
+    /// [`register_for_focus`]: #method.register_for_focus
     pub fn request_focus(&mut self) {
-        self.base_state.request_focus = true;
+        self.register_for_focus();
+        self.base_state.request_focus = Some(FocusChange::Focus(self.base_state.id));
+    }
+
+    /// Give keyboard focus to another widget, such as the field a label
+    /// names. Nothing happens if that widget isn't in the focus chain.
+    pub fn set_focus(&mut self, target: WidgetId) {
+        self.base_state.request_focus = Some(FocusChange::Focus(target));
+    }
+
+    /// Move focus to the next widget in the window's focus chain.
+    ///
+    /// The focus chain holds the widgets that called [`register_for_focus`],
+    /// in the order they were added to the tree. From the last widget, focus
+    /// moves to the first.
+    ///
+    /// [`register_for_focus`]: #method.register_for_focus
+    pub fn focus_next(&mut self) {
+        self.base_state.request_focus = Some(FocusChange::Next);
+    }
+
+    /// Move focus to the previous widget in the window's focus chain.
+    ///
+    /// See [`focus_next`](#method.focus_next).
+    pub fn focus_prev(&mut self) {
+        self.base_state.request_focus = Some(FocusChange::Previous);
+    }
+
+    /// Add this widget to the window's focus chain, so that it can be
+    /// reached with [`focus_next`] and [`focus_prev`].
+    ///
+    /// This should be called in response to [`LifeCycle::WidgetAdded`]. The
+    /// widget leaves the chain when it is removed.
+    ///
+    /// [`focus_next`]: #method.focus_next
+    /// [`focus_prev`]: #method.focus_prev
+    /// [`LifeCycle::WidgetAdded`]: enum.LifeCycle.html#variant.WidgetAdded
+    pub fn register_for_focus(&mut self) {
+        let id = self.base_state.id;
+        if !self.base_state.focus_chain.contains(&id) {
+            self.base_state.focus_chain.insert(0, id);
+            self.base_state.focus_chain_changed = true;
+        }
     }
 
     /// Request an animation frame.
@@ -693,10 +1561,36 @@ impl<'a, 'b> EventCtx<'a, 'b> {
     /// Request a timer event.
     ///
     /// The return value is a token, which can be used to associate the
-    /// request with the event.
+    /// request with the event. The event is delivered only to this widget
+    /// and its ancestors.
     pub fn request_timer(&mut self, deadline: Instant) -> TimerToken {
-        self.base_state.request_timer = true;
-        self.win_ctx.request_timer(deadline)
+        let token = self.win_ctx.request_timer(deadline);
+        self.base_state.timers.insert(token);
+        self.base_state.new_timers.push(token);
+        token
+    }
+
+    /// Cancel a timer requested by this widget.
+    ///
+    /// The timer event will not be delivered to this widget, and its
+    /// ancestors stop routing it. Cancelling a timer that has already fired
+    /// has no effect.
+    pub fn cancel_timer(&mut self, token: TimerToken) {
+        self.base_state.timers.remove(&token);
+        self.base_state.new_timers.retain(|t| *t != token);
+        self.base_state.cancelled_timers.push(token);
+    }
+
+    /// Schedule non-urgent work.
+    ///
+    /// An [`Event::Idle`] with this token will be delivered once pending
+    /// input has been handled, so that expensive work (such as building
+    /// layouts for offscreen content) doesn't delay the response to the user.
+    ///
+    /// [`Event::Idle`]: enum.Event.html#variant.Idle
+    pub fn schedule_idle(&mut self, token: IdleToken) {
+        self.base_state.idle.insert(token);
+        self.idle_queue.push(token);
     }
 
     /// Returns the layout size of the current widget.
@@ -704,27 +1598,168 @@ impl<'a, 'b> EventCtx<'a, 'b> {
         self.base_state.size()
     }
 
+    /// Set the z-index of the current widget, for instance to raise it above
+    /// its siblings while it is being dragged.
+    ///
+    /// See [`WidgetPod::set_z_index`](struct.WidgetPod.html#method.set_z_index).
+    pub fn set_z_index(&mut self, z_index: i32) {
+        if self.base_state.z_index != z_index {
+            self.base_state.z_index = z_index;
+            self.base_state.needs_inval = true;
+        }
+    }
+
+    /// Register this widget to handle [`Command`]s with the given selector.
+    ///
+    /// Once any widget in a window has registered for a selector, commands
+    /// with that selector are delivered only to the registered widgets,
+    /// instead of to the whole widget tree. They are offered to one widget
+    /// after another until one handles them; each command starts with the
+    /// widget after the one the previous command started with, so that one
+    /// busy widget doesn't always go first.
+    ///
+    /// Registrations end when the widget is removed, so a good place to
+    /// call this is in response to [`LifeCycle::WidgetAdded`], which is sent
+    /// again if the widget is added back.
+    ///
+    /// [`Command`]: struct.Command.html
+    /// [`LifeCycle::WidgetAdded`]: enum.LifeCycle.html#variant.WidgetAdded
+    pub fn register_for_command(&mut self, selector: Selector) {
+        let id = self.base_state.id;
+        self.base_state.added_handlers.push((selector, id));
+    }
+
     /// Submit a [`Command`] to be run after this event is handled.
     ///
     /// Commands are run in the order they are submitted; all commands
     /// submitted during the handling of an event are executed before
     /// the [`update()`] method is called.
     ///
+    /// The target can be a [`Target`], a [`WindowId`] or a [`WidgetId`];
+    /// if it is `None`, the command is sent to this widget's window.
+    ///
     /// [`Command`]: struct.Command.html
     /// [`update()`]: trait.Widget.html#tymethod.update
+    /// [`Target`]: enum.Target.html
+    /// [`WindowId`]: struct.WindowId.html
+    /// [`WidgetId`]: struct.WidgetId.html
     pub fn submit_command(
         &mut self,
         command: impl Into<Command>,
-        window_id: impl Into<Option<WindowId>>,
+        target: impl Into<Option<Target>>,
     ) {
-        let window_id = window_id.into().unwrap_or(self.window_id);
-        self.command_queue.push_back((window_id, command.into()))
+        let target = target.into().unwrap_or(Target::Window(self.window_id));
+        self.command_queue.push_back((target, command.into()))
+    }
+
+    /// Show a context menu at `location`, in window coordinates, such as the
+    /// [`window_pos`] of a right click.
+    ///
+    /// The menu's items send their commands to this widget's window. `T`
+    /// must be the application's data type, or the menu is dropped.
+    ///
+    /// [`window_pos`]: struct.MouseEvent.html#structfield.window_pos
+    pub fn show_context_menu<T: Any>(&mut self, menu: MenuDesc<T>, location: Point) {
+        let menu = ContextMenu::new(menu, location);
+        self.submit_command(Command::new(sys_cmd::SHOW_CONTEXT_MENU, menu), None);
+    }
+
+    /// Show a native dialog for choosing a file to open.
+    ///
+    /// If the user chooses a file, an [`OPEN_FILE`] command is sent to this
+    /// widget's window, with a [`FileInfo`] argument; with
+    /// [`FileDialogOptions::multi_selection`] it may hold several paths.
+    ///
+    /// [`OPEN_FILE`]: commands/constant.OPEN_FILE.html
+    /// [`FileInfo`]: struct.FileInfo.html
+    /// [`FileDialogOptions::multi_selection`]: struct.FileDialogOptions.html#method.multi_selection
+    pub fn open_file(&mut self, options: FileDialogOptions) {
+        self.submit_command(Command::new(sys_cmd::SHOW_OPEN_PANEL, options), None);
+    }
+
+    /// Show a native dialog for choosing where to save a file.
+    ///
+    /// If the user chooses a path, a [`SAVE_FILE`] command is sent to this
+    /// widget's window, with a [`FileInfo`] argument.
+    ///
+    /// [`SAVE_FILE`]: commands/constant.SAVE_FILE.html
+    /// [`FileInfo`]: struct.FileInfo.html
+    pub fn save_file(&mut self, options: FileDialogOptions) {
+        self.submit_command(Command::new(sys_cmd::SHOW_SAVE_PANEL, options), None);
+    }
+
+    /// Show a modal message dialog, such as a confirmation.
+    ///
+    /// The dialog's commands are sent to this widget's window once the user
+    /// answers; see [`MessageDialog`].
+    ///
+    /// [`MessageDialog`]: struct.MessageDialog.html
+    pub fn show_message_dialog(&mut self, dialog: MessageDialog) {
+        self.submit_command(Command::new(sys_cmd::SHOW_MESSAGE_DIALOG, dialog), None);
+    }
+
+    /// Ask screen readers to speak `text`, without moving focus.
+    ///
+    /// This is for changes that would otherwise go unnoticed by someone
+    /// using a screen reader, such as "3 results found" or "file saved".
+    ///
+    /// # Platform support
+    ///
+    /// Announcements are only made on macOS. On Windows and Linux the text
+    /// is dropped, and a warning is logged the first time, so anything
+    /// announced should also be shown in a widget. See
+    /// [`WindowHandle::announce`] for why.
+    ///
+    /// [`WindowHandle::announce`]: struct.WindowHandle.html#method.announce
+    pub fn announce(&mut self, text: &str, politeness: Politeness) {
+        self.window.announce(text, politeness);
+    }
+
+    /// Run a future off the UI thread, and submit a [`Command`] with
+    /// `selector` and the future's output once it completes.
+    ///
+    /// The future is run by the application's [`Executor`]. Its output is
+    /// the command's argument, as with [`ExtEventSink::submit_command`].
+    /// If no `target` is given, the command is sent to this widget's window.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// const FETCHED: Selector = Selector::new("my-app.fetched");
+    ///
+    /// ctx.spawn_future(async move { fetch(url).await }, FETCHED, None);
+    /// ```
+    ///
+    /// [`Command`]: struct.Command.html
+    /// [`Executor`]: trait.Executor.html
+    /// [`ExtEventSink::submit_command`]: struct.ExtEventSink.html#method.submit_command
+    pub fn spawn_future<F>(
+        &mut self,
+        future: F,
+        selector: Selector,
+        target: impl Into<Option<Target>>,
+    ) where
+        F: Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        let target = target.into().unwrap_or(Target::Window(self.window_id));
+        self.spawner.spawn(future, selector, target);
+    }
+
+    /// The id of this widget.
+    pub fn widget_id(&self) -> WidgetId {
+        self.base_state.id
     }
 
     /// Get the window id.
     pub fn window_id(&self) -> WindowId {
         self.window_id
     }
+
+    /// The application's saved state.
+    pub fn store(&self) -> &'a Store {
+        self.store
+    }
 }
 
 impl<'a, 'b> LayoutCtx<'a, 'b> {
@@ -737,6 +1772,55 @@ impl<'a, 'b> LayoutCtx<'a, 'b> {
     pub fn window_id(&self) -> WindowId {
         self.window_id
     }
+
+    /// The scale factor of the window, in device pixels per display point.
+    ///
+    /// This can be passed to [`BoxConstraints::constrain_to_pixels`] to
+    /// keep a widget's size on the pixel grid.
+    ///
+    /// [`BoxConstraints::constrain_to_pixels`]: struct.BoxConstraints.html#method.constrain_to_pixels
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The resources shared by all of the application's windows, as used
+    /// by this one.
+    pub fn resources(&self) -> WindowResources<'a> {
+        self.resources.for_window(self.window_id)
+    }
+
+    /// The application's saved state.
+    pub fn store(&self) -> &'a Store {
+        self.store
+    }
+
+    /// Bring a child up to date with the data and environment.
+    ///
+    /// Children are normally updated in `update`. Containers that choose
+    /// their children during layout, such as [`Responsive`], call this for
+    /// a child that was just built, or that missed updates while it was
+    /// hidden, before laying it out. Nothing happens if the child is up to
+    /// date.
+    ///
+    /// [`Responsive`]: widget/struct.Responsive.html
+    pub fn update_child<T: Data, W: Widget<T>>(
+        &mut self,
+        child: &mut WidgetPod<T, W>,
+        data: &T,
+        env: &Env,
+    ) {
+        let mut update_ctx = UpdateCtx {
+            text_factory: &mut *self.text_factory,
+            window: self.window,
+            // Layout is followed by a paint anyway.
+            needs_inval: false,
+            request_anim: false,
+            window_id: self.window_id,
+            store: self.store,
+        };
+        child.update(&mut update_ctx, data, env);
+        self.request_anim |= update_ctx.request_anim;
+    }
 }
 
 impl<'a, 'b> UpdateCtx<'a, 'b> {
@@ -748,6 +1832,14 @@ impl<'a, 'b> UpdateCtx<'a, 'b> {
         self.needs_inval = true;
     }
 
+    /// Request an animation frame.
+    ///
+    /// This lets a widget start animating in response to a change of the
+    /// data, such as a transition between views.
+    pub fn request_anim_frame(&mut self) {
+        self.request_anim = true;
+    }
+
     /// Get an object which can create text layouts.
     pub fn text(&mut self) -> &mut Text<'b> {
         self.text_factory
@@ -766,4 +1858,9 @@ impl<'a, 'b> UpdateCtx<'a, 'b> {
     pub fn window_id(&self) -> WindowId {
         self.window_id
     }
+
+    /// The application's saved state.
+    pub fn store(&self) -> &'a Store {
+        self.store
+    }
 }
@@ -0,0 +1,240 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Crash reports, written by a panic hook.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic::{self, Location};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::core::widget_stack;
+use crate::WindowId;
+
+/// The number of recent events and commands kept for a report.
+const RECENT_LEN: usize = 64;
+
+thread_local! {
+    /// Whether events are being recorded, because reports are enabled.
+    static RECORDING: Cell<bool> = Cell::new(false);
+    /// The most recent events and commands, oldest first.
+    static RECENT: RefCell<VecDeque<String>> = RefCell::new(VecDeque::new());
+    /// The type of the root widget of each open window.
+    static WINDOWS: RefCell<Vec<(WindowId, &'static str)>> = RefCell::new(Vec::new());
+}
+
+/// A function called with each crash report.
+pub(crate) type CrashCallback = dyn Fn(&CrashReport) + Send + Sync;
+
+/// What was known about the application when it panicked.
+///
+/// Reports are made by a panic hook that the [`AppLauncher`] installs if
+/// crash reports are enabled, with [`crash_report_dir`] or [`on_crash`].
+/// The hook runs before the panic unwinds or aborts, and the previous hook,
+/// which usually prints the message to stderr, still runs. The application
+/// recovers only if the panic happened inside an [`ErrorBoundary`]; then
+/// the widget that panicked is replaced, and the report describes a panic
+/// the application survived.
+///
+/// The widgets, windows and recent events are those of the thread that
+/// panicked, and are only known for the UI thread.
+///
+/// [`AppLauncher`]: struct.AppLauncher.html
+/// [`crash_report_dir`]: struct.AppLauncher.html#method.crash_report_dir
+/// [`on_crash`]: struct.AppLauncher.html#method.on_crash
+/// [`ErrorBoundary`]: widget/struct.ErrorBoundary.html
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    /// The panic message.
+    pub message: String,
+    /// Where the panic happened, as `file:line:column`.
+    pub location: Option<String>,
+    /// The name of the thread that panicked.
+    pub thread: Option<String>,
+    /// The backtrace of the panic.
+    pub backtrace: String,
+    /// The types of the widgets whose methods were running, from the root
+    /// of the window to the widget that panicked.
+    pub widget_path: Vec<String>,
+    /// The open windows, with the type of their root widget.
+    pub windows: Vec<String>,
+    /// The most recent events and commands, oldest first.
+    pub recent: Vec<String>,
+    /// The file the report was written to, if it was.
+    pub file: Option<PathBuf>,
+}
+
+impl CrashReport {
+    fn capture(payload: &(dyn Any + Send), location: Option<&Location>) -> CrashReport {
+        // The hook may run while these are borrowed, if the panic happened
+        // while updating them.
+        let windows = WINDOWS
+            .try_with(|windows| {
+                windows.try_borrow().ok().map(|windows| {
+                    windows
+                        .iter()
+                        .map(|(id, root)| format!("{:?}: {}", id, root))
+                        .collect()
+                })
+            })
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let recent = RECENT
+            .try_with(|recent| {
+                recent
+                    .try_borrow()
+                    .ok()
+                    .map(|recent| recent.iter().cloned().collect())
+            })
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let widget_path = widget_stack()
+            .unwrap_or_default()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        CrashReport {
+            message: panic_message(payload),
+            location: location.map(ToString::to_string),
+            thread: thread::current().name().map(String::from),
+            backtrace: Backtrace::force_capture().to_string(),
+            widget_path,
+            windows,
+            recent,
+            file: None,
+        }
+    }
+
+    /// Write the report to a new file in `dir`.
+    fn write(&mut self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or(0);
+        let path = dir.join(format!("crash-{}-{}.txt", secs, std::process::id()));
+        fs::write(&path, self.to_string())?;
+        self.file = Some(path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "panic: {}", self.message)?;
+        if let Some(location) = &self.location {
+            writeln!(f, "at: {}", location)?;
+        }
+        if let Some(thread) = &self.thread {
+            writeln!(f, "thread: {}", thread)?;
+        }
+        writeln!(f, "\nwidgets:")?;
+        for (depth, widget) in self.widget_path.iter().enumerate() {
+            writeln!(f, "{:indent$}{}", "", widget, indent = 2 * (depth + 1))?;
+        }
+        writeln!(f, "\nwindows:")?;
+        for window in &self.windows {
+            writeln!(f, "{}", window)?;
+        }
+        writeln!(f, "\nrecent events:")?;
+        for entry in &self.recent {
+            writeln!(f, "  {}", entry)?;
+        }
+        writeln!(f, "\nbacktrace:\n{}", self.backtrace)
+    }
+}
+
+/// Install the panic hook, and start recording events on this thread.
+pub(crate) fn install(dir: Option<PathBuf>, callback: Option<Box<CrashCallback>>) {
+    RECORDING.with(|recording| recording.set(true));
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let mut report = CrashReport::capture(info.payload(), info.location());
+        if let Some(dir) = &dir {
+            match report.write(dir) {
+                Ok(()) => log::error!("crash report written to {:?}", report.file),
+                Err(e) => log::error!("failed to write crash report to {:?}: {}", dir, e),
+            }
+        }
+        if let Some(callback) = &callback {
+            callback(&report);
+        }
+        previous(info);
+    }));
+}
+
+/// Whether reports are enabled on this thread.
+pub(crate) fn is_recording() -> bool {
+    RECORDING.try_with(Cell::get).unwrap_or(false)
+}
+
+/// The message of a panic, from its payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(s) => (*s).to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(s) => s.clone(),
+            None => "Box<dyn Any>".to_string(),
+        },
+    }
+}
+
+/// Remember an event or command for the next report, if reports are on.
+pub(crate) fn record(entry: impl FnOnce() -> String) {
+    if !is_recording() {
+        return;
+    }
+    RECENT.with(|recent| {
+        let mut recent = recent.borrow_mut();
+        if recent.len() == RECENT_LEN {
+            recent.pop_front();
+        }
+        recent.push_back(entry());
+    });
+}
+
+/// Remember the root widget of a window, for reports.
+pub(crate) fn window_added(id: WindowId, root_type: &'static str) {
+    WINDOWS.with(|windows| windows.borrow_mut().push((id, root_type)));
+}
+
+pub(crate) fn window_removed(id: WindowId) {
+    WINDOWS.with(|windows| windows.borrow_mut().retain(|(window, _)| *window != id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_events_are_bounded() {
+        RECORDING.with(|recording| recording.set(true));
+        for i in 0..RECENT_LEN + 3 {
+            record(|| format!("event {}", i));
+        }
+        let report = CrashReport::capture(&"boom", None);
+        assert_eq!(report.message, "boom");
+        assert_eq!(report.recent.len(), RECENT_LEN);
+        assert_eq!(report.recent[0], "event 3");
+    }
+}
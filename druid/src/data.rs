@@ -14,10 +14,16 @@
 
 //! Traits for handling value types.
 
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut, Range, RangeInclusive};
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::kurbo;
+use crate::piet;
 
 pub use druid_derive::Data;
 
@@ -64,6 +70,16 @@ pub use druid_derive::Data;
 /// This function must have a signature in the form, `fn<T>(&T, &T) -> bool`,
 /// where `T` is the type of the field.
 ///
+/// - **`#[druid(epsilon = 0.001)]`**
+///
+/// Consider two floats the same if they differ by no more than the given
+/// amount, using [`ApproxEq`].
+///
+/// By default, floats are the same only if they have the same bits, so a
+/// value that is driven by an animation can cause updates for changes too
+/// small to be visible. This attribute trades those updates for missing
+/// changes smaller than the tolerance.
+///
 /// ### Example:
 ///
 /// ```
@@ -72,16 +88,34 @@ pub use druid_derive::Data;
 /// # use druid::Data;
 /// #[derive(Clone, Data)]
 /// struct PathEntry {
-///     // There's no Data impl for PathBuf, but no problem
+///     // There's no Data impl for Vec, but no problem
 ///     #[druid(same_fn = "PartialEq::eq")]
+///     args: Vec<String>,
 ///     path: PathBuf,
 ///     priority: usize,
 ///     // This field is not part of our data model.
 ///     #[druid(ignore)]
 ///     last_read: Instant,
+///     // Changes smaller than this don't matter.
+///     #[druid(epsilon = 0.001)]
+///     progress: f64,
 /// }
 /// ```
 ///
+/// ## Floats
+///
+/// Floats are the same if they have the same bits. This is equality in the
+/// IEEE 754 total order, which [`TotalOrd`] implements: `0.0` and `-0.0`
+/// are different, and a NaN is the same as a NaN with the same bits. The
+/// `epsilon` attribute, or the [`Approx`] wrapper, instead compare floats
+/// within a tolerance, as described for [`ApproxEq`].
+///
+/// ## Implementations for other crates
+///
+/// `Data` is implemented for types from some other crates when the
+/// corresponding feature is enabled: `Uuid` with `uuid`, `Url` with `url`,
+/// and the date and time types with `chrono`.
+///
 /// ## C-style enums
 ///
 /// In the case of a "c-style" enum (one that only contains unit variants,
@@ -89,6 +123,9 @@ pub use druid_derive::Data;
 /// checks for equality. Therefore, such types must also implement `PartialEq`.
 ///
 /// [`Data::same`]: trait.Data.html#tymethod.same
+/// [`ApproxEq`]: trait.ApproxEq.html
+/// [`Approx`]: struct.Approx.html
+/// [`TotalOrd`]: struct.TotalOrd.html
 pub trait Data: Clone {
     /// Determine whether two values are the same.
     ///
@@ -130,6 +167,9 @@ impl_data_simple!(usize);
 impl_data_simple!(char);
 impl_data_simple!(bool);
 impl_data_simple!(String);
+impl_data_simple!(Duration);
+impl_data_simple!(Instant);
+impl_data_simple!(PathBuf);
 
 impl Data for f32 {
     fn same(&self, other: &Self) -> bool {
@@ -143,6 +183,184 @@ impl Data for f64 {
     }
 }
 
+/// Floating point types that can be compared within a tolerance.
+///
+/// This is used by the `#[druid(epsilon = ...)]` attribute of the `Data`
+/// derive macro, and by the [`Approx`] wrapper.
+///
+/// [`Approx`]: struct.Approx.html
+pub trait ApproxEq {
+    /// Returns `true` if the values differ by no more than `epsilon`.
+    ///
+    /// This is symmetric. All NaNs are equal to each other, whatever their
+    /// bits, and to nothing else; `0.0` and `-0.0` are equal, as are
+    /// infinities of the same sign.
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool;
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        f64::from(*self).approx_eq(&f64::from(*other), epsilon)
+    }
+}
+
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        if self.is_nan() || other.is_nan() {
+            return self.is_nan() && other.is_nan();
+        }
+        self == other || (self - other).abs() <= epsilon
+    }
+}
+
+/// A float that is the same as another if they differ by no more than a
+/// tolerance.
+///
+/// This is an alternative to the `#[druid(epsilon = ...)]` attribute, for
+/// when the value is not a field of a derived type, such as the data of a
+/// `Slider`. When the two values have different tolerances, the larger
+/// one is used, so that `a.same(&b)` is always `b.same(&a)`.
+///
+/// ```
+/// use druid::{Approx, Data};
+///
+/// let value = Approx::new(0.5, 0.01);
+/// assert!(value.same(&Approx::new(0.505, 0.01)));
+/// assert!(!value.same(&Approx::new(0.52, 0.01)));
+/// // The larger tolerance is used either way around.
+/// assert!(Approx::new(0.505, 0.0).same(&value));
+/// assert_eq!(*value, 0.5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Approx<T> {
+    value: T,
+    epsilon: f64,
+}
+
+impl<T> Approx<T> {
+    /// Create a new value with the given tolerance.
+    pub fn new(value: T, epsilon: f64) -> Self {
+        Approx { value, epsilon }
+    }
+
+    /// The tolerance used when comparing this value.
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: ApproxEq + Clone> Data for Approx<T> {
+    fn same(&self, other: &Self) -> bool {
+        let epsilon = self.epsilon.max(other.epsilon);
+        self.value.approx_eq(&other.value, epsilon)
+    }
+}
+
+impl<T> Deref for Approx<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Approx<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// A float that is ordered by the IEEE 754 total order.
+///
+/// Floats are only partially ordered, as NaN is not comparable with
+/// anything, and `0.0 == -0.0`. In the total order, every value has a
+/// place: negative NaNs come first, then negative infinity, the negative
+/// numbers, `-0.0`, `0.0`, the positive numbers, positive infinity, and
+/// positive NaNs. Two values are equal exactly when they have the same bits,
+/// so this is consistent with `Data::same`, and can be used where `Eq`,
+/// `Ord` or `Hash` are needed, such as for keys of a map.
+///
+/// ```
+/// use druid::TotalOrd;
+///
+/// let mut values = vec![TotalOrd(1.0), TotalOrd(std::f64::NAN), TotalOrd(0.0), TotalOrd(-0.0)];
+/// values.sort();
+/// assert_eq!(values[0], TotalOrd(-0.0));
+/// assert_ne!(TotalOrd(0.0), TotalOrd(-0.0));
+/// assert!(values[3].is_nan());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TotalOrd<T>(pub T);
+
+macro_rules! impl_total_ord {
+    ($float:ty, $int:ty, $uint:ty) => {
+        impl TotalOrd<$float> {
+            /// The bits of the value, as an integer in the same order.
+            fn key(self) -> $int {
+                let bits = self.0.to_bits() as $int;
+                // Negative values are ordered backwards by their bits, so
+                // all but the sign bit of those are flipped.
+                let shift = <$int>::max_value().count_ones();
+                bits ^ (((bits >> shift) as $uint >> 1) as $int)
+            }
+        }
+
+        impl PartialEq for TotalOrd<$float> {
+            fn eq(&self, other: &Self) -> bool {
+                self.key() == other.key()
+            }
+        }
+
+        impl Eq for TotalOrd<$float> {}
+
+        impl PartialOrd for TotalOrd<$float> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for TotalOrd<$float> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.key().cmp(&other.key())
+            }
+        }
+
+        impl Hash for TotalOrd<$float> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.key().hash(state)
+            }
+        }
+
+        impl Data for TotalOrd<$float> {
+            fn same(&self, other: &Self) -> bool {
+                self.0.same(&other.0)
+            }
+        }
+    };
+}
+
+impl_total_ord!(f32, i32, u32);
+impl_total_ord!(f64, i64, u64);
+
+impl<T> Deref for TotalOrd<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for TotalOrd<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
 impl<T: ?Sized> Data for Arc<T> {
     fn same(&self, other: &Self) -> bool {
         Arc::ptr_eq(self, other)
@@ -181,6 +399,35 @@ impl<T: Data, U: Data> Data for Result<T, U> {
     }
 }
 
+impl<T: Data> Data for Range<T> {
+    fn same(&self, other: &Self) -> bool {
+        self.start.same(&other.start) && self.end.same(&other.end)
+    }
+}
+
+impl<T: Data> Data for RangeInclusive<T> {
+    fn same(&self, other: &Self) -> bool {
+        self.start().same(other.start()) && self.end().same(other.end())
+    }
+}
+
+macro_rules! impl_data_array {
+    ($($n:expr),*) => {
+        $(
+            impl<T: Data> Data for [T; $n] {
+                fn same(&self, other: &Self) -> bool {
+                    self.iter().zip(other.iter()).all(|(a, b)| a.same(b))
+                }
+            }
+        )*
+    };
+}
+
+impl_data_array!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32
+);
+
 impl Data for () {
     fn same(&self, _other: &Self) -> bool {
         true
@@ -252,3 +499,32 @@ impl Data for kurbo::Size {
         self.width.same(&other.width) && self.height.same(&other.height)
     }
 }
+
+impl Data for piet::Color {
+    fn same(&self, other: &Self) -> bool {
+        self.as_rgba_u32() == other.as_rgba_u32()
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl_data_simple!(uuid::Uuid);
+
+#[cfg(feature = "url")]
+impl_data_simple!(url::Url);
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use super::Data;
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+    impl_data_simple!(chrono::Duration);
+    impl_data_simple!(NaiveDate);
+    impl_data_simple!(NaiveTime);
+    impl_data_simple!(NaiveDateTime);
+
+    impl<Tz: TimeZone> Data for DateTime<Tz> {
+        fn same(&self, other: &Self) -> bool {
+            self == other
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Modal message dialogs, for asking the user to confirm something.
+
+use crate::shell::MessageDialogOptions;
+use crate::Command;
+
+/// A modal dialog asking the user to confirm something, with the commands
+/// to send depending on their answer.
+///
+/// The dialog is shown by submitting a [`SHOW_MESSAGE_DIALOG`] command with
+/// it as the argument, or with [`EventCtx::show_message_dialog`]. It blocks
+/// the application until it is dismissed; then the [`on_confirm`] command is
+/// sent if the user chose OK or Yes, and the [`on_cancel`] command
+/// otherwise. Both are sent to the window that showed the dialog.
+///
+/// # Examples
+///
+/// ```
+/// use druid::{MessageDialog, MessageDialogOptions, Selector};
+///
+/// const DELETE: Selector = Selector::new("my-app.delete");
+///
+/// let options = MessageDialogOptions::new("Delete?", "This can't be undone.").warning();
+/// let dialog = MessageDialog::new(options).on_confirm(DELETE);
+/// ```
+///
+/// [`SHOW_MESSAGE_DIALOG`]: commands/constant.SHOW_MESSAGE_DIALOG.html
+/// [`EventCtx::show_message_dialog`]: struct.EventCtx.html#method.show_message_dialog
+/// [`on_confirm`]: #method.on_confirm
+/// [`on_cancel`]: #method.on_cancel
+#[derive(Debug, Clone)]
+pub struct MessageDialog {
+    pub(crate) options: MessageDialogOptions,
+    pub(crate) on_confirm: Option<Command>,
+    pub(crate) on_cancel: Option<Command>,
+}
+
+impl MessageDialog {
+    /// Create a dialog that sends no commands.
+    pub fn new(options: MessageDialogOptions) -> Self {
+        MessageDialog {
+            options,
+            on_confirm: None,
+            on_cancel: None,
+        }
+    }
+
+    /// Send this command if the user chooses OK or Yes.
+    pub fn on_confirm(mut self, command: impl Into<Command>) -> Self {
+        self.on_confirm = Some(command.into());
+        self
+    }
+
+    /// Send this command if the user chooses Cancel or No, or closes the
+    /// dialog.
+    pub fn on_cancel(mut self, command: impl Into<Command>) -> Self {
+        self.on_cancel = Some(command.into());
+        self
+    }
+
+    /// The command to send for the user's answer.
+    pub(crate) fn response(&self, confirmed: bool) -> Option<&Command> {
+        if confirmed {
+            self.on_confirm.as_ref()
+        } else {
+            self.on_cancel.as_ref()
+        }
+    }
+}
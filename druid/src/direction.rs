@@ -0,0 +1,197 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Alignment and insets in terms of start and end, for right-to-left layout.
+
+use crate::kurbo::{Insets, Rect};
+use crate::piet::UnitPoint;
+use crate::{theme, Env};
+
+/// The horizontal direction that content flows in.
+///
+/// The direction is taken from [`theme::RIGHT_TO_LEFT`], and decides which
+/// side is the start and which the end.
+///
+/// [`theme::RIGHT_TO_LEFT`]: theme/constant.RIGHT_TO_LEFT.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayoutDirection {
+    /// The start is on the left, as in English.
+    LeftToRight,
+    /// The start is on the right, as in Arabic and Hebrew.
+    RightToLeft,
+}
+
+impl LayoutDirection {
+    /// The direction set in `env`.
+    pub fn from_env(env: &Env) -> LayoutDirection {
+        if env.get(theme::RIGHT_TO_LEFT) {
+            LayoutDirection::RightToLeft
+        } else {
+            LayoutDirection::LeftToRight
+        }
+    }
+
+    /// Whether the start is on the right.
+    pub fn is_rtl(self) -> bool {
+        self == LayoutDirection::RightToLeft
+    }
+}
+
+impl Default for LayoutDirection {
+    fn default() -> Self {
+        LayoutDirection::LeftToRight
+    }
+}
+
+/// A point within a rectangle, like a [`UnitPoint`], whose horizontal
+/// position can be given from the start instead of the left.
+///
+/// Horizontal positions run from 0.0, at the start, to 1.0, at the end,
+/// and are mirrored in right-to-left layouts. Alignments made from a
+/// `UnitPoint` keep their sides in every direction.
+///
+/// [`UnitPoint`]: ../piet/struct.UnitPoint.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    horizontal: f64,
+    vertical: f64,
+    /// Whether `horizontal` is measured from the start, not the left.
+    logical: bool,
+}
+
+impl Alignment {
+    pub const TOP_START: Alignment = Alignment::new(0.0, 0.0);
+    pub const TOP: Alignment = Alignment::new(0.5, 0.0);
+    pub const TOP_END: Alignment = Alignment::new(1.0, 0.0);
+    pub const START: Alignment = Alignment::new(0.0, 0.5);
+    pub const CENTER: Alignment = Alignment::new(0.5, 0.5);
+    pub const END: Alignment = Alignment::new(1.0, 0.5);
+    pub const BOTTOM_START: Alignment = Alignment::new(0.0, 1.0);
+    pub const BOTTOM: Alignment = Alignment::new(0.5, 1.0);
+    pub const BOTTOM_END: Alignment = Alignment::new(1.0, 1.0);
+
+    /// Create an alignment `horizontal` of the way from the start to the
+    /// end, and `vertical` of the way from the top to the bottom.
+    pub const fn new(horizontal: f64, vertical: f64) -> Alignment {
+        Alignment {
+            horizontal,
+            vertical,
+            logical: true,
+        }
+    }
+
+    /// The point this alignment is at, in a layout flowing in `direction`.
+    pub fn resolve(self, direction: LayoutDirection) -> UnitPoint {
+        let horizontal = if self.logical && direction.is_rtl() {
+            1.0 - self.horizontal
+        } else {
+            self.horizontal
+        };
+        UnitPoint::new(horizontal, self.vertical)
+    }
+}
+
+impl From<UnitPoint> for Alignment {
+    fn from(point: UnitPoint) -> Alignment {
+        let point = point.resolve(Rect::new(0.0, 0.0, 1.0, 1.0));
+        Alignment {
+            horizontal: point.x,
+            vertical: point.y,
+            logical: false,
+        }
+    }
+}
+
+/// Insets, like kurbo's [`Insets`], with start and end sides instead of
+/// left and right.
+///
+/// [`Insets`]: ../kurbo/struct.Insets.html
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LogicalInsets {
+    /// The inset on the starting side.
+    pub start: f64,
+    /// The inset on the top.
+    pub top: f64,
+    /// The inset on the ending side.
+    pub end: f64,
+    /// The inset on the bottom.
+    pub bottom: f64,
+}
+
+impl LogicalInsets {
+    /// Create insets from each side.
+    pub const fn new(start: f64, top: f64, end: f64, bottom: f64) -> LogicalInsets {
+        LogicalInsets {
+            start,
+            top,
+            end,
+            bottom,
+        }
+    }
+
+    /// The insets on the left, top, right and bottom, in a layout flowing
+    /// in `direction`.
+    pub fn resolve(self, direction: LayoutDirection) -> Insets {
+        if direction.is_rtl() {
+            Insets::new(self.end, self.top, self.start, self.bottom)
+        } else {
+            Insets::new(self.start, self.top, self.end, self.bottom)
+        }
+    }
+}
+
+impl From<f64> for LogicalInsets {
+    fn from(inset: f64) -> LogicalInsets {
+        LogicalInsets::new(inset, inset, inset, inset)
+    }
+}
+
+impl From<(f64, f64)> for LogicalInsets {
+    fn from((horizontal, vertical): (f64, f64)) -> LogicalInsets {
+        LogicalInsets::new(horizontal, vertical, horizontal, vertical)
+    }
+}
+
+impl From<(f64, f64, f64, f64)> for LogicalInsets {
+    fn from((start, top, end, bottom): (f64, f64, f64, f64)) -> LogicalInsets {
+        LogicalInsets::new(start, top, end, bottom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kurbo::Point;
+
+    #[test]
+    fn mirrored_in_rtl() {
+        let unit = Rect::new(0.0, 0.0, 1.0, 1.0);
+        let rtl = LayoutDirection::RightToLeft;
+        let ltr = LayoutDirection::LeftToRight;
+        assert_eq!(
+            Alignment::TOP_START.resolve(rtl).resolve(unit),
+            Point::new(1.0, 0.0)
+        );
+        assert_eq!(
+            Alignment::TOP_START.resolve(ltr).resolve(unit),
+            Point::new(0.0, 0.0)
+        );
+        let left = Alignment::from(UnitPoint::LEFT);
+        assert_eq!(left.resolve(rtl).resolve(unit), Point::new(0.0, 0.5));
+
+        let insets = LogicalInsets::new(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(insets.resolve(rtl), Insets::new(3.0, 2.0, 1.0, 4.0));
+        assert_eq!(insets.resolve(ltr), Insets::new(1.0, 2.0, 3.0, 4.0));
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Easing curves, for the pace of animations.
+
+/// The pace of an animation: how far along it is, at each point in its
+/// duration.
+///
+/// Animations that start and stop gently look more natural than ones that
+/// move at a constant speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    /// A constant speed.
+    Linear,
+    /// Start slowly, and speed up towards the end.
+    EaseIn,
+    /// Start quickly, and slow down towards the end.
+    EaseOut,
+    /// Start slowly, speed up, and slow down towards the end.
+    EaseInOut,
+    /// A cubic Bézier curve from (0, 0) to (1, 1), with the control points
+    /// (x1, y1) and (x2, y2), as in CSS's `cubic-bezier()`. The x
+    /// coordinates must be between 0.0 and 1.0.
+    CubicBezier(f64, f64, f64, f64),
+    /// Overshoot the end and settle on it, like a spring.
+    Spring {
+        /// How many times it swings back and forth across the end.
+        oscillations: f64,
+        /// How quickly the swings die down; 0.0 leaves them at full size
+        /// until the end.
+        damping: f64,
+    },
+}
+
+impl Easing {
+    /// How far along the animation is, from 0.0 to 1.0, when `t` of its
+    /// duration has passed. `t` is clamped to the same range; a spring goes
+    /// past 1.0 on the way.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - 4.0 * (1.0 - t).powi(3),
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let s = solve_bezier(x1, x2, t);
+                bezier(y1, y2, s)
+            }
+            Easing::Spring {
+                oscillations,
+                damping,
+            } => {
+                // The swings also shrink with the time left, so the end is
+                // reached exactly.
+                let swing = (oscillations * 2.0 * t * std::f64::consts::PI).cos();
+                1.0 - (-damping * t).exp() * (1.0 - t) * swing
+            }
+        }
+    }
+
+    /// A spring that swings across the end a couple of times.
+    pub fn spring() -> Easing {
+        Easing::Spring {
+            oscillations: 2.0,
+            damping: 4.0,
+        }
+    }
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::EaseInOut
+    }
+}
+
+/// One coordinate of a cubic Bézier curve from 0.0 to 1.0, with the given
+/// control coordinates, at `s`.
+fn bezier(c1: f64, c2: f64, s: f64) -> f64 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * c1 + 3.0 * r * s * s * c2 + s * s * s
+}
+
+/// The `s` at which the x coordinate of the curve is `x`.
+fn solve_bezier(x1: f64, x2: f64, x: f64) -> f64 {
+    // The x coordinate only increases, when the control points are within
+    // the unit square, so bisection always finds it.
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..64 {
+        let mid = (low + high) / 2.0;
+        if bezier(x1, x2, mid) < x {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < 1e-9 {
+            break;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_start_and_end_in_place() {
+        for &easing in &[
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::CubicBezier(0.25, 0.1, 0.25, 1.0),
+            Easing::spring(),
+        ] {
+            assert!(easing.apply(-1.0).abs() < 1e-6);
+            assert!(easing.apply(0.0).abs() < 1e-6);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6);
+            assert!((easing.apply(2.0) - 1.0).abs() < 1e-6);
+        }
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert!((Easing::EaseInOut.apply(0.5) - 0.5).abs() < 1e-9);
+        let linear = Easing::CubicBezier(1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0);
+        assert!((linear.apply(0.3) - 0.3).abs() < 1e-6);
+        assert!(Easing::spring().apply(0.3) > 1.0);
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Editing actions for text widgets, and the keymap that produces them.
+
+use crate::shell::KeyCompare;
+use crate::{HotKey, KeyCode, KeyEvent, RawMods, SysMods};
+
+/// A movement of the cursor in a text widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    /// One grapheme to the left.
+    Left,
+    /// One grapheme to the right.
+    Right,
+    /// To the start of the previous word.
+    WordLeft,
+    /// To the end of the next word.
+    WordRight,
+    /// To the start of the line.
+    LineStart,
+    /// To the end of the line.
+    LineEnd,
+    /// To the line above, in multi-line text.
+    Up,
+    /// To the line below, in multi-line text.
+    Down,
+}
+
+/// An editing operation understood by text widgets.
+///
+/// Text widgets translate key presses into `EditAction`s using a [`KeyMap`].
+/// Actions can also be sent to the focused text widget directly, as the
+/// argument of an [`EDIT_ACTION`] command; this is how a menu item can,
+/// for instance, select all the text in the focused editor.
+///
+/// [`KeyMap`]: struct.KeyMap.html
+/// [`EDIT_ACTION`]: commands/constant.EDIT_ACTION.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditAction {
+    /// Replace the selection with the given text.
+    Insert(String),
+    /// Delete the selection, or the grapheme before the cursor.
+    Backspace,
+    /// Delete the selection, or the grapheme after the cursor.
+    Delete,
+    /// Delete from the cursor to the start of the previous word.
+    DeleteWordBackward,
+    /// Delete from the cursor to the end of the next word.
+    DeleteWordForward,
+    /// Move the cursor, collapsing the selection.
+    Move(Movement),
+    /// Move the active end of the selection.
+    ModifySelection(Movement),
+    /// Select all text.
+    SelectAll,
+    /// Swap the graphemes on either side of the cursor.
+    Transpose,
+    /// Copy the selection to the clipboard.
+    Copy,
+    /// Copy the selection to the clipboard and delete it.
+    Cut,
+    /// Replace the selection with the contents of the clipboard.
+    Paste,
+}
+
+/// A mapping from hotkeys to [`EditAction`]s.
+///
+/// The default keymap has the usual platform bindings; applications can add
+/// their own (for instance, emacs-style bindings) with [`bind`]. Bindings
+/// added later take precedence over earlier ones.
+///
+/// # Examples
+///
+/// ```
+/// use druid::{EditAction, KeyMap, Movement, RawMods};
+///
+/// let keymap = KeyMap::default()
+///     .bind(RawMods::Ctrl, "a", EditAction::Move(Movement::LineStart))
+///     .bind(RawMods::Ctrl, "e", EditAction::Move(Movement::LineEnd))
+///     .bind(RawMods::Ctrl, "t", EditAction::Transpose);
+/// ```
+///
+/// [`EditAction`]: enum.EditAction.html
+/// [`bind`]: #method.bind
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: Vec<(HotKey, EditAction)>,
+}
+
+impl KeyMap {
+    /// Create a keymap with no bindings.
+    pub fn empty() -> KeyMap {
+        KeyMap {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Builder-style method to add a binding.
+    pub fn bind(
+        mut self,
+        mods: impl Into<Option<RawMods>>,
+        key: impl Into<KeyCompare>,
+        action: EditAction,
+    ) -> Self {
+        self.bindings.push((HotKey::new(mods, key), action));
+        self
+    }
+
+    /// Return the action bound to this key event, if any.
+    ///
+    /// Unbound printable keys are not handled here; text widgets insert
+    /// their text.
+    pub fn action_for(&self, event: &KeyEvent) -> Option<EditAction> {
+        self.bindings
+            .iter()
+            .rev()
+            .find(|(hotkey, _)| hotkey.matches(event))
+            .map(|(_, action)| action.clone())
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> KeyMap {
+        use EditAction::*;
+        use Movement::*;
+
+        // Word movement uses Alt on macOS and Ctrl elsewhere.
+        #[cfg(target_os = "macos")]
+        let (word_mods, word_select_mods) = (RawMods::Alt, RawMods::AltShift);
+        #[cfg(not(target_os = "macos"))]
+        let (word_mods, word_select_mods) = (RawMods::Ctrl, RawMods::CtrlShift);
+
+        let keymap = KeyMap::empty()
+            .bind(None, KeyCode::Backspace, Backspace)
+            .bind(None, KeyCode::Delete, Delete)
+            .bind(word_mods, KeyCode::Backspace, DeleteWordBackward)
+            .bind(word_mods, KeyCode::Delete, DeleteWordForward)
+            .bind(None, KeyCode::ArrowLeft, Move(Left))
+            .bind(None, KeyCode::ArrowRight, Move(Right))
+            .bind(word_mods, KeyCode::ArrowLeft, Move(WordLeft))
+            .bind(word_mods, KeyCode::ArrowRight, Move(WordRight))
+            .bind(None, KeyCode::ArrowUp, Move(Up))
+            .bind(None, KeyCode::ArrowDown, Move(Down))
+            .bind(None, KeyCode::Home, Move(LineStart))
+            .bind(None, KeyCode::End, Move(LineEnd))
+            .bind(RawMods::Shift, KeyCode::ArrowLeft, ModifySelection(Left))
+            .bind(RawMods::Shift, KeyCode::ArrowRight, ModifySelection(Right))
+            .bind(RawMods::Shift, KeyCode::ArrowUp, ModifySelection(Up))
+            .bind(RawMods::Shift, KeyCode::ArrowDown, ModifySelection(Down))
+            .bind(
+                word_select_mods,
+                KeyCode::ArrowLeft,
+                ModifySelection(WordLeft),
+            )
+            .bind(
+                word_select_mods,
+                KeyCode::ArrowRight,
+                ModifySelection(WordRight),
+            )
+            .bind(RawMods::Shift, KeyCode::Home, ModifySelection(LineStart))
+            .bind(RawMods::Shift, KeyCode::End, ModifySelection(LineEnd))
+            .bind(SysMods::Cmd, "a", SelectAll);
+        platform_bindings(keymap)
+    }
+}
+
+/// Cmd+Arrow moves to the start or end of the line on macOS.
+#[cfg(target_os = "macos")]
+fn platform_bindings(keymap: KeyMap) -> KeyMap {
+    use EditAction::*;
+    use Movement::*;
+
+    keymap
+        .bind(SysMods::Cmd, KeyCode::ArrowLeft, Move(LineStart))
+        .bind(SysMods::Cmd, KeyCode::ArrowRight, Move(LineEnd))
+        .bind(
+            SysMods::CmdShift,
+            KeyCode::ArrowLeft,
+            ModifySelection(LineStart),
+        )
+        .bind(
+            SysMods::CmdShift,
+            KeyCode::ArrowRight,
+            ModifySelection(LineEnd),
+        )
+}
+
+#[cfg(not(target_os = "macos"))]
+fn platform_bindings(keymap: KeyMap) -> KeyMap {
+    keymap
+}
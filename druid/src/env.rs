@@ -19,12 +19,14 @@ use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::kurbo::{Point, Rect, Size};
-use crate::piet::{Color, LinearGradient};
+use crate::kurbo::{Insets, Point, Rect, Size};
+use crate::piet::{Color, GradientStop, LinearGradient, RadialGradient};
 
+use crate::core::widget_path;
 use crate::localization::L10nManager;
-use crate::Data;
+use crate::{ColorSpace, Data};
 
 /// An environment passed down through all widget traversals.
 ///
@@ -43,6 +45,20 @@ pub struct Env(Arc<EnvImpl>);
 struct EnvImpl {
     map: HashMap<String, Value>,
     l10n: Arc<L10nManager>,
+    missing_key_policy: MissingKeyPolicy,
+}
+
+/// What [`Env::get`] does when a key is not set.
+///
+/// [`Env::get`]: struct.Env.html#method.get
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingKeyPolicy {
+    /// Panic. This is the default.
+    Panic,
+    /// Log an error naming the key and the widget that asked for it, and
+    /// return a conspicuous fallback value (magenta, for colors) if the type
+    /// has one. Types without a fallback still panic, after logging.
+    Fallback,
 }
 
 /// A typed key.
@@ -66,10 +82,14 @@ pub enum Value {
     Point(Point),
     Size(Size),
     Rect(Rect),
+    Insets(Insets),
     Color(Color),
     LinearGradient(Arc<LinearGradient>),
+    RadialGradient(Arc<RadialGradient>),
     Float(f64),
     UnsignedInt(u64),
+    Bool(bool),
+    Duration(Duration),
     String(String),
 }
 
@@ -85,6 +105,14 @@ pub trait ValueType<'a>: Sized {
 
     /// Attempt to convert the generic `Value` into this type.
     fn try_from_value(v: &'a Value) -> Result<Self, EnvError>;
+
+    /// The value returned for a missing key under
+    /// [`MissingKeyPolicy::Fallback`].
+    ///
+    /// [`MissingKeyPolicy::Fallback`]: enum.MissingKeyPolicy.html#variant.Fallback
+    fn fallback() -> Option<Self> {
+        None
+    }
 }
 
 /// The error type for environment access.
@@ -105,13 +133,27 @@ impl Env {
     ///
     /// # Panics
     ///
-    /// Panics if the key is not found, or if it is present with the wrong type.
+    /// Panics if the key is present with the wrong type, or if it is not
+    /// found and the [`MissingKeyPolicy`] does not provide a fallback.
+    ///
+    /// [`MissingKeyPolicy`]: enum.MissingKeyPolicy.html
     pub fn get<'a, V: ValueType<'a>>(&'a self, key: Key<V>) -> V {
         if let Some(value) = self.0.map.get(key.key) {
-            value.to_inner_unchecked()
+            return value.to_inner_unchecked();
+        }
+        let path = widget_path();
+        let location = if path.is_empty() {
+            "<unknown>"
         } else {
-            panic!("key for {} not found", key.key)
+            path.as_str()
+        };
+        if self.0.missing_key_policy == MissingKeyPolicy::Fallback {
+            log::error!("key for {} not found, requested by {}", key.key, location);
+            if let Some(fallback) = V::fallback() {
+                return fallback;
+            }
         }
+        panic!("key for {} not found, requested by {}", key.key, location)
     }
 
     /// Gets a value from the environment.
@@ -155,6 +197,49 @@ impl Env {
         env.map.insert(key, value);
     }
 
+    /// Set what [`get`] does when a key is not found.
+    ///
+    /// [`get`]: #method.get
+    pub fn set_missing_key_policy(&mut self, policy: MissingKeyPolicy) {
+        Arc::make_mut(&mut self.0).missing_key_policy = policy;
+    }
+
+    /// Returns an iterator over all keys set in the environment, and their
+    /// values, in arbitrary order.
+    ///
+    /// This is intended for debugging, for instance to dump a theme.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.map.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// This environment with its colors, and the colors of its gradients,
+    /// converted from sRGB to `color_space`.
+    ///
+    /// This is how the theme is given to windows painted in another color
+    /// space.
+    pub(crate) fn to_color_space(&self, color_space: ColorSpace) -> Env {
+        let convert = |color: &Color| ColorSpace::Srgb.convert(color, color_space);
+        let convert_stops = |stops: &mut Vec<GradientStop>| {
+            for stop in stops {
+                stop.color = convert(&stop.color);
+            }
+        };
+        let mut env = self.clone();
+        for value in Arc::make_mut(&mut env.0).map.values_mut() {
+            match value {
+                Value::Color(color) => *color = convert(color),
+                Value::LinearGradient(gradient) => {
+                    convert_stops(&mut Arc::make_mut(gradient).stops)
+                }
+                Value::RadialGradient(gradient) => {
+                    convert_stops(&mut Arc::make_mut(gradient).stops)
+                }
+                _ => (),
+            }
+        }
+        env
+    }
+
     /// Returns a reference to the [`L10nManager`], which handles localization
     /// resources.
     ///
@@ -170,11 +255,15 @@ impl Debug for Value {
             Value::Point(p) => write!(f, "Point {:?}", p),
             Value::Size(s) => write!(f, "Size {:?}", s),
             Value::Rect(r) => write!(f, "Rect {:?}", r),
+            Value::Insets(i) => write!(f, "Insets {:?}", i),
             Value::Color(c) => write!(f, "Color {:?}", c),
             // TODO: make PaintBrush impl debug?
             Value::LinearGradient(g) => write!(f, "LinearGradient {:?}", g),
+            Value::RadialGradient(g) => write!(f, "RadialGradient {:?}", g),
             Value::Float(x) => write!(f, "Float {}", x),
             Value::UnsignedInt(x) => write!(f, "UnsignedInt {}", x),
+            Value::Bool(b) => write!(f, "Bool {}", b),
+            Value::Duration(d) => write!(f, "Duration {:?}", d),
             Value::String(s) => write!(f, "String {:?}", s),
         }
     }
@@ -193,6 +282,26 @@ impl<T> Key<T> {
     /// let float_key: Key<f64> = Key::new("a.very.good.float");
     /// let color_key: Key<Color> = Key::new("a.very.nice.color");
     /// ```
+    ///
+    /// Values that are expensive to clone, such as strings and gradients,
+    /// are retrieved by reference:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use druid::{Env, Key};
+    /// use druid::kurbo::Insets;
+    /// use druid::piet::LinearGradient;
+    ///
+    /// const PADDING: Key<Insets> = Key::new("my_app.padding");
+    /// const FADE_TIME: Key<Duration> = Key::new("my_app.fade_time");
+    /// const BACKGROUND: Key<&LinearGradient> = Key::new("my_app.background");
+    ///
+    /// let env = Env::default()
+    ///     .adding(PADDING, Insets::uniform(8.0))
+    ///     .adding(FADE_TIME, Duration::from_millis(250));
+    /// assert_eq!(env.get(FADE_TIME), Duration::from_millis(250));
+    /// assert!(env.try_get(BACKGROUND).is_none());
+    /// ```
     pub const fn new(key: &'static str) -> Self {
         Key {
             key,
@@ -201,6 +310,15 @@ impl<T> Key<T> {
     }
 }
 
+// Not derived, as that would require the value type to be `Copy`.
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Key<T> {}
+
 impl Value {
     /// Get a reference to the inner object.
     ///
@@ -220,10 +338,14 @@ impl Value {
             (Point(_), Point(_)) => true,
             (Size(_), Size(_)) => true,
             (Rect(_), Rect(_)) => true,
+            (Insets(_), Insets(_)) => true,
             (Color(_), Color(_)) => true,
             (LinearGradient(_), LinearGradient(_)) => true,
+            (RadialGradient(_), RadialGradient(_)) => true,
             (Float(_), Float(_)) => true,
             (UnsignedInt(_), UnsignedInt(_)) => true,
+            (Bool(_), Bool(_)) => true,
+            (Duration(_), Duration(_)) => true,
             (String(_), String(_)) => true,
             _ => false,
         }
@@ -239,10 +361,16 @@ impl Data for Value {
                 r1.x0.same(&r2.x0) && r1.y0.same(&r2.y0) && r1.x1.same(&r2.x1) && r1.y1.same(&r2.y1)
             }
             (Size(s1), Size(s2)) => s1.width.same(&s2.width) && s1.height.same(&s2.height),
+            (Insets(i1), Insets(i2)) => {
+                i1.x0.same(&i2.x0) && i1.y0.same(&i2.y0) && i1.x1.same(&i2.x1) && i1.y1.same(&i2.y1)
+            }
             (Color(c1), Color(c2)) => c1.as_rgba_u32() == c2.as_rgba_u32(),
             (LinearGradient(g1), LinearGradient(g2)) => Arc::ptr_eq(g1, g2),
+            (RadialGradient(g1), RadialGradient(g2)) => Arc::ptr_eq(g1, g2),
             (Float(f1), Float(f2)) => f1.same(&f2),
             (UnsignedInt(f1), UnsignedInt(f2)) => f1.same(&f2),
+            (Bool(b1), Bool(b2)) => b1 == b2,
+            (Duration(d1), Duration(d2)) => d1 == d2,
             (String(s1), String(s2)) => s1 == s2,
             _ => false,
         }
@@ -271,6 +399,7 @@ impl Default for Env {
         let inner = EnvImpl {
             l10n: Arc::new(l10n),
             map: HashMap::new(),
+            missing_key_policy: MissingKeyPolicy::Panic,
         };
         Env(Arc::new(inner))
     }
@@ -285,8 +414,14 @@ impl<T> From<Key<T>> for String {
 /// Use this macro for types which are cheap to clone (ie all `Copy` types).
 macro_rules! impl_value_type_owned {
     ($ty:ty, $var:ident) => {
+        impl_value_type_owned!($ty, $var, None);
+    };
+    ($ty:ty, $var:ident, $fallback:expr) => {
         impl<'a> ValueType<'a> for $ty {
             type Owned = $ty;
+            fn fallback() -> Option<Self> {
+                $fallback
+            }
             fn try_from_value(value: &Value) -> Result<Self, EnvError> {
                 match value {
                     Value::$var(f) => Ok(f.to_owned()),
@@ -310,9 +445,12 @@ macro_rules! impl_value_type_owned {
 /// Use this macro for types which require allocation but are not too
 /// expensive to clone.
 macro_rules! impl_value_type_borrowed {
-    ($ty:ty, $owned:ty, $var:ident) => {
+    ($ty:ty, $owned:ty, $var:ident, $fallback:expr) => {
         impl<'a> ValueType<'a> for &'a $ty {
             type Owned = $owned;
+            fn fallback() -> Option<Self> {
+                $fallback
+            }
             fn try_from_value(value: &'a Value) -> Result<Self, EnvError> {
                 match value {
                     Value::$var(f) => Ok(f),
@@ -367,9 +505,13 @@ macro_rules! impl_value_type_arc {
 
 impl_value_type_owned!(f64, Float);
 impl_value_type_owned!(u64, UnsignedInt);
-impl_value_type_owned!(Color, Color);
+impl_value_type_owned!(bool, Bool);
+impl_value_type_owned!(Color, Color, Some(Color::rgb8(0xff, 0x00, 0xff)));
 impl_value_type_owned!(Rect, Rect);
 impl_value_type_owned!(Point, Point);
 impl_value_type_owned!(Size, Size);
-impl_value_type_borrowed!(str, String, String);
+impl_value_type_owned!(Insets, Insets);
+impl_value_type_owned!(Duration, Duration);
+impl_value_type_borrowed!(str, String, String, Some("<missing>"));
 impl_value_type_arc!(LinearGradient, LinearGradient);
+impl_value_type_arc!(RadialGradient, RadialGradient);
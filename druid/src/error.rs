@@ -0,0 +1,45 @@
+// Copyright 2018 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors that widgets can run into.
+
+use std::fmt;
+
+use crate::piet;
+
+/// A failure of the render context while painting.
+///
+/// Painting doesn't stop for these; they are reported as `tracing` events,
+/// inside the span of the widget being painted, and reach the log when no
+/// `tracing` subscriber is set.
+#[derive(Debug)]
+pub enum PaintError {
+    /// The state of the render context couldn't be saved, so what was to
+    /// be painted with it was skipped.
+    Save(piet::Error),
+    /// The state of the render context couldn't be restored, so what is
+    /// painted after may have the wrong transform or clip.
+    Restore(piet::Error),
+}
+
+impl fmt::Display for PaintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            PaintError::Save(e) => write!(f, "saving render context failed: {:?}", e),
+            PaintError::Restore(e) => write!(f, "restoring render context failed: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for PaintError {}
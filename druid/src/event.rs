@@ -16,10 +16,10 @@
 
 use crate::kurbo::{Rect, Shape, Size, Vec2};
 
-use druid_shell::{Clipboard, KeyEvent, KeyModifiers, TimerToken};
+use druid_shell::{Clipboard, ColorScheme, KeyEvent, KeyModifiers, TimerToken};
 
 use crate::mouse::MouseEvent;
-use crate::Command;
+use crate::{Command, Selector, Target, WidgetId};
 
 /// An event, propagated downwards during event flow.
 ///
@@ -93,6 +93,17 @@ pub enum Event {
     /// Because of repeat, there may be a number `KeyDown` events before
     /// a corresponding `KeyUp` is sent.
     KeyUp(KeyEvent),
+    /// Called when alt and a letter or digit are pressed, with the lowercase
+    /// character, to choose the control with that [mnemonic].
+    ///
+    /// This is sent to every widget before the key press itself. A widget
+    /// whose mnemonic it is should act, as if clicked, and call
+    /// [`EventCtx::set_handled`]; the `KeyDown` event is then not sent.
+    /// Mnemonics are not used on macOS, where alt types characters.
+    ///
+    /// [mnemonic]: widget/struct.Label.html#mnemonics
+    /// [`EventCtx::set_handled`]: struct.EventCtx.html#method.set_handled
+    Mnemonic(char),
     /// Called when a paste command is received.
     Paste(Clipboard),
     /// Called when the mouse wheel or trackpad is scrolled.
@@ -108,26 +119,45 @@ pub enum Event {
     HotChanged(bool),
     /// Called when the focus status changes.
     ///
+    /// Like `HotChanged`, this is sent by the [`WidgetPod`] of each widget
+    /// whose status changed, and is not propagated by containers.
+    ///
     /// See [`has_focus`](struct.BaseState.html#method.has_focus) for
     /// discussion about the focus status.
+    ///
+    /// [`WidgetPod`]: struct.WidgetPod.html
     FocusChanged(bool),
     /// Called at the beginning of a new animation frame.
     ///
     /// On the first frame when transitioning from idle to animating, `interval`
     /// will be 0. (This logic is presently per-window but might change to
     /// per-widget to make it more consistent). Otherwise it is in nanoseconds.
+    ///
+    /// Where the platform reports it, the interval is between the times the
+    /// frames are expected to be shown, which are paced by the display's
+    /// refresh; see [`EventCtx::frame_timing`].
+    ///
+    /// [`EventCtx::frame_timing`]: struct.EventCtx.html#method.frame_timing
     AnimFrame(u64),
     /// Called on a timer event.
     ///
     /// Request a timer event through [`EventCtx::request_timer()`]. That will
     /// cause a timer event later.
     ///
-    /// Note that timer events from other widgets may be delivered as well. Use
-    /// the token returned from the `request_timer()` call to filter events more
-    /// precisely.
+    /// The event is delivered only to the widget that requested the timer,
+    /// passing through its ancestors. A container that requests timers of its
+    /// own should compare the token with the one returned from the
+    /// `request_timer()` call, since it also sees its descendants' timers.
     ///
     /// [`EventCtx::request_timer()`]: struct.EventCtx.html#method.request_timer
     Timer(TimerToken),
+    /// Called when the event loop is idle, after [`EventCtx::schedule_idle()`].
+    ///
+    /// As with timer events, idle events scheduled by other widgets may be
+    /// delivered as well; compare the token to the one that was scheduled.
+    ///
+    /// [`EventCtx::schedule_idle()`]: struct.EventCtx.html#method.schedule_idle
+    Idle(IdleToken),
     /// Called with an arbitrary [`Command`], submitted from elsewhere in
     /// the application.
     ///
@@ -135,19 +165,123 @@ pub enum Event {
     /// application-level hotkey, or they can be created dynamically by
     /// [`Widget`]s, at runtime, with [`EventCtx::submit_command`].
     ///
+    /// By default a command is delivered to every widget. Widgets can call
+    /// [`EventCtx::register_for_command`] so that commands with a given
+    /// selector are delivered only to them.
+    ///
     /// [`Command`]: struct.Command.html
     /// [`Widget`]: trait.Widget.html
     /// [`EventCtx::submit_command`]: struct.EventCtx.html#method.submit_command
+    /// [`EventCtx::register_for_command`]: struct.EventCtx.html#method.register_for_command
     Command(Command),
+    /// A [`Command`] on its way to a particular widget.
+    ///
+    /// This is delivered only along the path to the widget with the
+    /// [`WidgetId`] of the target, where it becomes an [`Event::Command`]
+    /// that is also delivered to the widget's descendants. Widgets don't
+    /// need to handle this themselves.
+    ///
+    /// [`Command`]: struct.Command.html
+    /// [`WidgetId`]: struct.WidgetId.html
+    /// [`Event::Command`]: #variant.Command
+    TargetedCommand(Target, Command),
+    /// Asks whether the focused widget can currently handle a command.
+    ///
+    /// This is sent, like keyboard events, only to the focused widget and its
+    /// ancestors, for each of the standard editing commands in
+    /// [`FOCUS_COMMANDS`] that appears in a menu about to be shown, and
+    /// before one of the [`EDITOR_COMMANDS`] is delivered. A widget that can
+    /// handle the command in its current state should call
+    /// [`EventCtx::set_handled`]; otherwise the menu item is disabled, or
+    /// the command is delivered to the whole application.
+    ///
+    /// The widget is asked after the event that made it change has been
+    /// handled and the data updated, never from inside its own `event`.
+    ///
+    /// [`FOCUS_COMMANDS`]: commands/constant.FOCUS_COMMANDS.html
+    /// [`EDITOR_COMMANDS`]: commands/constant.EDITOR_COMMANDS.html
+    /// [`EventCtx::set_handled`]: struct.EventCtx.html#method.set_handled
+    ValidateCommand(Selector),
+}
+
+/// Identifies a piece of deferred work, scheduled with
+/// [`EventCtx::schedule_idle()`].
+///
+/// [`EventCtx::schedule_idle()`]: struct.EventCtx.html#method.schedule_idle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdleToken(usize);
+
+impl IdleToken {
+    /// Create a new `IdleToken` with the given raw value.
+    pub const fn new(raw: usize) -> IdleToken {
+        IdleToken(raw)
+    }
 }
 
 /// Application life cycle events.
+///
+/// Containers should pass these on to all of their children, like other
+/// events; [`WidgetPod`] decides which children they reach.
+///
+/// [`WidgetPod`]: struct.WidgetPod.html
 #[derive(Debug, Clone, Copy)]
 pub enum LifeCycle {
     /// Sent to all widgets in a given window when that window is first instantiated.
     ///
     /// This is guaranteed to be the first event a window receives.
     WindowConnected,
+    /// Sent to a widget before any other event, when it is added to the
+    /// widget tree.
+    ///
+    /// This is the place for one-time setup that needs an [`EventCtx`],
+    /// such as [`register_for_focus`] and [`register_for_command`]. Widgets
+    /// in the initial tree receive it just before `WindowConnected`; widgets
+    /// added later, before their first event.
+    ///
+    /// A widget that has been removed, with `WidgetRemoved`, receives it
+    /// again if it is added back.
+    ///
+    /// [`EventCtx`]: struct.EventCtx.html
+    /// [`register_for_focus`]: struct.EventCtx.html#method.register_for_focus
+    /// [`register_for_command`]: struct.EventCtx.html#method.register_for_command
+    WidgetAdded,
+    /// Sent by a container to a child it stops showing, or is about to
+    /// drop, and so to all of that child's descendants.
+    ///
+    /// The widgets are taken out of the focus chain, and lose focus.
+    WidgetRemoved,
+    /// Sent along the paths to the widgets losing and gaining focus, so that
+    /// each [`WidgetPod`] on the way can update its focus status.
+    ///
+    /// Widgets should only pass this on; the widgets whose status changed
+    /// receive a `FocusChanged` event.
+    ///
+    /// [`WidgetPod`]: struct.WidgetPod.html
+    RouteFocusChanged {
+        /// The widget that had focus, if any.
+        old: Option<WidgetId>,
+        /// The widget that gets focus, if any.
+        new: Option<WidgetId>,
+    },
+    /// Sent to all widgets when alt is pressed, and again when it is
+    /// released, with whether mnemonics should be underlined.
+    ShowMnemonics(bool),
+    /// Sent to all widgets when the system's high contrast mode is turned
+    /// on or off, with whether it is now on.
+    ///
+    /// The [`Env`] has already changed; see [`theme::HIGH_CONTRAST`].
+    ///
+    /// [`Env`]: struct.Env.html
+    /// [`theme::HIGH_CONTRAST`]: theme/constant.HIGH_CONTRAST.html
+    HighContrastChanged(bool),
+    /// Sent to all widgets when the theme switches between its light and
+    /// dark palettes, with the palette now in use.
+    ///
+    /// The [`Env`] has already changed; see [`theme::DARK_MODE`].
+    ///
+    /// [`Env`]: struct.Env.html
+    /// [`theme::DARK_MODE`]: theme/constant.DARK_MODE.html
+    ColorSchemeChanged(ColorScheme),
 }
 
 /// A mouse wheel event.
@@ -213,10 +347,25 @@ impl Event {
         }
     }
 
+    /// Whether the event is delivered according to the mouse position.
+    ///
+    /// Containers with overlapping children should offer these events to
+    /// the topmost child first.
+    pub fn is_pointer_event(&self) -> bool {
+        match self {
+            Event::MouseDown(_)
+            | Event::MouseUp(_)
+            | Event::MouseMoved(_)
+            | Event::Wheel(_)
+            | Event::Zoom(_) => true,
+            _ => false,
+        }
+    }
+
     /// Whether the event should be propagated from parent to children.
     pub(crate) fn recurse(&self) -> bool {
         match self {
-            Event::HotChanged(_) => false,
+            Event::HotChanged(_) | Event::FocusChanged(_) => false,
             _ => true,
         }
     }
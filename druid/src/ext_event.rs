@@ -0,0 +1,227 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Submitting commands from outside the UI thread.
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use log::warn;
+
+use crate::shell::IdleHandle;
+use crate::{Command, Selector, Target, WindowId};
+
+/// A command sent from another thread, not yet delivered.
+type ExtCommand = (Selector, Option<Box<dyn Any + Send>>, Target);
+
+/// Wakes the UI thread when commands arrive.
+struct ExtWaker {
+    /// The window whose run loop is woken.
+    window_id: WindowId,
+    idle: IdleHandle,
+    /// Called on the UI thread with the window's handler; it should cause
+    /// the queue to be drained.
+    wake: fn(&dyn Any),
+}
+
+/// A handle for submitting [`Command`]s to the application from other
+/// threads, such as one doing network or file IO.
+///
+/// An `ExtEventSink` can be cloned and sent to any thread. Commands
+/// submitted through it are delivered on the UI thread, in the order they
+/// were submitted, as if they had been submitted by a widget.
+///
+/// A sink is obtained from [`AppLauncher::get_external_handle`] before the
+/// application is launched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::thread;
+/// use druid::widget::Label;
+/// use druid::{AppLauncher, Selector, Target, WindowDesc};
+///
+/// const DOWNLOADED: Selector = Selector::new("my-app.downloaded");
+///
+/// let window = WindowDesc::new(|| Label::new("Downloading…"));
+/// let launcher = AppLauncher::with_window(window);
+/// let sink = launcher.get_external_handle();
+/// thread::spawn(move || {
+///     let bytes: Vec<u8> = Vec::new(); // fetch something slow
+///     sink.submit_command(DOWNLOADED, bytes, Target::Global);
+/// });
+/// launcher.launch(0u32).expect("launch failed");
+/// ```
+///
+/// [`Command`]: struct.Command.html
+/// [`AppLauncher::get_external_handle`]: struct.AppLauncher.html#method.get_external_handle
+#[derive(Clone)]
+pub struct ExtEventSink {
+    queue: Arc<Mutex<VecDeque<ExtCommand>>>,
+    waker: Arc<Mutex<Option<ExtWaker>>>,
+}
+
+impl ExtEventSink {
+    pub(crate) fn new() -> Self {
+        ExtEventSink {
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            waker: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Submit a [`Command`] with an argument to the application.
+    ///
+    /// The argument must be `Send`, as it crosses threads; the command's
+    /// [`get_object`] returns it on the UI thread.
+    ///
+    /// If no `target` is given, the command is sent to every window, as
+    /// with [`Target::Global`].
+    ///
+    /// Commands submitted before the first window connects are delivered
+    /// once it does. Commands submitted after the application has quit are
+    /// dropped.
+    ///
+    /// [`Command`]: struct.Command.html
+    /// [`get_object`]: struct.Command.html#method.get_object
+    /// [`Target::Global`]: enum.Target.html#variant.Global
+    pub fn submit_command(
+        &self,
+        selector: Selector,
+        arg: impl Any + Send,
+        target: impl Into<Option<Target>>,
+    ) {
+        self.submit(selector, Some(Box::new(arg)), target.into());
+    }
+
+    /// Submit a [`Command`] with no argument to the application.
+    ///
+    /// [`Command`]: struct.Command.html
+    pub fn submit_selector(&self, selector: Selector, target: impl Into<Option<Target>>) {
+        self.submit(selector, None, target.into());
+    }
+
+    fn submit(&self, selector: Selector, arg: Option<Box<dyn Any + Send>>, target: Option<Target>) {
+        let was_empty = match self.queue.lock() {
+            Ok(mut queue) => {
+                let was_empty = queue.is_empty();
+                queue.push_back((selector, arg, target.unwrap_or(Target::Global)));
+                was_empty
+            }
+            Err(_) => {
+                warn!("external command {} dropped, queue is poisoned", selector);
+                return;
+            }
+        };
+        // A wake is already pending if the queue had commands in it.
+        if was_empty {
+            self.wake();
+        }
+    }
+
+    fn wake(&self) {
+        if let Ok(waker) = self.waker.lock() {
+            if let Some(waker) = waker.as_ref() {
+                let wake = waker.wake;
+                waker.idle.add_idle(move |handler| wake(handler));
+            }
+        }
+    }
+
+    /// Set the window whose run loop is woken when commands arrive, unless
+    /// one is set already.
+    pub(crate) fn set_waker(&self, window_id: WindowId, idle: IdleHandle, wake: fn(&dyn Any)) {
+        if let Ok(mut waker) = self.waker.lock() {
+            if waker.is_none() {
+                *waker = Some(ExtWaker {
+                    window_id,
+                    idle,
+                    wake,
+                });
+            } else {
+                return;
+            }
+        }
+        // Deliver anything submitted before there was a window to wake.
+        if self
+            .queue
+            .lock()
+            .map(|queue| !queue.is_empty())
+            .unwrap_or(false)
+        {
+            self.wake();
+        }
+    }
+
+    /// Stop waking a window that is closing.
+    ///
+    /// Returns `true` if it was the window being woken, in which case
+    /// another should be set with [`set_waker`].
+    ///
+    /// [`set_waker`]: #method.set_waker
+    pub(crate) fn window_removed(&self, window_id: WindowId) -> bool {
+        match self.waker.lock() {
+            Ok(mut waker) if waker.as_ref().map(|w| w.window_id) == Some(window_id) => {
+                *waker = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Take the commands submitted so far, in order.
+    pub(crate) fn take_commands(&self) -> Vec<(Target, Command)> {
+        let mut queue = match self.queue.lock() {
+            Ok(queue) => queue,
+            Err(_) => return Vec::new(),
+        };
+        queue
+            .drain(..)
+            .map(|(selector, arg, target)| {
+                let command = match arg {
+                    Some(arg) => Command::from_boxed(selector, arg),
+                    None => Command::from(selector),
+                };
+                (target, command)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const PING: Selector = Selector::new("test.ping");
+
+    #[test]
+    fn commands_from_other_threads() {
+        let sink = ExtEventSink::new();
+        let remote = sink.clone();
+        thread::spawn(move || {
+            remote.submit_command(PING, 42u32, None);
+            remote.submit_selector(PING, Target::Window(WindowId::next()));
+        })
+        .join()
+        .unwrap();
+
+        let commands = sink.take_commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].0, Target::Global);
+        assert_eq!(commands[0].1.get_object(), Some(&42u32));
+        assert!(commands[1].1.get_object::<u32>().is_none());
+        assert!(sink.take_commands().is_empty());
+    }
+}
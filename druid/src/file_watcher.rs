@@ -0,0 +1,219 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watching files for changes made outside of the application.
+//!
+//! This module is only available with the `file-watcher` feature.
+//!
+//! A [`FileWatcher`] watches files and directories on a thread of its own,
+//! and sends each change to the UI thread as a [`FILE_CHANGED`] command,
+//! to the target the path was registered with. Changes are debounced: a
+//! burst of writes to a file, as made by most programs saving it, is
+//! reported as a single change once the file has been quiet for a while.
+//!
+//! [`FileWatcher`]: struct.FileWatcher.html
+//! [`FILE_CHANGED`]: ../commands/constant.FILE_CHANGED.html
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{commands, ExtEventSink, Target};
+
+/// How long a file must be left alone before its changes are reported.
+const DEFAULT_DELAY: Duration = Duration::from_millis(200);
+
+/// A change to a watched file, the payload of a [`FILE_CHANGED`] command.
+///
+/// Paths are absolute.
+///
+/// [`FILE_CHANGED`]: ../commands/constant.FILE_CHANGED.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    /// A file or directory was created.
+    Created(PathBuf),
+    /// A file was written to.
+    Modified(PathBuf),
+    /// A file or directory was removed.
+    Removed(PathBuf),
+    /// A file or directory was moved or renamed.
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+/// The paths being watched, and where their changes are sent.
+type Targets = Arc<Mutex<HashMap<PathBuf, Target>>>;
+
+/// Watches files and directories, and sends their changes as commands.
+///
+/// Watching stops when the watcher is dropped, so it is usually kept for as
+/// long as the application runs, for instance by an [`AppDelegate`].
+///
+/// Many programs save a file by writing a new one and renaming it over the
+/// old; to notice such changes, watch the directory that contains the file
+/// and look at the paths of the changes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::file_watcher::FileWatcher;
+/// use druid::widget::Label;
+/// use druid::{AppLauncher, Target, WindowDesc};
+///
+/// let launcher = AppLauncher::with_window(WindowDesc::new(|| Label::new("Editor")));
+/// let mut watcher = FileWatcher::new(launcher.get_external_handle()).unwrap();
+/// watcher.watch("notes.txt", Target::Global).unwrap();
+/// ```
+///
+/// [`AppDelegate`]: ../trait.AppDelegate.html
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    targets: Targets,
+}
+
+impl FileChange {
+    /// The path of the file that changed; for a rename, its old path.
+    pub fn path(&self) -> &Path {
+        match self {
+            FileChange::Created(path) | FileChange::Modified(path) | FileChange::Removed(path) => {
+                path
+            }
+            FileChange::Renamed { from, .. } => from,
+        }
+    }
+}
+
+impl FileWatcher {
+    /// Create a watcher that sends changes through `sink`.
+    pub fn new(sink: ExtEventSink) -> io::Result<FileWatcher> {
+        FileWatcher::with_delay(sink, DEFAULT_DELAY)
+    }
+
+    /// Create a watcher that sends changes through `sink`, once a file has
+    /// been left alone for `delay`.
+    pub fn with_delay(sink: ExtEventSink, delay: Duration) -> io::Result<FileWatcher> {
+        let (tx, rx) = mpsc::channel();
+        let watcher = notify::watcher(tx, delay).map_err(to_io_error)?;
+        let targets = Targets::default();
+        let thread_targets = targets.clone();
+        thread::Builder::new()
+            .name("file-watcher".into())
+            .spawn(move || forward(rx, thread_targets, sink))?;
+        Ok(FileWatcher { watcher, targets })
+    }
+
+    /// Watch `path`, sending its changes to `target`. If `path` is a
+    /// directory, the changes of everything in it are sent, unless a path
+    /// within it is watched with another target.
+    pub fn watch(&mut self, path: impl AsRef<Path>, target: impl Into<Target>) -> io::Result<()> {
+        let path = absolute(path.as_ref());
+        let mode = if path.is_dir() {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        self.watcher.watch(&path, mode).map_err(to_io_error)?;
+        if let Ok(mut targets) = self.targets.lock() {
+            targets.insert(path, target.into());
+        }
+        Ok(())
+    }
+
+    /// Stop watching `path`.
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = absolute(path.as_ref());
+        if let Ok(mut targets) = self.targets.lock() {
+            targets.remove(&path);
+        }
+        self.watcher.unwatch(&path).map_err(to_io_error)
+    }
+}
+
+/// Send the changes reported by the watcher, until it is dropped.
+fn forward(events: Receiver<DebouncedEvent>, targets: Targets, sink: ExtEventSink) {
+    for event in events {
+        let change = match event {
+            DebouncedEvent::Create(path) => FileChange::Created(path),
+            DebouncedEvent::Write(path) => FileChange::Modified(path),
+            DebouncedEvent::Remove(path) => FileChange::Removed(path),
+            DebouncedEvent::Rename(from, to) => FileChange::Renamed { from, to },
+            DebouncedEvent::Error(err, path) => {
+                warn!("error watching {:?}: {}", path, err);
+                continue;
+            }
+            // Notices come ahead of the debounced changes, and changes of
+            // permissions don't change the contents.
+            _ => continue,
+        };
+        let target = match targets.lock() {
+            // A file renamed into a watched directory, as when a file is
+            // saved by renaming a new one over it, is only watched at `to`.
+            Ok(targets) => match &change {
+                FileChange::Renamed { from, to } => {
+                    target_for(&targets, from).or_else(|| target_for(&targets, to))
+                }
+                _ => target_for(&targets, change.path()),
+            },
+            Err(_) => return,
+        };
+        if let Some(target) = target {
+            sink.submit_command(commands::FILE_CHANGED, change, target);
+        }
+    }
+}
+
+/// The target of the closest watched path that contains `path`.
+fn target_for(targets: &HashMap<PathBuf, Target>, path: &Path) -> Option<Target> {
+    path.ancestors().find_map(|path| targets.get(path)).copied()
+}
+
+/// The path the watcher reports changes of `path` with.
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+fn to_io_error(err: notify::Error) -> io::Error {
+    match err {
+        notify::Error::Io(err) => err,
+        notify::Error::PathNotFound => io::Error::new(io::ErrorKind::NotFound, "path not found"),
+        err => io::Error::new(io::ErrorKind::Other, err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WindowId;
+
+    #[test]
+    fn closest_watched_path() {
+        let project = Target::Window(WindowId::next());
+        let notes = Target::Global;
+        let mut targets = HashMap::new();
+        targets.insert(PathBuf::from("/project"), project);
+        targets.insert(PathBuf::from("/project/notes.txt"), notes);
+
+        let target = |path: &str| target_for(&targets, Path::new(path));
+        assert_eq!(target("/project/src/main.rs"), Some(project));
+        assert_eq!(target("/project/notes.txt"), Some(notes));
+        assert_eq!(target("/elsewhere/notes.txt"), None);
+    }
+}
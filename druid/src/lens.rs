@@ -14,6 +14,8 @@
 
 //! Support for lenses, a way of focusing on subfields of data.
 
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
 use std::marker::PhantomData;
 use std::ops;
 use std::sync::Arc;
@@ -166,6 +168,26 @@ pub trait LensExt<A: ?Sized, B: ?Sized>: Lens<A, B> {
     {
         InArc::new(self)
     }
+
+    /// Combine a `Lens<A, B>` with a `Lens<A, C>` to produce a `Lens<A, (B, C)>`
+    ///
+    /// ```
+    /// # use druid::*;
+    /// let lens = lens!((u32, bool, f64), 0).zip(lens!((u32, bool, f64), 2));
+    /// let mut data = (1, true, 2.0);
+    /// assert_eq!(lens.get(&data), (1, 2.0));
+    /// lens.put(&mut data, (3, 4.0));
+    /// assert_eq!(data, (3, true, 4.0));
+    /// ```
+    fn zip<Other, C>(self, other: Other) -> Tuple2<Self, Other>
+    where
+        Other: Lens<A, C> + Sized,
+        B: Data,
+        C: Data,
+        Self: Sized,
+    {
+        Tuple2::new(self, other)
+    }
 }
 
 impl<A: ?Sized, B: ?Sized, T: Lens<A, B>> LensExt<A, B> for T {}
@@ -506,3 +528,246 @@ where
         v
     }
 }
+
+/// A `Lens` that joins the targets of two lenses over the same data
+/// into a tuple
+///
+/// The values are cloned on read. On mutation, each value is written
+/// back through its lens if it is no longer the [`same`].
+///
+/// See also `LensExt::zip`.
+///
+/// [`same`]: ../trait.Data.html#tymethod.same
+#[derive(Debug, Copy, Clone)]
+pub struct Tuple2<L1, L2> {
+    first: L1,
+    second: L2,
+}
+
+impl<L1, L2> Tuple2<L1, L2> {
+    /// Construct a lens from two lenses over the same data
+    pub fn new<A: ?Sized, B, C>(first: L1, second: L2) -> Self
+    where
+        L1: Lens<A, B>,
+        L2: Lens<A, C>,
+        B: Data,
+        C: Data,
+    {
+        Tuple2 { first, second }
+    }
+}
+
+impl<A, B, C, L1, L2> Lens<A, (B, C)> for Tuple2<L1, L2>
+where
+    A: ?Sized,
+    B: Data,
+    C: Data,
+    L1: Lens<A, B>,
+    L2: Lens<A, C>,
+{
+    fn with<V, F: FnOnce(&(B, C)) -> V>(&self, data: &A, f: F) -> V {
+        let first = self.first.with(data, |x| x.clone());
+        let second = self.second.with(data, |x| x.clone());
+        f(&(first, second))
+    }
+
+    fn with_mut<V, F: FnOnce(&mut (B, C)) -> V>(&self, data: &mut A, f: F) -> V {
+        let first = self.first.with(data, |x| x.clone());
+        let second = self.second.with(data, |x| x.clone());
+        let mut temp = (first, second);
+        let v = f(&mut temp);
+        let (first, second) = temp;
+        if self.first.with(data, |x| !x.same(&first)) {
+            self.first.with_mut(data, |x| *x = first);
+        }
+        if self.second.with(data, |x| !x.same(&second)) {
+            self.second.with_mut(data, |x| *x = second);
+        }
+        v
+    }
+}
+
+/// A collection whose items can be looked up by key.
+///
+/// This is implemented for vectors, which are keyed by index, and for maps.
+/// It is used by the [`Select`] lens.
+///
+/// [`Select`]: struct.Select.html
+pub trait KeyedCollection<K> {
+    /// The type of the items in the collection.
+    type Item;
+
+    /// Get the item with the given key, if there is one.
+    fn get_by_key(&self, key: &K) -> Option<&Self::Item>;
+
+    /// Get mutable access to the item with the given key, if there is one.
+    fn get_by_key_mut(&mut self, key: &K) -> Option<&mut Self::Item>;
+}
+
+impl<T> KeyedCollection<usize> for Vec<T> {
+    type Item = T;
+
+    fn get_by_key(&self, key: &usize) -> Option<&T> {
+        self.get(*key)
+    }
+
+    fn get_by_key_mut(&mut self, key: &usize) -> Option<&mut T> {
+        self.get_mut(*key)
+    }
+}
+
+impl<K: Hash + Eq, V> KeyedCollection<K> for HashMap<K, V> {
+    type Item = V;
+
+    fn get_by_key(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_by_key_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+}
+
+impl<K: Ord, V> KeyedCollection<K> for BTreeMap<K, V> {
+    type Item = V;
+
+    fn get_by_key(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_by_key_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+}
+
+/// Mutable access makes a copy of the collection if it is shared; the
+/// [`Select`] lens only asks for it when an item has actually changed.
+///
+/// [`Select`]: struct.Select.html
+impl<K, C: KeyedCollection<K> + Clone> KeyedCollection<K> for Arc<C> {
+    type Item = C::Item;
+
+    fn get_by_key(&self, key: &K) -> Option<&C::Item> {
+        self.as_ref().get_by_key(key)
+    }
+
+    fn get_by_key_mut(&mut self, key: &K) -> Option<&mut C::Item> {
+        Arc::make_mut(self).get_by_key_mut(key)
+    }
+}
+
+/// A `Lens` that focuses on the selected item of a collection.
+///
+/// `Select` combines a lens to a [`KeyedCollection`] with a lens to an
+/// `Option` of a key, and exposes `Some` item if the key is set and present
+/// in the collection, or `None` otherwise. This lets a detail view bind
+/// directly to the currently selected item.
+///
+/// The item is cloned on read. Changes to it are written back to the
+/// collection only if the item is no longer the [`same`]; setting it to
+/// `None` has no effect, as does any change when nothing is selected.
+///
+/// ```
+/// # use druid::*;
+/// # use std::sync::Arc;
+/// use druid::lens::Select;
+///
+/// #[derive(Clone)]
+/// struct Contacts {
+///     names: Arc<Vec<String>>,
+///     selected: Option<usize>,
+/// }
+///
+/// let lens = Select::new(lens!(Contacts, names), lens!(Contacts, selected));
+/// let mut data = Contacts {
+///     names: Arc::new(vec!["Ana".to_string(), "Bo".to_string()]),
+///     selected: Some(1),
+/// };
+/// assert_eq!(lens.get(&data), Some("Bo".to_string()));
+/// lens.put(&mut data, Some("Bea".to_string()));
+/// assert_eq!(data.names[1], "Bea");
+/// data.selected = None;
+/// assert_eq!(lens.get(&data), None);
+/// ```
+///
+/// [`KeyedCollection`]: trait.KeyedCollection.html
+/// [`same`]: ../trait.Data.html#tymethod.same
+#[derive(Debug, Copy)]
+pub struct Select<C, K, Coll, Key> {
+    collection: C,
+    key: K,
+    _marker: PhantomData<(Coll, Key)>,
+}
+
+impl<C, K, Coll, Key> Select<C, K, Coll, Key> {
+    /// Construct a lens from a lens to a collection and a lens to the key
+    /// of the selected item.
+    pub fn new<A: ?Sized>(collection: C, key: K) -> Self
+    where
+        C: Lens<A, Coll>,
+        K: Lens<A, Option<Key>>,
+        Coll: KeyedCollection<Key>,
+    {
+        Select {
+            collection,
+            key,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A, C, K, Coll, Key> Lens<A, Option<Coll::Item>> for Select<C, K, Coll, Key>
+where
+    A: ?Sized,
+    C: Lens<A, Coll>,
+    K: Lens<A, Option<Key>>,
+    Coll: KeyedCollection<Key>,
+    Coll::Item: Data,
+    Key: Clone,
+{
+    fn with<V, F: FnOnce(&Option<Coll::Item>) -> V>(&self, data: &A, f: F) -> V {
+        let item = match self.key.with(data, |key| key.clone()) {
+            Some(key) => self
+                .collection
+                .with(data, |coll| coll.get_by_key(&key).cloned()),
+            None => None,
+        };
+        f(&item)
+    }
+
+    fn with_mut<V, F: FnOnce(&mut Option<Coll::Item>) -> V>(&self, data: &mut A, f: F) -> V {
+        let key = match self.key.with(data, |key| key.clone()) {
+            Some(key) => key,
+            None => return f(&mut None),
+        };
+        let mut temp = self
+            .collection
+            .with(data, |coll| coll.get_by_key(&key).cloned());
+        let v = f(&mut temp);
+        if let Some(new) = temp {
+            let changed = self.collection.with(data, |coll| {
+                coll.get_by_key(&key)
+                    .map(|old| !old.same(&new))
+                    .unwrap_or(false)
+            });
+            if changed {
+                self.collection.with_mut(data, |coll| {
+                    if let Some(item) = coll.get_by_key_mut(&key) {
+                        *item = new;
+                    }
+                });
+            }
+        }
+        v
+    }
+}
+
+impl<C: Clone, K: Clone, Coll, Key> Clone for Select<C, K, Coll, Key> {
+    fn clone(&self) -> Self {
+        Select {
+            collection: self.collection.clone(),
+            key: self.key.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
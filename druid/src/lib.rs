@@ -21,44 +21,95 @@
 use druid_shell as shell;
 pub use druid_shell::{kurbo, piet};
 
+mod animation;
 mod app;
 mod app_delegate;
+mod automation;
+mod bloom;
 mod box_constraints;
+mod capture;
 mod command;
+mod command_line;
 mod core;
+mod crash;
 mod data;
+mod dialog;
+mod direction;
+mod easing;
+mod edit_action;
 mod env;
+mod error;
 mod event;
+mod ext_event;
+#[cfg(feature = "file-watcher")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-watcher")))]
+pub mod file_watcher;
 pub mod lens;
 mod localization;
 mod menu;
+mod mnemonic;
 mod mouse;
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+pub mod parallel;
+mod persistence;
+pub mod process;
+mod raster;
+mod resources;
+mod session;
+mod spawn;
+#[cfg(feature = "spellcheck")]
+#[cfg_attr(docsrs, doc(cfg(feature = "spellcheck")))]
+pub mod spellcheck;
+mod text_buffer;
 pub mod theme;
+mod view_state;
 pub mod widget;
 mod win_handler;
 mod window;
 
 // these are the types from shell that we expose; others we only use internally.
 pub use shell::{
-    Application, Clipboard, ClipboardFormat, Cursor, FileDialogOptions, FileInfo, FileSpec,
-    FormatId, HotKey, KeyCode, KeyEvent, KeyModifiers, MouseButton, RawMods, SysMods, Text,
-    TimerToken, WinCtx, WindowHandle,
+    Application, Clipboard, ClipboardFormat, ColorScheme, ColorSpace, Cursor, DialogStyle,
+    FileDialogOptions, FileInfo, FileSpec, FontMetrics, FormatId, FrameTiming, HighContrast,
+    HotKey, KeyCode, KeyEvent, KeyModifiers, MessageButtons, MessageDialogOptions, Monitor,
+    MouseButton, Politeness, PowerSource, RawMods, RenderBackend, Screen, SysMods, SystemEvent,
+    Text, TextAntialiasing, TextHinting, TextMetrics, TextRendering, TimerToken, WinCtx,
+    WindowHandle,
 };
 
 pub use crate::core::{
-    BaseState, BoxedWidget, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, WidgetPod,
+    BaseState, BoxedWidget, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, WidgetId, WidgetPod,
 };
-pub use app::{AppLauncher, WindowDesc};
+pub use animation::{Animation, AnimationId, Animator, Interpolate};
+pub use app::{AppLauncher, WindowDesc, WindowPlacement, WindowSizePolicy};
 pub use app_delegate::{AppDelegate, DelegateCtx};
+pub use automation::{AutomationNode, AutomationQuery};
 pub use box_constraints::BoxConstraints;
-pub use command::{sys as commands, Command, Selector};
-pub use data::Data;
-pub use env::{Env, Key, Value};
-pub use event::{Event, LifeCycle, WheelEvent};
+pub use capture::{CaptureRequest, ImageBuf};
+pub use command::{sys as commands, Command, Selector, Target};
+pub use command_line::CommandLine;
+pub use crash::CrashReport;
+pub use data::{Approx, ApproxEq, Data, TotalOrd};
+pub use dialog::MessageDialog;
+pub use direction::{Alignment, LayoutDirection, LogicalInsets};
+pub use easing::Easing;
+pub use edit_action::{EditAction, KeyMap, Movement};
+pub use env::{Env, Key, MissingKeyPolicy, Value};
+pub use error::PaintError;
+pub use event::{Event, IdleToken, LifeCycle, WheelEvent};
+pub use ext_event::ExtEventSink;
 pub use lens::{Lens, LensExt, LensWrap};
 pub use localization::LocalizedString;
 pub use menu::{sys as platform_menus, ContextMenu, MenuDesc, MenuItem};
 pub use mouse::MouseEvent;
+pub use persistence::Store;
+pub use raster::Raster;
+pub use resources::{Resources, WindowResources};
+pub use session::{Session, SessionWindow};
+pub use spawn::{BoxedFuture, Executor};
+pub use text_buffer::{EditableText, TextBuffer, TextEdit};
+pub use view_state::PersistView;
 pub use widget::Widget;
 pub use win_handler::DruidHandler;
 pub use window::{Window, WindowId};
@@ -89,6 +89,13 @@ struct ArgSource<T>(ArgClosure<T>);
 ///
 /// At its simplest, a `LocalizedString` is a key that can be resolved
 /// against a map of localized strings for a given locale.
+///
+/// A string can also have named arguments, which are computed from the
+/// widget's data by closures added with [`with_arg`]. The string is resolved
+/// again whenever the data or the environment changes, so arguments stay
+/// current; numeric arguments can be used with Fluent's plural rules.
+///
+/// [`with_arg`]: #method.with_arg
 #[derive(Debug, Clone)]
 pub struct LocalizedString<T> {
     pub(crate) key: &'static str,
@@ -96,8 +103,16 @@ pub struct LocalizedString<T> {
     args: Option<Vec<(&'static str, ArgSource<T>)>>,
     resolved: Option<String>,
     resolved_lang: Option<LanguageIdentifier>,
+    /// The data the arguments were last computed from.
+    resolved_data: Option<T>,
+    /// The environment the arguments were last computed with.
+    resolved_env: Option<ArgEnv>,
 }
 
+/// The environment arguments were computed with.
+#[derive(Clone)]
+struct ArgEnv(Env);
+
 /// A stack of localization resources, used for fallback.
 struct BundleStack(Vec<FluentBundle<Arc<FluentResource>>>);
 
@@ -300,6 +315,8 @@ impl<T> LocalizedString<T> {
             placeholder: None,
             resolved: None,
             resolved_lang: None,
+            resolved_data: None,
+            resolved_env: None,
         }
     }
 
@@ -327,6 +344,23 @@ impl<T: Data> LocalizedString<T> {
     /// is a function that will return a value for the given key from the current
     /// environment and data.
     ///
+    /// Arguments are recomputed when the data, the environment or the locale
+    /// changes. Numbers can be used to select a plural form:
+    ///
+    /// ```text
+    /// items-selected = { $count ->
+    ///     [one] One item selected
+    ///    *[other] { $count } items selected
+    /// }
+    /// ```
+    ///
+    /// ```
+    /// use druid::LocalizedString;
+    ///
+    /// let text: LocalizedString<usize> = LocalizedString::new("items-selected")
+    ///     .with_arg("count", |count: &usize, _env| (*count).into());
+    /// ```
+    ///
     /// [`ArgClosure`]: type.ArgClosure.html
     pub fn with_arg(
         mut self,
@@ -342,12 +376,22 @@ impl<T: Data> LocalizedString<T> {
     /// Lazily compute the localized value for this string based on the provided
     /// environment and data.
     ///
+    /// The string is recomputed if the language has changed, or if it has
+    /// arguments and `data` or `env` is not the same as the last time it was
+    /// resolved.
+    ///
     /// Returns `true` if the current value of the string has changed.
     pub fn resolve<'a>(&'a mut self, data: &T, env: &Env) -> bool {
-        //TODO: this recomputes the string if either the language has changed,
-        //or *anytime* we have arguments. Ideally we would be using a lens
-        //to only recompute when our actual data has changed.
-        if self.args.is_some()
+        let data_same = self
+            .resolved_data
+            .as_ref()
+            .map_or(false, |old| old.same(data));
+        let env_same = self
+            .resolved_env
+            .as_ref()
+            .map_or(false, |old| old.0.same(env));
+        let args_changed = self.args.is_some() && !(data_same && env_same);
+        if args_changed
             || self.resolved_lang.as_ref() != Some(&env.localization_manager().current_locale)
         {
             let args: Option<FluentArgs> = self
@@ -356,6 +400,10 @@ impl<T: Data> LocalizedString<T> {
                 .map(|a| a.iter().map(|(k, v)| (*k, (v.0)(data, env))).collect());
 
             self.resolved_lang = Some(env.localization_manager().current_locale.clone());
+            if self.args.is_some() {
+                self.resolved_data = Some(data.clone());
+                self.resolved_env = Some(ArgEnv(env.clone()));
+            }
             let next = env.localization_manager().localize(self.key, args.as_ref());
             let result = next != self.resolved;
             self.resolved = next;
@@ -366,6 +414,12 @@ impl<T: Data> LocalizedString<T> {
     }
 }
 
+impl std::fmt::Debug for ArgEnv {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Env")
+    }
+}
+
 impl<T> std::fmt::Debug for ArgSource<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "Arg Resolver {:p}", self.0)
@@ -35,6 +35,24 @@
 //! To change the menu for a window, you issue a [`SET_MENU`] command, the payload
 //! of which should be a new [`MenuDesc`]. The new menu will replace the old menu.
 //!
+//! Items that only need to be enabled, disabled or checked as the data
+//! changes don't need a new menu: give them a predicate with
+//! [`enabled_when`] or [`selected_when`], and the window's menu is rebuilt
+//! when the result changes.
+//!
+//! ## Context menus
+//!
+//! A widget shows a context menu with [`EventCtx::show_context_menu`],
+//! usually in response to a right click. Its items send their commands to
+//! the widget's window, like those of the window menu.
+//!
+//! ## Mnemonics
+//!
+//! An ampersand in a title marks the next letter as the item's mnemonic,
+//! as in `"&File"`, like the mnemonics of [labels]. Windows underlines it
+//! in native menus, and the [`MenuBar`] widget on every platform but macOS;
+//! elsewhere the marker is removed.
+//!
 //! ## The macOS app menu
 //!
 //! On macOS, the main menu belongs to the application, not to the window.
@@ -100,14 +118,21 @@
 //! ```
 //!
 //! [`MenuDesc`]: struct.MenuDesc.html
+//! [labels]: ../widget/struct.Label.html#mnemonics
+//! [`MenuBar`]: ../widget/struct.MenuBar.html
 //! [`Command`]: ../struct.Command.html
 //! [`Command` event]: ../enum.Event.html#variant.Command
 //! [`Selector`]: ../struct.Selector.html
 //! [`SET_MENU`]: ../struct.Selector.html#associatedconstant.SET_MENU
+//! [`enabled_when`]: struct.MenuItem.html#method.enabled_when
+//! [`selected_when`]: struct.MenuItem.html#method.selected_when
+//! [`EventCtx::show_context_menu`]: ../struct.EventCtx.html#method.show_context_menu
 
 use std::num::NonZeroU32;
+use std::sync::Arc;
 
 use crate::kurbo::Point;
+use crate::mnemonic;
 use crate::shell::{HotKey, KeyCompare, Menu as PlatformMenu, RawMods, SysMods};
 use crate::{commands, Command, Data, Env, KeyCode, LocalizedString, Selector};
 
@@ -148,10 +173,20 @@ pub struct MenuItem<T> {
     //highlighted: bool,
     selected: bool,
     enabled: bool, // (or state is stored elsewhere)
+    /// Computes `enabled` from the data, if it depends on it.
+    enabled_when: Option<Predicate<T>>,
+    /// Computes `selected` from the data, if it depends on it.
+    selected_when: Option<Predicate<T>>,
+    /// Whether the focused widget can handle this item's command; only
+    /// meaningful for the standard editing commands.
+    focus_enabled: bool,
     /// Identifies the platform object corresponding to this item.
     platform_id: MenuItemId,
 }
 
+/// A function of the data that decides an item's state.
+struct Predicate<T>(Arc<dyn Fn(&T, &Env) -> bool>);
+
 /// A menu displayed as a pop-over.
 #[derive(Debug, Clone)]
 pub struct ContextMenu<T> {
@@ -178,6 +213,9 @@ impl<T> MenuItem<T> {
             tool_tip: None,
             selected: false,
             enabled: true,
+            enabled_when: None,
+            selected_when: None,
+            focus_enabled: true,
             platform_id: MenuItemId::PLACEHOLDER,
         }
     }
@@ -228,6 +266,71 @@ impl<T> MenuItem<T> {
         }
         self
     }
+
+    /// Enable this item only while `f` returns `true` for the current data.
+    ///
+    /// Unlike [`disabled_if`], this is checked again whenever the data
+    /// changes, and the menu is updated to match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use druid::{LocalizedString, MenuDesc, MenuItem, Selector};
+    /// let undo = MenuItem::new(LocalizedString::new("Undo"), Selector::new("my-app.undo"))
+    ///     .enabled_when(|history: &Vec<String>, _env| !history.is_empty());
+    /// # MenuDesc::empty().append(undo);
+    /// ```
+    ///
+    /// [`disabled_if`]: #method.disabled_if
+    pub fn enabled_when(mut self, f: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.enabled_when = Some(Predicate(Arc::new(f)));
+        self
+    }
+
+    /// Check this item only while `f` returns `true` for the current data.
+    ///
+    /// Like [`enabled_when`], this is checked again whenever the data changes.
+    ///
+    /// [`enabled_when`]: #method.enabled_when
+    pub fn selected_when(mut self, f: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.selected_when = Some(Predicate(Arc::new(f)));
+        self
+    }
+
+    /// Recompute the state that depends on the data.
+    ///
+    /// Returns `true` if anything changed.
+    fn update_state(&mut self, data: &T, env: &Env) -> bool {
+        let mut changed = false;
+        if let Some(Predicate(f)) = &self.enabled_when {
+            let enabled = f(data, env);
+            changed |= enabled != self.enabled;
+            self.enabled = enabled;
+        }
+        if let Some(Predicate(f)) = &self.selected_when {
+            let selected = f(data, env);
+            changed |= selected != self.selected;
+            self.selected = selected;
+        }
+        changed
+    }
+
+    /// The title, as last resolved.
+    pub(crate) fn title(&self) -> &str {
+        self.title.localized_str()
+    }
+
+    pub(crate) fn command(&self) -> &Command {
+        &self.command
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn is_selected(&self) -> bool {
+        self.selected
+    }
 }
 
 impl<T: Data> MenuDesc<T> {
@@ -318,6 +421,56 @@ impl<T: Data> MenuDesc<T> {
         self.items.is_empty()
     }
 
+    /// The title of this menu, as last resolved.
+    pub(crate) fn title(&self) -> &str {
+        self.item.title()
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.item.enabled
+    }
+
+    /// The items, submenus and separators of this menu.
+    pub(crate) fn entries(&self) -> &[MenuEntry<T>] {
+        &self.items
+    }
+
+    /// Resolve the localized titles of this menu and all of its submenus,
+    /// for menus that druid draws itself.
+    ///
+    /// Returns `true` if any title changed.
+    pub(crate) fn resolve_titles(&mut self, data: &T, env: &Env) -> bool {
+        let mut changed = self.item.title.resolve(data, env);
+        for entry in &mut self.items {
+            match entry {
+                MenuEntry::Item(item) => changed |= item.title.resolve(data, env),
+                MenuEntry::SubMenu(menu) => changed |= menu.resolve_titles(data, env),
+                MenuEntry::Separator => (),
+            }
+        }
+        changed
+    }
+
+    /// Recompute the state of the items that depend on the data, and
+    /// resolve all the titles.
+    ///
+    /// Returns `true` if anything changed, in which case the platform menu
+    /// needs to be rebuilt.
+    pub(crate) fn update(&mut self, data: &T, env: &Env) -> bool {
+        let mut changed = self.item.title.resolve(data, env);
+        for entry in &mut self.items {
+            match entry {
+                MenuEntry::Item(item) => {
+                    changed |= item.title.resolve(data, env);
+                    changed |= item.update_state(data, env);
+                }
+                MenuEntry::SubMenu(menu) => changed |= menu.update(data, env),
+                MenuEntry::Separator => (),
+            }
+        }
+        changed
+    }
+
     /// Build an application or window menu for the current platform.
     ///
     /// This takes self as &mut because it resolves localization.
@@ -334,6 +487,7 @@ impl<T: Data> MenuDesc<T> {
 
     /// impl shared for window & context menus
     fn build_native_menu(&mut self, data: &T, env: &Env, for_popup: bool) -> PlatformMenu {
+        self.item.title.resolve(data, env);
         let mut menu = if for_popup {
             PlatformMenu::new_for_popup()
         } else {
@@ -343,12 +497,13 @@ impl<T: Data> MenuDesc<T> {
             match item {
                 MenuEntry::Item(ref mut item) => {
                     item.title.resolve(data, env);
+                    item.update_state(data, env);
                     item.platform_id = MenuItemId::next();
                     menu.add_item(
                         item.platform_id.as_u32(),
-                        item.title.localized_str(),
+                        &platform_title(item.title.localized_str()),
                         item.hotkey.as_ref(),
-                        item.enabled,
+                        item.enabled && item.focus_enabled,
                         item.selected,
                     );
                 }
@@ -358,7 +513,7 @@ impl<T: Data> MenuDesc<T> {
                     submenu.item.title.resolve(data, env);
                     menu.add_dropdown(
                         sub,
-                        &submenu.item.title.localized_str(),
+                        &platform_title(submenu.item.title.localized_str()),
                         submenu.item.enabled,
                     );
                 }
@@ -367,6 +522,34 @@ impl<T: Data> MenuDesc<T> {
         menu
     }
 
+    /// Enable or disable the items for the standard editing commands
+    /// ([`FOCUS_COMMANDS`]), depending on whether the focused widget can currently handle them.
+    ///
+    /// Returns `true` if any item changed, in which case the platform menu
+    /// needs to be rebuilt.
+    ///
+    /// [`FOCUS_COMMANDS`]: ../commands/constant.FOCUS_COMMANDS.html
+    pub(crate) fn validate_focus_commands(
+        &mut self,
+        can_handle: &mut dyn FnMut(&Selector) -> bool,
+    ) -> bool {
+        let mut changed = false;
+        for item in &mut self.items {
+            match item {
+                MenuEntry::Item(item) => {
+                    if commands::FOCUS_COMMANDS.contains(&item.command.selector) {
+                        let enabled = can_handle(&item.command.selector);
+                        changed |= enabled != item.focus_enabled;
+                        item.focus_enabled = enabled;
+                    }
+                }
+                MenuEntry::SubMenu(menu) => changed |= menu.validate_focus_commands(can_handle),
+                MenuEntry::Separator => (),
+            }
+        }
+        changed
+    }
+
     /// Given a command identifier from druid-shell, returns the command
     /// corresponding to that id in this menu, if one exists.
     pub(crate) fn command_for_id(&self, id: u32) -> Option<Command> {
@@ -387,7 +570,30 @@ impl<T: Data> MenuDesc<T> {
     }
 }
 
+/// The title of a native menu item. Windows shows mnemonic markers itself;
+/// other platforms would show them as they are.
+fn platform_title(title: &str) -> String {
+    if cfg!(target_os = "windows") {
+        title.to_string()
+    } else {
+        mnemonic::parse(title).0
+    }
+}
+
+impl<T> Clone for Predicate<T> {
+    fn clone(&self) -> Self {
+        Predicate(self.0.clone())
+    }
+}
+
+impl<T> std::fmt::Debug for Predicate<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Predicate")
+    }
+}
+
 impl<T> ContextMenu<T> {
+    /// Create a context menu shown at `location`, in window coordinates.
     pub fn new(menu: MenuDesc<T>, location: Point) -> Self {
         ContextMenu { menu, location }
     }
@@ -476,6 +682,15 @@ pub mod sys {
                 .hotkey(SysMods::Cmd, "v")
         }
 
+        /// The 'Select All' menu item.
+        pub fn select_all<T: Data>() -> MenuItem<T> {
+            MenuItem::new(
+                LocalizedString::new("common-menu-select-all"),
+                commands::SELECT_ALL,
+            )
+            .hotkey(SysMods::Cmd, "a")
+        }
+
         /// The 'Undo' menu item.
         pub fn undo<T: Data>() -> MenuItem<T> {
             MenuItem::new(LocalizedString::new("common-menu-undo"), commands::UNDO)
@@ -805,3 +1020,39 @@ pub mod sys {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_follows_data() {
+        let env = Env::default();
+        let mut menu: MenuDesc<u32> = MenuDesc::empty()
+            .append(
+                MenuItem::new(LocalizedString::new("Even"), Selector::NOOP)
+                    .selected_when(|data: &u32, _| data % 2 == 0),
+            )
+            .append(
+                MenuDesc::empty().append(
+                    MenuItem::new(LocalizedString::new("Big"), Selector::NOOP)
+                        .enabled_when(|data: &u32, _| *data > 10),
+                ),
+            );
+        menu.update(&1, &env);
+
+        assert!(menu.update(&2, &env));
+        assert!(!menu.update(&4, &env));
+        assert!(menu.update(&12, &env));
+        match (&menu.items[0], &menu.items[1]) {
+            (MenuEntry::Item(even), MenuEntry::SubMenu(sub)) => {
+                assert!(even.is_selected());
+                assert!(sub.entries().iter().all(|entry| match entry {
+                    MenuEntry::Item(big) => big.is_enabled(),
+                    _ => false,
+                }));
+            }
+            _ => panic!("unexpected menu layout"),
+        }
+    }
+}
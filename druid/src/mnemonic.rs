@@ -0,0 +1,118 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mnemonics: the underlined letters that choose a control with alt.
+//!
+//! A mnemonic is marked in a title with an ampersand before a letter or
+//! digit, as in `"&File"`; `"&&"` is a literal ampersand, as is an
+//! ampersand before any other character. Only the first marker counts.
+
+use druid_shell::{KeyCode, KeyEvent};
+
+/// Whether mnemonics are used on this platform. On macOS alt (option)
+/// types characters, and controls have no mnemonics.
+pub(crate) const ENABLED: bool = cfg!(not(target_os = "macos"));
+
+/// The mnemonic of a title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Mnemonic {
+    /// The byte offset of the underlined character, in the displayed text.
+    pub offset: usize,
+    /// The underlined character, in lowercase.
+    pub key: char,
+}
+
+impl Mnemonic {
+    /// The byte range of the underlined character, in `text`.
+    pub fn range(&self, text: &str) -> std::ops::Range<usize> {
+        let len = text[self.offset..].chars().next().map_or(0, char::len_utf8);
+        self.offset..self.offset + len
+    }
+}
+
+/// Remove the markers from a title, returning the text to display and
+/// its mnemonic, if it has one.
+pub(crate) fn parse(title: &str) -> (String, Option<Mnemonic>) {
+    let mut text = String::with_capacity(title.len());
+    let mut mnemonic = None;
+    let mut chars = title.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            match chars.peek() {
+                Some('&') => {
+                    chars.next();
+                }
+                Some(&next) if next.is_alphanumeric() && mnemonic.is_none() => {
+                    mnemonic = next.to_lowercase().next().map(|key| Mnemonic {
+                        offset: text.len(),
+                        key,
+                    });
+                    continue;
+                }
+                _ => (),
+            }
+        }
+        text.push(c);
+    }
+    (text, mnemonic)
+}
+
+/// The mnemonic chosen by a key press: alt and a letter or digit, without
+/// control or meta, which are used for shortcuts and to type characters.
+pub(crate) fn key_mnemonic(key: &KeyEvent) -> Option<char> {
+    if !ENABLED || !key.mods.alt || key.mods.ctrl || key.mods.meta {
+        return None;
+    }
+    let c = key.unmod_text()?.chars().next()?;
+    if c.is_alphanumeric() {
+        c.to_lowercase().next()
+    } else {
+        None
+    }
+}
+
+/// Whether this is the press or release of an alt key.
+pub(crate) fn is_alt(key: &KeyEvent) -> bool {
+    key.key_code == KeyCode::LeftAlt || key.key_code == KeyCode::RightAlt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_markers() {
+        let (text, mnemonic) = parse("&File");
+        assert_eq!(text, "File");
+        assert_eq!(
+            mnemonic,
+            Some(Mnemonic {
+                offset: 0,
+                key: 'f'
+            })
+        );
+
+        let (text, mnemonic) = parse("Save &As…");
+        assert_eq!(text, "Save As…");
+        assert_eq!(mnemonic.map(|m| m.range(&text)), Some(5..6));
+
+        let (text, mnemonic) = parse("Fish && Chips & more &Ünits &Two");
+        assert_eq!(text, "Fish & Chips & more Ünits Two");
+        assert_eq!(mnemonic.map(|m| m.key), Some('ü'));
+
+        let (text, mnemonic) = parse("trailing &");
+        assert_eq!(text, "trailing &");
+        assert_eq!(mnemonic, None);
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parallel updates of sibling widgets.
+//!
+//! This module is only available with the `parallel` feature.
+//!
+//! In a very large widget tree, most of the time spent in the update pass
+//! goes to [`Data::same`] comparisons, most of which find nothing has
+//! changed. Since data is read-only during update, the comparisons for
+//! sibling widgets can be done at the same time. Widgets themselves are not
+//! `Send`, though, so their `update` methods are still called on the UI
+//! thread, one after another, once the comparisons are done.
+//!
+//! [`Data::same`]: ../trait.Data.html#tymethod.same
+
+use rayon::prelude::*;
+
+use crate::{Data, Env, UpdateCtx, Widget, WidgetPod};
+
+/// With fewer children than this, dispatching the comparisons to the thread
+/// pool costs more than it saves.
+const MIN_PARALLEL_LEN: usize = 64;
+
+/// Propagate a data update to a set of sibling widgets, comparing their data
+/// in parallel.
+///
+/// Each child is updated with the data at the same index. This can be
+/// called by a container in place of calling [`WidgetPod::update`] on each
+/// child, when the children's data are independent of one another, such as
+/// the items of a list. Invalidations requested by the children are merged
+/// into `ctx` as usual.
+///
+/// # Panics
+///
+/// Panics if `children` and `data` have different lengths.
+///
+/// [`WidgetPod::update`]: ../struct.WidgetPod.html#method.update
+pub fn update_children<T, W>(
+    ctx: &mut UpdateCtx,
+    children: &mut [WidgetPod<T, W>],
+    data: &[T],
+    env: &Env,
+) where
+    T: Data + Sync,
+    W: Widget<T>,
+{
+    assert_eq!(
+        children.len(),
+        data.len(),
+        "update_children needs one item of data per child"
+    );
+    if children.len() < MIN_PARALLEL_LEN {
+        for (child, data) in children.iter_mut().zip(data) {
+            child.update(ctx, data, env);
+        }
+        return;
+    }
+
+    let old_data: Vec<Option<&T>> = children.iter().map(|child| child.old_data()).collect();
+    let same: Vec<bool> = old_data
+        .par_iter()
+        .zip(data.par_iter())
+        .map(|(old, new)| old.map(|old| old.same(new)).unwrap_or(false))
+        .collect();
+
+    for ((child, data), data_same) in children.iter_mut().zip(data).zip(same) {
+        child.update_with_data_same(ctx, data_same, data, env);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::kurbo::Size;
+    use crate::piet::{Device, RenderContext};
+    use crate::{
+        BaseState, BoxConstraints, Event, EventCtx, LayoutCtx, PaintCtx, Store, WindowHandle,
+        WindowId,
+    };
+
+    /// Counts its updates, and invalidates on each.
+    struct Counter(Rc<Cell<usize>>);
+
+    impl Widget<u32> for Counter {
+        fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut u32, _env: &Env) {}
+
+        fn update(&mut self, ctx: &mut UpdateCtx, _old: Option<&u32>, _data: &u32, _env: &Env) {
+            self.0.set(self.0.get() + 1);
+            ctx.invalidate();
+        }
+
+        fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &u32, _: &Env) -> Size {
+            bc.min()
+        }
+
+        fn paint(&mut self, _: &mut PaintCtx, _: &BaseState, _: &u32, _: &Env) {}
+    }
+
+    #[test]
+    fn merges_invalidations() {
+        let mut device = Device::new().unwrap();
+        let mut target = device.bitmap_target(1, 1, 1.0).unwrap();
+        let mut piet = target.render_context();
+        let window = WindowHandle::default();
+        let store = Store::default();
+        let env = Env::default();
+        let window_id = WindowId::next();
+
+        let updates = Rc::new(Cell::new(0));
+        let mut children: Vec<_> = (0..MIN_PARALLEL_LEN * 2)
+            .map(|_| WidgetPod::new(Counter(updates.clone())))
+            .collect();
+        let mut data: Vec<u32> = (0..children.len() as u32).collect();
+
+        let mut update = |children: &mut [WidgetPod<u32, Counter>], data: &[u32]| {
+            let mut ctx = UpdateCtx {
+                text_factory: piet.text(),
+                window: &window,
+                needs_inval: false,
+                request_anim: false,
+                window_id,
+                store: &store,
+            };
+            update_children(&mut ctx, children, data, &env);
+            ctx.needs_inval
+        };
+
+        assert!(update(&mut children, &data));
+        assert_eq!(updates.get(), children.len());
+
+        assert!(!update(&mut children, &data));
+        assert_eq!(updates.get(), children.len());
+
+        data[MIN_PARALLEL_LEN + 1] += 1;
+        assert!(update(&mut children, &data));
+        assert_eq!(updates.get(), children.len() + 1);
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Saving application state, such as settings, across restarts.
+
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Values saved across restarts of the application, by key.
+///
+/// The store is a single JSON object. Keys are dotted paths, such as
+/// `"settings.editor.tab_width"`, and each part of druid that saves state
+/// keeps to its own prefix. Any value that implements serde's `Serialize`
+/// and `Deserialize` can be saved.
+///
+/// If the application sets a file with [`AppLauncher::store_file`], the
+/// store is loaded from it at launch, and saved to it shortly after it
+/// changes, and when a window closes or the application quits. It is
+/// available from [`EventCtx`], [`UpdateCtx`] and [`LayoutCtx`].
+///
+/// [`AppLauncher::store_file`]: struct.AppLauncher.html#method.store_file
+/// [`EventCtx`]: struct.EventCtx.html
+/// [`UpdateCtx`]: struct.UpdateCtx.html
+/// [`LayoutCtx`]: struct.LayoutCtx.html
+#[derive(Debug, Default)]
+pub struct Store {
+    values: RefCell<Map<String, Value>>,
+    /// Whether anything changed since the store was loaded or saved.
+    changed: Cell<bool>,
+}
+
+impl Store {
+    /// Load a store saved in a file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Store> {
+        let text = fs::read_to_string(path)?;
+        let values = serde_json::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Store {
+            values: RefCell::new(values),
+            changed: Cell::new(false),
+        })
+    }
+
+    /// Save the store to a file, if it has changed since it was loaded or
+    /// last saved.
+    ///
+    /// The file is replaced in one step, so that a crash while saving
+    /// leaves the previous contents.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if !self.changed.get() {
+            return Ok(());
+        }
+        let path = path.as_ref();
+        let text = serde_json::to_string_pretty(&*self.values.borrow())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, text)?;
+        fs::rename(&temp, path)?;
+        self.changed.set(false);
+        Ok(())
+    }
+
+    /// Whether the store has changed since it was loaded or last saved.
+    pub fn has_changes(&self) -> bool {
+        self.changed.get()
+    }
+
+    /// The value saved under a key, if there is one and it can be read as
+    /// a `V`.
+    pub fn get<V: DeserializeOwned>(&self, key: &str) -> Option<V> {
+        let value = self.values.borrow().get(key).cloned()?;
+        match serde_json::from_value(value) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                log::warn!("ignoring saved value for {:?}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    /// Save a value under a key.
+    pub fn set<V: Serialize + ?Sized>(&self, key: &str, value: &V) {
+        let value = match serde_json::to_value(value) {
+            Ok(value) => value,
+            Err(e) => {
+                log::warn!("failed to save value for {:?}: {}", key, e);
+                return;
+            }
+        };
+        let mut values = self.values.borrow_mut();
+        if values.get(key) != Some(&value) {
+            values.insert(key.to_owned(), value);
+            self.changed.set(true);
+        }
+    }
+
+    /// Remove the value saved under a key.
+    pub fn remove(&self, key: &str) {
+        if self.values.borrow_mut().remove(key).is_some() {
+            self.changed.set(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changes_are_saved() {
+        let store = Store::default();
+        store.set("settings.volume", &0.5);
+        assert!(store.has_changes());
+        store.changed.set(false);
+        store.set("settings.volume", &0.5);
+        assert!(!store.has_changes());
+        assert_eq!(store.get::<f64>("settings.volume"), Some(0.5));
+        // A value of another type is ignored.
+        assert_eq!(store.get::<bool>("settings.volume"), None);
+        store.remove("settings.volume");
+        assert!(store.has_changes());
+        assert_eq!(store.get::<f64>("settings.volume"), None);
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running child processes, with their output delivered as commands.
+//!
+//! A [`Process`] is spawned from a [`std::process::Command`]. Each line the
+//! process writes to its standard output or standard error is sent to the
+//! UI thread as a [`PROCESS_OUTPUT`] command as soon as it is written, and
+//! a [`PROCESS_EXITED`] command follows the last line, once the process has
+//! exited.
+//!
+//! Processes the process starts may keep its output open after it exits;
+//! the exit is then sent shortly after the process exits, and any lines
+//! they write later still follow.
+//!
+//! Unlike [`EventCtx::spawn_future`], processes don't run on the
+//! application's executor: reading pipes and waiting for a process block,
+//! which the futures an executor runs must not do. Each process gets
+//! threads of its own instead.
+//!
+//! [`Process`]: struct.Process.html
+//! [`std::process::Command`]: https://doc.rust-lang.org/std/process/struct.Command.html
+//! [`PROCESS_OUTPUT`]: ../commands/constant.PROCESS_OUTPUT.html
+//! [`PROCESS_EXITED`]: ../commands/constant.PROCESS_EXITED.html
+//! [`EventCtx::spawn_future`]: ../struct.EventCtx.html#method.spawn_future
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{self, Child, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use crate::{commands, ExtEventSink, Target};
+
+/// How often to check whether a process has exited.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long after a process exits to wait for the rest of its output.
+const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+static PROCESS_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// Identifies a [`Process`], in the commands it sends.
+///
+/// [`Process`]: struct.Process.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProcessId(u64);
+
+/// Which of a process's outputs a line was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line of output, the argument of a [`PROCESS_OUTPUT`] command.
+///
+/// [`PROCESS_OUTPUT`]: ../commands/constant.PROCESS_OUTPUT.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessOutput {
+    pub process: ProcessId,
+    pub stream: OutputStream,
+    /// The line, without its line ending. Output that isn't UTF-8 is
+    /// converted lossily.
+    pub line: String,
+}
+
+/// The end of a process, the argument of a [`PROCESS_EXITED`] command.
+///
+/// [`PROCESS_EXITED`]: ../commands/constant.PROCESS_EXITED.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessExit {
+    pub process: ProcessId,
+    /// How the process exited, or `None` if that couldn't be found out.
+    pub status: Option<ExitStatus>,
+}
+
+/// A running child process, whose output is sent as commands.
+///
+/// Dropping a `Process` doesn't stop the process; its output is still
+/// sent until it exits.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::process::Process;
+/// use druid::widget::Label;
+/// use druid::{AppLauncher, Target, WindowDesc};
+///
+/// let launcher = AppLauncher::with_window(WindowDesc::new(|| Label::new("Build")));
+/// let mut cargo = std::process::Command::new("cargo");
+/// cargo.arg("build");
+/// let build = Process::spawn(&mut cargo, launcher.get_external_handle(), Target::Global);
+/// ```
+pub struct Process {
+    id: ProcessId,
+    child: Arc<Mutex<Child>>,
+}
+
+impl ProcessId {
+    fn next() -> ProcessId {
+        ProcessId(PROCESS_ID_COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Process {
+    /// Spawn `command`, sending its output and exit to `target` through
+    /// `sink`.
+    ///
+    /// The standard output and standard error of the command are replaced
+    /// by pipes; the rest of its configuration is used as is.
+    pub fn spawn(
+        command: &mut process::Command,
+        sink: ExtEventSink,
+        target: impl Into<Target>,
+    ) -> io::Result<Process> {
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let id = ProcessId::next();
+        let target = target.into();
+
+        // Each reader says when its output is closed.
+        let (closed_tx, closed) = channel();
+        let mut readers = 0;
+        let mut outputs: Vec<(OutputStream, Box<dyn Read + Send>)> = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            outputs.push((OutputStream::Stdout, Box::new(stdout)));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            outputs.push((OutputStream::Stderr, Box::new(stderr)));
+        }
+        for (stream, output) in outputs {
+            if let Err(e) = forward(id, stream, output, &sink, target, closed_tx.clone()) {
+                kill(&mut child);
+                return Err(e);
+            }
+            readers += 1;
+        }
+
+        let child = Arc::new(Mutex::new(child));
+        let waited = child.clone();
+        let waiter = thread::Builder::new()
+            .name("process-wait".into())
+            .spawn(move || {
+                let status = wait(&waited);
+                // The exit follows the last line of output, unless something
+                // the process started keeps its output open.
+                let deadline = Instant::now() + OUTPUT_GRACE;
+                for _ in 0..readers {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    if closed.recv_timeout(timeout).is_err() {
+                        break;
+                    }
+                }
+                let exit = ProcessExit {
+                    process: id,
+                    status,
+                };
+                sink.submit_command(commands::PROCESS_EXITED, exit, target);
+            });
+        if let Err(e) = waiter {
+            if let Ok(mut child) = child.lock() {
+                kill(&mut child);
+            }
+            return Err(e);
+        }
+
+        Ok(Process { id, child })
+    }
+
+    /// The id the process's commands are sent with.
+    pub fn id(&self) -> ProcessId {
+        self.id
+    }
+
+    /// Kill the process. Its exit is still sent.
+    pub fn kill(&self) -> io::Result<()> {
+        match self.child.lock() {
+            Ok(mut child) => child.kill(),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Send each line read from `output` on a thread of its own, and then
+/// send on `closed`.
+fn forward(
+    process: ProcessId,
+    stream: OutputStream,
+    output: impl Read + Send + 'static,
+    sink: &ExtEventSink,
+    target: Target,
+    closed: Sender<()>,
+) -> io::Result<()> {
+    let sink = sink.clone();
+    thread::Builder::new()
+        .name("process-output".into())
+        .spawn(move || {
+            read_lines(output, |line| {
+                let output = ProcessOutput {
+                    process,
+                    stream,
+                    line,
+                };
+                sink.submit_command(commands::PROCESS_OUTPUT, output, target);
+            });
+            let _ = closed.send(());
+        })?;
+    Ok(())
+}
+
+/// Stop a process that was spawned but couldn't be watched.
+fn kill(child: &mut Child) {
+    if let Err(e) = child.kill() {
+        warn!("failed to kill process: {}", e);
+    }
+    let _ = child.wait();
+}
+
+/// Call `f` with each line of `output`, until it is closed.
+fn read_lines(output: impl Read, mut f: impl FnMut(String)) {
+    let mut output = BufReader::new(output);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match output.read_until(b'\n', &mut buf) {
+            Ok(0) => return,
+            Ok(_) => {
+                if buf.ends_with(b"\n") {
+                    buf.pop();
+                    if buf.ends_with(b"\r") {
+                        buf.pop();
+                    }
+                }
+                f(String::from_utf8_lossy(&buf).into_owned());
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => {
+                warn!("failed to read process output: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Wait for the process to exit.
+///
+/// The lock is only held to check, so the process can still be killed.
+fn wait(child: &Mutex<Child>) -> Option<ExitStatus> {
+    loop {
+        let status = match child.lock() {
+            Ok(mut child) => child.try_wait(),
+            Err(_) => return None,
+        };
+        match status {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) => thread::sleep(EXIT_POLL_INTERVAL),
+            Err(e) => {
+                warn!("failed to wait for process: {}", e);
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_lines() {
+        let output: &[u8] = b"compiling\r\nwarning: \xff\n\nfinished";
+        let mut lines = Vec::new();
+        read_lines(output, |line| lines.push(line));
+        assert_eq!(lines, ["compiling", "warning: \u{fffd}", "", "finished"]);
+    }
+}
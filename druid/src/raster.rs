@@ -0,0 +1,68 @@
+// Copyright 2018 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Images of painted content, which can be drawn again without painting.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::{InterpolationMode, Piet, RenderContext};
+use crate::PaintCtx;
+
+/// An image in the format of the render context windows are painted with.
+pub(crate) type PietImage = <Piet<'static> as RenderContext>::Image;
+
+/// An image of painted content, made with [`PaintCtx::rasterize`].
+///
+/// Drawing a raster is much cheaper than painting complex content again,
+/// so widgets keep them for content that rarely changes. A raster is made
+/// at the scale of the window it is painted in, and should be made again if
+/// that changes.
+///
+/// [`PaintCtx::rasterize`]: struct.PaintCtx.html#method.rasterize
+pub struct Raster {
+    image: PietImage,
+    size: Size,
+    scale: f64,
+}
+
+impl Raster {
+    pub(crate) fn new(image: PietImage, size: Size, scale: f64) -> Raster {
+        Raster { image, size, scale }
+    }
+
+    /// The size of the painted content, in display points.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The number of pixels per display point the raster was made at.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Whether the raster can be drawn for content of `size`, at `scale`,
+    /// without being made again.
+    pub fn fits(&self, size: Size, scale: f64) -> bool {
+        self.size == size && self.scale == scale
+    }
+
+    /// Draw the raster with its top left at the origin, at its own size.
+    pub fn draw(&self, paint_ctx: &mut PaintCtx) {
+        self.draw_in(paint_ctx, Rect::from_origin_size(Point::ORIGIN, self.size));
+    }
+
+    /// Draw the raster scaled to fill `rect`.
+    pub fn draw_in(&self, paint_ctx: &mut PaintCtx, rect: Rect) {
+        paint_ctx.draw_image(&self.image, rect, InterpolationMode::Bilinear);
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resources shared between windows.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::WindowId;
+
+/// A cache of resources shared by all the windows of an application.
+///
+/// Expensive things like fonts, decoded images and icons can be created once
+/// and stored here, instead of once per window. Resources are identified by
+/// a name and their type, so the same name can be used for different types.
+/// It is available from [`PaintCtx`] and [`LayoutCtx`], as
+/// [`WindowResources`].
+///
+/// Resources are handed out as `Rc`s. The cache counts the windows that use
+/// each resource, and a resource is dropped when the last window using it
+/// closes. Resources inserted directly, outside of any window, are kept
+/// until they are removed, or until [`purge`] finds that they are no longer
+/// used outside of the cache.
+///
+/// Some resources belong to a platform render context, and can't be shared
+/// between windows on every platform; these should not be stored here.
+///
+/// # Examples
+///
+/// ```
+/// use druid::Resources;
+///
+/// let resources = Resources::default();
+/// let icon = resources.get_or_insert_with("icon", || vec![0u8; 1024]);
+/// let again = resources.get_or_insert_with("icon", || unreachable!());
+/// assert!(std::rc::Rc::ptr_eq(&icon, &again));
+/// ```
+///
+/// [`PaintCtx`]: struct.PaintCtx.html
+/// [`LayoutCtx`]: struct.LayoutCtx.html
+/// [`WindowResources`]: struct.WindowResources.html
+/// [`purge`]: #method.purge
+#[derive(Default)]
+pub struct Resources {
+    entries: RefCell<HashMap<(String, TypeId), Entry>>,
+}
+
+/// A resource, and the windows that use it.
+struct Entry {
+    resource: Rc<dyn Any>,
+    windows: Vec<WindowId>,
+}
+
+/// The [`Resources`] of an application, as used by one of its windows.
+///
+/// Resources got through this are counted as used by the window, and are
+/// dropped once every window that used them has closed.
+///
+/// [`Resources`]: struct.Resources.html
+#[derive(Clone, Copy)]
+pub struct WindowResources<'a> {
+    resources: &'a Resources,
+    window_id: WindowId,
+}
+
+impl Resources {
+    /// Get a resource, if it exists.
+    pub fn get<R: Any>(&self, name: &str) -> Option<Rc<R>> {
+        self.get_for(name, None)
+    }
+
+    /// Get a resource, creating it with `f` if it doesn't exist.
+    pub fn get_or_insert_with<R: Any>(&self, name: &str, f: impl FnOnce() -> R) -> Rc<R> {
+        self.get_or_insert_for(name, None, f)
+    }
+
+    /// Add a resource, replacing any existing resource of the same type with
+    /// this name.
+    pub fn insert<R: Any>(&self, name: &str, resource: R) -> Rc<R> {
+        self.insert_for(name, None, resource)
+    }
+
+    /// Remove a resource from the cache, returning it if it existed.
+    ///
+    /// Anything still holding the resource keeps it alive.
+    pub fn remove<R: Any>(&self, name: &str) -> Option<Rc<R>> {
+        let key = (name.to_owned(), TypeId::of::<R>());
+        let entry = self.entries.borrow_mut().remove(&key)?;
+        entry.resource.downcast().ok()
+    }
+
+    /// Drop the resources that are not used outside of the cache.
+    pub fn purge(&self) {
+        self.entries
+            .borrow_mut()
+            .retain(|_, entry| Rc::strong_count(&entry.resource) > 1);
+    }
+
+    /// The number of resources in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    /// The resources, as used by the window `window_id`.
+    pub(crate) fn for_window(&self, window_id: WindowId) -> WindowResources<'_> {
+        WindowResources {
+            resources: self,
+            window_id,
+        }
+    }
+
+    /// Forget that a window used any resources, dropping the resources that
+    /// no other window uses.
+    pub(crate) fn window_closed(&self, window_id: WindowId) {
+        self.entries.borrow_mut().retain(|_, entry| {
+            match entry.windows.iter().position(|id| *id == window_id) {
+                Some(idx) => {
+                    entry.windows.remove(idx);
+                    !entry.windows.is_empty()
+                }
+                None => true,
+            }
+        });
+    }
+
+    fn get_for<R: Any>(&self, name: &str, window_id: Option<WindowId>) -> Option<Rc<R>> {
+        let key = (name.to_owned(), TypeId::of::<R>());
+        let mut entries = self.entries.borrow_mut();
+        let entry = entries.get_mut(&key)?;
+        if let Some(window_id) = window_id {
+            if !entry.windows.contains(&window_id) {
+                entry.windows.push(window_id);
+            }
+        }
+        entry.resource.clone().downcast().ok()
+    }
+
+    fn get_or_insert_for<R: Any>(
+        &self,
+        name: &str,
+        window_id: Option<WindowId>,
+        f: impl FnOnce() -> R,
+    ) -> Rc<R> {
+        if let Some(resource) = self.get_for(name, window_id) {
+            return resource;
+        }
+        // `f` is called without the cache borrowed, so it can use the cache.
+        self.insert_for(name, window_id, f())
+    }
+
+    fn insert_for<R: Any>(&self, name: &str, window_id: Option<WindowId>, resource: R) -> Rc<R> {
+        let resource = Rc::new(resource);
+        let entry = Entry {
+            resource: resource.clone(),
+            windows: window_id.into_iter().collect(),
+        };
+        self.entries
+            .borrow_mut()
+            .insert((name.to_owned(), TypeId::of::<R>()), entry);
+        resource
+    }
+}
+
+impl<'a> WindowResources<'a> {
+    /// Get a resource, if it exists.
+    pub fn get<R: Any>(self, name: &str) -> Option<Rc<R>> {
+        self.resources.get_for(name, Some(self.window_id))
+    }
+
+    /// Get a resource, creating it with `f` if it doesn't exist.
+    pub fn get_or_insert_with<R: Any>(self, name: &str, f: impl FnOnce() -> R) -> Rc<R> {
+        self.resources
+            .get_or_insert_for(name, Some(self.window_id), f)
+    }
+
+    /// Add a resource, replacing any existing resource of the same type with
+    /// this name.
+    pub fn insert<R: Any>(self, name: &str, resource: R) -> Rc<R> {
+        self.resources
+            .insert_for(name, Some(self.window_id), resource)
+    }
+
+    /// The resources of the application, which aren't counted as used by
+    /// this window.
+    pub fn shared(self) -> &'a Resources {
+        self.resources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn purge_unused() {
+        let resources = Resources::default();
+        let kept = resources.insert("kept", 1u32);
+        resources.insert("dropped", 2u32);
+        resources.insert("kept", "a string");
+        assert_eq!(resources.len(), 3);
+
+        resources.purge();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources.get::<u32>("kept"), Some(kept));
+        assert!(resources.get::<&str>("kept").is_none());
+    }
+
+    #[test]
+    fn shared_between_windows() {
+        let resources = Resources::default();
+        let (first, second) = (WindowId::next(), WindowId::next());
+        resources.for_window(first).insert("shared", 1u32);
+        resources.for_window(second).get::<u32>("shared").unwrap();
+        resources.for_window(first).insert("first only", 2u32);
+        resources.insert("app", 3u32);
+
+        resources.window_closed(first);
+        assert!(resources.get::<u32>("shared").is_some());
+        assert!(resources.get::<u32>("first only").is_none());
+
+        resources.window_closed(second);
+        assert!(resources.get::<u32>("shared").is_none());
+        assert!(resources.get::<u32>("app").is_some());
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reopening the windows that were open when the application quit.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::Store;
+
+/// The key the session is kept under in the [`Store`].
+///
+/// [`Store`]: ../struct.Store.html
+const SESSION_KEY: &str = "session.windows";
+
+/// A function that returns the state to save for a window.
+pub(crate) type SessionStateFn<T> = dyn Fn(&T) -> String;
+
+/// A saved window: its kind, frame as `(x, y, width, height)`, state, and
+/// restoration id.
+type SavedWindow = (String, (f64, f64, f64, f64), String, String);
+
+/// A window in a saved [`Session`].
+///
+/// [`Session`]: struct.Session.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionWindow {
+    /// The kind of window, as given to [`WindowDesc::restorable`].
+    ///
+    /// [`WindowDesc::restorable`]: struct.WindowDesc.html#method.restorable
+    pub kind: String,
+    /// The position of the window's top left, in the coordinate space of
+    /// [`Screen`], and the size of its content.
+    ///
+    /// [`Screen`]: struct.Screen.html
+    pub frame: Rect,
+    /// The state the application saved for the window, such as the path of
+    /// the document it shows.
+    pub state: String,
+    /// The id the platform restores the window's own state by.
+    pub(crate) restoration_id: String,
+}
+
+/// The windows that were open when the application last quit, or when the
+/// session was last saved, in the order they were opened.
+///
+/// The session is kept in the application's [`Store`], and is saved as
+/// windows open, close and change, so that it survives a crash. See
+/// [`AppLauncher::restore_session`].
+///
+/// [`Store`]: struct.Store.html
+/// [`AppLauncher::restore_session`]: struct.AppLauncher.html#method.restore_session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Session {
+    /// The saved windows.
+    pub windows: Vec<SessionWindow>,
+}
+
+impl Session {
+    /// Load the session saved in a store. Windows that can't be read are
+    /// skipped.
+    pub fn load(store: &Store) -> Session {
+        let saved: Vec<SavedWindow> = store.get(SESSION_KEY).unwrap_or_default();
+        let windows = saved
+            .into_iter()
+            .filter_map(|(kind, (x, y, width, height), state, restoration_id)| {
+                if !(x.is_finite() && y.is_finite() && width > 0.0 && height > 0.0) {
+                    return None;
+                }
+                let frame = Rect::from_origin_size(Point::new(x, y), Size::new(width, height));
+                Some(SessionWindow {
+                    kind,
+                    frame,
+                    state,
+                    restoration_id,
+                })
+            })
+            .collect();
+        Session { windows }
+    }
+
+    /// Save the session in a store.
+    pub fn save(&self, store: &Store) {
+        let saved: Vec<SavedWindow> = self
+            .windows
+            .iter()
+            .map(|window| {
+                let frame = window.frame;
+                (
+                    window.kind.clone(),
+                    (frame.x0, frame.y0, frame.width(), frame.height()),
+                    window.state.clone(),
+                    window.restoration_id.clone(),
+                )
+            })
+            .collect();
+        store.set(SESSION_KEY, &saved);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let session = Session {
+            windows: vec![
+                SessionWindow {
+                    kind: "editor".into(),
+                    frame: Rect::new(10.0, 20.5, 810.0, 620.5),
+                    state: "/home/me/notes=draft.txt\nline 2".into(),
+                    restoration_id: "druid.session.0".into(),
+                },
+                SessionWindow {
+                    kind: "odd\\kind=".into(),
+                    frame: Rect::new(-300.0, 0.0, 0.5, 100.0),
+                    state: String::new(),
+                    restoration_id: "druid.session.1".into(),
+                },
+            ],
+        };
+        let store = Store::default();
+        session.save(&store);
+        assert_eq!(Session::load(&store), session);
+        let empty = (
+            String::new(),
+            (1.0, 2.0, 0.0, 4.0),
+            String::new(),
+            String::new(),
+        );
+        store.set(SESSION_KEY, &vec![empty]);
+        assert!(Session::load(&store).windows.is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running futures off the UI thread, with their output delivered as commands.
+
+use std::any::Any;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use log::error;
+
+use crate::{ExtEventSink, Selector, Target};
+
+/// A future that can be run on any thread, as given to an [`Executor`].
+///
+/// [`Executor`]: trait.Executor.html
+pub type BoxedFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Runs the futures spawned with [`EventCtx::spawn_future`].
+///
+/// Druid doesn't depend on any async runtime; an application that uses one
+/// can hand its futures to it by setting an executor with
+/// [`AppLauncher::executor`]. Any `Fn(BoxedFuture)` is an executor, so with
+/// tokio this might be `move |fut| { runtime_handle.spawn(fut); }`.
+///
+/// Without one, each future is run to completion on a thread of its own.
+///
+/// [`EventCtx::spawn_future`]: struct.EventCtx.html#method.spawn_future
+/// [`AppLauncher::executor`]: struct.AppLauncher.html#method.executor
+pub trait Executor: Send + Sync {
+    /// Run `future` to completion, off the UI thread.
+    fn spawn(&self, future: BoxedFuture);
+}
+
+impl<F: Fn(BoxedFuture) + Send + Sync> Executor for F {
+    fn spawn(&self, future: BoxedFuture) {
+        self(future)
+    }
+}
+
+/// The default executor: a thread per future.
+struct ThreadExecutor;
+
+impl Executor for ThreadExecutor {
+    fn spawn(&self, future: BoxedFuture) {
+        let spawned = thread::Builder::new()
+            .name("druid-future".into())
+            .spawn(move || block_on(future));
+        if let Err(e) = spawned {
+            error!("failed to spawn a thread for a future: {}", e);
+        }
+    }
+}
+
+/// Wakes a thread blocked in [`block_on`].
+///
+/// [`block_on`]: fn.block_on.html
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run a future to completion on the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            // A wake before we park makes `park` return immediately.
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Spawns futures on the application's executor, submitting their output
+/// through an [`ExtEventSink`].
+///
+/// [`ExtEventSink`]: struct.ExtEventSink.html
+#[derive(Clone)]
+pub(crate) struct Spawner {
+    executor: Arc<dyn Executor>,
+    sink: ExtEventSink,
+}
+
+impl Spawner {
+    pub(crate) fn new(executor: Option<Arc<dyn Executor>>, sink: ExtEventSink) -> Self {
+        Spawner {
+            executor: executor.unwrap_or_else(|| Arc::new(ThreadExecutor)),
+            sink,
+        }
+    }
+
+    /// Run `future`, then submit a command with `selector` and its output.
+    pub(crate) fn spawn<F>(&self, future: F, selector: Selector, target: Target)
+    where
+        F: Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        let sink = self.sink.clone();
+        self.executor.spawn(Box::pin(async move {
+            let output = future.await;
+            sink.submit_command(selector, output, target);
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DONE: Selector = Selector::new("test.done");
+
+    #[test]
+    fn output_is_submitted() {
+        let sink = ExtEventSink::new();
+        let spawner = Spawner::new(
+            Some(Arc::new(|fut: BoxedFuture| block_on(fut))),
+            sink.clone(),
+        );
+        spawner.spawn(async { 6 * 7 }, DONE, Target::Global);
+
+        let commands = sink.take_commands();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].1.selector, DONE);
+        assert_eq!(commands[0].1.get_object(), Some(&42));
+    }
+
+    #[test]
+    fn default_executor() {
+        let sink = ExtEventSink::new();
+        let spawner = Spawner::new(None, sink.clone());
+        spawner.spawn(async { "done" }, DONE, Target::Global);
+
+        for _ in 0..500 {
+            if let Some((_, command)) = sink.take_commands().pop() {
+                assert_eq!(command.get_object(), Some(&"done"));
+                return;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("future output was never submitted");
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spellchecking support for text widgets.
+//!
+//! This module is only available with the `spellcheck` feature.
+//!
+//! Text widgets are given a [`SpellChecker`], which reports the byte ranges
+//! of misspelled words and provides suggested replacements.
+//! [`SystemSpellChecker`] uses the system's spellchecker, and is only
+//! available on macOS; [`WordList`] is a simple portable fallback backed by
+//! a list of known words.
+//!
+//! [`SpellChecker`]: trait.SpellChecker.html
+//! [`SystemSpellChecker`]: struct.SystemSpellChecker.html
+//! [`WordList`]: struct.WordList.html
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::kurbo::{BezPath, Point};
+#[cfg(all(target_os = "macos", not(feature = "use_gtk")))]
+use crate::shell;
+
+/// The maximum number of suggestions shown in the context menu.
+pub(crate) const MAX_SUGGESTIONS: usize = 5;
+
+/// A source of spelling information for text widgets.
+pub trait SpellChecker: Debug {
+    /// Return `true` if `word` is spelled correctly.
+    fn is_correct(&self, word: &str) -> bool;
+
+    /// Return possible replacements for a misspelled `word`, best first.
+    fn suggestions(&self, word: &str) -> Vec<String>;
+
+    /// Return the byte ranges of all misspelled words in `text`.
+    ///
+    /// The default implementation splits `text` on unicode word boundaries
+    /// and calls [`is_correct`] on each alphabetic word.
+    ///
+    /// [`is_correct`]: #tymethod.is_correct
+    fn check(&self, text: &str) -> Vec<Range<usize>> {
+        text.split_word_bound_indices()
+            .filter(|(_, word)| word.chars().any(char::is_alphabetic))
+            .filter(|(_, word)| !self.is_correct(word))
+            .map(|(start, word)| start..start + word.len())
+            .collect()
+    }
+}
+
+/// A [`SpellChecker`] that accepts words from a fixed list.
+///
+/// Words are compared case-insensitively, and words containing digits are
+/// always accepted. Suggestions are known words within an edit distance
+/// of two.
+///
+/// [`SpellChecker`]: trait.SpellChecker.html
+#[derive(Debug, Clone, Default)]
+pub struct WordList {
+    words: HashSet<String>,
+}
+
+/// The system's spellchecker, for the languages the user has chosen.
+///
+/// This is `NSSpellChecker`, and is only available on macOS. On other
+/// platforms, a [`WordList`] can be used instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(target_os = "macos")]
+/// # fn main() {
+/// use druid::spellcheck::{SystemSpellChecker, WordList};
+/// use druid::widget::TextBox;
+///
+/// let textbox = match SystemSpellChecker::new() {
+///     Some(checker) => TextBox::raw().with_spellchecker(checker),
+///     None => TextBox::raw().with_spellchecker(WordList::new(&["hello", "world"])),
+/// };
+/// # }
+/// # #[cfg(not(target_os = "macos"))]
+/// # fn main() {}
+/// ```
+///
+/// [`WordList`]: struct.WordList.html
+#[cfg(all(target_os = "macos", not(feature = "use_gtk")))]
+#[cfg_attr(docsrs, doc(cfg(target_os = "macos")))]
+#[derive(Debug)]
+pub struct SystemSpellChecker(shell::SystemSpellChecker);
+
+/// The payload of a [`SHOW_SPELLING_SUGGESTIONS`] command.
+///
+/// [`SHOW_SPELLING_SUGGESTIONS`]: ../commands/constant.SHOW_SPELLING_SUGGESTIONS.html
+#[derive(Debug, Clone)]
+pub struct SpellingSuggestions {
+    /// The misspelled word.
+    pub word: String,
+    /// Suggested replacements, best first.
+    pub suggestions: Vec<String>,
+    /// The location of the menu, in window coordinates.
+    pub location: Point,
+}
+
+#[cfg(all(target_os = "macos", not(feature = "use_gtk")))]
+impl SystemSpellChecker {
+    /// Get the system's spellchecker, if it can be loaded.
+    ///
+    /// This must be called on the main thread.
+    pub fn new() -> Option<Self> {
+        shell::SystemSpellChecker::new().map(SystemSpellChecker)
+    }
+}
+
+#[cfg(all(target_os = "macos", not(feature = "use_gtk")))]
+impl SpellChecker for SystemSpellChecker {
+    fn is_correct(&self, word: &str) -> bool {
+        self.0.is_correct(word)
+    }
+
+    fn suggestions(&self, word: &str) -> Vec<String> {
+        let mut suggestions = self.0.suggestions(word);
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+}
+
+impl WordList {
+    /// Create a new `WordList` from an iterator of known words.
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        WordList {
+            words: words
+                .into_iter()
+                .map(|w| w.as_ref().to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Create a `WordList` from a dictionary file's contents, with one word
+    /// per line.
+    pub fn from_dictionary(contents: &str) -> Self {
+        WordList::new(contents.lines().map(str::trim).filter(|l| !l.is_empty()))
+    }
+
+    /// Add a word to the list.
+    pub fn add_word(&mut self, word: &str) {
+        self.words.insert(word.to_lowercase());
+    }
+}
+
+impl SpellChecker for WordList {
+    fn is_correct(&self, word: &str) -> bool {
+        word.chars().any(|c| c.is_numeric()) || self.words.contains(&word.to_lowercase())
+    }
+
+    fn suggestions(&self, word: &str) -> Vec<String> {
+        let word = word.to_lowercase();
+        let mut candidates = self
+            .words
+            .iter()
+            .map(|w| (edit_distance(&word, w), w))
+            .filter(|(dist, _)| *dist <= 2)
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, w)| w.to_owned())
+            .collect()
+    }
+}
+
+/// A zigzag line from `x0` to `x1` along `y`, used to underline misspelled
+/// words.
+pub(crate) fn squiggle_path(x0: f64, x1: f64, y: f64) -> BezPath {
+    const STEP: f64 = 2.;
+    let mut path = BezPath::new();
+    path.move_to((x0, y));
+    let mut x = x0;
+    let mut up = true;
+    while x < x1 {
+        x = (x + STEP).min(x1);
+        let dy = if up { -STEP / 2. } else { STEP / 2. };
+        path.line_to((x, y + dy));
+        up = !up;
+    }
+    path
+}
+
+/// The Levenshtein distance between two strings, in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_word_list() {
+        let checker = WordList::new(&["hello", "world"]);
+        let text = "Hello wrold 42";
+        assert_eq!(checker.check(text), vec![6..11]);
+        assert_eq!(checker.suggestions("wrold"), vec!["world".to_string()]);
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
@@ -0,0 +1,385 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A rope-backed text buffer, for use as widget data.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use xi_rope::{LinesMetric, Rope};
+
+use crate::Data;
+
+/// A single edit to a [`TextBuffer`]: the text in `range` is replaced with
+/// `text`.
+///
+/// [`TextBuffer`]: struct.TextBuffer.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    /// The byte range that is replaced.
+    pub range: Range<usize>,
+    /// The replacement text.
+    pub text: String,
+}
+
+/// Text that the text widgets can edit, such as a `String` or a
+/// [`TextBuffer`].
+///
+/// [`TextBuffer`]: struct.TextBuffer.html
+pub trait EditableText: Data {
+    /// The whole text.
+    ///
+    /// This may copy the text; use [`slice`] when only part of it is needed.
+    ///
+    /// [`slice`]: #tymethod.slice
+    fn text(&self) -> Cow<str>;
+
+    /// The text in the byte `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, or either end is not on a
+    /// character boundary.
+    fn slice(&self, range: Range<usize>) -> Cow<str>;
+
+    /// The length of the text, in bytes.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there is no text.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replace the text in the byte `range` with `text`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, or either end is not on a
+    /// character boundary.
+    fn edit(&mut self, range: Range<usize>, text: &str);
+}
+
+/// A text buffer backed by a rope.
+///
+/// Cloning a `TextBuffer` is cheap, regardless of the size of the text, and
+/// [`Data::same`] is a pointer comparison. This makes it a better choice than
+/// `String` as the data of text widgets that may hold large documents.
+///
+/// Edits made with [`edit`] are recorded, and can be reverted with [`undo`]
+/// and [`redo`]. The history is shared between clones, so recording an edit
+/// doesn't copy it.
+///
+/// Offsets are in bytes, and must be on character boundaries.
+///
+/// [`Data::same`]: trait.Data.html#tymethod.same
+/// [`edit`]: #method.edit
+/// [`undo`]: #method.undo
+/// [`redo`]: #method.redo
+#[derive(Clone, Default)]
+pub struct TextBuffer {
+    /// The text, shared between clones until one of them is edited.
+    rope: Arc<Rope>,
+    undo: EditStack,
+    redo: EditStack,
+}
+
+/// A stack of edits whose entries are shared between clones, so that
+/// pushing and popping never copies the rest of the stack.
+#[derive(Clone, Default)]
+struct EditStack(Option<Arc<EditNode>>);
+
+struct EditNode {
+    edit: TextEdit,
+    below: EditStack,
+}
+
+impl EditStack {
+    fn push(&mut self, edit: TextEdit) {
+        let below = EditStack(self.0.take());
+        self.0 = Some(Arc::new(EditNode { edit, below }));
+    }
+
+    fn pop(&mut self) -> Option<TextEdit> {
+        let top = self.0.take()?;
+        let (edit, below) = match Arc::try_unwrap(top) {
+            Ok(node) => (node.edit, node.below),
+            Err(shared) => (shared.edit.clone(), shared.below.clone()),
+        };
+        *self = below;
+        Some(edit)
+    }
+
+    fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl TextBuffer {
+    /// Create a new, empty `TextBuffer`.
+    pub fn new() -> TextBuffer {
+        TextBuffer::default()
+    }
+
+    /// The length of the text, in bytes.
+    pub fn len(&self) -> usize {
+        self.rope.len()
+    }
+
+    /// Returns `true` if the buffer contains no text.
+    pub fn is_empty(&self) -> bool {
+        self.rope.len() == 0
+    }
+
+    /// The number of lines in the buffer. An empty buffer has one line.
+    pub fn line_count(&self) -> usize {
+        self.rope.measure::<LinesMetric>() + 1
+    }
+
+    /// The byte range of the given line, excluding the line ending.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line` is not less than [`line_count`].
+    ///
+    /// [`line_count`]: #method.line_count
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        assert!(line < self.line_count(), "line {} out of bounds", line);
+        let start = self.rope.offset_of_line(line);
+        let end = if line + 1 < self.line_count() {
+            self.rope.offset_of_line(line + 1)
+        } else {
+            self.rope.len()
+        };
+        let text = self.rope.slice_to_cow(start..end);
+        let ending = text.len() - text.trim_end_matches(&['\r', '\n'][..]).len();
+        start..end - ending
+    }
+
+    /// The text of the given line, excluding the line ending.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line` is not less than [`line_count`].
+    ///
+    /// [`line_count`]: #method.line_count
+    pub fn line(&self, line: usize) -> Cow<str> {
+        self.rope.slice_to_cow(self.line_range(line))
+    }
+
+    /// The line containing the byte `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is greater than [`len`].
+    ///
+    /// [`len`]: #method.len
+    pub fn line_of_offset(&self, offset: usize) -> usize {
+        self.rope.line_of_offset(offset)
+    }
+
+    /// The text in the given byte range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, or either end is not on a
+    /// character boundary.
+    pub fn slice(&self, range: Range<usize>) -> Cow<str> {
+        self.rope.slice_to_cow(range)
+    }
+
+    /// Replace the text in `range` with `text`, recording the edit so that
+    /// it can be undone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, or either end is not on a
+    /// character boundary.
+    pub fn edit(&mut self, range: Range<usize>, text: &str) {
+        let inverse = self.apply(&TextEdit {
+            range,
+            text: text.to_owned(),
+        });
+        self.undo.push(inverse);
+        self.redo.clear();
+    }
+
+    /// Apply an edit without recording it, returning the edit that reverts it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range of the edit is out of bounds, or either end is
+    /// not on a character boundary.
+    pub fn apply(&mut self, edit: &TextEdit) -> TextEdit {
+        let old = self.rope.slice_to_cow(edit.range.clone()).into_owned();
+        Arc::make_mut(&mut self.rope).edit(edit.range.clone(), edit.text.as_str());
+        TextEdit {
+            range: edit.range.start..edit.range.start + edit.text.len(),
+            text: old,
+        }
+    }
+
+    /// Revert the most recent edit, returning the edit that was applied.
+    pub fn undo(&mut self) -> Option<TextEdit> {
+        let edit = self.undo.pop()?;
+        let inverse = self.apply(&edit);
+        self.redo.push(inverse);
+        Some(edit)
+    }
+
+    /// Reapply the most recently undone edit, returning the edit that was applied.
+    pub fn redo(&mut self) -> Option<TextEdit> {
+        let edit = self.redo.pop()?;
+        let inverse = self.apply(&edit);
+        self.undo.push(inverse);
+        Some(edit)
+    }
+
+    /// Returns `true` if there is an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns `true` if there is an edit to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+impl Data for TextBuffer {
+    fn same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.rope, &other.rope)
+    }
+}
+
+impl EditableText for TextBuffer {
+    fn text(&self) -> Cow<str> {
+        self.rope.slice_to_cow(..)
+    }
+
+    fn slice(&self, range: Range<usize>) -> Cow<str> {
+        TextBuffer::slice(self, range)
+    }
+
+    fn len(&self) -> usize {
+        TextBuffer::len(self)
+    }
+
+    fn edit(&mut self, range: Range<usize>, text: &str) {
+        TextBuffer::edit(self, range, text)
+    }
+}
+
+impl EditableText for String {
+    fn text(&self) -> Cow<str> {
+        Cow::Borrowed(self)
+    }
+
+    fn slice(&self, range: Range<usize>) -> Cow<str> {
+        Cow::Borrowed(&self[range])
+    }
+
+    fn len(&self) -> usize {
+        String::len(self)
+    }
+
+    fn edit(&mut self, range: Range<usize>, text: &str) {
+        self.replace_range(range, text)
+    }
+}
+
+impl From<&str> for TextBuffer {
+    fn from(src: &str) -> TextBuffer {
+        TextBuffer {
+            rope: Arc::new(Rope::from(src)),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<String> for TextBuffer {
+    fn from(src: String) -> TextBuffer {
+        TextBuffer::from(src.as_str())
+    }
+}
+
+impl fmt::Display for TextBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", String::from(&*self.rope))
+    }
+}
+
+impl fmt::Debug for TextBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TextBuffer({} bytes, {} lines)",
+            self.len(),
+            self.line_count()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let buf = TextBuffer::from("one\r\ntwo\nthree");
+        assert_eq!(buf.line_count(), 3);
+        assert_eq!(buf.line(0), "one");
+        assert_eq!(buf.line(1), "two");
+        assert_eq!(buf.line(2), "three");
+        assert_eq!(buf.line_of_offset(6), 1);
+        assert_eq!(TextBuffer::new().line_count(), 1);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut buf = TextBuffer::from("hello world");
+        let original = buf.clone();
+        buf.edit(0..5, "goodbye");
+        assert_eq!(buf.to_string(), "goodbye world");
+        assert!(!buf.same(&original));
+        assert!(original.same(&original.clone()));
+
+        buf.undo();
+        assert_eq!(buf.to_string(), "hello world");
+        assert!(buf.can_redo());
+        buf.redo();
+        assert_eq!(buf.to_string(), "goodbye world");
+        assert!(!buf.can_redo());
+    }
+
+    #[test]
+    fn history_is_shared_between_clones() {
+        let mut buf = TextBuffer::from("a");
+        buf.edit(1..1, "b");
+        let mut copy = buf.clone();
+        copy.edit(2..2, "c");
+        copy.undo();
+        copy.undo();
+        assert_eq!(copy.to_string(), "a");
+        assert_eq!(buf.to_string(), "ab");
+        buf.undo();
+        assert_eq!(buf.to_string(), "a");
+        assert!(!buf.can_undo());
+    }
+}
@@ -16,6 +16,7 @@
 
 use crate::piet::Color;
 
+use crate::shell::{ColorScheme, HighContrast};
 use crate::{Env, Key};
 
 pub const WINDOW_BACKGROUND_COLOR: Key<Color> = Key::new("window_background_color");
@@ -31,10 +32,17 @@ pub const FOREGROUND_LIGHT: Key<Color> = Key::new("foreground_light");
 pub const FOREGROUND_DARK: Key<Color> = Key::new("foreground_dark");
 pub const BUTTON_DARK: Key<Color> = Key::new("button_dark");
 pub const BUTTON_LIGHT: Key<Color> = Key::new("button_light");
+pub const BUTTON_BORDER_RADIUS: Key<f64> = Key::new("button_border_radius");
+pub const BUTTON_BORDER_WIDTH: Key<f64> = Key::new("button_border_width");
 pub const BORDER: Key<Color> = Key::new("border");
 pub const BORDER_LIGHT: Key<Color> = Key::new("border_light");
 pub const SELECTION_COLOR: Key<Color> = Key::new("selection_color");
+pub const FIND_HIGHLIGHT_COLOR: Key<Color> = Key::new("find_highlight_color");
+pub const SPELLING_ERROR_COLOR: Key<Color> = Key::new("spelling_error_color");
 pub const CURSOR_COLOR: Key<Color> = Key::new("cursor_color");
+pub const LOG_ERROR_COLOR: Key<Color> = Key::new("log_error_color");
+pub const LOG_WARNING_COLOR: Key<Color> = Key::new("log_warning_color");
+pub const FIELD_ERROR_COLOR: Key<Color> = Key::new("field_error_color");
 
 pub const FONT_NAME: Key<&str> = Key::new("font_name");
 pub const TEXT_SIZE_NORMAL: Key<f64> = Key::new("text_size_normal");
@@ -49,9 +57,153 @@ pub const SCROLL_BAR_WIDTH: Key<f64> = Key::new("scroll_bar_width");
 pub const SCROLL_BAR_PAD: Key<f64> = Key::new("scroll_bar_pad");
 pub const SCROLL_BAR_RADIUS: Key<f64> = Key::new("scroll_bar_radius");
 pub const SCROLL_BAR_EDGE_WIDTH: Key<f64> = Key::new("scroll_bar_edge_width");
+/// How quickly kinetic scrolling slows down: the fraction of its speed
+/// lost per second is `1 - e^-friction`.
+pub const SCROLL_FRICTION: Key<f64> = Key::new("scroll_friction");
 
-/// An initial theme.
+/// The scale of widgets' paddings and minimum sizes, from the theme's
+/// [`Density`].
+///
+/// Built-in widgets multiply their spacing by this, usually with
+/// [`spacing`]. It is set, along with the sizes above, by [`with_density`].
+///
+/// [`Density`]: enum.Density.html
+/// [`spacing`]: fn.spacing.html
+/// [`with_density`]: fn.with_density.html
+pub const DENSITY: Key<f64> = Key::new("density");
+
+/// Whether input widgets in this subtree should refuse edits.
+///
+/// Read-only widgets can still be focused, and their content can still be
+/// selected and copied. This is usually set with [`WidgetExt::read_only_if`].
+///
+/// [`WidgetExt::read_only_if`]: ../widget/trait.WidgetExt.html#method.read_only_if
+pub const READ_ONLY: Key<bool> = Key::new("read_only");
+
+/// Whether content in this subtree flows from right to left.
+///
+/// This decides which side is the start for [`Alignment`] and
+/// [`LogicalInsets`], as used by [`Align`], [`Padding`] and [`Flex`], which
+/// lays out rows from the right. It is false by default, and can be set
+/// for a subtree with [`WidgetExt::env_scope`].
+///
+/// [`Alignment`]: ../struct.Alignment.html
+/// [`LogicalInsets`]: ../struct.LogicalInsets.html
+/// [`Align`]: ../widget/struct.Align.html
+/// [`Padding`]: ../widget/struct.Padding.html
+/// [`Flex`]: ../widget/struct.Flex.html
+/// [`WidgetExt::env_scope`]: ../widget/trait.WidgetExt.html#method.env_scope
+pub const RIGHT_TO_LEFT: Key<bool> = Key::new("right_to_left");
+
+/// Whether the theme uses its dark palette.
+///
+/// The palette follows the [`ColorScheme`] chosen with
+/// [`AppLauncher::color_scheme`], or the system's, and can be switched
+/// while the application runs with the [`SET_COLOR_SCHEME`] command.
+/// Widgets are sent [`LifeCycle::ColorSchemeChanged`] when it switches.
+///
+/// [`ColorScheme`]: ../enum.ColorScheme.html
+/// [`AppLauncher::color_scheme`]: ../struct.AppLauncher.html#method.color_scheme
+/// [`SET_COLOR_SCHEME`]: ../commands/constant.SET_COLOR_SCHEME.html
+/// [`LifeCycle::ColorSchemeChanged`]: ../enum.LifeCycle.html#variant.ColorSchemeChanged
+pub const DARK_MODE: Key<bool> = Key::new("dark_mode");
+
+/// Whether the system's high contrast mode is on.
+///
+/// While it is, the color keys above are replaced by colors from the
+/// forced palette below, so that widgets that don't check this key still
+/// draw with it. Widgets with more specific needs use the palette
+/// directly, as [`Button`], [`Checkbox`], [`Radio`], [`Switch`], [`Slider`]
+/// and [`Stepper`] do for their faces and marks. Widgets are sent [`LifeCycle::HighContrastChanged`] when the
+/// mode is turned on or off.
+///
+/// [`Button`]: ../widget/struct.Button.html
+/// [`Checkbox`]: ../widget/struct.Checkbox.html
+/// [`Radio`]: ../widget/struct.Radio.html
+/// [`Switch`]: ../widget/struct.Switch.html
+/// [`Slider`]: ../widget/struct.Slider.html
+/// [`Stepper`]: ../widget/struct.Stepper.html
+/// [`LifeCycle::HighContrastChanged`]: ../enum.LifeCycle.html#variant.HighContrastChanged
+pub const HIGH_CONTRAST: Key<bool> = Key::new("high_contrast");
+/// The background of windows, in high contrast mode.
+pub const FORCED_WINDOW: Key<Color> = Key::new("forced_window");
+/// Text on the window background, in high contrast mode.
+pub const FORCED_WINDOW_TEXT: Key<Color> = Key::new("forced_window_text");
+/// The face of buttons and other controls, in high contrast mode.
+pub const FORCED_BUTTON_FACE: Key<Color> = Key::new("forced_button_face");
+/// Text on button faces, in high contrast mode.
+pub const FORCED_BUTTON_TEXT: Key<Color> = Key::new("forced_button_text");
+/// The background of selected or highlighted items, in high contrast mode.
+pub const FORCED_HIGHLIGHT: Key<Color> = Key::new("forced_highlight");
+/// Text on the highlight color, in high contrast mode.
+pub const FORCED_HIGHLIGHT_TEXT: Key<Color> = Key::new("forced_highlight_text");
+/// Text of disabled controls, in high contrast mode.
+pub const FORCED_DISABLED_TEXT: Key<Color> = Key::new("forced_disabled_text");
+
+/// How tightly widgets are packed.
+///
+/// The density is chosen with [`AppLauncher::density`], and can be
+/// switched while the application runs with the [`SET_DENSITY`] command.
+///
+/// [`AppLauncher::density`]: ../struct.AppLauncher.html#method.density
+/// [`SET_DENSITY`]: ../commands/constant.SET_DENSITY.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Density {
+    /// Less space, to fit more on screen, for data-dense tools.
+    Compact,
+    /// The default spacing.
+    Normal,
+    /// More space, for touch screens or a relaxed look.
+    Comfortable,
+}
+
+impl Density {
+    /// The factor paddings and minimum sizes are multiplied by.
+    pub fn scale(self) -> f64 {
+        match self {
+            Density::Compact => 0.75,
+            Density::Normal => 1.0,
+            Density::Comfortable => 1.25,
+        }
+    }
+}
+
+impl Default for Density {
+    fn default() -> Self {
+        Density::Normal
+    }
+}
+
+/// `length`, a padding or minimum size, scaled by the theme's density.
+pub fn spacing(env: &Env, length: f64) -> f64 {
+    length * env.get(DENSITY)
+}
+
+/// `env`, a theme from [`init_for`], with its widget sizes scaled for
+/// `density`.
+///
+/// [`init_for`]: fn.init_for.html
+pub fn with_density(env: Env, density: Density) -> Env {
+    let scale = density.scale();
+    let scaled = |key: Key<f64>| env.get(key) * scale;
+    let basic_height = scaled(BASIC_WIDGET_HEIGHT);
+    let bordered_height = scaled(BORDERED_WIDGET_HEIGHT);
+    let scroll_bar_width = scaled(SCROLL_BAR_WIDTH);
+    let scroll_bar_pad = scaled(SCROLL_BAR_PAD);
+    env.adding(DENSITY, scale)
+        .adding(BASIC_WIDGET_HEIGHT, basic_height)
+        .adding(BORDERED_WIDGET_HEIGHT, bordered_height)
+        .adding(SCROLL_BAR_WIDTH, scroll_bar_width)
+        .adding(SCROLL_BAR_PAD, scroll_bar_pad)
+}
+
+/// An initial theme, with the dark palette.
 pub fn init() -> Env {
+    init_for(ColorScheme::Dark)
+}
+
+/// An initial theme, with the palette for a color scheme.
+pub fn init_for(scheme: ColorScheme) -> Env {
     let mut env = Env::default()
         .adding(WINDOW_BACKGROUND_COLOR, Color::rgb8(0x29, 0x29, 0x29))
         .adding(LABEL_COLOR, Color::rgb8(0xf0, 0xf0, 0xea))
@@ -64,10 +216,20 @@ pub fn init() -> Env {
         .adding(FOREGROUND_DARK, Color::rgb8(0xbf, 0xbf, 0xbf))
         .adding(BUTTON_DARK, Color::BLACK)
         .adding(BUTTON_LIGHT, Color::rgb8(0x21, 0x21, 0x21))
+        .adding(BUTTON_BORDER_RADIUS, 4.)
+        .adding(BUTTON_BORDER_WIDTH, 2.)
         .adding(BORDER, Color::rgb8(0x3a, 0x3a, 0x3a))
         .adding(BORDER_LIGHT, Color::rgb8(0xa1, 0xa1, 0xa1))
         .adding(SELECTION_COLOR, Color::rgb8(0xf3, 0x00, 0x21))
         .adding(CURSOR_COLOR, Color::WHITE)
+        .adding(
+            FIND_HIGHLIGHT_COLOR,
+            Color::rgb8(0xf0, 0xc0, 0x40).with_alpha(0.5),
+        )
+        .adding(SPELLING_ERROR_COLOR, Color::rgb8(0xe0, 0x40, 0x40))
+        .adding(LOG_ERROR_COLOR, Color::rgb8(0xf0, 0x60, 0x60))
+        .adding(LOG_WARNING_COLOR, Color::rgb8(0xf0, 0xc0, 0x40))
+        .adding(FIELD_ERROR_COLOR, Color::rgb8(0xf0, 0x60, 0x60))
         .adding(TEXT_SIZE_NORMAL, 15.0)
         .adding(BASIC_WIDGET_HEIGHT, 18.0)
         .adding(BORDERED_WIDGET_HEIGHT, 24.0)
@@ -78,7 +240,24 @@ pub fn init() -> Env {
         .adding(SCROLL_BAR_WIDTH, 8.)
         .adding(SCROLL_BAR_PAD, 2.)
         .adding(SCROLL_BAR_RADIUS, 5.)
-        .adding(SCROLL_BAR_EDGE_WIDTH, 1.);
+        .adding(SCROLL_BAR_EDGE_WIDTH, 1.)
+        .adding(SCROLL_FRICTION, 4.)
+        .adding(DENSITY, 1.0)
+        .adding(READ_ONLY, false)
+        .adding(RIGHT_TO_LEFT, false)
+        .adding(DARK_MODE, true)
+        .adding(HIGH_CONTRAST, false)
+        .adding(FORCED_WINDOW, Color::WHITE)
+        .adding(FORCED_WINDOW_TEXT, Color::BLACK)
+        .adding(FORCED_BUTTON_FACE, Color::WHITE)
+        .adding(FORCED_BUTTON_TEXT, Color::BLACK)
+        .adding(FORCED_HIGHLIGHT, Color::rgb8(0x00, 0x00, 0xc0))
+        .adding(FORCED_HIGHLIGHT_TEXT, Color::WHITE)
+        .adding(FORCED_DISABLED_TEXT, Color::rgb8(0x60, 0x60, 0x60));
+
+    if scheme == ColorScheme::Light {
+        env = light(env);
+    }
 
     #[cfg(target_os = "windows")]
     {
@@ -96,3 +275,107 @@ pub fn init() -> Env {
     }
     env
 }
+
+/// `env` with the colors of the light palette.
+fn light(env: Env) -> Env {
+    env.adding(DARK_MODE, false)
+        .adding(WINDOW_BACKGROUND_COLOR, Color::rgb8(0xf2, 0xf2, 0xf2))
+        .adding(LABEL_COLOR, Color::rgb8(0x1a, 0x1a, 0x1a))
+        .adding(PLACEHOLDER_COLOR, Color::rgb8(0x80, 0x80, 0x80))
+        .adding(PRIMARY_LIGHT, Color::rgb8(0x5c, 0xc4, 0xff))
+        .adding(PRIMARY_DARK, Color::rgb8(0x00, 0x6e, 0xc0))
+        .adding(BACKGROUND_LIGHT, Color::rgb8(0xff, 0xff, 0xff))
+        .adding(BACKGROUND_DARK, Color::rgb8(0xe6, 0xe6, 0xe6))
+        .adding(FOREGROUND_LIGHT, Color::rgb8(0x1a, 0x1a, 0x1a))
+        .adding(FOREGROUND_DARK, Color::rgb8(0x4d, 0x4d, 0x4d))
+        .adding(BUTTON_DARK, Color::rgb8(0xd9, 0xd9, 0xd9))
+        .adding(BUTTON_LIGHT, Color::rgb8(0xfa, 0xfa, 0xfa))
+        .adding(BORDER, Color::rgb8(0xb3, 0xb3, 0xb3))
+        .adding(BORDER_LIGHT, Color::rgb8(0x5e, 0x5e, 0x5e))
+        .adding(SELECTION_COLOR, Color::rgb8(0x9c, 0xcc, 0xff))
+        .adding(CURSOR_COLOR, Color::BLACK)
+        .adding(SCROLL_BAR_COLOR, Color::rgb8(0x40, 0x40, 0x40))
+        .adding(SCROLL_BAR_BORDER_COLOR, Color::rgb8(0xc0, 0xc0, 0xc0))
+}
+
+/// The theme for high contrast mode: `env` with its colors replaced by
+/// the system's forced palette.
+pub(crate) fn high_contrast(env: &Env, colors: &HighContrast) -> Env {
+    let window = || colors.window.clone();
+    let text = || colors.window_text.clone();
+    let face = || colors.button_face.clone();
+    let highlight = || colors.highlight.clone();
+    env.clone()
+        .adding(HIGH_CONTRAST, true)
+        .adding(FORCED_WINDOW, window())
+        .adding(FORCED_WINDOW_TEXT, text())
+        .adding(FORCED_BUTTON_FACE, face())
+        .adding(FORCED_BUTTON_TEXT, colors.button_text.clone())
+        .adding(FORCED_HIGHLIGHT, highlight())
+        .adding(FORCED_HIGHLIGHT_TEXT, colors.highlight_text.clone())
+        .adding(FORCED_DISABLED_TEXT, colors.disabled_text.clone())
+        .adding(WINDOW_BACKGROUND_COLOR, window())
+        .adding(LABEL_COLOR, text())
+        .adding(PLACEHOLDER_COLOR, colors.disabled_text.clone())
+        .adding(PRIMARY_LIGHT, highlight())
+        .adding(PRIMARY_DARK, highlight())
+        .adding(BACKGROUND_LIGHT, window())
+        .adding(BACKGROUND_DARK, window())
+        .adding(FOREGROUND_LIGHT, text())
+        .adding(FOREGROUND_DARK, text())
+        .adding(BUTTON_LIGHT, face())
+        .adding(BUTTON_DARK, face())
+        .adding(BORDER, text())
+        .adding(BORDER_LIGHT, highlight())
+        .adding(SELECTION_COLOR, highlight())
+        .adding(FIND_HIGHLIGHT_COLOR, highlight())
+        .adding(CURSOR_COLOR, text())
+        .adding(SCROLL_BAR_COLOR, text())
+        .adding(SCROLL_BAR_BORDER_COLOR, window())
+        .adding(SCROLL_BAR_MAX_OPACITY, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn high_contrast_replaces_colors() {
+        let colors = HighContrast {
+            window: Color::BLACK,
+            window_text: Color::WHITE,
+            button_face: Color::BLACK,
+            button_text: Color::WHITE,
+            highlight: Color::rgb8(0xff, 0xff, 0x00),
+            highlight_text: Color::BLACK,
+            disabled_text: Color::rgb8(0x80, 0x80, 0x80),
+        };
+        let env = high_contrast(&init(), &colors);
+        assert!(env.get(HIGH_CONTRAST));
+        assert_eq!(env.get(LABEL_COLOR).as_rgba_u32(), 0xffff_ffff);
+        assert_eq!(env.get(SELECTION_COLOR).as_rgba_u32(), 0xffff_00ff);
+        assert_eq!(env.get(FORCED_HIGHLIGHT_TEXT).as_rgba_u32(), 0x0000_00ff);
+        assert!(!init().get(HIGH_CONTRAST));
+    }
+
+    #[test]
+    fn densities() {
+        let compact = with_density(init(), Density::Compact);
+        assert_eq!(compact.get(DENSITY), 0.75);
+        assert_eq!(compact.get(BORDERED_WIDGET_HEIGHT), 18.0);
+        assert_eq!(spacing(&compact, 8.0), 6.0);
+        assert_eq!(spacing(&init(), 8.0), 8.0);
+    }
+
+    #[test]
+    fn palettes() {
+        assert!(init().get(DARK_MODE));
+        let light = init_for(ColorScheme::Light);
+        assert!(!light.get(DARK_MODE));
+        assert_eq!(light.get(LABEL_COLOR).as_rgba_u32(), 0x1a1a_1aff);
+        assert_eq!(
+            light.get(TEXT_SIZE_NORMAL),
+            init_for(ColorScheme::Dark).get(TEXT_SIZE_NORMAL)
+        );
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Saving view state, such as scroll offsets, across restarts.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Widgets with view state that can be saved and restored.
+///
+/// View state is the part of a widget's state that isn't in the
+/// application's data, but that the user would expect to find as they
+/// left it, like the offset of a [`Scroll`] or the position of a
+/// [`Split`]'s divider. A widget opts in by being wrapped in a [`Persist`],
+/// usually with [`WidgetExt::persist_view`], which keeps the state in the
+/// application's [`Store`].
+///
+/// [`Scroll`]: widget/struct.Scroll.html
+/// [`Split`]: widget/struct.Split.html
+/// [`Persist`]: widget/struct.Persist.html
+/// [`WidgetExt::persist_view`]: widget/trait.WidgetExt.html#method.persist_view
+/// [`Store`]: struct.Store.html
+pub trait PersistView {
+    /// The type of the view state.
+    type State: Serialize + DeserializeOwned;
+
+    /// The current view state.
+    fn view_state(&self) -> Self::State;
+
+    /// Restore view state returned by [`view_state`], possibly by another
+    /// version of the application. Invalid state should be ignored.
+    ///
+    /// [`view_state`]: #tymethod.view_state
+    fn restore_view_state(&mut self, state: Self::State);
+}
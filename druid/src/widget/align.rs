@@ -16,15 +16,15 @@
 
 use crate::kurbo::{Rect, Size};
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
-    WidgetPod,
+    Alignment, BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LayoutDirection,
+    PaintCtx, UpdateCtx, Widget, WidgetPod,
 };
 
 use crate::piet::UnitPoint;
 
 /// A widget that aligns its child.
 pub struct Align<T: Data> {
-    align: UnitPoint,
+    align: Alignment,
     child: WidgetPod<T, Box<dyn Widget<T>>>,
     width_factor: Option<f64>,
     height_factor: Option<f64>,
@@ -33,12 +33,15 @@ pub struct Align<T: Data> {
 impl<T: Data> Align<T> {
     /// Create widget with alignment.
     ///
-    /// Note that the `align` parameter is specified as a `UnitPoint` in
-    /// terms of left and right. This is inadequate for bidi-aware layout
-    /// and thus the API will change when druid gains bidi capability.
-    pub fn new(align: UnitPoint, child: impl Widget<T> + 'static) -> Align<T> {
+    /// The `align` parameter is either an [`Alignment`], in terms of start
+    /// and end, which is mirrored when [`theme::RIGHT_TO_LEFT`] is set, or
+    /// a `UnitPoint`, in terms of left and right, which never is.
+    ///
+    /// [`Alignment`]: ../struct.Alignment.html
+    /// [`theme::RIGHT_TO_LEFT`]: ../theme/constant.RIGHT_TO_LEFT.html
+    pub fn new(align: impl Into<Alignment>, child: impl Widget<T> + 'static) -> Align<T> {
         Align {
-            align,
+            align: align.into(),
             child: WidgetPod::new(child).boxed(),
             width_factor: None,
             height_factor: None,
@@ -60,10 +63,22 @@ impl<T: Data> Align<T> {
         Align::new(UnitPoint::LEFT, child)
     }
 
+    /// Create widget aligned to the start, the left unless the layout is
+    /// right-to-left.
+    pub fn start(child: impl Widget<T> + 'static) -> Align<T> {
+        Align::new(Alignment::START, child)
+    }
+
+    /// Create widget aligned to the end, the right unless the layout is
+    /// right-to-left.
+    pub fn end(child: impl Widget<T> + 'static) -> Align<T> {
+        Align::new(Alignment::END, child)
+    }
+
     /// Align only in the horizontal axis, keeping the child's size in the vertical.
-    pub fn horizontal(align: UnitPoint, child: impl Widget<T> + 'static) -> Align<T> {
+    pub fn horizontal(align: impl Into<Alignment>, child: impl Widget<T> + 'static) -> Align<T> {
         Align {
-            align,
+            align: align.into(),
             child: WidgetPod::new(child).boxed(),
             width_factor: None,
             height_factor: Some(1.0),
@@ -71,9 +86,9 @@ impl<T: Data> Align<T> {
     }
 
     /// Align only in the vertical axis, keeping the child's size in the horizontal.
-    pub fn vertical(align: UnitPoint, child: impl Widget<T> + 'static) -> Align<T> {
+    pub fn vertical(align: impl Into<Alignment>, child: impl Widget<T> + 'static) -> Align<T> {
         Align {
-            align,
+            align: align.into(),
             child: WidgetPod::new(child).boxed(),
             width_factor: Some(1.0),
             height_factor: None,
@@ -120,6 +135,7 @@ impl<T: Data> Widget<T> for Align<T> {
         let extra_height = (my_size.height - size.height).max(0.);
         let origin = self
             .align
+            .resolve(LayoutDirection::from_env(env))
             .resolve(Rect::new(0., 0., extra_width, extra_height));
         self.child
             .set_layout_rect(Rect::from_origin_size(origin, size));
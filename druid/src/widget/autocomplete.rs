@@ -0,0 +1,465 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A text box that suggests completions.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayoutBuilder};
+use crate::theme;
+use crate::widget::TextBox;
+use crate::{
+    BaseState, BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, PaintCtx, TimerToken,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// The default time to wait after the last edit before querying.
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(150);
+/// How often to check for the answer to an asynchronous query.
+const POLL_INTERVAL: Duration = Duration::from_millis(30);
+/// The height of a row in the suggestion list.
+const ROW_HEIGHT: f64 = 22.0;
+/// The default number of suggestions shown at once.
+const DEFAULT_MAX_VISIBLE: usize = 8;
+
+/// Delivers the answer to an asynchronous [`AutoComplete`] query.
+///
+/// A responder can be sent to another thread. Once a newer query has been
+/// started, the responder is cancelled: [`is_cancelled`] returns `true`,
+/// and anything it sends is ignored.
+///
+/// [`AutoComplete`]: struct.AutoComplete.html
+/// [`is_cancelled`]: #method.is_cancelled
+#[derive(Clone)]
+pub struct Responder {
+    slot: Arc<Mutex<Option<Vec<String>>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Responder {
+    fn new() -> Self {
+        Responder {
+            slot: Arc::new(Mutex::new(None)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Send the suggestions for the query.
+    pub fn respond(&self, suggestions: Vec<String>) {
+        if !self.is_cancelled() {
+            *self.slot.lock().unwrap() = Some(suggestions);
+        }
+    }
+
+    /// Whether the query has been superseded, so that work on it can stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    fn take(&self) -> Option<Vec<String>> {
+        self.slot.lock().unwrap().take()
+    }
+}
+
+enum Provider {
+    Sync(Box<dyn Fn(&str) -> Vec<String>>),
+    Async(Box<dyn Fn(&str, Responder)>),
+}
+
+/// A text box that shows a list of suggestions below it as the user types.
+///
+/// Suggestions come from a provider, which is queried once typing pauses
+/// for the debounce time. The provider is either a closure returning the
+/// suggestions, or a closure that starts a query and answers later, from
+/// any thread, through a [`Responder`]; a query that is superseded by a
+/// newer one is cancelled, and its answer dropped.
+///
+/// The up and down arrow keys move through the suggestions, enter or a
+/// click accepts one, and escape, or clicking elsewhere, closes the list.
+/// While the list is open, the wheel scrolls it.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::AutoComplete;
+///
+/// const FRUIT: &[&str] = &["apple", "apricot", "banana", "cherry"];
+///
+/// let fruit = AutoComplete::new(|text| {
+///     FRUIT
+///         .iter()
+///         .filter(|fruit| fruit.starts_with(text))
+///         .map(|fruit| fruit.to_string())
+///         .collect()
+/// });
+/// ```
+///
+/// [`Responder`]: struct.Responder.html
+pub struct AutoComplete {
+    text_box: WidgetPod<String, TextBox>,
+    provider: Provider,
+    debounce: Duration,
+    max_visible: usize,
+    debounce_timer: TimerToken,
+    poll_timer: TimerToken,
+    pending: Option<Responder>,
+    /// The text as last seen in an event, to tell edits from outside changes.
+    text: String,
+    suggestions: Vec<String>,
+    selected: Option<usize>,
+    /// The first suggestion shown, when there are more than fit.
+    scroll: usize,
+}
+
+impl AutoComplete {
+    /// Create a new `AutoComplete` with a provider that returns the
+    /// suggestions for some text.
+    pub fn new(provider: impl Fn(&str) -> Vec<String> + 'static) -> Self {
+        Self::with_provider(Provider::Sync(Box::new(provider)))
+    }
+
+    /// Create a new `AutoComplete` with a provider that starts a query for
+    /// some text, and answers it later through the [`Responder`].
+    ///
+    /// [`Responder`]: struct.Responder.html
+    pub fn new_async(provider: impl Fn(&str, Responder) + 'static) -> Self {
+        Self::with_provider(Provider::Async(Box::new(provider)))
+    }
+
+    fn with_provider(provider: Provider) -> Self {
+        AutoComplete {
+            text_box: WidgetPod::new(TextBox::raw()),
+            provider,
+            debounce: DEFAULT_DEBOUNCE,
+            max_visible: DEFAULT_MAX_VISIBLE,
+            debounce_timer: TimerToken::INVALID,
+            poll_timer: TimerToken::INVALID,
+            pending: None,
+            text: String::new(),
+            suggestions: Vec::new(),
+            selected: None,
+            scroll: 0,
+        }
+    }
+
+    /// Builder-style method to set how long to wait after the last edit
+    /// before querying the provider.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Builder-style method to set the most suggestions shown at once.
+    pub fn max_visible(mut self, max_visible: usize) -> Self {
+        self.max_visible = max_visible.max(1);
+        self
+    }
+
+    fn is_open(&self) -> bool {
+        !self.suggestions.is_empty()
+    }
+
+    /// Close the suggestion list and drop any query in flight.
+    fn close(&mut self, ctx: &mut EventCtx) {
+        self.cancel();
+        self.suggestions.clear();
+        self.selected = None;
+        self.scroll = 0;
+        ctx.set_active(false);
+        ctx.invalidate();
+    }
+
+    fn cancel(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.cancel();
+        }
+        self.debounce_timer = TimerToken::INVALID;
+        self.poll_timer = TimerToken::INVALID;
+    }
+
+    fn query(&mut self, ctx: &mut EventCtx, text: &str) {
+        self.cancel();
+        match &self.provider {
+            Provider::Sync(provider) => {
+                let suggestions = provider(text);
+                self.show(ctx, suggestions);
+            }
+            Provider::Async(provider) => {
+                let responder = Responder::new();
+                provider(text, responder.clone());
+                self.pending = Some(responder);
+                self.poll_timer = ctx.request_timer(Instant::now() + POLL_INTERVAL);
+            }
+        }
+    }
+
+    fn show(&mut self, ctx: &mut EventCtx, suggestions: Vec<String>) {
+        self.suggestions = suggestions;
+        self.selected = None;
+        self.scroll = 0;
+        // Stay active while the list is open, to see clicks outside the
+        // text box.
+        ctx.set_active(self.is_open());
+        ctx.invalidate();
+    }
+
+    fn select(&mut self, selected: usize) {
+        self.selected = Some(selected);
+        if selected < self.scroll {
+            self.scroll = selected;
+        } else if selected >= self.scroll + self.max_visible {
+            self.scroll = selected + 1 - self.max_visible;
+        }
+    }
+
+    fn accept(&mut self, ctx: &mut EventCtx, index: usize, data: &mut String) {
+        if let Some(suggestion) = self.suggestions.get(index) {
+            *data = suggestion.clone();
+            self.text = data.clone();
+        }
+        self.close(ctx);
+    }
+
+    /// The area of the suggestion list.
+    fn list_rect(&self, env: &Env) -> Rect {
+        let text_box = self.text_box.get_layout_rect();
+        let rows = self.suggestions.len().min(self.max_visible);
+        Rect::new(
+            0.0,
+            text_box.y1,
+            text_box.width(),
+            text_box.y1 + rows as f64 * theme::spacing(env, ROW_HEIGHT),
+        )
+    }
+}
+
+impl Widget<String> for AutoComplete {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut String, env: &Env) {
+        match event {
+            Event::KeyDown(key) if self.is_open() => match key.key_code {
+                KeyCode::ArrowDown => {
+                    let next = self.selected.map_or(0, |selected| selected + 1);
+                    self.select(next.min(self.suggestions.len() - 1));
+                    ctx.invalidate();
+                    ctx.set_handled();
+                    return;
+                }
+                KeyCode::ArrowUp => {
+                    let previous = self
+                        .selected
+                        .map_or(0, |selected| selected.saturating_sub(1));
+                    self.select(previous);
+                    ctx.invalidate();
+                    ctx.set_handled();
+                    return;
+                }
+                KeyCode::Return | KeyCode::NumpadEnter if self.selected.is_some() => {
+                    self.accept(ctx, self.selected.unwrap(), data);
+                    ctx.set_handled();
+                    return;
+                }
+                KeyCode::Escape => {
+                    self.close(ctx);
+                    ctx.set_handled();
+                    return;
+                }
+                _ => (),
+            },
+            Event::MouseDown(mouse) if self.is_open() => {
+                let list = self.list_rect(env);
+                if list.contains(mouse.pos) {
+                    let row = ((mouse.pos.y - list.y0) / theme::spacing(env, ROW_HEIGHT)) as usize;
+                    self.accept(ctx, self.scroll + row, data);
+                    ctx.set_handled();
+                    return;
+                }
+                if !self.text_box.get_layout_rect().contains(mouse.pos) {
+                    self.close(ctx);
+                    return;
+                }
+            }
+            Event::MouseMoved(mouse) if self.is_open() => {
+                let list = self.list_rect(env);
+                if list.contains(mouse.pos) {
+                    let row = ((mouse.pos.y - list.y0) / theme::spacing(env, ROW_HEIGHT)) as usize;
+                    if self.selected != Some(self.scroll + row) {
+                        self.selected = Some(self.scroll + row);
+                        ctx.invalidate();
+                    }
+                }
+            }
+            Event::Wheel(wheel) if self.is_open() => {
+                let max_scroll = self.suggestions.len().saturating_sub(self.max_visible);
+                if wheel.delta.y > 0.0 {
+                    self.scroll = (self.scroll + 1).min(max_scroll);
+                } else if wheel.delta.y < 0.0 {
+                    self.scroll = self.scroll.saturating_sub(1);
+                }
+                ctx.invalidate();
+                ctx.set_handled();
+                return;
+            }
+            Event::Timer(token) if *token == self.debounce_timer => {
+                self.debounce_timer = TimerToken::INVALID;
+                let text = data.clone();
+                self.query(ctx, &text);
+                return;
+            }
+            Event::Timer(token) if *token == self.poll_timer => {
+                let answer = self.pending.as_ref().and_then(Responder::take);
+                match answer {
+                    Some(suggestions) => {
+                        self.pending = None;
+                        self.poll_timer = TimerToken::INVALID;
+                        self.show(ctx, suggestions);
+                    }
+                    None => {
+                        self.poll_timer = ctx.request_timer(Instant::now() + POLL_INTERVAL);
+                    }
+                }
+                return;
+            }
+            _ => (),
+        }
+
+        self.text_box.event(ctx, event, data, env);
+        if *data != self.text {
+            self.text = data.clone();
+            self.cancel();
+            if data.is_empty() {
+                self.close(ctx);
+            } else if self.debounce == Duration::from_millis(0) {
+                let text = data.clone();
+                self.query(ctx, &text);
+            } else {
+                self.debounce_timer = ctx.request_timer(Instant::now() + self.debounce);
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: Option<&String>,
+        data: &String,
+        env: &Env,
+    ) {
+        self.text_box.update(ctx, data, env);
+        // Text changed from outside is not a query.
+        if *data != self.text {
+            self.text = data.clone();
+            self.cancel();
+            if self.is_open() {
+                self.suggestions.clear();
+                self.selected = None;
+                ctx.invalidate();
+            }
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &String,
+        env: &Env,
+    ) -> Size {
+        let size = self.text_box.layout(layout_ctx, bc, data, env);
+        self.text_box
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        _base_state: &BaseState,
+        data: &String,
+        env: &Env,
+    ) {
+        self.text_box.paint_with_offset(paint_ctx, data, env);
+        if !self.is_open() {
+            return;
+        }
+
+        let list = self.list_rect(env);
+        let visible: Vec<String> = self
+            .suggestions
+            .iter()
+            .skip(self.scroll)
+            .take(self.max_visible)
+            .cloned()
+            .collect();
+        let selected = self
+            .selected
+            .and_then(|selected| selected.checked_sub(self.scroll));
+        let background = env.get(theme::BACKGROUND_LIGHT);
+        let border = env.get(theme::BORDER_LIGHT);
+        let highlight = env.get(theme::PRIMARY_DARK);
+        let text_color = env.get(theme::LABEL_COLOR);
+        let font_name = env.get(theme::FONT_NAME).to_string();
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let row_height = theme::spacing(env, ROW_HEIGHT);
+
+        paint_ctx.paint_overlay(1, move |ctx| {
+            ctx.fill(list, &background);
+            let font = ctx
+                .text()
+                .new_font_by_name(&font_name, font_size)
+                .build()
+                .unwrap();
+            for (i, suggestion) in visible.iter().enumerate() {
+                let top = list.y0 + i as f64 * row_height;
+                if selected == Some(i) {
+                    let row = Rect::new(list.x0, top, list.x1, top + row_height);
+                    ctx.fill(row, &highlight);
+                }
+                let layout = ctx
+                    .text()
+                    .new_text_layout(&font, suggestion)
+                    .build()
+                    .unwrap();
+                let baseline = top + (row_height + font_size) / 2.0 - 2.0;
+                ctx.draw_text(&layout, (list.x0 + 4.0, baseline), &text_color);
+            }
+            ctx.stroke(list, &border, 1.0);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_responder_drops_answer() {
+        let responder = Responder::new();
+        let sent = responder.clone();
+        responder.cancel();
+        assert!(sent.is_cancelled());
+        sent.respond(vec!["late".to_string()]);
+        assert_eq!(responder.take(), None);
+
+        let responder = Responder::new();
+        responder.clone().respond(vec!["answer".to_string()]);
+        assert_eq!(responder.take(), Some(vec!["answer".to_string()]));
+    }
+}
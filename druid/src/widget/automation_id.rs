@@ -0,0 +1,73 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that tags its child with an automation id.
+
+use std::marker::PhantomData;
+
+use crate::kurbo::Size;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+};
+
+/// A widget that tags its child with a stable string id, so that UI
+/// testing tools can find it without relying on coordinates.
+///
+/// The tagged widgets of a window are listed in answer to an
+/// [`AutomationQuery`]. This is usually made with
+/// [`WidgetExt::automation_id`].
+///
+/// [`AutomationQuery`]: ../struct.AutomationQuery.html
+/// [`WidgetExt::automation_id`]: trait.WidgetExt.html#method.automation_id
+pub struct AutomationId<T: Data, W: Widget<T>> {
+    id: String,
+    child: W,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Data, W: Widget<T>> AutomationId<T, W> {
+    /// Create a new `AutomationId` widget.
+    pub fn new(id: impl Into<String>, child: W) -> AutomationId<T, W> {
+        AutomationId {
+            id: id.into(),
+            child,
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Widget<T> for AutomationId<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.child.event(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, old_data, data, env)
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        self.child.layout(layout_ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        paint_ctx.add_automation_node(&self.id, base_state);
+        self.child.paint(paint_ctx, base_state, data, env);
+    }
+}
@@ -19,10 +19,22 @@ use crate::piet::{LinearGradient, RenderContext, UnitPoint};
 use crate::theme;
 use crate::widget::{Align, Label, LabelText, SizedBox};
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    BaseState, BoxConstraints, Command, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle,
+    PaintCtx, UpdateCtx, Widget,
 };
 
+/// The space between a button's label and its sides, before it is scaled by
+/// the theme's density.
+const LABEL_PADDING_X: f64 = 8.;
+/// The space above and below a button's label, before it is scaled.
+const LABEL_PADDING_Y: f64 = 2.;
+
 /// A button with a text label.
+///
+/// The label can have a [mnemonic], as in `"&Save"`; alt and the mnemonic
+/// click the button.
+///
+/// [mnemonic]: struct.Label.html#mnemonics
 pub struct Button<T> {
     label: Label<T>,
     /// A closure that will be invoked when the button is clicked.
@@ -42,6 +54,23 @@ impl<T: Data + 'static> Button<T> {
         }
     }
 
+    /// Create a new button that sends a command to its window when clicked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::Button;
+    /// use druid::commands;
+    ///
+    /// let quit = Button::<u32>::command("Quit", commands::QUIT_APP);
+    /// ```
+    pub fn command(text: impl Into<LabelText<T>>, command: impl Into<Command>) -> Button<T> {
+        let command = command.into();
+        Button::new(text, move |ctx, _, _| {
+            ctx.submit_command(command.clone(), None)
+        })
+    }
+
     /// Create a new button with a fixed size.
     pub fn sized(
         text: impl Into<LabelText<T>>,
@@ -88,7 +117,24 @@ impl<T: Data> Widget<T> for Button<T> {
                     }
                 }
             }
-            Event::HotChanged(_) => {
+            Event::HotChanged(_) | Event::FocusChanged(_) => {
+                ctx.invalidate();
+            }
+            Event::KeyDown(key_event) if ctx.has_focus() => match key_event.key_code {
+                KeyCode::Space | KeyCode::Return | KeyCode::NumpadEnter => {
+                    (self.action)(ctx, data, env);
+                    ctx.set_handled();
+                    ctx.invalidate();
+                }
+                _ => (),
+            },
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
+            Event::LifeCycle(LifeCycle::ShowMnemonics(_)) => {
+                self.label.event(ctx, event, data, env);
+            }
+            Event::Mnemonic(c) if self.label.mnemonic(data, env) == Some(*c) => {
+                (self.action)(ctx, data, env);
+                ctx.set_handled();
                 ctx.invalidate();
             }
             _ => (),
@@ -108,15 +154,56 @@ impl<T: Data> Widget<T> for Button<T> {
     ) -> Size {
         bc.debug_check("Button");
 
-        self.label.layout(layout_ctx, bc, data, env)
+        let padding = Size::new(
+            2. * theme::spacing(env, LABEL_PADDING_X),
+            2. * theme::spacing(env, LABEL_PADDING_Y),
+        );
+        let label_size = self
+            .label
+            .layout(layout_ctx, &bc.shrink(padding), data, env);
+        bc.constrain(Size::new(
+            label_size.width + padding.width,
+            label_size.height + padding.height,
+        ))
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
         let is_active = base_state.is_active();
         let is_hot = base_state.is_hot();
 
-        let rounded_rect =
-            RoundedRect::from_origin_size(Point::ORIGIN, base_state.size().to_vec2(), 4.);
+        let border_width = env.get(theme::BUTTON_BORDER_WIDTH);
+        let rounded_rect = RoundedRect::from_origin_size(
+            Point::ORIGIN,
+            base_state.size().to_vec2(),
+            env.get(theme::BUTTON_BORDER_RADIUS),
+        );
+
+        if env.get(theme::HIGH_CONTRAST) {
+            // Flat colors from the forced palette, with the pressed button
+            // drawn as highlighted.
+            let (face, text) = if is_active {
+                (
+                    env.get(theme::FORCED_HIGHLIGHT),
+                    env.get(theme::FORCED_HIGHLIGHT_TEXT),
+                )
+            } else {
+                (
+                    env.get(theme::FORCED_BUTTON_FACE),
+                    env.get(theme::FORCED_BUTTON_TEXT),
+                )
+            };
+            let border = if is_hot || base_state.has_focus() {
+                env.get(theme::FORCED_HIGHLIGHT)
+            } else {
+                text.clone()
+            };
+            paint_ctx.fill(rounded_rect, &face);
+            paint_ctx.stroke(rounded_rect, &border, border_width);
+            let env = env.clone().adding(theme::LABEL_COLOR, text);
+            self.label.paint(paint_ctx, base_state, data, &env);
+            return;
+        }
+
         let bg_gradient = if is_active {
             LinearGradient::new(
                 UnitPoint::TOP,
@@ -131,13 +218,15 @@ impl<T: Data> Widget<T> for Button<T> {
             )
         };
 
-        let border_color = if is_hot {
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else if is_hot {
             env.get(theme::BORDER_LIGHT)
         } else {
             env.get(theme::BORDER)
         };
 
-        paint_ctx.stroke(rounded_rect, &border_color, 2.0);
+        paint_ctx.stroke(rounded_rect, &border_color, border_width);
 
         paint_ctx.fill(rounded_rect, &bg_gradient);
 
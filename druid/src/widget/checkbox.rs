@@ -19,7 +19,8 @@ use crate::piet::{LineCap, LineJoin, LinearGradient, RenderContext, StrokeStyle,
 use crate::theme;
 use crate::widget::Align;
 use crate::{
-    BaseState, BoxConstraints, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    BaseState, BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget,
 };
 
 /// A checkbox that toggles a boolean
@@ -33,7 +34,7 @@ impl Checkbox {
 }
 
 impl Widget<bool> for Checkbox {
-    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut bool, _env: &Env) {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut bool, env: &Env) {
         match event {
             Event::MouseDown(_) => {
                 ctx.set_active(true);
@@ -42,7 +43,7 @@ impl Widget<bool> for Checkbox {
             Event::MouseUp(_) => {
                 if ctx.is_active() {
                     ctx.set_active(false);
-                    if ctx.is_hot() {
+                    if ctx.is_hot() && !env.get(theme::READ_ONLY) {
                         if *data {
                             *data = false;
                         } else {
@@ -52,9 +53,19 @@ impl Widget<bool> for Checkbox {
                     ctx.invalidate();
                 }
             }
-            Event::HotChanged(_) => {
+            Event::KeyDown(key_event)
+                if ctx.has_focus() && key_event.key_code == KeyCode::Space =>
+            {
+                if !env.get(theme::READ_ONLY) {
+                    *data = !*data;
+                }
+                ctx.set_handled();
+                ctx.invalidate();
+            }
+            Event::HotChanged(_) | Event::FocusChanged(_) => {
                 ctx.invalidate();
             }
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
             _ => (),
         }
     }
@@ -84,6 +95,37 @@ impl Widget<bool> for Checkbox {
         let rect =
             RoundedRect::from_origin_size(Point::ORIGIN, Size::new(size, size).to_vec2(), 2.);
 
+        let mut style = StrokeStyle::new();
+        style.set_line_cap(LineCap::Round);
+        style.set_line_join(LineJoin::Round);
+
+        if env.get(theme::HIGH_CONTRAST) {
+            // Flat colors from the forced palette, with a checked box drawn
+            // as highlighted.
+            let (face, mark) = if *data {
+                (
+                    env.get(theme::FORCED_HIGHLIGHT),
+                    env.get(theme::FORCED_HIGHLIGHT_TEXT),
+                )
+            } else {
+                (
+                    env.get(theme::FORCED_BUTTON_FACE),
+                    env.get(theme::FORCED_BUTTON_TEXT),
+                )
+            };
+            let border = if base_state.is_hot() || base_state.has_focus() {
+                env.get(theme::FORCED_HIGHLIGHT)
+            } else {
+                env.get(theme::FORCED_BUTTON_TEXT)
+            };
+            paint_ctx.fill(rect, &face);
+            paint_ctx.stroke(rect, &border, 1.);
+            if *data {
+                paint_ctx.stroke_styled(check_mark(), &mark, 2., &style);
+            }
+            return;
+        }
+
         //Paint the background
         let background_gradient = LinearGradient::new(
             UnitPoint::TOP,
@@ -96,7 +138,9 @@ impl Widget<bool> for Checkbox {
 
         paint_ctx.fill(rect, &background_gradient);
 
-        let border_color = if base_state.is_hot() {
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else if base_state.is_hot() {
             env.get(theme::BORDER_LIGHT)
         } else {
             env.get(theme::BORDER)
@@ -105,16 +149,16 @@ impl Widget<bool> for Checkbox {
         paint_ctx.stroke(rect, &border_color, 1.);
 
         if *data {
-            let mut path = BezPath::new();
-            path.move_to((4.0, 9.0));
-            path.line_to((8.0, 13.0));
-            path.line_to((14.0, 5.0));
-
-            let mut style = StrokeStyle::new();
-            style.set_line_cap(LineCap::Round);
-            style.set_line_join(LineJoin::Round);
-
-            paint_ctx.stroke_styled(path, &env.get(theme::LABEL_COLOR), 2., &style);
+            paint_ctx.stroke_styled(check_mark(), &env.get(theme::LABEL_COLOR), 2., &style);
         }
     }
 }
+
+/// The mark in a checked box.
+fn check_mark() -> BezPath {
+    let mut path = BezPath::new();
+    path.move_to((4.0, 9.0));
+    path.line_to((8.0, 13.0));
+    path.line_to((14.0, 5.0));
+    path
+}
@@ -0,0 +1,62 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A controller that makes any widget clickable.
+
+use super::Controller;
+use crate::{Data, Env, Event, EventCtx, Widget};
+
+/// A [`Controller`] that calls an action when its widget is clicked.
+///
+/// As with a [`Button`], a click is a press and release of the mouse over
+/// the widget; releasing elsewhere cancels it. The widget still receives
+/// every event. This is usually added with [`WidgetExt::on_click`].
+///
+/// [`Controller`]: trait.Controller.html
+/// [`Button`]: struct.Button.html
+/// [`WidgetExt::on_click`]: trait.WidgetExt.html#method.on_click
+pub struct Click<T> {
+    action: Box<dyn Fn(&mut EventCtx, &mut T, &Env)>,
+}
+
+impl<T: Data> Click<T> {
+    /// Create a controller that calls `action` when its widget is clicked.
+    pub fn new(action: impl Fn(&mut EventCtx, &mut T, &Env) + 'static) -> Click<T> {
+        Click {
+            action: Box::new(action),
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Controller<T, W> for Click<T> {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::MouseDown(_) => {
+                ctx.set_active(true);
+                ctx.invalidate();
+            }
+            Event::MouseUp(_) => {
+                if ctx.is_active() {
+                    ctx.set_active(false);
+                    ctx.invalidate();
+                    if ctx.is_hot() {
+                        (self.action)(ctx, data, env);
+                    }
+                }
+            }
+            _ => (),
+        }
+        child.event(ctx, event, data, env);
+    }
+}
@@ -14,8 +14,9 @@
 
 //! A convenience widget that combines common styling and positioning widgets.
 
-use crate::shell::kurbo::{Point, Rect, Size};
+use crate::shell::kurbo::{Insets, Point, Rect, RoundedRect, Size};
 use crate::shell::piet::{PaintBrush, RenderContext};
+use crate::widget::BackgroundBrush;
 use crate::{
     BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
     WidgetPod,
@@ -26,15 +27,36 @@ struct BorderState {
     brush: PaintBrush,
 }
 
-#[derive(Default)]
-struct ContainerStyle {
-    background: Option<PaintBrush>,
+struct ContainerStyle<T> {
+    background: Option<BackgroundBrush<T>>,
     border: Option<BorderState>,
+    padding: Insets,
+    corner_radius: f64,
 }
 
 /// A convenience widget that combines common styling and positioning widgets.
+///
+/// The child is surrounded by padding, then by a border, and the
+/// background is painted under both. Containers are usually made with the
+/// [`WidgetExt`] methods, and the methods here style the same container
+/// when chained:
+///
+/// ```
+/// use druid::piet::Color;
+/// use druid::theme;
+/// use druid::widget::{Label, WidgetExt};
+///
+/// let card = Label::new("Card")
+///     .background(theme::BACKGROUND_LIGHT)
+///     .padding(8.0)
+///     .border(Color::rgb8(0x80, 0x80, 0x80), 1.0)
+///     .rounded(4.0);
+/// # let _: &dyn druid::Widget<u32> = &card;
+/// ```
+///
+/// [`WidgetExt`]: trait.WidgetExt.html
 pub struct Container<T: Data> {
-    style: ContainerStyle,
+    style: ContainerStyle<T>,
     inner: WidgetPod<T, Box<dyn Widget<T>>>,
 }
 
@@ -42,13 +64,21 @@ impl<T: Data> Container<T> {
     /// Create Container with a child
     pub fn new(inner: impl Widget<T> + 'static) -> Self {
         Self {
-            style: ContainerStyle::default(),
+            style: ContainerStyle {
+                background: None,
+                border: None,
+                padding: Insets::uniform(0.0),
+                corner_radius: 0.0,
+            },
             inner: WidgetPod::new(inner).boxed(),
         }
     }
 
-    /// Paint background with a color or a gradient.
-    pub fn background(mut self, brush: impl Into<PaintBrush>) -> Self {
+    /// Paint background with a color, a gradient, a color from the
+    /// environment or a [`Painter`].
+    ///
+    /// [`Painter`]: struct.Painter.html
+    pub fn background(mut self, brush: impl Into<BackgroundBrush<T>>) -> Self {
         self.style.background = Some(brush.into());
         self
     }
@@ -61,6 +91,26 @@ impl<T: Data> Container<T> {
         });
         self
     }
+
+    /// Builder-style method to add space between the child and the border,
+    /// filled by the background.
+    ///
+    /// This is unlike [`WidgetExt::padding`], which adds space outside of
+    /// a widget; it takes the same [`Insets`], a single `f64`, or tuples.
+    ///
+    /// [`WidgetExt::padding`]: trait.WidgetExt.html#method.padding
+    /// [`Insets`]: https://docs.rs/kurbo/0.5.4/kurbo/struct.Insets.html
+    pub fn padding(mut self, insets: impl Into<Insets>) -> Self {
+        self.style.padding = insets.into();
+        self
+    }
+
+    /// Builder-style method to round the corners of the background and
+    /// the border.
+    pub fn rounded(mut self, radius: f64) -> Self {
+        self.style.corner_radius = radius;
+        self
+    }
 }
 
 impl<T: Data + 'static> Widget<T> for Container<T> {
@@ -75,28 +125,37 @@ impl<T: Data + 'static> Widget<T> for Container<T> {
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
         bc.debug_check("Container");
 
-        // Shrink constraints by border offset
+        // Shrink constraints by border offset and padding
         let border_width = match self.style.border {
             Some(ref border) => border.width,
             None => 0.0,
         };
-        let child_bc = bc.shrink((2.0 * border_width, 2.0 * border_width));
+        let padding = self.style.padding;
+        let inset_width = 2.0 * border_width + padding.x0 + padding.x1;
+        let inset_height = 2.0 * border_width + padding.y0 + padding.y1;
+        let child_bc = bc.shrink((inset_width, inset_height));
         let size = self.inner.layout(ctx, &child_bc, data, env);
-        let origin = Point::new(border_width, border_width);
+        let origin = Point::new(border_width + padding.x0, border_width + padding.y0);
         self.inner
             .set_layout_rect(Rect::from_origin_size(origin, size));
 
-        Size::new(
-            size.width + 2.0 * border_width,
-            size.height + 2.0 * border_width,
-        )
+        Size::new(size.width + inset_width, size.height + inset_height)
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
-        // Paint background color
+        let radius = self.style.corner_radius;
+
+        // Paint background
         if let Some(ref brush) = self.style.background {
-            let rect = Rect::from_origin_size(Point::ZERO, base_state.size());
-            paint_ctx.render_ctx.fill(rect, brush);
+            if radius > 0.0 {
+                let bounds = Rect::from_origin_size(Point::ORIGIN, base_state.size());
+                paint_ctx.with_save(|ctx| {
+                    ctx.clip(RoundedRect::from_rect(bounds, radius));
+                    brush.paint(ctx, base_state, data, env);
+                });
+            } else {
+                brush.paint(paint_ctx, base_state, data, env);
+            }
         }
 
         // Paint border
@@ -107,9 +166,12 @@ impl<T: Data + 'static> Widget<T> for Container<T> {
                 base_state.size().height - border.width,
             );
             let rect = Rect::from_origin_size((offset, offset), size);
+            // The stroke is centered on the rect, inside the background's
+            // rounded corners.
+            let shape = RoundedRect::from_rect(rect, (radius - offset).max(0.0));
             paint_ctx
                 .render_ctx
-                .stroke(rect, &border.brush, border.width);
+                .stroke(shape, &border.brush, border.width);
         }
 
         // Paint child
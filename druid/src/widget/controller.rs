@@ -0,0 +1,121 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Adding behavior to a widget without writing a new widget.
+
+use crate::kurbo::Size;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+};
+
+/// Behavior that can be added to a widget, with a [`ControllerHost`].
+///
+/// A controller sees the events and updates of its widget before the
+/// widget does, and decides whether, and how, to pass them on. Both
+/// methods pass them on unchanged by default, so a controller only
+/// implements what it changes. The widget's layout and painting are left
+/// alone.
+///
+/// Controllers are usually added with [`WidgetExt::controller`].
+///
+/// # Examples
+///
+/// A controller that keeps a text box from seeing the escape key:
+///
+/// ```
+/// use druid::widget::{Controller, TextBox, WidgetExt};
+/// use druid::{Env, Event, EventCtx, KeyCode, Widget};
+///
+/// struct IgnoreEscape;
+///
+/// impl<W: Widget<String>> Controller<String, W> for IgnoreEscape {
+///     fn event(
+///         &mut self,
+///         child: &mut W,
+///         ctx: &mut EventCtx,
+///         event: &Event,
+///         data: &mut String,
+///         env: &Env,
+///     ) {
+///         match event {
+///             Event::KeyDown(key) if key.key_code == KeyCode::Escape => (),
+///             _ => child.event(ctx, event, data, env),
+///         }
+///     }
+/// }
+///
+/// let text_box = TextBox::new().controller(IgnoreEscape);
+/// ```
+///
+/// [`ControllerHost`]: struct.ControllerHost.html
+/// [`WidgetExt::controller`]: trait.WidgetExt.html#method.controller
+pub trait Controller<T, W: Widget<T>> {
+    /// Handle an event, usually passing it on to `child`.
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        child.event(ctx, event, data, env)
+    }
+
+    /// Handle a change of the data, usually passing it on to `child`.
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: Option<&T>,
+        data: &T,
+        env: &Env,
+    ) {
+        child.update(ctx, old_data, data, env)
+    }
+}
+
+/// A widget that adds the behavior of a [`Controller`] to its child.
+///
+/// [`Controller`]: trait.Controller.html
+pub struct ControllerHost<W, C> {
+    widget: W,
+    controller: C,
+}
+
+impl<W, C> ControllerHost<W, C> {
+    /// Create a widget that adds the behavior of `controller` to `widget`.
+    pub fn new(widget: W, controller: C) -> ControllerHost<W, C> {
+        ControllerHost { widget, controller }
+    }
+}
+
+impl<T: Data, W: Widget<T>, C: Controller<T, W>> Widget<T> for ControllerHost<W, C> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.controller
+            .event(&mut self.widget, ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        self.controller
+            .update(&mut self.widget, ctx, old_data, data, env)
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        self.widget.layout(layout_ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.widget.paint(paint_ctx, base_state, data, env)
+    }
+}
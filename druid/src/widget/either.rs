@@ -15,17 +15,37 @@
 //! A widget that switches dynamically between two child views.
 
 use crate::kurbo::{Point, Rect, Size};
+use crate::widget::Transition;
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
-    WidgetPod,
+    Animation, BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    PaintCtx, UpdateCtx, Widget, WidgetPod,
 };
 
 /// A widget that switches between two possible child views.
+///
+/// Only the branch being shown receives events. When the branches switch,
+/// the hidden branch is sent [`LifeCycle::WidgetRemoved`], and the branch
+/// shown is sent [`LifeCycle::WidgetAdded`] before its next event.
+///
+/// The switch is instant, unless a [`Transition`] is set; the hidden branch
+/// is then sent `WidgetRemoved` once the transition is over.
+///
+/// [`Transition`]: struct.Transition.html
+/// [`LifeCycle::WidgetRemoved`]: ../enum.LifeCycle.html#variant.WidgetRemoved
+/// [`LifeCycle::WidgetAdded`]: ../enum.LifeCycle.html#variant.WidgetAdded
 pub struct Either<T: Data> {
     closure: Box<dyn Fn(&T, &Env) -> bool>,
     true_branch: WidgetPod<T, Box<dyn Widget<T>>>,
     false_branch: WidgetPod<T, Box<dyn Widget<T>>>,
     current: bool,
+    /// The branch that received the last event.
+    ///
+    /// The branch is switched in `update`, which can't send events, so the
+    /// branch being hidden is told at the next event.
+    shown: bool,
+    transition: Option<Transition>,
+    /// The transition, while it runs.
+    animation: Option<Animation<f64>>,
 }
 
 impl<T: Data> Either<T> {
@@ -43,12 +63,55 @@ impl<T: Data> Either<T> {
             true_branch: WidgetPod::new(true_branch).boxed(),
             false_branch: WidgetPod::new(false_branch).boxed(),
             current: false,
+            shown: false,
+            transition: None,
+            animation: None,
+        }
+    }
+
+    /// Builder-style method to animate switching between the branches.
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.transition = Some(transition);
+        self
+    }
+
+    /// The branch being shown, and the one being hidden.
+    fn branches(
+        &mut self,
+    ) -> (
+        &mut WidgetPod<T, Box<dyn Widget<T>>>,
+        &mut WidgetPod<T, Box<dyn Widget<T>>>,
+    ) {
+        if self.current {
+            (&mut self.true_branch, &mut self.false_branch)
+        } else {
+            (&mut self.false_branch, &mut self.true_branch)
         }
     }
 }
 
 impl<T: Data> Widget<T> for Either<T> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            if let Some(animation) = &mut self.animation {
+                if animation.advance(*interval) {
+                    self.animation = None;
+                } else {
+                    ctx.request_anim_frame();
+                }
+                ctx.invalidate();
+            }
+        }
+
+        if self.shown != self.current && self.animation.is_none() {
+            let hidden = if self.shown {
+                &mut self.true_branch
+            } else {
+                &mut self.false_branch
+            };
+            hidden.event(ctx, &Event::LifeCycle(LifeCycle::WidgetRemoved), data, env);
+            self.shown = self.current;
+        }
         if self.current {
             self.true_branch.event(ctx, event, data, env)
         } else {
@@ -56,17 +119,23 @@ impl<T: Data> Widget<T> for Either<T> {
         }
     }
 
-    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
         let current = (self.closure)(data, env);
         if current != self.current {
             self.current = current;
+            // The first branch shown appears at once.
+            if let (Some(transition), Some(_)) = (&self.transition, old_data) {
+                self.animation = Some(transition.animation());
+                ctx.request_anim_frame();
+            }
             ctx.invalidate();
-            // TODO: more event flow to request here.
         }
-        if self.current {
-            self.true_branch.update(ctx, data, env);
-        } else {
-            self.false_branch.update(ctx, data, env);
+        let transitioning = self.animation.is_some();
+        let (shown, hidden) = self.branches();
+        shown.update(ctx, data, env);
+        // The hidden branch is kept up to date while it is still painted.
+        if transitioning {
+            hidden.update(ctx, data, env);
         }
     }
 
@@ -77,24 +146,31 @@ impl<T: Data> Widget<T> for Either<T> {
         data: &T,
         env: &Env,
     ) -> Size {
-        if self.current {
-            let size = self.true_branch.layout(layout_ctx, bc, data, env);
-            self.true_branch
-                .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
-            size
-        } else {
-            let size = self.false_branch.layout(layout_ctx, bc, data, env);
-            self.false_branch
-                .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
-            size
+        let transitioning = self.animation.is_some();
+        let (shown, hidden) = self.branches();
+        if transitioning {
+            let size = hidden.layout(layout_ctx, bc, data, env);
+            hidden.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
         }
+        let size = shown.layout(layout_ctx, bc, data, env);
+        shown.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
     }
 
-    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
-        if self.current {
-            self.true_branch.paint(paint_ctx, data, env);
-        } else {
-            self.false_branch.paint(paint_ctx, data, env);
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let transition = self.transition;
+        let t = self.animation.as_ref().map(Animation::value);
+        let (shown, hidden) = self.branches();
+        match (transition, t) {
+            (Some(transition), Some(t)) => transition.paint(
+                paint_ctx,
+                base_state.size(),
+                t,
+                env,
+                |ctx| hidden.paint(ctx, data, env),
+                |ctx| shown.paint(ctx, data, env),
+            ),
+            _ => shown.paint(paint_ctx, data, env),
         }
     }
 }
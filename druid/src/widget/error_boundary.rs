@@ -0,0 +1,156 @@
+// Copyright 2018 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that contains panics in its child.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use log::error;
+
+use crate::crash::panic_message;
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that replaces its child with a fallback if the child panics.
+///
+/// A panic in any of the child's methods is caught here instead of
+/// unwinding through the rest of the application. The crash report is still
+/// made, if reports are enabled, and the panic is logged; then the child is
+/// dropped, and the widget built by the fallback function, which is given
+/// the panic message, is shown in its place.
+///
+/// Nothing can be caught when the application is built with
+/// `panic = "abort"`. A panic while painting may leave the rest of that
+/// frame painted with the child's transform or clip.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{ErrorBoundary, Label};
+///
+/// let guarded = ErrorBoundary::new(Label::new("Plugin"), |message| {
+///     Box::new(Label::new(format!("The plugin failed: {}", message)))
+/// });
+/// # let _: ErrorBoundary<u32> = guarded;
+/// ```
+pub struct ErrorBoundary<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    fallback: Box<dyn Fn(&str) -> Box<dyn Widget<T>>>,
+    /// The child that panicked, to be removed on the next event.
+    failed: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    /// Whether the fallback is shown.
+    recovered: bool,
+}
+
+impl<T: Data> ErrorBoundary<T> {
+    /// Create a widget that shows `child`, or the widget `fallback` builds
+    /// if the child panics.
+    pub fn new(
+        child: impl Widget<T> + 'static,
+        fallback: impl Fn(&str) -> Box<dyn Widget<T>> + 'static,
+    ) -> ErrorBoundary<T> {
+        ErrorBoundary {
+            child: WidgetPod::new(Box::new(child)),
+            fallback: Box::new(fallback),
+            failed: None,
+            recovered: false,
+        }
+    }
+
+    /// Run `f` with the child, and replace it with the fallback if it
+    /// panics.
+    ///
+    /// Returns `None` if it panicked. A panic in the fallback itself is not
+    /// caught.
+    fn guard<R>(
+        &mut self,
+        f: impl FnOnce(&mut WidgetPod<T, Box<dyn Widget<T>>>) -> R,
+    ) -> Option<R> {
+        if self.recovered {
+            return Some(f(&mut self.child));
+        }
+        match panic::catch_unwind(AssertUnwindSafe(|| f(&mut self.child))) {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                let message = panic_message(&*payload);
+                error!("a widget panicked, showing the fallback: {}", message);
+                let fallback = WidgetPod::new((self.fallback)(&message));
+                self.failed = Some(std::mem::replace(&mut self.child, fallback));
+                self.recovered = true;
+                None
+            }
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for ErrorBoundary<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        // The child that panicked is sent what it needs to leave the focus
+        // chain, but may panic again; that is ignored.
+        if let Some(mut failed) = self.failed.take() {
+            let removed = Event::LifeCycle(LifeCycle::WidgetRemoved);
+            let _ =
+                panic::catch_unwind(AssertUnwindSafe(|| failed.event(ctx, &removed, data, env)));
+        }
+        if self
+            .guard(|child| child.event(ctx, event, data, env))
+            .is_none()
+        {
+            ctx.invalidate();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        if self.guard(|child| child.update(ctx, data, env)).is_none() {
+            ctx.invalidate();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("ErrorBoundary");
+
+        let size = match self.guard(|child| child.layout(layout_ctx, bc, data, env)) {
+            Some(size) => size,
+            // The fallback is laid out in the child's place.
+            None => self.child.layout(layout_ctx, bc, data, env),
+        };
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        if self
+            .guard(|child| child.paint(paint_ctx, data, env))
+            .is_none()
+        {
+            // The fallback hasn't been laid out, so it is painted at the
+            // size the child had.
+            let rect = self.failed.as_ref().map(|failed| failed.get_layout_rect());
+            if let Some(rect) = rect {
+                self.child.set_layout_rect(rect);
+            }
+            self.child.paint(paint_ctx, data, env);
+        }
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A form field with a validation or help message.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayout, TextLayoutBuilder};
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    WidgetPod,
+};
+
+/// How long the message takes to appear or disappear, in nanoseconds.
+const ANIMATION_DURATION: f64 = 150_000_000.0;
+/// The space between the field and its message.
+const MESSAGE_GAP: f64 = 4.0;
+/// The size of message text, relative to `theme::TEXT_SIZE_NORMAL`.
+const MESSAGE_TEXT_SCALE: f64 = 0.85;
+
+/// Where a [`Field`] shows its message.
+///
+/// [`Field`]: struct.Field.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessagePosition {
+    /// Below the field, in a line of its own.
+    Below,
+    /// To the right of the field.
+    Beside,
+}
+
+/// A wrapper that shows a validation error, or otherwise some help text,
+/// next to a form field.
+///
+/// The data is checked with the validator after every event the field
+/// handles, and whenever the data changes. Space for the message is made
+/// in layout, and the message slides in and out as it appears and
+/// disappears when the change comes from editing the field. To stop the
+/// widgets around the field from moving at all, use [`reserve_space`].
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Field, TextBox};
+///
+/// let name = Field::new(TextBox::new())
+///     .help("As it appears on your passport")
+///     .validate(|name: &String| {
+///         if name.trim().is_empty() {
+///             Err("A name is required".to_string())
+///         } else {
+///             Ok(())
+///         }
+///     });
+/// ```
+///
+/// [`reserve_space`]: #method.reserve_space
+pub struct Field<T: Data> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    validator: Option<Box<dyn Fn(&T) -> Result<(), String>>>,
+    help: Option<String>,
+    position: MessagePosition,
+    reserve_space: bool,
+    /// The current validation error.
+    error: Option<String>,
+    /// The message being shown, which is kept while it slides out.
+    shown: Option<String>,
+    /// How much of the message is revealed, from 0 to 1.
+    reveal: f64,
+    /// The size of the shown message, at full size.
+    message_size: Size,
+}
+
+impl<T: Data> Field<T> {
+    /// Create a new `Field` around `child`.
+    pub fn new(child: impl Widget<T> + 'static) -> Self {
+        Field {
+            child: WidgetPod::new(child).boxed(),
+            validator: None,
+            help: None,
+            position: MessagePosition::Below,
+            reserve_space: false,
+            error: None,
+            shown: None,
+            reveal: 0.0,
+            message_size: Size::ZERO,
+        }
+    }
+
+    /// Builder-style method to set the validator, which returns an error
+    /// message for invalid data.
+    pub fn validate(mut self, validator: impl Fn(&T) -> Result<(), String> + 'static) -> Self {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    /// Builder-style method to set help text, shown when there is no error.
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self.shown = self.help.clone();
+        self.reveal = 1.0;
+        self
+    }
+
+    /// Builder-style method to set where the message is shown.
+    pub fn message_position(mut self, position: MessagePosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Builder-style method to always leave room for a line of message
+    /// below the field, even when there is none.
+    ///
+    /// This has no effect on messages shown beside the field, whose width
+    /// depends on the message.
+    pub fn reserve_space(mut self) -> Self {
+        self.reserve_space = true;
+        self
+    }
+
+    /// The message that should be shown.
+    fn message(&self) -> Option<&String> {
+        self.error.as_ref().or_else(|| self.help.as_ref())
+    }
+
+    /// Check the data, returning `true` if the message to show changed.
+    fn check(&mut self, data: &T) -> bool {
+        let error = match &self.validator {
+            Some(validator) => validator(data).err(),
+            None => None,
+        };
+        if error == self.error {
+            return false;
+        }
+        let old = self.message().cloned();
+        self.error = error;
+        self.message() != old.as_ref()
+    }
+
+    /// Whether the message is fully shown, or fully hidden.
+    fn is_settled(&self) -> bool {
+        let target = if self.message().is_some() { 1.0 } else { 0.0 };
+        (self.reveal - target).abs() < std::f64::EPSILON && self.shown.as_ref() == self.message()
+    }
+
+    fn is_error_shown(&self) -> bool {
+        self.error.is_some() && self.shown == self.error
+    }
+}
+
+impl<T: Data> Widget<T> for Field<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            let step = *interval as f64 / ANIMATION_DURATION;
+            if self.shown.as_ref() == self.message() && self.shown.is_some() {
+                self.reveal = (self.reveal + step).min(1.0);
+            } else {
+                // Hide the old message before showing a different one.
+                self.reveal = (self.reveal - step).max(0.0);
+                if self.reveal <= 0.0 {
+                    self.shown = self.message().cloned();
+                }
+            }
+            if !self.is_settled() {
+                ctx.request_anim_frame();
+            }
+            ctx.invalidate();
+        }
+
+        self.child.event(ctx, event, data, env);
+        if self.check(data) {
+            ctx.request_anim_frame();
+            ctx.invalidate();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+        // Changes from elsewhere don't get an animation frame, so the
+        // message switches at once.
+        if self.check(data) {
+            self.shown = self.message().cloned();
+            self.reveal = if self.shown.is_some() { 1.0 } else { 0.0 };
+            ctx.invalidate();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Field");
+
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL) * MESSAGE_TEXT_SCALE;
+        let line_height = (font_size * 1.2).ceil();
+        self.message_size = match &self.shown {
+            Some(message) => {
+                let font = layout_ctx
+                    .text()
+                    .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+                    .build()
+                    .unwrap();
+                let layout = layout_ctx
+                    .text()
+                    .new_text_layout(&font, message)
+                    .build()
+                    .unwrap();
+                Size::new(layout.width(), line_height)
+            }
+            None => Size::new(0.0, line_height),
+        };
+
+        match self.position {
+            MessagePosition::Below => {
+                let message_height = if self.reserve_space {
+                    line_height + MESSAGE_GAP
+                } else {
+                    (line_height + MESSAGE_GAP) * self.reveal
+                };
+                let child_bc = bc.shrink((0.0, message_height));
+                let child_size = self.child.layout(layout_ctx, &child_bc, data, env);
+                self.child
+                    .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, child_size));
+                bc.constrain(Size::new(
+                    child_size.width,
+                    child_size.height + message_height,
+                ))
+            }
+            MessagePosition::Beside => {
+                let message_width = (self.message_size.width + MESSAGE_GAP) * self.reveal;
+                let child_bc = bc.shrink((message_width, 0.0));
+                let child_size = self.child.layout(layout_ctx, &child_bc, data, env);
+                self.child
+                    .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, child_size));
+                bc.constrain(Size::new(
+                    child_size.width + message_width,
+                    child_size.height,
+                ))
+            }
+        }
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint_with_offset(paint_ctx, data, env);
+
+        let message = match &self.shown {
+            Some(message) if self.reveal > 0.0 => message,
+            _ => return,
+        };
+        let size = base_state.size();
+        let child = self.child.get_layout_rect();
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL) * MESSAGE_TEXT_SCALE;
+        let (area, origin) = match self.position {
+            MessagePosition::Below => {
+                let top = child.y1 + MESSAGE_GAP * self.reveal;
+                let area = Rect::new(0.0, child.y1, size.width, size.height);
+                (area, Point::new(0.0, top + font_size))
+            }
+            MessagePosition::Beside => {
+                let left = child.x1 + MESSAGE_GAP;
+                let area = Rect::new(child.x1, 0.0, size.width, size.height);
+                let baseline = (child.height() + font_size) / 2.0 - 1.0;
+                (area, Point::new(left, baseline))
+            }
+        };
+        let color = if self.is_error_shown() {
+            env.get(theme::FIELD_ERROR_COLOR)
+        } else {
+            env.get(theme::PLACEHOLDER_COLOR)
+        };
+        let color = color.with_alpha(self.reveal);
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+            .build()
+            .unwrap();
+        let layout = paint_ctx
+            .text()
+            .new_text_layout(&font, message)
+            .build()
+            .unwrap();
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(area);
+            ctx.draw_text(&layout, origin, &color);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    fn field() -> Field<String> {
+        Field::new(Label::new("field"))
+            .help("help")
+            .validate(|text: &String| {
+                if text.is_empty() {
+                    Err("empty".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+    }
+
+    #[test]
+    fn error_replaces_help() {
+        let mut field = field();
+        assert_eq!(field.message().map(String::as_str), Some("help"));
+        assert!(field.check(&String::new()));
+        assert_eq!(field.message().map(String::as_str), Some("empty"));
+        assert!(!field.check(&String::new()));
+        assert!(field.check(&"text".to_string()));
+        assert_eq!(field.message().map(String::as_str), Some("help"));
+    }
+}
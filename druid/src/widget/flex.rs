@@ -17,10 +17,12 @@
 use crate::kurbo::{Point, Rect, Size};
 
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
-    WidgetPod,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LayoutDirection, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
 };
 
+use super::z_order::ZOrder;
+
 /// A builder for a row widget that can contain flex children.
 ///
 /// The actual widget is implemented by [`Flex`], but this builder
@@ -37,9 +39,54 @@ pub struct Row;
 pub struct Column;
 
 /// A container with either horizontal or vertical layout.
+///
+/// When [`theme::RIGHT_TO_LEFT`] is set, the layout is mirrored: rows run
+/// from right to left, and the start of a column's cross axis is its right.
+///
+/// [`theme::RIGHT_TO_LEFT`]: ../theme/constant.RIGHT_TO_LEFT.html
 pub struct Flex<T: Data> {
     direction: Axis,
+    main_alignment: MainAxisAlignment,
+    cross_alignment: CrossAxisAlignment,
+    spacing: f64,
     children: Vec<ChildWidget<T>>,
+    z_order: ZOrder,
+}
+
+/// How a [`Flex`] arranges its children along its main axis, when they
+/// don't fill it.
+///
+/// The main axis is horizontal for a row and vertical for a column. Extra
+/// space is only available when the container is bounded along that axis.
+///
+/// [`Flex`]: struct.Flex.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MainAxisAlignment {
+    /// Children are packed at the start.
+    Start,
+    /// Children are packed in the center.
+    Center,
+    /// Children are packed at the end.
+    End,
+    /// Extra space is divided between the children, with none at either end.
+    SpaceBetween,
+    /// Extra space is divided evenly before, between and after the children.
+    SpaceEvenly,
+    /// Each child gets an equal amount of extra space on either side.
+    SpaceAround,
+}
+
+/// How a [`Flex`] positions each child along its cross axis.
+///
+/// [`Flex`]: struct.Flex.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossAxisAlignment {
+    /// Children are aligned to the top of a row or the start of a column.
+    Start,
+    /// Children are centered.
+    Center,
+    /// Children are aligned to the bottom of a row or the end of a column.
+    End,
 }
 
 struct ChildWidget<T: Data> {
@@ -80,6 +127,36 @@ impl Axis {
     }
 }
 
+impl MainAxisAlignment {
+    /// The space before the first child and the extra space between
+    /// children, given the free space along the axis and the number of
+    /// children.
+    fn spacing(self, free: f64, count: usize) -> (f64, f64) {
+        let n = count as f64;
+        match self {
+            MainAxisAlignment::Start => (0.0, 0.0),
+            MainAxisAlignment::Center => (free / 2.0, 0.0),
+            MainAxisAlignment::End => (free, 0.0),
+            MainAxisAlignment::SpaceBetween if count > 1 => (0.0, free / (n - 1.0)),
+            MainAxisAlignment::SpaceBetween => (0.0, 0.0),
+            MainAxisAlignment::SpaceEvenly => (free / (n + 1.0), free / (n + 1.0)),
+            MainAxisAlignment::SpaceAround if count > 0 => (free / n / 2.0, free / n),
+            MainAxisAlignment::SpaceAround => (0.0, 0.0),
+        }
+    }
+}
+
+impl CrossAxisAlignment {
+    /// The offset of a child of the given extent within the cross axis.
+    fn offset(self, available: f64, extent: f64) -> f64 {
+        match self {
+            CrossAxisAlignment::Start => 0.0,
+            CrossAxisAlignment::Center => ((available - extent) / 2.0).max(0.0),
+            CrossAxisAlignment::End => (available - extent).max(0.0),
+        }
+    }
+}
+
 impl Row {
     /// Create a new row widget.
     ///
@@ -103,11 +180,15 @@ impl Column {
 impl<T: Data> Flex<T> {
     /// Create a new horizontal stack.
     ///
-    /// The child widgets are laid out horizontally, from left to right.
+    /// The child widgets are laid out horizontally, from start to end.
     pub fn row() -> Self {
         Flex {
             direction: Axis::Horizontal,
+            main_alignment: MainAxisAlignment::Start,
+            cross_alignment: CrossAxisAlignment::Start,
+            spacing: 0.0,
             children: Vec::new(),
+            z_order: ZOrder::default(),
         }
     }
 
@@ -117,10 +198,34 @@ impl<T: Data> Flex<T> {
     pub fn column() -> Self {
         Flex {
             direction: Axis::Vertical,
+            main_alignment: MainAxisAlignment::Start,
+            cross_alignment: CrossAxisAlignment::Start,
+            spacing: 0.0,
             children: Vec::new(),
+            z_order: ZOrder::default(),
         }
     }
 
+    /// Builder-style method to set how children are arranged along the
+    /// main axis, when they don't fill it.
+    pub fn main_axis_alignment(mut self, alignment: MainAxisAlignment) -> Self {
+        self.main_alignment = alignment;
+        self
+    }
+
+    /// Builder-style method to set how children are positioned along the
+    /// cross axis.
+    pub fn cross_axis_alignment(mut self, alignment: CrossAxisAlignment) -> Self {
+        self.cross_alignment = alignment;
+        self
+    }
+
+    /// Builder-style method to set the space between adjacent children.
+    pub fn spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
     /// Builder-style variant of `add_child`
     ///
     /// Convenient for assembling a group of widgets in a single expression.
@@ -148,12 +253,27 @@ impl<T: Data> Flex<T> {
         };
         self.children.push(child);
     }
+
+    /// Sort the children into paint order.
+    fn sort_z_order(&mut self) {
+        let children = &self.children;
+        self.z_order
+            .sort(children.len(), |idx| children[idx].widget.z_index());
+    }
 }
 
 impl<T: Data> Widget<T> for Flex<T> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
-        for child in &mut self.children {
-            child.widget.event(ctx, event, data, env);
+        if event.is_pointer_event() {
+            // Hit test the topmost child first.
+            self.sort_z_order();
+            for &idx in self.z_order.indices().iter().rev() {
+                self.children[idx].widget.event(ctx, event, data, env);
+            }
+        } else {
+            for child in &mut self.children {
+                child.widget.event(ctx, event, data, env);
+            }
         }
     }
 
@@ -173,7 +293,8 @@ impl<T: Data> Widget<T> for Flex<T> {
         bc.debug_check("Flex");
 
         // Measure non-flex children.
-        let mut total_non_flex = 0.0;
+        let gaps = self.children.len().saturating_sub(1) as f64;
+        let mut total_non_flex = self.spacing * gaps;
         let mut minor = self.direction.minor(bc.min());
         for child in &mut self.children {
             if child.params.flex == 0.0 {
@@ -225,32 +346,81 @@ impl<T: Data> Widget<T> for Flex<T> {
             }
         }
 
+        let content: f64 = self
+            .children
+            .iter()
+            .map(|child| self.direction.major(child.widget.get_layout_rect().size()))
+            .sum::<f64>()
+            + self.spacing * gaps;
+        let fills_major = flex_sum > 0.0 || self.main_alignment != MainAxisAlignment::Start;
+        let extent = if fills_major && total_major.is_finite() {
+            total_major.max(content)
+        } else {
+            content
+        };
+        let (start, extra) = self
+            .main_alignment
+            .spacing(extent - content, self.children.len());
+
         // Finalize layout, assigning positions to each child.
-        let mut major = 0.0;
+        let mut major = start;
         for child in &mut self.children {
-            // top-align, could do center etc. based on child height
             let rect = child.widget.get_layout_rect();
-            let pos: Point = self.direction.pack(major, 0.0).into();
+            let child_minor = self.direction.minor(rect.size());
+            let minor_pos = self.cross_alignment.offset(minor, child_minor);
+            let pos: Point = self.direction.pack(major, minor_pos).into();
             child.widget.set_layout_rect(rect.with_origin(pos));
-            major += self.direction.major(rect.size());
+            major += self.direction.major(rect.size()) + self.spacing + extra;
         }
 
         if flex_sum > 0.0 && total_major.is_infinite() {
             log::warn!("A child of Flex is flex, but Flex is unbounded.")
         }
 
-        if flex_sum > 0.0 {
-            major = total_major;
-        }
+        let major = extent;
 
         // TODO: should be able to make this `into`
         let (width, height) = self.direction.pack(major, minor);
+
+        if LayoutDirection::from_env(env).is_rtl() {
+            for child in &mut self.children {
+                let rect = child.widget.get_layout_rect();
+                let x = width - rect.x1;
+                child.widget.set_layout_rect(rect.with_origin((x, rect.y0)));
+            }
+        }
+
         Size::new(width, height)
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
-        for child in &mut self.children {
-            child.widget.paint_with_offset(paint_ctx, data, env);
+        self.sort_z_order();
+        for &idx in self.z_order.indices() {
+            self.children[idx]
+                .widget
+                .paint_with_offset(paint_ctx, data, env);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_axis_spacing() {
+        assert_eq!(MainAxisAlignment::Start.spacing(30.0, 3), (0.0, 0.0));
+        assert_eq!(MainAxisAlignment::Center.spacing(30.0, 3), (15.0, 0.0));
+        assert_eq!(MainAxisAlignment::End.spacing(30.0, 3), (30.0, 0.0));
+        assert_eq!(
+            MainAxisAlignment::SpaceBetween.spacing(30.0, 3),
+            (0.0, 15.0)
+        );
+        assert_eq!(MainAxisAlignment::SpaceBetween.spacing(30.0, 1), (0.0, 0.0));
+        assert_eq!(
+            MainAxisAlignment::SpaceEvenly.spacing(30.0, 2),
+            (10.0, 10.0)
+        );
+        assert_eq!(MainAxisAlignment::SpaceAround.spacing(30.0, 3), (5.0, 10.0));
+    }
+}
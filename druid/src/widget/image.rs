@@ -0,0 +1,329 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that shows an image.
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+use log::error;
+
+use crate::kurbo::{Affine, Point, Rect, Size};
+use crate::piet::{ImageFormat, InterpolationMode, RenderContext};
+use crate::raster::PietImage;
+use crate::{
+    BaseState, BoxConstraints, ColorSpace, Data, Env, Event, EventCtx, ImageBuf, LayoutCtx,
+    PaintCtx, UpdateCtx, Widget, WindowResources,
+};
+
+/// How an image is sized and placed in the space it is given.
+///
+/// The image is centered, whatever its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FillStrat {
+    /// As large as possible while showing all of the image.
+    Contain,
+    /// As small as possible while filling the space, cutting off the
+    /// image's edges.
+    Cover,
+    /// Stretched to fill the space, changing its aspect ratio.
+    Fill,
+    /// As wide as the space, keeping its aspect ratio.
+    FitWidth,
+    /// As tall as the space, keeping its aspect ratio.
+    FitHeight,
+    /// At its own size.
+    None,
+}
+
+impl Default for FillStrat {
+    fn default() -> Self {
+        FillStrat::Contain
+    }
+}
+
+impl FillStrat {
+    /// The transform that takes an image of `image_size`, at the origin, to
+    /// where it is shown in a space of `size`.
+    pub fn affine_to_fill(self, size: Size, image_size: Size) -> Affine {
+        let scale_x = size.width / image_size.width;
+        let scale_y = size.height / image_size.height;
+        let (scale_x, scale_y) = match self {
+            FillStrat::Contain => (scale_x.min(scale_y), scale_x.min(scale_y)),
+            FillStrat::Cover => (scale_x.max(scale_y), scale_x.max(scale_y)),
+            FillStrat::Fill => (scale_x, scale_y),
+            FillStrat::FitWidth => (scale_x, scale_x),
+            FillStrat::FitHeight => (scale_y, scale_y),
+            FillStrat::None => (1.0, 1.0),
+        };
+        let x = (size.width - image_size.width * scale_x) / 2.0;
+        let y = (size.height - image_size.height * scale_y) / 2.0;
+        Affine::new([scale_x, 0.0, 0.0, scale_y, x, y])
+    }
+}
+
+/// The image shown by an [`Image`].
+enum ImageSource<T> {
+    Static(Arc<ImageBuf>),
+    Dynamic(Box<dyn Fn(&T, &Env) -> Option<Arc<ImageBuf>>>),
+}
+
+/// A widget that shows an image.
+///
+/// The image is either fixed, or taken from the data, so that it can be
+/// changed while the application runs, for instance when it has been
+/// loaded in the background. The widget asks for the image's size, in
+/// display points, and the image is fitted to the space the widget is
+/// given according to its [`FillStrat`].
+///
+/// Images in another color space than the window's, as tagged with
+/// [`ImageBuf::with_color_space`], are converted before they are shown.
+/// The conversion is kept in the application's [`Resources`], so an image
+/// shown in several windows is only converted once. The image is made into
+/// an image of the render context the first time it is painted, and only
+/// made again when the image or the window's color space changes.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use druid::widget::{FillStrat, Image};
+/// use druid::ImageBuf;
+///
+/// let checker = ImageBuf::from_raw(vec![0, 0, 0, 255, 255, 255, 255, 255], 2, 1);
+/// let fixed: Image<u32> = Image::new(checker).fill_mode(FillStrat::Cover);
+///
+/// // Shows whatever image has been loaded into the data.
+/// let loaded = Image::dynamic(|data: &Option<Arc<ImageBuf>>, _env| data.clone());
+/// ```
+///
+/// [`FillStrat`]: enum.FillStrat.html
+/// [`ImageBuf::with_color_space`]: ../struct.ImageBuf.html#method.with_color_space
+/// [`Resources`]: ../struct.Resources.html
+pub struct Image<T> {
+    source: ImageSource<T>,
+    fill: FillStrat,
+    interpolation: InterpolationMode,
+    /// The image as of the last update.
+    current: Option<Arc<ImageBuf>>,
+    /// The current image converted to the color space of the window, if
+    /// that is different.
+    converted: Option<Rc<Converted>>,
+    /// The current image in the format of the render context, and the color
+    /// space it was made for.
+    piet_image: Option<(PietImage, ColorSpace)>,
+}
+
+/// An image converted to another color space.
+struct Converted {
+    /// The original image, which is kept alive so that its address, which
+    /// names the conversion, isn't reused.
+    source: Arc<ImageBuf>,
+    image: ImageBuf,
+}
+
+impl<T: Data> Image<T> {
+    /// Create a widget that shows `image`.
+    pub fn new(image: impl Into<Arc<ImageBuf>>) -> Image<T> {
+        let image = image.into();
+        Image {
+            source: ImageSource::Static(image.clone()),
+            fill: FillStrat::default(),
+            interpolation: InterpolationMode::Bilinear,
+            current: Some(image),
+            converted: None,
+            piet_image: None,
+        }
+    }
+
+    /// Create a widget that shows the image returned by `image`, which is
+    /// called when the data changes.
+    ///
+    /// Nothing is shown while it returns `None`. The image is only painted
+    /// again when a different `Arc` is returned.
+    pub fn dynamic(image: impl Fn(&T, &Env) -> Option<Arc<ImageBuf>> + 'static) -> Image<T> {
+        Image {
+            source: ImageSource::Dynamic(Box::new(image)),
+            fill: FillStrat::default(),
+            interpolation: InterpolationMode::Bilinear,
+            current: None,
+            converted: None,
+            piet_image: None,
+        }
+    }
+
+    /// Builder-style method to set how the image is fitted to the widget.
+    pub fn fill_mode(mut self, fill: FillStrat) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Builder-style method to set how the image's pixels are sampled when
+    /// it is scaled.
+    ///
+    /// The default, bilinear, suits photos; nearest neighbor keeps pixel
+    /// art and icons sharp.
+    pub fn interpolation_mode(mut self, interpolation: InterpolationMode) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// The size of the current image, if there is one with any pixels.
+    fn image_size(&self) -> Option<Size> {
+        self.current
+            .as_ref()
+            .filter(|image| image.width() > 0 && image.height() > 0)
+            .map(|image| Size::new(image.width() as f64, image.height() as f64))
+    }
+
+    /// Make the current image into an image of the render context, in the
+    /// window's color space.
+    fn make_piet_image(&mut self, paint_ctx: &mut PaintCtx) -> Option<PietImage> {
+        let image = self.current.as_ref()?;
+        let color_space = paint_ctx.color_space();
+        let image = if image.color_space() == color_space {
+            &**image
+        } else {
+            match &self.converted {
+                Some(converted)
+                    if Arc::ptr_eq(&converted.source, image)
+                        && converted.image.color_space() == color_space => {}
+                _ => self.converted = Some(convert(paint_ctx.resources(), image, color_space)),
+            }
+            &self.converted.as_ref().unwrap().image
+        };
+        match paint_ctx.make_image(
+            image.width(),
+            image.height(),
+            image.pixels(),
+            ImageFormat::RgbaSeparate,
+        ) {
+            Ok(piet_image) => Some(piet_image),
+            Err(e) => {
+                error!("failed to make image: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Image<T> {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        if let ImageSource::Dynamic(image) = &self.source {
+            let image = image(data, env);
+            if !self.current.same(&image) {
+                self.current = image;
+                self.converted = None;
+                self.piet_image = None;
+                ctx.invalidate();
+            }
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("Image");
+        bc.constrain(self.image_size().unwrap_or(Size::ZERO))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, _env: &Env) {
+        let image_size = match self.image_size() {
+            Some(size) => size,
+            None => return,
+        };
+        let color_space = paint_ctx.color_space();
+        match &self.piet_image {
+            Some((_, made_for)) if *made_for == color_space => {}
+            _ => {
+                self.piet_image = self
+                    .make_piet_image(paint_ctx)
+                    .map(|piet_image| (piet_image, color_space))
+            }
+        }
+        let piet_image = match &self.piet_image {
+            Some((piet_image, _)) => piet_image,
+            None => return,
+        };
+        let size = base_state.size();
+        let transform = self.fill.affine_to_fill(size, image_size);
+        let interpolation = self.interpolation;
+        paint_ctx.with_save(|ctx| {
+            // Covering and unscaled images may be larger than the widget.
+            ctx.clip(Rect::from_origin_size(Point::ORIGIN, size));
+            ctx.transform(transform);
+            ctx.draw_image(
+                &piet_image,
+                Rect::from_origin_size(Point::ORIGIN, image_size),
+                interpolation,
+            );
+        });
+    }
+}
+
+/// Convert `image` to `color_space`, or get the conversion made for another
+/// window.
+fn convert(
+    resources: WindowResources,
+    image: &Arc<ImageBuf>,
+    color_space: ColorSpace,
+) -> Rc<Converted> {
+    let address = &**image as *const ImageBuf as usize;
+    let name = format!("druid.image.{:x}.{:?}", address, color_space);
+    resources.get_or_insert_with(&name, || Converted {
+        source: image.clone(),
+        image: image.to_color_space(color_space),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_strategies() {
+        let space = Size::new(200.0, 100.0);
+        let image = Size::new(50.0, 50.0);
+        let placed = |fill: FillStrat| {
+            let transform = fill.affine_to_fill(space, image);
+            let p0 = transform * Point::ORIGIN;
+            let p1 = transform * Point::new(image.width, image.height);
+            Rect::from_points(p0, p1)
+        };
+        assert_eq!(
+            placed(FillStrat::Contain),
+            Rect::new(50.0, 0.0, 150.0, 100.0)
+        );
+        assert_eq!(
+            placed(FillStrat::Cover),
+            Rect::new(0.0, -50.0, 200.0, 150.0)
+        );
+        assert_eq!(placed(FillStrat::Fill), Rect::new(0.0, 0.0, 200.0, 100.0));
+        assert_eq!(
+            placed(FillStrat::FitWidth),
+            Rect::new(0.0, -50.0, 200.0, 150.0)
+        );
+        assert_eq!(
+            placed(FillStrat::FitHeight),
+            Rect::new(50.0, 0.0, 150.0, 100.0)
+        );
+        assert_eq!(placed(FillStrat::None), Rect::new(75.0, 25.0, 125.0, 75.0));
+    }
+}
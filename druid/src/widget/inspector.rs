@@ -0,0 +1,353 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A property grid generated from a description of a type.
+
+use std::fmt::Display;
+use std::mem;
+use std::str::FromStr;
+
+use crate::kurbo::{Rect, Size};
+use crate::piet::{Color, RenderContext};
+use crate::theme;
+use crate::widget::{Checkbox, Flex, Label, RadioGroup, TextBox, WidgetExt};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, Lens, PaintCtx, UpdateCtx,
+    Widget,
+};
+
+/// The default width of the property name column.
+const DEFAULT_LABEL_WIDTH: f64 = 120.0;
+/// The size of the swatch shown next to a color.
+const SWATCH_SIZE: f64 = 18.0;
+
+/// A type that can be edited in an [`Inspector`].
+///
+/// Implementations describe their fields, in order, by adding a row to
+/// the [`Properties`] for each one.
+///
+/// # Examples
+///
+/// ```
+/// use druid::piet::Color;
+/// use druid::widget::{Inspect, Properties};
+/// use druid::{Data, Lens};
+///
+/// #[derive(Clone, Data, Lens)]
+/// struct Shape {
+///     name: String,
+///     sides: u32,
+///     filled: bool,
+///     color: Color,
+/// }
+///
+/// impl Inspect for Shape {
+///     fn inspect(properties: &mut Properties<Self>) {
+///         properties
+///             .text("Name", Shape::name)
+///             .number("Sides", Shape::sides)
+///             .bool("Filled", Shape::filled)
+///             .color("Color", Shape::color);
+///     }
+/// }
+/// ```
+///
+/// [`Inspector`]: struct.Inspector.html
+/// [`Properties`]: struct.Properties.html
+pub trait Inspect: Data {
+    /// Add a row to `properties` for each editable field.
+    fn inspect(properties: &mut Properties<Self>);
+}
+
+/// The rows of an [`Inspector`], each a property name and its editor.
+///
+/// [`Inspector`]: struct.Inspector.html
+pub struct Properties<T> {
+    rows: Vec<(String, Box<dyn Widget<T>>)>,
+}
+
+impl<T: Data + 'static> Properties<T> {
+    fn new() -> Self {
+        Properties { rows: Vec::new() }
+    }
+
+    /// Add a checkbox for a `bool` property.
+    pub fn bool(
+        &mut self,
+        name: impl Into<String>,
+        lens: impl Lens<T, bool> + 'static,
+    ) -> &mut Self {
+        self.custom(name, Checkbox::new().lens(lens))
+    }
+
+    /// Add a text field for a numeric property.
+    ///
+    /// Text that does not parse as a number is left in the field, and the
+    /// data keeps its last good value.
+    pub fn number<N>(
+        &mut self,
+        name: impl Into<String>,
+        lens: impl Lens<T, N> + 'static,
+    ) -> &mut Self
+    where
+        N: Data + FromStr + Display + 'static,
+    {
+        let editor = FieldEditor::new(
+            |value: &N| value.to_string(),
+            |text| text.trim().parse().ok(),
+        );
+        self.custom(name, editor.lens(lens))
+    }
+
+    /// Add a text field for a `String` property.
+    pub fn text(
+        &mut self,
+        name: impl Into<String>,
+        lens: impl Lens<T, String> + 'static,
+    ) -> &mut Self {
+        self.custom(name, TextBox::new().lens(lens))
+    }
+
+    /// Add a swatch and a hex field for a color property.
+    ///
+    /// Colors are written as `#rrggbb` or `#rrggbbaa`.
+    pub fn color(
+        &mut self,
+        name: impl Into<String>,
+        lens: impl Lens<T, Color> + 'static,
+    ) -> &mut Self {
+        let editor = Flex::row()
+            .with_child(Swatch.fix_width(SWATCH_SIZE).fix_height(SWATCH_SIZE), 0.0)
+            .with_child(
+                FieldEditor::new(format_color, parse_color).padding((4.0, 0.0, 0.0, 0.0)),
+                1.0,
+            );
+        self.custom(name, editor.lens(lens))
+    }
+
+    /// Add radio buttons for a property with a fixed set of values, such as
+    /// a field-less enum.
+    pub fn choice<V: Data + PartialEq + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        lens: impl Lens<T, V> + 'static,
+        variants: impl IntoIterator<Item = (impl Into<String>, V)>,
+    ) -> &mut Self {
+        let variants = variants
+            .into_iter()
+            .map(|(label, variant)| (label.into(), variant));
+        self.custom(name, RadioGroup::new(variants).lens(lens))
+    }
+
+    /// Add a row with any widget as its editor.
+    pub fn custom(
+        &mut self,
+        name: impl Into<String>,
+        editor: impl Widget<T> + 'static,
+    ) -> &mut Self {
+        self.rows.push((name.into(), Box::new(editor)));
+        self
+    }
+}
+
+/// A two-column grid of property names and editors, generated from the
+/// data's [`Inspect`] implementation.
+///
+/// [`Inspect`]: trait.Inspect.html
+pub struct Inspector<T: Data> {
+    grid: Flex<T>,
+}
+
+impl<T: Inspect + 'static> Inspector<T> {
+    /// Create a new `Inspector` for `T`.
+    pub fn new() -> Self {
+        Self::with_label_width(DEFAULT_LABEL_WIDTH)
+    }
+
+    /// Create a new `Inspector` for `T`, with property names in a column
+    /// of the given width.
+    pub fn with_label_width(label_width: f64) -> Self {
+        let mut properties = Properties::new();
+        T::inspect(&mut properties);
+        let mut grid = Flex::column();
+        for (name, editor) in properties.rows {
+            let row = Flex::row()
+                .with_child(Label::new(name).fix_width(label_width), 0.0)
+                .with_child(editor, 1.0)
+                .padding((0.0, 2.0));
+            grid.add_child(row, 0.0);
+        }
+        Inspector { grid }
+    }
+}
+
+impl<T: Inspect + 'static> Default for Inspector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Data> Widget<T> for Inspector<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.grid.event(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        self.grid.update(ctx, old_data, data, env)
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        self.grid.layout(layout_ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.grid.paint(paint_ctx, base_state, data, env)
+    }
+}
+
+/// A text field editing a value through its text representation.
+///
+/// Like [`Parse`], but the data always holds a valid value.
+///
+/// [`Parse`]: struct.Parse.html
+struct FieldEditor<V> {
+    text_box: Box<dyn Widget<String>>,
+    state: String,
+    format: fn(&V) -> String,
+    parse: fn(&str) -> Option<V>,
+}
+
+impl<V: Data> FieldEditor<V> {
+    fn new(format: fn(&V) -> String, parse: fn(&str) -> Option<V>) -> Self {
+        FieldEditor {
+            text_box: Box::new(TextBox::new()),
+            state: String::new(),
+            format,
+            parse,
+        }
+    }
+}
+
+impl<V: Data> Widget<V> for FieldEditor<V> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut V, env: &Env) {
+        self.text_box.event(ctx, event, &mut self.state, env);
+        if let Some(value) = (self.parse)(&self.state) {
+            if !value.same(data) {
+                *data = value;
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&V>, data: &V, env: &Env) {
+        // Keep text the user is typing, as long as it still means the data.
+        let in_step = (self.parse)(&self.state).map_or(false, |value| value.same(data));
+        if in_step && old_data.is_some() {
+            return;
+        }
+        let old = mem::replace(&mut self.state, (self.format)(data));
+        let old = old_data.map(|_| old);
+        self.text_box.update(ctx, old.as_ref(), &self.state, env)
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &V,
+        env: &Env,
+    ) -> Size {
+        self.text_box.layout(layout_ctx, bc, &self.state, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &V, env: &Env) {
+        self.text_box.paint(paint_ctx, base_state, &self.state, env)
+    }
+}
+
+/// A square filled with the color in the data.
+struct Swatch;
+
+impl Widget<Color> for Swatch {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut Color, _env: &Env) {}
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: Option<&Color>,
+        _data: &Color,
+        _env: &Env,
+    ) {
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &Color,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("Swatch");
+        bc.constrain(Size::new(SWATCH_SIZE, SWATCH_SIZE))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &Color, env: &Env) {
+        let rect = Rect::from_origin_size((0.0, 0.0), base_state.size());
+        paint_ctx.fill(rect, data);
+        paint_ctx.stroke(rect, &env.get(theme::BORDER), 1.0);
+    }
+}
+
+/// Format a color as `#rrggbbaa`.
+fn format_color(color: &Color) -> String {
+    format!("#{:08x}", color.as_rgba_u32())
+}
+
+/// Parse a color written as `#rrggbb` or `#rrggbbaa`; the `#` is optional.
+fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.trim();
+    let hex = hex.trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let byte = |shift: u32| (value >> shift) as u8;
+    match hex.len() {
+        6 => Some(Color::rgb8(byte(16), byte(8), byte(0))),
+        8 => Some(Color::rgba8(byte(24), byte(16), byte(8), byte(0))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_round_trip() {
+        let color = parse_color("#12ab34").unwrap();
+        assert_eq!(color.as_rgba_u32(), 0x12ab_34ff);
+        assert_eq!(format_color(&color), "#12ab34ff");
+        let color = parse_color(" 12ab3480").unwrap();
+        assert_eq!(color.as_rgba_u32(), 0x12ab_3480);
+        assert!(parse_color("#12ab3").is_none());
+        assert!(parse_color("#12ab3g").is_none());
+    }
+}
@@ -14,15 +14,16 @@
 
 //! A label widget.
 
-use crate::kurbo::{Point, Rect, Size};
+use crate::kurbo::{Line, Point, Rect, Size};
+use crate::mnemonic;
 use crate::piet::{
     FontBuilder, PietText, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
     UnitPoint,
 };
 use crate::theme;
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LocalizedString, PaintCtx,
-    UpdateCtx, Widget,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, FontMetrics, LayoutCtx, LifeCycle,
+    LocalizedString, PaintCtx, TextMetrics, UpdateCtx, Widget, WidgetId,
 };
 
 /// The text for the label
@@ -37,9 +38,36 @@ pub enum LabelText<T> {
 }
 
 /// A label that displays some text.
+///
+/// # Mnemonics
+///
+/// An ampersand before a letter or digit marks it as the label's mnemonic,
+/// as in `"&Name:"`; it is not displayed, and is written `"&&"` for a
+/// literal ampersand before a letter. The mnemonic is underlined while
+/// alt is held, and alt and the mnemonic focus the label's
+/// [`mnemonic_target`]. A [`Button`] is clicked by its label's mnemonic.
+///
+/// Mnemonics are not used on macOS, where the marker is still removed.
+///
+/// [`mnemonic_target`]: #method.mnemonic_target
+/// [`Button`]: struct.Button.html
 pub struct Label<T> {
     text: LabelText<T>,
     align: UnitPoint,
+    /// The widget focused by the label's mnemonic.
+    mnemonic_target: Option<WidgetId>,
+    /// Whether the mnemonic is underlined.
+    show_mnemonic: bool,
+    /// The text as of the last update, with its mnemonic marker.
+    resolved: Option<String>,
+    /// The layout of `resolved`, kept until the text or font changes.
+    layout: Option<CachedLayout>,
+}
+
+struct CachedLayout {
+    font_name: String,
+    font_size: f64,
+    layout: PietTextLayout,
 }
 
 impl<T: Data> Label<T> {
@@ -64,6 +92,10 @@ impl<T: Data> Label<T> {
         Self {
             text,
             align: UnitPoint::LEFT,
+            mnemonic_target: None,
+            show_mnemonic: false,
+            resolved: None,
+            layout: None,
         }
     }
 
@@ -73,15 +105,60 @@ impl<T: Data> Label<T> {
         self
     }
 
-    fn get_layout(&mut self, t: &mut PietText, env: &Env, data: &T) -> PietTextLayout {
+    /// Builder-style method to set the widget focused when the label's
+    /// mnemonic is chosen, typically the field it names.
+    ///
+    /// The target's id can be set with [`WidgetPod::with_id`].
+    ///
+    /// [`WidgetPod::with_id`]: ../struct.WidgetPod.html#method.with_id
+    pub fn mnemonic_target(mut self, target: WidgetId) -> Self {
+        self.mnemonic_target = Some(target);
+        self
+    }
+
+    /// The lowercase mnemonic of the label's text, if it has one.
+    pub(crate) fn mnemonic(&self, data: &T, env: &Env) -> Option<char> {
+        self.text.with_display_text(data, env, |text| {
+            mnemonic::parse(text).1.map(|mnemonic| mnemonic.key)
+        })
+    }
+
+    /// Whether the cached layout was made with the theme's current font.
+    fn font_same(&self, env: &Env) -> bool {
+        self.layout.as_ref().map_or(false, |cached| {
+            cached.font_name == env.get(theme::FONT_NAME)
+                && cached.font_size == env.get(theme::TEXT_SIZE_NORMAL)
+        })
+    }
+
+    /// Lay out the text again, if it or the font has changed.
+    fn ensure_layout(&mut self, t: &mut PietText, env: &Env, data: &T) {
+        if self.font_same(env) {
+            return;
+        }
+        // Widgets that embed a label may lay it out before updating it.
+        if self.resolved.is_none() {
+            self.resolved = Some(self.text.with_display_text(data, env, String::from));
+        }
+        let text = self.resolved.as_ref().map_or("", String::as_str);
+        let layout = Self::layout_text(t, env, &mnemonic::parse(text).0);
+        self.layout = Some(CachedLayout {
+            font_name: env.get(theme::FONT_NAME).to_string(),
+            font_size: env.get(theme::TEXT_SIZE_NORMAL),
+            layout,
+        });
+    }
+
+    fn font_metrics(t: &mut PietText, env: &Env) -> FontMetrics {
+        t.font_metrics(env.get(theme::FONT_NAME), env.get(theme::TEXT_SIZE_NORMAL))
+    }
+
+    fn layout_text(t: &mut PietText, env: &Env, text: &str) -> PietTextLayout {
         let font_name = env.get(theme::FONT_NAME);
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
 
-        // TODO: caching of both the format and the layout
         let font = t.new_font_by_name(font_name, font_size).build().unwrap();
-        self.text.with_display_text(data, env, |text| {
-            t.new_text_layout(&font, &text).build().unwrap()
-        })
+        t.new_text_layout(&font, text).build().unwrap()
     }
 }
 
@@ -109,10 +186,33 @@ impl<T: Data> LabelText<T> {
 }
 
 impl<T: Data> Widget<T> for Label<T> {
-    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::LifeCycle(LifeCycle::ShowMnemonics(show)) => {
+                self.show_mnemonic = *show;
+                ctx.invalidate();
+            }
+            Event::Mnemonic(c) => {
+                if let Some(target) = self.mnemonic_target {
+                    if self.mnemonic(data, env) == Some(*c) {
+                        ctx.set_focus(target);
+                        ctx.set_handled();
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
 
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
-        if self.text.resolve(data, env) {
+        self.text.resolve(data, env);
+        let text = self.text.with_display_text(data, env, String::from);
+        if self.resolved.as_ref() != Some(&text) {
+            self.resolved = Some(text);
+            self.layout = None;
+            ctx.invalidate();
+        } else if self.layout.is_some() && !self.font_same(env) {
+            self.layout = None;
             ctx.invalidate();
         }
     }
@@ -126,29 +226,50 @@ impl<T: Data> Widget<T> for Label<T> {
     ) -> Size {
         bc.debug_check("Label");
 
-        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
-        let text_layout = self.get_layout(layout_ctx.text(), env, data);
-        // This magical 1.2 constant helps center the text vertically in the rect it's given
-        bc.constrain(Size::new(text_layout.width(), font_size * 1.2))
+        self.ensure_layout(layout_ctx.text(), env, data);
+        let width = self
+            .layout
+            .as_ref()
+            .map_or(0.0, |cached| cached.layout.width());
+        let metrics = Self::font_metrics(layout_ctx.text(), env);
+        bc.constrain(Size::new(width, metrics.line_height))
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
-        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
-        let text_layout = self.get_layout(paint_ctx.text(), env, data);
+        self.ensure_layout(paint_ctx.text(), env, data);
+        let metrics = Self::font_metrics(paint_ctx.text(), env);
+        let text_layout = match &self.layout {
+            Some(cached) => &cached.layout,
+            None => return,
+        };
 
-        // Find the origin for the text
+        // Align the line within the label, then find its baseline.
         let mut origin = self.align.resolve(Rect::from_origin_size(
             Point::ORIGIN,
             Size::new(
                 (base_state.size().width - text_layout.width()).max(0.0),
-                base_state.size().height + (font_size * 1.2) / 2.,
+                (base_state.size().height - metrics.line_height).max(0.0),
             ),
         ));
+        origin.y += metrics.line_baseline();
 
         //Make sure we don't draw the text too low
         origin.y = origin.y.min(base_state.size().height);
 
-        paint_ctx.draw_text(&text_layout, origin, &env.get(theme::LABEL_COLOR));
+        let color = env.get(theme::LABEL_COLOR);
+        paint_ctx.draw_text(text_layout, origin, &color);
+
+        if self.show_mnemonic {
+            let (text, mnemonic) = self.text.with_display_text(data, env, mnemonic::parse);
+            if let Some(mnemonic) = mnemonic {
+                let range = mnemonic.range(&text);
+                let x0 = Self::layout_text(paint_ctx.text(), env, &text[..range.start]).width();
+                let x1 = Self::layout_text(paint_ctx.text(), env, &text[..range.end]).width();
+                let y = origin.y + 2.0;
+                let underline = Line::new((origin.x + x0, y), (origin.x + x1, y));
+                paint_ctx.stroke(underline, &color, 1.0);
+            }
+        }
     }
 }
 
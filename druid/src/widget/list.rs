@@ -14,19 +14,57 @@
 
 //! Simple list view widget.
 
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+use log::warn;
+
 use crate::kurbo::{Point, Rect, Size};
 
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
-    WidgetPod,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
 };
 
 /// A list widget for a variable-size collection of items.
+///
+/// The list works over any data implementing [`ListIter`], such as
+/// `Arc<Vec<T>>`, or `(S, Arc<Vec<T>>)` to give each item access to some
+/// shared state `S`. A child widget is built with the closure for each
+/// item, and children are created and dropped in `update` as the
+/// collection grows and shrinks. Children are stacked vertically.
+///
+/// A dropped child is sent [`LifeCycle::WidgetRemoved`] before the next
+/// event, so that it leaves the focus chain.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use druid::widget::{Label, List};
+/// use druid::Widget;
+///
+/// let list = List::new(|| Label::new(|item: &String, _env: &_| item.clone()));
+/// # let _: &dyn Widget<Arc<Vec<String>>> = &list;
+/// ```
+///
+/// [`ListIter`]: trait.ListIter.html
+/// [`LifeCycle::WidgetRemoved`]: ../enum.LifeCycle.html#variant.WidgetRemoved
 pub struct List<T: Data> {
     closure: Box<dyn Fn() -> Box<dyn Widget<T>>>,
     children: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+    /// Computes the key of an item, if the list is keyed.
+    key_fn: Option<Box<dyn Fn(&T) -> ItemKey>>,
+    /// The keys of the items the children were last matched to.
+    keys: Vec<ItemKey>,
+    /// Children that were dropped, to be removed on the next event, with
+    /// the last data they were given.
+    retired: Vec<(WidgetPod<T, Box<dyn Widget<T>>>, T)>,
+    /// The distinct z-indices of the children, lowest first.
+    z_levels: Vec<i32>,
 }
 
 impl<T: Data> List<T> {
@@ -36,7 +74,124 @@ impl<T: Data> List<T> {
         List {
             closure: Box::new(move || Box::new(closure())),
             children: Vec::new(),
+            key_fn: None,
+            keys: Vec::new(),
+            retired: Vec::new(),
+            z_levels: Vec::new(),
+        }
+    }
+
+    /// Builder-style method to give each item a stable key.
+    ///
+    /// By default, children are matched to items by position, so inserting
+    /// or removing an item shifts the state of every child after it. With a
+    /// key, a child follows its item when items are inserted, removed, or
+    /// reordered, keeping its internal state, such as focus or a scroll
+    /// offset.
+    ///
+    /// Keys are compared for equality, not only by their hashes, so
+    /// different keys never share a child. Keys should be unique within the
+    /// list; children of items with the same key are matched in order, and
+    /// a warning is logged.
+    pub fn with_key<K: Hash + Eq + 'static>(mut self, key: impl Fn(&T) -> K + 'static) -> Self {
+        self.key_fn = Some(Box::new(move |item| ItemKey::new(key(item))));
+        self
+    }
+
+    /// Move, create, and drop children so that they match the keys of the
+    /// items in `data`.
+    ///
+    /// Returns `true` if the children changed.
+    fn sync_keyed_children<I: ListIter<T>>(&mut self, data: &I) -> bool {
+        let key_fn = match &self.key_fn {
+            Some(key_fn) => key_fn,
+            None => return false,
+        };
+        let mut new_keys = Vec::with_capacity(data.data_len());
+        data.for_each(|item, _| new_keys.push(key_fn(item)));
+        if new_keys == self.keys {
+            return false;
+        }
+        let mut seen = HashSet::with_capacity(new_keys.len());
+        if !new_keys.iter().all(|key| seen.insert(key)) {
+            warn!("List items have duplicate keys; their children may swap state.");
+        }
+
+        // Children are pushed in reverse, so that if keys are repeated they
+        // are popped in their original order.
+        let mut old_children = HashMap::new();
+        for (key, child) in self.keys.drain(..).zip(self.children.drain(..)).rev() {
+            old_children.entry(key).or_insert_with(Vec::new).push(child);
+        }
+        for key in &new_keys {
+            let child = match old_children.get_mut(key).and_then(Vec::pop) {
+                Some(child) => child,
+                None => WidgetPod::new((self.closure)()),
+            };
+            self.children.push(child);
+        }
+        for child in old_children.into_iter().flat_map(|(_, children)| children) {
+            self.retire(child);
         }
+        self.keys = new_keys;
+        true
+    }
+
+    /// Collect the distinct z-indices of the children.
+    ///
+    /// Children can only be visited in the order of their items, so those
+    /// with a higher z-index are painted in a later pass over the items.
+    fn sort_z_levels(&mut self) {
+        self.z_levels.clear();
+        self.z_levels
+            .extend(self.children.iter().map(|child| child.z_index()));
+        self.z_levels.sort_unstable();
+        self.z_levels.dedup();
+    }
+
+    /// Keep a dropped child until the next event, to remove it from the
+    /// tree. A child that was never given data was never added.
+    fn retire(&mut self, child: WidgetPod<T, Box<dyn Widget<T>>>) {
+        if let Some(data) = child.old_data().cloned() {
+            self.retired.push((child, data));
+        }
+    }
+}
+
+/// The key of an item of a keyed [`List`] or row of a keyed [`Table`], of
+/// any type.
+///
+/// [`List`]: struct.List.html
+/// [`Table`]: struct.Table.html
+pub(crate) struct ItemKey {
+    key: Box<dyn Any>,
+    hash: u64,
+    eq: fn(&dyn Any, &dyn Any) -> bool,
+}
+
+impl ItemKey {
+    pub(crate) fn new<K: Hash + Eq + 'static>(key: K) -> Self {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        ItemKey {
+            key: Box::new(key),
+            hash: hasher.finish(),
+            eq: |a, b| a.downcast_ref::<K>() == b.downcast_ref::<K>(),
+        }
+    }
+}
+
+impl PartialEq for ItemKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && (self.eq)(&*self.key, &*other.key)
+    }
+}
+
+impl Eq for ItemKey {}
+
+impl Hash for ItemKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state)
     }
 }
 
@@ -125,16 +280,40 @@ impl<T1: Data, T: Data> ListIter<(T1, T)> for (T1, Arc<Vec<T>>) {
 
 impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
-        let mut children = self.children.iter_mut();
-        data.for_each_mut(|child_data, _| {
-            if let Some(child) = children.next() {
-                child.event(ctx, event, child_data, env);
+        let removed = Event::LifeCycle(LifeCycle::WidgetRemoved);
+        for (mut child, mut child_data) in self.retired.drain(..) {
+            child.event(ctx, &removed, &mut child_data, env);
+        }
+
+        if event.is_pointer_event() {
+            // The children painted on top get the mouse first.
+            self.sort_z_levels();
+            for &level in self.z_levels.iter().rev() {
+                let mut children = self.children.iter_mut();
+                data.for_each_mut(|child_data, _| {
+                    if let Some(child) = children.next() {
+                        if child.z_index() == level {
+                            child.event(ctx, event, child_data, env);
+                        }
+                    }
+                });
             }
-        });
+        } else {
+            let mut children = self.children.iter_mut();
+            data.for_each_mut(|child_data, _| {
+                if let Some(child) = children.next() {
+                    child.event(ctx, event, child_data, env);
+                }
+            });
+        }
     }
 
     #[allow(clippy::comparison_chain)] // clippy doesn't like our very reasonable if  { } else if { }
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        if self.sync_keyed_children(data) {
+            ctx.invalidate();
+        }
+
         let mut children = self.children.iter_mut();
         data.for_each(|child_data, _| {
             if let Some(child) = children.next() {
@@ -144,7 +323,10 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
 
         let len = self.children.len();
         if len > data.data_len() {
-            self.children.truncate(data.data_len())
+            let removed: Vec<_> = self.children.drain(data.data_len()..).collect();
+            for child in removed {
+                self.retire(child);
+            }
         } else if len < data.data_len() {
             data.for_each(|child_data, i| {
                 if i < len {
@@ -165,6 +347,8 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
         data: &T,
         env: &Env,
     ) -> Size {
+        bc.debug_check("List");
+
         let mut width = bc.min().width;
         let mut y = 0.0;
 
@@ -191,11 +375,16 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
-        let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
-            if let Some(child) = children.next() {
-                child.paint_with_offset(paint_ctx, child_data, env);
-            }
-        });
+        self.sort_z_levels();
+        for &level in &self.z_levels {
+            let mut children = self.children.iter_mut();
+            data.for_each(|child_data, _| {
+                if let Some(child) = children.next() {
+                    if child.z_index() == level {
+                        child.paint_with_offset(paint_ctx, child_data, env);
+                    }
+                }
+            });
+        }
     }
 }
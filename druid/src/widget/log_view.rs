@@ -0,0 +1,460 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A view of a growing log.
+
+use std::sync::Arc;
+
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::piet::{
+    Color, FontBuilder, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
+};
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+};
+
+/// The number of lines in each chunk of a `LogBuffer`.
+const CHUNK_SIZE: usize = 256;
+const LEFT_PADDING: f64 = 4.0;
+
+/// The severity of a log line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+/// A line of a log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    /// How important the line is.
+    pub severity: Severity,
+    /// The text of the line, without a line break.
+    pub text: String,
+}
+
+/// The lines of a log, which can only grow.
+///
+/// Lines are stored in shared chunks, so that adding a line to a buffer
+/// that is also held elsewhere (such as by the previous version of the
+/// application data) copies only the last chunk, not the whole log.
+#[derive(Debug, Clone, Default)]
+pub struct LogBuffer {
+    chunks: Arc<Vec<Arc<Vec<LogLine>>>>,
+    len: usize,
+}
+
+/// The data shown by a [`LogView`].
+///
+/// [`LogView`]: struct.LogView.html
+#[derive(Debug, Clone)]
+pub struct LogData {
+    /// The lines of the log.
+    pub lines: LogBuffer,
+    /// Only lines containing this text are shown, if it isn't empty.
+    pub filter: String,
+    /// Only lines of at least this severity are shown.
+    pub min_severity: Severity,
+    /// Occurrences of this text are highlighted, if it isn't empty.
+    pub search: String,
+    /// Whether the view sticks to the end of the log as lines are added.
+    pub follow: bool,
+}
+
+/// A scrolling view of the lines of a log, colored by severity.
+///
+/// The view is virtualized: only the lines that are visible are laid out
+/// and drawn, so it can show very long logs. The lines shown are filtered
+/// by the data's `filter` and `min_severity`; when only lines have been
+/// added, just the new lines are filtered. When the filter changes, the
+/// view keeps the line at its top in place, or the first line after it
+/// that is still shown.
+///
+/// While the data's `follow` flag is set, the view stays at the end of the
+/// log as it grows. Scrolling up clears the flag, and scrolling back to the
+/// end sets it again, so it can also be bound to a checkbox.
+pub struct LogView {
+    /// The indices of the lines that pass the filter.
+    shown: Vec<usize>,
+    /// The number of lines that have been filtered.
+    scanned: usize,
+    /// The filter `shown` was computed with.
+    filter: Option<(String, Severity)>,
+    /// The vertical scroll position, in display points.
+    offset: f64,
+    line_height: f64,
+    height: f64,
+}
+
+impl Severity {
+    /// The color lines of this severity are drawn with.
+    fn color(self, env: &Env) -> Color {
+        match self {
+            Severity::Trace | Severity::Debug => env.get(theme::PLACEHOLDER_COLOR),
+            Severity::Info => env.get(theme::LABEL_COLOR),
+            Severity::Warning => env.get(theme::LOG_WARNING_COLOR),
+            Severity::Error => env.get(theme::LOG_ERROR_COLOR),
+        }
+    }
+}
+
+impl LogLine {
+    /// Create a log line.
+    pub fn new(severity: Severity, text: impl Into<String>) -> LogLine {
+        LogLine {
+            severity,
+            text: text.into(),
+        }
+    }
+}
+
+impl LogBuffer {
+    /// Create an empty buffer.
+    pub fn new() -> LogBuffer {
+        LogBuffer::default()
+    }
+
+    /// Add a line at the end of the log.
+    pub fn push(&mut self, line: LogLine) {
+        let chunks = Arc::make_mut(&mut self.chunks);
+        match chunks.last_mut() {
+            Some(chunk) if chunk.len() < CHUNK_SIZE => Arc::make_mut(chunk).push(line),
+            _ => {
+                let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+                chunk.push(line);
+                chunks.push(Arc::new(chunk));
+            }
+        }
+        self.len += 1;
+    }
+
+    /// The number of lines.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no lines.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The line at `index`, if it exists.
+    pub fn get(&self, index: usize) -> Option<&LogLine> {
+        self.chunks
+            .get(index / CHUNK_SIZE)
+            .and_then(|chunk| chunk.get(index % CHUNK_SIZE))
+    }
+
+    /// An iterator over the lines.
+    pub fn iter(&self) -> impl Iterator<Item = &LogLine> {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+}
+
+impl Data for LogBuffer {
+    fn same(&self, other: &Self) -> bool {
+        // Chunks are only ever added to, so the length tells in-place
+        // additions apart.
+        self.chunks.same(&other.chunks) && self.len == other.len
+    }
+}
+
+impl LogData {
+    /// Create log data with no lines, following the end of the log.
+    pub fn new() -> LogData {
+        LogData::default()
+    }
+}
+
+impl Default for LogData {
+    fn default() -> Self {
+        LogData {
+            lines: LogBuffer::new(),
+            filter: String::new(),
+            min_severity: Severity::Trace,
+            search: String::new(),
+            follow: true,
+        }
+    }
+}
+
+impl Data for LogData {
+    fn same(&self, other: &Self) -> bool {
+        self.lines.same(&other.lines)
+            && self.filter == other.filter
+            && self.min_severity == other.min_severity
+            && self.search == other.search
+            && self.follow == other.follow
+    }
+}
+
+impl LogView {
+    /// Create a new `LogView`.
+    pub fn new() -> LogView {
+        LogView {
+            shown: Vec::new(),
+            scanned: 0,
+            filter: None,
+            offset: 0.0,
+            line_height: 0.0,
+            height: 0.0,
+        }
+    }
+
+    /// Bring the filtered lines up to date with the data.
+    fn refilter(&mut self, data: &LogData) {
+        let same_filter = match &self.filter {
+            Some((filter, severity)) => *filter == data.filter && *severity == data.min_severity,
+            None => false,
+        };
+        // A shorter log is a different log, so the view starts over.
+        let replaced = data.lines.len() < self.scanned;
+        let mut anchor = None;
+        if !same_filter || replaced {
+            if !replaced && self.line_height > 0.0 {
+                // The offset is in rows of the old filter.
+                let row = (self.offset / self.line_height).floor();
+                let within = self.offset - row * self.line_height;
+                anchor = self.shown.get(row as usize).map(|&index| (index, within));
+            }
+            self.shown.clear();
+            self.scanned = 0;
+            self.offset = 0.0;
+            self.filter = Some((data.filter.clone(), data.min_severity));
+        }
+        for index in self.scanned..data.lines.len() {
+            if let Some(line) = data.lines.get(index) {
+                if line.severity >= data.min_severity
+                    && (data.filter.is_empty() || line.text.contains(&data.filter))
+                {
+                    self.shown.push(index);
+                }
+            }
+        }
+        self.scanned = data.lines.len();
+        if let Some((index, within)) = anchor {
+            let row = match self.shown.binary_search(&index) {
+                Ok(row) => row,
+                Err(row) => row,
+            };
+            self.offset = row as f64 * self.line_height + within;
+        }
+    }
+
+    fn max_offset(&self) -> f64 {
+        (self.shown.len() as f64 * self.line_height - self.height).max(0.0)
+    }
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        LogView::new()
+    }
+}
+
+impl Widget<LogData> for LogView {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut LogData, _env: &Env) {
+        if let Event::Wheel(wheel) = event {
+            let delta = ctx.wheel_remaining(wheel);
+            let max = self.max_offset();
+            let offset = (self.offset + delta.y).max(0.0).min(max);
+            let change = offset - self.offset;
+            self.offset = offset;
+            if delta.y < 0.0 && offset < max {
+                data.follow = false;
+            } else if delta.y > 0.0 && offset >= max {
+                data.follow = true;
+            }
+            ctx.consume_wheel(Vec2::new(0.0, change));
+            ctx.invalidate();
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: Option<&LogData>,
+        data: &LogData,
+        _env: &Env,
+    ) {
+        self.refilter(data);
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &LogData,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("LogView");
+        self.line_height = (env.get(theme::TEXT_SIZE_NORMAL) * 1.4).round();
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            bc.min().width
+        };
+        let height = if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            self.shown.len() as f64 * self.line_height
+        };
+        let size = bc.constrain(Size::new(width, height));
+        self.height = size.height;
+        self.offset = if data.follow {
+            self.max_offset()
+        } else {
+            self.offset.min(self.max_offset())
+        };
+        size
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        base_state: &BaseState,
+        data: &LogData,
+        env: &Env,
+    ) {
+        let size = base_state.size();
+        let region = paint_ctx.region().to_rect();
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+            .build()
+            .unwrap();
+        let highlight_color = env.get(theme::FIND_HIGHLIGHT_COLOR);
+        let line_height = self.line_height.max(1.0);
+
+        // Only the lines within the region are laid out.
+        let first = ((self.offset + region.y0) / line_height).floor().max(0.0) as usize;
+        let last = ((self.offset + region.y1) / line_height).ceil().max(0.0) as usize;
+        let last = last.min(self.shown.len());
+
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(Rect::from_origin_size(Point::ORIGIN, size));
+            for row in first.min(last)..last {
+                let line = match data.lines.get(self.shown[row]) {
+                    Some(line) => line,
+                    None => continue,
+                };
+                let top = row as f64 * line_height - self.offset;
+                let layout: PietTextLayout = ctx
+                    .text()
+                    .new_text_layout(&font, &line.text)
+                    .build()
+                    .unwrap();
+
+                if !data.search.is_empty() {
+                    let x_at = |offset: usize| {
+                        layout
+                            .hit_test_text_position(offset)
+                            .map_or(0.0, |position| position.point.x)
+                    };
+                    for (start, found) in line.text.match_indices(&data.search) {
+                        let rect = Rect::new(
+                            LEFT_PADDING + x_at(start),
+                            top,
+                            LEFT_PADDING + x_at(start + found.len()),
+                            top + line_height,
+                        );
+                        ctx.fill(rect, &highlight_color);
+                    }
+                }
+
+                let baseline = top + (line_height + font_size) * 0.5 - 2.0;
+                let color = line.severity.color(env);
+                ctx.draw_text(&layout, (LEFT_PADDING, baseline), &color);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_appends_across_chunks() {
+        let mut buffer = LogBuffer::new();
+        for i in 0..CHUNK_SIZE + 10 {
+            buffer.push(LogLine::new(Severity::Info, format!("line {}", i)));
+        }
+        assert_eq!(buffer.len(), CHUNK_SIZE + 10);
+        assert_eq!(buffer.get(CHUNK_SIZE + 1).unwrap().text, "line 257");
+        assert_eq!(buffer.iter().count(), buffer.len());
+
+        let old = buffer.clone();
+        buffer.push(LogLine::new(Severity::Error, "oops"));
+        assert!(!old.same(&buffer));
+        assert_eq!(old.len() + 1, buffer.len());
+        assert!(Arc::ptr_eq(&old.chunks[0], &buffer.chunks[0]));
+    }
+
+    #[test]
+    fn filters_new_lines_only() {
+        let mut data = LogData::new();
+        data.lines.push(LogLine::new(Severity::Debug, "starting"));
+        data.lines.push(LogLine::new(Severity::Error, "disk full"));
+        data.min_severity = Severity::Info;
+
+        let mut view = LogView::new();
+        view.refilter(&data);
+        assert_eq!(view.shown, vec![1]);
+
+        data.lines
+            .push(LogLine::new(Severity::Warning, "disk nearly full"));
+        view.refilter(&data);
+        assert_eq!(view.shown, vec![1, 2]);
+
+        data.filter = "nearly".into();
+        view.refilter(&data);
+        assert_eq!(view.shown, vec![2]);
+    }
+
+    #[test]
+    fn refilter_keeps_top_line() {
+        let mut data = LogData::new();
+        for i in 0..10 {
+            let severity = if i % 2 == 0 {
+                Severity::Info
+            } else {
+                Severity::Error
+            };
+            data.lines
+                .push(LogLine::new(severity, format!("line {}", i)));
+        }
+        let mut view = LogView::new();
+        view.line_height = 10.0;
+        view.refilter(&data);
+        // Line 5 is at the top, scrolled by half a line.
+        view.offset = 55.0;
+
+        data.min_severity = Severity::Error;
+        view.refilter(&data);
+        assert_eq!(view.shown, vec![1, 3, 5, 7, 9]);
+        assert_eq!(view.offset, 25.0);
+
+        // Line 5 is at the top, and isn't shown after this: line 7 is.
+        data.filter = "7".into();
+        view.refilter(&data);
+        assert_eq!(view.offset, 5.0);
+    }
+}
@@ -0,0 +1,721 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A text box for input that follows a fixed format.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{Line, Point, RoundedRect, Size};
+use crate::piet::{
+    FontBuilder, PietText, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
+};
+use crate::theme;
+use crate::{
+    commands, Application, BaseState, BoxConstraints, Cursor, EditAction, Env, Event, EventCtx,
+    KeyCode, KeyMap, LayoutCtx, LifeCycle, Movement, PaintCtx, Selector, TimerToken, UpdateCtx,
+    Widget,
+};
+
+const PADDING_TOP: f64 = 5.;
+const PADDING_LEFT: f64 = 4.;
+
+/// One position of a [`Mask`].
+///
+/// [`Mask`]: struct.Mask.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MaskChar {
+    /// A character that is always shown, and never typed.
+    Literal(char),
+    /// A slot for an ASCII digit.
+    Digit,
+    /// A slot for a letter.
+    Letter,
+    /// A slot for a letter or a digit.
+    AlphaNumeric,
+}
+
+impl MaskChar {
+    fn accepts(self, c: char) -> bool {
+        match self {
+            MaskChar::Literal(_) => false,
+            MaskChar::Digit => c.is_ascii_digit(),
+            MaskChar::Letter => c.is_alphabetic(),
+            MaskChar::AlphaNumeric => c.is_alphanumeric(),
+        }
+    }
+}
+
+/// The format of the input of a [`MaskedTextBox`].
+///
+/// A mask is written as a template, in which `#` is a slot for a digit,
+/// `A` a slot for a letter, and `*` a slot for either. Any other character
+/// is a literal, shown in place and skipped over while typing; a `\`
+/// makes the following character a literal, even if it is one of the
+/// slot characters.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::Mask;
+///
+/// let date = Mask::new("##/##/####");
+/// assert_eq!(date.display("0412", '_'), "04/12/____");
+/// assert!(!date.is_complete("0412"));
+/// ```
+///
+/// [`MaskedTextBox`]: struct.MaskedTextBox.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mask {
+    chars: Vec<MaskChar>,
+}
+
+impl Mask {
+    /// Create a mask from a template.
+    pub fn new(template: &str) -> Mask {
+        let mut chars = Vec::new();
+        let mut escaped = false;
+        for c in template.chars() {
+            let mask_char = match c {
+                _ if escaped => MaskChar::Literal(c),
+                '\\' => {
+                    escaped = true;
+                    continue;
+                }
+                '#' => MaskChar::Digit,
+                'A' => MaskChar::Letter,
+                '*' => MaskChar::AlphaNumeric,
+                c => MaskChar::Literal(c),
+            };
+            escaped = false;
+            chars.push(mask_char);
+        }
+        Mask { chars }
+    }
+
+    /// The slots of the mask, in order.
+    fn slots(&self) -> impl Iterator<Item = MaskChar> + '_ {
+        self.chars.iter().cloned().filter(|c| !matches_literal(*c))
+    }
+
+    /// The number of characters the mask takes.
+    pub fn slot_count(&self) -> usize {
+        self.slots().count()
+    }
+
+    /// Whether every slot is filled by `value`.
+    pub fn is_complete(&self, value: &str) -> bool {
+        self.is_valid(value) && value.chars().count() == self.slot_count()
+    }
+
+    /// Whether `value` fills the first slots of the mask with characters
+    /// they accept.
+    pub fn is_valid(&self, value: &str) -> bool {
+        let mut slots = self.slots();
+        value
+            .chars()
+            .all(|c| slots.next().map_or(false, |slot| slot.accepts(c)))
+    }
+
+    /// The text shown for `value`: the mask's literals, with the slots
+    /// filled from `value` and the rest shown as `placeholder`.
+    pub fn display(&self, value: &str, placeholder: char) -> String {
+        self.display_with_offsets(value, placeholder).0
+    }
+
+    /// The display text, and the byte offset in it of each slot, followed by
+    /// the offset of the end of the text.
+    fn display_with_offsets(&self, value: &str, placeholder: char) -> (String, Vec<usize>) {
+        let mut text = String::new();
+        let mut offsets = Vec::new();
+        let mut value = value.chars();
+        for mask_char in &self.chars {
+            match mask_char {
+                MaskChar::Literal(c) => text.push(*c),
+                _ => {
+                    offsets.push(text.len());
+                    text.push(value.next().unwrap_or(placeholder));
+                }
+            }
+        }
+        offsets.push(text.len());
+        (text, offsets)
+    }
+
+    /// Replace the characters of `value` in `range` (in slots) with the
+    /// accepted characters of `text`, returning the new value and the slot
+    /// after the inserted text.
+    ///
+    /// Characters of `text` that no slot would accept are dropped, so that
+    /// formatted text, literals and all, can be pasted. Characters after the
+    /// edit move to fill the freed slots, and are dropped if they no longer
+    /// fit.
+    fn replace(&self, value: &str, range: Range<usize>, text: &str) -> (String, usize) {
+        let value: Vec<char> = value.chars().collect();
+        let slots: Vec<MaskChar> = self.slots().collect();
+        let start = range.start.min(value.len());
+        let end = range.end.min(value.len()).max(start);
+
+        let mut result: Vec<char> = value[..start].to_vec();
+        for c in text.chars() {
+            match slots.get(result.len()) {
+                Some(slot) if slot.accepts(c) => result.push(c),
+                Some(_) => (),
+                None => break,
+            }
+        }
+        let caret = result.len();
+        for &c in &value[end..] {
+            match slots.get(result.len()) {
+                Some(slot) if slot.accepts(c) => result.push(c),
+                _ => break,
+            }
+        }
+        (result.into_iter().collect(), caret)
+    }
+
+    /// The start of the group of slots before `slot`; groups are separated
+    /// by literals.
+    fn group_start(&self, slot: usize) -> usize {
+        let boundaries = self.group_boundaries();
+        boundaries
+            .iter()
+            .rev()
+            .cloned()
+            .find(|&boundary| boundary < slot)
+            .unwrap_or(0)
+    }
+
+    /// The end of the group of slots after `slot`.
+    fn group_end(&self, slot: usize) -> usize {
+        let boundaries = self.group_boundaries();
+        boundaries
+            .iter()
+            .cloned()
+            .find(|&boundary| boundary > slot)
+            .unwrap_or_else(|| self.slot_count())
+    }
+
+    /// The slot indices at which a literal separates slots.
+    fn group_boundaries(&self) -> Vec<usize> {
+        let mut boundaries = Vec::new();
+        let mut slot = 0;
+        for mask_char in &self.chars {
+            if matches_literal(*mask_char) {
+                if boundaries.last() != Some(&slot) {
+                    boundaries.push(slot);
+                }
+            } else {
+                slot += 1;
+            }
+        }
+        boundaries
+    }
+}
+
+fn matches_literal(mask_char: MaskChar) -> bool {
+    match mask_char {
+        MaskChar::Literal(_) => true,
+        _ => false,
+    }
+}
+
+/// A text box whose input follows a [`Mask`], such as a phone number or a
+/// date.
+///
+/// The data holds only the characters typed into the slots of the mask;
+/// the literals are shown, but are not part of the data. Use
+/// [`Mask::is_complete`] to check that every slot is filled.
+///
+/// Typing moves the caret over literals, and characters a slot does not
+/// accept are ignored, including when pasting. Word movement moves between
+/// the groups of slots separated by literals. Editing goes through the same
+/// [`EditAction`]s and [`KeyMap`] as a [`TextBox`], and tab moves focus in
+/// the same way.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::MaskedTextBox;
+///
+/// let phone = MaskedTextBox::new("(###) ###-####");
+/// ```
+///
+/// [`Mask`]: struct.Mask.html
+/// [`Mask::is_complete`]: struct.Mask.html#method.is_complete
+/// [`EditAction`]: ../enum.EditAction.html
+/// [`KeyMap`]: ../struct.KeyMap.html
+/// [`TextBox`]: struct.TextBox.html
+#[derive(Debug, Clone)]
+pub struct MaskedTextBox {
+    mask: Mask,
+    placeholder: char,
+    keymap: KeyMap,
+    /// The fixed end of the selection, in slots.
+    anchor: usize,
+    /// The moving end of the selection, in slots.
+    caret: usize,
+    width: f64,
+    cursor_timer: TimerToken,
+    cursor_on: bool,
+}
+
+impl MaskedTextBox {
+    /// Create a new `MaskedTextBox` with a [`Mask`] template.
+    ///
+    /// [`Mask`]: struct.Mask.html
+    pub fn new(template: &str) -> Self {
+        MaskedTextBox {
+            mask: Mask::new(template),
+            placeholder: '_',
+            keymap: KeyMap::default(),
+            anchor: 0,
+            caret: 0,
+            width: 0.0,
+            cursor_timer: TimerToken::INVALID,
+            cursor_on: false,
+        }
+    }
+
+    /// Builder-style method to set the character shown in empty slots.
+    pub fn placeholder(mut self, placeholder: char) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// Builder-style method to set the [`KeyMap`] used to translate key
+    /// presses into [`EditAction`]s.
+    ///
+    /// [`KeyMap`]: ../struct.KeyMap.html
+    /// [`EditAction`]: ../enum.EditAction.html
+    pub fn with_keymap(mut self, keymap: KeyMap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Returns the mask.
+    pub fn mask(&self) -> &Mask {
+        &self.mask
+    }
+
+    fn selection(&self) -> Range<usize> {
+        self.anchor.min(self.caret)..self.anchor.max(self.caret)
+    }
+
+    fn caret_to(&mut self, slot: usize) {
+        self.anchor = slot;
+        self.caret = slot;
+    }
+
+    fn get_layout(&self, piet_text: &mut PietText, text: &str, env: &Env) -> PietTextLayout {
+        let font = piet_text
+            .new_font_by_name(env.get(theme::FONT_NAME), env.get(theme::TEXT_SIZE_NORMAL))
+            .build()
+            .unwrap();
+        piet_text.new_text_layout(&font, text).build().unwrap()
+    }
+
+    /// The filled slot nearest to a point.
+    fn slot_for_point(
+        &self,
+        point: Point,
+        layout: &PietTextLayout,
+        offsets: &[usize],
+        len: usize,
+        env: &Env,
+    ) -> usize {
+        let x = point.x - theme::spacing(env, PADDING_LEFT);
+        let slot_x = |slot: usize| {
+            layout
+                .hit_test_text_position(offsets[slot])
+                .map_or(0.0, |hit| hit.point.x)
+        };
+        (0..=len)
+            .min_by(|a, b| {
+                let a = (slot_x(*a) - x).abs();
+                let b = (slot_x(*b) - x).abs();
+                a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(0)
+    }
+
+    fn slot_for_movement(&self, movement: Movement, len: usize) -> usize {
+        match movement {
+            Movement::Left => self.caret.saturating_sub(1),
+            Movement::Right => (self.caret + 1).min(len),
+            Movement::WordLeft => self.mask.group_start(self.caret),
+            Movement::WordRight => self.mask.group_end(self.caret).min(len),
+            Movement::LineStart | Movement::Up => 0,
+            Movement::LineEnd | Movement::Down => len,
+        }
+    }
+
+    fn insert(&mut self, data: &mut String, text: &str) {
+        let (value, caret) = self.mask.replace(data, self.selection(), text);
+        *data = value;
+        self.caret_to(caret);
+    }
+
+    fn copy(&self, data: &str) {
+        let range = self.selection();
+        let text: String = data
+            .chars()
+            .skip(range.start)
+            .take(range.end - range.start)
+            .collect();
+        if !text.is_empty() {
+            Application::clipboard().put_string(text);
+        }
+    }
+
+    fn can_handle_command(&self, selector: &Selector, read_only: bool) -> bool {
+        let has_selection = self.anchor != self.caret;
+        match *selector {
+            commands::COPY => has_selection,
+            commands::CUT | commands::DELETE => !read_only && has_selection,
+            commands::PASTE => !read_only,
+            commands::SELECT_ALL => true,
+            _ => false,
+        }
+    }
+
+    fn action_for_command(selector: &Selector) -> Option<EditAction> {
+        match *selector {
+            commands::COPY => Some(EditAction::Copy),
+            commands::CUT => Some(EditAction::Cut),
+            commands::DELETE => Some(EditAction::Delete),
+            commands::PASTE => Some(EditAction::Paste),
+            commands::SELECT_ALL => Some(EditAction::SelectAll),
+            _ => None,
+        }
+    }
+
+    fn do_edit_action(
+        &mut self,
+        ctx: &mut EventCtx,
+        action: EditAction,
+        data: &mut String,
+        read_only: bool,
+    ) {
+        let len = data.chars().count();
+        let has_selection = self.anchor != self.caret;
+        match action {
+            EditAction::Insert(text) if !read_only => self.insert(data, &text),
+            EditAction::Backspace | EditAction::DeleteWordBackward
+                if !read_only && !has_selection =>
+            {
+                self.anchor = match action {
+                    EditAction::Backspace => self.caret.saturating_sub(1),
+                    _ => self.mask.group_start(self.caret),
+                };
+                self.insert(data, "");
+            }
+            EditAction::Delete | EditAction::DeleteWordForward if !read_only && !has_selection => {
+                self.anchor = match action {
+                    EditAction::Delete => (self.caret + 1).min(len),
+                    _ => self.mask.group_end(self.caret).min(len),
+                };
+                self.insert(data, "");
+            }
+            EditAction::Backspace
+            | EditAction::Delete
+            | EditAction::DeleteWordBackward
+            | EditAction::DeleteWordForward
+                if !read_only =>
+            {
+                self.insert(data, "")
+            }
+            EditAction::Move(movement) => {
+                let to = match movement {
+                    Movement::Left if has_selection => self.selection().start,
+                    Movement::Right if has_selection => self.selection().end,
+                    movement => self.slot_for_movement(movement, len),
+                };
+                self.caret_to(to);
+            }
+            EditAction::ModifySelection(movement) => {
+                self.caret = self.slot_for_movement(movement, len);
+            }
+            EditAction::SelectAll => {
+                self.anchor = 0;
+                self.caret = len;
+            }
+            EditAction::Copy => self.copy(data),
+            EditAction::Cut => {
+                self.copy(data);
+                if !read_only && has_selection {
+                    self.insert(data, "");
+                }
+            }
+            EditAction::Paste if !read_only => {
+                if let Some(string) = Application::clipboard().get_string() {
+                    self.insert(data, &string);
+                }
+            }
+            _ => (),
+        }
+        self.reset_cursor_blink(ctx);
+    }
+
+    fn reset_cursor_blink(&mut self, ctx: &mut EventCtx) {
+        self.cursor_on = true;
+        let deadline = Instant::now() + Duration::from_millis(500);
+        self.cursor_timer = ctx.request_timer(deadline);
+    }
+}
+
+impl Widget<String> for MaskedTextBox {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut String, env: &Env) {
+        // Guard against external changes in data.
+        let len = data.chars().count();
+        self.anchor = self.anchor.min(len);
+        self.caret = self.caret.min(len);
+        let read_only = env.get(theme::READ_ONLY);
+
+        match event {
+            Event::MouseDown(mouse) => {
+                ctx.request_focus();
+                ctx.set_active(true);
+                let (text, offsets) = self.mask.display_with_offsets(data, self.placeholder);
+                let layout = self.get_layout(ctx.text(), &text, env);
+                let slot = self.slot_for_point(mouse.pos, &layout, &offsets, len, env);
+                if mouse.mods.shift {
+                    self.caret = slot;
+                } else {
+                    self.caret_to(slot);
+                }
+                ctx.invalidate();
+                self.reset_cursor_blink(ctx);
+            }
+            Event::MouseMoved(mouse) => {
+                ctx.set_cursor(&Cursor::IBeam);
+                if ctx.is_active() {
+                    let (text, offsets) = self.mask.display_with_offsets(data, self.placeholder);
+                    let layout = self.get_layout(ctx.text(), &text, env);
+                    self.caret = self.slot_for_point(mouse.pos, &layout, &offsets, len, env);
+                    ctx.invalidate();
+                }
+            }
+            Event::MouseUp(_) => {
+                if ctx.is_active() {
+                    ctx.set_active(false);
+                    ctx.invalidate();
+                }
+            }
+            Event::Timer(id) => {
+                if *id == self.cursor_timer {
+                    self.cursor_on = !self.cursor_on;
+                    ctx.invalidate();
+                    let deadline = Instant::now() + Duration::from_millis(500);
+                    self.cursor_timer = ctx.request_timer(deadline);
+                }
+            }
+            Event::ValidateCommand(selector) => {
+                if self.can_handle_command(selector, read_only) {
+                    ctx.set_handled();
+                }
+            }
+            Event::Command(ref cmd)
+                if ctx.has_focus() && self.can_handle_command(&cmd.selector, read_only) =>
+            {
+                if let Some(action) = Self::action_for_command(&cmd.selector) {
+                    self.do_edit_action(ctx, action, data, read_only);
+                    ctx.invalidate();
+                    ctx.set_handled();
+                }
+            }
+            Event::Command(ref cmd) if ctx.has_focus() && cmd.selector == commands::EDIT_ACTION => {
+                if let Some(action) = cmd.get_object::<EditAction>() {
+                    self.do_edit_action(ctx, action.clone(), data, read_only);
+                    ctx.invalidate();
+                }
+                ctx.set_handled();
+            }
+            Event::Paste(ref item) if !read_only => {
+                if let Some(string) = item.get_string() {
+                    self.insert(data, &string);
+                    self.reset_cursor_blink(ctx);
+                    ctx.invalidate();
+                }
+            }
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
+            Event::KeyDown(key_event)
+                if key_event.key_code == KeyCode::Tab
+                    && self.keymap.action_for(key_event).is_none() =>
+            {
+                if key_event.mods.shift {
+                    ctx.focus_prev();
+                } else {
+                    ctx.focus_next();
+                }
+                ctx.set_handled();
+            }
+            Event::KeyDown(key_event) => {
+                let action = match self.keymap.action_for(key_event) {
+                    Some(action) => Some(action),
+                    None if key_event.key_code.is_printable() => key_event
+                        .text()
+                        .map(|text| EditAction::Insert(text.to_owned())),
+                    None => None,
+                };
+                if let Some(action) = action {
+                    self.do_edit_action(ctx, action, data, read_only);
+                }
+                ctx.invalidate();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: Option<&String>,
+        _data: &String,
+        _env: &Env,
+    ) {
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &String,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("MaskedTextBox");
+
+        self.width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            100.0
+        };
+        bc.constrain((self.width, env.get(theme::BORDERED_WIDGET_HEIGHT)))
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        base_state: &BaseState,
+        data: &String,
+        env: &Env,
+    ) {
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let height = env.get(theme::BORDERED_WIDGET_HEIGHT);
+        let background_color = if env.get(theme::READ_ONLY) {
+            env.get(theme::BACKGROUND_DARK)
+        } else {
+            env.get(theme::BACKGROUND_LIGHT)
+        };
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else {
+            env.get(theme::BORDER)
+        };
+
+        let shape = RoundedRect::from_origin_size(
+            Point::ORIGIN,
+            Size::new(self.width - 1.0, height).to_vec2(),
+            2.,
+        );
+        paint_ctx.fill(shape, &background_color);
+
+        let len = data.chars().count();
+        let (text, offsets) = self.mask.display_with_offsets(data, self.placeholder);
+        // The filled part is drawn over the whole, placeholders and all.
+        let filled_end = if len == 0 { 0 } else { offsets[len - 1] + 1 };
+        let filled_end = (filled_end..=text.len())
+            .find(|end| text.is_char_boundary(*end))
+            .unwrap_or_else(|| text.len());
+        let layout = self.get_layout(paint_ctx.text(), &text, env);
+        let filled_layout = self.get_layout(paint_ctx.text(), &text[..filled_end], env);
+        let x_for_slot = |slot: usize| {
+            layout
+                .hit_test_text_position(offsets[slot.min(len)])
+                .map_or(0.0, |hit| hit.point.x)
+        };
+
+        let selection = self.selection();
+        let caret_x = x_for_slot(self.caret);
+        let selection_x = (x_for_slot(selection.start), x_for_slot(selection.end));
+        let padding_left = theme::spacing(env, PADDING_LEFT);
+        let padding_top = theme::spacing(env, PADDING_TOP);
+        let text_pos = Point::new(padding_left, font_size * 0.8 + padding_top);
+        let has_focus = base_state.has_focus();
+        let cursor_on = self.cursor_on;
+        paint_ctx.with_save(|rc| {
+            rc.clip(shape);
+            if selection.start != selection.end {
+                let rect = RoundedRect::from_origin_size(
+                    Point::new(selection_x.0 + padding_left - 1., padding_top - 2.),
+                    Size::new(selection_x.1 - selection_x.0 + 2., font_size + 4.).to_vec2(),
+                    1.,
+                );
+                rc.fill(rect, &env.get(theme::SELECTION_COLOR));
+            }
+            rc.draw_text(&layout, text_pos, &env.get(theme::PLACEHOLDER_COLOR));
+            rc.draw_text(&filled_layout, text_pos, &env.get(theme::LABEL_COLOR));
+
+            if has_focus && cursor_on {
+                let x = caret_x + padding_left;
+                let line = Line::new((x, padding_top - 2.), (x, padding_top + font_size + 2.));
+                rc.stroke(line, &env.get(theme::CURSOR_COLOR), 1.);
+            }
+        });
+
+        paint_ctx.stroke(shape, &border_color, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_fills_slots() {
+        let mask = Mask::new("(###) ###-####");
+        assert_eq!(mask.slot_count(), 10);
+        assert_eq!(mask.display("55512", '_'), "(555) 12_-____");
+        assert_eq!(Mask::new(r"\#A").display("x", '_'), "#x");
+    }
+
+    #[test]
+    fn replace_skips_literals_and_rejects() {
+        let mask = Mask::new("##/##/####");
+        let (value, caret) = mask.replace("", 0..0, "12/31/1999");
+        assert_eq!(value, "12311999");
+        assert_eq!(caret, 8);
+        assert!(mask.is_complete(&value));
+
+        let (value, caret) = mask.replace("1231", 1..1, "x9");
+        assert_eq!(value, "19231");
+        assert_eq!(caret, 2);
+
+        // Deleting shifts later characters back, dropping any that no
+        // longer fit their slot.
+        let mask = Mask::new("A#");
+        let (value, _) = mask.replace("a1", 0..1, "");
+        assert_eq!(value, "");
+    }
+
+    #[test]
+    fn groups() {
+        let mask = Mask::new("##/##/####");
+        assert_eq!(mask.group_start(3), 2);
+        assert_eq!(mask.group_start(2), 0);
+        assert_eq!(mask.group_end(0), 2);
+        assert_eq!(mask.group_end(4), 8);
+    }
+}
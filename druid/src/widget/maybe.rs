@@ -0,0 +1,177 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget for optional data.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::widget::SizedBox;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget for an `Option<T>`, which shows one child with the value when
+/// there is one, and another when there isn't.
+///
+/// A new child is built each time the data switches between `Some` and
+/// `None`, so that nothing is kept from the last value, or the last time
+/// there was none. The child being replaced is sent
+/// [`LifeCycle::WidgetRemoved`] before the next event.
+///
+/// This pairs with [`Parse`], whose data is `None` until its text parses.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Label, Maybe};
+///
+/// let price = Maybe::new(
+///     || Label::new(|price: &f64, _env: &_| format!("${:.2}", price)),
+///     || Label::new("No price"),
+/// );
+/// ```
+///
+/// [`LifeCycle::WidgetRemoved`]: ../enum.LifeCycle.html#variant.WidgetRemoved
+/// [`Parse`]: struct.Parse.html
+pub struct Maybe<T> {
+    some_maker: Box<dyn Fn() -> Box<dyn Widget<T>>>,
+    none_maker: Box<dyn Fn() -> Box<dyn Widget<()>>>,
+    /// The child for the current data, once it has been updated.
+    widget: Option<MaybeWidget<T>>,
+    /// Children that were replaced, to be removed on the next event. A
+    /// child for `Some` is kept with the last value it was given.
+    retired: Vec<(MaybeWidget<T>, Option<T>)>,
+}
+
+enum MaybeWidget<T> {
+    Some(WidgetPod<T, Box<dyn Widget<T>>>),
+    None(WidgetPod<(), Box<dyn Widget<()>>>),
+}
+
+impl<T: Data> Maybe<T> {
+    /// Create a widget that shows the child built by `some_maker` when
+    /// there is a value, and the one built by `none_maker` when there isn't.
+    pub fn new<W1, W2>(
+        some_maker: impl Fn() -> W1 + 'static,
+        none_maker: impl Fn() -> W2 + 'static,
+    ) -> Maybe<T>
+    where
+        W1: Widget<T> + 'static,
+        W2: Widget<()> + 'static,
+    {
+        Maybe {
+            some_maker: Box::new(move || Box::new(some_maker())),
+            none_maker: Box::new(move || Box::new(none_maker())),
+            widget: None,
+            retired: Vec::new(),
+        }
+    }
+
+    /// Create a widget that shows the child built by `some_maker` when
+    /// there is a value, and nothing when there isn't.
+    pub fn or_empty<W: Widget<T> + 'static>(some_maker: impl Fn() -> W + 'static) -> Maybe<T> {
+        Maybe::new(some_maker, SizedBox::empty)
+    }
+}
+
+impl<T: Data> Widget<Option<T>> for Maybe<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Option<T>, env: &Env) {
+        let removed = Event::LifeCycle(LifeCycle::WidgetRemoved);
+        for (widget, mut last) in self.retired.drain(..) {
+            match (widget, &mut last) {
+                (MaybeWidget::Some(mut child), Some(value)) => {
+                    child.event(ctx, &removed, value, env)
+                }
+                (MaybeWidget::None(mut child), _) => child.event(ctx, &removed, &mut (), env),
+                // A child that was never given a value has nothing to remove.
+                (MaybeWidget::Some(_), None) => (),
+            }
+        }
+
+        // The data only switches between `Some` and `None` in update; until
+        // then, the child doesn't match it, and isn't given events.
+        match (&mut self.widget, data) {
+            (Some(MaybeWidget::Some(child)), Some(value)) => child.event(ctx, event, value, env),
+            (Some(MaybeWidget::None(child)), None) => child.event(ctx, event, &mut (), env),
+            _ => (),
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: Option<&Option<T>>,
+        data: &Option<T>,
+        env: &Env,
+    ) {
+        let matches = match (&self.widget, data) {
+            (Some(MaybeWidget::Some(_)), Some(_)) | (Some(MaybeWidget::None(_)), None) => true,
+            _ => false,
+        };
+        if !matches {
+            if let Some(old) = self.widget.take() {
+                let last = old_data.and_then(|old| old.clone());
+                self.retired.push((old, last));
+            }
+            self.widget = Some(match data {
+                Some(_) => MaybeWidget::Some(WidgetPod::new((self.some_maker)())),
+                None => MaybeWidget::None(WidgetPod::new((self.none_maker)())),
+            });
+            ctx.invalidate();
+        }
+        match (&mut self.widget, data) {
+            (Some(MaybeWidget::Some(child)), Some(value)) => child.update(ctx, value, env),
+            (Some(MaybeWidget::None(child)), None) => child.update(ctx, &(), env),
+            _ => unreachable!(),
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Option<T>,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Maybe");
+
+        match (&mut self.widget, data) {
+            (Some(MaybeWidget::Some(child)), Some(value)) => {
+                let size = child.layout(layout_ctx, bc, value, env);
+                child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+                size
+            }
+            (Some(MaybeWidget::None(child)), None) => {
+                let size = child.layout(layout_ctx, bc, &(), env);
+                child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+                size
+            }
+            _ => bc.min(),
+        }
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        _base_state: &BaseState,
+        data: &Option<T>,
+        env: &Env,
+    ) {
+        match (&mut self.widget, data) {
+            (Some(MaybeWidget::Some(child)), Some(value)) => child.paint(paint_ctx, value, env),
+            (Some(MaybeWidget::None(child)), None) => child.paint(paint_ctx, &(), env),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,774 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A menu bar drawn by druid, rather than by the platform.
+
+use std::collections::HashMap;
+
+use crate::kurbo::{Line, Point, Rect, Size};
+use crate::menu::MenuEntry;
+use crate::mnemonic::{self, Mnemonic};
+use crate::piet::{Color, FontBuilder, RenderContext, Text, TextLayout, TextLayoutBuilder};
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, KeyCode, KeyEvent, LayoutCtx, LifeCycle,
+    MenuDesc, PaintCtx, UpdateCtx, Widget, WidgetPod,
+};
+
+/// The padding at either end of a title in the bar.
+const BAR_PADDING: f64 = 8.0;
+/// The height of an item in a menu.
+const ROW_HEIGHT: f64 = 22.0;
+/// The height of a separator in a menu.
+const SEPARATOR_HEIGHT: f64 = 7.0;
+/// The padding above the first and below the last item of a menu.
+const POPUP_PADDING: f64 = 4.0;
+/// The space at either end of an item, for its check mark and submenu arrow.
+const GUTTER: f64 = 22.0;
+/// The z-index of open menus.
+const POPUP_Z_INDEX: i32 = 2;
+
+/// A menu bar at the top of a window, drawn and handled by druid.
+///
+/// This is an alternative to the native window menu, for platforms or
+/// styles where it isn't wanted. It is built from the same [`MenuDesc`],
+/// whose submenus are shown in the bar; choosing an item submits its
+/// command to the window, as the native menu does.
+///
+/// The bar is shown above its child, and is meant to be the root widget of
+/// the window: keys reach it when no widget has focus, or when the focused
+/// widget is inside it.
+///
+/// # Keyboard
+///
+/// Pressing and releasing alt selects the first menu; alt and a title's
+/// [mnemonic] opens that menu. A title without an `&` marker uses its first
+/// letter or digit. While the bar is active, the arrow keys move between
+/// menus and items, enter or space chooses an item, a letter chooses the
+/// item it underlines, and escape closes the innermost menu.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Label, MenuBar};
+/// use druid::{commands, LocalizedString, MenuDesc, MenuItem};
+///
+/// let file = MenuDesc::new(LocalizedString::new("File")).append(MenuItem::new(
+///     LocalizedString::new("Quit"),
+///     commands::QUIT_APP,
+/// ));
+/// let root = MenuBar::new(MenuDesc::empty().append(file), Label::new("Content"));
+/// # let _: MenuBar<u32> = root;
+/// ```
+///
+/// [`MenuDesc`]: ../struct.MenuDesc.html
+/// [mnemonic]: struct.Label.html#mnemonics
+pub struct MenuBar<T: Data> {
+    menu: MenuDesc<T>,
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    size: Size,
+    bar_height: f64,
+    /// The rectangles of the entries of the bar.
+    titles: Vec<Rect>,
+    /// The widths of the menus, by the path of their entries.
+    popup_widths: HashMap<Vec<usize>, f64>,
+    /// The highlighted entry of the bar, then of each open menu.
+    ///
+    /// The bar is active while this isn't empty.
+    path: Vec<usize>,
+    /// The number of open menus.
+    open: usize,
+    /// Whether alt has been pressed, with no other key since.
+    alt_alone: bool,
+    /// Whether mnemonics are underlined.
+    show_mnemonics: bool,
+}
+
+impl<T: Data> MenuBar<T> {
+    /// Create a new `MenuBar` showing the entries of `menu` above `child`.
+    pub fn new(menu: MenuDesc<T>, child: impl Widget<T> + 'static) -> Self {
+        MenuBar {
+            menu,
+            child: WidgetPod::new(child).boxed(),
+            size: Size::ZERO,
+            bar_height: 0.0,
+            titles: Vec::new(),
+            popup_widths: HashMap::new(),
+            path: Vec::new(),
+            open: 0,
+            alt_alone: false,
+            show_mnemonics: false,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    /// The entries shown at a level: the bar for level 0, and the open menus
+    /// after it.
+    fn entries(&self, level: usize) -> &[MenuEntry<T>] {
+        let mut menu = &self.menu;
+        for &index in self.path.iter().take(level) {
+            match menu.entries().get(index) {
+                Some(MenuEntry::SubMenu(submenu)) => menu = submenu,
+                _ => return &[],
+            }
+        }
+        menu.entries()
+    }
+
+    /// The rectangle of an open menu, for levels from 1.
+    fn popup_rect(&self, level: usize) -> Rect {
+        let width = self
+            .popup_widths
+            .get(&self.path[..level])
+            .cloned()
+            .unwrap_or(0.0);
+        let height: f64 = self.entries(level).iter().map(entry_height).sum();
+        let height = height + 2.0 * POPUP_PADDING;
+        if level == 1 {
+            let title_x = self.titles.get(self.path[0]).map_or(0.0, |title| title.x0);
+            let x = title_x.min((self.size.width - width).max(0.0));
+            return Rect::from_origin_size((x, self.bar_height), Size::new(width, height));
+        }
+        let parent = self.popup_rect(level - 1);
+        let offset: f64 = self.entries(level - 1)[..self.path[level - 1]]
+            .iter()
+            .map(entry_height)
+            .sum();
+        let y = parent.y0 + offset;
+        // Open to the left when there is no room to the right.
+        let x = if parent.x1 + width > self.size.width {
+            (parent.x0 - width).max(0.0)
+        } else {
+            parent.x1
+        };
+        Rect::from_origin_size((x, y), Size::new(width, height))
+    }
+
+    /// The rectangles of the entries of an open menu.
+    fn row_rects(&self, level: usize) -> Vec<Rect> {
+        let popup = self.popup_rect(level);
+        let mut y = popup.y0 + POPUP_PADDING;
+        self.entries(level)
+            .iter()
+            .map(|entry| {
+                let height = entry_height(entry);
+                let rect = Rect::new(popup.x0, y, popup.x1, y + height);
+                y += height;
+                rect
+            })
+            .collect()
+    }
+
+    /// The level and entry under a point, if it is on the bar or an open menu.
+    fn hit(&self, pos: Point) -> Option<(usize, Option<usize>)> {
+        for level in (1..=self.open).rev() {
+            if self.popup_rect(level).contains(pos) {
+                let rows = self.row_rects(level);
+                let index = rows.iter().position(|row| row.contains(pos));
+                let index = index.filter(|&i| !is_separator(&self.entries(level)[i]));
+                return Some((level, index));
+            }
+        }
+        if pos.y < self.bar_height {
+            let index = self.titles.iter().position(|title| title.contains(pos));
+            return Some((0, index.filter(|&i| !is_separator(&self.entries(0)[i]))));
+        }
+        None
+    }
+
+    /// Highlight an entry, opening it if it is a submenu.
+    ///
+    /// With `select_first`, the first entry of the submenu is highlighted,
+    /// as when it is opened from the keyboard.
+    fn highlight(&mut self, level: usize, index: usize, select_first: bool) {
+        self.path.truncate(level);
+        self.path.push(index);
+        self.open = level;
+        let first = match self.entries(level).get(index) {
+            Some(MenuEntry::SubMenu(menu)) if menu.is_enabled() => step(menu.entries(), None, true),
+            _ => return,
+        };
+        self.open = level + 1;
+        if let Some(first) = first.filter(|_| select_first) {
+            self.path.push(first);
+        }
+    }
+
+    /// Choose an entry: submit an item's command, or open a submenu.
+    fn choose(&mut self, ctx: &mut EventCtx, level: usize, index: usize, from_keyboard: bool) {
+        match self.entries(level).get(index) {
+            Some(MenuEntry::Item(item)) => {
+                if item.is_enabled() {
+                    ctx.submit_command(item.command().clone(), None);
+                    self.close();
+                }
+            }
+            Some(MenuEntry::SubMenu(_)) => self.highlight(level, index, from_keyboard),
+            _ => (),
+        }
+    }
+
+    fn close(&mut self) {
+        self.path.clear();
+        self.open = 0;
+    }
+
+    /// Move the highlight of the bar, keeping its menu open if one is.
+    fn move_in_bar(&mut self, forward: bool) {
+        let from = self.path.first().cloned();
+        if let Some(index) = step(self.entries(0), from, forward) {
+            if self.open > 0 {
+                self.highlight(0, index, true);
+            } else {
+                self.path = vec![index];
+            }
+        }
+    }
+
+    /// Handle alt and mnemonics. Alt pressed and released alone activates
+    /// the bar, or closes it.
+    ///
+    /// Returns `true` if the event was handled.
+    fn alt_key(&mut self, ctx: &mut EventCtx, event: &Event) -> bool {
+        match event {
+            Event::LifeCycle(LifeCycle::ShowMnemonics(show)) => {
+                self.show_mnemonics = *show;
+                ctx.invalidate();
+                false
+            }
+            Event::KeyDown(key) => {
+                self.alt_alone = mnemonic::is_alt(key) && (self.alt_alone || !key.is_repeat);
+                false
+            }
+            Event::Mnemonic(c) => {
+                self.alt_alone = false;
+                let level = self.open;
+                match find_mnemonic(self.entries(level), *c) {
+                    Some(index) => {
+                        self.choose(ctx, level, index, true);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Event::KeyUp(key) if mnemonic::ENABLED && mnemonic::is_alt(key) => {
+                let alone = self.alt_alone;
+                self.alt_alone = false;
+                if alone && self.is_active() {
+                    self.close();
+                } else if alone {
+                    if let Some(first) = step(self.entries(0), None, true) {
+                        self.path = vec![first];
+                        self.open = 0;
+                    }
+                }
+                alone
+            }
+            _ => false,
+        }
+    }
+
+    fn key_down(&mut self, ctx: &mut EventCtx, key: &KeyEvent) {
+        let level = self.open;
+        match key.key_code {
+            KeyCode::Escape if self.open > 0 => {
+                self.open -= 1;
+                self.path.truncate(self.open + 1);
+            }
+            KeyCode::Escape => self.close(),
+            KeyCode::ArrowLeft if self.open > 1 => {
+                self.open -= 1;
+                self.path.truncate(self.open + 1);
+            }
+            KeyCode::ArrowLeft => self.move_in_bar(false),
+            KeyCode::ArrowRight => {
+                let submenu = match self.path.get(level) {
+                    Some(&index) if level > 0 => match self.entries(level).get(index) {
+                        Some(MenuEntry::SubMenu(menu)) if menu.is_enabled() => Some(index),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match submenu {
+                    Some(index) => self.highlight(level, index, true),
+                    None => self.move_in_bar(true),
+                }
+            }
+            KeyCode::ArrowDown | KeyCode::ArrowUp => {
+                let forward = key.key_code == KeyCode::ArrowDown;
+                if level == 0 {
+                    self.highlight(0, self.path[0], forward);
+                    if !forward && self.open > 0 {
+                        if let Some(last) = step(self.entries(1), None, false) {
+                            self.path.push(last);
+                        }
+                    }
+                } else {
+                    let from = self.path.get(level).cloned();
+                    if let Some(index) = step(self.entries(level), from, forward) {
+                        self.path.truncate(level);
+                        self.path.push(index);
+                    }
+                }
+            }
+            KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space => {
+                // The deepest highlighted entry: the bar's, or an open menu's.
+                let level = self.path.len() - 1;
+                self.choose(ctx, level, self.path[level], true);
+            }
+            _ => {
+                let entries = self.entries(level);
+                if let Some(index) = key_char(key).and_then(|c| find_mnemonic(entries, c)) {
+                    self.choose(ctx, level, index, true);
+                }
+            }
+        }
+    }
+
+    fn mouse_down(&mut self, ctx: &mut EventCtx, pos: Point) -> bool {
+        match self.hit(pos) {
+            Some((0, Some(index))) if self.open > 0 && self.path[0] == index => self.close(),
+            Some((level, Some(index))) => self.choose(ctx, level, index, false),
+            Some((_, None)) if self.is_active() => (),
+            None if self.is_active() => self.close(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn mouse_moved(&mut self, pos: Point) {
+        match self.hit(pos) {
+            Some((0, Some(index))) if self.path[0] != index => self.highlight(0, index, false),
+            Some((level, Some(index))) if level > 0 && self.path.get(level) != Some(&index) => {
+                self.highlight(level, index, false)
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for MenuBar<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let handled = self.alt_key(ctx, event)
+            || match event {
+                Event::KeyDown(key) if self.is_active() => {
+                    self.key_down(ctx, key);
+                    true
+                }
+                Event::KeyUp(_) if self.is_active() => true,
+                Event::MouseDown(mouse) => self.mouse_down(ctx, mouse.pos),
+                Event::MouseMoved(mouse) if self.open > 0 => {
+                    self.mouse_moved(mouse.pos);
+                    true
+                }
+                Event::MouseUp(_) | Event::Wheel(_) if self.open > 0 => true,
+                Event::LifeCycle(LifeCycle::WidgetAdded) => {
+                    self.menu.resolve_titles(data, env);
+                    false
+                }
+                _ => false,
+            };
+        if handled {
+            // Capture the mouse while a menu is open, so a click anywhere
+            // closes it.
+            ctx.set_active(self.open > 0);
+            ctx.set_handled();
+            ctx.invalidate();
+        } else {
+            self.child.event(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        if self.menu.resolve_titles(data, env) {
+            ctx.invalidate();
+        }
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("MenuBar");
+
+        self.bar_height = env.get(theme::BORDERED_WIDGET_HEIGHT);
+        let font = layout_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), env.get(theme::TEXT_SIZE_NORMAL))
+            .build()
+            .unwrap();
+        let text = layout_ctx.text();
+        let mut width_of = |title: &str| {
+            text.new_text_layout(&font, &mnemonic::parse(title).0)
+                .build()
+                .map(|layout| layout.width())
+                .unwrap_or(0.0)
+        };
+
+        self.titles.clear();
+        let mut x = 0.0;
+        for entry in self.menu.entries() {
+            let width = match entry_title(entry) {
+                Some(title) => width_of(title) + 2.0 * BAR_PADDING,
+                None => BAR_PADDING,
+            };
+            self.titles
+                .push(Rect::new(x, 0.0, x + width, self.bar_height));
+            x += width;
+        }
+        self.popup_widths.clear();
+        measure_popups(
+            self.menu.entries(),
+            &mut Vec::new(),
+            &mut width_of,
+            &mut self.popup_widths,
+        );
+
+        let child_bc = bc.shrink((0.0, self.bar_height));
+        let child_size = self.child.layout(layout_ctx, &child_bc, data, env);
+        let origin = Point::new(0.0, self.bar_height);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(origin, child_size));
+
+        let width = child_size.width.max(x);
+        self.size = bc.constrain(Size::new(width, child_size.height + self.bar_height));
+        self.size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint_with_offset(paint_ctx, data, env);
+
+        let style = Style {
+            font_name: env.get(theme::FONT_NAME).to_string(),
+            font_size: env.get(theme::TEXT_SIZE_NORMAL),
+            text: env.get(theme::LABEL_COLOR),
+            disabled: env.get(theme::PLACEHOLDER_COLOR),
+            highlight: env.get(theme::PRIMARY_DARK),
+            background: env.get(theme::BACKGROUND_LIGHT),
+            border: env.get(theme::BORDER_LIGHT),
+            show_mnemonics: mnemonic::ENABLED && (self.show_mnemonics || self.is_active()),
+        };
+
+        let bar = Rect::new(0.0, 0.0, self.size.width, self.bar_height);
+        paint_ctx.fill(bar, &style.background);
+        let bottom = self.bar_height - 0.5;
+        paint_ctx.stroke(
+            Line::new((0.0, bottom), (self.size.width, bottom)),
+            &style.border,
+            1.0,
+        );
+        for (i, (entry, rect)) in self.menu.entries().iter().zip(&self.titles).enumerate() {
+            let title = match entry_title(entry) {
+                Some(title) => title,
+                None => continue,
+            };
+            if self.path.first() == Some(&i) {
+                paint_ctx.fill(*rect, &style.highlight);
+            }
+            let color = if entry_enabled(entry) {
+                &style.text
+            } else {
+                &style.disabled
+            };
+            style.draw_title(paint_ctx, title, rect.x0 + BAR_PADDING, rect, color);
+        }
+
+        // The open menus are drawn above the rest of the window.
+        let popups: Vec<Popup> = (1..=self.open)
+            .map(|level| {
+                let highlighted = self.path.get(level).cloned();
+                let rows = self
+                    .entries(level)
+                    .iter()
+                    .zip(self.row_rects(level))
+                    .enumerate()
+                    .map(|(i, (entry, rect))| Row {
+                        rect,
+                        title: entry_title(entry).map(String::from),
+                        enabled: entry_enabled(entry),
+                        checked: match entry {
+                            MenuEntry::Item(item) => item.is_selected(),
+                            _ => false,
+                        },
+                        submenu: match entry {
+                            MenuEntry::SubMenu(_) => true,
+                            _ => false,
+                        },
+                        highlighted: highlighted == Some(i),
+                    })
+                    .collect();
+                Popup {
+                    rect: self.popup_rect(level),
+                    rows,
+                }
+            })
+            .collect();
+        if !popups.is_empty() {
+            paint_ctx.paint_overlay(POPUP_Z_INDEX, move |ctx| {
+                for popup in popups {
+                    style.draw_popup(ctx, popup);
+                }
+            });
+        }
+    }
+}
+
+/// An open menu, ready to paint.
+struct Popup {
+    rect: Rect,
+    rows: Vec<Row>,
+}
+
+/// An entry of an open menu; separators have no title.
+struct Row {
+    rect: Rect,
+    title: Option<String>,
+    enabled: bool,
+    checked: bool,
+    submenu: bool,
+    highlighted: bool,
+}
+
+/// The theme values used to paint the bar and its menus.
+struct Style {
+    font_name: String,
+    font_size: f64,
+    text: Color,
+    disabled: Color,
+    highlight: Color,
+    background: Color,
+    border: Color,
+    show_mnemonics: bool,
+}
+
+impl Style {
+    /// Draw a title, vertically centered in `rect`, underlining its
+    /// mnemonic if they are shown.
+    fn draw_title(&self, ctx: &mut PaintCtx, title: &str, x: f64, rect: &Rect, color: &Color) {
+        let font = ctx
+            .text()
+            .new_font_by_name(&self.font_name, self.font_size)
+            .build()
+            .unwrap();
+        let (text, mnemonic) = title_mnemonic(title);
+        let layout = ctx.text().new_text_layout(&font, &text).build().unwrap();
+        let baseline = rect.y0 + (rect.height() + self.font_size) / 2.0 - 2.0;
+        ctx.draw_text(&layout, (x, baseline), color);
+
+        if let Some(mnemonic) = mnemonic.filter(|_| self.show_mnemonics) {
+            let range = mnemonic.range(&text);
+            let mut width_of = |text: &str| {
+                ctx.text()
+                    .new_text_layout(&font, text)
+                    .build()
+                    .map(|layout| layout.width())
+                    .unwrap_or(0.0)
+            };
+            let x0 = x + width_of(&text[..range.start]);
+            let x1 = x + width_of(&text[..range.end]);
+            let y = baseline + 2.0;
+            ctx.stroke(Line::new((x0, y), (x1, y)), color, 1.0);
+        }
+    }
+
+    fn draw_popup(&self, ctx: &mut PaintCtx, popup: Popup) {
+        ctx.fill(popup.rect, &self.background);
+        for row in popup.rows {
+            let rect = row.rect;
+            let title = match row.title {
+                Some(title) => title,
+                None => {
+                    let y = rect.y0 + rect.height() / 2.0;
+                    let line = Line::new((rect.x0 + 4.0, y), (rect.x1 - 4.0, y));
+                    ctx.stroke(line, &self.border, 1.0);
+                    continue;
+                }
+            };
+            if row.highlighted {
+                ctx.fill(rect, &self.highlight);
+            }
+            let color = if row.enabled {
+                &self.text
+            } else {
+                &self.disabled
+            };
+            if row.checked {
+                self.draw_title(ctx, "✓", rect.x0 + 6.0, &rect, color);
+            }
+            if row.submenu {
+                self.draw_title(ctx, "▸", rect.x1 - GUTTER + 6.0, &rect, color);
+            }
+            self.draw_title(ctx, &title, rect.x0 + GUTTER, &rect, color);
+        }
+        let border = Rect::new(
+            popup.rect.x0 + 0.5,
+            popup.rect.y0 + 0.5,
+            popup.rect.x1 - 0.5,
+            popup.rect.y1 - 0.5,
+        );
+        ctx.stroke(border, &self.border, 1.0);
+    }
+}
+
+/// Record the width of the menus of `entries`, and of their submenus.
+fn measure_popups<T: Data>(
+    entries: &[MenuEntry<T>],
+    path: &mut Vec<usize>,
+    width_of: &mut dyn FnMut(&str) -> f64,
+    widths: &mut HashMap<Vec<usize>, f64>,
+) {
+    for (i, entry) in entries.iter().enumerate() {
+        if let MenuEntry::SubMenu(menu) = entry {
+            path.push(i);
+            let width = menu
+                .entries()
+                .iter()
+                .filter_map(entry_title)
+                .map(|title| width_of(title))
+                .fold(0.0, f64::max);
+            widths.insert(path.clone(), width + 2.0 * GUTTER);
+            measure_popups(menu.entries(), path, width_of, widths);
+            path.pop();
+        }
+    }
+}
+
+fn entry_title<T: Data>(entry: &MenuEntry<T>) -> Option<&str> {
+    match entry {
+        MenuEntry::Item(item) => Some(item.title()),
+        MenuEntry::SubMenu(menu) => Some(menu.title()),
+        MenuEntry::Separator => None,
+    }
+}
+
+fn entry_enabled<T: Data>(entry: &MenuEntry<T>) -> bool {
+    match entry {
+        MenuEntry::Item(item) => item.is_enabled(),
+        MenuEntry::SubMenu(menu) => menu.is_enabled(),
+        MenuEntry::Separator => false,
+    }
+}
+
+fn entry_height<T>(entry: &MenuEntry<T>) -> f64 {
+    if is_separator(entry) {
+        SEPARATOR_HEIGHT
+    } else {
+        ROW_HEIGHT
+    }
+}
+
+fn is_separator<T>(entry: &MenuEntry<T>) -> bool {
+    match entry {
+        MenuEntry::Separator => true,
+        _ => false,
+    }
+}
+
+/// The lowercase character a key types, ignoring modifiers.
+fn key_char(key: &KeyEvent) -> Option<char> {
+    let c = key.unmod_text()?.chars().next()?;
+    c.to_lowercase().next()
+}
+
+/// The text to display for a title, and its mnemonic: the marked
+/// character, or else the first letter or digit.
+fn title_mnemonic(title: &str) -> (String, Option<Mnemonic>) {
+    let (text, mnemonic) = mnemonic::parse(title);
+    let mnemonic = mnemonic.or_else(|| {
+        let (offset, c) = text.char_indices().find(|(_, c)| c.is_alphanumeric())?;
+        let key = c.to_lowercase().next()?;
+        Some(Mnemonic { offset, key })
+    });
+    (text, mnemonic)
+}
+
+/// The first entry whose mnemonic is `c`.
+fn find_mnemonic<T: Data>(entries: &[MenuEntry<T>], c: char) -> Option<usize> {
+    entries.iter().position(|entry| {
+        entry_title(entry)
+            .and_then(|title| title_mnemonic(title).1)
+            .map_or(false, |mnemonic| mnemonic.key == c)
+    })
+}
+
+/// The entry after (or before) `from`, skipping separators and wrapping
+/// around. With no `from`, the first (or last) entry.
+fn step<T>(entries: &[MenuEntry<T>], from: Option<usize>, forward: bool) -> Option<usize> {
+    let len = entries.len();
+    let candidates = (1..=len).map(|offset| match (from, forward) {
+        (Some(from), true) => (from + offset) % len,
+        (Some(from), false) => (from + len - offset % len) % len,
+        (None, true) => offset - 1,
+        (None, false) => len - offset,
+    });
+    for index in candidates {
+        if !is_separator(&entries[index]) {
+            return Some(index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LocalizedString, MenuItem, Selector};
+
+    fn item(title: &'static str) -> MenuEntry<u32> {
+        MenuItem::new(LocalizedString::new(title), Selector::NOOP).into()
+    }
+
+    #[test]
+    fn step_skips_separators() {
+        let entries = vec![
+            item("Open"),
+            MenuEntry::Separator,
+            item("Save"),
+            item("Quit"),
+        ];
+        assert_eq!(step(&entries, None, true), Some(0));
+        assert_eq!(step(&entries, None, false), Some(3));
+        assert_eq!(step(&entries, Some(0), true), Some(2));
+        assert_eq!(step(&entries, Some(2), false), Some(0));
+        assert_eq!(step(&entries, Some(3), true), Some(0));
+        assert_eq!(step(&entries, Some(0), false), Some(3));
+        assert_eq!(step::<u32>(&[MenuEntry::Separator], None, true), None);
+    }
+
+    #[test]
+    fn mnemonics() {
+        let key = |title| title_mnemonic(title).1.map(|mnemonic| mnemonic.key);
+        assert_eq!(key("File"), Some('f'));
+        assert_eq!(key("…Quit"), Some('q'));
+        assert_eq!(key("E&xit"), Some('x'));
+        assert_eq!(key("--"), None);
+
+        let entries = vec![
+            item("File"),
+            MenuEntry::Separator,
+            item("&Edit"),
+            item("E&xit"),
+        ];
+        assert_eq!(find_mnemonic(&entries, 'e'), Some(2));
+        assert_eq!(find_mnemonic(&entries, 'x'), Some(3));
+        assert_eq!(find_mnemonic(&entries, 'q'), None);
+    }
+}
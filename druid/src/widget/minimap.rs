@@ -0,0 +1,217 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A scroll container with an overview of its contents.
+
+use crate::kurbo::{Affine, Point, Rect, Size, Vec2};
+use crate::piet::{Color, RenderContext};
+use crate::theme;
+use crate::widget::Scroll;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, Raster, UpdateCtx,
+    Widget, WidgetPod,
+};
+
+/// The default width of the overview.
+const DEFAULT_WIDTH: f64 = 120.0;
+
+/// A scroll container with a scaled-down overview of its contents along
+/// its right edge.
+///
+/// The overview shows the whole of the content, scaled to fit, with the
+/// visible part outlined. Clicking or dragging in the overview scrolls the
+/// content to that point.
+///
+/// The overview is a [`Raster`] of the content, which is only painted again
+/// when something in the content changes, or its size does.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Label, Minimap};
+///
+/// let view = Minimap::new(Label::new("A very long document")).width(80.0);
+/// # let _: Minimap<(), _> = view;
+/// ```
+///
+/// [`Raster`]: ../struct.Raster.html
+pub struct Minimap<T: Data, W: Widget<T>> {
+    scroll: WidgetPod<T, Scroll<T, W>>,
+    width: f64,
+    dragging: bool,
+    /// The scaled-down content, and the content's count of changes when it
+    /// was painted.
+    snapshot: Option<(Raster, u64)>,
+}
+
+impl<T: Data, W: Widget<T>> Minimap<T, W> {
+    /// Create a new `Minimap`, scrolling `child`.
+    pub fn new(child: W) -> Self {
+        Minimap {
+            scroll: WidgetPod::new(Scroll::new(child)),
+            width: DEFAULT_WIDTH,
+            dragging: false,
+            snapshot: None,
+        }
+    }
+
+    /// Builder-style method to set the width of the overview.
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Returns a reference to the scroll container.
+    pub fn scroll(&self) -> &Scroll<T, W> {
+        self.scroll.widget()
+    }
+
+    /// Returns a mutable reference to the scroll container.
+    pub fn scroll_mut(&mut self) -> &mut Scroll<T, W> {
+        self.scroll.widget_mut()
+    }
+
+    /// The area of the overview, for a widget of this size.
+    fn overview_rect(&self, size: Size) -> Rect {
+        let width = self.width.min(size.width);
+        Rect::new(size.width - width, 0.0, size.width, size.height)
+    }
+
+    /// The scale of the content within the overview.
+    fn overview_scale(&self, overview: Rect) -> f64 {
+        let content = self.scroll.widget().child_size();
+        if content.width <= 0.0 || content.height <= 0.0 {
+            return 1.0;
+        }
+        (overview.width() / content.width)
+            .min(overview.height() / content.height)
+            .min(1.0)
+    }
+
+    /// Scroll so that the viewport is centered on a point in the overview.
+    fn scroll_to(&mut self, ctx: &mut EventCtx, pos: Point) {
+        let overview = self.overview_rect(ctx.size());
+        let viewport = self.scroll.get_layout_rect().size();
+        let target = (pos - overview.origin()) / self.overview_scale(overview);
+        let offset = target - Vec2::new(viewport.width, viewport.height) / 2.0;
+        let delta = offset - self.scroll.widget().offset();
+        if self.scroll.widget_mut().scroll(delta, viewport) {
+            ctx.invalidate();
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Widget<T> for Minimap<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let overview = self.overview_rect(ctx.size());
+        match event {
+            Event::MouseDown(mouse) if overview.contains(mouse.pos) => {
+                self.dragging = true;
+                ctx.set_active(true);
+                self.scroll_to(ctx, mouse.pos);
+                ctx.set_handled();
+                return;
+            }
+            Event::MouseMoved(mouse) if self.dragging => {
+                self.scroll_to(ctx, mouse.pos);
+                ctx.set_handled();
+                return;
+            }
+            Event::MouseUp(_) if self.dragging => {
+                self.dragging = false;
+                ctx.set_active(false);
+                ctx.set_handled();
+                return;
+            }
+            _ => (),
+        }
+        self.scroll.event(ctx, event, data, env);
+        // Keep the outline in step with scrolling done by the container.
+        if let Event::Wheel(_) = event {
+            ctx.invalidate();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.scroll.update(ctx, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Minimap");
+
+        let size = bc.constrain(Size::new(100.0 + self.width, 100.0));
+        let overview = self.overview_rect(size);
+        let scroll_size = Size::new(overview.x0, size.height);
+        let scroll_bc = BoxConstraints::tight(scroll_size);
+        let scroll_size = self.scroll.layout(layout_ctx, &scroll_bc, data, env);
+        self.scroll
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, scroll_size));
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.scroll.paint_with_offset(paint_ctx, data, env);
+
+        let overview = self.overview_rect(base_state.size());
+        let scale = self.overview_scale(overview);
+        let origin = overview.origin().to_vec2();
+        let viewport = self.scroll.get_layout_rect().size();
+        let offset = self.scroll.widget().offset();
+        let content_size = self.scroll.widget().child_size();
+        let snapshot_size = Size::new(content_size.width * scale, content_size.height * scale);
+
+        let content = self.scroll.widget_mut().child_pod();
+        let changes = content.changes();
+        let fresh = match &self.snapshot {
+            Some((raster, seen)) => {
+                *seen == changes && raster.fits(snapshot_size, paint_ctx.scale())
+            }
+            None => false,
+        };
+        if !fresh {
+            self.snapshot = paint_ctx
+                .rasterize(snapshot_size, |ctx| {
+                    ctx.transform_save(Affine::scale(scale), |ctx| {
+                        let region = Rect::from_origin_size(Point::ORIGIN, content_size);
+                        ctx.with_child_ctx(region, |ctx| content.paint(ctx, data, env));
+                    });
+                })
+                .map(|raster| (raster, changes));
+        }
+
+        let snapshot = &self.snapshot;
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(overview);
+            ctx.fill(overview, &env.get(theme::BACKGROUND_DARK));
+            if let Some((raster, _)) = snapshot {
+                raster.draw_in(
+                    ctx,
+                    Rect::from_origin_size(overview.origin(), snapshot_size),
+                );
+            }
+            let outline = Rect::from_origin_size(
+                (offset * scale + origin).to_point(),
+                Size::new(viewport.width * scale, viewport.height * scale),
+            );
+            ctx.fill(outline, &Color::WHITE.with_alpha(0.15));
+            ctx.stroke(outline, &env.get(theme::BORDER_LIGHT), 1.0);
+        });
+    }
+}
@@ -15,54 +15,121 @@
 //! Common widgets.
 
 mod align;
+mod autocomplete;
+mod automation_id;
 mod button;
 mod checkbox;
+mod click;
 mod container;
+mod controller;
 mod either;
 mod env_scope;
+mod error_boundary;
+mod field;
 mod flex;
+mod image;
+mod inspector;
 mod label;
 mod list;
+mod log_view;
+mod masked_textbox;
+mod maybe;
+mod menu_bar;
+mod minimap;
+#[cfg(feature = "node-graph")]
+#[cfg_attr(docsrs, doc(cfg(feature = "node-graph")))]
+mod node_graph;
+mod number_box;
 mod padding;
+mod painter;
 mod parse;
+mod persist;
 mod progress_bar;
 mod radio;
+mod read_only;
+mod responsive;
+mod retained;
+mod ruler;
 mod scroll;
+mod settings;
 mod sized_box;
 mod slider;
 mod split;
+mod stepper;
 #[cfg(feature = "svg")]
 #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
 mod svg;
 mod switch;
+mod table;
+mod text_area;
+mod text_view;
 mod textbox;
+mod timeline;
+mod token_field;
+mod transition;
+mod view_switcher;
 mod widget_ext;
+mod z_order;
+mod zoom_view;
 
 pub use align::Align;
+pub use autocomplete::{AutoComplete, Responder};
+pub use automation_id::AutomationId;
 pub use button::Button;
 pub use checkbox::Checkbox;
+pub use click::Click;
 pub use container::Container;
+pub use controller::{Controller, ControllerHost};
 pub use either::Either;
 pub use env_scope::EnvScope;
-pub use flex::{Column, Flex, Row};
+pub use error_boundary::ErrorBoundary;
+pub use field::{Field, MessagePosition};
+pub use flex::{Column, CrossAxisAlignment, Flex, MainAxisAlignment, Row};
+pub use image::{FillStrat, Image};
+pub use inspector::{Inspect, Inspector, Properties};
 pub use label::{Label, LabelText};
 pub use list::{List, ListIter};
+pub use log_view::{LogBuffer, LogData, LogLine, LogView, Severity};
+pub use masked_textbox::{Mask, MaskedTextBox};
+pub use maybe::Maybe;
+pub use menu_bar::MenuBar;
+pub use minimap::Minimap;
+#[cfg(feature = "node-graph")]
+#[cfg_attr(docsrs, doc(cfg(feature = "node-graph")))]
+pub use node_graph::{Graph, GraphNode, NodeGraph, NodeId, Port, Wire};
+pub use number_box::{NumberBox, NumberFormat, Numeric};
 pub use padding::Padding;
+pub use painter::{BackgroundBrush, Painter};
 pub use parse::Parse;
+pub use persist::Persist;
 pub use progress_bar::ProgressBar;
 pub use radio::{Radio, RadioGroup};
-pub use scroll::Scroll;
+pub use read_only::ReadOnly;
+pub use responsive::Responsive;
+pub use retained::Retained;
+pub use ruler::Ruler;
+pub use scroll::{Overscroll, Scroll};
+pub use settings::Settings;
 pub use sized_box::SizedBox;
 pub use slider::Slider;
 pub use split::Split;
+pub use stepper::Stepper;
 #[cfg(feature = "svg")]
 #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
 pub use svg::{Svg, SvgData};
 pub use switch::Switch;
+pub use table::{Table, TableColumn, TableData};
+pub use text_area::{FindMatches, TextArea};
+pub use text_view::TextView;
 pub use textbox::TextBox;
+pub use timeline::{Clip, Timeline, TimelineData, Track};
+pub use token_field::TokenField;
+pub use transition::{Transition, TransitionKind};
+pub use view_switcher::ViewSwitcher;
 pub use widget_ext::WidgetExt;
+pub use zoom_view::{ZoomState, ZoomView};
 
-use std::ops::DerefMut;
+use std::ops::{Deref, DerefMut};
 
 use crate::kurbo::Size;
 use crate::{BaseState, BoxConstraints, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx};
@@ -168,6 +235,14 @@ pub trait Widget<T> {
     /// afterwards. In addition, they can apply masks and transforms on
     /// the render context, which is especially useful for scrolling.
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env);
+
+    /// The name of the widget's type, used in debugging output.
+    ///
+    /// This does not need to be implemented; the default returns the
+    /// full path of the implementing type.
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 // TODO: explore getting rid of this (ie be consistent about using
@@ -188,4 +263,8 @@ impl<T> Widget<T> for Box<dyn Widget<T>> {
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
         self.deref_mut().paint(paint_ctx, base_state, data, env);
     }
+
+    fn type_name(&self) -> &'static str {
+        self.deref().type_name()
+    }
 }
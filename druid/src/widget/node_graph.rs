@@ -0,0 +1,606 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An editor for graphs of nodes connected by wires.
+
+use std::sync::Arc;
+
+use crate::kurbo::{BezPath, Circle, Point, Rect, RoundedRect, Size, Vec2};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayoutBuilder};
+use crate::theme;
+use crate::widget::ZoomState;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, MouseButton,
+    MouseEvent, PaintCtx, UpdateCtx, Widget,
+};
+
+const NODE_WIDTH: f64 = 140.0;
+const TITLE_HEIGHT: f64 = 24.0;
+const PORT_SPACING: f64 = 20.0;
+const PORT_RADIUS: f64 = 5.0;
+/// How close the pointer must be to a port to hit it, in display points.
+const PORT_HIT_DISTANCE: f64 = 8.0;
+/// How much a wheel delta of one point zooms with Ctrl held.
+const WHEEL_ZOOM_RATE: f64 = 0.002;
+
+/// The identifier of a node in a [`Graph`].
+///
+/// [`Graph`]: struct.Graph.html
+pub type NodeId = u64;
+
+/// A node of a [`Graph`].
+///
+/// [`Graph`]: struct.Graph.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    /// The node's identifier, which must be unique within its graph.
+    pub id: NodeId,
+    /// The title shown at the top of the node.
+    pub title: String,
+    /// The position of the node's top left corner.
+    pub pos: Point,
+    /// The number of input ports, on the left of the node.
+    pub inputs: usize,
+    /// The number of output ports, on the right of the node.
+    pub outputs: usize,
+}
+
+/// One of the ports of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Port {
+    /// The node the port belongs to.
+    pub node: NodeId,
+    /// The index of the port among the node's inputs or outputs.
+    pub index: usize,
+}
+
+/// A wire from an output port to an input port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Wire {
+    /// The output port the wire starts at.
+    pub from: Port,
+    /// The input port the wire ends at.
+    pub to: Port,
+}
+
+/// The data edited by a [`NodeGraph`]: nodes, the wires connecting them,
+/// the selected nodes and the view.
+///
+/// An input port takes at most one wire; an output port can feed any
+/// number.
+///
+/// [`NodeGraph`]: struct.NodeGraph.html
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    /// The nodes, in painting order: later nodes are drawn above earlier
+    /// ones.
+    pub nodes: Arc<Vec<GraphNode>>,
+    /// The wires between nodes.
+    pub wires: Arc<Vec<Wire>>,
+    /// The identifiers of the selected nodes.
+    pub selection: Arc<Vec<NodeId>>,
+    /// The zoom level and pan offset of the editor.
+    pub view: ZoomState,
+}
+
+/// A widget that edits a [`Graph`].
+///
+/// Nodes are dragged by their body, and a node is selected by clicking it;
+/// with Shift held, clicking toggles it in the selection. Dragging from an
+/// empty area selects the nodes within a box. Dragging from an output port
+/// to an input port connects them, and dragging the end of a wire away
+/// from its input port disconnects it. Delete or Backspace removes the
+/// selected nodes.
+///
+/// The wheel pans the view, as does dragging with the middle button; with
+/// Ctrl held, the wheel zooms around the pointer.
+///
+/// All changes are made to the data, so they can be observed, undone or
+/// saved by the application.
+///
+/// This widget is available with the `node-graph` feature.
+///
+/// [`Graph`]: struct.Graph.html
+pub struct NodeGraph {
+    drag: Option<Drag>,
+    min_zoom: f64,
+    max_zoom: f64,
+    last_mouse_pos: Option<Point>,
+}
+
+/// A drag in progress.
+enum Drag {
+    /// Moving the selected nodes; the last pointer position is in content
+    /// coordinates.
+    Nodes { last: Point },
+    /// Connecting a wire from an output port to the pointer.
+    Wire { from: Port, to: Point },
+    /// Selecting the nodes in a box, in addition to those in `base`.
+    Select {
+        start: Point,
+        end: Point,
+        base: Vec<NodeId>,
+    },
+    /// Panning the view; the last pointer position is in view coordinates.
+    Pan { last: Point },
+}
+
+impl GraphNode {
+    /// Create a node with the given identifier, title, position and number
+    /// of ports.
+    pub fn new(
+        id: NodeId,
+        title: impl Into<String>,
+        pos: impl Into<Point>,
+        inputs: usize,
+        outputs: usize,
+    ) -> GraphNode {
+        GraphNode {
+            id,
+            title: title.into(),
+            pos: pos.into(),
+            inputs,
+            outputs,
+        }
+    }
+
+    /// The area the node covers.
+    pub fn rect(&self) -> Rect {
+        let ports = self.inputs.max(self.outputs) as f64;
+        let size = Size::new(NODE_WIDTH, TITLE_HEIGHT + ports * PORT_SPACING + 8.0);
+        Rect::from_origin_size(self.pos, size)
+    }
+
+    /// The position of an input port.
+    pub fn input_pos(&self, index: usize) -> Point {
+        self.pos + Vec2::new(0.0, Self::port_y(index))
+    }
+
+    /// The position of an output port.
+    pub fn output_pos(&self, index: usize) -> Point {
+        self.pos + Vec2::new(NODE_WIDTH, Self::port_y(index))
+    }
+
+    fn port_y(index: usize) -> f64 {
+        TITLE_HEIGHT + PORT_SPACING * (index as f64 + 0.5)
+    }
+}
+
+impl Graph {
+    /// Create an empty graph.
+    pub fn new() -> Graph {
+        Graph::default()
+    }
+
+    /// The node with this identifier, if it exists.
+    pub fn node(&self, id: NodeId) -> Option<&GraphNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Add a node.
+    pub fn add_node(&mut self, node: GraphNode) {
+        Arc::make_mut(&mut self.nodes).push(node);
+    }
+
+    /// Remove nodes, and the wires connected to them.
+    pub fn remove_nodes(&mut self, ids: &[NodeId]) {
+        if ids.is_empty() {
+            return;
+        }
+        Arc::make_mut(&mut self.nodes).retain(|node| !ids.contains(&node.id));
+        Arc::make_mut(&mut self.wires)
+            .retain(|wire| !ids.contains(&wire.from.node) && !ids.contains(&wire.to.node));
+        Arc::make_mut(&mut self.selection).retain(|id| !ids.contains(id));
+    }
+
+    /// Add a wire, replacing any wire already connected to its input port.
+    ///
+    /// Wires from a node to itself, and wires to ports that don't exist,
+    /// are ignored. Returns `true` if the wire was added.
+    pub fn connect(&mut self, wire: Wire) -> bool {
+        let valid = wire.from.node != wire.to.node
+            && self
+                .node(wire.from.node)
+                .map_or(false, |node| wire.from.index < node.outputs)
+            && self
+                .node(wire.to.node)
+                .map_or(false, |node| wire.to.index < node.inputs);
+        if !valid || self.wires.contains(&wire) {
+            return false;
+        }
+        let wires = Arc::make_mut(&mut self.wires);
+        wires.retain(|existing| existing.to != wire.to);
+        wires.push(wire);
+        true
+    }
+
+    /// Remove the wire connected to an input port, returning it.
+    pub fn disconnect(&mut self, to: Port) -> Option<Wire> {
+        let index = self.wires.iter().position(|wire| wire.to == to)?;
+        Some(Arc::make_mut(&mut self.wires).remove(index))
+    }
+
+    /// Returns `true` if the node is selected.
+    pub fn is_selected(&self, id: NodeId) -> bool {
+        self.selection.contains(&id)
+    }
+
+    /// The topmost node at a point.
+    fn node_at(&self, pos: Point) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .rev()
+            .find(|node| node.rect().contains(pos))
+            .map(|node| node.id)
+    }
+
+    /// The output port within `distance` of a point.
+    fn output_at(&self, pos: Point, distance: f64) -> Option<Port> {
+        self.nodes.iter().rev().find_map(|node| {
+            (0..node.outputs)
+                .find(|&index| (node.output_pos(index) - pos).hypot() <= distance)
+                .map(|index| Port {
+                    node: node.id,
+                    index,
+                })
+        })
+    }
+
+    /// The input port within `distance` of a point.
+    fn input_at(&self, pos: Point, distance: f64) -> Option<Port> {
+        self.nodes.iter().rev().find_map(|node| {
+            (0..node.inputs)
+                .find(|&index| (node.input_pos(index) - pos).hypot() <= distance)
+                .map(|index| Port {
+                    node: node.id,
+                    index,
+                })
+        })
+    }
+}
+
+impl Data for Graph {
+    fn same(&self, other: &Self) -> bool {
+        self.nodes.same(&other.nodes)
+            && self.wires.same(&other.wires)
+            && self.selection.same(&other.selection)
+            && self.view.same(&other.view)
+    }
+}
+
+impl NodeGraph {
+    /// Create a new `NodeGraph` editor.
+    pub fn new() -> NodeGraph {
+        NodeGraph {
+            drag: None,
+            min_zoom: 0.1,
+            max_zoom: 4.0,
+            last_mouse_pos: None,
+        }
+    }
+
+    /// Builder-style method to set the range of zoom levels the user can
+    /// reach. The default is from 0.1 to 4.
+    pub fn zoom_range(mut self, min: f64, max: f64) -> Self {
+        self.min_zoom = min;
+        self.max_zoom = max;
+        self
+    }
+
+    fn mouse_down(&mut self, mouse: &MouseEvent, data: &mut Graph) {
+        let pos = data.view.to_content(mouse.pos);
+        let distance = PORT_HIT_DISTANCE / data.view.zoom;
+        if mouse.button == MouseButton::Middle {
+            self.drag = Some(Drag::Pan { last: mouse.pos });
+        } else if let Some(from) = data.output_at(pos, distance) {
+            self.drag = Some(Drag::Wire { from, to: pos });
+        } else if let Some(wire) = data
+            .input_at(pos, distance)
+            .and_then(|to| data.disconnect(to))
+        {
+            self.drag = Some(Drag::Wire {
+                from: wire.from,
+                to: pos,
+            });
+        } else if let Some(id) = data.node_at(pos) {
+            let selection = Arc::make_mut(&mut data.selection);
+            if mouse.mods.shift {
+                match selection.iter().position(|selected| *selected == id) {
+                    Some(index) => {
+                        selection.remove(index);
+                    }
+                    None => selection.push(id),
+                }
+            } else if !selection.contains(&id) {
+                selection.clear();
+                selection.push(id);
+            }
+            self.drag = Some(Drag::Nodes { last: pos });
+        } else {
+            let base = if mouse.mods.shift {
+                data.selection.to_vec()
+            } else {
+                Vec::new()
+            };
+            data.selection = Arc::new(base.clone());
+            self.drag = Some(Drag::Select {
+                start: pos,
+                end: pos,
+                base,
+            });
+        }
+    }
+
+    fn mouse_moved(&mut self, mouse: &MouseEvent, data: &mut Graph) {
+        let pos = data.view.to_content(mouse.pos);
+        match &mut self.drag {
+            Some(Drag::Nodes { last }) => {
+                let delta = pos - *last;
+                *last = pos;
+                let selection = data.selection.clone();
+                for node in Arc::make_mut(&mut data.nodes).iter_mut() {
+                    if selection.contains(&node.id) {
+                        node.pos += delta;
+                    }
+                }
+            }
+            Some(Drag::Wire { to, .. }) => *to = pos,
+            Some(Drag::Select { start, end, base }) => {
+                *end = pos;
+                let rect = Rect::from_points(*start, *end);
+                let mut selection = base.clone();
+                for node in data.nodes.iter() {
+                    let overlap = node.rect().intersect(rect);
+                    if overlap.width() > 0.0
+                        && overlap.height() > 0.0
+                        && !selection.contains(&node.id)
+                    {
+                        selection.push(node.id);
+                    }
+                }
+                data.selection = Arc::new(selection);
+            }
+            Some(Drag::Pan { last }) => {
+                let delta = mouse.pos - *last;
+                *last = mouse.pos;
+                data.view.offset -= delta / data.view.zoom;
+            }
+            None => (),
+        }
+    }
+
+    fn mouse_up(&mut self, mouse: &MouseEvent, data: &mut Graph) {
+        if let Some(Drag::Wire { from, .. }) = self.drag {
+            let pos = data.view.to_content(mouse.pos);
+            let distance = PORT_HIT_DISTANCE / data.view.zoom;
+            if let Some(to) = data.input_at(pos, distance) {
+                data.connect(Wire { from, to });
+            }
+        }
+        self.drag = None;
+    }
+
+    fn zoom_by(&self, data: &mut Graph, factor: f64, center: Point) {
+        let zoom = (data.view.zoom * factor)
+            .max(self.min_zoom)
+            .min(self.max_zoom);
+        data.view.zoom_around(zoom, center);
+    }
+}
+
+impl Default for NodeGraph {
+    fn default() -> Self {
+        NodeGraph::new()
+    }
+}
+
+/// The curve of a wire between two points.
+fn wire_path(from: Point, to: Point) -> BezPath {
+    let handle = Vec2::new(((to.x - from.x).abs() * 0.5).max(40.0), 0.0);
+    let mut path = BezPath::new();
+    path.move_to(from);
+    path.curve_to(from + handle, to - handle, to);
+    path
+}
+
+impl Widget<Graph> for NodeGraph {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Graph, _env: &Env) {
+        match event {
+            Event::MouseDown(mouse) => {
+                ctx.request_focus();
+                ctx.set_active(true);
+                self.mouse_down(mouse, data);
+                ctx.invalidate();
+            }
+            Event::MouseMoved(mouse) => {
+                self.last_mouse_pos = Some(mouse.pos);
+                if self.drag.is_some() {
+                    self.mouse_moved(mouse, data);
+                    ctx.invalidate();
+                }
+            }
+            Event::MouseUp(mouse) => {
+                if self.drag.is_some() {
+                    self.mouse_up(mouse, data);
+                    ctx.set_active(false);
+                    ctx.invalidate();
+                }
+            }
+            Event::Wheel(wheel) if wheel.mods.ctrl => {
+                let center = self.last_mouse_pos.unwrap_or(Point::ORIGIN);
+                self.zoom_by(data, (-wheel.delta.y * WHEEL_ZOOM_RATE).exp(), center);
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            Event::Wheel(wheel) => {
+                let delta = ctx.wheel_remaining(wheel);
+                data.view.offset += delta / data.view.zoom;
+                ctx.consume_wheel(delta);
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            Event::Zoom(delta) => {
+                let center = self.last_mouse_pos.unwrap_or(Point::ORIGIN);
+                self.zoom_by(data, 1.0 + delta, center);
+                ctx.invalidate();
+            }
+            Event::KeyDown(key)
+                if key.key_code == KeyCode::Delete || key.key_code == KeyCode::Backspace =>
+            {
+                let selection = data.selection.to_vec();
+                data.remove_nodes(&selection);
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: Option<&Graph>,
+        _data: &Graph,
+        _env: &Env,
+    ) {
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &Graph,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("NodeGraph");
+        bc.constrain(bc.max())
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &Graph, env: &Env) {
+        let viewport = Rect::from_origin_size(Point::ORIGIN, base_state.size());
+        paint_ctx.fill(viewport, &env.get(theme::BACKGROUND_DARK));
+
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), env.get(theme::TEXT_SIZE_NORMAL))
+            .build()
+            .unwrap();
+        let wire_color = env.get(theme::FOREGROUND_DARK);
+        let port_color = env.get(theme::PRIMARY_LIGHT);
+        let border_color = env.get(theme::BORDER);
+        let selected_color = env.get(theme::SELECTION_COLOR);
+        let node_color = env.get(theme::BUTTON_DARK);
+        let label_color = env.get(theme::LABEL_COLOR);
+        let drag = &self.drag;
+
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(viewport);
+            ctx.transform(data.view.transform());
+
+            for wire in data.wires.iter() {
+                let from = data
+                    .node(wire.from.node)
+                    .map(|n| n.output_pos(wire.from.index));
+                let to = data.node(wire.to.node).map(|n| n.input_pos(wire.to.index));
+                if let (Some(from), Some(to)) = (from, to) {
+                    ctx.stroke(wire_path(from, to), &wire_color, 2.0);
+                }
+            }
+            if let Some(Drag::Wire { from, to }) = drag {
+                if let Some(node) = data.node(from.node) {
+                    ctx.stroke(
+                        wire_path(node.output_pos(from.index), *to),
+                        &port_color,
+                        2.0,
+                    );
+                }
+            }
+
+            for node in data.nodes.iter() {
+                let rect = RoundedRect::from_rect(node.rect(), 4.0);
+                ctx.fill(rect, &node_color);
+                let (border, width) = if data.is_selected(node.id) {
+                    (&selected_color, 2.0)
+                } else {
+                    (&border_color, 1.0)
+                };
+                ctx.stroke(rect, border, width);
+
+                let layout = ctx
+                    .text()
+                    .new_text_layout(&font, &node.title)
+                    .build()
+                    .unwrap();
+                let baseline = node.pos + Vec2::new(8.0, TITLE_HEIGHT * 0.5 + 5.0);
+                ctx.draw_text(&layout, baseline, &label_color);
+
+                for index in 0..node.inputs {
+                    ctx.fill(Circle::new(node.input_pos(index), PORT_RADIUS), &port_color);
+                }
+                for index in 0..node.outputs {
+                    ctx.fill(
+                        Circle::new(node.output_pos(index), PORT_RADIUS),
+                        &port_color,
+                    );
+                }
+            }
+
+            if let Some(Drag::Select { start, end, .. }) = drag {
+                let rect = Rect::from_points(*start, *end);
+                ctx.fill(rect, &selected_color.clone().with_alpha(0.2));
+                ctx.stroke(rect, &selected_color, 1.0 / data.view.zoom);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_nodes() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_node(GraphNode::new(1, "Source", (0.0, 0.0), 0, 1));
+        graph.add_node(GraphNode::new(2, "Sink", (200.0, 0.0), 2, 0));
+        graph
+    }
+
+    #[test]
+    fn connect_replaces_input() {
+        let mut graph = two_nodes();
+        let from = Port { node: 1, index: 0 };
+        let to = Port { node: 2, index: 1 };
+        assert!(graph.connect(Wire { from, to }));
+        assert!(!graph.connect(Wire { from, to }));
+        assert!(!graph.connect(Wire { from: to, to: from }));
+        assert_eq!(graph.wires.len(), 1);
+
+        graph.remove_nodes(&[1]);
+        assert!(graph.wires.is_empty());
+        assert_eq!(graph.nodes.len(), 1);
+    }
+
+    #[test]
+    fn port_hit_testing() {
+        let graph = two_nodes();
+        let output = graph.node(1).unwrap().output_pos(0);
+        assert_eq!(
+            graph.output_at(output + Vec2::new(2.0, 2.0), 8.0),
+            Some(Port { node: 1, index: 0 })
+        );
+        assert_eq!(graph.input_at(output, 8.0), None);
+        assert_eq!(graph.node_at(Point::new(210.0, 10.0)), Some(2));
+    }
+}
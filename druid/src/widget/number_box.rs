@@ -0,0 +1,525 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A text box for numbers, with spin buttons.
+
+use std::fmt::Display;
+
+use crate::kurbo::{BezPath, Line, Point, Rect, Size};
+use crate::piet::RenderContext;
+use crate::theme;
+use crate::widget::TextBox;
+use crate::{
+    Application, BaseState, BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx,
+    LifeCycle, PaintCtx, UpdateCtx, Widget, WidgetPod,
+};
+
+/// The width of the spin buttons.
+const SPIN_WIDTH: f64 = 16.0;
+/// The width used when the width is unbounded.
+const DEFAULT_WIDTH: f64 = 120.0;
+
+/// A number type that can be edited in a [`NumberBox`].
+///
+/// This is implemented for the primitive integer and floating point types.
+///
+/// [`NumberBox`]: struct.NumberBox.html
+pub trait Numeric: Data + Copy + PartialOrd + Display {
+    /// Whether the type only holds whole numbers.
+    const INTEGER: bool;
+
+    /// Convert the value to an `f64`.
+    fn to_f64(self) -> f64;
+
+    /// Convert an `f64` to the nearest value of this type.
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! impl_numeric_int {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                const INTEGER: bool = true;
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    let min = <$t>::min_value() as f64;
+                    let max = <$t>::max_value() as f64;
+                    value.round().max(min).min(max) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl Numeric for f32 {
+    const INTEGER: bool = false;
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl Numeric for f64 {
+    const INTEGER: bool = false;
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+/// How numbers are written: the decimal separator, and the separator
+/// between groups of thousands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat {
+    decimal: char,
+    grouping: Option<char>,
+}
+
+impl NumberFormat {
+    /// Create a format with the given separators.
+    pub fn new(decimal: char, grouping: Option<char>) -> Self {
+        NumberFormat { decimal, grouping }
+    }
+
+    /// The format commonly used with the language of a locale, such as
+    /// `"de-DE"`.
+    ///
+    /// Unknown languages get the English format.
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale
+            .split(|c| c == '-' || c == '_')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        match language.as_str() {
+            "da" | "de" | "el" | "es" | "id" | "it" | "nl" | "pt" | "tr" => {
+                NumberFormat::new(',', Some('.'))
+            }
+            "cs" | "fi" | "fr" | "hu" | "nb" | "no" | "pl" | "ru" | "sk" | "sv" | "uk" => {
+                NumberFormat::new(',', Some('\u{a0}'))
+            }
+            _ => NumberFormat::new('.', Some(',')),
+        }
+    }
+
+    /// The format for the current locale.
+    pub fn current() -> Self {
+        NumberFormat::for_locale(&Application::get_locale())
+    }
+
+    /// Write a number, with a fixed number of decimal places if `precision`
+    /// is given, or as many as are needed otherwise.
+    pub fn format(&self, value: f64, precision: Option<usize>) -> String {
+        match precision {
+            Some(precision) => self.localize(&format!("{:.*}", precision, value)),
+            None => self.localize(&value.to_string()),
+        }
+    }
+
+    /// Write a number formatted by Rust, such as `-1234.5`, in this format.
+    fn localize(&self, text: &str) -> String {
+        let (sign, digits) = if text.starts_with('-') {
+            ("-", &text[1..])
+        } else {
+            ("", &text[..])
+        };
+        let (whole, fraction) = match digits.find('.') {
+            Some(point) => (&digits[..point], Some(&digits[point + 1..])),
+            None => (digits, None),
+        };
+
+        let mut result = String::from(sign);
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                if let Some(grouping) = self.grouping {
+                    result.push(grouping);
+                }
+            }
+            result.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Read a number, ignoring group separators and spaces.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let mut normalized = String::with_capacity(text.len());
+        for c in text.trim().chars() {
+            if c == self.decimal {
+                normalized.push('.');
+            } else if Some(c) == self.grouping || c.is_whitespace() {
+                continue;
+            } else if c == '\u{2212}' {
+                // The minus sign, rather than a hyphen.
+                normalized.push('-');
+            } else {
+                normalized.push(c);
+            }
+        }
+        normalized
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+    }
+}
+
+/// A text box for editing a number, with buttons to step it up and down.
+///
+/// Text is read and written in the [`NumberFormat`] of the current locale.
+/// While typing, the data is changed whenever the text is a valid number
+/// within the bounds; otherwise the box is outlined as an error. Pressing
+/// enter, or moving focus away, writes the number back in its format,
+/// clamping it to the bounds or restoring the last valid value.
+///
+/// The spin buttons, the up and down arrow keys, and the mouse wheel while
+/// the box has focus change the number by the step.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::NumberBox;
+///
+/// let quantity = NumberBox::<u32>::new().min(1).max(99);
+/// let price = NumberBox::<f64>::new().min(0.0).step(0.5).precision(2);
+/// ```
+///
+/// [`NumberFormat`]: struct.NumberFormat.html
+pub struct NumberBox<T: Numeric> {
+    input: WidgetPod<String, TextBox>,
+    text: String,
+    min: Option<T>,
+    max: Option<T>,
+    step: f64,
+    precision: Option<usize>,
+    /// The format, if set; otherwise that of the current locale is used.
+    format: Option<NumberFormat>,
+    is_valid: bool,
+}
+
+impl<T: Numeric> NumberBox<T> {
+    /// Create a new `NumberBox`, with a step of one.
+    pub fn new() -> Self {
+        NumberBox {
+            input: WidgetPod::new(TextBox::raw()),
+            text: String::new(),
+            min: None,
+            max: None,
+            step: 1.0,
+            precision: None,
+            format: None,
+            is_valid: true,
+        }
+    }
+
+    /// Builder-style method to set the smallest allowed value.
+    pub fn min(mut self, min: T) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Builder-style method to set the largest allowed value.
+    pub fn max(mut self, max: T) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Builder-style method to set how much the spin buttons change the
+    /// value.
+    pub fn step(mut self, step: T) -> Self {
+        self.step = step.to_f64();
+        self
+    }
+
+    /// Builder-style method to show a fixed number of decimal places.
+    ///
+    /// This has no effect for integer types.
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Builder-style method to set the [`NumberFormat`], instead of using
+    /// that of the current locale.
+    ///
+    /// [`NumberFormat`]: struct.NumberFormat.html
+    pub fn format(mut self, format: NumberFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    fn number_format(&self) -> NumberFormat {
+        self.format.unwrap_or_else(NumberFormat::current)
+    }
+
+    /// The value of some text, if it is a number of the right type.
+    fn value_of(&self, text: &str) -> Option<T> {
+        let value = self.number_format().parse(text)?;
+        let converted = T::from_f64(value);
+        if T::INTEGER && converted.to_f64() != value {
+            // A fraction, or out of the type's range.
+            return None;
+        }
+        Some(converted)
+    }
+
+    fn in_bounds(&self, value: T) -> bool {
+        self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value <= max)
+    }
+
+    fn clamp(&self, value: T) -> T {
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => min,
+            (_, Some(max)) if value > max => max,
+            _ => value,
+        }
+    }
+
+    fn format_value(&self, value: T) -> String {
+        let format = self.number_format();
+        match self.precision {
+            Some(precision) if !T::INTEGER => format.format(value.to_f64(), Some(precision)),
+            _ => format.localize(&value.to_string()),
+        }
+    }
+
+    /// Set the data, and write it in the text box.
+    fn set_value(&mut self, value: T, data: &mut T) {
+        *data = value;
+        self.text = self.format_value(value);
+        self.is_valid = true;
+    }
+
+    /// Change the value by a number of steps.
+    fn step_by(&mut self, steps: f64, data: &mut T) {
+        let current = self.value_of(&self.text).unwrap_or(*data);
+        let value = self.clamp(T::from_f64(current.to_f64() + steps * self.step));
+        self.set_value(value, data);
+    }
+
+    /// Write the typed value back in its format, or restore the data if the
+    /// text is not a number.
+    fn commit(&mut self, data: &mut T) {
+        let value = match self.value_of(&self.text) {
+            Some(value) => self.clamp(value),
+            None => *data,
+        };
+        self.set_value(value, data);
+    }
+
+    fn spin_rect(&self, size: Size) -> Rect {
+        Rect::new(size.width - SPIN_WIDTH, 0.0, size.width, size.height)
+    }
+}
+
+impl<T: Numeric> Default for NumberBox<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Numeric> Widget<T> for NumberBox<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::LifeCycle(LifeCycle::WidgetAdded) if self.format.is_none() => {
+                // Look the locale up once, rather than for every keystroke.
+                self.format = Some(NumberFormat::current());
+            }
+            Event::MouseDown(mouse) if self.spin_rect(ctx.size()).contains(mouse.pos) => {
+                let steps = if mouse.pos.y < ctx.size().height / 2.0 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                self.step_by(steps, data);
+                ctx.invalidate();
+                ctx.set_handled();
+                return;
+            }
+            Event::Wheel(wheel) if ctx.has_focus() && wheel.delta.y != 0.0 => {
+                let steps = if wheel.delta.y < 0.0 { 1.0 } else { -1.0 };
+                self.step_by(steps, data);
+                ctx.invalidate();
+                ctx.set_handled();
+                return;
+            }
+            Event::KeyDown(key)
+                if key.key_code == KeyCode::ArrowUp || key.key_code == KeyCode::ArrowDown =>
+            {
+                let steps = if key.key_code == KeyCode::ArrowUp {
+                    1.0
+                } else {
+                    -1.0
+                };
+                self.step_by(steps, data);
+                ctx.invalidate();
+                ctx.set_handled();
+                return;
+            }
+            Event::KeyDown(key)
+                if key.key_code == KeyCode::Return || key.key_code == KeyCode::NumpadEnter =>
+            {
+                self.commit(data);
+                ctx.invalidate();
+                ctx.set_handled();
+                return;
+            }
+            Event::FocusChanged(false) => {
+                self.commit(data);
+                ctx.invalidate();
+            }
+            _ => (),
+        }
+
+        let old_text = self.text.clone();
+        self.input.event(ctx, event, &mut self.text, env);
+        if self.text != old_text {
+            match self.value_of(&self.text) {
+                Some(value) if self.in_bounds(value) => {
+                    *data = value;
+                    self.is_valid = true;
+                }
+                _ => self.is_valid = self.text.trim().is_empty(),
+            }
+            ctx.invalidate();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        // Keep the text as typed while it has the same value, so that
+        // typing "1." isn't rewritten as "1".
+        if self.value_of(&self.text) != Some(*data) {
+            self.text = self.format_value(*data);
+            self.is_valid = true;
+            ctx.invalidate();
+        }
+        self.input.update(ctx, &self.text, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("NumberBox");
+
+        let height = env.get(theme::BORDERED_WIDGET_HEIGHT);
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            DEFAULT_WIDTH
+        };
+        let input_bc = BoxConstraints::tight(Size::new((width - SPIN_WIDTH).max(0.0), height));
+        let input_size = self.input.layout(layout_ctx, &input_bc, &self.text, env);
+        self.input
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, input_size));
+        bc.constrain(Size::new(width, height))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        self.input.paint_with_offset(paint_ctx, &self.text, env);
+
+        let size = base_state.size();
+        let spin = self.spin_rect(size);
+        let outline = Rect::new(spin.x0 + 0.5, 0.5, spin.x1 - 0.5, spin.y1 - 0.5);
+        paint_ctx.fill(outline, &env.get(theme::BUTTON_LIGHT));
+        paint_ctx.stroke(outline, &env.get(theme::BORDER), 1.0);
+        let middle = spin.height() / 2.0;
+        paint_ctx.stroke(
+            Line::new((spin.x0, middle), (spin.x1, middle)),
+            &env.get(theme::BORDER),
+            1.0,
+        );
+
+        let enabled = env.get(theme::LABEL_COLOR);
+        let disabled = env.get(theme::PLACEHOLDER_COLOR);
+        let center_x = spin.x0 + SPIN_WIDTH / 2.0;
+        let arrow = SPIN_WIDTH / 4.0;
+        for &up in &[true, false] {
+            let center_y = if up { middle / 2.0 } else { middle * 1.5 };
+            let (tip, base) = if up {
+                (center_y - arrow / 2.0, center_y + arrow / 2.0)
+            } else {
+                (center_y + arrow / 2.0, center_y - arrow / 2.0)
+            };
+            let mut path = BezPath::new();
+            path.move_to((center_x, tip));
+            path.line_to((center_x + arrow, base));
+            path.line_to((center_x - arrow, base));
+            path.close_path();
+            let at_bound = if up {
+                self.max.map_or(false, |max| *data >= max)
+            } else {
+                self.min.map_or(false, |min| *data <= min)
+            };
+            paint_ctx.fill(path, if at_bound { &disabled } else { &enabled });
+        }
+
+        if !self.is_valid {
+            let input = self.input.get_layout_rect();
+            let outline = Rect::new(0.5, 0.5, input.x1 - 0.5, input.y1 - 0.5);
+            paint_ctx.stroke(outline, &env.get(theme::FIELD_ERROR_COLOR), 1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_parse() {
+        let english = NumberFormat::for_locale("en-US");
+        assert_eq!(english.format(1234567.5, None), "1,234,567.5");
+        assert_eq!(english.format(-1234.0, Some(2)), "-1,234.00");
+        assert_eq!(english.format(12.0, None), "12");
+        assert_eq!(english.parse(" 1,234.5 "), Some(1234.5));
+        assert_eq!(english.parse("abc"), None);
+
+        let german = NumberFormat::for_locale("de_DE");
+        assert_eq!(german.format(1234.25, None), "1.234,25");
+        assert_eq!(german.parse("1.234,25"), Some(1234.25));
+    }
+
+    #[test]
+    fn integers_reject_fractions_and_clamp() {
+        let number_box = NumberBox::<u8>::new()
+            .format(NumberFormat::new('.', None))
+            .min(10)
+            .max(20);
+        assert_eq!(number_box.value_of("12"), Some(12));
+        assert_eq!(number_box.value_of("12.5"), None);
+        assert_eq!(number_box.value_of("300"), None);
+        assert_eq!(number_box.clamp(5), 10);
+        assert_eq!(number_box.clamp(25), 20);
+    }
+}
@@ -16,8 +16,8 @@
 
 use crate::kurbo::{Insets, Point, Rect, Size};
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
-    WidgetPod,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LayoutDirection,
+    LogicalInsets, PaintCtx, UpdateCtx, Widget, WidgetPod,
 };
 
 /// A widget that just adds padding around its child.
@@ -26,6 +26,9 @@ pub struct Padding<T: Data> {
     right: f64,
     top: f64,
     bottom: f64,
+    /// Whether `left` and `right` are the start and end, swapped in
+    /// right-to-left layouts.
+    logical: bool,
 
     child: WidgetPod<T, Box<dyn Widget<T>>>,
 }
@@ -39,6 +42,7 @@ impl<T: Data> Padding<T> {
             right: padding,
             top: padding,
             bottom: padding,
+            logical: false,
             child: WidgetPod::new(child).boxed(),
         }
     }
@@ -79,6 +83,40 @@ impl<T: Data> Padding<T> {
             right: insets.x1,
             top: insets.y0,
             bottom: insets.y1,
+            logical: false,
+            child: WidgetPod::new(child).boxed(),
+        }
+    }
+
+    /// Create a new widget with padding on its start and end sides, which
+    /// are swapped when [`theme::RIGHT_TO_LEFT`] is set.
+    ///
+    /// The padding can be a [`LogicalInsets`], or a f64 or tuple as for
+    /// [`Padding::new`], with (start, top, end, bottom) values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::{Label, Padding};
+    ///
+    /// // Room for an icon before the text, whichever way it flows.
+    /// let _: Padding<()> = Padding::logical((24.0, 0.0, 4.0, 0.0), Label::new("indented"));
+    /// ```
+    ///
+    /// [`theme::RIGHT_TO_LEFT`]: ../theme/constant.RIGHT_TO_LEFT.html
+    /// [`LogicalInsets`]: ../struct.LogicalInsets.html
+    /// [`Padding::new`]: #method.new
+    pub fn logical(
+        insets: impl Into<LogicalInsets>,
+        child: impl Widget<T> + 'static,
+    ) -> Padding<T> {
+        let insets = insets.into();
+        Padding {
+            left: insets.start,
+            right: insets.end,
+            top: insets.top,
+            bottom: insets.bottom,
+            logical: true,
             child: WidgetPod::new(child).boxed(),
         }
     }
@@ -102,12 +140,17 @@ impl<T: Data> Widget<T> for Padding<T> {
     ) -> Size {
         bc.debug_check("Padding");
 
+        let left = if self.logical && LayoutDirection::from_env(env).is_rtl() {
+            self.right
+        } else {
+            self.left
+        };
         let hpad = self.left + self.right;
         let vpad = self.top + self.bottom;
 
         let child_bc = bc.shrink((hpad, vpad));
         let size = self.child.layout(layout_ctx, &child_bc, data, env);
-        let origin = Point::new(self.left, self.top);
+        let origin = Point::new(left, self.top);
         self.child
             .set_layout_rect(Rect::from_origin_size(origin, size));
         Size::new(size.width + hpad, size.height + vpad)
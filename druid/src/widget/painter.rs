@@ -0,0 +1,154 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Custom drawing, as a widget or as the background of another.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::{Color, LinearGradient, PaintBrush, RadialGradient, RenderContext};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, Key, LayoutCtx, PaintCtx, UpdateCtx,
+    Widget,
+};
+
+/// A widget that paints with a closure.
+///
+/// The closure is given the same arguments as [`Widget::paint`], and can
+/// draw anything. The painter takes all the space it is given, and is
+/// repainted whenever the data changes.
+///
+/// A painter is usually used as the background of a [`Container`], through
+/// [`BackgroundBrush`], but can also be a widget of its own.
+///
+/// # Examples
+///
+/// ```
+/// use druid::kurbo::{Circle, Point};
+/// use druid::piet::RenderContext;
+/// use druid::theme;
+/// use druid::widget::Painter;
+///
+/// let dot = Painter::new(|paint_ctx, base_state, data: &f64, env| {
+///     let size = base_state.size();
+///     let center = Point::new(size.width / 2.0, size.height / 2.0);
+///     let radius = size.width.min(size.height) / 2.0 * data;
+///     paint_ctx.fill(Circle::new(center, radius), &env.get(theme::PRIMARY_LIGHT));
+/// });
+/// ```
+///
+/// [`Widget::paint`]: ../trait.Widget.html#tymethod.paint
+/// [`Container`]: struct.Container.html
+/// [`BackgroundBrush`]: enum.BackgroundBrush.html
+pub struct Painter<T>(Box<dyn Fn(&mut PaintCtx, &BaseState, &T, &Env)>);
+
+impl<T> Painter<T> {
+    /// Create a painter that draws with `paint`.
+    pub fn new(paint: impl Fn(&mut PaintCtx, &BaseState, &T, &Env) + 'static) -> Painter<T> {
+        Painter(Box::new(paint))
+    }
+}
+
+impl<T: Data> Widget<T> for Painter<T> {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, _data: &T, _env: &Env) {
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("Painter");
+        bc.max()
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        (self.0)(paint_ctx, base_state, data, env);
+    }
+}
+
+/// What fills the background of a widget.
+///
+/// Colors and gradients convert into a brush, as do color keys, which are
+/// looked up in the environment when painting, so that the background
+/// follows the theme, and painters, for anything else.
+pub enum BackgroundBrush<T> {
+    /// A solid color.
+    Color(Color),
+    /// A color from the environment.
+    ColorKey(Key<Color>),
+    /// A linear gradient.
+    Linear(LinearGradient),
+    /// A radial gradient.
+    Radial(RadialGradient),
+    /// Custom drawing.
+    Painter(Painter<T>),
+}
+
+impl<T> BackgroundBrush<T> {
+    /// Fill a widget's bounds with this brush.
+    pub fn paint(&self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let bounds = Rect::from_origin_size(Point::ORIGIN, base_state.size());
+        match self {
+            BackgroundBrush::Color(color) => paint_ctx.fill(bounds, color),
+            BackgroundBrush::ColorKey(key) => paint_ctx.fill(bounds, &env.get(*key)),
+            BackgroundBrush::Linear(gradient) => paint_ctx.fill(bounds, gradient),
+            BackgroundBrush::Radial(gradient) => paint_ctx.fill(bounds, gradient),
+            BackgroundBrush::Painter(painter) => (painter.0)(paint_ctx, base_state, data, env),
+        }
+    }
+}
+
+impl<T> From<Color> for BackgroundBrush<T> {
+    fn from(color: Color) -> BackgroundBrush<T> {
+        BackgroundBrush::Color(color)
+    }
+}
+
+impl<T> From<Key<Color>> for BackgroundBrush<T> {
+    fn from(key: Key<Color>) -> BackgroundBrush<T> {
+        BackgroundBrush::ColorKey(key)
+    }
+}
+
+impl<T> From<LinearGradient> for BackgroundBrush<T> {
+    fn from(gradient: LinearGradient) -> BackgroundBrush<T> {
+        BackgroundBrush::Linear(gradient)
+    }
+}
+
+impl<T> From<RadialGradient> for BackgroundBrush<T> {
+    fn from(gradient: RadialGradient) -> BackgroundBrush<T> {
+        BackgroundBrush::Radial(gradient)
+    }
+}
+
+impl<T> From<PaintBrush> for BackgroundBrush<T> {
+    fn from(brush: PaintBrush) -> BackgroundBrush<T> {
+        match brush {
+            PaintBrush::Color(color) => BackgroundBrush::Color(color),
+            PaintBrush::Linear(gradient) => BackgroundBrush::Linear(gradient),
+            PaintBrush::Radial(gradient) => BackgroundBrush::Radial(gradient),
+        }
+    }
+}
+
+impl<T> From<Painter<T>> for BackgroundBrush<T> {
+    fn from(painter: Painter<T>) -> BackgroundBrush<T> {
+        BackgroundBrush::Painter(painter)
+    }
+}
@@ -8,6 +8,11 @@ use crate::{
 };
 
 /// Converts a `Widget<String>` to a `Widget<Option<T>>`, mapping parse errors to None
+///
+/// For numbers, [`NumberBox`] is usually a better choice: it keeps the last
+/// valid value instead of `None`, and handles locales, bounds and stepping.
+///
+/// [`NumberBox`]: struct.NumberBox.html
 pub struct Parse<T> {
     widget: T,
     state: String,
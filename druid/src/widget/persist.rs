@@ -0,0 +1,154 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that saves and restores its child's view state.
+
+use std::marker::PhantomData;
+
+use crate::kurbo::Size;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, Key, LayoutCtx, LifeCycle, PaintCtx,
+    PersistView, UpdateCtx, Widget,
+};
+
+/// The path of the innermost `Persist`, for the ones inside it.
+const PARENT_PATH: Key<&str> = Key::new("druid.persist.parent-path");
+
+/// A widget that saves its child's view state in the application's
+/// [`Store`], and restores it when the child is added.
+///
+/// The state is kept under `"view_state."` and a path made of `key` and
+/// the keys of any `Persist` widgets this one is inside; siblings need
+/// distinct keys. It is saved after the events that can change it, such
+/// as clicks, the wheel, keys and commands, and when the widget is removed. This is usually made with [`WidgetExt::persist_view`].
+///
+/// [`Store`]: ../struct.Store.html
+/// [`WidgetExt::persist_view`]: trait.WidgetExt.html#method.persist_view
+pub struct Persist<T: Data, W: Widget<T> + PersistView> {
+    key: String,
+    /// The full path, once known.
+    path: Option<String>,
+    child: W,
+    /// The environment this widget was last given, and the one made from
+    /// it for the child, with this widget's path added.
+    env: Option<(Env, Env)>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Data, W: Widget<T> + PersistView> Persist<T, W> {
+    /// Create a new `Persist` widget.
+    pub fn new(key: impl Into<String>, child: W) -> Persist<T, W> {
+        Persist {
+            key: key.into(),
+            path: None,
+            child,
+            env: None,
+            phantom: Default::default(),
+        }
+    }
+
+    /// The environment for the child, which is only rebuilt when the
+    /// environment this widget is given changes.
+    fn child_env(&mut self, env: &Env) -> Env {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return env.clone(),
+        };
+        match &self.env {
+            Some((parent, child)) if parent.same(env) => child.clone(),
+            _ => {
+                let child = env.clone().adding(PARENT_PATH, path.as_str());
+                self.env = Some((env.clone(), child.clone()));
+                child
+            }
+        }
+    }
+
+    /// The key of the child's state in the store.
+    fn store_key(&self) -> Option<String> {
+        self.path
+            .as_ref()
+            .map(|path| format!("view_state.{}", path))
+    }
+}
+
+impl<T: Data, W: Widget<T> + PersistView> Widget<T> for Persist<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::LifeCycle(LifeCycle::WidgetAdded) = event {
+            if self.path.is_none() {
+                self.path = Some(match env.try_get(PARENT_PATH) {
+                    Some(parent) => format!("{}/{}", parent, self.key),
+                    None => self.key.clone(),
+                });
+                let key = self.store_key().unwrap_or_default();
+                if let Some(state) = ctx.store().get(&key) {
+                    self.child.restore_view_state(state);
+                }
+            }
+        }
+        let child_env = self.child_env(env);
+        self.child.event(ctx, event, data, &child_env);
+        if !may_change_view_state(event) {
+            return;
+        }
+        // The store only counts a value as changed if it differs from the
+        // one it has.
+        if let Some(key) = self.store_key() {
+            ctx.store().set(&key, &self.child.view_state());
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        let child_env = self.child_env(env);
+        self.child.update(ctx, old_data, data, &child_env);
+        if let Some(key) = self.store_key() {
+            ctx.store().set(&key, &self.child.view_state());
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        let child_env = self.child_env(env);
+        self.child.layout(layout_ctx, bc, data, &child_env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let child_env = self.child_env(env);
+        self.child.paint(paint_ctx, base_state, data, &child_env);
+    }
+}
+
+/// Whether `event` can change a widget's view state, so that it should be
+/// saved afterwards.
+///
+/// Pointer movement and animation frames come too often to save after each;
+/// a drag ends with a mouse up, and the state is saved again when the widget
+/// is removed.
+fn may_change_view_state(event: &Event) -> bool {
+    match event {
+        Event::MouseDown(_)
+        | Event::MouseUp(_)
+        | Event::Wheel(_)
+        | Event::KeyDown(_)
+        | Event::Command(_)
+        | Event::TargetedCommand(..)
+        | Event::LifeCycle(LifeCycle::WidgetRemoved) => true,
+        _ => false,
+    }
+}
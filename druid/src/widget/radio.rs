@@ -21,8 +21,8 @@ use crate::piet::{LinearGradient, RenderContext, UnitPoint};
 use crate::theme;
 use crate::widget::{Align, Flex, Label, LabelText, Padding};
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
-    WidgetPod,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
 };
 
 /// A group of radio buttons
@@ -78,9 +78,17 @@ impl<T: Data + PartialEq> Widget<T> for Radio<T> {
                     ctx.invalidate();
                 }
             }
-            Event::HotChanged(_) => {
+            Event::KeyDown(key_event)
+                if ctx.has_focus() && key_event.key_code == KeyCode::Space =>
+            {
+                *data = self.variant.clone();
+                ctx.set_handled();
                 ctx.invalidate();
             }
+            Event::HotChanged(_) | Event::FocusChanged(_) => {
+                ctx.invalidate();
+            }
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
             _ => (),
         }
     }
@@ -99,7 +107,7 @@ impl<T: Data + PartialEq> Widget<T> for Radio<T> {
         bc.debug_check("Radio");
 
         let label_size = self.child_label.layout(layout_ctx, &bc, data, env);
-        let padding = 5.0;
+        let padding = theme::spacing(env, 5.0);
         let label_x_offset = env.get(theme::BASIC_WIDGET_HEIGHT) + padding;
         let origin = Point::new(label_x_offset, 0.0);
 
@@ -117,6 +125,24 @@ impl<T: Data + PartialEq> Widget<T> for Radio<T> {
 
         let circle = Circle::new((size / 2., size / 2.), 7.);
 
+        if env.get(theme::HIGH_CONTRAST) {
+            // Flat colors from the forced palette, with the selected button
+            // marked in the highlight color.
+            let border = if base_state.is_hot() || base_state.has_focus() {
+                env.get(theme::FORCED_HIGHLIGHT)
+            } else {
+                env.get(theme::FORCED_BUTTON_TEXT)
+            };
+            paint_ctx.fill(circle, &env.get(theme::FORCED_BUTTON_FACE));
+            paint_ctx.stroke(circle, &border, 1.);
+            if *data == self.variant {
+                let inner_circle = Circle::new((size / 2., size / 2.), 3.);
+                paint_ctx.fill(inner_circle, &env.get(theme::FORCED_HIGHLIGHT));
+            }
+            self.child_label.paint_with_offset(paint_ctx, data, env);
+            return;
+        }
+
         // Paint the background
         let background_gradient = LinearGradient::new(
             UnitPoint::TOP,
@@ -129,7 +155,9 @@ impl<T: Data + PartialEq> Widget<T> for Radio<T> {
 
         paint_ctx.fill(circle, &background_gradient);
 
-        let border_color = if base_state.is_hot() {
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else if base_state.is_hot() {
             env.get(theme::BORDER_LIGHT)
         } else {
             env.get(theme::BORDER)
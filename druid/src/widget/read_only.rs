@@ -0,0 +1,114 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that makes its child read-only based on the data.
+
+use std::marker::PhantomData;
+
+use crate::kurbo::Size;
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+};
+
+/// A widget that puts its child into read-only mode when a closure
+/// returns `true`.
+///
+/// Read-only is distinct from disabled: the child can still be focused,
+/// and its contents can still be selected and copied, but input widgets
+/// such as [`TextBox`], [`Slider`] and [`Checkbox`] will not modify their
+/// data.
+///
+/// This works by setting [`theme::READ_ONLY`] in the child's [`Env`].
+///
+/// [`TextBox`]: struct.TextBox.html
+/// [`Slider`]: struct.Slider.html
+/// [`Checkbox`]: struct.Checkbox.html
+/// [`theme::READ_ONLY`]: ../theme/constant.READ_ONLY.html
+/// [`Env`]: ../struct.Env.html
+pub struct ReadOnly<T: Data, W: Widget<T>> {
+    f: Box<dyn Fn(&T, &Env) -> bool>,
+    child: W,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Data, W: Widget<T>> ReadOnly<T, W> {
+    /// Create a new `ReadOnly` widget.
+    ///
+    /// The closure is called with the current data and environment; the child
+    /// is read-only whenever it returns `true`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use druid::Widget;
+    /// # use druid::widget::{ReadOnly, TextBox};
+    ///
+    /// # fn build_widget() -> impl Widget<String> {
+    ///
+    /// ReadOnly::new(|data: &String, _env| data.len() > 10, TextBox::raw())
+    ///
+    /// # }
+    /// ```
+    pub fn new(f: impl Fn(&T, &Env) -> bool + 'static, child: W) -> ReadOnly<T, W> {
+        ReadOnly {
+            f: Box::new(f),
+            child,
+            phantom: Default::default(),
+        }
+    }
+
+    fn child_env(&self, data: &T, env: &Env) -> Env {
+        let read_only = (self.f)(data, env);
+        let mut new_env = env.clone();
+        if read_only {
+            new_env.set(theme::READ_ONLY, true);
+        }
+        new_env
+    }
+}
+
+impl<T: Data, W: Widget<T>> Widget<T> for ReadOnly<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let new_env = self.child_env(data, env);
+        self.child.event(ctx, event, data, &new_env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        let new_env = self.child_env(data, env);
+        if let Some(old_data) = old_data {
+            if (self.f)(old_data, env) != (self.f)(data, env) {
+                ctx.invalidate();
+            }
+        }
+        self.child.update(ctx, old_data, data, &new_env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("ReadOnly");
+
+        let new_env = self.child_env(data, env);
+        self.child.layout(layout_ctx, &bc, data, &new_env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let new_env = self.child_env(data, env);
+        self.child.paint(paint_ctx, base_state, data, &new_env);
+    }
+}
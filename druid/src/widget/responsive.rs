@@ -0,0 +1,178 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that picks its layout by the space available to it.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that shows one of several layouts, chosen by the width
+/// available to it.
+///
+/// Each layout has a breakpoint, the smallest width it is used at, and the
+/// widest layout that fits is shown. This lets a window rearrange its
+/// content, for instance from side-by-side panes to a single stacked
+/// column, as it shrinks. The choice is made during layout, so it follows
+/// the constraints as they change.
+///
+/// Layouts are built the first time they are shown, and kept afterwards,
+/// so their state survives resizing back and forth. Only the layout being
+/// shown is updated and receives events; a layout that is shown again is
+/// brought up to date before it is laid out. When the layouts switch, the
+/// hidden one is sent [`LifeCycle::WidgetRemoved`] before the next event.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Flex, Label, Responsive};
+///
+/// let panes = Responsive::new(|| {
+///     Flex::column()
+///         .with_child(Label::new("Sidebar"), 0.0)
+///         .with_child(Label::new("Content"), 1.0)
+/// })
+/// .breakpoint(600.0, || {
+///     Flex::row()
+///         .with_child(Label::new("Sidebar"), 0.0)
+///         .with_child(Label::new("Content"), 1.0)
+/// });
+/// # let _: &dyn druid::Widget<u32> = &panes;
+/// ```
+///
+/// [`LifeCycle::WidgetRemoved`]: ../enum.LifeCycle.html#variant.WidgetRemoved
+pub struct Responsive<T: Data> {
+    /// The layouts, narrowest first.
+    layouts: Vec<Layout<T>>,
+    /// The layout chosen by the last layout pass.
+    current: usize,
+    /// The layout that received the last event.
+    shown: Option<usize>,
+}
+
+struct Layout<T: Data> {
+    min_width: f64,
+    build: Box<dyn Fn() -> Box<dyn Widget<T>>>,
+    child: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+}
+
+impl<T: Data> Responsive<T> {
+    /// Create a widget whose narrowest layout is built by `build`.
+    pub fn new<W: Widget<T> + 'static>(build: impl Fn() -> W + 'static) -> Responsive<T> {
+        Responsive {
+            layouts: vec![Layout::new(0.0, build)],
+            current: 0,
+            shown: None,
+        }
+    }
+
+    /// Builder-style method to add a layout, used when at least
+    /// `min_width` is available.
+    pub fn breakpoint<W: Widget<T> + 'static>(
+        mut self,
+        min_width: f64,
+        build: impl Fn() -> W + 'static,
+    ) -> Self {
+        let index = self
+            .layouts
+            .iter()
+            .position(|layout| layout.min_width > min_width)
+            .unwrap_or_else(|| self.layouts.len());
+        self.layouts.insert(index, Layout::new(min_width, build));
+        self
+    }
+
+    /// The widest layout that fits in `width`.
+    fn choose(&self, width: f64) -> usize {
+        self.layouts
+            .iter()
+            .rposition(|layout| layout.min_width <= width)
+            .unwrap_or(0)
+    }
+}
+
+impl<T: Data> Layout<T> {
+    fn new<W: Widget<T> + 'static>(min_width: f64, build: impl Fn() -> W + 'static) -> Layout<T> {
+        Layout {
+            min_width,
+            build: Box::new(move || Box::new(build())),
+            child: None,
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Responsive<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if self.shown != Some(self.current) {
+            let removed = Event::LifeCycle(LifeCycle::WidgetRemoved);
+            if let Some(hidden) = self.shown.and_then(|i| self.layouts[i].child.as_mut()) {
+                hidden.event(ctx, &removed, data, env);
+            }
+            self.shown = Some(self.current);
+        }
+        if let Some(child) = &mut self.layouts[self.current].child {
+            child.event(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        if let Some(child) = &mut self.layouts[self.current].child {
+            child.update(ctx, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Responsive");
+
+        self.current = self.choose(bc.max().width);
+        let layout = &mut self.layouts[self.current];
+        let build = &layout.build;
+        let child = layout.child.get_or_insert_with(|| WidgetPod::new(build()));
+        layout_ctx.update_child(child, data, env);
+        let size = child.layout(layout_ctx, bc, data, env);
+        child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        if let Some(child) = &mut self.layouts[self.current].child {
+            child.paint(paint_ctx, data, env);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    #[test]
+    fn widest_layout_that_fits() {
+        let responsive = Responsive::<u32>::new(|| Label::new("narrow"))
+            .breakpoint(900.0, || Label::new("wide"))
+            .breakpoint(500.0, || Label::new("medium"));
+        assert_eq!(responsive.choose(100.0), 0);
+        assert_eq!(responsive.choose(500.0), 1);
+        assert_eq!(responsive.choose(899.0), 1);
+        assert_eq!(responsive.choose(std::f64::INFINITY), 2);
+    }
+}
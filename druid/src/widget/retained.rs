@@ -0,0 +1,83 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that keeps what its child paints.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    WidgetPod,
+};
+
+/// A widget that keeps a [`Raster`] of what its child paints, and draws it
+/// instead of painting the child again until something in the child
+/// changes.
+///
+/// The child is painted again when it or one of its descendants is
+/// invalidated or updated, or its size changes; see
+/// [`WidgetPod::retained`]. This suits content that is expensive to paint
+/// and rarely changes, such as charts or the static parts of a dashboard.
+/// This is usually made with [`WidgetExt::retained`].
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Label, Retained};
+///
+/// let widget = Retained::new(Label::new("Rarely changes"));
+/// # let _: Retained<(), _> = widget;
+/// ```
+///
+/// [`Raster`]: ../struct.Raster.html
+/// [`WidgetPod::retained`]: ../struct.WidgetPod.html#method.retained
+/// [`WidgetExt::retained`]: trait.WidgetExt.html#method.retained
+pub struct Retained<T: Data, W: Widget<T>> {
+    child: WidgetPod<T, W>,
+}
+
+impl<T: Data, W: Widget<T>> Retained<T, W> {
+    /// Create a new `Retained` widget.
+    pub fn new(child: W) -> Retained<T, W> {
+        Retained {
+            child: WidgetPod::new(child).retained(),
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Widget<T> for Retained<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.child.event(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        let size = self.child.layout(layout_ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _base_state: &BaseState, data: &T, env: &Env) {
+        self.child.paint_with_offset(paint_ctx, data, env);
+    }
+}
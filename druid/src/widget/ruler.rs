@@ -0,0 +1,325 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rulers that follow a zoomable view.
+
+use std::sync::Arc;
+
+use crate::kurbo::{Line, Point, Rect, Size};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayoutBuilder};
+use crate::theme;
+use crate::widget::flex::Axis;
+use crate::widget::ZoomState;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, Lens, PaintCtx, UpdateCtx,
+    Widget,
+};
+
+/// The thickness of a ruler.
+const RULER_SIZE: f64 = 20.0;
+/// The smallest distance between labelled ticks, in display points.
+const MIN_LABEL_SPACING: f64 = 50.0;
+/// How close the pointer must be to a guide to pick it up.
+const GUIDE_HIT_DISTANCE: f64 = 4.0;
+
+/// A ruler along one edge of a [`ZoomView`], with draggable guides.
+///
+/// The ruler follows the view through the same [`ZoomState`], and labels
+/// its ticks in the units set with [`units`]. Dragging from the ruler
+/// creates a guide; guides can be dragged to move them, and are removed by
+/// dragging them back onto the ruler. Guide positions are kept in the data,
+/// in content coordinates, so the view's content can draw them.
+///
+/// [`ZoomView`]: struct.ZoomView.html
+/// [`ZoomState`]: struct.ZoomState.html
+/// [`units`]: #method.units
+pub struct Ruler<Z, G> {
+    axis: Axis,
+    zoom_lens: Z,
+    guides_lens: G,
+    unit_name: String,
+    /// The size of a unit, in content coordinates.
+    unit_size: f64,
+    /// The index of the guide being dragged.
+    dragging: Option<usize>,
+}
+
+impl<Z, G> Ruler<Z, G> {
+    /// Create a horizontal ruler, for the top edge of a view.
+    ///
+    /// It takes lenses to the view's [`ZoomState`] and to the positions of
+    /// the vertical guides.
+    ///
+    /// [`ZoomState`]: struct.ZoomState.html
+    pub fn horizontal(zoom_lens: Z, guides_lens: G) -> Self {
+        Self::new(Axis::Horizontal, zoom_lens, guides_lens)
+    }
+
+    /// Create a vertical ruler, for the left edge of a view.
+    ///
+    /// It takes lenses to the view's [`ZoomState`] and to the positions of
+    /// the horizontal guides.
+    ///
+    /// [`ZoomState`]: struct.ZoomState.html
+    pub fn vertical(zoom_lens: Z, guides_lens: G) -> Self {
+        Self::new(Axis::Vertical, zoom_lens, guides_lens)
+    }
+
+    fn new(axis: Axis, zoom_lens: Z, guides_lens: G) -> Self {
+        Ruler {
+            axis,
+            zoom_lens,
+            guides_lens,
+            unit_name: String::new(),
+            unit_size: 1.0,
+            dragging: None,
+        }
+    }
+
+    /// Builder-style method to set the units of the ruler: their name, shown
+    /// after the first label, and their size in content coordinates.
+    pub fn units(mut self, name: impl Into<String>, size: f64) -> Self {
+        self.unit_name = name.into();
+        self.unit_size = size;
+        self
+    }
+
+    /// The position along the ruler of a point in the widget.
+    fn along(&self, pos: Point) -> f64 {
+        match self.axis {
+            Axis::Horizontal => pos.x,
+            Axis::Vertical => pos.y,
+        }
+    }
+
+    /// Whether a point in the widget is over the ruler itself.
+    fn on_ruler(&self, pos: Point, size: Size) -> bool {
+        match self.axis {
+            Axis::Horizontal => pos.y >= 0.0 && pos.y < size.height,
+            Axis::Vertical => pos.x >= 0.0 && pos.x < size.width,
+        }
+    }
+
+    /// Convert a position along the ruler to content coordinates.
+    fn to_content(&self, state: &ZoomState, along: f64) -> f64 {
+        let point = match self.axis {
+            Axis::Horizontal => Point::new(along, 0.0),
+            Axis::Vertical => Point::new(0.0, along),
+        };
+        self.along(state.to_content(point))
+    }
+
+    /// Convert a position in content coordinates to a position along the
+    /// ruler.
+    fn to_ruler(&self, state: &ZoomState, content: f64) -> f64 {
+        let point = match self.axis {
+            Axis::Horizontal => Point::new(content, 0.0),
+            Axis::Vertical => Point::new(0.0, content),
+        };
+        self.along(state.to_view(point))
+    }
+}
+
+/// The distance between labelled ticks, in units: 1, 2 or 5 times a power
+/// of ten, so that labels are at least `MIN_LABEL_SPACING` apart.
+pub(crate) fn tick_step(points_per_unit: f64) -> f64 {
+    let min_step = MIN_LABEL_SPACING / points_per_unit;
+    let magnitude = 10f64.powf(min_step.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= min_step)
+        .unwrap_or(10.0 * magnitude)
+}
+
+impl<T, Z, G> Widget<T> for Ruler<Z, G>
+where
+    T: Data,
+    Z: Lens<T, ZoomState>,
+    G: Lens<T, Arc<Vec<f64>>>,
+{
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, _env: &Env) {
+        let state = self.zoom_lens.with(data, |state| *state);
+        match event {
+            Event::MouseDown(mouse) => {
+                let along = self.along(mouse.pos);
+                let pos = self.to_content(&state, along);
+                let hit_distance = GUIDE_HIT_DISTANCE / state.zoom;
+                let dragging = self.guides_lens.with_mut(data, |guides| {
+                    let hit = guides
+                        .iter()
+                        .position(|guide| (guide - pos).abs() <= hit_distance);
+                    hit.unwrap_or_else(|| {
+                        Arc::make_mut(guides).push(pos);
+                        guides.len() - 1
+                    })
+                });
+                self.dragging = Some(dragging);
+                ctx.set_active(true);
+                ctx.invalidate();
+            }
+            Event::MouseMoved(mouse) => {
+                if let Some(index) = self.dragging {
+                    let pos = self.to_content(&state, self.along(mouse.pos));
+                    self.guides_lens.with_mut(data, |guides| {
+                        if let Some(guide) = Arc::make_mut(guides).get_mut(index) {
+                            *guide = pos;
+                        }
+                    });
+                    ctx.invalidate();
+                }
+            }
+            Event::MouseUp(mouse) => {
+                if let Some(index) = self.dragging.take() {
+                    if self.on_ruler(mouse.pos, ctx.size()) {
+                        self.guides_lens.with_mut(data, |guides| {
+                            if index < guides.len() {
+                                Arc::make_mut(guides).remove(index);
+                            }
+                        });
+                    }
+                    ctx.set_active(false);
+                    ctx.invalidate();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, _env: &Env) {
+        let changed = match old_data {
+            Some(old) => {
+                let zoom_same = self
+                    .zoom_lens
+                    .with(old, |old| self.zoom_lens.with(data, |new| old.same(new)));
+                let guides_same = self
+                    .guides_lens
+                    .with(old, |old| self.guides_lens.with(data, |new| old.same(new)));
+                !(zoom_same && guides_same)
+            }
+            None => true,
+        };
+        if changed {
+            ctx.invalidate();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("Ruler");
+        let size = match self.axis {
+            Axis::Horizontal if bc.is_width_bounded() => Size::new(bc.max().width, RULER_SIZE),
+            Axis::Vertical if bc.is_height_bounded() => Size::new(RULER_SIZE, bc.max().height),
+            Axis::Horizontal => Size::new(bc.min().width, RULER_SIZE),
+            Axis::Vertical => Size::new(RULER_SIZE, bc.min().height),
+        };
+        bc.constrain(size)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let state = self.zoom_lens.with(data, |state| *state);
+        let size = base_state.size();
+        let rect = Rect::from_origin_size(Point::ORIGIN, size);
+        paint_ctx.fill(rect, &env.get(theme::BACKGROUND_LIGHT));
+
+        let line_color = env.get(theme::BORDER);
+        let label_color = env.get(theme::LABEL_COLOR);
+        let length = self.along(Point::new(size.width, size.height));
+        let line = |along: f64, depth: f64| match self.axis {
+            Axis::Horizontal => Line::new((along, RULER_SIZE - depth), (along, RULER_SIZE)),
+            Axis::Vertical => Line::new((RULER_SIZE - depth, along), (RULER_SIZE, along)),
+        };
+
+        // The edge next to the view.
+        let edge = match self.axis {
+            Axis::Horizontal => Line::new((0.0, size.height), (size.width, size.height)),
+            Axis::Vertical => Line::new((size.width, 0.0), (size.width, size.height)),
+        };
+        paint_ctx.stroke(edge, &line_color, 1.0);
+
+        // Ticks, with a label on every fifth.
+        let points_per_unit = state.zoom * self.unit_size;
+        if points_per_unit > 0.0 && self.unit_size > 0.0 {
+            let label_step = tick_step(points_per_unit);
+            let step = label_step / 5.0;
+            let first_unit = self.to_content(&state, 0.0) / self.unit_size;
+            let last_unit = self.to_content(&state, length) / self.unit_size;
+            let font_name = env.get(theme::FONT_NAME);
+            let font_size = env.get(theme::TEXT_SIZE_NORMAL) * 0.7;
+            let font = paint_ctx
+                .text()
+                .new_font_by_name(font_name, font_size)
+                .build()
+                .unwrap();
+
+            let mut index = (first_unit / step).floor() as i64;
+            while (index as f64) * step <= last_unit {
+                let unit = index as f64 * step;
+                let along = self.to_ruler(&state, unit * self.unit_size);
+                let labelled = index % 5 == 0;
+                let depth = if labelled {
+                    RULER_SIZE * 0.6
+                } else {
+                    RULER_SIZE * 0.25
+                };
+                paint_ctx.stroke(line(along, depth), &line_color, 1.0);
+                if labelled {
+                    let text = if index == 0 && !self.unit_name.is_empty() {
+                        format!("0 {}", self.unit_name)
+                    } else {
+                        format!("{}", (unit * 1000.0).round() / 1000.0)
+                    };
+                    let layout = paint_ctx
+                        .text()
+                        .new_text_layout(&font, &text)
+                        .build()
+                        .unwrap();
+                    let pos = match self.axis {
+                        Axis::Horizontal => Point::new(along + 2.0, font_size),
+                        Axis::Vertical => Point::new(2.0, along + font_size + 1.0),
+                    };
+                    paint_ctx.draw_text(&layout, pos, &label_color);
+                }
+                index += 1;
+            }
+        }
+
+        // Guides.
+        let guide_color = env.get(theme::PRIMARY_LIGHT);
+        self.guides_lens.with(data, |guides| {
+            for guide in guides.iter() {
+                let along = self.to_ruler(&state, *guide);
+                paint_ctx.stroke(line(along, RULER_SIZE), &guide_color, 2.0);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_steps() {
+        assert_eq!(tick_step(1.0), 50.0);
+        assert_eq!(tick_step(10.0), 5.0);
+        assert_eq!(tick_step(30.0), 2.0);
+        assert_eq!(tick_step(100.0), 0.5);
+    }
+}
@@ -14,7 +14,6 @@
 
 //! A container that scrolls its contents.
 
-use log::error;
 use std::f64::INFINITY;
 use std::time::{Duration, Instant};
 
@@ -22,10 +21,25 @@ use crate::kurbo::{Affine, Point, Rect, RoundedRect, Size, Vec2};
 use crate::piet::RenderContext;
 use crate::theme;
 use crate::{
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, TimerToken,
-    UpdateCtx, Widget, WidgetPod,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, MouseEvent, PaintCtx,
+    PersistView, TimerToken, UpdateCtx, Widget, WidgetPod,
 };
 
+/// How close to the edge of the viewport a drag starts autoscrolling.
+const AUTOSCROLL_MARGIN: f64 = 16.0;
+/// The largest autoscroll step, per tick.
+const AUTOSCROLL_MAX_STEP: f64 = 32.0;
+/// The time between autoscroll steps.
+const AUTOSCROLL_INTERVAL: Duration = Duration::from_millis(30);
+/// Kinetic scrolling stops below this speed, in display points per second.
+const KINETIC_MIN_SPEED: f64 = 20.0;
+/// The time, in seconds, over which the velocity of a drag follows the
+/// pointer: older movement counts for less, so that a pointer held still
+/// before release doesn't fling the content.
+const KINETIC_VELOCITY_TIME: f64 = 0.02;
+/// How quickly content that has bounced past its edge springs back.
+const BOUNCE_STIFFNESS: f64 = 12.0;
+
 #[derive(Debug, Clone)]
 enum ScrollDirection {
     Horizontal,
@@ -79,6 +93,33 @@ struct ScrollBarsState {
     held: BarHeldState,
 }
 
+/// A drag near the edge of the viewport, scrolling the content.
+struct Autoscroll {
+    /// The last mouse move, in the coordinate space of the viewport.
+    event: MouseEvent,
+    timer_id: TimerToken,
+}
+
+/// What kinetic scrolling does at the edges of the content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overscroll {
+    /// Stop at the edge.
+    Clamp,
+    /// Move past the edge, then spring back.
+    Bounce,
+}
+
+/// The state of kinetic scrolling.
+struct Kinetic {
+    overscroll: Overscroll,
+    /// The last position and time of a drag in progress.
+    drag: Option<(Point, Instant)>,
+    /// In display points per second.
+    velocity: Vec2,
+    /// Whether the content is still moving after a drag.
+    animating: bool,
+}
+
 impl Default for ScrollBarsState {
     fn default() -> Self {
         Self {
@@ -95,13 +136,32 @@ impl Default for ScrollBarsState {
 /// This container holds a single child, and uses the wheel to scroll it
 /// when the child's bounds are larger than the viewport.
 ///
-/// The child is laid out with completely unconstrained layout bounds.
+/// Dragging from within the content (for instance, to select text) to near
+/// the edge of the viewport scrolls the content in that direction.
+///
+/// Wheel events go to the innermost scroll container under the pointer.
+/// When it reaches the end of its content, the rest of the movement scrolls
+/// the enclosing container, if there is one.
+///
+/// Painting is clipped to the viewport, and scroll bars are shown while
+/// scrolling, fading out after [`theme::SCROLL_BAR_FADE_DELAY`]. The bars
+/// can also be dragged.
+///
+/// By default the child is laid out with completely unconstrained layout
+/// bounds; [`vertical`] and [`horizontal`] keep it constrained along the
+/// axis that does not scroll.
+///
+/// [`theme::SCROLL_BAR_FADE_DELAY`]: ../theme/constant.SCROLL_BAR_FADE_DELAY.html
+/// [`vertical`]: #method.vertical
+/// [`horizontal`]: #method.horizontal
 pub struct Scroll<T: Data, W: Widget<T>> {
     child: WidgetPod<T, W>,
     child_size: Size,
     scroll_offset: Vec2,
     direction: ScrollDirection,
     scroll_bars: ScrollBarsState,
+    autoscroll: Option<Autoscroll>,
+    kinetic: Option<Kinetic>,
 }
 
 impl<T: Data, W: Widget<T>> Scroll<T, W> {
@@ -117,6 +177,8 @@ impl<T: Data, W: Widget<T>> Scroll<T, W> {
             scroll_offset: Vec2::new(0.0, 0.0),
             direction: ScrollDirection::All,
             scroll_bars: ScrollBarsState::default(),
+            autoscroll: None,
+            kinetic: None,
         }
     }
 
@@ -134,6 +196,25 @@ impl<T: Data, W: Widget<T>> Scroll<T, W> {
         self
     }
 
+    /// Builder-style method to scroll by dragging the content, which keeps
+    /// moving after it is released.
+    ///
+    /// This is for devices and platforms that don't provide momentum
+    /// scrolling themselves. A drag that starts on a child that handles the
+    /// mouse (such as a button) is left to the child. The content slows down
+    /// according to [`theme::SCROLL_FRICTION`].
+    ///
+    /// [`theme::SCROLL_FRICTION`]: ../theme/constant.SCROLL_FRICTION.html
+    pub fn kinetic(mut self, overscroll: Overscroll) -> Self {
+        self.kinetic = Some(Kinetic {
+            overscroll,
+            drag: None,
+            velocity: Vec2::new(0.0, 0.0),
+            animating: false,
+        });
+        self
+    }
+
     /// Returns a reference to the child widget.
     pub fn child(&self) -> &W {
         self.child.widget()
@@ -144,19 +225,162 @@ impl<T: Data, W: Widget<T>> Scroll<T, W> {
         self.child.widget_mut()
     }
 
+    /// The child's pod, for containers that paint the content elsewhere.
+    pub(crate) fn child_pod(&mut self) -> &mut WidgetPod<T, W> {
+        &mut self.child
+    }
+
+    /// The size of the content, as of the last layout.
+    pub(crate) fn child_size(&self) -> Size {
+        self.child_size
+    }
+
     /// Update the scroll.
     ///
     /// Returns `true` if the scroll has been updated.
     pub fn scroll(&mut self, delta: Vec2, size: Size) -> bool {
+        self.scroll_by(delta, size).hypot2() > 1e-12
+    }
+
+    /// Scroll as far as possible by `delta`, returning the change in offset.
+    fn scroll_by(&mut self, delta: Vec2, size: Size) -> Vec2 {
         let mut offset = self.scroll_offset + delta;
         offset.x = offset.x.min(self.child_size.width - size.width).max(0.0);
         offset.y = offset.y.min(self.child_size.height - size.height).max(0.0);
-        if (offset - self.scroll_offset).hypot2() > 1e-12 {
+        let change = offset - self.scroll_offset;
+        if change.hypot2() > 1e-12 {
             self.scroll_offset = offset;
-            true
-        } else {
-            false
         }
+        change
+    }
+
+    /// The largest offset for which the viewport is within the content.
+    fn max_offset(&self, size: Size) -> Vec2 {
+        Vec2::new(
+            (self.child_size.width - size.width).max(0.0),
+            (self.child_size.height - size.height).max(0.0),
+        )
+    }
+
+    /// Move the content with a drag or its momentum, returning the change
+    /// in offset.
+    fn pan(&mut self, delta: Vec2, size: Size, overscroll: Overscroll) -> Vec2 {
+        if overscroll == Overscroll::Clamp {
+            return self.scroll_by(delta, size);
+        }
+        // Past the edge, the content moves at half speed.
+        let max = self.max_offset(size);
+        let axis = |offset: f64, delta: f64, max: f64| {
+            if max <= 0.0 {
+                offset
+            } else if offset < 0.0 || offset > max {
+                offset + delta * 0.5
+            } else {
+                offset + delta
+            }
+        };
+        let before = self.scroll_offset;
+        self.scroll_offset = Vec2::new(
+            axis(before.x, delta.x, max.x),
+            axis(before.y, delta.y, max.y),
+        );
+        self.scroll_offset - before
+    }
+
+    /// Handle a mouse event for dragging the content, if kinetic scrolling
+    /// is enabled.
+    fn kinetic_drag(&mut self, ctx: &mut EventCtx, event: &Event, env: &Env) {
+        let size = ctx.size();
+        let kinetic = match &mut self.kinetic {
+            Some(kinetic) => kinetic,
+            None => return,
+        };
+        match event {
+            Event::MouseDown(mouse) if !ctx.is_handled() && !self.child.has_active() => {
+                ctx.set_active(true);
+                kinetic.drag = Some((mouse.pos, Instant::now()));
+                kinetic.velocity = Vec2::new(0.0, 0.0);
+                kinetic.animating = false;
+            }
+            Event::MouseMoved(mouse) => {
+                let (last_pos, last_time) = match kinetic.drag {
+                    Some(drag) => drag,
+                    None => return,
+                };
+                let now = Instant::now();
+                let overscroll = kinetic.overscroll;
+                kinetic.drag = Some((mouse.pos, now));
+                // The content follows the pointer.
+                let change = self.pan(last_pos - mouse.pos, size, overscroll);
+                let dt = now.duration_since(last_time).as_secs_f64();
+                if dt > 0.0 {
+                    let kinetic = self.kinetic.as_mut().unwrap();
+                    let keep = velocity_decay(dt);
+                    kinetic.velocity = kinetic.velocity * keep + change / dt * (1.0 - keep);
+                }
+                ctx.invalidate();
+            }
+            Event::MouseUp(_) if kinetic.drag.is_some() => {
+                ctx.set_active(false);
+                // The pointer has been still since the last move.
+                let (_, last_time) = kinetic.drag.take().unwrap();
+                let dt = last_time.elapsed().as_secs_f64();
+                kinetic.velocity = kinetic.velocity * velocity_decay(dt);
+                kinetic.animating = true;
+                ctx.request_anim_frame();
+                self.reset_scrollbar_fade(ctx, env);
+            }
+            _ => (),
+        }
+    }
+
+    /// Move the content by its momentum for one animation frame, returning
+    /// `true` if it is still moving.
+    fn kinetic_step(&mut self, interval: u64, size: Size, env: &Env) -> bool {
+        let max = self.max_offset(size);
+        let kinetic = match &mut self.kinetic {
+            Some(kinetic) if kinetic.animating => kinetic,
+            _ => return false,
+        };
+        let overscroll = kinetic.overscroll;
+        let velocity = kinetic.velocity;
+        let dt = interval as f64 * 1e-9;
+        let decay = (-env.get(theme::SCROLL_FRICTION) * dt).exp();
+
+        let change = self.pan(velocity * dt, size, overscroll);
+        let mut velocity = velocity * decay;
+        if overscroll == Overscroll::Clamp && dt > 0.0 {
+            // Stop in any direction where we hit the edge.
+            if change.x.abs() < 1e-6 {
+                velocity.x = 0.0;
+            }
+            if change.y.abs() < 1e-6 {
+                velocity.y = 0.0;
+            }
+        } else if overscroll == Overscroll::Bounce {
+            // Spring back from past the edge.
+            let spring = 1.0 - (-BOUNCE_STIFFNESS * dt).exp();
+            let offset = self.scroll_offset;
+            let target = Vec2::new(offset.x.max(0.0).min(max.x), offset.y.max(0.0).min(max.y));
+            let mut offset = offset + (target - offset) * spring;
+            if target != self.scroll_offset {
+                velocity = velocity * (1.0 - spring);
+            }
+            if (target - offset).hypot() < 0.5 {
+                offset = target;
+            }
+            self.scroll_offset = offset;
+        }
+
+        let in_bounds = self.scroll_offset.x >= 0.0
+            && self.scroll_offset.x <= max.x
+            && self.scroll_offset.y >= 0.0
+            && self.scroll_offset.y <= max.y;
+        let kinetic = self.kinetic.as_mut().unwrap();
+        kinetic.velocity = velocity;
+        // The first frame has no interval, so we always continue from it.
+        kinetic.animating = interval == 0 || velocity.hypot() >= KINETIC_MIN_SPEED || !in_bounds;
+        kinetic.animating
     }
 
     /// Makes the scrollbars visible, and resets the fade timer.
@@ -168,6 +392,52 @@ impl<T: Data, W: Widget<T>> Scroll<T, W> {
         self.scroll_bars.timer_id = ctx.request_timer(deadline);
     }
 
+    /// Start, continue or stop autoscrolling, as a drag in the content
+    /// moves relative to the edges of the viewport.
+    fn update_autoscroll(&mut self, ctx: &mut EventCtx, event: &MouseEvent, viewport: Rect) {
+        if !self.child.has_active() || autoscroll_delta(event.pos, viewport) == Vec2::new(0.0, 0.0)
+        {
+            self.autoscroll = None;
+            return;
+        }
+        match &mut self.autoscroll {
+            Some(autoscroll) => autoscroll.event = event.clone(),
+            None => {
+                let timer_id = ctx.request_timer(Instant::now() + AUTOSCROLL_INTERVAL);
+                self.autoscroll = Some(Autoscroll {
+                    event: event.clone(),
+                    timer_id,
+                });
+            }
+        }
+    }
+
+    /// Scroll one autoscroll step, and move the drag with the content.
+    fn autoscroll_step(&mut self, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        let size = ctx.size();
+        let viewport = Rect::from_origin_size(Point::ORIGIN, size);
+        let mut event = match &self.autoscroll {
+            Some(autoscroll) => autoscroll.event.clone(),
+            None => return,
+        };
+        let change = self.scroll_by(autoscroll_delta(event.pos, viewport), size);
+        if change.hypot2() <= 1e-12 {
+            // We've reached the end of the content.
+            self.autoscroll = None;
+            return;
+        }
+        ctx.invalidate();
+        self.reset_scrollbar_fade(ctx, env);
+
+        event.pos += self.scroll_offset;
+        self.child.event(ctx, &Event::MouseMoved(event), data, env);
+
+        let timer_id = ctx.request_timer(Instant::now() + AUTOSCROLL_INTERVAL);
+        if let Some(autoscroll) = &mut self.autoscroll {
+            autoscroll.timer_id = timer_id;
+        }
+    }
+
     /// Returns the current scroll offset.
     pub fn offset(&self) -> Vec2 {
         self.scroll_offset
@@ -263,6 +533,46 @@ impl<T: Data, W: Widget<T>> Scroll<T, W> {
     }
 }
 
+/// How much of the velocity of a drag is kept after `dt` seconds.
+fn velocity_decay(dt: f64) -> f64 {
+    (-dt / KINETIC_VELOCITY_TIME).exp()
+}
+
+/// The autoscroll step for a drag at `pos`: towards any edge of the
+/// viewport that `pos` is near or beyond, faster the further it is.
+fn autoscroll_delta(pos: Point, viewport: Rect) -> Vec2 {
+    let axis = |pos: f64, min: f64, max: f64| {
+        let step = if pos < min + AUTOSCROLL_MARGIN {
+            pos - (min + AUTOSCROLL_MARGIN)
+        } else if pos > max - AUTOSCROLL_MARGIN {
+            pos - (max - AUTOSCROLL_MARGIN)
+        } else {
+            0.0
+        };
+        step.max(-AUTOSCROLL_MAX_STEP).min(AUTOSCROLL_MAX_STEP)
+    };
+    Vec2::new(
+        axis(pos.x, viewport.x0, viewport.x1),
+        axis(pos.y, viewport.y0, viewport.y1),
+    )
+}
+
+/// The view state of a scroll container is its offset.
+impl<T: Data, W: Widget<T>> PersistView for Scroll<T, W> {
+    type State = (f64, f64);
+
+    fn view_state(&self) -> (f64, f64) {
+        (self.scroll_offset.x, self.scroll_offset.y)
+    }
+
+    fn restore_view_state(&mut self, (x, y): (f64, f64)) {
+        if x.is_finite() && y.is_finite() {
+            // Layout clamps the offset to the content.
+            self.scroll_offset = Vec2::new(x, y);
+        }
+    }
+}
+
 impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
         let size = ctx.size();
@@ -344,9 +654,20 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
             if let Some(child_event) = child_event {
                 self.child.event(ctx, &child_event, data, env)
             };
+            self.kinetic_drag(ctx, event, env);
+            if let Event::AnimFrame(interval) = event {
+                let was_animating = self.kinetic.as_ref().map_or(false, |k| k.animating);
+                if self.kinetic_step(*interval, size, env) {
+                    ctx.request_anim_frame();
+                }
+                if was_animating {
+                    ctx.invalidate();
+                }
+            }
 
             match event {
                 Event::MouseMoved(event) => {
+                    self.update_autoscroll(ctx, event, viewport);
                     let mut transformed_event = event.clone();
                     transformed_event.pos += self.scroll_offset;
                     let pos = transformed_event.pos;
@@ -370,22 +691,33 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
                         ctx.request_anim_frame();
                     }
                 }
+                Event::MouseUp(_) => self.autoscroll = None,
                 Event::Timer(id) if *id == self.scroll_bars.timer_id => {
                     // Schedule scroll bars animation
                     ctx.request_anim_frame();
                     self.scroll_bars.timer_id = TimerToken::INVALID;
                 }
+                Event::Timer(id) if self.autoscroll.as_ref().map(|a| a.timer_id) == Some(*id) => {
+                    self.autoscroll_step(ctx, data, env);
+                }
                 _ => (),
             }
         }
 
+        // Scroll by whatever a nested scroll area didn't use; the rest is
+        // left for the enclosing one.
         if !ctx.is_handled() {
             if let Event::Wheel(wheel) = event {
-                if self.scroll(wheel.delta, size) {
+                let remaining = ctx.wheel_remaining(wheel);
+                let change = self.scroll_by(remaining, size);
+                if change.hypot2() > 1e-12 {
+                    ctx.consume_wheel(change);
                     ctx.invalidate();
-                    ctx.set_handled();
                     self.reset_scrollbar_fade(ctx, &env);
                 }
+                if (remaining - change).hypot2() <= 1e-12 {
+                    ctx.set_handled();
+                }
             }
         }
     }
@@ -403,26 +735,28 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
         self.child
             .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
         let self_size = bc.constrain(Size::new(100.0, 100.0));
-        let _ = self.scroll(Vec2::new(0.0, 0.0), self_size);
+        // Content being dragged or moving by its momentum may be past its
+        // edge for now.
+        let kinetic_moving = match &self.kinetic {
+            Some(kinetic) => kinetic.drag.is_some() || kinetic.animating,
+            None => false,
+        };
+        if !kinetic_moving {
+            let _ = self.scroll(Vec2::new(0.0, 0.0), self_size);
+        }
         self_size
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
-        if let Err(e) = paint_ctx.save() {
-            error!("saving render context failed: {:?}", e);
-            return;
-        }
         let viewport = Rect::from_origin_size(Point::ORIGIN, base_state.size());
-        paint_ctx.clip(viewport);
-        paint_ctx.transform(Affine::translate(-self.scroll_offset));
-
-        let visible = viewport.with_origin(self.scroll_offset.to_point());
-        paint_ctx.with_child_ctx(visible, |ctx| self.child.paint(ctx, data, env));
-
-        self.draw_bars(paint_ctx, viewport, env);
-
-        if let Err(e) = paint_ctx.restore() {
-            error!("restoring render context failed: {:?}", e);
-        }
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(viewport);
+            ctx.transform_save(Affine::translate(-self.scroll_offset), |ctx| {
+                let visible = viewport.with_origin(self.scroll_offset.to_point());
+                ctx.with_child_ctx(visible, |ctx| self.child.paint(ctx, data, env));
+
+                self.draw_bars(ctx, viewport, env);
+            });
+        });
     }
 }
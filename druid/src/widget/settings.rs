@@ -0,0 +1,404 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A preferences pane, with categories and search.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayoutBuilder};
+use crate::theme;
+use crate::widget::{TextBox, WidgetExt};
+use crate::{
+    BaseState, BoxConstraints, BoxedWidget, Data, Env, Event, EventCtx, LayoutCtx, Lens, LifeCycle,
+    PaintCtx, Store, UpdateCtx, Widget, WidgetPod,
+};
+
+const SIDEBAR_WIDTH: f64 = 160.0;
+const LABEL_WIDTH: f64 = 200.0;
+const CATEGORY_HEIGHT: f64 = 24.0;
+const ROW_HEIGHT: f64 = 28.0;
+const PADDING: f64 = 8.0;
+
+/// A preferences pane: a sidebar of categories, with a search field, and
+/// the settings of the selected category beside it.
+///
+/// Each setting is declared with a key, a label, a widget and a lens to
+/// its value in the data. While the search field is not empty, the settings
+/// of every category whose label or key contains the search text are shown
+/// instead of the selected category.
+///
+/// Settings are saved in the application's [`Store`], under their key
+/// prefixed with `settings.`: the saved values are read into the data when
+/// the pane is added to a window, and each value is saved when it changes.
+/// Keys should be stable, so that settings saved by an earlier run are found.
+/// An application that shows the pane in its own window can call [`restore`]
+/// at launch, so that the settings apply before the window is opened.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Checkbox, Settings, Slider};
+/// use druid::{Data, Lens};
+///
+/// #[derive(Clone, Data, Lens)]
+/// struct Preferences {
+///     autosave: bool,
+///     volume: f64,
+/// }
+///
+/// let settings = Settings::new()
+///     .category("General")
+///     .setting(
+///         "general.autosave",
+///         "Save automatically",
+///         Checkbox::new(),
+///         Preferences::autosave,
+///     )
+///     .category("Sound")
+///     .setting("sound.volume", "Volume", Slider::new(), Preferences::volume);
+/// # let _: Settings<Preferences> = settings;
+/// ```
+///
+/// [`Store`]: ../struct.Store.html
+/// [`restore`]: #method.restore
+pub struct Settings<T: Data> {
+    categories: Vec<Category<T>>,
+    selected: usize,
+    search: BoxedWidget<String>,
+    search_text: String,
+    /// The top of the category list, below the search field.
+    categories_top: f64,
+    /// The settings shown, as category and setting indices, with the
+    /// rectangle of their row.
+    rows: Vec<(usize, usize, Rect)>,
+}
+
+struct Category<T: Data> {
+    name: String,
+    settings: Vec<Setting<T>>,
+}
+
+struct Setting<T: Data> {
+    key: String,
+    label: String,
+    widget: BoxedWidget<T>,
+    /// Read the saved value, if any, into the data.
+    restore: Box<dyn Fn(&Store, &mut T)>,
+    /// Save the value in the data.
+    save: Box<dyn Fn(&Store, &T)>,
+}
+
+impl<T: Data + 'static> Settings<T> {
+    /// Create a new, empty settings pane.
+    pub fn new() -> Self {
+        Settings {
+            categories: Vec::new(),
+            selected: 0,
+            search: WidgetPod::new(TextBox::with_placeholder("Search")).boxed(),
+            search_text: String::new(),
+            categories_top: 0.0,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Builder-style method to add a category. Settings added after it
+    /// belong to it.
+    pub fn category(mut self, name: impl Into<String>) -> Self {
+        self.categories.push(Category {
+            name: name.into(),
+            settings: Vec::new(),
+        });
+        self
+    }
+
+    /// Builder-style method to add a setting to the last category, edited
+    /// by `widget` through `lens`.
+    ///
+    /// If no category has been added yet, one named "General" is created.
+    /// The value is saved in the application's [`Store`] under `key`.
+    ///
+    /// [`Store`]: ../struct.Store.html
+    pub fn setting<U, L, W>(
+        mut self,
+        key: impl Into<String>,
+        label: impl Into<String>,
+        widget: W,
+        lens: L,
+    ) -> Self
+    where
+        U: Data + Serialize + DeserializeOwned + 'static,
+        L: Lens<T, U> + Clone + 'static,
+        W: Widget<U> + 'static,
+    {
+        if self.categories.is_empty() {
+            self = self.category("General");
+        }
+        let key = key.into();
+        let store_key = format!("settings.{}", key);
+        let restore = {
+            let (lens, store_key) = (lens.clone(), store_key.clone());
+            move |store: &Store, data: &mut T| {
+                if let Some(value) = store.get::<U>(&store_key) {
+                    lens.with_mut(data, |field| *field = value);
+                }
+            }
+        };
+        let save = {
+            let lens = lens.clone();
+            move |store: &Store, data: &T| lens.with(data, |field| store.set(&store_key, field))
+        };
+        let setting = Setting {
+            key,
+            label: label.into(),
+            widget: WidgetPod::new(Box::new(widget.lens(lens)) as Box<dyn Widget<T>>),
+            restore: Box::new(restore),
+            save: Box::new(save),
+        };
+        self.categories.last_mut().unwrap().settings.push(setting);
+        self
+    }
+
+    /// The keys of all the settings, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.settings().map(|setting| setting.key.as_str())
+    }
+
+    /// Read the settings saved in a store into the data.
+    ///
+    /// The pane does this itself when it is added to a window.
+    pub fn restore(&self, store: &Store, data: &mut T) {
+        for setting in self.settings() {
+            (setting.restore)(store, data);
+        }
+    }
+}
+
+impl<T: Data + 'static> Default for Settings<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Data> Settings<T> {
+    fn settings(&self) -> impl Iterator<Item = &Setting<T>> {
+        self.categories
+            .iter()
+            .flat_map(|category| category.settings.iter())
+    }
+
+    /// The settings to show, as category and setting indices.
+    fn visible(&self) -> Vec<(usize, usize)> {
+        let query = self.search_text.trim().to_lowercase();
+        if query.is_empty() {
+            return match self.categories.get(self.selected) {
+                Some(category) => (0..category.settings.len())
+                    .map(|index| (self.selected, index))
+                    .collect(),
+                None => Vec::new(),
+            };
+        }
+        let mut visible = Vec::new();
+        for (category_index, category) in self.categories.iter().enumerate() {
+            for (index, setting) in category.settings.iter().enumerate() {
+                if setting_matches(&setting.key, &setting.label, &query) {
+                    visible.push((category_index, index));
+                }
+            }
+        }
+        visible
+    }
+
+    /// The category at a point in the sidebar, if any.
+    fn category_at(&self, pos: Point, env: &Env) -> Option<usize> {
+        if pos.x < 0.0 || pos.x >= SIDEBAR_WIDTH || pos.y < self.categories_top {
+            return None;
+        }
+        let category_height = theme::spacing(env, CATEGORY_HEIGHT);
+        let index = ((pos.y - self.categories_top) / category_height) as usize;
+        if index < self.categories.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether a setting matches a lowercase search query.
+fn setting_matches(key: &str, label: &str, query: &str) -> bool {
+    key.to_lowercase().contains(query) || label.to_lowercase().contains(query)
+}
+
+impl<T: Data> Widget<T> for Settings<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::LifeCycle(LifeCycle::WidgetAdded) = event {
+            for setting in self.settings() {
+                (setting.restore)(ctx.store(), data);
+            }
+        }
+        if let Event::MouseDown(mouse) = event {
+            if let Some(index) = self.category_at(mouse.pos, env) {
+                self.selected = index;
+                self.search_text.clear();
+                ctx.invalidate();
+                ctx.set_handled();
+                return;
+            }
+        }
+
+        let old_search = self.search_text.clone();
+        self.search.event(ctx, event, &mut self.search_text, env);
+        if self.search_text != old_search {
+            ctx.invalidate();
+        }
+
+        for &(category, index, _) in &self.rows {
+            let setting = &mut self.categories[category].settings[index];
+            setting.widget.event(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        if !old_data.map_or(true, |old| old.same(data)) {
+            for setting in self.settings() {
+                (setting.save)(ctx.store(), data);
+            }
+        }
+        self.search.update(ctx, &self.search_text, env);
+        for category in &mut self.categories {
+            for setting in &mut category.settings {
+                setting.widget.update(ctx, data, env);
+            }
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Settings");
+
+        let padding = theme::spacing(env, PADDING);
+        let row_height = theme::spacing(env, ROW_HEIGHT);
+        let category_height = theme::spacing(env, CATEGORY_HEIGHT);
+
+        let search_bc = BoxConstraints::new(
+            Size::new(SIDEBAR_WIDTH - 2.0 * padding, 0.0),
+            Size::new(SIDEBAR_WIDTH - 2.0 * padding, std::f64::INFINITY),
+        );
+        let search_size = self
+            .search
+            .layout(layout_ctx, &search_bc, &self.search_text, env);
+        self.search
+            .set_layout_rect(Rect::from_origin_size((padding, padding), search_size));
+        self.categories_top = search_size.height + 2.0 * padding;
+
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            bc.min().width.max(SIDEBAR_WIDTH + LABEL_WIDTH + 200.0)
+        };
+        let widget_x = SIDEBAR_WIDTH + padding + LABEL_WIDTH;
+        let widget_width = (width - widget_x - padding).max(0.0);
+
+        self.rows.clear();
+        let mut y = padding;
+        for (category, index) in self.visible() {
+            let setting = &mut self.categories[category].settings[index];
+            let widget_bc = BoxConstraints::new(
+                Size::new(0.0, 0.0),
+                Size::new(widget_width, std::f64::INFINITY),
+            );
+            let size = setting.widget.layout(layout_ctx, &widget_bc, data, env);
+            let height = size.height.max(row_height);
+            let origin = Point::new(widget_x, y + (height - size.height) / 2.0);
+            setting
+                .widget
+                .set_layout_rect(Rect::from_origin_size(origin, size));
+            let row = Rect::new(SIDEBAR_WIDTH, y, width, y + height);
+            self.rows.push((category, index, row));
+            y += height;
+        }
+
+        let sidebar_height = self.categories_top + category_height * self.categories.len() as f64;
+        let height = if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            y.max(sidebar_height) + padding
+        };
+        bc.constrain(Size::new(width, height))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let size = base_state.size();
+        let sidebar = Rect::new(0.0, 0.0, SIDEBAR_WIDTH, size.height);
+        paint_ctx.fill(sidebar, &env.get(theme::BACKGROUND_DARK));
+
+        let padding = theme::spacing(env, PADDING);
+        let category_height = theme::spacing(env, CATEGORY_HEIGHT);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+            .build()
+            .unwrap();
+        let label_color = env.get(theme::LABEL_COLOR);
+        let searching = !self.search_text.trim().is_empty();
+
+        for (index, category) in self.categories.iter().enumerate() {
+            let top = self.categories_top + index as f64 * category_height;
+            if index == self.selected && !searching {
+                let rect = Rect::new(0.0, top, SIDEBAR_WIDTH, top + category_height);
+                paint_ctx.fill(rect, &env.get(theme::PRIMARY_DARK));
+            }
+            let layout = paint_ctx
+                .text()
+                .new_text_layout(&font, &category.name)
+                .build()
+                .unwrap();
+            let baseline = top + (category_height + font_size) / 2.0 - 2.0;
+            paint_ctx.draw_text(&layout, (padding, baseline), &label_color);
+        }
+        self.search
+            .paint_with_offset(paint_ctx, &self.search_text, env);
+
+        for &(category, index, row) in &self.rows {
+            let setting = &mut self.categories[category].settings[index];
+            let layout = paint_ctx
+                .text()
+                .new_text_layout(&font, &setting.label)
+                .build()
+                .unwrap();
+            let baseline = row.y0 + (row.height() + font_size) / 2.0 - 2.0;
+            paint_ctx.draw_text(&layout, (row.x0 + padding, baseline), &label_color);
+            setting.widget.paint_with_offset(paint_ctx, data, env);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_matches_key_or_label() {
+        assert!(setting_matches("editor.tab_width", "Tab width", "tab"));
+        assert!(setting_matches("editor.tab_width", "Tab width", "editor."));
+        assert!(!setting_matches("editor.tab_width", "Tab width", "font"));
+    }
+}
@@ -19,21 +19,65 @@ use crate::piet::{LinearGradient, RenderContext, UnitPoint};
 use crate::theme;
 use crate::widget::Align;
 use crate::{
-    BaseState, BoxConstraints, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    BaseState, BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget,
 };
 
 /// A slider, allowing interactive update of a numeric value.
-#[derive(Debug, Clone, Default)]
+///
+/// The value is between 0 and 1 unless another range is given with
+/// [`with_range`]; values outside the range are shown at its ends.
+///
+/// [`with_range`]: #method.with_range
+#[derive(Debug, Clone)]
 pub struct Slider {
+    min: f64,
+    max: f64,
+    step: Option<f64>,
     knob_pos: Point,
     knob_hovered: bool,
     x_offset: f64,
 }
 
+impl Default for Slider {
+    fn default() -> Self {
+        Slider {
+            min: 0.0,
+            max: 1.0,
+            step: None,
+            knob_pos: Point::ORIGIN,
+            knob_hovered: false,
+            x_offset: 0.0,
+        }
+    }
+}
+
 impl Slider {
     pub fn new() -> impl Widget<f64> {
         Align::vertical(UnitPoint::CENTER, Self::default())
     }
+
+    /// Create a slider for values from `min` to `max`.
+    ///
+    /// If there is a `step`, dragging the knob moves the value in multiples
+    /// of it from `min`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::Slider;
+    ///
+    /// let volume = Slider::with_range(0.0, 11.0, Some(1.0));
+    /// ```
+    pub fn with_range(min: f64, max: f64, step: Option<f64>) -> impl Widget<f64> {
+        let slider = Slider {
+            min,
+            max,
+            step: step.filter(|step| *step > 0.0),
+            ..Default::default()
+        };
+        Align::vertical(UnitPoint::CENTER, slider)
+    }
 }
 
 impl Slider {
@@ -43,9 +87,40 @@ impl Slider {
     }
 
     fn calculate_value(&self, mouse_x: f64, knob_width: f64, slider_width: f64) -> f64 {
-        ((mouse_x + self.x_offset - knob_width / 2.) / (slider_width - knob_width))
+        let fraction = ((mouse_x + self.x_offset - knob_width / 2.) / (slider_width - knob_width))
             .max(0.0)
-            .min(1.0)
+            .min(1.0);
+        self.snap(self.min + fraction * (self.max - self.min))
+    }
+
+    /// Round a value in the range to the nearest step.
+    fn snap(&self, value: f64) -> f64 {
+        match self.step {
+            Some(step) => {
+                let snapped = self.min + ((value - self.min) / step).round() * step;
+                snapped
+                    .max(self.min.min(self.max))
+                    .min(self.max.max(self.min))
+            }
+            None => value,
+        }
+    }
+
+    /// The value after pressing an arrow key in `direction`: one step, or
+    /// a twentieth of the range if there are no steps.
+    fn key_stepped(&self, value: f64, direction: f64) -> f64 {
+        let step = self.step.unwrap_or((self.max - self.min).abs() / 20.0);
+        let next = value + direction * step;
+        self.snap(next.max(self.min.min(self.max)).min(self.max.max(self.min)))
+    }
+
+    /// How far along the range a value is, from 0 to 1.
+    fn fraction(&self, value: f64) -> f64 {
+        let span = self.max - self.min;
+        if span == 0.0 {
+            return 0.0;
+        }
+        ((value - self.min) / span).max(0.0).min(1.0)
     }
 }
 
@@ -54,6 +129,20 @@ impl Widget<f64> for Slider {
         let knob_size = env.get(theme::BASIC_WIDGET_HEIGHT);
         let slider_width = ctx.size().width;
 
+        match event {
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
+            Event::FocusChanged(_) => ctx.invalidate(),
+            _ => (),
+        }
+
+        if env.get(theme::READ_ONLY) {
+            if let Event::MouseMoved(mouse) = event {
+                self.knob_hovered = ctx.is_hot() && self.knob_hit_test(knob_size, mouse.pos);
+                ctx.invalidate();
+            }
+            return;
+        }
+
         match event {
             Event::MouseDown(mouse) => {
                 ctx.set_active(true);
@@ -85,6 +174,16 @@ impl Widget<f64> for Slider {
                 }
                 ctx.invalidate();
             }
+            Event::KeyDown(key_event) if ctx.has_focus() => {
+                let direction = match key_event.key_code {
+                    KeyCode::ArrowRight | KeyCode::ArrowUp => 1.0,
+                    KeyCode::ArrowLeft | KeyCode::ArrowDown => -1.0,
+                    _ => return,
+                };
+                *data = self.key_stepped(*data, direction);
+                ctx.set_handled();
+                ctx.invalidate();
+            }
             _ => (),
         }
     }
@@ -118,7 +217,7 @@ impl Widget<f64> for Slider {
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &f64, env: &Env) {
-        let clamped = data.max(0.0).min(1.0);
+        let clamped = self.fraction(*data);
         let rect = Rect::from_origin_size(Point::ORIGIN, base_state.size());
         let knob_size = env.get(theme::BASIC_WIDGET_HEIGHT);
         let track_thickness = 4.;
@@ -175,15 +274,52 @@ impl Widget<f64> for Slider {
         };
 
         //Paint the border
-        let border_color = if is_hovered || is_active {
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else if is_hovered || is_active {
             env.get(theme::FOREGROUND_LIGHT)
         } else {
             env.get(theme::FOREGROUND_DARK)
         };
 
+        if env.get(theme::HIGH_CONTRAST) {
+            // A flat knob from the forced palette.
+            let border = if base_state.has_focus() || is_hovered || is_active {
+                env.get(theme::FORCED_HIGHLIGHT)
+            } else {
+                env.get(theme::FORCED_BUTTON_TEXT)
+            };
+            paint_ctx.stroke(knob_circle, &border, 2.);
+            paint_ctx.fill(knob_circle, &env.get(theme::FORCED_BUTTON_FACE));
+            return;
+        }
+
         paint_ctx.stroke(knob_circle, &border_color, 2.);
 
         //Actually paint the knob
         paint_ctx.fill(knob_circle, &knob_gradient);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_in_range_and_steps() {
+        let slider = Slider {
+            min: 10.0,
+            max: 20.0,
+            step: Some(2.5),
+            ..Default::default()
+        };
+        // A 110 wide slider with a 10 wide knob has 100 points of travel.
+        assert_eq!(slider.calculate_value(5.0, 10.0, 110.0), 10.0);
+        assert_eq!(slider.calculate_value(60.0, 10.0, 110.0), 15.0);
+        assert_eq!(slider.calculate_value(66.0, 10.0, 110.0), 15.0);
+        assert_eq!(slider.calculate_value(70.0, 10.0, 110.0), 17.5);
+        assert_eq!(slider.calculate_value(500.0, 10.0, 110.0), 20.0);
+        assert_eq!(slider.fraction(12.5), 0.25);
+        assert_eq!(slider.fraction(0.0), 0.0);
+    }
+}
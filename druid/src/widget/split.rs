@@ -19,7 +19,7 @@ use crate::piet::RenderContext;
 use crate::widget::flex::Axis;
 use crate::{
     theme, BaseState, BoxConstraints, Cursor, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx,
-    UpdateCtx, Widget, WidgetPod,
+    PersistView, UpdateCtx, Widget, WidgetPod,
 };
 
 ///A container containing two other widgets, splitting the area either horizontally or vertically.
@@ -124,6 +124,19 @@ impl<T: Data> Split<T> {
         }
     }
 }
+/// The view state of a split is its split point.
+impl<T: Data> PersistView for Split<T> {
+    type State = f64;
+
+    fn view_state(&self) -> f64 {
+        self.split_point
+    }
+    fn restore_view_state(&mut self, split_point: f64) {
+        if split_point > 0.0 && split_point < 1.0 {
+            self.split_point = split_point;
+        }
+    }
+}
 impl<T: Data> Widget<T> for Split<T> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
         if self.child1.is_active() {
@@ -171,11 +184,18 @@ impl<T: Data> Widget<T> for Split<T> {
                 _ => {}
             }
         }
-        if !self.child1.is_active() {
-            self.child1.event(ctx, event, data, env);
+        // The mouse goes to the child painted on top first.
+        let raised = event.is_pointer_event() && self.child2.z_index() > self.child1.z_index();
+        let (first, second) = if raised {
+            (&mut self.child2, &mut self.child1)
+        } else {
+            (&mut self.child1, &mut self.child2)
+        };
+        if !first.is_active() {
+            first.event(ctx, event, data, env);
         }
-        if !self.child2.is_active() {
-            self.child2.event(ctx, event, data, env);
+        if !second.is_active() {
+            second.event(ctx, event, data, env);
         }
     }
 
@@ -303,7 +323,12 @@ impl<T: Data> Widget<T> for Split<T> {
         paint_ctx.stroke(line1, &line_color, 1.0);
         paint_ctx.stroke(line2, &line_color, 1.0);
 
-        self.child1.paint_with_offset(paint_ctx, &data, env);
-        self.child2.paint_with_offset(paint_ctx, &data, env);
+        if self.child1.z_index() > self.child2.z_index() {
+            self.child2.paint_with_offset(paint_ctx, &data, env);
+            self.child1.paint_with_offset(paint_ctx, &data, env);
+        } else {
+            self.child1.paint_with_offset(paint_ctx, &data, env);
+            self.child2.paint_with_offset(paint_ctx, &data, env);
+        }
     }
 }
@@ -0,0 +1,310 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A pair of buttons that step a number up and down.
+
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{BezPath, Line, Point, Rect, Size};
+use crate::piet::RenderContext;
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, PaintCtx,
+    TimerToken, UpdateCtx, Widget,
+};
+
+/// The width of the stepper.
+const STEPPER_WIDTH: f64 = 16.0;
+/// How long a button is held before the value starts repeating.
+const INITIAL_DELAY: Duration = Duration::from_millis(500);
+/// How often the value changes while a button is held.
+const REPEAT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Increment and decrement buttons for a number.
+///
+/// Clicking the upper button adds the step to the value and clicking the
+/// lower one subtracts it; holding either down repeats this. The value is
+/// kept from `min` to `max`, or wraps around from one to the other if
+/// [`wrap`] is set.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::Stepper;
+///
+/// let hours = Stepper::new().min(0.0).max(23.0).wrap(true);
+/// ```
+///
+/// [`wrap`]: #method.wrap
+#[derive(Debug, Clone)]
+pub struct Stepper {
+    min: f64,
+    max: f64,
+    step: f64,
+    wrap: bool,
+    /// The direction of the held button, 1 for up and -1 for down.
+    held: Option<f64>,
+    timer_id: TimerToken,
+}
+
+impl Stepper {
+    /// Create a stepper with no bounds and a step of 1.
+    pub fn new() -> Stepper {
+        Stepper {
+            min: std::f64::MIN,
+            max: std::f64::MAX,
+            step: 1.0,
+            wrap: false,
+            held: None,
+            timer_id: TimerToken::INVALID,
+        }
+    }
+
+    /// Builder-style method to set the smallest value.
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Builder-style method to set the largest value.
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = max;
+        self
+    }
+
+    /// Builder-style method to set how much each click changes the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is not greater than zero.
+    pub fn step(mut self, step: f64) -> Self {
+        assert!(step > 0.0, "stepper step {} is not positive", step);
+        self.step = step;
+        self
+    }
+
+    /// Builder-style method to wrap around at the ends of the range instead
+    /// of stopping.
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// The value after stepping `value` in `direction`.
+    fn stepped(&self, value: f64, direction: f64) -> f64 {
+        let next = value + direction * self.step;
+        if next > self.max {
+            if self.wrap && value >= self.max {
+                self.min
+            } else {
+                self.max
+            }
+        } else if next < self.min {
+            if self.wrap && value <= self.min {
+                self.max
+            } else {
+                self.min
+            }
+        } else {
+            next
+        }
+    }
+
+    /// Whether stepping in `direction` can't change the value.
+    fn at_bound(&self, value: f64, direction: f64) -> bool {
+        if self.wrap {
+            false
+        } else if direction > 0.0 {
+            value >= self.max
+        } else {
+            value <= self.min
+        }
+    }
+}
+
+impl Default for Stepper {
+    fn default() -> Self {
+        Stepper::new()
+    }
+}
+
+impl Widget<f64> for Stepper {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut f64, env: &Env) {
+        match event {
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
+            Event::FocusChanged(_) => ctx.invalidate(),
+            _ => (),
+        }
+        if env.get(theme::READ_ONLY) {
+            return;
+        }
+        match event {
+            Event::MouseDown(mouse) => {
+                let direction = if mouse.pos.y < ctx.size().height / 2.0 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                ctx.set_active(true);
+                self.held = Some(direction);
+                *data = self.stepped(*data, direction);
+                self.timer_id = ctx.request_timer(Instant::now() + INITIAL_DELAY);
+                ctx.invalidate();
+            }
+            Event::MouseUp(_) => {
+                if ctx.is_active() {
+                    ctx.set_active(false);
+                    self.held = None;
+                    self.timer_id = TimerToken::INVALID;
+                    ctx.invalidate();
+                }
+            }
+            Event::Timer(id) if *id == self.timer_id => {
+                if let Some(direction) = self.held {
+                    *data = self.stepped(*data, direction);
+                    self.timer_id = ctx.request_timer(Instant::now() + REPEAT_INTERVAL);
+                    ctx.invalidate();
+                }
+            }
+            Event::KeyDown(key_event) if ctx.has_focus() => {
+                let direction = match key_event.key_code {
+                    KeyCode::ArrowUp => 1.0,
+                    KeyCode::ArrowDown => -1.0,
+                    _ => return,
+                };
+                *data = self.stepped(*data, direction);
+                ctx.set_handled();
+                ctx.invalidate();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&f64>, _data: &f64, _env: &Env) {
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &f64,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Stepper");
+        bc.constrain(Size::new(
+            STEPPER_WIDTH,
+            env.get(theme::BORDERED_WIDGET_HEIGHT),
+        ))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &f64, env: &Env) {
+        let size = base_state.size();
+        let high_contrast = env.get(theme::HIGH_CONTRAST);
+        let outline = Rect::new(0.5, 0.5, size.width - 0.5, size.height - 0.5);
+        paint_ctx.fill(outline, &env.get(theme::BUTTON_LIGHT));
+        let middle = size.height / 2.0;
+        if let Some(direction) = self.held {
+            let pressed = if direction > 0.0 {
+                Rect::new(0.0, 0.0, size.width, middle)
+            } else {
+                Rect::new(0.0, middle, size.width, size.height)
+            };
+            // In high contrast mode both button colors are the same face,
+            // so the pressed button is drawn as highlighted.
+            let pressed_color = if high_contrast {
+                env.get(theme::FORCED_HIGHLIGHT)
+            } else {
+                env.get(theme::BUTTON_DARK)
+            };
+            paint_ctx.fill(pressed, &pressed_color);
+        }
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else {
+            env.get(theme::BORDER)
+        };
+        paint_ctx.stroke(outline, &border_color, 1.0);
+        paint_ctx.stroke(
+            Line::new((0.0, middle), (size.width, middle)),
+            &env.get(theme::BORDER),
+            1.0,
+        );
+
+        let (enabled, pressed, disabled) = if high_contrast {
+            (
+                env.get(theme::FORCED_BUTTON_TEXT),
+                env.get(theme::FORCED_HIGHLIGHT_TEXT),
+                env.get(theme::FORCED_DISABLED_TEXT),
+            )
+        } else {
+            (
+                env.get(theme::LABEL_COLOR),
+                env.get(theme::LABEL_COLOR),
+                env.get(theme::PLACEHOLDER_COLOR),
+            )
+        };
+        let center_x = size.width / 2.0;
+        let arrow = size.width / 4.0;
+        for &direction in &[1.0, -1.0] {
+            let center_y = if direction > 0.0 {
+                middle / 2.0
+            } else {
+                middle * 1.5
+            };
+            let half = direction * arrow / 2.0;
+            let mut path = BezPath::new();
+            path.move_to(Point::new(center_x, center_y - half));
+            path.line_to(Point::new(center_x + arrow, center_y + half));
+            path.line_to(Point::new(center_x - arrow, center_y + half));
+            path.close_path();
+            let color = if self.at_bound(*data, direction) {
+                &disabled
+            } else if self
+                .held
+                .map_or(false, |held| (held > 0.0) == (direction > 0.0))
+            {
+                &pressed
+            } else {
+                &enabled
+            };
+            paint_ctx.fill(path, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_and_wraps() {
+        let stepper = Stepper::new().min(0.0).max(10.0).step(4.0);
+        assert_eq!(stepper.stepped(4.0, 1.0), 8.0);
+        assert_eq!(stepper.stepped(8.0, 1.0), 10.0);
+        assert_eq!(stepper.stepped(10.0, 1.0), 10.0);
+        assert_eq!(stepper.stepped(2.0, -1.0), 0.0);
+
+        let stepper = stepper.wrap(true);
+        assert_eq!(stepper.stepped(8.0, 1.0), 10.0);
+        assert_eq!(stepper.stepped(10.0, 1.0), 0.0);
+        assert_eq!(stepper.stepped(0.0, -1.0), 10.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_step() {
+        Stepper::new().step(0.0);
+    }
+}
@@ -26,16 +26,47 @@ use usvg;
 use crate::{
     kurbo::BezPath,
     piet::{Color, RenderContext},
-    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, Point, Size,
-    UpdateCtx, Widget,
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, Point, Raster,
+    Size, UpdateCtx, Widget,
 };
 
+/// The number of sizes for which an `Svg` keeps a raster.
+const CACHE_SIZE: usize = 4;
+
 /// A widget that renders a SVG
+///
+/// The SVG is rendered into a [`Raster`] for the size of the widget and the
+/// scale of the window the first time it is painted, and the raster is
+/// drawn until either changes. Rasters for the few most recently used sizes
+/// are kept, so that switching between them (for instance when a window
+/// moves between monitors) doesn't render the SVG again.
+///
+/// [`Raster`]: ../struct.Raster.html
 pub struct Svg<T> {
     svg_data: SvgData,
+    /// Rendered rasters, most recently used first.
+    cache: Vec<(CacheKey, Raster)>,
     phantom: PhantomData<T>,
 }
 
+/// The width, height, window scale, and SVG a raster was rendered for.
+#[derive(Clone, Copy, PartialEq)]
+struct CacheKey {
+    width: u64,
+    height: u64,
+    scale: u64,
+    /// The address of the SVG's tree, which is shared by clones of the
+    /// same `SvgData`.
+    content: usize,
+}
+
+/// A path of an SVG, transformed and ready to paint.
+struct PreparedPath {
+    path: BezPath,
+    fill: Option<Color>,
+    stroke: Option<(Color, f64)>,
+}
+
 impl<T: Data> Svg<T> {
     /// Create an SVG-drawing widget from SvgData.
     ///
@@ -43,10 +74,22 @@ impl<T: Data> Svg<T> {
     pub fn new(svg_data: SvgData) -> impl Widget<T> {
         Svg {
             svg_data,
+            cache: Vec::new(),
             phantom: Default::default(),
         }
     }
 
+    /// The paths of the SVG, scaled to fit `size`.
+    fn paths_for_size(&self, size: Size) -> Vec<PreparedPath> {
+        //TODO: options for aspect ratio or scaling based on height
+        let svg_size = self.get_size();
+        let scale = size.width / svg_size.width;
+        let origin_x = (size.width - (svg_size.width * scale)) / 2.0;
+        let origin_y = (size.height - (svg_size.height * scale)) / 2.0;
+        let origin = Point::new(origin_x, origin_y);
+        self.svg_data.prepare_paths(scale, origin)
+    }
+
     /// Measure the SVG's size
     #[allow(clippy::needless_return)]
     fn get_size(&self) -> Size {
@@ -86,14 +129,27 @@ impl<T: Data> Widget<T> for Svg<T> {
         }
     }
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, _data: &T, _env: &Env) {
-        //TODO: options for aspect ratio or scaling based on height
-        let scale = base_state.size().width / self.get_size().width;
-
-        let origin_x = (base_state.size().width - (self.get_size().width * scale)) / 2.0;
-        let origin_y = (base_state.size().height - (self.get_size().height * scale)) / 2.0;
-        let origin = Point::new(origin_x, origin_y);
-
-        self.svg_data.to_piet(scale, origin, paint_ctx);
+        let size = base_state.size();
+        let key = CacheKey {
+            width: size.width.to_bits(),
+            height: size.height.to_bits(),
+            scale: paint_ctx.scale().to_bits(),
+            content: &*self.svg_data.tree as *const usvg::Tree as usize,
+        };
+        if let Some(idx) = self.cache.iter().position(|(k, _)| *k == key) {
+            let entry = self.cache.remove(idx);
+            self.cache.insert(0, entry);
+        } else {
+            let paths = self.paths_for_size(size);
+            match paint_ctx.rasterize(size, |ctx| paint_paths(&paths, ctx)) {
+                Some(raster) => {
+                    self.cache.insert(0, (key, raster));
+                    self.cache.truncate(CACHE_SIZE);
+                }
+                None => return paint_paths(&paths, paint_ctx),
+            }
+        }
+        self.cache[0].1.draw(paint_ctx);
     }
 }
 
@@ -126,6 +182,12 @@ impl SvgData {
 
     /// Convert SvgData into Piet draw instructions
     pub fn to_piet(&self, scale: f64, offset: Point, paint_ctx: &mut PaintCtx) {
+        paint_paths(&self.prepare_paths(scale, offset), paint_ctx);
+    }
+
+    /// Build the paths of the SVG, scaled and offset.
+    fn prepare_paths(&self, scale: f64, offset: Point) -> Vec<PreparedPath> {
+        let mut paths = Vec::new();
         let root = self.tree.root();
 
         for n in root.children() {
@@ -166,21 +228,15 @@ impl SvgData {
                             }
                         }
                     }
-                    match &p.fill {
-                        Some(fill) => {
-                            let brush = color_from_usvg(&fill.paint, fill.opacity);
-                            paint_ctx.fill(path.clone(), &brush);
-                        }
-                        None => {}
-                    }
-
-                    match &p.stroke {
-                        Some(stroke) => {
-                            let brush = color_from_usvg(&stroke.paint, stroke.opacity);
-                            paint_ctx.stroke(path.clone(), &brush, stroke.width.value());
-                        }
-                        None => {}
-                    }
+                    let fill = p
+                        .fill
+                        .as_ref()
+                        .map(|fill| color_from_usvg(&fill.paint, fill.opacity));
+                    let stroke = p.stroke.as_ref().map(|stroke| {
+                        let brush = color_from_usvg(&stroke.paint, stroke.opacity);
+                        (brush, stroke.width.value())
+                    });
+                    paths.push(PreparedPath { path, fill, stroke });
                 }
                 usvg::NodeKind::Defs => {
                     // TODO: implement defs
@@ -191,6 +247,18 @@ impl SvgData {
                 }
             }
         }
+        paths
+    }
+}
+
+fn paint_paths(paths: &[PreparedPath], paint_ctx: &mut PaintCtx) {
+    for p in paths {
+        if let Some(brush) = &p.fill {
+            paint_ctx.fill(p.path.clone(), brush);
+        }
+        if let Some((brush, width)) = &p.stroke {
+            paint_ctx.stroke(p.path.clone(), brush, *width);
+        }
     }
 }
 
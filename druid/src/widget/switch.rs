@@ -21,7 +21,8 @@ use crate::piet::{
 use crate::theme;
 use crate::widget::Align;
 use crate::{
-    BaseState, BoxConstraints, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx, Widget,
+    BaseState, BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, PaintCtx,
+    UpdateCtx, Widget,
 };
 
 const SWITCH_PADDING: f64 = 3.;
@@ -56,7 +57,8 @@ impl Switch {
         let font_name = env.get(theme::FONT_NAME);
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
         let switch_height = env.get(theme::BORDERED_WIDGET_HEIGHT);
-        let knob_size = switch_height - 2. * SWITCH_PADDING;
+        let padding = theme::spacing(env, SWITCH_PADDING);
+        let knob_size = switch_height - 2. * padding;
 
         let font = paint_ctx
             .text()
@@ -100,16 +102,18 @@ impl Switch {
         off_label_origin.y = off_label_origin.y.min(switch_height);
 
         on_label_origin.x = self.knob_pos.x - switch_width + knob_size;
-        off_label_origin.x = switch_width - off_label_layout.width() - SWITCH_PADDING * 2.
+        off_label_origin.x = switch_width - off_label_layout.width() - padding * 2.
             + self.knob_pos.x
             - knob_size / 2.
-            - SWITCH_PADDING;
+            - padding;
 
-        paint_ctx.draw_text(
-            &on_label_layout,
-            on_label_origin,
-            &env.get(theme::LABEL_COLOR),
-        );
+        // In high contrast mode, the on label is on the highlight color.
+        let on_label_color = if env.get(theme::HIGH_CONTRAST) {
+            env.get(theme::FORCED_HIGHLIGHT_TEXT)
+        } else {
+            env.get(theme::LABEL_COLOR)
+        };
+        paint_ctx.draw_text(&on_label_layout, on_label_origin, &on_label_color);
         paint_ctx.draw_text(
             &off_label_layout,
             off_label_origin,
@@ -121,10 +125,11 @@ impl Switch {
 impl Widget<bool> for Switch {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut bool, env: &Env) {
         let switch_height = env.get(theme::BORDERED_WIDGET_HEIGHT);
+        let padding = theme::spacing(env, SWITCH_PADDING);
         let switch_width = switch_height * SWITCH_WIDTH_RATIO;
-        let knob_size = switch_height - 2. * SWITCH_PADDING;
-        let on_pos = switch_width - knob_size / 2. - SWITCH_PADDING;
-        let off_pos = knob_size / 2. + SWITCH_PADDING;
+        let knob_size = switch_height - 2. * padding;
+        let on_pos = switch_width - knob_size / 2. - padding;
+        let off_pos = knob_size / 2. + padding;
 
         match event {
             Event::MouseDown(_) => {
@@ -157,6 +162,17 @@ impl Widget<bool> for Switch {
                 }
                 ctx.invalidate();
             }
+            Event::KeyDown(key_event)
+                if ctx.has_focus() && key_event.key_code == KeyCode::Space =>
+            {
+                *data = !*data;
+                ctx.set_handled();
+                ctx.invalidate();
+                self.animation_in_progress = true;
+                ctx.request_anim_frame();
+            }
+            Event::FocusChanged(_) => ctx.invalidate(),
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
             Event::AnimFrame(_) => {
                 // move knob to right position depending on the value
                 if self.animation_in_progress {
@@ -185,17 +201,18 @@ impl Widget<bool> for Switch {
         _data: &bool,
         env: &Env,
     ) -> Size {
-        let width =
-            (2. * SWITCH_PADDING + env.get(theme::BORDERED_WIDGET_HEIGHT)) * SWITCH_WIDTH_RATIO;
+        let padding = theme::spacing(env, SWITCH_PADDING);
+        let width = (2. * padding + env.get(theme::BORDERED_WIDGET_HEIGHT)) * SWITCH_WIDTH_RATIO;
         bc.constrain(Size::new(width, env.get(theme::BORDERED_WIDGET_HEIGHT)))
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &bool, env: &Env) {
         let switch_height = env.get(theme::BORDERED_WIDGET_HEIGHT);
+        let padding = theme::spacing(env, SWITCH_PADDING);
         let switch_width = switch_height * SWITCH_WIDTH_RATIO;
-        let knob_size = switch_height - 2. * SWITCH_PADDING;
-        let on_pos = switch_width - knob_size / 2. - SWITCH_PADDING;
-        let off_pos = knob_size / 2. + SWITCH_PADDING;
+        let knob_size = switch_height - 2. * padding;
+        let on_pos = switch_width - knob_size / 2. - padding;
+        let off_pos = knob_size / 2. + padding;
 
         let background_rect = RoundedRect::from_origin_size(
             Point::ORIGIN,
@@ -212,7 +229,7 @@ impl Widget<bool> for Switch {
             }
         };
 
-        self.knob_pos = Point::new(self.knob_pos.x, knob_size / 2. + SWITCH_PADDING);
+        self.knob_pos = Point::new(self.knob_pos.x, knob_size / 2. + padding);
         let knob_circle = Circle::new(self.knob_pos, knob_size / 2.);
 
         // paint different background for on and off state
@@ -237,7 +254,12 @@ impl Widget<bool> for Switch {
             ),
         );
 
-        paint_ctx.stroke(background_rect, &env.get(theme::BORDER), 2.0);
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else {
+            env.get(theme::BORDER)
+        };
+        paint_ctx.stroke(background_rect, &border_color, 2.0);
         paint_ctx.fill(background_rect, &background_gradient_on_state);
         paint_ctx.fill(background_rect, &background_gradient_off_state);
         paint_ctx.clip(background_rect);
@@ -269,15 +291,26 @@ impl Widget<bool> for Switch {
             normal_knob_gradient
         };
 
-        // paint the border
-        let border_color = if is_hovered || is_active {
-            env.get(theme::FOREGROUND_LIGHT)
+        if env.get(theme::HIGH_CONTRAST) {
+            // A flat knob from the forced palette.
+            let border = if is_hovered || is_active {
+                env.get(theme::FORCED_HIGHLIGHT)
+            } else {
+                env.get(theme::FORCED_BUTTON_TEXT)
+            };
+            paint_ctx.stroke(knob_circle, &border, 2.);
+            paint_ctx.fill(knob_circle, &env.get(theme::FORCED_BUTTON_FACE));
         } else {
-            env.get(theme::FOREGROUND_DARK)
-        };
+            // paint the border
+            let border_color = if is_hovered || is_active {
+                env.get(theme::FOREGROUND_LIGHT)
+            } else {
+                env.get(theme::FOREGROUND_DARK)
+            };
 
-        paint_ctx.stroke(knob_circle, &border_color, 2.);
-        paint_ctx.fill(knob_circle, &knob_gradient);
+            paint_ctx.stroke(knob_circle, &border_color, 2.);
+            paint_ctx.fill(knob_circle, &knob_gradient);
+        }
 
         // paint on/off label
         self.paint_labels(paint_ctx, base_state, env, switch_width);
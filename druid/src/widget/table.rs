@@ -0,0 +1,684 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A table of rows, with resizable and sortable columns.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+use std::sync::Arc;
+
+use log::warn;
+
+use crate::kurbo::{Line, Point, Rect, Size, Vec2};
+use crate::piet::{FontBuilder, PietTextLayout, RenderContext, Text, TextLayoutBuilder};
+use crate::theme;
+use crate::widget::list::ItemKey;
+use crate::{
+    BaseState, BoxConstraints, Cursor, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, PaintCtx,
+    PersistView, UpdateCtx, Widget, WidgetPod,
+};
+
+/// How close to the edge of a header the pointer resizes its column.
+const RESIZE_HANDLE: f64 = 4.0;
+const HEADER_PADDING: f64 = 6.0;
+
+type Cells<R> = Vec<WidgetPod<R, Box<dyn Widget<R>>>>;
+
+/// The data shown by a [`Table`].
+///
+/// [`Table`]: struct.Table.html
+#[derive(Debug, Clone)]
+pub struct TableData<R> {
+    /// The rows of the table, in their own order; the table may show them
+    /// sorted.
+    pub rows: Arc<Vec<R>>,
+    /// The index in `rows` of the selected row, if any.
+    pub selected: Option<usize>,
+}
+
+/// A column of a [`Table`].
+///
+/// [`Table`]: struct.Table.html
+pub struct TableColumn<R> {
+    title: String,
+    width: f64,
+    min_width: f64,
+    build: Box<dyn Fn() -> Box<dyn Widget<R>>>,
+    sort: Option<Box<dyn Fn(&R, &R) -> Ordering>>,
+}
+
+/// A table showing a row of cells for each item of a [`TableData`].
+///
+/// Each column builds a widget for its cell in each row. The table is
+/// virtualized: only the rows that are visible have widgets, so it can
+/// show very long tables. Rows that scroll out of view are sent
+/// [`LifeCycle::WidgetRemoved`] before the next event, and dropped.
+///
+/// Dragging the edge of a column's header resizes the column. Clicking
+/// the header of a column with a [`sort_by`] comparison sorts the rows by
+/// it, and clicking it again reverses the order. Clicking a row selects
+/// it.
+///
+/// Rows are matched to their cells by position in the data, unless the
+/// table is given a key with [`with_key`].
+///
+/// [`TableData`]: struct.TableData.html
+/// [`LifeCycle::WidgetRemoved`]: ../enum.LifeCycle.html#variant.WidgetRemoved
+/// [`sort_by`]: struct.TableColumn.html#method.sort_by
+/// [`with_key`]: #method.with_key
+pub struct Table<R> {
+    columns: Vec<TableColumn<R>>,
+    /// Computes the key of a row, if the table is keyed.
+    key_fn: Option<Box<dyn Fn(&R) -> ItemKey>>,
+    row_height: f64,
+    /// The index in the data of the row shown at each position.
+    order: Vec<usize>,
+    /// The column the rows are sorted by, and whether they are descending.
+    sort: Option<(usize, bool)>,
+    /// Whether the sort changed without the data at hand, so the rows
+    /// must be sorted again at the next layout.
+    needs_resort: bool,
+    /// The index in the data and the cells of each visible row, by the
+    /// row's key.
+    rows: HashMap<ItemKey, (usize, Cells<R>)>,
+    /// Rows that are no longer visible, to be removed on the next event,
+    /// with the last data they were given.
+    retired: Vec<(Cells<R>, R)>,
+    /// The column being resized, with the pointer's starting position and
+    /// the column's starting width.
+    resizing: Option<(usize, f64, f64)>,
+    /// The vertical scroll position, in display points.
+    offset: f64,
+    height: f64,
+}
+
+impl<R: Data> TableData<R> {
+    /// Create table data with no row selected.
+    pub fn new(rows: impl Into<Arc<Vec<R>>>) -> TableData<R> {
+        TableData {
+            rows: rows.into(),
+            selected: None,
+        }
+    }
+}
+
+impl<R: Data> Data for TableData<R> {
+    fn same(&self, other: &Self) -> bool {
+        self.rows.same(&other.rows) && self.selected == other.selected
+    }
+}
+
+impl<R: Data> TableColumn<R> {
+    /// Create a column titled `title`, `width` wide, whose cells are built
+    /// by `build`.
+    pub fn new<W: Widget<R> + 'static>(
+        title: impl Into<String>,
+        width: f64,
+        build: impl Fn() -> W + 'static,
+    ) -> TableColumn<R> {
+        TableColumn {
+            title: title.into(),
+            width,
+            min_width: RESIZE_HANDLE * 4.0,
+            build: Box::new(move || Box::new(build())),
+            sort: None,
+        }
+    }
+
+    /// Builder-style method to set the narrowest the column can be resized
+    /// to.
+    pub fn min_width(mut self, min_width: f64) -> Self {
+        self.min_width = min_width;
+        self.width = self.width.max(min_width);
+        self
+    }
+
+    /// Builder-style method to let the rows be sorted by this column,
+    /// using `compare`.
+    pub fn sort_by(mut self, compare: impl Fn(&R, &R) -> Ordering + 'static) -> Self {
+        self.sort = Some(Box::new(compare));
+        self
+    }
+}
+
+impl<R: Data> Table<R> {
+    /// Create a table with no columns.
+    pub fn new() -> Table<R> {
+        Table {
+            columns: Vec::new(),
+            key_fn: None,
+            row_height: 24.0,
+            order: Vec::new(),
+            sort: None,
+            needs_resort: false,
+            rows: HashMap::new(),
+            retired: Vec::new(),
+            resizing: None,
+            offset: 0.0,
+            height: 0.0,
+        }
+    }
+
+    /// Builder-style method to add a column.
+    pub fn column(mut self, column: TableColumn<R>) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Builder-style method to set the height of the header and the rows.
+    pub fn row_height(mut self, row_height: f64) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    /// Builder-style method to give each row a stable key.
+    ///
+    /// By default, the cells of a row belong to its position in the data,
+    /// so inserting or removing a row gives the state of the cells of every
+    /// row after it to another row. With a key, the cells follow their row
+    /// when rows are inserted, removed, or replaced. Keys should be unique
+    /// within the table; only one of the rows with a repeated key is shown
+    /// with cells, and a warning is logged.
+    pub fn with_key<K: Hash + Eq + 'static>(mut self, key: impl Fn(&R) -> K + 'static) -> Self {
+        self.key_fn = Some(Box::new(move |row| ItemKey::new(key(row))));
+        self
+    }
+
+    /// The key of the row at `index` in the data.
+    fn row_key(&self, index: usize, row: &R) -> ItemKey {
+        match &self.key_fn {
+            Some(key_fn) => key_fn(row),
+            None => ItemKey::new(index),
+        }
+    }
+
+    /// Find the visible rows in the changed `rows`, and retire the cells of
+    /// those that were removed, which had the data in `old_rows`.
+    fn reindex(&mut self, old_rows: Option<&[R]>, rows: &[R]) {
+        let indices: Option<HashMap<ItemKey, usize>> = self.key_fn.as_ref().map(|key_fn| {
+            // The first row with a key has its cells.
+            rows.iter()
+                .enumerate()
+                .rev()
+                .map(|(index, row)| (key_fn(row), index))
+                .collect()
+        });
+        if indices
+            .as_ref()
+            .map_or(false, |indices| indices.len() < rows.len())
+        {
+            warn!("Table rows have duplicate keys; only one of each has cells.");
+        }
+        let retired = &mut self.retired;
+        self.rows.retain(|key, (index, cells)| {
+            let new_index = match &indices {
+                Some(indices) => indices.get(key).cloned(),
+                None if *index < rows.len() => Some(*index),
+                None => None,
+            };
+            if let Some(new_index) = new_index {
+                *index = new_index;
+                return true;
+            }
+            // Without the data they had, the cells are just dropped.
+            if let Some(row) = old_rows.and_then(|old_rows| old_rows.get(*index)) {
+                retired.push((mem::replace(cells, Vec::new()), row.clone()));
+            }
+            false
+        });
+    }
+
+    /// Compute the order the rows are shown in.
+    fn resort(&mut self, rows: &[R]) {
+        self.needs_resort = false;
+        self.order = (0..rows.len()).collect();
+        let columns = &self.columns;
+        let sort = self
+            .sort
+            .and_then(|(column, descending)| Some((columns[column].sort.as_ref()?, descending)));
+        if let Some((compare, descending)) = sort {
+            self.order.sort_by(|&a, &b| {
+                let ordering = compare(&rows[a], &rows[b]);
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+    }
+
+    /// Sort by `column`, or reverse the order if the rows are already
+    /// sorted by it. Returns `false` if the column can't be sorted by.
+    fn toggle_sort(&mut self, column: usize) -> bool {
+        if self.columns[column].sort.is_none() {
+            return false;
+        }
+        self.sort = match self.sort {
+            Some((current, descending)) if current == column => Some((column, !descending)),
+            _ => Some((column, false)),
+        };
+        true
+    }
+
+    /// The column at `x`, if any.
+    fn column_at(&self, x: f64) -> Option<usize> {
+        let mut right = 0.0;
+        for (i, column) in self.columns.iter().enumerate() {
+            right += column.width;
+            if x < right {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// The column whose right edge is near `x`, if any.
+    fn resize_handle_at(&self, x: f64) -> Option<usize> {
+        let mut right = 0.0;
+        for (i, column) in self.columns.iter().enumerate() {
+            right += column.width;
+            if (x - right).abs() <= RESIZE_HANDLE {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// The range of positions of the rows within the viewport.
+    fn visible_rows(&self) -> (usize, usize) {
+        let row_height = self.row_height.max(1.0);
+        let body = (self.height - self.row_height).max(0.0);
+        let first = (self.offset / row_height).floor().max(0.0) as usize;
+        let last = ((self.offset + body) / row_height).ceil().max(0.0) as usize;
+        let last = last.min(self.order.len());
+        (first.min(last), last)
+    }
+
+    fn max_offset(&self) -> f64 {
+        let body = (self.height - self.row_height).max(0.0);
+        (self.order.len() as f64 * self.row_height - body).max(0.0)
+    }
+}
+
+impl<R: Data> Default for Table<R> {
+    fn default() -> Self {
+        Table::new()
+    }
+}
+
+/// The view state of a table is the width of each column, the column the
+/// rows are sorted by, and the scroll offset.
+impl<R: Data> PersistView for Table<R> {
+    type State = (Vec<f64>, Option<(usize, bool)>, f64);
+
+    fn view_state(&self) -> Self::State {
+        let widths = self.columns.iter().map(|column| column.width).collect();
+        (widths, self.sort, self.offset)
+    }
+
+    fn restore_view_state(&mut self, (widths, sort, offset): Self::State) {
+        // The columns may have changed since the state was saved.
+        if widths.len() == self.columns.len() {
+            for (column, width) in self.columns.iter_mut().zip(widths) {
+                if width.is_finite() {
+                    column.width = width.max(column.min_width);
+                }
+            }
+        }
+        let columns = &self.columns;
+        let sortable = |(column, _): (usize, bool)| {
+            columns
+                .get(column)
+                .map_or(false, |column| column.sort.is_some())
+        };
+        if sort.map_or(true, sortable) {
+            self.sort = sort;
+            self.needs_resort = true;
+        }
+        if offset.is_finite() {
+            // Layout clamps the offset to the rows.
+            self.offset = offset.max(0.0);
+        }
+    }
+}
+
+impl<R: Data> Widget<TableData<R>> for Table<R> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut TableData<R>, env: &Env) {
+        let removed = Event::LifeCycle(LifeCycle::WidgetRemoved);
+        for (mut cells, mut row) in self.retired.drain(..) {
+            for cell in &mut cells {
+                cell.event(ctx, &removed, &mut row, env);
+            }
+        }
+
+        // While a column is resized, the pointer belongs to the header, but
+        // other events still go to the cells.
+        if let Some((column, start_x, start_width)) = self.resizing {
+            match event {
+                Event::MouseMoved(mouse) => {
+                    let min_width = self.columns[column].min_width;
+                    self.columns[column].width =
+                        (start_width + mouse.pos.x - start_x).max(min_width);
+                    ctx.set_cursor(&Cursor::ResizeLeftRight);
+                    ctx.invalidate();
+                    return;
+                }
+                Event::MouseUp(_) => {
+                    self.resizing = None;
+                    ctx.set_active(false);
+                    return;
+                }
+                Event::MouseDown(_) => return,
+                _ => (),
+            }
+        }
+
+        let in_header = match event {
+            Event::MouseDown(mouse) | Event::MouseUp(mouse) | Event::MouseMoved(mouse) => {
+                mouse.pos.y < self.row_height
+            }
+            _ => false,
+        };
+        let cell_active = self
+            .rows
+            .values()
+            .flat_map(|(_, cells)| cells)
+            .any(|cell| cell.has_active());
+        if !in_header || cell_active {
+            for (index, cells) in self.rows.values_mut() {
+                let index = *index;
+                let mut row = match data.rows.get(index) {
+                    Some(row) => row.clone(),
+                    None => continue,
+                };
+                for cell in cells.iter_mut() {
+                    cell.event(ctx, event, &mut row, env);
+                }
+                if !row.same(&data.rows[index]) {
+                    Arc::make_mut(&mut data.rows)[index] = row;
+                }
+            }
+        }
+        if ctx.is_handled() {
+            return;
+        }
+
+        match event {
+            Event::Wheel(wheel) => {
+                let delta = ctx.wheel_remaining(wheel);
+                let offset = (self.offset + delta.y).max(0.0).min(self.max_offset());
+                let change = offset - self.offset;
+                self.offset = offset;
+                ctx.consume_wheel(Vec2::new(0.0, change));
+                ctx.invalidate();
+            }
+            Event::MouseDown(mouse) if in_header => {
+                if let Some(column) = self.resize_handle_at(mouse.pos.x) {
+                    self.resizing = Some((column, mouse.pos.x, self.columns[column].width));
+                    ctx.set_active(true);
+                } else if let Some(column) = self.column_at(mouse.pos.x) {
+                    if self.toggle_sort(column) {
+                        self.resort(&data.rows);
+                        ctx.invalidate();
+                    }
+                }
+                ctx.set_handled();
+            }
+            Event::MouseDown(mouse) => {
+                let position = (mouse.pos.y - self.row_height + self.offset) / self.row_height;
+                if position >= 0.0 {
+                    if let Some(&index) = self.order.get(position as usize) {
+                        data.selected = Some(index);
+                        ctx.set_handled();
+                    }
+                }
+            }
+            Event::MouseMoved(mouse) if in_header => {
+                if self.resize_handle_at(mouse.pos.x).is_some() {
+                    ctx.set_cursor(&Cursor::ResizeLeftRight);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: Option<&TableData<R>>,
+        data: &TableData<R>,
+        env: &Env,
+    ) {
+        if old_data.map_or(true, |old| !old.rows.same(&data.rows)) {
+            self.resort(&data.rows);
+            self.reindex(old_data.map(|old| &old.rows[..]), &data.rows);
+        }
+        for (index, cells) in self.rows.values_mut() {
+            for cell in cells.iter_mut() {
+                cell.update(ctx, &data.rows[*index], env);
+            }
+        }
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &TableData<R>,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Table");
+
+        if self.needs_resort {
+            self.resort(&data.rows);
+        }
+
+        let columns_width: f64 = self.columns.iter().map(|column| column.width).sum();
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            columns_width
+        };
+        let height = if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            (self.order.len() + 1) as f64 * self.row_height
+        };
+        let size = bc.constrain(Size::new(width, height));
+        self.height = size.height;
+        self.offset = self.offset.min(self.max_offset());
+
+        let (first, last) = self.visible_rows();
+        let visible = &self.order[first..last];
+        let retired = &mut self.retired;
+        self.rows.retain(|_, (index, cells)| {
+            if visible.contains(index) {
+                return true;
+            }
+            if let Some(row) = data.rows.get(*index) {
+                retired.push((mem::replace(cells, Vec::new()), row.clone()));
+            }
+            false
+        });
+
+        for position in first..last {
+            let index = self.order[position];
+            let row = match data.rows.get(index) {
+                Some(row) => row,
+                None => continue,
+            };
+            let key = self.row_key(index, row);
+            let columns = &self.columns;
+            let (cells_index, cells) = self.rows.entry(key).or_insert_with(|| {
+                let cells = columns
+                    .iter()
+                    .map(|column| WidgetPod::new((column.build)()))
+                    .collect();
+                (index, cells)
+            });
+            if *cells_index != index {
+                // Another row with the same key has these cells.
+                continue;
+            }
+            let top = (position + 1) as f64 * self.row_height - self.offset;
+            let mut left = 0.0;
+            for (cell, column) in cells.iter_mut().zip(columns) {
+                layout_ctx.update_child(cell, row, env);
+                let cell_size = Size::new(column.width, self.row_height);
+                let cell_bc = BoxConstraints::tight(cell_size);
+                cell.layout(layout_ctx, &cell_bc, row, env);
+                cell.set_layout_rect(Rect::from_origin_size(Point::new(left, top), cell_size));
+                left += column.width;
+            }
+        }
+        size
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        base_state: &BaseState,
+        data: &TableData<R>,
+        env: &Env,
+    ) {
+        let size = base_state.size();
+        let row_height = self.row_height;
+        let border_color = env.get(theme::BORDER);
+        let (first, last) = self.visible_rows();
+
+        paint_ctx.with_save(|ctx| {
+            let body = Rect::new(0.0, row_height, size.width, size.height);
+            ctx.clip(body);
+            for position in first..last {
+                let index = self.order[position];
+                let top = (position + 1) as f64 * row_height - self.offset;
+                if data.selected == Some(index) {
+                    let rect = Rect::new(0.0, top, size.width, top + row_height);
+                    ctx.fill(rect, &env.get(theme::SELECTION_COLOR));
+                }
+            }
+            for (index, cells) in self.rows.values_mut() {
+                let row = match data.rows.get(*index) {
+                    Some(row) => row,
+                    None => continue,
+                };
+                for cell in cells {
+                    cell.paint_with_offset(ctx, row, env);
+                }
+            }
+        });
+
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+            .build()
+            .unwrap();
+        let header = Rect::new(0.0, 0.0, size.width, row_height);
+        paint_ctx.fill(header, &env.get(theme::BACKGROUND_LIGHT));
+        let label_color = env.get(theme::LABEL_COLOR);
+        let baseline = (row_height + font_size) * 0.5 - 2.0;
+        let mut left = 0.0;
+        for (i, column) in self.columns.iter().enumerate() {
+            let title = match self.sort {
+                Some((sorted, false)) if sorted == i => format!("{} \u{25b2}", column.title),
+                Some((sorted, true)) if sorted == i => format!("{} \u{25bc}", column.title),
+                _ => column.title.clone(),
+            };
+            let layout: PietTextLayout = paint_ctx
+                .text()
+                .new_text_layout(&font, &title)
+                .build()
+                .unwrap();
+            let cell = Rect::new(left, 0.0, left + column.width, row_height);
+            paint_ctx.with_save(|ctx| {
+                ctx.clip(cell);
+                ctx.draw_text(&layout, (left + HEADER_PADDING, baseline), &label_color);
+            });
+            left += column.width;
+            let edge = Line::new((left - 0.5, 0.0), (left - 0.5, row_height));
+            paint_ctx.stroke(edge, &border_color, 1.0);
+        }
+        let bottom = Line::new((0.0, row_height - 0.5), (size.width, row_height - 0.5));
+        paint_ctx.stroke(bottom, &border_color, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    #[test]
+    fn sorts_by_column() {
+        let mut table = Table::<(String, u32)>::new()
+            .column(TableColumn::new("Name", 100.0, || Label::new("")))
+            .column(TableColumn::new("Age", 50.0, || Label::new("")).sort_by(|a, b| a.1.cmp(&b.1)));
+        let rows = vec![
+            ("b".to_string(), 30),
+            ("a".to_string(), 10),
+            ("c".to_string(), 20),
+        ];
+        table.resort(&rows);
+        assert_eq!(table.order, vec![0, 1, 2]);
+
+        assert!(!table.toggle_sort(0));
+        assert!(table.toggle_sort(1));
+        table.resort(&rows);
+        assert_eq!(table.order, vec![1, 2, 0]);
+        assert!(table.toggle_sort(1));
+        table.resort(&rows);
+        assert_eq!(table.order, vec![0, 2, 1]);
+
+        assert_eq!(table.column_at(120.0), Some(1));
+        assert_eq!(table.resize_handle_at(98.0), Some(0));
+        assert_eq!(table.column_at(200.0), None);
+    }
+
+    #[test]
+    fn keyed_cells_follow_their_row() {
+        let mut table = Table::<(String, u32)>::new()
+            .with_key(|row| row.0.clone())
+            .column(TableColumn::new("Name", 100.0, || Label::new("")));
+        let rows = vec![("a".to_string(), 10), ("b".to_string(), 20)];
+        for (index, row) in rows.iter().enumerate() {
+            let key = table.row_key(index, row);
+            table.rows.insert(key, (index, Vec::new()));
+        }
+
+        let inserted = vec![
+            ("z".to_string(), 0),
+            ("a".to_string(), 10),
+            ("b".to_string(), 20),
+        ];
+        table.reindex(Some(&rows[..]), &inserted);
+        let index_of = |table: &Table<_>, key: &str| {
+            let key = ItemKey::new(key.to_string());
+            table.rows.get(&key).map(|(index, _)| *index)
+        };
+        assert_eq!(index_of(&table, "a"), Some(1));
+        assert_eq!(index_of(&table, "b"), Some(2));
+
+        let removed = vec![("z".to_string(), 0), ("b".to_string(), 20)];
+        table.reindex(Some(&inserted[..]), &removed);
+        assert_eq!(index_of(&table, "a"), None);
+        assert_eq!(index_of(&table, "b"), Some(1));
+        assert_eq!(table.retired.len(), 1);
+        assert_eq!(table.retired[0].1, ("a".to_string(), 10));
+    }
+}
@@ -0,0 +1,1064 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A multi-line text editing widget, with word wrap.
+
+use std::any::Any;
+use std::ops::Range;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{Line, Point, Rect, RoundedRect, Size};
+use crate::piet::{
+    FontBuilder, PietText, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
+};
+use crate::theme;
+use crate::widget::textbox::{next_grapheme, next_word, prev_grapheme, prev_word};
+use crate::{
+    commands, Application, BaseState, BoxConstraints, Command, Cursor, EditAction, EditableText,
+    Env, Event, EventCtx, KeyMap, LayoutCtx, LifeCycle, Movement, PaintCtx, Selector, TimerToken,
+    UpdateCtx, Widget,
+};
+
+const PADDING_TOP: f64 = 5.;
+const PADDING_LEFT: f64 = 4.;
+/// Line height, as a multiple of the font size.
+const LINE_HEIGHT_FACTOR: f64 = 1.2;
+/// The width used when the width is unbounded.
+const DEFAULT_WIDTH: f64 = 300.;
+
+/// The result of a find operation in a [`TextArea`].
+///
+/// A searchable `TextArea` submits this as the argument of a
+/// [`FIND_MATCHES`] command whenever its matches change, so that the
+/// application can store it in its data.
+///
+/// [`TextArea`]: struct.TextArea.html
+/// [`FIND_MATCHES`]: ../commands/constant.FIND_MATCHES.html
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FindMatches {
+    /// The number of matches for the current query.
+    pub count: usize,
+    /// The index of the currently selected match, if any.
+    pub current: Option<usize>,
+}
+
+/// A multi-line text editor, which wraps its text to its width.
+///
+/// Lines are broken after whitespace where possible, and within a word
+/// that does not fit on a line of its own. Up and down move the caret
+/// between the wrapped lines, keeping its horizontal position, and home and
+/// end move to the start and end of the wrapped line. Otherwise editing goes
+/// through the same [`EditAction`]s and [`KeyMap`] as a [`TextBox`].
+///
+/// The text can be a `String`, or any other [`EditableText`], such as a
+/// [`TextBuffer`] for long documents.
+///
+/// When its height is unbounded, as it is in a vertical [`Scroll`], the
+/// `TextArea` is as tall as its text. Given a fixed height, it scrolls its
+/// text itself, with the mouse wheel and to keep the caret in view.
+///
+/// A [`searchable`] `TextArea` also highlights the matches of a find query.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Scroll, TextArea};
+///
+/// let notes = Scroll::new(TextArea::new()).vertical();
+/// ```
+///
+/// [`EditAction`]: ../enum.EditAction.html
+/// [`KeyMap`]: ../struct.KeyMap.html
+/// [`TextBox`]: struct.TextBox.html
+/// [`EditableText`]: ../trait.EditableText.html
+/// [`TextBuffer`]: ../struct.TextBuffer.html
+/// [`Scroll`]: struct.Scroll.html
+/// [`searchable`]: #method.searchable
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    keymap: KeyMap,
+    /// The fixed end of the selection, as a byte offset.
+    anchor: usize,
+    /// The moving end of the selection, as a byte offset.
+    caret: usize,
+    /// The horizontal position kept while moving up and down.
+    goal_x: Option<f64>,
+    /// The paragraphs of the text, with the lines each is wrapped into.
+    paragraphs: Vec<Paragraph>,
+    /// The wrapped lines of all the paragraphs, as byte ranges of the text.
+    lines: Vec<Range<usize>>,
+    /// A clone of the data the lines were wrapped for, compared with
+    /// [`Data::same`] to find out if the text changed.
+    ///
+    /// [`Data::same`]: ../trait.Data.html#tymethod.same
+    wrapped: Option<Rc<dyn Any>>,
+    /// An edit made since the text was wrapped: the replaced byte range, and
+    /// the length of the text that replaced it.
+    pending_edit: Option<(Range<usize>, usize)>,
+    wrap_width: f64,
+    scroll_y: f64,
+    size: Size,
+    content_height: f64,
+    cursor_timer: TimerToken,
+    cursor_on: bool,
+    searchable: bool,
+    find_query: String,
+    /// The matches of `find_query`, as byte ranges of the text.
+    find_matches: Vec<Range<usize>>,
+    current_match: Option<usize>,
+    /// Whether the matches changed in `update`, and are reported on the
+    /// next animation frame.
+    report_pending: bool,
+}
+
+impl TextArea {
+    /// Create a new `TextArea`.
+    pub fn new() -> Self {
+        TextArea {
+            keymap: KeyMap::default(),
+            anchor: 0,
+            caret: 0,
+            goal_x: None,
+            paragraphs: vec![Paragraph {
+                range: 0..0,
+                lines: vec![0..0],
+            }],
+            lines: vec![0..0],
+            wrapped: None,
+            pending_edit: None,
+            wrap_width: 0.0,
+            scroll_y: 0.0,
+            size: Size::ZERO,
+            content_height: 0.0,
+            cursor_timer: TimerToken::INVALID,
+            cursor_on: false,
+            searchable: false,
+            find_query: String::new(),
+            find_matches: Vec::new(),
+            current_match: None,
+            report_pending: false,
+        }
+    }
+
+    /// Builder-style method to set the [`KeyMap`] used to translate key
+    /// presses into [`EditAction`]s.
+    ///
+    /// [`KeyMap`]: ../struct.KeyMap.html
+    /// [`EditAction`]: ../enum.EditAction.html
+    pub fn with_keymap(mut self, keymap: KeyMap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Builder-style method to make this `TextArea` respond to find commands.
+    ///
+    /// A searchable `TextArea` highlights every match of the query set with
+    /// [`FIND`], selects and scrolls to the next or previous match on
+    /// [`FIND_NEXT`] and [`FIND_PREVIOUS`], and reports its matches with a
+    /// [`FIND_MATCHES`] command, including when edits change them.
+    ///
+    /// [`FIND`]: ../commands/constant.FIND.html
+    /// [`FIND_NEXT`]: ../commands/constant.FIND_NEXT.html
+    /// [`FIND_PREVIOUS`]: ../commands/constant.FIND_PREVIOUS.html
+    /// [`FIND_MATCHES`]: ../commands/constant.FIND_MATCHES.html
+    pub fn searchable(mut self) -> Self {
+        self.searchable = true;
+        self
+    }
+
+    fn line_height(env: &Env) -> f64 {
+        env.get(theme::TEXT_SIZE_NORMAL) * LINE_HEIGHT_FACTOR
+    }
+
+    fn padding_top(env: &Env) -> f64 {
+        theme::spacing(env, PADDING_TOP)
+    }
+
+    fn padding_left(env: &Env) -> f64 {
+        theme::spacing(env, PADDING_LEFT)
+    }
+
+    fn selection(&self) -> Range<usize> {
+        self.anchor.min(self.caret)..self.anchor.max(self.caret)
+    }
+
+    fn caret_to(&mut self, offset: usize) {
+        self.anchor = offset;
+        self.caret = offset;
+    }
+
+    fn get_layout(piet_text: &mut PietText, text: &str, env: &Env) -> PietTextLayout {
+        let font = piet_text
+            .new_font_by_name(env.get(theme::FONT_NAME), env.get(theme::TEXT_SIZE_NORMAL))
+            .build()
+            .unwrap();
+        piet_text.new_text_layout(&font, text).build().unwrap()
+    }
+
+    /// Returns `true` if the lines were wrapped for `data`.
+    fn is_wrapped<T: EditableText + 'static>(&self, data: &T) -> bool {
+        self.wrapped
+            .as_ref()
+            .and_then(|wrapped| wrapped.downcast_ref::<T>())
+            .map_or(false, |wrapped| wrapped.same(data))
+    }
+
+    /// Wrap the text again, if it or the width changed since the last time.
+    ///
+    /// After an edit made by the `TextArea` itself, only the paragraphs the
+    /// edit touched are wrapped again.
+    fn ensure_wrapped<T: EditableText + 'static>(
+        &mut self,
+        piet_text: &mut PietText,
+        data: &T,
+        width: f64,
+        env: &Env,
+    ) {
+        let edit = self.pending_edit.take();
+        let same_width = (self.wrap_width - width).abs() < std::f64::EPSILON;
+        if same_width && self.is_wrapped(data) {
+            return;
+        }
+        let wrap = |paragraph: &str| {
+            let layout = Self::get_layout(piet_text, paragraph, env);
+            let x_of = |offset| {
+                layout
+                    .hit_test_text_position(offset)
+                    .map_or(0.0, |hit| hit.point.x)
+            };
+            wrap_paragraph(paragraph, width, x_of)
+        };
+        match edit {
+            Some((range, new_len)) if same_width => {
+                rewrap_edit(&mut self.paragraphs, data, range, new_len, wrap)
+            }
+            _ => {
+                let text = data.text();
+                // Guard against external changes in data.
+                self.clamp_selection(&text);
+                self.paragraphs = wrap_paragraphs(&text, 0, wrap);
+            }
+        }
+        self.lines = self
+            .paragraphs
+            .iter()
+            .flat_map(|paragraph| paragraph.lines.iter().cloned())
+            .collect();
+        self.wrapped = Some(Rc::new(data.clone()));
+        self.wrap_width = width;
+    }
+
+    /// The index of the wrapped line the offset is shown on.
+    fn line_of(&self, offset: usize) -> usize {
+        self.lines
+            .iter()
+            .rposition(|line| line.start <= offset)
+            .unwrap_or(0)
+    }
+
+    /// The last offset the caret can take on a line, given the line's text.
+    ///
+    /// On a line that wraps, this is before the character at the break, as
+    /// the offset after it is shown at the start of the next line.
+    fn line_end(&self, index: usize, line_text: &str) -> usize {
+        let line = &self.lines[index];
+        let wraps = self
+            .lines
+            .get(index + 1)
+            .map_or(false, |next| next.start == line.end);
+        if wraps {
+            line.start + prev_grapheme(line_text, line_text.len())
+        } else {
+            line.end
+        }
+    }
+
+    /// Apply `f`, which moves from an offset in a string, to the text
+    /// around `offset`: its paragraph and the ones before and after it.
+    ///
+    /// This avoids going through the whole text, at the cost of not moving
+    /// further than the neighbouring paragraphs.
+    fn near(&self, data: &impl EditableText, offset: usize, f: fn(&str, usize) -> usize) -> usize {
+        let index = self
+            .paragraphs
+            .iter()
+            .rposition(|paragraph| paragraph.range.start <= offset)
+            .unwrap_or(0);
+        let start = self.paragraphs[index.saturating_sub(1)].range.start;
+        let last = (index + 1).min(self.paragraphs.len() - 1);
+        let end = self.paragraphs[last].range.end;
+        start + f(&data.slice(start..end), offset - start)
+    }
+
+    /// The horizontal position of an offset within its wrapped line.
+    fn x_of(
+        &self,
+        piet_text: &mut PietText,
+        offset: usize,
+        data: &impl EditableText,
+        env: &Env,
+    ) -> f64 {
+        let line = self.lines[self.line_of(offset)].clone();
+        let layout = Self::get_layout(piet_text, &data.slice(line.clone()), env);
+        layout
+            .hit_test_text_position(offset - line.start)
+            .map_or(0.0, |hit| hit.point.x)
+    }
+
+    /// The offset nearest to a horizontal position on a wrapped line.
+    fn offset_in_line(
+        &self,
+        piet_text: &mut PietText,
+        index: usize,
+        x: f64,
+        data: &impl EditableText,
+        env: &Env,
+    ) -> usize {
+        let line = self.lines[index].clone();
+        let line_text = data.slice(line.clone());
+        let layout = Self::get_layout(piet_text, &line_text, env);
+        let offset = line.start
+            + layout
+                .hit_test_point(Point::new(x, 0.0))
+                .metrics
+                .text_position;
+        offset.min(self.line_end(index, &line_text))
+    }
+
+    /// The offset nearest to a point in the widget.
+    fn offset_for_point(
+        &self,
+        piet_text: &mut PietText,
+        point: Point,
+        data: &impl EditableText,
+        env: &Env,
+    ) -> usize {
+        let y = point.y + self.scroll_y - Self::padding_top(env);
+        let index = (y / Self::line_height(env)).floor().max(0.0) as usize;
+        let index = index.min(self.lines.len() - 1);
+        let x = point.x - Self::padding_left(env);
+        self.offset_in_line(piet_text, index, x, data, env)
+    }
+
+    fn offset_for_movement(
+        &mut self,
+        piet_text: &mut PietText,
+        movement: Movement,
+        data: &impl EditableText,
+        env: &Env,
+    ) -> usize {
+        let index = self.line_of(self.caret);
+        match movement {
+            Movement::Left => self.near(data, self.caret, prev_grapheme),
+            Movement::Right => self.near(data, self.caret, next_grapheme),
+            Movement::WordLeft => self.near(data, self.caret, prev_word),
+            Movement::WordRight => self.near(data, self.caret, next_word),
+            Movement::LineStart => self.lines[index].start,
+            Movement::LineEnd => self.line_end(index, &data.slice(self.lines[index].clone())),
+            Movement::Up | Movement::Down => {
+                let x = match self.goal_x {
+                    Some(x) => x,
+                    None => self.x_of(piet_text, self.caret, data, env),
+                };
+                self.goal_x = Some(x);
+                match movement {
+                    Movement::Up if index == 0 => 0,
+                    Movement::Up => self.offset_in_line(piet_text, index - 1, x, data, env),
+                    _ if index + 1 >= self.lines.len() => data.len(),
+                    _ => self.offset_in_line(piet_text, index + 1, x, data, env),
+                }
+            }
+        }
+    }
+
+    /// Replace the text in `range`, remembering the edit so that only the
+    /// paragraphs it touches are wrapped again.
+    fn edit(&mut self, data: &mut impl EditableText, range: Range<usize>, text: &str) {
+        data.edit(range.clone(), text);
+        self.pending_edit = match self.pending_edit {
+            None => Some((range, text.len())),
+            // Edits can only be rewrapped one at a time; wrap everything.
+            Some(_) => {
+                self.wrapped = None;
+                None
+            }
+        };
+    }
+
+    fn insert(&mut self, data: &mut impl EditableText, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let selection = self.selection();
+        self.edit(data, selection.clone(), &text);
+        self.caret_to(selection.start + text.len());
+    }
+
+    fn copy(&self, data: &impl EditableText) {
+        let selection = self.selection();
+        if selection.start != selection.end {
+            Application::clipboard().put_string(&data.slice(selection));
+        }
+    }
+
+    fn can_handle_command(&self, selector: &Selector, read_only: bool) -> bool {
+        let has_selection = self.anchor != self.caret;
+        match *selector {
+            commands::COPY => has_selection,
+            commands::CUT | commands::DELETE => !read_only && has_selection,
+            commands::PASTE => !read_only,
+            commands::SELECT_ALL => true,
+            _ => false,
+        }
+    }
+
+    fn action_for_command(selector: &Selector) -> Option<EditAction> {
+        match *selector {
+            commands::COPY => Some(EditAction::Copy),
+            commands::CUT => Some(EditAction::Cut),
+            commands::DELETE => Some(EditAction::Delete),
+            commands::PASTE => Some(EditAction::Paste),
+            commands::SELECT_ALL => Some(EditAction::SelectAll),
+            _ => None,
+        }
+    }
+
+    fn do_edit_action<T: EditableText + 'static>(
+        &mut self,
+        ctx: &mut EventCtx,
+        action: EditAction,
+        data: &mut T,
+        env: &Env,
+    ) {
+        let read_only = env.get(theme::READ_ONLY);
+        let has_selection = self.anchor != self.caret;
+        let keeps_goal = match action {
+            EditAction::Move(movement) | EditAction::ModifySelection(movement) => {
+                movement == Movement::Up || movement == Movement::Down
+            }
+            _ => false,
+        };
+        if !keeps_goal {
+            self.goal_x = None;
+        }
+
+        match action {
+            EditAction::Insert(text) if !read_only => self.insert(data, &text),
+            EditAction::Backspace | EditAction::DeleteWordBackward
+                if !read_only && !has_selection =>
+            {
+                self.anchor = match action {
+                    EditAction::Backspace => self.near(data, self.caret, prev_grapheme),
+                    _ => self.near(data, self.caret, prev_word),
+                };
+                self.insert(data, "");
+            }
+            EditAction::Delete | EditAction::DeleteWordForward if !read_only && !has_selection => {
+                self.anchor = match action {
+                    EditAction::Delete => self.near(data, self.caret, next_grapheme),
+                    _ => self.near(data, self.caret, next_word),
+                };
+                self.insert(data, "");
+            }
+            EditAction::Backspace
+            | EditAction::Delete
+            | EditAction::DeleteWordBackward
+            | EditAction::DeleteWordForward
+                if !read_only =>
+            {
+                self.insert(data, "")
+            }
+            EditAction::Move(movement) => {
+                let to = match movement {
+                    Movement::Left if has_selection => self.selection().start,
+                    Movement::Right if has_selection => self.selection().end,
+                    movement => self.offset_for_movement(ctx.text(), movement, data, env),
+                };
+                self.caret_to(to);
+            }
+            EditAction::ModifySelection(movement) => {
+                self.caret = self.offset_for_movement(ctx.text(), movement, data, env);
+            }
+            EditAction::SelectAll => {
+                self.anchor = 0;
+                self.caret = data.len();
+            }
+            EditAction::Transpose if !read_only && !has_selection => {
+                if self.caret > 0 && self.caret < data.len() {
+                    let start = self.near(data, self.caret, prev_grapheme);
+                    let end = self.near(data, self.caret, next_grapheme);
+                    let swapped = format!(
+                        "{}{}",
+                        data.slice(self.caret..end),
+                        data.slice(start..self.caret)
+                    );
+                    self.edit(data, start..end, &swapped);
+                    self.caret_to(end);
+                }
+            }
+            EditAction::Copy => self.copy(data),
+            EditAction::Cut => {
+                self.copy(data);
+                if !read_only && has_selection {
+                    self.insert(data, "");
+                }
+            }
+            EditAction::Paste if !read_only => {
+                if let Some(string) = Application::clipboard().get_string() {
+                    self.insert(data, &string);
+                }
+            }
+            _ => (),
+        }
+        self.after_edit(ctx, data, env);
+    }
+
+    /// Rewrap after a change and scroll the caret into view.
+    fn after_edit<T: EditableText + 'static>(&mut self, ctx: &mut EventCtx, data: &T, env: &Env) {
+        let width = self.wrap_width;
+        self.ensure_wrapped(ctx.text(), data, width, env);
+        self.content_height =
+            self.lines.len() as f64 * Self::line_height(env) + 2. * Self::padding_top(env);
+
+        let line_height = Self::line_height(env);
+        let top = self.line_of(self.caret) as f64 * line_height;
+        let bottom = top + line_height + 2. * Self::padding_top(env);
+        if top < self.scroll_y {
+            self.scroll_y = top;
+        } else if bottom > self.scroll_y + self.size.height {
+            self.scroll_y = bottom - self.size.height;
+        }
+        self.clamp_scroll();
+        self.reset_cursor_blink(ctx);
+        ctx.invalidate();
+    }
+
+    fn find_state(&self) -> FindMatches {
+        FindMatches {
+            count: self.find_matches.len(),
+            current: self.current_match,
+        }
+    }
+
+    /// Recompute the matches for the current find query.
+    ///
+    /// Returns `true` if the match count or current match changed.
+    fn update_find_matches(&mut self, data: &impl EditableText) -> bool {
+        let old = self.find_state();
+        self.find_matches = if self.find_query.is_empty() {
+            Vec::new()
+        } else {
+            data.text()
+                .match_indices(self.find_query.as_str())
+                .map(|(start, s)| start..start + s.len())
+                .collect()
+        };
+        if self.find_matches.is_empty() {
+            self.current_match = None;
+        } else if let Some(current) = self.current_match {
+            self.current_match = Some(current.min(self.find_matches.len() - 1));
+        }
+        self.find_state() != old
+    }
+
+    /// Select the match at `idx`, scroll it into view, and tell the
+    /// application about it.
+    fn select_match<T: EditableText + 'static>(
+        &mut self,
+        ctx: &mut EventCtx,
+        idx: Option<usize>,
+        data: &T,
+        env: &Env,
+    ) {
+        self.current_match = idx;
+        if let Some(range) = idx.and_then(|idx| self.find_matches.get(idx).cloned()) {
+            self.anchor = range.start;
+            self.caret = range.end;
+            self.goal_x = None;
+        }
+        self.after_edit(ctx, data, env);
+        self.report_find_matches(ctx);
+    }
+
+    fn report_find_matches(&mut self, ctx: &mut EventCtx) {
+        self.report_pending = false;
+        let command = Command::new(commands::FIND_MATCHES, self.find_state());
+        ctx.submit_command(command, None);
+    }
+
+    /// Keep the selection within `data`, on character boundaries, after
+    /// the text was changed from outside.
+    fn clamp_selection(&mut self, data: &str) {
+        self.anchor = clamp_to_char_boundary(data, self.anchor);
+        self.caret = clamp_to_char_boundary(data, self.caret);
+    }
+
+    fn clamp_scroll(&mut self) {
+        let max = (self.content_height - self.size.height).max(0.0);
+        self.scroll_y = self.scroll_y.max(0.0).min(max);
+    }
+
+    fn reset_cursor_blink(&mut self, ctx: &mut EventCtx) {
+        self.cursor_on = true;
+        let deadline = Instant::now() + Duration::from_millis(500);
+        self.cursor_timer = ctx.request_timer(deadline);
+    }
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The offset in `text` nearest to `offset`, at or before it, that is at the
+/// start of a character.
+fn clamp_to_char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// A paragraph of the text, with the lines it is wrapped into.
+#[derive(Debug, Clone, PartialEq)]
+struct Paragraph {
+    /// The byte range of the paragraph in the text, without its line break.
+    range: Range<usize>,
+    /// The wrapped lines, as byte ranges of the text.
+    lines: Vec<Range<usize>>,
+}
+
+/// Split `text`, which starts at byte `offset` of the whole text, into
+/// paragraphs, and break each into lines with `wrap`.
+fn wrap_paragraphs(
+    text: &str,
+    offset: usize,
+    mut wrap: impl FnMut(&str) -> Vec<Range<usize>>,
+) -> Vec<Paragraph> {
+    let mut start = offset;
+    text.split('\n')
+        .map(|paragraph| {
+            let lines = wrap(paragraph)
+                .into_iter()
+                .map(|line| start + line.start..start + line.end)
+                .collect();
+            let range = start..start + paragraph.len();
+            start = range.end + 1;
+            Paragraph { range, lines }
+        })
+        .collect()
+}
+
+/// Update `paragraphs` after the byte `range` of the text was replaced with
+/// `new_len` bytes, wrapping again only the paragraphs the edit touched and
+/// moving the ones after them.
+fn rewrap_edit(
+    paragraphs: &mut Vec<Paragraph>,
+    data: &impl EditableText,
+    range: Range<usize>,
+    new_len: usize,
+    wrap: impl FnMut(&str) -> Vec<Range<usize>>,
+) {
+    // An edit touches a paragraph if it reaches the paragraph or the line
+    // break after it.
+    let first = paragraphs
+        .iter()
+        .position(|paragraph| paragraph.range.end >= range.start)
+        .unwrap_or(paragraphs.len() - 1);
+    let last = paragraphs
+        .iter()
+        .rposition(|paragraph| paragraph.range.start <= range.end)
+        .unwrap_or(0)
+        .max(first);
+    let moved = |offset: usize| offset + new_len - range.len();
+    for paragraph in &mut paragraphs[last + 1..] {
+        paragraph.range = moved(paragraph.range.start)..moved(paragraph.range.end);
+        for line in &mut paragraph.lines {
+            *line = moved(line.start)..moved(line.end);
+        }
+    }
+    let start = paragraphs[first].range.start;
+    let end = moved(paragraphs[last].range.end);
+    let rewrapped = wrap_paragraphs(&data.slice(start..end), start, wrap);
+    paragraphs.splice(first..=last, rewrapped);
+}
+
+/// Break a paragraph into lines no wider than `width`, given the
+/// horizontal position of each byte offset.
+///
+/// Lines break after whitespace, which is allowed to run past the width,
+/// or within a word that does not fit on a line by itself. The returned
+/// ranges cover the whole paragraph.
+fn wrap_paragraph(paragraph: &str, width: f64, x_of: impl Fn(usize) -> f64) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut last_break = None;
+    for (index, c) in paragraph.char_indices() {
+        let end = index + c.len_utf8();
+        if c.is_whitespace() {
+            last_break = Some(end);
+            continue;
+        }
+        while index > start && x_of(end) - x_of(start) > width {
+            let at = match last_break {
+                Some(at) if at > start && at <= index => at,
+                _ => index,
+            };
+            lines.push(start..at);
+            start = at;
+            last_break = None;
+        }
+    }
+    lines.push(start..paragraph.len());
+    lines
+}
+
+impl<T: EditableText + 'static> Widget<T> for TextArea {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let width = self.wrap_width;
+        self.ensure_wrapped(ctx.text(), data, width, env);
+        let read_only = env.get(theme::READ_ONLY);
+
+        match event {
+            Event::MouseDown(mouse) => {
+                ctx.request_focus();
+                ctx.set_active(true);
+                let offset = self.offset_for_point(ctx.text(), mouse.pos, data, env);
+                if mouse.mods.shift {
+                    self.caret = offset;
+                } else {
+                    self.caret_to(offset);
+                }
+                self.goal_x = None;
+                ctx.invalidate();
+                self.reset_cursor_blink(ctx);
+            }
+            Event::MouseMoved(mouse) => {
+                ctx.set_cursor(&Cursor::IBeam);
+                if ctx.is_active() {
+                    self.caret = self.offset_for_point(ctx.text(), mouse.pos, data, env);
+                    self.goal_x = None;
+                    ctx.invalidate();
+                }
+            }
+            Event::MouseUp(_) => {
+                if ctx.is_active() {
+                    ctx.set_active(false);
+                    ctx.invalidate();
+                }
+            }
+            Event::Wheel(wheel) if self.content_height > self.size.height => {
+                self.scroll_y += wheel.delta.y;
+                self.clamp_scroll();
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            Event::Timer(id) => {
+                if *id == self.cursor_timer {
+                    self.cursor_on = !self.cursor_on;
+                    ctx.invalidate();
+                    let deadline = Instant::now() + Duration::from_millis(500);
+                    self.cursor_timer = ctx.request_timer(deadline);
+                }
+            }
+            Event::ValidateCommand(selector) => {
+                if self.can_handle_command(selector, read_only) {
+                    ctx.set_handled();
+                }
+            }
+            Event::Command(ref cmd)
+                if ctx.has_focus() && self.can_handle_command(&cmd.selector, read_only) =>
+            {
+                if let Some(action) = Self::action_for_command(&cmd.selector) {
+                    self.do_edit_action(ctx, action, data, env);
+                    ctx.set_handled();
+                }
+            }
+            Event::Command(ref cmd) if ctx.has_focus() && cmd.selector == commands::EDIT_ACTION => {
+                if let Some(action) = cmd.get_object::<EditAction>() {
+                    self.do_edit_action(ctx, action.clone(), data, env);
+                }
+                ctx.set_handled();
+            }
+            Event::Command(ref cmd) if self.searchable && cmd.selector == commands::FIND => {
+                self.find_query = cmd.get_object::<String>().cloned().unwrap_or_default();
+                self.update_find_matches(data);
+                // Select the first match after the caret, like most editors.
+                let from = self.selection().start;
+                let first = self
+                    .find_matches
+                    .iter()
+                    .position(|range| range.start >= from)
+                    .or_else(|| self.find_matches.first().map(|_| 0));
+                self.select_match(ctx, first, data, env);
+                ctx.set_handled();
+            }
+            Event::Command(ref cmd)
+                if self.searchable
+                    && (cmd.selector == commands::FIND_NEXT
+                        || cmd.selector == commands::FIND_PREVIOUS) =>
+            {
+                let count = self.find_matches.len();
+                if count > 0 {
+                    let next = match self.current_match {
+                        None => 0,
+                        Some(idx) if cmd.selector == commands::FIND_NEXT => (idx + 1) % count,
+                        Some(idx) => (idx + count - 1) % count,
+                    };
+                    self.select_match(ctx, Some(next), data, env);
+                }
+                ctx.set_handled();
+            }
+            Event::AnimFrame(_) if self.report_pending => self.report_find_matches(ctx),
+            Event::Paste(ref item) if !read_only => {
+                if let Some(string) = item.get_string() {
+                    self.goal_x = None;
+                    self.insert(data, &string);
+                    self.after_edit(ctx, data, env);
+                }
+            }
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
+            Event::KeyDown(key_event) if ctx.has_focus() => {
+                let action = match self.keymap.action_for(key_event) {
+                    Some(action) => Some(action),
+                    None if key_event.key_code.is_printable() => key_event
+                        .text()
+                        .map(|text| EditAction::Insert(text.to_owned())),
+                    None => None,
+                };
+                if let Some(action) = action {
+                    // Keys that do nothing here are left for hotkeys.
+                    let consumed = match action {
+                        EditAction::Insert(_) => !read_only,
+                        _ => true,
+                    };
+                    self.do_edit_action(ctx, action, data, env);
+                    if consumed {
+                        ctx.set_handled();
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        let changed = !old_data.map_or(false, |old| old.same(data));
+        if changed {
+            // Does nothing if the change is an edit already rewrapped.
+            let width = self.wrap_width;
+            self.ensure_wrapped(ctx.text(), data, width, env);
+        }
+        if self.searchable && changed && self.update_find_matches(data) {
+            // Commands can't be submitted from here; report on the next frame.
+            self.report_pending = true;
+            ctx.request_anim_frame();
+        }
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("TextArea");
+
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            DEFAULT_WIDTH
+        };
+        let wrap_width = width - 2. * Self::padding_left(env);
+        self.ensure_wrapped(layout_ctx.text(), data, wrap_width, env);
+        self.content_height =
+            self.lines.len() as f64 * Self::line_height(env) + 2. * Self::padding_top(env);
+        let height = if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            self.content_height
+        };
+        self.size = bc.constrain(Size::new(width, height));
+        self.clamp_scroll();
+        self.size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let line_height = Self::line_height(env);
+        let padding_top = Self::padding_top(env);
+        let padding_left = Self::padding_left(env);
+        let background_color = if env.get(theme::READ_ONLY) {
+            env.get(theme::BACKGROUND_DARK)
+        } else {
+            env.get(theme::BACKGROUND_LIGHT)
+        };
+        let border_color = if base_state.has_focus() {
+            env.get(theme::PRIMARY_LIGHT)
+        } else {
+            env.get(theme::BORDER)
+        };
+        let selection_color = env.get(theme::SELECTION_COLOR);
+        let find_highlight_color = env.get(theme::FIND_HIGHLIGHT_COLOR);
+        let text_color = env.get(theme::LABEL_COLOR);
+
+        let size = base_state.size();
+        let shape = RoundedRect::from_origin_size(
+            Point::ORIGIN,
+            Size::new(size.width - 1.0, size.height).to_vec2(),
+            2.,
+        );
+        paint_ctx.fill(shape, &background_color);
+
+        // Only the lines in the visible region are laid out.
+        let visible = paint_ctx.region().to_rect();
+        let first = ((visible.y0 + self.scroll_y - padding_top) / line_height)
+            .floor()
+            .max(0.) as usize;
+        let last = ((visible.y1 + self.scroll_y - padding_top) / line_height)
+            .ceil()
+            .max(0.) as usize;
+        let last = last.min(self.lines.len());
+
+        let selection = self.selection();
+        let caret_line = self.line_of(self.caret);
+        let show_caret = base_state.has_focus() && self.cursor_on;
+        let mut rows = Vec::new();
+        for index in first.min(last)..last {
+            let line = self.lines[index].clone();
+            let line_text = data.slice(line.clone());
+            let layout = Self::get_layout(paint_ctx.text(), &line_text, env);
+            let x_of = |offset: usize| {
+                layout
+                    .hit_test_text_position(offset - line.start)
+                    .map_or(0.0, |hit| hit.point.x)
+            };
+            let top = padding_top + index as f64 * line_height - self.scroll_y;
+            let row_rect = |x0: f64, x1: f64| {
+                Rect::new(x0 + padding_left, top, x1 + padding_left, top + line_height)
+            };
+
+            let matches: Vec<Rect> = self
+                .find_matches
+                .iter()
+                .filter(|range| range.start < line.end && range.end > line.start)
+                .map(|range| {
+                    row_rect(
+                        x_of(range.start.max(line.start)),
+                        x_of(range.end.min(line.end)),
+                    )
+                })
+                .collect();
+            let mut highlight = None;
+            if selection.start != selection.end
+                && selection.start <= line.end
+                && selection.end > line.start
+            {
+                let x0 = x_of(selection.start.max(line.start));
+                let mut x1 = x_of(selection.end.min(line.end));
+                // Show that the line break at the end of the line is selected.
+                if selection.end > line.end && self.line_end(index, &line_text) == line.end {
+                    x1 += font_size / 3.;
+                }
+                highlight = Some(row_rect(x0, x1));
+            }
+            let caret = if show_caret && index == caret_line {
+                Some(x_of(self.caret) + padding_left)
+            } else {
+                None
+            };
+            rows.push((layout, top, matches, highlight, caret));
+        }
+
+        paint_ctx.with_save(|rc| {
+            rc.clip(shape);
+            for (layout, top, matches, highlight, caret) in &rows {
+                for rect in matches {
+                    rc.fill(*rect, &find_highlight_color);
+                }
+                if let Some(rect) = highlight {
+                    rc.fill(*rect, &selection_color);
+                }
+                rc.draw_text(layout, (padding_left, top + font_size), &text_color);
+                if let Some(x) = caret {
+                    let line = Line::new((*x, *top), (*x, top + line_height));
+                    rc.stroke(line, &env.get(theme::CURSOR_COLOR), 1.);
+                }
+            }
+        });
+
+        paint_ctx.stroke(shape, &border_color, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_at_whitespace_and_long_words() {
+        let x_of = |offset: usize| offset as f64 * 10.0;
+        assert_eq!(wrap_paragraph("", 50.0, x_of), vec![0..0]);
+        assert_eq!(wrap_paragraph("one two", 100.0, x_of), vec![0..7]);
+        assert_eq!(
+            wrap_paragraph("one two six", 50.0, x_of),
+            vec![0..4, 4..8, 8..11]
+        );
+        assert_eq!(
+            wrap_paragraph("abcdefgh ij", 50.0, x_of),
+            vec![0..5, 5..9, 9..11]
+        );
+        // Trailing whitespace stays on its line.
+        assert_eq!(
+            wrap_paragraph("abcd      e", 50.0, x_of),
+            vec![0..10, 10..11]
+        );
+    }
+
+    #[test]
+    fn rewrap_only_touched_paragraphs() {
+        let wrap = |paragraph: &str| wrap_paragraph(paragraph, 50.0, |offset| offset as f64 * 10.0);
+        let text = "one two\nthree four\nfive six";
+        let edits = [
+            // Typing within a paragraph.
+            (4..4, "more "),
+            // Splitting a paragraph.
+            (3..4, "\n"),
+            // Joining two paragraphs.
+            (18..19, ""),
+            // Replacing across paragraphs.
+            (2..20, "x\ny"),
+            // Appending to the last paragraph.
+            (27..27, " end"),
+        ];
+        for (range, new) in edits.iter() {
+            let mut paragraphs = wrap_paragraphs(text, 0, wrap);
+            let mut data = text.to_string();
+            data.edit(range.clone(), new);
+            rewrap_edit(&mut paragraphs, &data, range.clone(), new.len(), wrap);
+            assert_eq!(paragraphs, wrap_paragraphs(&data, 0, wrap));
+        }
+    }
+
+    #[test]
+    fn clamp_inside_character() {
+        // "é" takes two bytes.
+        assert_eq!(clamp_to_char_boundary("né", 2), 1);
+        assert_eq!(clamp_to_char_boundary("né", 3), 3);
+        assert_eq!(clamp_to_char_boundary("né", 10), 3);
+    }
+}
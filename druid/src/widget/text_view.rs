@@ -0,0 +1,175 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget for displaying large amounts of multi-line text.
+
+use std::collections::HashMap;
+
+use crate::kurbo::{Point, Size};
+use crate::piet::{
+    FontBuilder, PietText, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
+};
+use crate::theme;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, TextBuffer,
+    UpdateCtx, Widget,
+};
+
+/// Line height, as a multiple of the font size.
+const LINE_HEIGHT_FACTOR: f64 = 1.2;
+const PADDING_LEFT: f64 = 4.;
+/// Lines laid out beyond the visible region, to keep scrolling smooth.
+const OVERSCAN_LINES: usize = 8;
+
+/// A widget that displays multi-line text, laying out only the visible lines.
+///
+/// Rather than building one text layout for the whole document, `TextView`
+/// reads lines from its [`TextBuffer`] and builds layouts only for the lines
+/// that intersect the visible region when painting. This keeps the cost of
+/// painting proportional to the size of the viewport, so it can show
+/// documents that are many megabytes long. Put it in a [`Scroll`] to scroll.
+///
+/// Lines are not wrapped. Where the width isn't bounded, as in a [`Scroll`]
+/// that scrolls horizontally, every line is measured once after each
+/// change of the text, to find the widget's width.
+///
+/// [`TextBuffer`]: ../struct.TextBuffer.html
+/// [`Scroll`]: struct.Scroll.html
+#[derive(Default)]
+pub struct TextView {
+    /// Layouts for recently painted lines, by line number.
+    layouts: HashMap<usize, PietTextLayout>,
+    /// The width of the widest line, once it has been measured.
+    max_line_width: Option<f64>,
+}
+
+impl TextView {
+    /// Create a new `TextView`.
+    pub fn new() -> TextView {
+        TextView::default()
+    }
+
+    fn line_height(env: &Env) -> f64 {
+        env.get(theme::TEXT_SIZE_NORMAL) * LINE_HEIGHT_FACTOR
+    }
+
+    fn build_line_layout(&self, piet_text: &mut PietText, line: &str, env: &Env) -> PietTextLayout {
+        let font_name = env.get(theme::FONT_NAME);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let font = piet_text
+            .new_font_by_name(font_name, font_size)
+            .build()
+            .unwrap();
+        piet_text.new_text_layout(&font, line).build().unwrap()
+    }
+
+    /// The width of the widest line of `data`.
+    fn measure(piet_text: &mut PietText, data: &TextBuffer, env: &Env) -> f64 {
+        let font_name = env.get(theme::FONT_NAME);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let font = piet_text
+            .new_font_by_name(font_name, font_size)
+            .build()
+            .unwrap();
+        (0..data.line_count())
+            .map(|line| {
+                let text = data.line(line);
+                piet_text
+                    .new_text_layout(&font, &text)
+                    .build()
+                    .unwrap()
+                    .width()
+            })
+            .fold(0., f64::max)
+    }
+}
+
+impl Widget<TextBuffer> for TextView {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut TextBuffer, _env: &Env) {}
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: Option<&TextBuffer>,
+        data: &TextBuffer,
+        _env: &Env,
+    ) {
+        if old_data.map(|old| !old.same(data)).unwrap_or(true) {
+            self.layouts.clear();
+            self.max_line_width = None;
+            ctx.invalidate();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &TextBuffer,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("TextView");
+
+        let height = data.line_count() as f64 * Self::line_height(env);
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            let max_line_width = match self.max_line_width {
+                Some(width) => width,
+                None => {
+                    let width = Self::measure(layout_ctx.text(), data, env);
+                    self.max_line_width = Some(width);
+                    width
+                }
+            };
+            max_line_width + theme::spacing(env, PADDING_LEFT) * 2.
+        };
+        bc.constrain(Size::new(width, height))
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        _base_state: &BaseState,
+        data: &TextBuffer,
+        env: &Env,
+    ) {
+        let line_height = Self::line_height(env);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let text_color = env.get(theme::LABEL_COLOR);
+        let padding_left = theme::spacing(env, PADDING_LEFT);
+
+        let visible = paint_ctx.region().to_rect();
+        let first = ((visible.y0 / line_height).floor().max(0.) as usize)
+            .saturating_sub(OVERSCAN_LINES)
+            .min(data.line_count());
+        let last = ((visible.y1 / line_height).ceil().max(0.) as usize + OVERSCAN_LINES)
+            .min(data.line_count());
+
+        // Forget layouts for lines that have scrolled well out of view.
+        self.layouts
+            .retain(|line, _| *line >= first && *line < last);
+
+        for line in first..last {
+            if !self.layouts.contains_key(&line) {
+                let text = data.line(line);
+                let layout = self.build_line_layout(paint_ctx.text(), &text, env);
+                self.layouts.insert(line, layout);
+            }
+            let layout = &self.layouts[&line];
+            let baseline = line as f64 * line_height + font_size;
+            paint_ctx.draw_text(layout, Point::new(padding_left, baseline), &text_color);
+        }
+    }
+}
@@ -17,11 +17,12 @@
 use std::cmp::{max, min};
 use std::ops::Range;
 use std::time::{Duration, Instant};
-use unicode_segmentation::GraphemeCursor;
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
 
 use crate::{
-    Application, BaseState, BoxConstraints, Cursor, Env, Event, EventCtx, HotKey, KeyCode,
-    LayoutCtx, PaintCtx, RawMods, SysMods, TimerToken, UpdateCtx, Widget,
+    commands, Application, BaseState, BoxConstraints, Cursor, EditAction, EditableText, Env, Event,
+    EventCtx, KeyCode, KeyMap, LayoutCtx, LifeCycle, Movement, PaintCtx, Selector, TimerToken,
+    UpdateCtx, Widget,
 };
 
 use crate::kurbo::{Affine, Line, Point, RoundedRect, Size, Vec2};
@@ -91,6 +92,29 @@ impl Selection {
 }
 
 /// A widget that allows user text input.
+///
+/// The text box edits a single line of text, which can be any
+/// [`EditableText`]: a `String`, or a [`TextBuffer`] to keep an undo
+/// history. [`new`] and [`with_placeholder`] edit a `String`; use [`raw`]
+/// for other text. The caret is placed
+/// with the mouse and blinks while the text box has focus; the selection is
+/// extended by dragging, or with the arrow keys while shift is held. Key
+/// presses are translated into [`EditAction`]s by its [`KeyMap`], and the
+/// clipboard is used through the [`COPY`], [`CUT`] and [`PASTE`] commands,
+/// as well as [`Event::Paste`]. Unless the keymap binds it, tab moves focus
+/// to the next widget in the focus chain, and shift-tab to the previous one.
+///
+/// [`EditableText`]: ../trait.EditableText.html
+/// [`TextBuffer`]: ../struct.TextBuffer.html
+/// [`new`]: #method.new
+/// [`with_placeholder`]: #method.with_placeholder
+/// [`raw`]: #method.raw
+/// [`EditAction`]: ../enum.EditAction.html
+/// [`KeyMap`]: ../struct.KeyMap.html
+/// [`COPY`]: ../commands/constant.COPY.html
+/// [`CUT`]: ../commands/constant.CUT.html
+/// [`PASTE`]: ../commands/constant.PASTE.html
+/// [`Event::Paste`]: ../enum.Event.html#variant.Paste
 #[derive(Debug, Clone)]
 pub struct TextBox {
     placeholder: String,
@@ -99,6 +123,11 @@ pub struct TextBox {
     selection: Selection,
     cursor_timer: TimerToken,
     cursor_on: bool,
+    keymap: KeyMap,
+    #[cfg(feature = "spellcheck")]
+    spellchecker: Option<std::sync::Arc<dyn crate::spellcheck::SpellChecker>>,
+    #[cfg(feature = "spellcheck")]
+    misspelled: Vec<Range<usize>>,
 }
 
 impl TextBox {
@@ -122,9 +151,79 @@ impl TextBox {
             cursor_timer: TimerToken::INVALID,
             cursor_on: false,
             placeholder: String::new(),
+            keymap: KeyMap::default(),
+            #[cfg(feature = "spellcheck")]
+            spellchecker: None,
+            #[cfg(feature = "spellcheck")]
+            misspelled: Vec::new(),
         }
     }
 
+    /// Builder-style method to set the [`KeyMap`] used to translate key
+    /// presses into [`EditAction`]s.
+    ///
+    /// [`KeyMap`]: ../struct.KeyMap.html
+    /// [`EditAction`]: ../enum.EditAction.html
+    pub fn with_keymap(mut self, keymap: KeyMap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Builder-style method to check the spelling of this TextBox's contents.
+    ///
+    /// Misspelled words are underlined, and right-clicking one shows a menu
+    /// of suggested replacements.
+    #[cfg(feature = "spellcheck")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "spellcheck")))]
+    pub fn with_spellchecker(
+        mut self,
+        checker: impl crate::spellcheck::SpellChecker + 'static,
+    ) -> Self {
+        self.spellchecker = Some(std::sync::Arc::new(checker));
+        self
+    }
+
+    /// If the point is over a misspelled word, select it and ask the window
+    /// to show a menu of suggestions. Returns `true` if a menu was requested.
+    #[cfg(feature = "spellcheck")]
+    fn show_spelling_suggestions(
+        &mut self,
+        ctx: &mut EventCtx,
+        offset: usize,
+        window_pos: Point,
+        data: &str,
+    ) -> bool {
+        use crate::spellcheck::{SpellingSuggestions, MAX_SUGGESTIONS};
+
+        let checker = match self.spellchecker.as_ref() {
+            Some(checker) => checker,
+            None => return false,
+        };
+        let range = match self
+            .misspelled
+            .iter()
+            .find(|r| r.start <= offset && offset <= r.end)
+        {
+            Some(range) => range.clone(),
+            None => return false,
+        };
+        let word = &data[range.clone()];
+        let mut suggestions = checker.suggestions(word);
+        suggestions.truncate(MAX_SUGGESTIONS);
+        self.selection = Selection::new(range.start, range.end);
+
+        let payload = SpellingSuggestions {
+            word: word.to_owned(),
+            suggestions,
+            location: window_pos,
+        };
+        ctx.submit_command(
+            crate::Command::new(commands::SHOW_SPELLING_SUGGESTIONS, payload),
+            None,
+        );
+        true
+    }
+
     fn get_layout(&self, piet_text: &mut PietText, data: &str, env: &Env) -> PietTextLayout {
         let font_name = env.get(theme::FONT_NAME);
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
@@ -137,10 +236,10 @@ impl TextBox {
         piet_text.new_text_layout(&font, data).build().unwrap()
     }
 
-    fn insert(&mut self, src: &mut String, new: &str) {
+    fn insert(&mut self, src: &mut impl EditableText, new: &str) {
         // TODO: handle incomplete graphemes
 
-        src.replace_range(self.selection.range(), new);
+        src.edit(self.selection.range(), new);
         self.selection = Selection::caret(self.selection.min() + new.len());
     }
 
@@ -154,10 +253,11 @@ impl TextBox {
 
     /// For a given point, returns the corresponding offset (in bytes) of
     /// the grapheme cluster closest to that point.
-    fn offset_for_point(&self, point: Point, layout: &PietTextLayout) -> usize {
+    fn offset_for_point(&self, point: Point, layout: &PietTextLayout, env: &Env) -> usize {
         // Translating from screenspace to Piet's text layout representation.
         // We need to account for hscroll_offset state and TextBox's padding.
-        let translated_point = Point::new(point.x + self.hscroll_offset - PADDING_LEFT, point.y);
+        let padding_left = theme::spacing(env, PADDING_LEFT);
+        let translated_point = Point::new(point.x + self.hscroll_offset - padding_left, point.y);
         let hit_test = layout.hit_test_point(translated_point);
         hit_test.metrics.text_position
     }
@@ -174,11 +274,11 @@ impl TextBox {
     }
 
     /// Calculate a stateful scroll offset
-    fn update_hscroll(&mut self, layout: &PietTextLayout) {
+    fn update_hscroll(&mut self, layout: &PietTextLayout, env: &Env) {
         let cursor_x = self.x_for_offset(layout, self.cursor());
         let overall_text_width = layout.width();
 
-        let padding = PADDING_LEFT * 2.;
+        let padding = theme::spacing(env, PADDING_LEFT) * 2.;
         if overall_text_width < self.width {
             // There's no offset if text is smaller than text box
             //
@@ -202,18 +302,141 @@ impl TextBox {
 
     // TODO: Grapheme isn't the correct unit for backspace, see:
     // https://github.com/xi-editor/xi-editor/blob/master/rust/core-lib/src/backspace.rs
-    fn backspace(&mut self, src: &mut String) {
+    fn backspace(&mut self, src: &mut impl EditableText) {
         if self.selection.is_caret() {
             let cursor = self.cursor();
-            let new_cursor = prev_grapheme(&src, cursor);
-            src.replace_range(new_cursor..cursor, "");
+            let new_cursor = prev_grapheme(&src.text(), cursor);
+            src.edit(new_cursor..cursor, "");
             self.cursor_to(new_cursor);
         } else {
-            src.replace_range(self.selection.range(), "");
+            src.edit(self.selection.range(), "");
             self.cursor_to(self.selection.min());
         }
     }
 
+    /// The cursor position after the given movement.
+    fn offset_for_movement(&self, movement: Movement, data: &str) -> usize {
+        let cursor = self.cursor();
+        match movement {
+            Movement::Left => prev_grapheme(data, cursor),
+            Movement::Right => next_grapheme(data, cursor),
+            Movement::WordLeft => prev_word(data, cursor),
+            Movement::WordRight => next_word(data, cursor),
+            Movement::LineStart | Movement::Up => 0,
+            Movement::LineEnd | Movement::Down => data.len(),
+        }
+    }
+
+    fn copy(&self, data: &str) {
+        if let Some(text) = data.get(self.selection.range()) {
+            Application::clipboard().put_string(text);
+        }
+    }
+
+    /// Whether we can currently handle one of the standard editing commands.
+    fn can_handle_command(&self, selector: &Selector, read_only: bool) -> bool {
+        match *selector {
+            commands::COPY => !self.selection.is_caret(),
+            commands::CUT | commands::DELETE => !read_only && !self.selection.is_caret(),
+            commands::PASTE => !read_only,
+            commands::SELECT_ALL => true,
+            _ => false,
+        }
+    }
+
+    /// The edit action performed for one of the standard editing commands.
+    fn action_for_command(selector: &Selector) -> Option<EditAction> {
+        match *selector {
+            commands::COPY => Some(EditAction::Copy),
+            commands::CUT => Some(EditAction::Cut),
+            commands::DELETE => Some(EditAction::Delete),
+            commands::PASTE => Some(EditAction::Paste),
+            commands::SELECT_ALL => Some(EditAction::SelectAll),
+            _ => None,
+        }
+    }
+
+    fn do_edit_action(
+        &mut self,
+        ctx: &mut EventCtx,
+        action: EditAction,
+        data: &mut impl EditableText,
+        read_only: bool,
+    ) {
+        match action {
+            EditAction::Insert(text) if !read_only => self.insert(data, &text),
+            EditAction::Backspace if !read_only => self.backspace(data),
+            EditAction::Delete if !read_only => {
+                if self.selection.is_caret() {
+                    // Never touch the characters before the cursor.
+                    let text = data.text();
+                    if next_grapheme_exists(&text, self.cursor()) {
+                        self.cursor_to(next_grapheme(&text, self.cursor()));
+                        drop(text);
+                        self.backspace(data);
+                    }
+                } else {
+                    self.backspace(data);
+                }
+            }
+            EditAction::DeleteWordBackward if !read_only => {
+                if self.selection.is_caret() {
+                    self.selection.start = prev_word(&data.text(), self.cursor());
+                }
+                self.backspace(data);
+            }
+            EditAction::DeleteWordForward if !read_only => {
+                if self.selection.is_caret() {
+                    self.selection.start = next_word(&data.text(), self.cursor());
+                }
+                self.backspace(data);
+            }
+            EditAction::Move(movement) => {
+                let to = match movement {
+                    Movement::Left if !self.selection.is_caret() => self.selection.min(),
+                    Movement::Right if !self.selection.is_caret() => self.selection.max(),
+                    movement => self.offset_for_movement(movement, &data.text()),
+                };
+                self.cursor_to(to);
+            }
+            EditAction::ModifySelection(movement) => {
+                self.selection.end = self.offset_for_movement(movement, &data.text());
+            }
+            EditAction::SelectAll => self.selection = Selection::new(0, data.text().len()),
+            EditAction::Transpose if !read_only && self.selection.is_caret() => {
+                let cursor = self.cursor();
+                let text = data.text();
+                let mid = if next_grapheme_exists(&text, cursor) {
+                    cursor
+                } else {
+                    prev_grapheme(&text, cursor)
+                };
+                let start = prev_grapheme(&text, mid);
+                let end = next_grapheme(&text, mid);
+                if start < mid && mid < end {
+                    let swapped = format!("{}{}", &text[mid..end], &text[start..mid]);
+                    drop(text);
+                    data.edit(start..end, &swapped);
+                    self.cursor_to(end);
+                }
+            }
+            EditAction::Copy => self.copy(&data.text()),
+            EditAction::Cut => {
+                self.copy(&data.text());
+                if !read_only && !self.selection.is_caret() {
+                    self.backspace(data);
+                }
+            }
+            EditAction::Paste if !read_only => {
+                if let Some(string) = Application::clipboard().get_string() {
+                    self.insert(data, &string);
+                }
+            }
+            _ => (),
+        }
+        self.reset_cursor_blink(ctx);
+    }
+
     fn reset_cursor_blink(&mut self, ctx: &mut EventCtx) {
         self.cursor_on = true;
         let deadline = Instant::now() + Duration::from_millis(500);
@@ -221,17 +444,32 @@ impl TextBox {
     }
 }
 
-impl Widget<String> for TextBox {
-    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut String, env: &Env) {
+impl<T: EditableText> Widget<T> for TextBox {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
         // Guard against external changes in data
-        self.selection = self.selection.constrain_to(data);
+        self.selection = self.selection.constrain_to(&data.text());
+        let read_only = env.get(theme::READ_ONLY);
 
-        let mut text_layout = self.get_layout(ctx.text(), data, env);
+        let mut text_layout = self.get_layout(ctx.text(), &data.text(), env);
         match event {
             Event::MouseDown(mouse) => {
                 ctx.request_focus();
+                let cursor_off = self.offset_for_point(mouse.pos, &text_layout, env);
+                #[cfg(feature = "spellcheck")]
+                {
+                    if mouse.button == crate::MouseButton::Right
+                        && self.show_spelling_suggestions(
+                            ctx,
+                            cursor_off,
+                            mouse.window_pos,
+                            &data.text(),
+                        )
+                    {
+                        ctx.invalidate();
+                        return;
+                    }
+                }
                 ctx.set_active(true);
-                let cursor_off = self.offset_for_point(mouse.pos, &text_layout);
                 if mouse.mods.shift {
                     self.selection.end = cursor_off;
                 } else {
@@ -243,7 +481,7 @@ impl Widget<String> for TextBox {
             Event::MouseMoved(mouse) => {
                 ctx.set_cursor(&Cursor::IBeam);
                 if ctx.is_active() {
-                    self.selection.end = self.offset_for_point(mouse.pos, &text_layout);
+                    self.selection.end = self.offset_for_point(mouse.pos, &text_layout, env);
                     ctx.invalidate();
                 }
             }
@@ -261,112 +499,108 @@ impl Widget<String> for TextBox {
                     self.cursor_timer = ctx.request_timer(deadline);
                 }
             }
+            Event::ValidateCommand(selector) => {
+                if self.can_handle_command(selector, read_only) {
+                    ctx.set_handled();
+                }
+            }
             Event::Command(ref cmd)
-                if ctx.has_focus()
-                    && (cmd.selector == crate::commands::COPY
-                        || cmd.selector == crate::commands::CUT) =>
+                if ctx.has_focus() && self.can_handle_command(&cmd.selector, read_only) =>
             {
-                if let Some(text) = data.get(self.selection.range()) {
-                    Application::clipboard().put_string(text);
+                if let Some(action) = Self::action_for_command(&cmd.selector) {
+                    self.do_edit_action(ctx, action, data, read_only);
+                    text_layout = self.get_layout(ctx.text(), &data.text(), env);
+                    self.update_hscroll(&text_layout, env);
+                    ctx.invalidate();
+                    ctx.set_handled();
                 }
-                if !self.selection.is_caret() && cmd.selector == crate::commands::CUT {
-                    self.backspace(data);
+            }
+            Event::Command(ref cmd) if ctx.has_focus() && cmd.selector == commands::EDIT_ACTION => {
+                if let Some(action) = cmd.get_object::<EditAction>() {
+                    self.do_edit_action(ctx, action.clone(), data, read_only);
+                    text_layout = self.get_layout(ctx.text(), &data.text(), env);
+                    self.update_hscroll(&text_layout, env);
+                    ctx.invalidate();
+                }
+                ctx.set_handled();
+            }
+            #[cfg(feature = "spellcheck")]
+            Event::Command(ref cmd)
+                if ctx.has_focus()
+                    && !read_only
+                    && cmd.selector == commands::REPLACE_MISSPELLING =>
+            {
+                if let Some(word) = cmd.get_object::<String>() {
+                    self.insert(data, word);
+                    text_layout = self.get_layout(ctx.text(), &data.text(), env);
+                    self.update_hscroll(&text_layout, env);
+                    ctx.invalidate();
                 }
                 ctx.set_handled();
             }
-            Event::Paste(ref item) => {
+            Event::Paste(ref item) if !read_only => {
                 if let Some(string) = item.get_string() {
                     self.insert(data, &string);
                     self.reset_cursor_blink(ctx);
                 }
             }
-            Event::KeyDown(key_event) => {
-                match key_event {
-                    // Select all (Ctrl+A || Cmd+A)
-                    k_e if (HotKey::new(SysMods::Cmd, "a")).matches(k_e) => {
-                        self.selection = Selection::new(0, data.len());
-                    }
-                    // Jump left (Ctrl+ArrowLeft || Cmd+ArrowLeft)
-                    k_e if HotKey::new(SysMods::Cmd, KeyCode::ArrowLeft).matches(k_e)
-                        || HotKey::new(None, KeyCode::Home).matches(k_e) =>
-                    {
-                        self.cursor_to(0);
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Jump right (Ctrl+ArrowRight || Cmd+ArrowRight)
-                    k_e if HotKey::new(SysMods::Cmd, KeyCode::ArrowRight).matches(k_e)
-                        || HotKey::new(None, KeyCode::End).matches(k_e) =>
+            Event::LifeCycle(LifeCycle::WidgetAdded) => ctx.register_for_focus(),
+            Event::KeyDown(key_event)
+                if ctx.has_focus()
+                    && key_event.key_code == KeyCode::Tab
+                    && self.keymap.action_for(key_event).is_none() =>
+            {
+                if key_event.mods.shift {
+                    ctx.focus_prev();
+                } else {
+                    ctx.focus_next();
+                }
+                ctx.set_handled();
+            }
+            Event::KeyDown(key_event) if ctx.has_focus() => {
+                let action = match self.keymap.action_for(key_event) {
+                    Some(action) => Some(action),
+                    // A single line has no line breaks; Enter is left to the
+                    // parent.
+                    None if key_event.key_code == KeyCode::Return
+                        || key_event.key_code == KeyCode::NumpadEnter =>
                     {
-                        self.cursor_to(data.len());
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Select left (Shift+ArrowLeft)
-                    k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowLeft)).matches(k_e) => {
-                        self.selection.end = prev_grapheme(data, self.cursor());
-                    }
-                    // Select right (Shift+ArrowRight)
-                    k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowRight)).matches(k_e) => {
-                        self.selection.end = next_grapheme(data, self.cursor());
+                        None
                     }
-                    // Move left (ArrowLeft)
-                    k_e if (HotKey::new(None, KeyCode::ArrowLeft)).matches(k_e) => {
-                        if self.selection.is_caret() {
-                            self.cursor_to(prev_grapheme(data, self.cursor()));
-                        } else {
-                            self.cursor_to(self.selection.min());
-                        }
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Move right (ArrowRight)
-                    k_e if (HotKey::new(None, KeyCode::ArrowRight)).matches(k_e) => {
-                        if self.selection.is_caret() {
-                            self.cursor_to(next_grapheme(data, self.cursor()));
-                        } else {
-                            self.cursor_to(self.selection.max());
-                        }
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Backspace
-                    k_e if (HotKey::new(None, KeyCode::Backspace)).matches(k_e) => {
-                        self.backspace(data);
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Delete
-                    k_e if (HotKey::new(None, KeyCode::Delete)).matches(k_e) => {
-                        if self.selection.is_caret() {
-                            // Never touch the characters before the cursor.
-                            if next_grapheme_exists(data, self.cursor()) {
-                                self.cursor_to(next_grapheme(data, self.cursor()));
-                                self.backspace(data);
-                            }
-                        } else {
-                            self.backspace(data);
-                        }
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Actual typing
-                    k_e if k_e.key_code.is_printable() => {
-                        let incoming_text = k_e.text().unwrap_or("");
-                        self.insert(data, incoming_text);
-                        self.reset_cursor_blink(ctx);
+                    None if key_event.key_code.is_printable() => key_event
+                        .text()
+                        .map(|text| EditAction::Insert(text.to_owned())),
+                    None => None,
+                };
+                if let Some(action) = action {
+                    // Keys that do nothing here are left for hotkeys.
+                    let consumed = match action {
+                        EditAction::Insert(_) => !read_only,
+                        _ => true,
+                    };
+                    self.do_edit_action(ctx, action, data, read_only);
+                    if consumed {
+                        ctx.set_handled();
                     }
-                    _ => {}
                 }
-                text_layout = self.get_layout(ctx.text(), data, env);
-                self.update_hscroll(&text_layout);
+                text_layout = self.get_layout(ctx.text(), &data.text(), env);
+                self.update_hscroll(&text_layout, env);
                 ctx.invalidate();
             }
             _ => (),
         }
     }
 
-    fn update(
-        &mut self,
-        ctx: &mut UpdateCtx,
-        _old_data: Option<&String>,
-        _data: &String,
-        _env: &Env,
-    ) {
+    #[cfg_attr(not(feature = "spellcheck"), allow(unused_variables))]
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, _env: &Env) {
+        #[cfg(feature = "spellcheck")]
+        {
+            if let Some(checker) = self.spellchecker.as_ref() {
+                if !old_data.map_or(false, |old| old.same(data)) {
+                    self.misspelled = checker.check(&data.text());
+                }
+            }
+        }
         ctx.invalidate();
     }
 
@@ -374,7 +608,7 @@ impl Widget<String> for TextBox {
         &mut self,
         _layout_ctx: &mut LayoutCtx,
         bc: &BoxConstraints,
-        _data: &String,
+        _data: &T,
         env: &Env,
     ) -> Size {
         let default_width = 100.0;
@@ -388,25 +622,27 @@ impl Widget<String> for TextBox {
         bc.constrain((self.width, env.get(theme::BORDERED_WIDGET_HEIGHT)))
     }
 
-    fn paint(
-        &mut self,
-        paint_ctx: &mut PaintCtx,
-        base_state: &BaseState,
-        data: &String,
-        env: &Env,
-    ) {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
         // Guard against changes in data following `event`
-        let content = if data.is_empty() {
+        let text = data.text();
+        let content = if text.is_empty() {
             &self.placeholder
         } else {
-            data
+            &*text
         };
 
         self.selection = self.selection.constrain_to(content);
 
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
         let height = env.get(theme::BORDERED_WIDGET_HEIGHT);
-        let background_color = env.get(theme::BACKGROUND_LIGHT);
+        let padding_left = theme::spacing(env, PADDING_LEFT);
+        let padding_top = theme::spacing(env, PADDING_TOP);
+        let read_only = env.get(theme::READ_ONLY);
+        let background_color = if read_only {
+            env.get(theme::BACKGROUND_DARK)
+        } else {
+            env.get(theme::BACKGROUND_LIGHT)
+        };
         let selection_color = env.get(theme::SELECTION_COLOR);
         let text_color = env.get(theme::LABEL_COLOR);
         let placeholder_color = env.get(theme::PLACEHOLDER_COLOR);
@@ -449,7 +685,7 @@ impl Widget<String> for TextBox {
                     let selection_width = right_offset - left_offset;
 
                     let selection_pos =
-                        Point::new(left_offset + PADDING_LEFT - 1., PADDING_TOP - 2.);
+                        Point::new(left_offset + padding_left - 1., padding_top - 2.);
                     let selection_rect = RoundedRect::from_origin_size(
                         selection_pos,
                         Size::new(selection_width + 2., font_size + 4.).to_vec2(),
@@ -460,8 +696,8 @@ impl Widget<String> for TextBox {
 
                 // Layout, measure, and draw text
                 let text_height = font_size * 0.8;
-                let text_pos = Point::new(0.0 + PADDING_LEFT, text_height + PADDING_TOP);
-                let color = if data.is_empty() {
+                let text_pos = Point::new(0.0 + padding_left, text_height + padding_top);
+                let color = if text.is_empty() {
                     &placeholder_color
                 } else {
                     &text_color
@@ -469,8 +705,26 @@ impl Widget<String> for TextBox {
 
                 rc.draw_text(&text_layout, text_pos, color);
 
-                // Paint the cursor if focused and there's no selection
-                if has_focus && self.cursor_on && self.selection.is_caret() {
+                // Underline misspelled words
+                #[cfg(feature = "spellcheck")]
+                {
+                    if !text.is_empty() {
+                        let squiggle_color = env.get(theme::SPELLING_ERROR_COLOR);
+                        for range in &self.misspelled {
+                            let x0 = self.x_for_offset(&text_layout, range.start);
+                            let x1 = self.x_for_offset(&text_layout, range.end);
+                            let path = crate::spellcheck::squiggle_path(
+                                text_pos.x + x0,
+                                text_pos.x + x1,
+                                text_pos.y + 2.,
+                            );
+                            rc.stroke(path, &squiggle_color, 1.);
+                        }
+                    }
+                }
+
+                // Paint the cursor if focused, editable, and there's no selection
+                if has_focus && !read_only && self.cursor_on && self.selection.is_caret() {
                     let cursor_x = self.x_for_offset(&text_layout, self.cursor());
                     let xy = text_pos + Vec2::new(cursor_x, 2. - font_size);
                     let x2y2 = xy + Vec2::new(0., font_size + 2.);
@@ -488,7 +742,7 @@ impl Widget<String> for TextBox {
 }
 
 /// Gets the next character from the given index.
-fn next_grapheme(src: &str, from: usize) -> usize {
+pub(crate) fn next_grapheme(src: &str, from: usize) -> usize {
     let mut c = GraphemeCursor::new(from, src.len(), true);
     let next_boundary = c.next_boundary(src, 0).unwrap();
     if let Some(next) = next_boundary {
@@ -509,8 +763,26 @@ fn next_grapheme_exists(src: &str, from: usize) -> bool {
     }
 }
 
+/// Gets the start of the word before the given index.
+pub(crate) fn prev_word(src: &str, from: usize) -> usize {
+    src.split_word_bound_indices()
+        .filter(|(idx, word)| *idx < from && word.chars().any(char::is_alphanumeric))
+        .map(|(idx, _)| idx)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Gets the end of the word after the given index.
+pub(crate) fn next_word(src: &str, from: usize) -> usize {
+    src.split_word_bound_indices()
+        .map(|(idx, word)| (idx + word.len(), word))
+        .find(|(end, word)| *end > from && word.chars().any(char::is_alphanumeric))
+        .map(|(end, _)| end)
+        .unwrap_or_else(|| src.len())
+}
+
 /// Gets the previous character from the given index.
-fn prev_grapheme(src: &str, from: usize) -> usize {
+pub(crate) fn prev_grapheme(src: &str, from: usize) -> usize {
     let mut c = GraphemeCursor::new(from, src.len(), true);
     let prev_boundary = c.prev_boundary(src, 0).unwrap();
     if let Some(prev) = prev_boundary {
@@ -0,0 +1,489 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracks of clips laid out in time.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::commands;
+use crate::kurbo::{Line, Point, Rect, RoundedRect, Size, Vec2};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayoutBuilder};
+use crate::theme;
+use crate::widget::ruler::tick_step;
+use crate::{
+    BaseState, BoxConstraints, Cursor, Data, Env, Event, EventCtx, LayoutCtx, PaintCtx, UpdateCtx,
+    Widget,
+};
+
+const HEADER_HEIGHT: f64 = 20.0;
+const TRACK_HEIGHT: f64 = 40.0;
+/// How close the pointer must be to the end of a clip to resize it.
+const EDGE_HIT_DISTANCE: f64 = 5.0;
+/// The narrowest a clip can be resized to, in display points.
+const MIN_CLIP_WIDTH: f64 = 4.0;
+/// How much a wheel delta of one point zooms with Ctrl held.
+const WHEEL_ZOOM_RATE: f64 = 0.002;
+
+/// A clip on a [`Track`], covering a range of time.
+///
+/// [`Track`]: struct.Track.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+    /// The text shown on the clip.
+    pub label: String,
+    /// The time the clip covers.
+    pub range: Range<f64>,
+}
+
+/// A row of clips in a [`Timeline`].
+///
+/// [`Timeline`]: struct.Timeline.html
+#[derive(Debug, Clone, Default)]
+pub struct Track {
+    /// The name of the track, shown at its left.
+    pub name: String,
+    /// The clips, which may overlap; later clips are drawn above earlier
+    /// ones.
+    pub clips: Arc<Vec<Clip>>,
+}
+
+/// The data shown by a [`Timeline`].
+///
+/// Times are in whatever unit the application uses, such as seconds or
+/// days.
+///
+/// [`Timeline`]: struct.Timeline.html
+#[derive(Debug, Clone)]
+pub struct TimelineData {
+    /// The tracks, from top to bottom.
+    pub tracks: Arc<Vec<Track>>,
+    /// The time of the playhead.
+    pub playhead: f64,
+    /// The time at the left edge of the view.
+    pub start: f64,
+    /// The number of display points per unit of time.
+    pub scale: f64,
+}
+
+/// A widget showing tracks of clips along a time axis, with a playhead.
+///
+/// Clips can be dragged to move them, or by their ends to resize them.
+/// Clicking or dragging in the header moves the playhead, which can also
+/// be set with the [`SET_PLAYHEAD`] command.
+///
+/// The wheel scrolls the time axis horizontally and the tracks vertically;
+/// with Ctrl held, it zooms the time axis around the pointer. Only the
+/// tracks and clips within the region being painted are drawn, so the
+/// timeline can hold many of them.
+///
+/// [`SET_PLAYHEAD`]: ../commands/constant.SET_PLAYHEAD.html
+pub struct Timeline {
+    drag: Option<Drag>,
+    /// The vertical scroll position of the tracks.
+    track_offset: f64,
+    min_scale: f64,
+    max_scale: f64,
+    last_mouse_pos: Option<Point>,
+}
+
+/// A drag in progress.
+enum Drag {
+    Playhead,
+    Clip {
+        track: usize,
+        clip: usize,
+        mode: ClipDrag,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClipDrag {
+    /// Moving the clip; the time is the offset of the pointer from the start
+    /// of the clip.
+    Move(f64),
+    ResizeStart,
+    ResizeEnd,
+}
+
+impl Clip {
+    /// Create a clip covering a range of time.
+    pub fn new(label: impl Into<String>, range: Range<f64>) -> Clip {
+        Clip {
+            label: label.into(),
+            range,
+        }
+    }
+}
+
+impl Track {
+    /// Create a track with the given name and clips.
+    pub fn new(name: impl Into<String>, clips: Vec<Clip>) -> Track {
+        Track {
+            name: name.into(),
+            clips: Arc::new(clips),
+        }
+    }
+}
+
+impl TimelineData {
+    /// Create timeline data with these tracks, at the default scale.
+    pub fn new(tracks: Vec<Track>) -> TimelineData {
+        TimelineData {
+            tracks: Arc::new(tracks),
+            ..Default::default()
+        }
+    }
+
+    /// The time at a horizontal position in the view.
+    pub fn time_at(&self, x: f64) -> f64 {
+        self.start + x / self.scale
+    }
+
+    /// The horizontal position of a time in the view.
+    pub fn x_at(&self, time: f64) -> f64 {
+        (time - self.start) * self.scale
+    }
+}
+
+impl Default for TimelineData {
+    fn default() -> Self {
+        TimelineData {
+            tracks: Arc::new(Vec::new()),
+            playhead: 0.0,
+            start: 0.0,
+            scale: 50.0,
+        }
+    }
+}
+
+impl Data for TimelineData {
+    fn same(&self, other: &Self) -> bool {
+        self.tracks.same(&other.tracks)
+            && self.playhead.same(&other.playhead)
+            && self.start.same(&other.start)
+            && self.scale.same(&other.scale)
+    }
+}
+
+impl Timeline {
+    /// Create a new `Timeline`.
+    pub fn new() -> Timeline {
+        Timeline {
+            drag: None,
+            track_offset: 0.0,
+            min_scale: 1e-3,
+            max_scale: 1e4,
+            last_mouse_pos: None,
+        }
+    }
+
+    /// Builder-style method to set the range of scales, in display points
+    /// per unit of time, the user can zoom to.
+    pub fn scale_range(mut self, min: f64, max: f64) -> Self {
+        self.min_scale = min;
+        self.max_scale = max;
+        self
+    }
+
+    /// The index of the track at a vertical position, if any.
+    fn track_at(&self, y: f64, data: &TimelineData) -> Option<usize> {
+        if y < HEADER_HEIGHT {
+            return None;
+        }
+        let index = ((y - HEADER_HEIGHT + self.track_offset) / TRACK_HEIGHT).floor() as usize;
+        if index < data.tracks.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// The vertical position of the top of a track.
+    fn track_y(&self, index: usize) -> f64 {
+        HEADER_HEIGHT + index as f64 * TRACK_HEIGHT - self.track_offset
+    }
+
+    /// The clip under a point, and how dragging it would change it.
+    fn clip_at(&self, pos: Point, data: &TimelineData) -> Option<(usize, usize, ClipDrag)> {
+        let track = self.track_at(pos.y, data)?;
+        let time = data.time_at(pos.x);
+        let edge = EDGE_HIT_DISTANCE / data.scale;
+        data.tracks[track]
+            .clips
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, clip)| time >= clip.range.start - edge && time <= clip.range.end + edge)
+            .map(|(index, clip)| {
+                let mode = if (time - clip.range.start).abs() <= edge {
+                    ClipDrag::ResizeStart
+                } else if (time - clip.range.end).abs() <= edge {
+                    ClipDrag::ResizeEnd
+                } else {
+                    ClipDrag::Move(time - clip.range.start)
+                };
+                (track, index, mode)
+            })
+    }
+
+    /// Scroll the tracks vertically, keeping them within the view.
+    fn scroll_tracks(&mut self, delta: f64, height: f64, data: &TimelineData) {
+        let content = data.tracks.len() as f64 * TRACK_HEIGHT;
+        let max = (content - (height - HEADER_HEIGHT)).max(0.0);
+        self.track_offset = (self.track_offset + delta).max(0.0).min(max);
+    }
+
+    fn drag_to(&mut self, pos: Point, data: &mut TimelineData) {
+        let time = data.time_at(pos.x);
+        let min_length = MIN_CLIP_WIDTH / data.scale;
+        match self.drag {
+            Some(Drag::Playhead) => data.playhead = time.max(0.0),
+            Some(Drag::Clip { track, clip, mode }) => {
+                let tracks = Arc::make_mut(&mut data.tracks);
+                let clips = match tracks.get_mut(track) {
+                    Some(track) => Arc::make_mut(&mut track.clips),
+                    None => return,
+                };
+                let clip = match clips.get_mut(clip) {
+                    Some(clip) => clip,
+                    None => return,
+                };
+                let range = &mut clip.range;
+                match mode {
+                    ClipDrag::Move(grab) => {
+                        let length = range.end - range.start;
+                        range.start = (time - grab).max(0.0);
+                        range.end = range.start + length;
+                    }
+                    ClipDrag::ResizeStart => {
+                        range.start = time.max(0.0).min(range.end - min_length);
+                    }
+                    ClipDrag::ResizeEnd => {
+                        range.end = time.max(range.start + min_length);
+                    }
+                }
+            }
+            None => (),
+        }
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline::new()
+    }
+}
+
+impl Widget<TimelineData> for Timeline {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut TimelineData, _env: &Env) {
+        match event {
+            Event::MouseDown(mouse) => {
+                self.drag = if mouse.pos.y < HEADER_HEIGHT {
+                    Some(Drag::Playhead)
+                } else {
+                    self.clip_at(mouse.pos, data)
+                        .map(|(track, clip, mode)| Drag::Clip { track, clip, mode })
+                };
+                if self.drag.is_some() {
+                    ctx.set_active(true);
+                    self.drag_to(mouse.pos, data);
+                    ctx.invalidate();
+                }
+            }
+            Event::MouseMoved(mouse) => {
+                self.last_mouse_pos = Some(mouse.pos);
+                if self.drag.is_some() {
+                    self.drag_to(mouse.pos, data);
+                    ctx.invalidate();
+                }
+                let mode = match &self.drag {
+                    Some(Drag::Clip { mode, .. }) => Some(*mode),
+                    Some(Drag::Playhead) => None,
+                    None => self.clip_at(mouse.pos, data).map(|(_, _, mode)| mode),
+                };
+                match mode {
+                    Some(ClipDrag::ResizeStart) | Some(ClipDrag::ResizeEnd) => {
+                        ctx.set_cursor(&Cursor::ResizeLeftRight)
+                    }
+                    Some(ClipDrag::Move(_)) => ctx.set_cursor(&Cursor::OpenHand),
+                    None => ctx.set_cursor(&Cursor::Arrow),
+                }
+            }
+            Event::MouseUp(_) => {
+                if self.drag.take().is_some() {
+                    ctx.set_active(false);
+                    ctx.invalidate();
+                }
+            }
+            Event::Wheel(wheel) if wheel.mods.ctrl => {
+                let x = self.last_mouse_pos.map_or(0.0, |pos| pos.x);
+                let time = data.time_at(x);
+                let factor = (-wheel.delta.y * WHEEL_ZOOM_RATE).exp();
+                data.scale = (data.scale * factor)
+                    .max(self.min_scale)
+                    .min(self.max_scale);
+                data.start = time - x / data.scale;
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            Event::Wheel(wheel) => {
+                let delta = ctx.wheel_remaining(wheel);
+                data.start = (data.start + delta.x / data.scale).max(0.0);
+                let before = self.track_offset;
+                self.scroll_tracks(delta.y, ctx.size().height, data);
+                ctx.consume_wheel(Vec2::new(delta.x, self.track_offset - before));
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            Event::Command(cmd) if cmd.selector == commands::SET_PLAYHEAD => {
+                if let Some(time) = cmd.get_object::<f64>() {
+                    data.playhead = *time;
+                    ctx.invalidate();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: Option<&TimelineData>,
+        _data: &TimelineData,
+        _env: &Env,
+    ) {
+        ctx.invalidate();
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &TimelineData,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("Timeline");
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            bc.min().width
+        };
+        let height = if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            HEADER_HEIGHT + data.tracks.len() as f64 * TRACK_HEIGHT
+        };
+        let size = bc.constrain(Size::new(width, height));
+        self.scroll_tracks(0.0, size.height, data);
+        size
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        base_state: &BaseState,
+        data: &TimelineData,
+        env: &Env,
+    ) {
+        let size = base_state.size();
+        let region = paint_ctx.region().to_rect();
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL) * 0.8;
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+            .build()
+            .unwrap();
+        let label_color = env.get(theme::LABEL_COLOR);
+        let border_color = env.get(theme::BORDER);
+        let clip_color = env.get(theme::PRIMARY_DARK);
+        let first_time = data.time_at(region.x0);
+        let last_time = data.time_at(region.x1);
+
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(Rect::from_origin_size(Point::ORIGIN, size));
+            ctx.fill(region, &env.get(theme::BACKGROUND_DARK));
+
+            // Only the tracks within the region are drawn.
+            let first_track = self.track_at(region.y0.max(HEADER_HEIGHT), data);
+            for index in first_track
+                .into_iter()
+                .flat_map(|first| first..data.tracks.len())
+            {
+                let y = self.track_y(index);
+                if y > region.y1 {
+                    break;
+                }
+                let track = &data.tracks[index];
+                let line = Line::new((0.0, y + TRACK_HEIGHT), (size.width, y + TRACK_HEIGHT));
+                ctx.stroke(line, &border_color, 1.0);
+
+                for clip in track.clips.iter() {
+                    if clip.range.end < first_time || clip.range.start > last_time {
+                        continue;
+                    }
+                    let rect = Rect::new(
+                        data.x_at(clip.range.start),
+                        y + 2.0,
+                        data.x_at(clip.range.end),
+                        y + TRACK_HEIGHT - 2.0,
+                    );
+                    let shape = RoundedRect::from_rect(rect, 3.0);
+                    ctx.fill(shape, &clip_color);
+                    ctx.stroke(shape, &border_color, 1.0);
+                    let layout = ctx
+                        .text()
+                        .new_text_layout(&font, &clip.label)
+                        .build()
+                        .unwrap();
+                    ctx.with_save(|ctx| {
+                        ctx.clip(rect);
+                        let pos = Point::new(rect.x0.max(0.0) + 4.0, rect.y0 + font_size + 2.0);
+                        ctx.draw_text(&layout, pos, &label_color);
+                    });
+                }
+
+                let layout = ctx
+                    .text()
+                    .new_text_layout(&font, &track.name)
+                    .build()
+                    .unwrap();
+                let pos = Point::new(4.0, y + TRACK_HEIGHT - 4.0);
+                ctx.draw_text(&layout, pos, &env.get(theme::PLACEHOLDER_COLOR));
+            }
+
+            // The header, with labelled ticks.
+            let header = Rect::new(0.0, 0.0, size.width, HEADER_HEIGHT);
+            ctx.fill(header, &env.get(theme::BACKGROUND_LIGHT));
+            let step = tick_step(data.scale);
+            let mut tick = (first_time / step).floor() * step;
+            while tick <= last_time {
+                let x = data.x_at(tick);
+                ctx.stroke(
+                    Line::new((x, HEADER_HEIGHT * 0.5), (x, HEADER_HEIGHT)),
+                    &border_color,
+                    1.0,
+                );
+                let text = format!("{}", (tick * 1000.0).round() / 1000.0);
+                let layout = ctx.text().new_text_layout(&font, &text).build().unwrap();
+                ctx.draw_text(&layout, (x + 2.0, font_size), &label_color);
+                tick += step;
+            }
+
+            let x = data.x_at(data.playhead);
+            let playhead_color = env.get(theme::PRIMARY_LIGHT);
+            ctx.stroke(Line::new((x, 0.0), (x, size.height)), &playhead_color, 2.0);
+        });
+    }
+}
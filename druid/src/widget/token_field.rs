@@ -0,0 +1,350 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A text field for a list of tokens, such as tags or email recipients.
+
+use std::sync::Arc;
+
+use crate::kurbo::{Line, Point, Rect, RoundedRect, Size};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayout, TextLayoutBuilder};
+use crate::theme;
+use crate::widget::{AutoComplete, TextBox};
+use crate::{
+    BaseState, BoxConstraints, BoxedWidget, Env, Event, EventCtx, KeyCode, LayoutCtx, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// The padding around the tokens and the input.
+const PADDING: f64 = 3.0;
+/// The space between tokens.
+const GAP: f64 = 4.0;
+/// The padding at either end of a token's label.
+const CHIP_PADDING: f64 = 6.0;
+/// The size of a token's remove button.
+const REMOVE_SIZE: f64 = 8.0;
+/// The narrowest the input is made before it wraps to a new line.
+const MIN_INPUT_WIDTH: f64 = 80.0;
+
+/// A text field whose entries are shown as removable chips.
+///
+/// Typing a separator (a comma, by default) or pressing enter commits the
+/// typed text as a token. Backspace in an empty input removes the last
+/// token, and each token can be removed with its button. Tokens wrap onto
+/// new lines when they don't fit.
+///
+/// The input can suggest completions, with [`with_suggestions`].
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::TokenField;
+///
+/// let recipients = TokenField::with_suggestions(|text| {
+///     ["ada@example.com", "grace@example.com"]
+///         .iter()
+///         .filter(|address| address.starts_with(text))
+///         .map(|address| address.to_string())
+///         .collect()
+/// })
+/// .separators(&[',', ';', ' ']);
+/// ```
+///
+/// [`with_suggestions`]: #method.with_suggestions
+pub struct TokenField {
+    input: BoxedWidget<String>,
+    text: String,
+    separators: Vec<char>,
+    /// The rectangles of the tokens, from the last layout.
+    chips: Vec<Rect>,
+}
+
+impl TokenField {
+    /// Create a new `TokenField`.
+    pub fn new() -> Self {
+        Self::with_input(TextBox::raw())
+    }
+
+    /// Create a new `TokenField` that suggests completions of the typed
+    /// text, as an [`AutoComplete`] does.
+    ///
+    /// [`AutoComplete`]: struct.AutoComplete.html
+    pub fn with_suggestions(provider: impl Fn(&str) -> Vec<String> + 'static) -> Self {
+        Self::with_input(AutoComplete::new(provider))
+    }
+
+    fn with_input(input: impl Widget<String> + 'static) -> Self {
+        TokenField {
+            input: WidgetPod::new(input).boxed(),
+            text: String::new(),
+            separators: vec![','],
+            chips: Vec::new(),
+        }
+    }
+
+    /// Builder-style method to set the characters that commit a token.
+    pub fn separators(mut self, separators: &[char]) -> Self {
+        self.separators = separators.to_vec();
+        self
+    }
+
+    /// Commit the typed text as tokens, split at separators, keeping any
+    /// text after the last separator in the input.
+    fn commit(&mut self, data: &mut Arc<Vec<String>>, all: bool) {
+        let (tokens, rest) = split_tokens(&self.text, &self.separators, all);
+        if !tokens.is_empty() {
+            Arc::make_mut(data).extend(tokens);
+        }
+        self.text = rest;
+    }
+
+    /// The area of a token's remove button.
+    fn remove_rect(chip: Rect, env: &Env) -> Rect {
+        let x1 = chip.x1 - theme::spacing(env, CHIP_PADDING);
+        let y0 = chip.y0 + (chip.height() - REMOVE_SIZE) / 2.0;
+        Rect::new(x1 - REMOVE_SIZE, y0, x1, y0 + REMOVE_SIZE)
+    }
+}
+
+impl Default for TokenField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split text into trimmed, non-empty tokens at any of the separators.
+///
+/// Unless `all` is set, the text after the last separator is returned
+/// instead of being made a token, as it may still be being typed.
+fn split_tokens(text: &str, separators: &[char], all: bool) -> (Vec<String>, String) {
+    let (complete, rest) = match text.rfind(|c| separators.contains(&c)) {
+        _ if all => (text, ""),
+        Some(end) => {
+            let separator_len = text[end..].chars().next().map_or(0, char::len_utf8);
+            (&text[..end], &text[end + separator_len..])
+        }
+        None => ("", text),
+    };
+    let tokens = complete
+        .split(|c| separators.contains(&c))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+        .collect();
+    (tokens, rest.to_string())
+}
+
+impl Widget<Arc<Vec<String>>> for TokenField {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Arc<Vec<String>>, env: &Env) {
+        match event {
+            Event::MouseDown(mouse) => {
+                let removed = self.chips.iter().position(|chip| {
+                    let remove = Self::remove_rect(*chip, env);
+                    let slop = Rect::new(
+                        remove.x0 - 2.0,
+                        remove.y0 - 2.0,
+                        remove.x1 + 2.0,
+                        remove.y1 + 2.0,
+                    );
+                    slop.contains(mouse.pos)
+                });
+                if let Some(index) = removed {
+                    if index < data.len() {
+                        Arc::make_mut(data).remove(index);
+                    }
+                    ctx.invalidate();
+                    ctx.set_handled();
+                    return;
+                }
+            }
+            Event::KeyDown(key) if key.key_code == KeyCode::Backspace && self.text.is_empty() => {
+                if !data.is_empty() {
+                    Arc::make_mut(data).pop();
+                    ctx.invalidate();
+                }
+                ctx.set_handled();
+                return;
+            }
+            _ => (),
+        }
+
+        let old_text = self.text.clone();
+        self.input.event(ctx, event, &mut self.text, env);
+
+        let enter = match event {
+            Event::KeyDown(key) => {
+                key.key_code == KeyCode::Return || key.key_code == KeyCode::NumpadEnter
+            }
+            _ => false,
+        };
+        if enter && !ctx.is_handled() {
+            self.commit(data, true);
+            ctx.set_handled();
+            ctx.invalidate();
+        } else if self.text != old_text {
+            self.commit(data, false);
+            ctx.invalidate();
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: Option<&Arc<Vec<String>>>,
+        data: &Arc<Vec<String>>,
+        env: &Env,
+    ) {
+        self.input.update(ctx, &self.text, env);
+        if old_data.map_or(true, |old| !Arc::ptr_eq(old, data)) {
+            ctx.invalidate();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Arc<Vec<String>>,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("TokenField");
+
+        let row_height = env.get(theme::BORDERED_WIDGET_HEIGHT);
+        let padding = theme::spacing(env, PADDING);
+        let gap = theme::spacing(env, GAP);
+        let chip_padding = theme::spacing(env, CHIP_PADDING);
+        let font = layout_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), env.get(theme::TEXT_SIZE_NORMAL))
+            .build()
+            .unwrap();
+        let widths: Vec<f64> = data
+            .iter()
+            .map(|token| {
+                let layout = layout_ctx
+                    .text()
+                    .new_text_layout(&font, token)
+                    .build()
+                    .unwrap();
+                layout.width() + 3.0 * chip_padding + REMOVE_SIZE
+            })
+            .collect();
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            let content: f64 = widths.iter().map(|width| width + gap).sum();
+            (content + MIN_INPUT_WIDTH + 2.0 * padding).max(bc.min().width)
+        };
+        let right = width - padding;
+
+        self.chips.clear();
+        let mut x = padding;
+        let mut y = padding;
+        for chip_width in widths {
+            if x > padding && x + chip_width > right {
+                x = padding;
+                y += row_height + gap;
+            }
+            let chip = Rect::from_origin_size((x, y), Size::new(chip_width, row_height));
+            self.chips.push(chip);
+            x += chip_width + gap;
+        }
+
+        if x > padding && right - x < MIN_INPUT_WIDTH {
+            x = padding;
+            y += row_height + gap;
+        }
+        let input_width = (right - x).max(0.0);
+        let input_bc = BoxConstraints::new(
+            Size::new(input_width, 0.0),
+            Size::new(input_width, row_height),
+        );
+        let input_size = self.input.layout(layout_ctx, &input_bc, &self.text, env);
+        self.input
+            .set_layout_rect(Rect::from_origin_size((x, y), input_size));
+
+        bc.constrain(Size::new(width, y + row_height + padding))
+    }
+
+    fn paint(
+        &mut self,
+        paint_ctx: &mut PaintCtx,
+        base_state: &BaseState,
+        data: &Arc<Vec<String>>,
+        env: &Env,
+    ) {
+        let size = base_state.size();
+        let rect = Rect::from_origin_size(Point::ORIGIN, size);
+        paint_ctx.fill(rect, &env.get(theme::BACKGROUND_LIGHT));
+        let border = Rect::new(0.5, 0.5, size.width - 0.5, size.height - 0.5);
+        paint_ctx.stroke(border, &env.get(theme::BORDER), 1.0);
+
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+            .build()
+            .unwrap();
+        let chip_color = env.get(theme::BUTTON_LIGHT);
+        let border_color = env.get(theme::BORDER_LIGHT);
+        let label_color = env.get(theme::LABEL_COLOR);
+        for (token, chip) in data.iter().zip(&self.chips) {
+            let shape = RoundedRect::from_rect(*chip, chip.height() / 2.0);
+            paint_ctx.fill(shape, &chip_color);
+            paint_ctx.stroke(shape, &border_color, 1.0);
+
+            let layout = paint_ctx
+                .text()
+                .new_text_layout(&font, token)
+                .build()
+                .unwrap();
+            let baseline = chip.y0 + (chip.height() + font_size) / 2.0 - 2.0;
+            let x = chip.x0 + theme::spacing(env, CHIP_PADDING);
+            paint_ctx.draw_text(&layout, (x, baseline), &label_color);
+
+            let cross = Self::remove_rect(*chip, env);
+            paint_ctx.stroke(
+                Line::new((cross.x0, cross.y0), (cross.x1, cross.y1)),
+                &label_color,
+                1.5,
+            );
+            paint_ctx.stroke(
+                Line::new((cross.x0, cross.y1), (cross.x1, cross.y0)),
+                &label_color,
+                1.5,
+            );
+        }
+
+        self.input.paint_with_offset(paint_ctx, &self.text, env);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_at_separators() {
+        let (tokens, rest) = split_tokens("a, b,,c;d", &[',', ';'], false);
+        assert_eq!(tokens, vec!["a", "b", "c"]);
+        assert_eq!(rest, "d");
+
+        let (tokens, rest) = split_tokens(" a , b ", &[','], true);
+        assert_eq!(tokens, vec!["a", "b"]);
+        assert_eq!(rest, "");
+
+        let (tokens, rest) = split_tokens("partial", &[','], false);
+        assert!(tokens.is_empty());
+        assert_eq!(rest, "partial");
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Animated transitions between views.
+
+use std::time::Duration;
+
+use crate::kurbo::{Affine, Point, Rect, Size, Vec2};
+use crate::piet::RenderContext;
+use crate::{theme, Animation, Easing, Env, PaintCtx};
+
+/// How the old view makes way for the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// The old view fades out, and then the new view fades in.
+    ///
+    /// Views can't be painted translucent, so they fade to and from the
+    /// window background, [`theme::WINDOW_BACKGROUND_COLOR`].
+    ///
+    /// [`theme::WINDOW_BACKGROUND_COLOR`]: ../theme/constant.WINDOW_BACKGROUND_COLOR.html
+    Fade,
+    /// Both views move left, the new view coming in from the right.
+    SlideLeft,
+    /// Both views move right, the new view coming in from the left.
+    SlideRight,
+    /// Both views move up, the new view coming in from the bottom.
+    SlideUp,
+    /// Both views move down, the new view coming in from the top.
+    SlideDown,
+}
+
+/// An animated transition, for widgets that switch between views, such as
+/// [`Either`] and [`ViewSwitcher`].
+///
+/// While the transition runs, the old view keeps being painted, but only
+/// the new one receives events.
+///
+/// A [`TransitionKind::Fade`] doesn't cross-fade the views: it fades the
+/// old view to the window background color and the new view in from it,
+/// so inside a container of another color the views flash through the
+/// window background. The slides suit such places better.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use druid::widget::{Either, Label, Transition, TransitionKind};
+///
+/// let status = Either::new(
+///     |online: &bool, _env| *online,
+///     Label::new("Online"),
+///     Label::new("Offline"),
+/// )
+/// .transition(Transition::new(TransitionKind::Fade, Duration::from_millis(250)));
+/// ```
+///
+/// [`Either`]: struct.Either.html
+/// [`ViewSwitcher`]: struct.ViewSwitcher.html
+/// [`TransitionKind::Fade`]: enum.TransitionKind.html#variant.Fade
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    kind: TransitionKind,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Transition {
+    /// Create a transition of the given kind, that takes `duration`.
+    pub fn new(kind: TransitionKind, duration: Duration) -> Transition {
+        Transition {
+            kind,
+            duration,
+            easing: Easing::default(),
+        }
+    }
+
+    /// Builder-style method to set the pace of the transition. The default
+    /// is [`Easing::EaseInOut`].
+    ///
+    /// [`Easing::EaseInOut`]: ../enum.Easing.html#variant.EaseInOut
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// The animation that runs the transition, from 0.0 to 1.0.
+    pub(crate) fn animation(&self) -> Animation<f64> {
+        Animation::new(0.0, 1.0, self.duration).easing(self.easing)
+    }
+
+    /// Paint the views being switched, in an area of `size`, when the
+    /// transition's [`animation`] is at `t`.
+    ///
+    /// [`animation`]: #method.animation
+    pub(crate) fn paint(
+        &self,
+        paint_ctx: &mut PaintCtx,
+        size: Size,
+        t: f64,
+        env: &Env,
+        old: impl FnOnce(&mut PaintCtx),
+        new: impl FnOnce(&mut PaintCtx),
+    ) {
+        let bounds = Rect::from_origin_size(Point::ORIGIN, size);
+        let offset = match self.kind {
+            TransitionKind::Fade => {
+                let cover = if t < 0.5 {
+                    old(paint_ctx);
+                    t * 2.0
+                } else {
+                    new(paint_ctx);
+                    (1.0 - t) * 2.0
+                };
+                let color = env.get(theme::WINDOW_BACKGROUND_COLOR).with_alpha(cover);
+                paint_ctx.fill(bounds, &color);
+                return;
+            }
+            TransitionKind::SlideLeft => Vec2::new(-size.width, 0.0),
+            TransitionKind::SlideRight => Vec2::new(size.width, 0.0),
+            TransitionKind::SlideUp => Vec2::new(0.0, -size.height),
+            TransitionKind::SlideDown => Vec2::new(0.0, size.height),
+        };
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(bounds);
+            ctx.transform_save(Affine::translate(offset * t), |ctx| {
+                ctx.with_child_ctx(bounds, old)
+            });
+            ctx.transform_save(Affine::translate(offset * (t - 1.0)), |ctx| {
+                ctx.with_child_ctx(bounds, new)
+            });
+        });
+    }
+}
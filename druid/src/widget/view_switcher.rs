@@ -0,0 +1,187 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that builds a new child view whenever a value derived from the
+//! data changes.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::widget::Transition;
+use crate::{
+    Animation, BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    PaintCtx, UpdateCtx, Widget, WidgetPod,
+};
+
+/// A widget that switches between any number of child views.
+///
+/// Where [`Either`] keeps two branches, a `ViewSwitcher` picks a value of
+/// type `U` from the data, and builds a new child for it whenever the
+/// value changes. The old child is dropped, after being sent
+/// [`LifeCycle::WidgetRemoved`] before the next event, so nothing of its
+/// state is kept. With a [`Transition`], the old child is painted until the
+/// transition is over, and removed then.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Button, Label, ViewSwitcher};
+///
+/// let tabs = ViewSwitcher::new(
+///     |tab: &u32, _env| *tab,
+///     |tab, _data, _env| match tab {
+///         0 => Box::new(Label::new("Welcome")),
+///         1 => Box::new(Button::new("Back", |_ctx, tab: &mut u32, _env| *tab = 0)),
+///         _ => Box::new(Label::new("About")),
+///     },
+/// );
+/// ```
+///
+/// [`Either`]: struct.Either.html
+/// [`LifeCycle::WidgetRemoved`]: ../enum.LifeCycle.html#variant.WidgetRemoved
+/// [`Transition`]: struct.Transition.html
+pub struct ViewSwitcher<T: Data, U: PartialEq> {
+    child_picker: Box<dyn Fn(&T, &Env) -> U>,
+    child_builder: Box<dyn Fn(&U, &T, &Env) -> Box<dyn Widget<T>>>,
+    active_child: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    active_child_id: Option<U>,
+    /// Children that were replaced, to be removed on the next event.
+    ///
+    /// The child is switched in `update`, which can't send events.
+    retired: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+    transition: Option<Transition>,
+    /// The child being replaced, while the transition runs.
+    outgoing_child: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    /// The transition, while it runs.
+    animation: Option<Animation<f64>>,
+}
+
+impl<T: Data, U: PartialEq> ViewSwitcher<T, U> {
+    /// Create a widget that shows the child built by `child_builder` for
+    /// the value `child_picker` returns.
+    ///
+    /// The picker is called whenever the data changes, and the builder
+    /// whenever the value it picks changes.
+    pub fn new(
+        child_picker: impl Fn(&T, &Env) -> U + 'static,
+        child_builder: impl Fn(&U, &T, &Env) -> Box<dyn Widget<T>> + 'static,
+    ) -> ViewSwitcher<T, U> {
+        ViewSwitcher {
+            child_picker: Box::new(child_picker),
+            child_builder: Box::new(child_builder),
+            active_child: None,
+            active_child_id: None,
+            retired: Vec::new(),
+            transition: None,
+            outgoing_child: None,
+            animation: None,
+        }
+    }
+
+    /// Builder-style method to animate switching between children.
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.transition = Some(transition);
+        self
+    }
+}
+
+impl<T: Data, U: PartialEq> Widget<T> for ViewSwitcher<T, U> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            if let Some(animation) = &mut self.animation {
+                if animation.advance(*interval) {
+                    self.animation = None;
+                    self.retired.extend(self.outgoing_child.take());
+                } else {
+                    ctx.request_anim_frame();
+                }
+                ctx.invalidate();
+            }
+        }
+
+        let removed = Event::LifeCycle(LifeCycle::WidgetRemoved);
+        for mut child in self.retired.drain(..) {
+            child.event(ctx, &removed, data, env);
+        }
+        if let Some(child) = &mut self.active_child {
+            child.event(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: Option<&T>, data: &T, env: &Env) {
+        let child_id = (self.child_picker)(data, env);
+        if self.active_child_id.as_ref() != Some(&child_id) {
+            let child = WidgetPod::new((self.child_builder)(&child_id, data, env));
+            if let Some(old) = self.active_child.replace(child) {
+                if let Some(transition) = &self.transition {
+                    // A transition that was running is cut short.
+                    self.retired.extend(self.outgoing_child.replace(old));
+                    self.animation = Some(transition.animation());
+                    ctx.request_anim_frame();
+                } else {
+                    self.retired.push(old);
+                }
+            }
+            self.active_child_id = Some(child_id);
+            ctx.invalidate();
+        }
+        if let Some(child) = &mut self.active_child {
+            child.update(ctx, data, env);
+        }
+        if let Some(child) = &mut self.outgoing_child {
+            child.update(ctx, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("ViewSwitcher");
+
+        if let Some(child) = &mut self.outgoing_child {
+            let size = child.layout(layout_ctx, bc, data, env);
+            child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        }
+        match &mut self.active_child {
+            Some(child) => {
+                let size = child.layout(layout_ctx, bc, data, env);
+                child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+                size
+            }
+            None => bc.min(),
+        }
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        match (
+            &self.transition,
+            &self.animation,
+            &mut self.outgoing_child,
+            &mut self.active_child,
+        ) {
+            (Some(transition), Some(animation), Some(old), Some(new)) => transition.paint(
+                paint_ctx,
+                base_state.size(),
+                animation.value(),
+                env,
+                |ctx| old.paint(ctx, data, env),
+                |ctx| new.paint(ctx, data, env),
+            ),
+            (_, _, _, Some(child)) => child.paint(paint_ctx, data, env),
+            _ => (),
+        }
+    }
+}
@@ -15,10 +15,13 @@
 //! Convenience methods for widgets.
 
 use crate::kurbo::Insets;
-use crate::piet::{PaintBrush, UnitPoint};
+use crate::piet::PaintBrush;
 
-use super::{Align, Container, EnvScope, Padding, Parse, SizedBox};
-use crate::{Data, Env, Lens, LensWrap, Widget};
+use super::{
+    Align, AutomationId, BackgroundBrush, Click, Container, Controller, ControllerHost, EnvScope,
+    Padding, Parse, Persist, ReadOnly, Retained, SizedBox,
+};
+use crate::{Alignment, Data, Env, EventCtx, Lens, LensWrap, LogicalInsets, PersistView, Widget};
 
 /// A trait that provides extra methods for combining `Widget`s.
 pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
@@ -30,6 +33,15 @@ pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
         Padding::new(insets, self)
     }
 
+    /// Wrap this widget in a [`Padding`] widget with the given
+    /// [`LogicalInsets`], whose start and end follow the layout direction.
+    ///
+    /// [`Padding`]: struct.Padding.html
+    /// [`LogicalInsets`]: ../struct.LogicalInsets.html
+    fn logical_padding(self, insets: impl Into<LogicalInsets>) -> Padding<T> {
+        Padding::logical(insets, self)
+    }
+
     /// Wrap this widget in an [`Align`] widget, configured to center it.
     ///
     /// [`Align`]: struct.Align.html
@@ -51,17 +63,33 @@ pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
         Align::right(self)
     }
 
+    /// Wrap this widget in an [`Align`] widget, configured to align to the
+    /// start of the layout direction.
+    ///
+    /// [`Align`]: struct.Align.html
+    fn align_start(self) -> Align<T> {
+        Align::start(self)
+    }
+
+    /// Wrap this widget in an [`Align`] widget, configured to align to the
+    /// end of the layout direction.
+    ///
+    /// [`Align`]: struct.Align.html
+    fn align_end(self) -> Align<T> {
+        Align::end(self)
+    }
+
     /// Wrap this widget in an [`Align`] widget, configured to align vertically.
     ///
     /// [`Align`]: struct.Align.html
-    fn align_vertical(self, align: UnitPoint) -> Align<T> {
+    fn align_vertical(self, align: impl Into<Alignment>) -> Align<T> {
         Align::vertical(align, self)
     }
 
     /// Wrap this widget in an [`Align`] widget, configured to align horizontally.
     ///
     /// [`Align`]: struct.Align.html
-    fn align_horizontal(self, align: UnitPoint) -> Align<T> {
+    fn align_horizontal(self, align: impl Into<Alignment>) -> Align<T> {
         Align::horizontal(align, self)
     }
 
@@ -86,14 +114,16 @@ pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
         SizedBox::new(self).expand()
     }
 
-    /// Wrap this widget in a [`Container`] using the provided [`PaintBrush`]
-    /// as the background.
+    /// Wrap this widget in a [`Container`] using the provided
+    /// [`BackgroundBrush`] as the background.
     ///
-    /// The `PaintBrush` argument can be any color or gradient.
+    /// The brush can be any color or gradient, a color key from the
+    /// environment, or a [`Painter`].
     ///
     /// [`Container`]: struct.Container.html
-    /// [`PaintBrush`]: https://docs.rs/piet/0.0.7/piet/enum.PaintBrush.html
-    fn background(self, brush: impl Into<PaintBrush>) -> Container<T> {
+    /// [`BackgroundBrush`]: enum.BackgroundBrush.html
+    /// [`Painter`]: struct.Painter.html
+    fn background(self, brush: impl Into<BackgroundBrush<T>>) -> Container<T> {
         Container::new(self).background(brush)
     }
 
@@ -107,6 +137,14 @@ pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
         Container::new(self).border(brush, width)
     }
 
+    /// Wrap this widget in a [`Container`] with rounded corners, for its
+    /// background and border.
+    ///
+    /// [`Container`]: struct.Container.html
+    fn rounded(self, radius: f64) -> Container<T> {
+        Container::new(self).rounded(radius)
+    }
+
     /// Wrap this widget in a [`EnvScope`] widget, modifying the parent
     /// [`Env`] with the provided closure.
     ///
@@ -116,6 +154,72 @@ pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
         EnvScope::new(f, self)
     }
 
+    /// Wrap this widget in a [`ControllerHost`], adding the behavior of
+    /// `controller`.
+    ///
+    /// [`ControllerHost`]: struct.ControllerHost.html
+    fn controller<C: Controller<T, Self>>(self, controller: C) -> ControllerHost<Self, C> {
+        ControllerHost::new(self, controller)
+    }
+
+    /// Call `action` when this widget is clicked, with a [`Click`]
+    /// controller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::{Label, WidgetExt};
+    /// use druid::Env;
+    ///
+    /// let counter = Label::new(|count: &u32, _env: &Env| format!("Clicked {} times", count))
+    ///     .on_click(|_ctx, count, _env| *count += 1);
+    /// ```
+    ///
+    /// [`Click`]: struct.Click.html
+    fn on_click(
+        self,
+        action: impl Fn(&mut EventCtx, &mut T, &Env) + 'static,
+    ) -> ControllerHost<Self, Click<T>> {
+        self.controller(Click::new(action))
+    }
+
+    /// Wrap this widget in a [`ReadOnly`] widget, which makes it read-only
+    /// whenever the provided closure returns `true`.
+    ///
+    /// [`ReadOnly`]: struct.ReadOnly.html
+    fn read_only_if(self, f: impl Fn(&T, &Env) -> bool + 'static) -> ReadOnly<T, Self> {
+        ReadOnly::new(f, self)
+    }
+
+    /// Wrap this widget in a [`Retained`] widget, which keeps what it paints
+    /// and draws that again until something in it changes.
+    ///
+    /// [`Retained`]: struct.Retained.html
+    fn retained(self) -> Retained<T, Self> {
+        Retained::new(self)
+    }
+
+    /// Wrap this widget in an [`AutomationId`] widget, which tags it with
+    /// an id that UI testing tools can find it by.
+    ///
+    /// [`AutomationId`]: struct.AutomationId.html
+    fn automation_id(self, id: impl Into<String>) -> AutomationId<T, Self> {
+        AutomationId::new(id, self)
+    }
+
+    /// Wrap this widget in a [`Persist`] widget, which saves its view
+    /// state, such as a scroll offset, in the application's [`Store`]
+    /// under `key`, and restores it when the application is next run.
+    ///
+    /// [`Persist`]: struct.Persist.html
+    /// [`Store`]: ../struct.Store.html
+    fn persist_view(self, key: impl Into<String>) -> Persist<T, Self>
+    where
+        Self: PersistView,
+    {
+        Persist::new(key, self)
+    }
+
     /// Wrap this widget in a [`LensWrap`] widget for the provided [`Lens`].
     ///
     /// [`LensWrap`]: ../struct.LensWrap.html
@@ -139,7 +243,7 @@ impl<T: Data + 'static, W: Widget<T> + 'static> WidgetExt<T> for W {}
 // will choose an impl on a type over an impl in a trait for methods with the same
 // name.
 impl<T: Data + 'static> Container<T> {
-    pub fn with_background(self, brush: impl Into<PaintBrush>) -> Container<T> {
+    pub fn with_background(self, brush: impl Into<BackgroundBrush<T>>) -> Container<T> {
         self.background(brush)
     }
 
@@ -0,0 +1,42 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The order in which a container's children are painted and hit tested.
+
+/// The indices of a container's children, sorted by their z-index.
+///
+/// The indices are kept between uses, so that ordering the children for
+/// every paint and mouse event doesn't allocate.
+#[derive(Debug, Default)]
+pub(crate) struct ZOrder {
+    indices: Vec<usize>,
+}
+
+impl ZOrder {
+    /// Sort the indices of `len` children by the z-index `z_index` gives
+    /// for each, lowest first. Children with the same z-index keep their
+    /// order.
+    pub fn sort(&mut self, len: usize, z_index: impl Fn(usize) -> i32) {
+        self.indices.clear();
+        self.indices.extend(0..len);
+        // Unlike a stable sort, this doesn't allocate.
+        self.indices
+            .sort_unstable_by_key(|&idx| (z_index(idx), idx));
+    }
+
+    /// The indices, in paint order, as of the last sort.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+}
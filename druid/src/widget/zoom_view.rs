@@ -0,0 +1,304 @@
+// Copyright 2019 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A container that zooms and pans its contents.
+
+use std::f64::INFINITY;
+
+use crate::commands;
+use crate::kurbo::{Affine, Point, Rect, Size, Vec2};
+use crate::piet::RenderContext;
+use crate::{
+    BaseState, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, Lens, PaintCtx, UpdateCtx,
+    Widget, WidgetPod,
+};
+
+/// How much a wheel delta of one point zooms with Ctrl held.
+const WHEEL_ZOOM_RATE: f64 = 0.002;
+
+/// The zoom level and pan offset of a [`ZoomView`].
+///
+/// Keeping this in the application data lets other widgets, such as a
+/// [`Ruler`], follow the view.
+///
+/// [`ZoomView`]: struct.ZoomView.html
+/// [`Ruler`]: struct.Ruler.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoomState {
+    /// The number of display points per unit of content.
+    pub zoom: f64,
+    /// The point of the content at the top left of the view.
+    pub offset: Vec2,
+}
+
+/// A container that zooms and pans its child.
+///
+/// The zoom level and offset are stored in the data, as a [`ZoomState`]
+/// reached through a lens. The child is laid out with unbounded
+/// constraints, and takes the size of its content.
+///
+/// The wheel pans the view; with Ctrl held, it zooms around the pointer,
+/// as does a pinch on a trackpad. The view also handles the
+/// [`ZOOM_TO_FIT`] and [`ZOOM_ACTUAL_SIZE`] commands.
+///
+/// # Examples
+///
+/// ```
+/// use druid::lens;
+/// use druid::widget::{Label, ZoomState, ZoomView};
+///
+/// let view = ZoomView::new(
+///     Label::new("A very large diagram"),
+///     lens!((String, ZoomState), 1),
+/// );
+/// ```
+///
+/// [`ZoomState`]: struct.ZoomState.html
+/// [`ZOOM_TO_FIT`]: ../commands/constant.ZOOM_TO_FIT.html
+/// [`ZOOM_ACTUAL_SIZE`]: ../commands/constant.ZOOM_ACTUAL_SIZE.html
+pub struct ZoomView<T: Data, W: Widget<T>, L> {
+    child: WidgetPod<T, W>,
+    lens: L,
+    child_size: Size,
+    min_zoom: f64,
+    max_zoom: f64,
+    /// The last pointer position, which zooming is centered on.
+    last_mouse_pos: Option<Point>,
+}
+
+impl ZoomState {
+    /// A `ZoomState` showing the content at its actual size.
+    pub fn new() -> ZoomState {
+        ZoomState::default()
+    }
+
+    /// The transform from content coordinates to view coordinates.
+    pub fn transform(&self) -> Affine {
+        Affine::scale(self.zoom) * Affine::translate(-self.offset)
+    }
+
+    /// Convert a point in the view to a point in the content.
+    pub fn to_content(&self, point: Point) -> Point {
+        (point.to_vec2() / self.zoom + self.offset).to_point()
+    }
+
+    /// Convert a point in the content to a point in the view.
+    pub fn to_view(&self, point: Point) -> Point {
+        ((point - self.offset).to_vec2() * self.zoom).to_point()
+    }
+
+    /// The region of the content that is visible in a view of this size.
+    pub fn visible_rect(&self, view_size: Size) -> Rect {
+        let size = Size::new(view_size.width / self.zoom, view_size.height / self.zoom);
+        Rect::from_origin_size(self.offset.to_point(), size)
+    }
+
+    /// Change the zoom level, keeping the content under `center` (a point
+    /// in the view) in place.
+    pub fn zoom_around(&mut self, zoom: f64, center: Point) {
+        let fixed = self.to_content(center);
+        self.zoom = zoom;
+        self.offset = fixed.to_vec2() - center.to_vec2() / zoom;
+    }
+
+    /// Zoom and center the view so that `content` fits within it.
+    pub fn fit(&mut self, content: Rect, view_size: Size) {
+        if content.width() <= 0.0 || content.height() <= 0.0 {
+            return;
+        }
+        self.zoom = (view_size.width / content.width()).min(view_size.height / content.height());
+        let half_view = Vec2::new(view_size.width, view_size.height) / (2.0 * self.zoom);
+        self.offset = content.center().to_vec2() - half_view;
+    }
+}
+
+impl Default for ZoomState {
+    fn default() -> Self {
+        ZoomState {
+            zoom: 1.0,
+            offset: Vec2::new(0.0, 0.0),
+        }
+    }
+}
+
+impl Data for ZoomState {
+    fn same(&self, other: &Self) -> bool {
+        self.zoom.same(&other.zoom) && self.offset.same(&other.offset)
+    }
+}
+
+impl<T: Data, W: Widget<T>, L: Lens<T, ZoomState>> ZoomView<T, W, L> {
+    /// Create a new `ZoomView`, with a lens to its [`ZoomState`].
+    ///
+    /// [`ZoomState`]: struct.ZoomState.html
+    pub fn new(child: W, lens: L) -> Self {
+        ZoomView {
+            child: WidgetPod::new(child),
+            lens,
+            child_size: Size::ZERO,
+            min_zoom: 0.1,
+            max_zoom: 10.0,
+            last_mouse_pos: None,
+        }
+    }
+
+    /// Builder-style method to set the range of zoom levels the user can
+    /// reach. The default is from 0.1 to 10.
+    pub fn zoom_range(mut self, min: f64, max: f64) -> Self {
+        self.min_zoom = min;
+        self.max_zoom = max;
+        self
+    }
+
+    /// Zoom by `factor` around a point in the view.
+    fn zoom_by(&self, ctx: &mut EventCtx, data: &mut T, factor: f64, center: Point) {
+        let (min, max) = (self.min_zoom, self.max_zoom);
+        self.lens.with_mut(data, |state| {
+            let zoom = (state.zoom * factor).max(min).min(max);
+            state.zoom_around(zoom, center);
+        });
+        ctx.invalidate();
+    }
+
+    /// The point zooming is centered on: the pointer, or the middle of the
+    /// view.
+    fn zoom_center(&self, size: Size) -> Point {
+        self.last_mouse_pos
+            .unwrap_or_else(|| Rect::from_origin_size(Point::ORIGIN, size).center())
+    }
+}
+
+impl<T: Data, W: Widget<T>, L: Lens<T, ZoomState>> Widget<T> for ZoomView<T, W, L> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let size = ctx.size();
+        let state = self.lens.with(data, |state| *state);
+        let child_event = match event {
+            Event::MouseDown(mouse) => {
+                let mut mouse = mouse.clone();
+                mouse.pos = state.to_content(mouse.pos);
+                Some(Event::MouseDown(mouse))
+            }
+            Event::MouseUp(mouse) => {
+                let mut mouse = mouse.clone();
+                mouse.pos = state.to_content(mouse.pos);
+                Some(Event::MouseUp(mouse))
+            }
+            Event::MouseMoved(mouse) => {
+                self.last_mouse_pos = Some(mouse.pos);
+                let mut mouse = mouse.clone();
+                mouse.pos = state.to_content(mouse.pos);
+                Some(Event::MouseMoved(mouse))
+            }
+            Event::Wheel(wheel) if wheel.mods.ctrl => None,
+            Event::Zoom(_) => None,
+            other => Some(other.clone()),
+        };
+        if let Some(child_event) = child_event {
+            self.child.event(ctx, &child_event, data, env);
+        }
+        if ctx.is_handled() {
+            return;
+        }
+
+        match event {
+            Event::Wheel(wheel) if wheel.mods.ctrl => {
+                let factor = (-wheel.delta.y * WHEEL_ZOOM_RATE).exp();
+                self.zoom_by(ctx, data, factor, self.zoom_center(size));
+                ctx.set_handled();
+            }
+            Event::Wheel(wheel) => {
+                let delta = ctx.wheel_remaining(wheel);
+                self.lens
+                    .with_mut(data, |state| state.offset += delta / state.zoom);
+                ctx.consume_wheel(delta);
+                ctx.invalidate();
+                ctx.set_handled();
+            }
+            Event::Zoom(delta) => {
+                self.zoom_by(ctx, data, 1.0 + delta, self.zoom_center(size));
+                ctx.set_handled();
+            }
+            Event::Command(cmd) if cmd.selector == commands::ZOOM_TO_FIT => {
+                let content = Rect::from_origin_size(Point::ORIGIN, self.child_size);
+                let (min, max) = (self.min_zoom, self.max_zoom);
+                self.lens.with_mut(data, |state| {
+                    state.fit(content, size);
+                    let zoom = state.zoom.max(min).min(max);
+                    let center = Rect::from_origin_size(Point::ORIGIN, size).center();
+                    state.zoom_around(zoom, center);
+                });
+                ctx.invalidate();
+            }
+            Event::Command(cmd) if cmd.selector == commands::ZOOM_ACTUAL_SIZE => {
+                self.lens.with_mut(data, |state| *state = ZoomState::new());
+                ctx.invalidate();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: Option<&T>, data: &T, env: &Env) {
+        let changed = match old_data {
+            Some(old) => !self
+                .lens
+                .with(old, |old| self.lens.with(data, |new| old.same(new))),
+            None => true,
+        };
+        if changed {
+            ctx.invalidate();
+        }
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("ZoomView");
+
+        let child_bc = BoxConstraints::new(Size::ZERO, Size::new(INFINITY, INFINITY));
+        self.child_size = self.child.layout(layout_ctx, &child_bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, self.child_size));
+
+        // Fill the space available, or fit the content when unbounded.
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            self.child_size.width
+        };
+        let height = if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            self.child_size.height
+        };
+        bc.constrain(Size::new(width, height))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, base_state: &BaseState, data: &T, env: &Env) {
+        let state = self.lens.with(data, |state| *state);
+        let viewport = Rect::from_origin_size(Point::ORIGIN, base_state.size());
+        let child = &mut self.child;
+        paint_ctx.with_save(|ctx| {
+            ctx.clip(viewport);
+            ctx.transform_save(state.transform(), |ctx| {
+                let visible = state.visible_rect(viewport.size());
+                ctx.with_child_ctx(visible, |ctx| child.paint(ctx, data, env));
+            });
+        });
+    }
+}
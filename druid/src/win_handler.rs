@@ -15,26 +15,38 @@
 //! The implementation of the WinHandler trait (druid-shell integration).
 
 use std::any::Any;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::time::Instant;
 
 use log::{error, info, warn};
 
-use crate::kurbo::{Rect, Size, Vec2};
-use crate::piet::{Piet, RenderContext};
+use crate::kurbo::{Affine, Point, Rect, Size, Vec2};
+use crate::piet::{Device, ImageFormat, Piet, RenderContext};
 use crate::shell::{
-    Application, Cursor, FileDialogOptions, MouseEvent, WinCtx, WinHandler, WindowHandle,
+    Application, ColorScheme, Cursor, FileDialogOptions, FrameTiming, HighContrast, HotKey,
+    MouseEvent, Screen, WinCtx, WinHandler, WindowHandle,
 };
 
+use crate::app::EnvSetupFn;
 use crate::app_delegate::{AppDelegate, DelegateCtx};
+use crate::crash;
 use crate::menu::ContextMenu;
-use crate::theme;
-use crate::window::Window;
+use crate::session::SessionStateFn;
+use crate::spawn::Spawner;
+use crate::theme::{self, Density};
+use crate::window::{PendingWindow, Window};
+#[cfg(feature = "spellcheck")]
+use crate::{spellcheck::SpellingSuggestions, LocalizedString, MenuItem};
 use crate::{
-    BaseState, Command, Data, Env, Event, EventCtx, KeyEvent, KeyModifiers, LayoutCtx, LifeCycle,
-    MenuDesc, PaintCtx, TimerToken, UpdateCtx, WheelEvent, WindowDesc, WindowId,
+    AutomationNode, AutomationQuery, BaseState, CaptureRequest, ColorSpace, Command, Data, Env,
+    Event, EventCtx, ExtEventSink, IdleToken, ImageBuf, KeyEvent, KeyModifiers, LayoutCtx,
+    LifeCycle, MenuDesc, MessageDialog, PaintCtx, Resources, Selector, Session, SessionWindow,
+    Store, Target, TimerToken, UpdateCtx, WheelEvent, WidgetId, WindowDesc, WindowId,
+    WindowSizePolicy,
 };
 
 use crate::command::sys as sys_cmd;
@@ -50,27 +62,111 @@ pub struct DruidHandler<T: Data> {
     app_state: Rc<RefCell<AppState<T>>>,
     /// The id for the current window.
     window_id: WindowId,
+    /// Whether mouse move and wheel events are coalesced, one per frame.
+    coalesce_input: bool,
+    /// Mouse move and wheel events not yet delivered, in the order they
+    /// arrived.
+    pending_input: Vec<Event>,
+    /// Whether an idle callback has been added, and hasn't run yet.
+    idle_requested: Cell<bool>,
+    /// Whether the idle callback has run, and the idle work is due.
+    idle_due: Cell<bool>,
+    /// Whether an idle callback to save the store has been added, and
+    /// hasn't run yet.
+    store_save_requested: Cell<bool>,
 }
 
 /// State shared by all windows in the UI.
 pub(crate) struct AppState<T: Data> {
     delegate: Option<Box<dyn AppDelegate<T>>>,
-    command_queue: VecDeque<(WindowId, Command)>,
+    command_queue: VecDeque<(Target, Command)>,
     windows: Windows<T>,
     pub(crate) env: Env,
     pub(crate) data: T,
+    /// Resources shared by all windows.
+    resources: Resources,
+    /// The application's saved state.
+    pub(crate) store: Store,
+    /// The file the store is saved to.
+    store_file: Option<PathBuf>,
+    /// The names of windows whose position is remembered when they close.
+    remembered_windows: HashMap<WindowId, String>,
+    /// Whether the open windows are saved in the session.
+    pub(crate) keeps_session: bool,
+    /// The windows saved in the session, with their kinds, state and
+    /// restoration ids.
+    session_windows: HashMap<WindowId, (String, Rc<SessionStateFn<T>>, String)>,
+    /// Whether the application is quitting, so that the windows it closes
+    /// stay in the session.
+    quitting: bool,
+    /// Commands submitted from other threads.
+    ext_event_sink: ExtEventSink,
+    /// Runs futures spawned by widgets.
+    spawner: Spawner,
+    /// Commands sent once the first window connects.
+    pub(crate) launch_commands: Vec<(Target, Command)>,
+    /// Shortcuts that work in every window, with their commands.
+    pub(crate) hotkeys: Vec<(HotKey, Command)>,
+    /// Changes to the theme made by the application.
+    env_setup: Option<Box<EnvSetupFn>>,
+    /// The color scheme the application asked for, or `None` to follow the
+    /// system's.
+    preferred_scheme: Option<ColorScheme>,
+    /// The color scheme of the theme, once it is built.
+    color_scheme: Option<ColorScheme>,
+    /// How tightly the theme packs widgets.
+    density: Density,
+    /// The environment before any changes for high contrast mode.
+    base_env: Env,
+    /// The system's high contrast colors, if the mode is on.
+    high_contrast: Option<HighContrast>,
 }
 
 /// All active windows.
 struct Windows<T: Data> {
     windows: HashMap<WindowId, Window<T>>,
     state: HashMap<WindowId, WindowState>,
+    /// Windows that have been built but have not yet connected.
+    pending: HashMap<WindowId, PendingWindow<T>>,
+    /// The environment converted for windows painted in other color spaces
+    /// than sRGB, with the environment it was converted from.
+    converted_envs: HashMap<ColorSpace, (Env, Env)>,
 }
 
 /// Per-window state not owned by user code.
 pub(crate) struct WindowState {
     pub(crate) handle: WindowHandle,
-    prev_paint_time: Option<Instant>,
+    /// The time of the last animation frame, while animating.
+    prev_frame_time: Option<Instant>,
+    /// The timing of the frame about to be painted, if the platform
+    /// reported it.
+    frame_timing: Option<FrameTiming>,
+    /// Idle work scheduled by widgets, not yet delivered.
+    idle_queue: Vec<IdleToken>,
+    /// Whether something has changed since the window was last painted.
+    needs_paint: bool,
+    /// Whether the focus has moved since the menu's editing commands were
+    /// last validated.
+    menu_stale: bool,
+    /// The widgets registered to handle commands, by selector, in the order
+    /// they registered.
+    command_handlers: HashMap<Selector, Vec<WidgetId>>,
+    /// For each selector, how many commands have been offered to its
+    /// handlers, which picks the handler the next one is offered to first.
+    command_rotation: HashMap<Selector, usize>,
+}
+
+impl WindowState {
+    /// Ask for the window to be painted.
+    ///
+    /// Only the first request before a paint reaches the platform; the
+    /// others would be answered by the same frame.
+    fn invalidate(&mut self) {
+        if !self.needs_paint {
+            self.needs_paint = true;
+            self.handle.invalidate();
+        }
+    }
 }
 
 /// Everything required for a window to handle an event.
@@ -78,42 +174,67 @@ struct SingleWindowState<'a, T: Data> {
     window_id: WindowId,
     window: &'a mut Window<T>,
     state: &'a mut WindowState,
-    command_queue: &'a mut VecDeque<(WindowId, Command)>,
+    command_queue: &'a mut VecDeque<(Target, Command)>,
     data: &'a mut T,
     env: &'a Env,
+    resources: &'a Resources,
+    store: &'a Store,
+    spawner: &'a Spawner,
 }
 
 impl<T: Data> Windows<T> {
     fn connect(&mut self, id: WindowId, handle: WindowHandle) {
+        if let Some(pending) = self.pending.remove(&id) {
+            let window = pending.into_window(&handle);
+            crash::window_added(id, window.root.widget().type_name());
+            self.windows.insert(id, window);
+        }
         let state = WindowState {
             handle,
-            prev_paint_time: None,
+            prev_frame_time: None,
+            frame_timing: None,
+            idle_queue: Vec::new(),
+            needs_paint: false,
+            menu_stale: false,
+            command_handlers: HashMap::new(),
+            command_rotation: HashMap::new(),
         };
         self.state.insert(id, state);
     }
 
-    fn add(&mut self, id: WindowId, window: Window<T>) {
-        self.windows.insert(id, window);
+    fn add(&mut self, id: WindowId, window: PendingWindow<T>) {
+        self.pending.insert(id, window);
+    }
+
+    fn contains(&self, id: WindowId) -> bool {
+        self.windows.contains_key(&id) || self.pending.contains_key(&id)
     }
 
     fn remove(&mut self, id: WindowId) -> Option<WindowHandle> {
+        crash::window_removed(id);
+        self.pending.remove(&id);
         self.windows.remove(&id);
         self.state.remove(&id).map(|state| state.handle)
     }
 
     //TODO: rename me?
+    #[allow(clippy::too_many_arguments)]
     fn get<'a>(
         &'a mut self,
         window_id: WindowId,
-        command_queue: &'a mut VecDeque<(WindowId, Command)>,
+        command_queue: &'a mut VecDeque<(Target, Command)>,
         data: &'a mut T,
         env: &'a Env,
+        resources: &'a Resources,
+        store: &'a Store,
+        spawner: &'a Spawner,
     ) -> Option<SingleWindowState<'a, T>> {
         let state = self.state.get_mut(&window_id);
         let window = self.windows.get_mut(&window_id);
 
         match (state, window) {
             (Some(state), Some(window)) => {
+                let env = converted_env(&mut self.converted_envs, env, window.color_space);
                 return Some(SingleWindowState {
                     window_id,
                     window,
@@ -121,7 +242,10 @@ impl<T: Data> Windows<T> {
                     command_queue,
                     data,
                     env,
-                })
+                    resources,
+                    store,
+                    spawner,
+                });
             }
             (None, Some(_)) => warn!("missing window for id {:?}", window_id),
             (Some(_), None) => warn!("missing state for window id {:?}", window_id),
@@ -133,60 +257,234 @@ impl<T: Data> Windows<T> {
 
 impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
     fn paint(&mut self, piet: &mut Piet, ctx: &mut dyn WinCtx) -> bool {
-        let request_anim = self.do_anim_frame(ctx);
-        self.do_layout(piet);
+        self.state.needs_paint = false;
+        let mut request_anim = self.do_anim_frame(ctx);
+        request_anim |= self.do_layout(piet);
         piet.clear(self.env.get(theme::WINDOW_BACKGROUND_COLOR));
         self.do_paint(piet);
         request_anim
     }
 
     fn do_anim_frame(&mut self, ctx: &mut dyn WinCtx) -> bool {
-        // TODO: this calculation uses wall-clock time of the paint call, which
-        // potentially has jitter.
+        // Frames are timed by when they are expected to be shown, so that
+        // animations move evenly with the display's refresh. Platforms that
+        // don't report it fall back to the wall-clock time of the paint call,
+        // which potentially has jitter.
         //
         // See https://github.com/xi-editor/druid/issues/85 for discussion.
-        let this_paint_time = Instant::now();
-        let prev_paint_time = self.state.prev_paint_time;
-        let interval = if let Some(last) = prev_paint_time {
-            let duration = this_paint_time.duration_since(last);
-            1_000_000_000 * duration.as_secs() + u64::from(duration.subsec_nanos())
-        } else {
-            0
-        };
+        let this_frame_time = self
+            .state
+            .frame_timing
+            .map(|timing| timing.presentation_time)
+            .unwrap_or_else(Instant::now);
+        let interval = self
+            .state
+            .prev_frame_time
+            .and_then(|last| this_frame_time.checked_duration_since(last))
+            .map(|duration| 1_000_000_000 * duration.as_secs() + u64::from(duration.subsec_nanos()))
+            .unwrap_or(0);
         let anim_frame_event = Event::AnimFrame(interval);
         let (_, _, request_anim) = self.do_event_inner(anim_frame_event, ctx);
         let prev = if request_anim {
-            Some(this_paint_time)
+            Some(this_frame_time)
         } else {
             None
         };
-        self.state.prev_paint_time = prev;
+        self.state.prev_frame_time = prev;
+        // The timing is only good for this frame.
+        self.state.frame_timing = None;
         request_anim
     }
 
-    fn do_layout(&mut self, piet: &mut Piet) {
+    /// Lay out the window, returning whether a widget requested an
+    /// animation frame.
+    fn do_layout(&mut self, piet: &mut Piet) -> bool {
         let mut layout_ctx = LayoutCtx {
             text_factory: piet.text(),
+            window: &self.state.handle,
             window_id: self.window_id,
+            scale: f64::from(self.state.handle.get_dpi()) / 96.0,
+            resources: self.resources,
+            store: self.store,
+            request_anim: false,
         };
         self.window.layout(&mut layout_ctx, self.data, self.env);
+        layout_ctx.request_anim
+    }
+
+    /// The size to open a window that sizes to its content at, fitted to
+    /// the work area of its monitor.
+    fn content_size(&mut self, win_ctx: &mut dyn WinCtx) -> Option<Size> {
+        if self.window.size_policy != WindowSizePolicy::SizeToContent {
+            return None;
+        }
+        let mut layout_ctx = LayoutCtx {
+            text_factory: win_ctx.text_factory(),
+            window: &self.state.handle,
+            window_id: self.window_id,
+            scale: f64::from(self.state.handle.get_dpi()) / 96.0,
+            resources: self.resources,
+            store: self.store,
+            request_anim: false,
+        };
+        let size = self
+            .window
+            .content_size(&mut layout_ctx, self.data, self.env);
+
+        let monitors = Screen::get_monitors();
+        let contains = |area: Rect, pos: Point| {
+            pos.x >= area.x0 && pos.x < area.x1 && pos.y >= area.y0 && pos.y < area.y1
+        };
+        let work_area = self
+            .state
+            .handle
+            .get_position()
+            .and_then(|pos| {
+                monitors
+                    .iter()
+                    .find(|m| contains(m.virtual_work_rect(), pos))
+            })
+            .or_else(|| monitors.iter().find(|m| m.is_primary()))
+            .map(|m| m.virtual_work_rect());
+        let size = match work_area {
+            Some(area) => Size::new(size.width.min(area.width()), size.height.min(area.height())),
+            None => size,
+        };
+        if size.width.is_finite() && size.height.is_finite() {
+            Some(size)
+        } else {
+            warn!("window content has no size, and there is no monitor to fit it to");
+            None
+        }
     }
 
     fn do_paint(&mut self, piet: &mut Piet) {
+        let mut automation = Vec::new();
+        self.paint_window(piet, &mut automation, None);
+        self.window.automation = automation;
+    }
+
+    fn paint_window(
+        &mut self,
+        piet: &mut Piet,
+        automation: &mut Vec<AutomationNode>,
+        find_rect: Option<(WidgetId, &mut Option<Rect>)>,
+    ) {
         let mut paint_ctx = PaintCtx {
             render_ctx: piet,
             window_id: self.window_id,
             region: Rect::ZERO.into(),
+            transform: Affine::default(),
+            overlays: Vec::new(),
+            resources: self.resources,
+            automation,
+            find_rect,
+            scale: f64::from(self.state.handle.get_dpi()) / 96.0,
+            color_space: self.window.color_space,
         };
         self.window.paint(&mut paint_ctx, self.data, self.env);
     }
 
+    /// Paint the window into an image, or the part of it showing `widget`.
+    ///
+    /// Returns `None` if the image couldn't be made, or if the widget wasn't
+    /// painted.
+    fn capture(&mut self, widget: Option<WidgetId>) -> Option<ImageBuf> {
+        let scale = f64::from(self.state.handle.get_dpi()) / 96.0;
+        let size = self.window.size();
+        let width = (size.width * scale).ceil() as usize;
+        let height = (size.height * scale).ceil() as usize;
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let mut device = match Device::new() {
+            Ok(device) => device,
+            Err(e) => {
+                error!("failed to create a device for capture: {:?}", e);
+                return None;
+            }
+        };
+        let mut target = match device.bitmap_target(width, height, scale) {
+            Ok(target) => target,
+            Err(e) => {
+                error!("failed to create a bitmap for capture: {:?}", e);
+                return None;
+            }
+        };
+        let mut rect = None;
+        {
+            let mut piet = target.render_context();
+            piet.clear(self.env.get(theme::WINDOW_BACKGROUND_COLOR));
+            let find_rect = widget.map(|id| (id, &mut rect));
+            self.paint_window(&mut piet, &mut Vec::new(), find_rect);
+            if let Err(e) = piet.finish() {
+                error!("failed to paint for capture: {:?}", e);
+                return None;
+            }
+        }
+        let pixels = match target.into_raw_pixels(ImageFormat::RgbaSeparate) {
+            Ok(pixels) => pixels,
+            Err(e) => {
+                error!("failed to read back captured pixels: {:?}", e);
+                return None;
+            }
+        };
+        let image =
+            ImageBuf::from_raw(pixels, width, height).with_color_space(self.window.color_space);
+        match widget {
+            None => Some(image),
+            Some(_) => rect.map(|rect| {
+                let rect = Rect::new(
+                    rect.x0 * scale,
+                    rect.y0 * scale,
+                    rect.x1 * scale,
+                    rect.y1 * scale,
+                );
+                image.crop(rect)
+            }),
+        }
+    }
+
     /// Send an event to the widget hierarchy.
     ///
     /// Returns three flags. The first is true if the event was handled. The
     /// second is true if invalidation is requested. The third is true if an
     /// animation frame is requested.
     fn do_event_inner(&mut self, event: Event, win_ctx: &mut dyn WinCtx) -> (bool, bool, bool) {
+        let cmd = match event {
+            Event::Command(cmd) => cmd,
+            event => return self.send_event(event, win_ctx),
+        };
+        let handlers = match self.state.command_handlers.get(&cmd.selector) {
+            Some(handlers) if !sys_cmd::FOCUS_COMMANDS.contains(&cmd.selector) => handlers.clone(),
+            _ => return self.send_event(Event::Command(cmd), win_ctx),
+        };
+        // Each command is offered first to the handler after the one the
+        // previous command was offered to first.
+        let rotation = self
+            .state
+            .command_rotation
+            .entry(cmd.selector.clone())
+            .or_insert(0);
+        let start = *rotation % handlers.len();
+        *rotation = rotation.wrapping_add(1);
+        let (mut is_handled, mut needs_inval, mut request_anim) = (false, false, false);
+        for i in 0..handlers.len() {
+            let target = Target::Widget(handlers[(start + i) % handlers.len()]);
+            let event = Event::TargetedCommand(target, cmd.clone());
+            let (handled, inval, anim) = self.send_event(event, win_ctx);
+            needs_inval |= inval;
+            request_anim |= anim;
+            if handled {
+                is_handled = true;
+                break;
+            }
+        }
+        (is_handled, needs_inval, request_anim)
+    }
+
+    /// Send an event to the root widget.
+    fn send_event(&mut self, event: Event, win_ctx: &mut dyn WinCtx) -> (bool, bool, bool) {
         // should there be a root base state persisting in the ui state instead?
         let mut cursor = match event {
             Event::MouseMoved(..) => Some(Cursor::Arrow),
@@ -202,36 +500,116 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
             other => other,
         };
 
-        let mut base_state = BaseState::default();
+        let mut base_state = BaseState::new(WidgetId::root());
+        // Keys only go to the root widget if no widget has focus.
+        base_state.has_focus = self.window.focus().is_some();
         let mut ctx = EventCtx {
             win_ctx,
             cursor: &mut cursor,
             command_queue: self.command_queue,
+            idle_queue: &mut self.state.idle_queue,
             base_state: &mut base_state,
             is_handled: false,
             is_root: true,
-            had_active: self.window.root.has_active(),
+            had_active: self.window.has_active(),
             window: &self.state.handle,
             window_id: self.window_id,
+            spawner: self.spawner,
+            wheel_consumed: Vec2::new(0.0, 0.0),
+            // The timing is of the frame being prepared, which only the
+            // animation frame is part of.
+            frame_timing: match event {
+                Event::AnimFrame(_) => self.state.frame_timing,
+                _ => None,
+            },
+            store: self.store,
         };
         self.window.event(&mut ctx, &event, self.data, self.env);
 
+        let (added, removed) = ctx.base_state.take_handler_changes();
+        self.update_command_handlers(added, removed);
         let is_handled = ctx.is_handled;
-        if ctx.base_state.request_focus {
-            let focus_event = Event::FocusChanged(true);
-            self.window
-                .event(&mut ctx, &focus_event, self.data, self.env);
-        }
+        let focus_changed = match ctx.base_state.request_focus.take() {
+            Some(change) => {
+                ctx.is_handled = false;
+                self.window
+                    .change_focus(&mut ctx, change, self.data, self.env)
+            }
+            None => false,
+        };
         let needs_inval = ctx.base_state.needs_inval;
         let request_anim = ctx.base_state.request_anim;
         if let Some(cursor) = cursor {
             win_ctx.set_cursor(&cursor);
         }
+        // The menu is validated once the event has been handled and the
+        // data updated, so the widget isn't asked from inside this event.
+        self.state.menu_stale |= focus_changed;
 
         (is_handled, needs_inval, request_anim)
     }
 
-    fn set_menu(&mut self, cmd: &Command) {
+    /// Add the widgets that registered to handle commands, and forget the
+    /// registrations of widgets that were removed.
+    fn update_command_handlers(
+        &mut self,
+        added: Vec<(Selector, WidgetId)>,
+        removed: Vec<WidgetId>,
+    ) {
+        let handlers = &mut self.state.command_handlers;
+        if !removed.is_empty() {
+            for ids in handlers.values_mut() {
+                ids.retain(|id| !removed.contains(id));
+            }
+            handlers.retain(|_, ids| !ids.is_empty());
+        }
+        for (selector, id) in added {
+            let ids = handlers.entry(selector).or_insert_with(Vec::new);
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    /// Ask the focused widget whether it can currently handle `selector`.
+    fn can_handle(&mut self, selector: &Selector, win_ctx: &mut dyn WinCtx) -> bool {
+        let event = Event::ValidateCommand(selector.clone());
+        let (is_handled, _, _) = self.do_event_inner(event, win_ctx);
+        is_handled
+    }
+
+    /// The focused widget, if it can currently handle `selector`.
+    fn focused_editor(
+        &mut self,
+        selector: &Selector,
+        win_ctx: &mut dyn WinCtx,
+    ) -> Option<WidgetId> {
+        let focus = self.window.focus()?;
+        if self.can_handle(selector, win_ctx) {
+            Some(focus)
+        } else {
+            None
+        }
+    }
+
+    /// Update the enabled state of the window menu's editing commands, if
+    /// focus has moved since they were last validated.
+    fn revalidate_menu(&mut self, win_ctx: &mut dyn WinCtx) {
+        if !std::mem::replace(&mut self.state.menu_stale, false) {
+            return;
+        }
+        let mut menu = match self.window.menu.take() {
+            Some(menu) => menu,
+            None => return,
+        };
+        if menu.validate_focus_commands(&mut |sel| self.can_handle(sel, win_ctx)) {
+            let platform_menu = menu.build_window_menu(&self.data, &self.env);
+            self.state.handle.set_menu(platform_menu);
+        }
+        self.window.menu = Some(menu);
+    }
+
+    fn set_menu(&mut self, cmd: &Command, win_ctx: &mut dyn WinCtx) {
         let mut menu = match cmd.get_object::<MenuDesc<T>>() {
             Some(menu) => menu.to_owned(),
             None => {
@@ -239,13 +617,14 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
                 return;
             }
         };
+        menu.validate_focus_commands(&mut |sel| self.can_handle(sel, win_ctx));
 
         let platform_menu = menu.build_window_menu(&self.data, &self.env);
         self.state.handle.set_menu(platform_menu);
         self.window.menu = Some(menu);
     }
 
-    fn show_context_menu(&mut self, cmd: &Command) {
+    fn show_context_menu(&mut self, cmd: &Command, win_ctx: &mut dyn WinCtx) {
         let (mut menu, point) = match cmd.get_object::<ContextMenu<T>>() {
             Some(ContextMenu { menu, location }) => (menu.to_owned(), *location),
             None => {
@@ -253,11 +632,52 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
                 return;
             }
         };
+        menu.validate_focus_commands(&mut |sel| self.can_handle(sel, win_ctx));
         let platform_menu = menu.build_popup_menu(&self.data, &self.env);
         self.state.handle.show_context_menu(platform_menu, point);
         self.window.context_menu = Some(menu);
     }
 
+    #[cfg(feature = "spellcheck")]
+    fn show_spelling_suggestions(&mut self, cmd: &Command) {
+        let SpellingSuggestions {
+            suggestions,
+            location,
+            ..
+        } = match cmd.get_object::<SpellingSuggestions>() {
+            Some(suggestions) => suggestions,
+            None => {
+                warn!("show-spelling-suggestions command is missing suggestions object.");
+                return;
+            }
+        };
+        let mut menu = if suggestions.is_empty() {
+            MenuDesc::empty().append(
+                MenuItem::new(
+                    LocalizedString::new("druid-spelling-no-guesses"),
+                    Selector::NOOP,
+                )
+                .disabled(),
+            )
+        } else {
+            MenuDesc::empty().append_iter(|| {
+                suggestions.iter().map(|word| {
+                    let suggestion = word.clone();
+                    MenuItem::new(
+                        LocalizedString::new("druid-spelling-suggestion")
+                            .with_arg("word", move |_, _| suggestion.clone().into()),
+                        Command::new(sys_cmd::REPLACE_MISSPELLING, word.clone()),
+                    )
+                })
+            })
+        };
+        let platform_menu = menu.build_popup_menu(&self.data, &self.env);
+        self.state
+            .handle
+            .show_context_menu(platform_menu, *location);
+        self.window.context_menu = Some(menu);
+    }
+
     fn window_got_focus(&mut self) {
         #[cfg(target_os = "macos")]
         self.macos_update_app_menu()
@@ -287,16 +707,40 @@ impl<'a, T: Data + 'static> SingleWindowState<'a, T> {
 impl<T: Data + 'static> AppState<T> {
     pub(crate) fn new(
         data: T,
-        env: Env,
+        env_setup: Option<Box<EnvSetupFn>>,
+        preferred_scheme: Option<ColorScheme>,
+        density: Density,
         delegate: Option<Box<dyn AppDelegate<T>>>,
+        ext_event_sink: ExtEventSink,
+        spawner: Spawner,
     ) -> Rc<RefCell<Self>> {
-        Rc::new(RefCell::new(AppState {
+        let mut state = AppState {
             delegate,
             command_queue: VecDeque::new(),
             data,
-            env,
+            base_env: Env::default(),
+            env: Env::default(),
+            resources: Resources::default(),
+            store: Store::default(),
+            store_file: None,
             windows: Windows::default(),
-        }))
+            remembered_windows: HashMap::new(),
+            keeps_session: false,
+            session_windows: HashMap::new(),
+            quitting: false,
+            ext_event_sink,
+            spawner,
+            launch_commands: Vec::new(),
+            hotkeys: Vec::new(),
+            env_setup,
+            preferred_scheme,
+            color_scheme: None,
+            density,
+            high_contrast: None,
+        };
+        state.update_color_scheme();
+        state.update_high_contrast();
+        Rc::new(RefCell::new(state))
     }
 
     fn get_menu_cmd(&self, window_id: WindowId, cmd_id: u32) -> Option<Command> {
@@ -340,17 +784,217 @@ impl<T: Data + 'static> AppState<T> {
         }
     }
 
+    /// Give the delegate a command; returns `false` if it should go no
+    /// further.
+    fn delegate_cmd(&mut self, id: WindowId, target: Target, cmd: &Command) -> bool {
+        self.with_delegate(id, |del, data, env, ctx| {
+            del.command(target, cmd, data, env, ctx)
+        })
+        .unwrap_or(true)
+    }
+
     fn connect(&mut self, id: WindowId, handle: WindowHandle) {
+        if let Some(idle) = handle.get_idle_handle() {
+            let wake = DruidHandler::<T>::wake_for_ext_events;
+            self.ext_event_sink.set_waker(id, idle, wake);
+        }
         self.windows.connect(id, handle);
         self.with_delegate(id, |del, data, env, ctx| {
             del.window_added(id, data, env, ctx)
         });
     }
 
-    pub(crate) fn add_window(&mut self, id: WindowId, window: Window<T>) {
+    pub(crate) fn add_window(&mut self, id: WindowId, window: PendingWindow<T>) {
         self.windows.add(id, window);
     }
 
+    /// Check the system's high contrast mode, and update the theme if it has
+    /// changed.
+    ///
+    /// Returns whether the mode is on, if it changed.
+    fn update_high_contrast(&mut self) -> Option<bool> {
+        let current = HighContrast::current();
+        if current == self.high_contrast {
+            return None;
+        }
+        self.high_contrast = current;
+        self.env = self.high_contrast_env();
+        self.invalidate_windows();
+        Some(self.high_contrast.is_some())
+    }
+
+    /// Check the color scheme the theme should use, and rebuild the theme
+    /// if it has changed.
+    ///
+    /// Returns the new color scheme, if it changed.
+    fn update_color_scheme(&mut self) -> Option<ColorScheme> {
+        let scheme = self.preferred_scheme.unwrap_or_else(ColorScheme::current);
+        if self.color_scheme == Some(scheme) {
+            return None;
+        }
+        self.color_scheme = Some(scheme);
+        self.rebuild_theme(scheme);
+        Some(scheme)
+    }
+
+    /// Change the theme's density, rebuilding the theme if it has changed.
+    ///
+    /// Returns whether the theme was rebuilt.
+    fn set_density(&mut self, density: Density) -> bool {
+        if self.density == density {
+            return false;
+        }
+        self.density = density;
+        match self.color_scheme {
+            Some(scheme) => {
+                self.rebuild_theme(scheme);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Build the theme for `scheme` and the density, with the application's
+    /// changes, and repaint every window with it.
+    fn rebuild_theme(&mut self, scheme: ColorScheme) {
+        let mut env = theme::with_density(theme::init_for(scheme), self.density);
+        if let Some(f) = &self.env_setup {
+            f(&mut env);
+        }
+        self.base_env = env;
+        self.env = self.high_contrast_env();
+        self.invalidate_windows();
+    }
+
+    /// The base environment, with the high contrast palette if it is on.
+    fn high_contrast_env(&self) -> Env {
+        match &self.high_contrast {
+            Some(colors) => theme::high_contrast(&self.base_env, colors),
+            None => self.base_env.clone(),
+        }
+    }
+
+    fn invalidate_windows(&mut self) {
+        for state in self.windows.state.values_mut() {
+            state.invalidate();
+        }
+    }
+
+    /// The ids of the windows that have connected.
+    fn window_ids(&self) -> Vec<WindowId> {
+        self.windows.windows.keys().copied().collect()
+    }
+
+    /// Whether a window with this id has been added and not yet removed.
+    pub(crate) fn has_window(&self, id: WindowId) -> bool {
+        self.windows.contains(id)
+    }
+
+    /// The handle of a window, if it exists and has connected.
+    pub(crate) fn window_handle(&self, id: WindowId) -> Option<WindowHandle> {
+        self.windows
+            .state
+            .get(&id)
+            .map(|state| state.handle.clone())
+    }
+
+    /// The frame of a window on screen, if it exists.
+    pub(crate) fn window_frame(&self, id: WindowId) -> Option<Rect> {
+        let position = self.windows.state.get(&id)?.handle.get_position()?;
+        let size = self.windows.windows.get(&id)?.size();
+        Some(Rect::from_origin_size(position, size))
+    }
+
+    /// Remember the position of this window under `name` when it closes.
+    pub(crate) fn remember_position(&mut self, id: WindowId, name: String) {
+        self.remembered_windows.insert(id, name);
+    }
+
+    /// Save this window in the session, returning the id the platform
+    /// restores its state by.
+    ///
+    /// Windows reopened from the session keep the id they were saved with;
+    /// others get one that no open window has.
+    pub(crate) fn add_session_window(
+        &mut self,
+        id: WindowId,
+        kind: String,
+        state: Rc<SessionStateFn<T>>,
+        restoration_id: Option<String>,
+    ) -> String {
+        let restoration_id = restoration_id.unwrap_or_else(|| {
+            (0..)
+                .map(|n| format!("druid.session.{}", n))
+                .find(|candidate| {
+                    !self
+                        .session_windows
+                        .values()
+                        .any(|(_, _, taken)| taken == candidate)
+                })
+                .unwrap_or_default()
+        });
+        self.session_windows
+            .insert(id, (kind, state, restoration_id.clone()));
+        restoration_id
+    }
+
+    /// The position of the last window closed with this name.
+    pub(crate) fn last_position(&self, name: &str) -> Option<Point> {
+        let key = format!("window_positions.{}", name);
+        let (x, y) = self.store.get::<(f64, f64)>(&key)?;
+        Some(Point::new(x, y))
+    }
+
+    /// The command of the first global shortcut matching this key.
+    fn hotkey_command(&self, event: &KeyEvent) -> Option<Command> {
+        self.hotkeys
+            .iter()
+            .find(|(hotkey, _)| hotkey.matches(event))
+            .map(|(_, command)| command.clone())
+    }
+
+    /// Keep the store in a file, loading it if the file exists.
+    pub(crate) fn load_store(&mut self, path: PathBuf) {
+        match Store::load(&path) {
+            Ok(store) => self.store = store,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => warn!("failed to load the store from {:?}: {}", path, e),
+        }
+        self.store_file = Some(path);
+    }
+
+    /// Save the store, if the application keeps it in a file.
+    fn save_store(&self) {
+        if let Some(path) = &self.store_file {
+            if let Err(e) = self.store.save(path) {
+                error!("failed to save the store to {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// Save the open windows in the store, if the application keeps its
+    /// session.
+    pub(crate) fn save_session(&self) {
+        if !self.keeps_session {
+            return;
+        }
+        let mut ids: Vec<_> = self.session_windows.keys().copied().collect();
+        ids.sort();
+        let windows = ids
+            .into_iter()
+            .filter_map(|id| {
+                let (kind, state, restoration_id) = &self.session_windows[&id];
+                Some(SessionWindow {
+                    kind: kind.clone(),
+                    frame: self.window_frame(id)?,
+                    state: state(&self.data),
+                    restoration_id: restoration_id.clone(),
+                })
+            })
+            .collect();
+        Session { windows }.save(&self.store);
+    }
+
     /// Called after this window has been closed by the platform.
     ///
     /// We clean up resources and notifiy the delegate, if necessary.
@@ -358,7 +1002,35 @@ impl<T: Data + 'static> AppState<T> {
         self.with_delegate(window_id, |del, data, env, ctx| {
             del.window_removed(window_id, data, env, ctx)
         });
+        // The position was captured while the window was still open.
+        self.remembered_windows.remove(&window_id);
+        if !self.quitting && self.session_windows.contains_key(&window_id) {
+            // The last window stays in the session, so that closing it
+            // doesn't lose what was open.
+            if self.session_windows.len() > 1 {
+                self.session_windows.remove(&window_id);
+            }
+            self.save_session();
+        }
+        self.session_windows.remove(&window_id);
         self.windows.remove(window_id);
+        self.save_store();
+        if self.ext_event_sink.window_removed(window_id) {
+            // Wake another window instead, if there is one.
+            let other = self
+                .windows
+                .state
+                .iter()
+                .find_map(|(id, state)| state.handle.get_idle_handle().map(|idle| (*id, idle)));
+            if let Some((id, idle)) = other {
+                let wake = DruidHandler::<T>::wake_for_ext_events;
+                self.ext_event_sink.set_waker(id, idle, wake);
+            }
+        }
+        // Resources that were only used by this window are no longer needed.
+        self.resources.window_closed(window_id);
+        let closed = Command::new(sys_cmd::WINDOW_CLOSED, window_id);
+        self.command_queue.push_back((Target::Global, closed));
     }
 
     /// triggered by a menu item or other command.
@@ -367,11 +1039,30 @@ impl<T: Data + 'static> AppState<T> {
     /// window handle; the platform should close the window, and then call
     /// our handlers `destroy()` method, at which point we can do our cleanup.
     fn request_close_window(&mut self, window_id: WindowId) {
+        self.capture_position(window_id);
         if let Some(state) = self.windows.state.get_mut(&window_id) {
             state.handle.close();
         }
     }
 
+    /// Remember the position of a window that is about to close, if it is
+    /// placed with `WindowPlacement::RememberLast`.
+    fn capture_position(&mut self, window_id: WindowId) {
+        let name = match self.remembered_windows.get(&window_id) {
+            Some(name) => name,
+            None => return,
+        };
+        let position = self
+            .windows
+            .state
+            .get(&window_id)
+            .and_then(|state| state.handle.get_position());
+        if let Some(position) = position {
+            let key = format!("window_positions.{}", name);
+            self.store.set(&key, &(position.x, position.y));
+        }
+    }
+
     fn show_window(&mut self, id: WindowId) {
         if let Some(state) = self.windows.state.get(&id) {
             state.handle.bring_to_front_and_focus();
@@ -384,9 +1075,39 @@ impl<T: Data + 'static> AppState<T> {
             ref mut windows,
             ref mut data,
             ref env,
+            ref resources,
+            ref store,
+            ref spawner,
             ..
         } = self;
-        windows.get(window_id, command_queue, data, env)
+        windows.get(
+            window_id,
+            command_queue,
+            data,
+            env,
+            resources,
+            store,
+            spawner,
+        )
+    }
+
+    /// The handle of a window that sizes to its content, and the size it
+    /// should be given.
+    fn content_size(
+        &mut self,
+        window_id: WindowId,
+        win_ctx: &mut dyn WinCtx,
+    ) -> Option<(WindowHandle, Size)> {
+        let mut win = self.assemble_window_state(window_id)?;
+        let size = win.content_size(win_ctx)?;
+        Some((win.state.handle.clone(), size))
+    }
+
+    /// Record when the next frame of a window is expected to be shown.
+    fn prepare_frame(&mut self, window_id: WindowId, timing: FrameTiming) {
+        if let Some(state) = self.windows.state.get_mut(&window_id) {
+            state.frame_timing = Some(timing);
+        }
     }
 
     fn paint(&mut self, window_id: WindowId, piet: &mut Piet, ctx: &mut dyn WinCtx) -> bool {
@@ -395,6 +1116,16 @@ impl<T: Data + 'static> AppState<T> {
             .unwrap_or(false)
     }
 
+    /// Whether a window has changed since it was last painted, or is
+    /// animating.
+    fn needs_paint(&self, window_id: WindowId) -> bool {
+        self.windows
+            .state
+            .get(&window_id)
+            .map(|state| state.needs_paint || state.prev_frame_time.is_some())
+            .unwrap_or(false)
+    }
+
     fn do_event(&mut self, source_id: WindowId, event: Event, win_ctx: &mut dyn WinCtx) -> bool {
         let event = self.delegate_event(source_id, event);
 
@@ -404,13 +1135,20 @@ impl<T: Data + 'static> AppState<T> {
                 match cmd.selector {
                     sys_cmd::SET_MENU => {
                         if let Some(mut win) = self.assemble_window_state(source_id) {
-                            win.set_menu(cmd);
+                            win.set_menu(cmd, win_ctx);
                         }
                         return true;
                     }
                     sys_cmd::SHOW_CONTEXT_MENU => {
                         if let Some(mut win) = self.assemble_window_state(source_id) {
-                            win.show_context_menu(cmd);
+                            win.show_context_menu(cmd, win_ctx);
+                        }
+                        return true;
+                    }
+                    #[cfg(feature = "spellcheck")]
+                    sys_cmd::SHOW_SPELLING_SUGGESTIONS => {
+                        if let Some(mut win) = self.assemble_window_state(source_id) {
+                            win.show_spelling_suggestions(cmd);
                         }
                         return true;
                     }
@@ -425,31 +1163,101 @@ impl<T: Data + 'static> AppState<T> {
             // if the event was swallowed by the delegate we consider it handled?
             (true, false, false)
         };
+        self.update_windows(source_id, anim || dirty, win_ctx);
+        is_handled
+    }
 
+    /// Send `update` to every window, after something happened in
+    /// `source_id`, and repaint those that need it.
+    ///
+    /// `source_dirty` is whether the source window needs repainting anyway.
+    fn update_windows(
+        &mut self,
+        source_id: WindowId,
+        source_dirty: bool,
+        win_ctx: &mut dyn WinCtx,
+    ) {
         let AppState {
             ref mut windows,
             ref data,
             ref env,
+            ref store,
             ..
         } = self;
-        let Windows { state, windows } = windows;
+        let Windows {
+            state,
+            windows,
+            converted_envs,
+            ..
+        } = windows;
 
         // we send `update` to all windows, not just the active one:
         for (id, window) in windows {
-            if let Some(state) = state.get(id) {
+            if let Some(state) = state.get_mut(id) {
+                let env = converted_env(converted_envs, env, window.color_space);
                 let mut update_ctx = UpdateCtx {
                     text_factory: win_ctx.text_factory(),
                     window: &state.handle,
                     needs_inval: false,
+                    request_anim: false,
                     window_id: *id,
+                    store,
                 };
                 window.update(&mut update_ctx, data, env);
-                if update_ctx.needs_inval || (*id == source_id && (anim || dirty)) {
-                    update_ctx.window.invalidate();
+                // On macOS the menu bar belongs to the application, and shows
+                // the menu of the frontmost window; the others are rebuilt when
+                // they get focus.
+                if cfg!(not(target_os = "macos")) || *id == source_id {
+                    window.update_menu(&state.handle, data, env);
+                }
+                if update_ctx.needs_inval
+                    || update_ctx.request_anim
+                    || (*id == source_id && source_dirty)
+                {
+                    state.invalidate();
                 }
             }
         }
-        is_handled
+        if let Some(mut win) = self.assemble_window_state(source_id) {
+            win.revalidate_menu(win_ctx);
+        }
+    }
+
+    /// Where to send one of the [`EDITOR_COMMANDS`]: to the focused widget
+    /// of the window, if it can handle the command, or else to `target`.
+    ///
+    /// [`EDITOR_COMMANDS`]: ../commands/constant.EDITOR_COMMANDS.html
+    fn editor_target(
+        &mut self,
+        window_id: WindowId,
+        target: Target,
+        selector: &Selector,
+        win_ctx: &mut dyn WinCtx,
+    ) -> Target {
+        if let Target::Widget(_) = target {
+            return target;
+        }
+        self.assemble_window_state(window_id)
+            .and_then(|mut win| win.focused_editor(selector, win_ctx))
+            .map(Target::Widget)
+            .unwrap_or(target)
+    }
+
+    /// Whether widgets in this window have scheduled idle work.
+    fn has_idle_work(&self, window_id: WindowId) -> bool {
+        self.windows
+            .state
+            .get(&window_id)
+            .map(|state| !state.idle_queue.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Take the idle work scheduled by widgets in this window.
+    fn take_idle_work(&mut self, window_id: WindowId) -> Vec<IdleToken> {
+        match self.windows.state.get_mut(&window_id) {
+            Some(state) => std::mem::replace(&mut state.idle_queue, Vec::new()),
+            None => Vec::new(),
+        }
     }
 
     fn window_got_focus(&mut self, window_id: WindowId, _ctx: &mut dyn WinCtx) {
@@ -465,11 +1273,50 @@ impl<T: Data + 'static> DruidHandler<T> {
     pub(crate) fn new_shared(
         app_state: Rc<RefCell<AppState<T>>>,
         window_id: WindowId,
+        coalesce_input: bool,
     ) -> DruidHandler<T> {
         DruidHandler {
             app_state,
             window_id,
+            coalesce_input,
+            pending_input: Vec::new(),
+            idle_requested: Cell::new(false),
+            idle_due: Cell::new(false),
+            store_save_requested: Cell::new(false),
+        }
+    }
+
+    /// Deliver any coalesced input events.
+    ///
+    /// This is called before each frame, and before any other event, so that
+    /// widgets see events in the order they happened.
+    fn flush_pending_input(&mut self, win_ctx: &mut dyn WinCtx) {
+        for event in std::mem::replace(&mut self.pending_input, Vec::new()) {
+            self.do_event(event, win_ctx);
+        }
+    }
+
+    /// Hold an input event until the next frame, merging it into the
+    /// previous one if that is the same kind of event, with the same
+    /// modifiers.
+    fn coalesce(&mut self, event: Event, win_ctx: &mut dyn WinCtx) {
+        match (self.pending_input.last_mut(), &event) {
+            (Some(Event::MouseMoved(last)), Event::MouseMoved(new)) if last.mods == new.mods => {
+                // Only the latest position matters.
+                *last = new.clone();
+                return;
+            }
+            (Some(Event::Wheel(last)), Event::Wheel(new)) if last.mods == new.mods => {
+                last.delta = last.delta + new.delta;
+                return;
+            }
+            _ => (),
         }
+        // One frame delivers everything held until then.
+        if self.pending_input.is_empty() {
+            win_ctx.invalidate();
+        }
+        self.pending_input.push(event);
     }
 
     /// Send an event to the widget hierarchy.
@@ -479,24 +1326,218 @@ impl<T: Data + 'static> DruidHandler<T> {
     /// This is principally because in certain cases (such as keydown on Windows)
     /// the OS needs to know if an event was handled.
     fn do_event(&mut self, event: Event, win_ctx: &mut dyn WinCtx) -> bool {
+        self.flush_pending_input(win_ctx);
+        let frequent = match event {
+            Event::MouseMoved(_) | Event::AnimFrame(_) => true,
+            _ => false,
+        };
+        if !frequent {
+            crash::record(|| format!("{:?}: {:?}", self.window_id, event));
+        }
         let result = self
             .app_state
             .borrow_mut()
             .do_event(self.window_id, event, win_ctx);
         self.process_commands(win_ctx);
+        self.run_idle_work(win_ctx);
+        self.request_idle();
+        if !frequent {
+            // Keep the saved session current, so that a crash doesn't lose
+            // it. Any change is saved with the rest of the store.
+            self.app_state.borrow().save_session();
+        }
+        self.request_store_save();
         result
     }
 
     fn process_commands(&mut self, win_ctx: &mut dyn WinCtx) {
+        {
+            let mut app_state = self.app_state.borrow_mut();
+            let ext_commands = app_state.ext_event_sink.take_commands();
+            app_state.command_queue.extend(ext_commands);
+        }
         loop {
             let next_cmd = self.app_state.borrow_mut().command_queue.pop_front();
             match next_cmd {
-                Some((id, cmd)) => self.handle_cmd(id, cmd, win_ctx),
+                Some((target, cmd)) => self.handle_cmd(target, cmd, win_ctx),
                 None => break,
             }
         }
     }
 
+    /// Add an idle callback, if widgets have scheduled idle work and one
+    /// hasn't been added already.
+    fn request_idle(&self) {
+        if self.idle_requested.get() {
+            return;
+        }
+        let idle = {
+            let app_state = self.app_state.borrow();
+            if !app_state.has_idle_work(self.window_id) {
+                return;
+            }
+            app_state
+                .windows
+                .state
+                .get(&self.window_id)
+                .and_then(|state| state.handle.get_idle_handle())
+        };
+        match idle {
+            Some(idle) => {
+                self.idle_requested.set(true);
+                idle.add_idle(|handler| Self::idle_work_due(handler));
+            }
+            None => warn!("window {:?} can't run idle work", self.window_id),
+        }
+    }
+
+    /// Called from the idle callback, with this window's handler.
+    ///
+    /// There is no `WinCtx` here, so the work is only marked as due, and a
+    /// frame is requested; it is delivered before the frame is painted.
+    fn idle_work_due(handler: &dyn Any) {
+        let handler = match handler.downcast_ref::<DruidHandler<T>>() {
+            Some(handler) => handler,
+            None => {
+                error!("idle callback called with an unknown handler");
+                return;
+            }
+        };
+        handler.idle_requested.set(false);
+        handler.idle_due.set(true);
+        // If the app state is borrowed we are inside an event, and the work
+        // is delivered when it finishes.
+        if let Ok(app_state) = handler.app_state.try_borrow() {
+            if let Some(state) = app_state.windows.state.get(&handler.window_id) {
+                state.handle.invalidate();
+            }
+        }
+    }
+
+    /// Deliver the idle work scheduled by widgets, if the idle callback has
+    /// run since it was scheduled.
+    fn run_idle_work(&mut self, win_ctx: &mut dyn WinCtx) {
+        if !self.idle_due.replace(false) {
+            return;
+        }
+        let tokens = self.app_state.borrow_mut().take_idle_work(self.window_id);
+        for token in tokens {
+            self.do_event(Event::Idle(token), win_ctx);
+        }
+    }
+
+    /// Add an idle callback to save the store, if it has changed and one
+    /// hasn't been added already.
+    ///
+    /// Saving once pending input has been handled means a burst of changes,
+    /// such as dragging a slider, is saved once.
+    fn request_store_save(&self) {
+        if self.store_save_requested.get() {
+            return;
+        }
+        let idle = {
+            let app_state = self.app_state.borrow();
+            if app_state.store_file.is_none() || !app_state.store.has_changes() {
+                return;
+            }
+            app_state
+                .windows
+                .state
+                .get(&self.window_id)
+                .and_then(|state| state.handle.get_idle_handle())
+        };
+        if let Some(idle) = idle {
+            self.store_save_requested.set(true);
+            idle.add_idle(|handler| Self::save_store_when_idle(handler));
+        }
+    }
+
+    /// Called from the idle callback added by `request_store_save`, with
+    /// this window's handler.
+    fn save_store_when_idle(handler: &dyn Any) {
+        let handler = match handler.downcast_ref::<DruidHandler<T>>() {
+            Some(handler) => handler,
+            None => {
+                error!("store save callback called with an unknown handler");
+                return;
+            }
+        };
+        handler.store_save_requested.set(false);
+        // If the app state is borrowed we are inside an event, and the save
+        // is requested again when it finishes.
+        if let Ok(app_state) = handler.app_state.try_borrow() {
+            app_state.save_store();
+        }
+    }
+
+    /// Called on the UI thread when commands have been submitted from other
+    /// threads, with this window's handler.
+    ///
+    /// We can't deliver the commands here, as there is no `WinCtx`, so we
+    /// request a frame; `paint` delivers them.
+    fn wake_for_ext_events(handler: &dyn Any) {
+        let handler = match handler.downcast_ref::<DruidHandler<T>>() {
+            Some(handler) => handler,
+            None => {
+                error!("external event waker called with an unknown handler");
+                return;
+            }
+        };
+        // If the app state is borrowed we are inside an event, and the
+        // commands are delivered when it finishes.
+        if let Ok(app_state) = handler.app_state.try_borrow() {
+            if let Some(state) = app_state.windows.state.get(&handler.window_id) {
+                state.handle.invalidate();
+            }
+        }
+    }
+
+    /// Resize a window that sizes to its content, from an idle callback
+    /// with this window's handler.
+    fn resize_to_content(handler: &dyn Any, size: Size) {
+        let handler = match handler.downcast_ref::<DruidHandler<T>>() {
+            Some(handler) => handler,
+            None => {
+                error!("content size callback called with an unknown handler");
+                return;
+            }
+        };
+        // The handle is cloned so that the app state isn't borrowed when
+        // the platform sends the new size.
+        let handle = match handler.app_state.try_borrow() {
+            Ok(app_state) => app_state
+                .windows
+                .state
+                .get(&handler.window_id)
+                .map(|state| state.handle.clone()),
+            Err(_) => None,
+        };
+        match handle {
+            Some(handle) => handle.set_size(size),
+            None => warn!(
+                "window {:?} could not be sized to its content",
+                handler.window_id
+            ),
+        }
+    }
+
+    /// Send the command of the global shortcut matching this key, if there
+    /// is one.
+    fn send_hotkey(&mut self, event: &KeyEvent, win_ctx: &mut dyn WinCtx) -> bool {
+        let command = self.app_state.borrow().hotkey_command(event);
+        match command {
+            Some(command) => {
+                self.app_state
+                    .borrow_mut()
+                    .command_queue
+                    .push_back((Target::Window(self.window_id), command));
+                self.process_commands(win_ctx);
+                true
+            }
+            None => false,
+        }
+    }
+
     fn handle_system_cmd(&mut self, cmd_id: u32, win_ctx: &mut dyn WinCtx) {
         let cmd = self.app_state.borrow().get_menu_cmd(self.window_id, cmd_id);
         match cmd {
@@ -504,19 +1545,42 @@ impl<T: Data + 'static> DruidHandler<T> {
                 .app_state
                 .borrow_mut()
                 .command_queue
-                .push_back((self.window_id, cmd)),
+                .push_back((Target::Window(self.window_id), cmd)),
             None => warn!("No command for menu id {}", cmd_id),
         }
         self.process_commands(win_ctx)
     }
 
     /// Handle a command. Top level commands (e.g. for creating and destroying windows)
-    /// have their logic here; other commands are passed to the window.
-    fn handle_cmd(&mut self, window_id: WindowId, cmd: Command, win_ctx: &mut dyn WinCtx) {
+    /// have their logic here; other commands are passed to the target windows.
+    fn handle_cmd(&mut self, target: Target, cmd: Command, win_ctx: &mut dyn WinCtx) {
+        crash::record(|| format!("{:?}: {} to {:?}", self.window_id, cmd.selector, target));
+        if !self
+            .app_state
+            .borrow_mut()
+            .delegate_cmd(self.window_id, target, &cmd)
+        {
+            return;
+        }
+        // Window-level commands without a window target apply to this window.
+        let window_id = match target {
+            Target::Window(id) => id,
+            Target::Global | Target::Widget(_) => self.window_id,
+        };
+        // Undo and redo go to the focused editor when there is one, and
+        // otherwise to the whole application.
+        let target = if sys_cmd::EDITOR_COMMANDS.contains(&cmd.selector) {
+            self.app_state
+                .borrow_mut()
+                .editor_target(window_id, target, &cmd.selector, win_ctx)
+        } else {
+            target
+        };
         //FIXME: we need some way of getting the correct `WinCtx` for this window.
         match &cmd.selector {
             &sys_cmd::SHOW_OPEN_PANEL => self.show_open_panel(cmd, window_id, win_ctx),
             &sys_cmd::SHOW_SAVE_PANEL => self.show_save_panel(cmd, window_id, win_ctx),
+            &sys_cmd::SHOW_MESSAGE_DIALOG => self.show_message_dialog(cmd, window_id, win_ctx),
             &sys_cmd::NEW_WINDOW => self.new_window(cmd),
             &sys_cmd::CLOSE_WINDOW => self.request_close_window(cmd, window_id),
             &sys_cmd::SHOW_WINDOW => self.show_window(cmd),
@@ -524,12 +1588,25 @@ impl<T: Data + 'static> DruidHandler<T> {
             &sys_cmd::HIDE_APPLICATION => self.hide_app(),
             &sys_cmd::HIDE_OTHERS => self.hide_others(),
             &sys_cmd::PASTE => self.do_paste(window_id, win_ctx),
+            &sys_cmd::QUERY_AUTOMATION => self.query_automation(cmd, target),
+            &sys_cmd::CAPTURE_IMAGE => self.capture_image(cmd, window_id),
+            &sys_cmd::SET_COLOR_SCHEME => self.set_color_scheme(cmd, window_id, win_ctx),
+            &sys_cmd::SET_DENSITY => self.set_density(cmd, window_id, win_ctx),
             sel => {
-                info!("handle_cmd {}", sel);
-                let event = Event::Command(cmd);
-                self.app_state
-                    .borrow_mut()
-                    .do_event(window_id, event, win_ctx);
+                info!("handle_cmd {} for {:?}", sel, target);
+                let window_ids = match target {
+                    Target::Window(id) => vec![id],
+                    Target::Global | Target::Widget(_) => self.app_state.borrow().window_ids(),
+                };
+                for id in window_ids {
+                    // A widget target is found along the way; each window
+                    // only passes it on if it may contain the widget.
+                    let event = match target {
+                        Target::Widget(_) => Event::TargetedCommand(target, cmd.clone()),
+                        _ => Event::Command(cmd.clone()),
+                    };
+                    self.app_state.borrow_mut().do_event(id, event, win_ctx);
+                }
             }
         }
     }
@@ -564,6 +1641,23 @@ impl<T: Data + 'static> DruidHandler<T> {
         }
     }
 
+    fn show_message_dialog(&mut self, cmd: Command, window_id: WindowId, win_ctx: &mut dyn WinCtx) {
+        let dialog = match cmd.get_object::<MessageDialog>() {
+            Some(dialog) => dialog,
+            None => {
+                warn!("show_message_dialog command is missing a MessageDialog");
+                return;
+            }
+        };
+        let confirmed = win_ctx.message_dialog_sync(dialog.options.clone());
+        if let Some(cmd) = dialog.response(confirmed) {
+            let event = Event::Command(cmd.clone());
+            self.app_state
+                .borrow_mut()
+                .do_event(window_id, event, win_ctx);
+        }
+    }
+
     fn new_window(&mut self, cmd: Command) {
         let desc = match cmd.get_object::<WindowDesc<T>>() {
             Some(wd) => wd,
@@ -595,12 +1689,104 @@ impl<T: Data + 'static> DruidHandler<T> {
         self.app_state.borrow_mut().show_window(id);
     }
 
+    fn query_automation(&mut self, cmd: Command, target: Target) {
+        let query = match cmd.get_object::<AutomationQuery>() {
+            Some(query) => query,
+            None => {
+                warn!("automation query command is missing its query");
+                return;
+            }
+        };
+        let app_state = self.app_state.borrow();
+        let nodes = app_state
+            .windows
+            .windows
+            .iter()
+            .filter(|(id, _)| match target {
+                Target::Window(target) => **id == target,
+                Target::Global | Target::Widget(_) => true,
+            })
+            .flat_map(|(_, window)| window.automation.iter().cloned())
+            .collect();
+        query.reply(nodes);
+    }
+
+    fn capture_image(&mut self, cmd: Command, window_id: WindowId) {
+        let request = match cmd.get_object::<CaptureRequest>() {
+            Some(request) => request,
+            None => {
+                warn!("capture command is missing its request");
+                return;
+            }
+        };
+        let mut app_state = self.app_state.borrow_mut();
+        let image = app_state
+            .assemble_window_state(window_id)
+            .and_then(|mut win| win.capture(request.widget));
+        if let Some(reply) = request.reply(image) {
+            app_state.command_queue.push_back(reply);
+        }
+    }
+
+    fn set_color_scheme(&mut self, cmd: Command, window_id: WindowId, win_ctx: &mut dyn WinCtx) {
+        let scheme = match cmd.get_object::<Option<ColorScheme>>() {
+            Some(scheme) => *scheme,
+            None => {
+                warn!("set color scheme command is missing its color scheme");
+                return;
+            }
+        };
+        let changed = {
+            let mut app_state = self.app_state.borrow_mut();
+            app_state.preferred_scheme = scheme;
+            let changed = app_state.update_color_scheme();
+            // Widgets see the new theme in `update`, and are laid out again
+            // before the windows are painted.
+            if changed.is_some() {
+                app_state.update_windows(window_id, true, win_ctx);
+            }
+            changed
+        };
+        if let Some(scheme) = changed {
+            self.send_to_windows(LifeCycle::ColorSchemeChanged(scheme), win_ctx);
+        }
+    }
+
+    fn set_density(&mut self, cmd: Command, window_id: WindowId, win_ctx: &mut dyn WinCtx) {
+        match cmd.get_object::<Density>() {
+            Some(density) => {
+                let mut app_state = self.app_state.borrow_mut();
+                // Widgets see the new theme in `update`, and are laid out
+                // again before the windows are painted.
+                if app_state.set_density(*density) {
+                    app_state.update_windows(window_id, true, win_ctx);
+                }
+            }
+            None => warn!("set density command is missing its density"),
+        }
+    }
+
+    /// Send a lifecycle event to every window.
+    fn send_to_windows(&mut self, event: LifeCycle, win_ctx: &mut dyn WinCtx) {
+        let window_ids = self.app_state.borrow().window_ids();
+        for id in window_ids {
+            let event = Event::LifeCycle(event);
+            self.app_state.borrow_mut().do_event(id, event, win_ctx);
+        }
+    }
+
     fn do_paste(&mut self, window_id: WindowId, ctx: &mut dyn WinCtx) {
         let event = Event::Paste(Application::clipboard());
         self.app_state.borrow_mut().do_event(window_id, event, ctx);
     }
 
     fn quit(&self) {
+        {
+            let mut app_state = self.app_state.borrow_mut();
+            app_state.save_session();
+            app_state.save_store();
+            app_state.quitting = true;
+        }
         Application::quit()
     }
 
@@ -625,14 +1811,53 @@ impl<T: Data + 'static> WinHandler for DruidHandler<T> {
     }
 
     fn connected(&mut self, ctx: &mut dyn WinCtx) {
+        {
+            // The first window to connect sends the launch commands, once it
+            // has handled `WindowConnected`.
+            let mut app_state = self.app_state.borrow_mut();
+            let launch_commands = std::mem::replace(&mut app_state.launch_commands, Vec::new());
+            app_state.command_queue.extend(launch_commands);
+        }
         let event = Event::LifeCycle(LifeCycle::WindowConnected);
         self.do_event(event, ctx);
+
+        // The content is measured now, while there is a `WinCtx`, but the
+        // resize waits for an idle callback: resizing from inside
+        // `connected` re-enters the platform window while it is being set up.
+        let content_size = self
+            .app_state
+            .borrow_mut()
+            .content_size(self.window_id, ctx);
+        if let Some((handle, size)) = content_size {
+            match handle.get_idle_handle() {
+                Some(idle) => idle.add_idle(move |handler| Self::resize_to_content(handler, size)),
+                None => handle.set_size(size),
+            }
+        }
+    }
+
+    fn prepare_frame(&mut self, timing: FrameTiming) {
+        self.app_state
+            .borrow_mut()
+            .prepare_frame(self.window_id, timing);
     }
 
     fn paint(&mut self, piet: &mut Piet, ctx: &mut dyn WinCtx) -> bool {
+        self.flush_pending_input(ctx);
+        self.process_commands(ctx);
+        self.run_idle_work(ctx);
         self.app_state.borrow_mut().paint(self.window_id, piet, ctx)
     }
 
+    fn needs_paint(&mut self, ctx: &mut dyn WinCtx) -> bool {
+        // Frames are also requested to deliver coalesced input and commands
+        // from other threads, which may or may not change anything.
+        self.flush_pending_input(ctx);
+        self.process_commands(ctx);
+        self.run_idle_work(ctx);
+        self.app_state.borrow().needs_paint(self.window_id)
+    }
+
     fn size(&mut self, width: u32, height: u32, ctx: &mut dyn WinCtx) {
         let event = Event::Size(Size::new(f64::from(width), f64::from(height)));
         self.do_event(event, ctx);
@@ -655,11 +1880,25 @@ impl<T: Data + 'static> WinHandler for DruidHandler<T> {
 
     fn mouse_move(&mut self, event: &MouseEvent, ctx: &mut dyn WinCtx) {
         let event = Event::MouseMoved(event.clone().into());
-        self.do_event(event, ctx);
+        if self.coalesce_input {
+            self.coalesce(event, ctx);
+        } else {
+            self.do_event(event, ctx);
+        }
     }
 
     fn key_down(&mut self, event: KeyEvent, ctx: &mut dyn WinCtx) -> bool {
-        self.do_event(Event::KeyDown(event), ctx)
+        // Global shortcuts with Ctrl, Alt or Command never type text, and go
+        // before the widgets, like menu shortcuts. The others only apply
+        // when no widget wants the key, so they can't interrupt typing.
+        let mods = event.mods;
+        if (mods.ctrl || mods.alt || mods.meta) && self.send_hotkey(&event, ctx) {
+            return true;
+        }
+        if self.do_event(Event::KeyDown(event), ctx) {
+            return true;
+        }
+        self.send_hotkey(&event, ctx)
     }
 
     fn key_up(&mut self, event: KeyEvent, ctx: &mut dyn WinCtx) {
@@ -667,8 +1906,19 @@ impl<T: Data + 'static> WinHandler for DruidHandler<T> {
     }
 
     fn wheel(&mut self, delta: Vec2, mods: KeyModifiers, ctx: &mut dyn WinCtx) {
+        // Shift turns the vertical wheel into a horizontal one. macOS
+        // already does this for us.
+        let delta = if cfg!(not(target_os = "macos")) && mods.shift && delta.x == 0.0 {
+            Vec2::new(delta.y, 0.0)
+        } else {
+            delta
+        };
         let event = Event::Wheel(WheelEvent { delta, mods });
-        self.do_event(event, ctx);
+        if self.coalesce_input {
+            self.coalesce(event, ctx);
+        } else {
+            self.do_event(event, ctx);
+        }
     }
 
     fn zoom(&mut self, delta: f64, ctx: &mut dyn WinCtx) {
@@ -680,6 +1930,29 @@ impl<T: Data + 'static> WinHandler for DruidHandler<T> {
         self.app_state
             .borrow_mut()
             .window_got_focus(self.window_id, ctx);
+        // Not every platform reports appearance changes.
+        self.appearance_changed(ctx);
+    }
+
+    fn appearance_changed(&mut self, ctx: &mut dyn WinCtx) {
+        let (high_contrast, scheme) = {
+            let mut app_state = self.app_state.borrow_mut();
+            let high_contrast = app_state.update_high_contrast();
+            let scheme = app_state.update_color_scheme();
+            // Widgets see the new theme in `update`, and are laid out again
+            // before the windows are painted.
+            if high_contrast.is_some() || scheme.is_some() {
+                app_state.update_windows(self.window_id, true, ctx);
+            }
+            (high_contrast, scheme)
+        };
+        if let Some(on) = high_contrast {
+            self.send_to_windows(LifeCycle::HighContrastChanged(on), ctx);
+        }
+        if let Some(scheme) = scheme {
+            self.send_to_windows(LifeCycle::ColorSchemeChanged(scheme), ctx);
+        }
+        self.process_commands(ctx);
     }
 
     fn timer(&mut self, token: TimerToken, ctx: &mut dyn WinCtx) {
@@ -690,10 +1963,15 @@ impl<T: Data + 'static> WinHandler for DruidHandler<T> {
         self
     }
 
+    fn will_close(&mut self, _ctx: &mut dyn WinCtx) {
+        self.app_state.borrow_mut().capture_position(self.window_id);
+    }
+
     fn destroy(&mut self, ctx: &mut dyn WinCtx) {
         self.app_state
             .borrow_mut()
             .remove_window(self.window_id, ctx);
+        self.process_commands(ctx);
     }
 }
 
@@ -702,6 +1980,30 @@ impl<T: Data> Default for Windows<T> {
         Windows {
             windows: HashMap::new(),
             state: HashMap::new(),
+            pending: HashMap::new(),
+            converted_envs: HashMap::new(),
         }
     }
 }
+
+/// The environment for a window painted in `color_space`, with the theme's
+/// colors converted to it.
+///
+/// Conversions are kept, and made again only when the environment changes.
+fn converted_env<'a>(
+    converted_envs: &'a mut HashMap<ColorSpace, (Env, Env)>,
+    env: &'a Env,
+    color_space: ColorSpace,
+) -> &'a Env {
+    if color_space == ColorSpace::Srgb {
+        return env;
+    }
+    let (source, converted) = converted_envs
+        .entry(color_space)
+        .or_insert_with(|| (env.clone(), env.to_color_space(color_space)));
+    if !source.same(env) {
+        *source = env.clone();
+        *converted = env.to_color_space(color_space);
+    }
+    converted
+}
@@ -15,13 +15,16 @@
 //! Management of multiple windows.
 
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
 use crate::kurbo::{Point, Rect, Size};
 
-use crate::shell::WindowHandle;
+use crate::core::FocusChange;
+use crate::mnemonic;
+use crate::shell::{ColorSpace, WindowHandle};
 use crate::{
-    BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LocalizedString, MenuDesc,
-    PaintCtx, UpdateCtx, Widget, WidgetPod,
+    AutomationNode, BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    LocalizedString, MenuDesc, PaintCtx, UpdateCtx, Widget, WidgetId, WidgetPod, WindowSizePolicy,
 };
 
 /// A unique identifier for a window.
@@ -30,16 +33,78 @@ pub struct WindowId(u32);
 
 static WINDOW_ID_COUNTER: AtomicU32 = AtomicU32::new(1);
 
+/// A function that builds the root widget of a window.
+pub(crate) type RootBuilderFn<T> = dyn Fn(&WindowHandle) -> Box<dyn Widget<T>>;
+
+/// A window whose platform window has been created, but has not yet
+/// connected; the root widget is built when it does.
+pub(crate) struct PendingWindow<T: Data> {
+    root_builder: Arc<RootBuilderFn<T>>,
+    title: LocalizedString<T>,
+    menu: Option<MenuDesc<T>>,
+    color_space: ColorSpace,
+    size_policy: WindowSizePolicy,
+    layer_builders: Vec<Arc<RootBuilderFn<T>>>,
+}
+
 /// Per-window state not owned by user code.
 pub struct Window<T: Data> {
+    /// The window's content.
     pub(crate) root: WidgetPod<T, Box<dyn Widget<T>>>,
+    /// Layers above the content, such as overlays and debugging displays,
+    /// from the bottom up.
+    layers: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
     pub(crate) title: LocalizedString<T>,
     size: Size,
     pub(crate) menu: Option<MenuDesc<T>>,
     pub(crate) context_menu: Option<MenuDesc<T>>,
+    /// The widget with keyboard focus.
+    focus: Option<WidgetId>,
+    /// Whether mnemonics are underlined, while alt is held.
+    show_mnemonics: bool,
+    /// The widgets with automation ids, as of the last paint.
+    pub(crate) automation: Vec<AutomationNode>,
+    /// The color space the window is painted in.
+    pub(crate) color_space: ColorSpace,
+    /// How the window's size is chosen when it opens.
+    pub(crate) size_policy: WindowSizePolicy,
     // delegate?
 }
 
+impl<T: Data> PendingWindow<T> {
+    pub(crate) fn new(
+        root_builder: Arc<RootBuilderFn<T>>,
+        title: LocalizedString<T>,
+        menu: Option<MenuDesc<T>>,
+        color_space: ColorSpace,
+        size_policy: WindowSizePolicy,
+        layer_builders: Vec<Arc<RootBuilderFn<T>>>,
+    ) -> PendingWindow<T> {
+        PendingWindow {
+            root_builder,
+            title,
+            menu,
+            color_space,
+            size_policy,
+            layer_builders,
+        }
+    }
+
+    /// Build the root widget and layers, now that the window has a handle.
+    pub(crate) fn into_window(self, handle: &WindowHandle) -> Window<T> {
+        let root = (self.root_builder)(handle);
+        let mut window = Window::new(root, self.title, self.menu);
+        window.color_space = self.color_space;
+        window.size_policy = self.size_policy;
+        window.layers = self
+            .layer_builders
+            .iter()
+            .map(|builder| WidgetPod::new(builder(handle)))
+            .collect();
+        window
+    }
+}
+
 impl<T: Data> Window<T> {
     pub fn new(
         root: impl Widget<T> + 'static,
@@ -48,10 +113,41 @@ impl<T: Data> Window<T> {
     ) -> Window<T> {
         Window {
             root: WidgetPod::new(Box::new(root)),
+            layers: Vec::new(),
             size: Size::ZERO,
             title,
             menu,
             context_menu: None,
+            focus: None,
+            show_mnemonics: false,
+            automation: Vec::new(),
+            color_space: ColorSpace::Srgb,
+            size_policy: WindowSizePolicy::Default,
+        }
+    }
+
+    /// The size of the window's content.
+    pub(crate) fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The content and the layers above it, from the bottom up.
+    fn pods_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut WidgetPod<T, Box<dyn Widget<T>>>> {
+        std::iter::once(&mut self.root).chain(self.layers.iter_mut())
+    }
+
+    /// Whether a widget in any layer is active.
+    pub(crate) fn has_active(&self) -> bool {
+        self.root.has_active() || self.layers.iter().any(|layer| layer.has_active())
+    }
+
+    /// Send an event to each layer, from the top down, as to the children of
+    /// a container; once a layer handles it, the layers below don't see it.
+    fn layers_event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        for pod in self.pods_mut().rev() {
+            pod.event(ctx, event, data, env);
         }
     }
 
@@ -59,28 +155,108 @@ impl<T: Data> Window<T> {
         if let Event::Size(size) = event {
             self.size = *size;
         }
-        self.root.event(ctx, event, data, env);
+        let key_event = match event {
+            Event::KeyDown(key) => Some((key, true)),
+            Event::KeyUp(key) => Some((key, false)),
+            _ => None,
+        };
+        let mut chose_mnemonic = false;
+        if let Some((key, is_down)) = key_event.filter(|_| mnemonic::ENABLED) {
+            let show = if mnemonic::is_alt(key) {
+                is_down
+            } else {
+                key.mods.alt
+            };
+            self.show_mnemonics(ctx, show, data, env);
+            // Mnemonics take precedence over the focused widget's keys.
+            if let Some(c) = mnemonic::key_mnemonic(key).filter(|_| is_down) {
+                self.layers_event(ctx, &Event::Mnemonic(c), data, env);
+                chose_mnemonic = ctx.is_handled;
+            }
+        }
+        if !chose_mnemonic {
+            self.layers_event(ctx, event, data, env);
+        }
 
         if let Some(cursor) = ctx.cursor {
             ctx.win_ctx.set_cursor(&cursor);
         }
     }
 
+    /// Tell the widgets whether to underline mnemonics, if it has changed.
+    fn show_mnemonics(&mut self, ctx: &mut EventCtx, show: bool, data: &mut T, env: &Env) {
+        if show != self.show_mnemonics {
+            self.show_mnemonics = show;
+            let event = Event::LifeCycle(LifeCycle::ShowMnemonics(show));
+            self.layers_event(ctx, &event, data, env);
+            // The key itself is still delivered.
+            ctx.is_handled = false;
+        }
+    }
+
+    /// The widget with keyboard focus, if any.
+    pub(crate) fn focus(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    /// Apply a change of focus requested by a widget, routing it to the
+    /// widgets involved.
+    ///
+    /// Returns `true` if the focused widget changed.
+    pub(crate) fn change_focus(
+        &mut self,
+        ctx: &mut EventCtx,
+        change: FocusChange,
+        data: &mut T,
+        env: &Env,
+    ) -> bool {
+        // Focus moves through the content, then the layers above it.
+        let chain: Vec<WidgetId> = std::iter::once(&self.root)
+            .chain(&self.layers)
+            .flat_map(|pod| pod.focus_chain().iter().cloned())
+            .collect();
+        let new = focus_target(&chain, self.focus, change);
+        if new == self.focus {
+            return false;
+        }
+        let event = Event::LifeCycle(LifeCycle::RouteFocusChanged {
+            old: self.focus,
+            new,
+        });
+        self.focus = new;
+        self.layers_event(ctx, &event, data, env);
+        true
+    }
+
     pub fn update(&mut self, update_ctx: &mut UpdateCtx, data: &T, env: &Env) {
         self.update_title(&update_ctx.window, data, env);
-        self.root.update(update_ctx, data, env);
+        for pod in self.pods_mut() {
+            pod.update(update_ctx, data, env);
+        }
     }
 
     pub fn layout(&mut self, layout_ctx: &mut LayoutCtx, data: &T, env: &Env) {
+        // Every layer covers the whole window.
         let bc = BoxConstraints::tight(self.size);
-        let size = self.root.layout(layout_ctx, &bc, data, env);
-        self.root
-            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        for pod in self.pods_mut() {
+            let size = pod.layout(layout_ctx, &bc, data, env);
+            pod.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        }
+    }
+
+    /// The size the content asks for, given unbounded constraints.
+    pub(crate) fn content_size(&mut self, layout_ctx: &mut LayoutCtx, data: &T, env: &Env) -> Size {
+        let unbounded = Size::new(std::f64::INFINITY, std::f64::INFINITY);
+        let bc = BoxConstraints::new(Size::ZERO, unbounded);
+        self.root.layout(layout_ctx, &bc, data, env)
     }
 
     pub fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         let visible = Rect::from_origin_size(Point::ZERO, self.size);
-        paint_ctx.with_child_ctx(visible, |ctx| self.root.paint(ctx, data, env));
+        for pod in self.pods_mut() {
+            paint_ctx.with_child_ctx(visible, |ctx| pod.paint(ctx, data, env));
+        }
+        paint_ctx.paint_overlays(visible);
     }
 
     pub(crate) fn update_title(&mut self, win_handle: &WindowHandle, data: &T, env: &Env) {
@@ -89,6 +265,16 @@ impl<T: Data> Window<T> {
         }
     }
 
+    /// Rebuild the window menu if the data changed its items' titles or
+    /// state.
+    pub(crate) fn update_menu(&mut self, win_handle: &WindowHandle, data: &T, env: &Env) {
+        if let Some(menu) = self.menu.as_mut() {
+            if menu.update(data, env) {
+                win_handle.set_menu(menu.build_window_menu(data, env));
+            }
+        }
+    }
+
     pub(crate) fn get_menu_cmd(&self, cmd_id: u32) -> Option<Command> {
         self.context_menu
             .as_ref()
@@ -97,6 +283,28 @@ impl<T: Data> Window<T> {
     }
 }
 
+/// The widget to focus after a change, given the focus chain and the
+/// currently focused widget.
+///
+/// Focus only goes to widgets in the chain, as focus is routed along it.
+fn focus_target(
+    chain: &[WidgetId],
+    focus: Option<WidgetId>,
+    change: FocusChange,
+) -> Option<WidgetId> {
+    let len = chain.len();
+    let current = focus.and_then(|focus| chain.iter().position(|id| *id == focus));
+    match change {
+        FocusChange::Focus(id) if chain.contains(&id) => Some(id),
+        FocusChange::Focus(_) => focus,
+        _ if len == 0 => focus,
+        FocusChange::Next => Some(chain[current.map_or(0, |index| (index + 1) % len)]),
+        FocusChange::Previous => {
+            Some(chain[current.map_or(len - 1, |index| (index + len - 1) % len)])
+        }
+    }
+}
+
 impl WindowId {
     /// Allocate a new, unique window id.
     ///
@@ -106,3 +314,35 @@ impl WindowId {
         WindowId(id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_wraps_around_chain() {
+        let chain = [WidgetId::next(), WidgetId::next(), WidgetId::next()];
+        let next = |focus| focus_target(&chain, focus, FocusChange::Next);
+        let previous = |focus| focus_target(&chain, focus, FocusChange::Previous);
+        assert_eq!(next(None), Some(chain[0]));
+        assert_eq!(next(Some(chain[0])), Some(chain[1]));
+        assert_eq!(next(Some(chain[2])), Some(chain[0]));
+        assert_eq!(previous(None), Some(chain[2]));
+        assert_eq!(previous(Some(chain[0])), Some(chain[2]));
+        // A widget outside the chain moves to its start.
+        let outside = WidgetId::next();
+        assert_eq!(next(Some(outside)), Some(chain[0]));
+        assert_eq!(
+            focus_target(&[], Some(outside), FocusChange::Next),
+            Some(outside)
+        );
+        assert_eq!(
+            focus_target(&chain, Some(chain[1]), FocusChange::Focus(outside)),
+            Some(chain[1])
+        );
+        assert_eq!(
+            focus_target(&chain, None, FocusChange::Focus(chain[2])),
+            Some(chain[2])
+        );
+    }
+}